@@ -1,48 +1,245 @@
 // IMPORTS
 
+#[cfg(feature = "archive")]
 use flate2::read::GzDecoder;
+#[cfg(feature = "archive")]
 use log::debug;
+#[cfg(all(unix, feature = "archive"))]
+use std::os::unix::fs::PermissionsExt;
 use std::{
-    fs::File,
-    io::{self, BufReader, Error, ErrorKind},
+    fs,
+    io::{self, Error, ErrorKind, Read, Write},
     path::Path,
 };
+#[cfg(feature = "archive")]
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Component, PathBuf},
+};
 #[cfg(test)]
 use stub_trait::stub;
+#[cfg(feature = "archive")]
 use tar::Archive;
+#[cfg(feature = "archive")]
 use zip::ZipArchive;
 
+// CONSTS
+
+const GZIP_MAGIC_BYTES: &[u8] = &[0x1f, 0x8b];
+const XZ_MAGIC_BYTES: &[u8] = &[0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00];
+const ZIP_MAGIC_BYTES: &[u8] = &[0x50, 0x4b, 0x03, 0x04];
+const ZSTD_MAGIC_BYTES: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
 // TYPES
 
 pub type Result = io::Result<()>;
 
+// ENUMS
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    TarGz,
+    TarXz,
+    TarZst,
+    Zip,
+}
+
+impl Format {
+    pub fn from_extension(filename: &str) -> Option<Self> {
+        if filename.ends_with(".tar.gz") || filename.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if filename.ends_with(".tar.xz") || filename.ends_with(".txz") {
+            Some(Self::TarXz)
+        } else if filename.ends_with(".tar.zst") || filename.ends_with(".tzst") {
+            Some(Self::TarZst)
+        } else if filename.ends_with(".zip") {
+            Some(Self::Zip)
+        } else {
+            None
+        }
+    }
+
+    pub fn from_magic_bytes(header: &[u8]) -> Option<Self> {
+        if header.starts_with(GZIP_MAGIC_BYTES) {
+            Some(Self::TarGz)
+        } else if header.starts_with(XZ_MAGIC_BYTES) {
+            Some(Self::TarXz)
+        } else if header.starts_with(ZSTD_MAGIC_BYTES) {
+            Some(Self::TarZst)
+        } else if header.starts_with(ZIP_MAGIC_BYTES) {
+            Some(Self::Zip)
+        } else {
+            None
+        }
+    }
+}
+
 // TRAITS
 
 #[cfg_attr(test, stub)]
-pub trait Unarchiver {
-    fn untar(&self, archive_filepath: &Path, dest: &Path) -> Result;
+pub trait Unarchiver: Send + Sync {
+    fn extract_dir_from_tar(&self, archive_filepath: &Path, dirpath: &Path, dest: &Path) -> Result;
+
+    fn extract_from_tar(
+        &self,
+        archive_filepath: &Path,
+        entry: &Path,
+        out: &mut dyn Write,
+    ) -> Result;
+
+    fn unarchive(&self, archive_filepath: &Path, dest: &Path, strip_components: usize) -> Result;
 
-    fn unzip(&self, archive_filepath: &Path, dest: &Path) -> Result;
+    fn untar(&self, archive_filepath: &Path, dest: &Path, strip_components: usize) -> Result;
+
+    fn untar_xz(&self, archive_filepath: &Path, dest: &Path, strip_components: usize) -> Result;
+
+    fn untar_zst(&self, archive_filepath: &Path, dest: &Path, strip_components: usize) -> Result;
+
+    fn unzip(&self, archive_filepath: &Path, dest: &Path, strip_components: usize) -> Result;
 }
 
 // STRUCTS
 
 pub struct DefaultUnarchiver;
 
+#[cfg(feature = "archive")]
 impl Unarchiver for DefaultUnarchiver {
-    fn untar(&self, archive_filepath: &Path, dest: &Path) -> Result {
+    fn extract_dir_from_tar(&self, archive_filepath: &Path, dirpath: &Path, dest: &Path) -> Result {
         debug!(
-            "Extracting {} into {}",
+            "Extracting {} from {} into {}",
+            dirpath.display(),
             archive_filepath.display(),
             dest.display(),
         );
         let tar_file = File::open(archive_filepath)?;
         let decoder = GzDecoder::new(BufReader::new(tar_file));
         let mut tar = Archive::new(decoder);
-        tar.unpack(dest)
+        fs::create_dir_all(dest)?;
+        let mut found = false;
+        for archived_file in tar.entries()? {
+            let mut archived_file = archived_file?;
+            let path = archived_file.path()?.into_owned();
+            if path.strip_prefix(".").unwrap_or(&path).starts_with(dirpath) {
+                archived_file.unpack_in(dest)?;
+                found = true;
+            }
+        }
+        if found {
+            Ok(())
+        } else {
+            Err(Error::new(
+                ErrorKind::NotFound,
+                format!(
+                    "{}: {} not found in archive",
+                    archive_filepath.display(),
+                    dirpath.display()
+                ),
+            ))
+        }
+    }
+
+    fn extract_from_tar(
+        &self,
+        archive_filepath: &Path,
+        entry: &Path,
+        out: &mut dyn Write,
+    ) -> Result {
+        debug!(
+            "Extracting {} from {}",
+            entry.display(),
+            archive_filepath.display(),
+        );
+        let tar_file = File::open(archive_filepath)?;
+        let decoder = GzDecoder::new(BufReader::new(tar_file));
+        let mut tar = Archive::new(decoder);
+        for archived_file in tar.entries()? {
+            let mut archived_file = archived_file?;
+            let path = archived_file.path()?.into_owned();
+            if path.strip_prefix(".").unwrap_or(&path) == entry {
+                io::copy(&mut archived_file, out)?;
+                return Ok(());
+            }
+        }
+        Err(Error::new(
+            ErrorKind::NotFound,
+            format!(
+                "{}: {} not found in archive",
+                archive_filepath.display(),
+                entry.display()
+            ),
+        ))
     }
 
-    fn unzip(&self, archive_filepath: &Path, dest: &Path) -> Result {
+    fn unarchive(&self, archive_filepath: &Path, dest: &Path, strip_components: usize) -> Result {
+        let format = detect_format(archive_filepath)?;
+        debug!("{} detected as {:?}", archive_filepath.display(), format);
+        match format {
+            Format::TarGz => self.untar(archive_filepath, dest, strip_components),
+            Format::TarXz => self.untar_xz(archive_filepath, dest, strip_components),
+            Format::TarZst => self.untar_zst(archive_filepath, dest, strip_components),
+            Format::Zip => self.unzip(archive_filepath, dest, strip_components),
+        }
+    }
+
+    fn untar(&self, archive_filepath: &Path, dest: &Path, strip_components: usize) -> Result {
+        debug!(
+            "Extracting {} into {}",
+            archive_filepath.display(),
+            dest.display(),
+        );
+        let tar_file = File::open(archive_filepath)?;
+        let decoder = GzDecoder::new(BufReader::new(tar_file));
+        unpack_tar(Archive::new(decoder), dest, strip_components)
+    }
+
+    #[cfg(feature = "xz")]
+    fn untar_xz(&self, archive_filepath: &Path, dest: &Path, strip_components: usize) -> Result {
+        debug!(
+            "Extracting {} into {}",
+            archive_filepath.display(),
+            dest.display(),
+        );
+        let tar_file = File::open(archive_filepath)?;
+        let decoder = liblzma::read::XzDecoder::new(BufReader::new(tar_file));
+        unpack_tar(Archive::new(decoder), dest, strip_components)
+    }
+
+    #[cfg(not(feature = "xz"))]
+    fn untar_xz(&self, archive_filepath: &Path, _dest: &Path, _strip_components: usize) -> Result {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            format!(
+                "{}: xz archives are not supported (denv must be built with the xz feature)",
+                archive_filepath.display()
+            ),
+        ))
+    }
+
+    #[cfg(feature = "zstd")]
+    fn untar_zst(&self, archive_filepath: &Path, dest: &Path, strip_components: usize) -> Result {
+        debug!(
+            "Extracting {} into {}",
+            archive_filepath.display(),
+            dest.display(),
+        );
+        let tar_file = File::open(archive_filepath)?;
+        let decoder = zstd::Decoder::new(tar_file)?;
+        unpack_tar(Archive::new(decoder), dest, strip_components)
+    }
+
+    #[cfg(not(feature = "zstd"))]
+    fn untar_zst(&self, archive_filepath: &Path, _dest: &Path, _strip_components: usize) -> Result {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            format!(
+                "{}: zstd archives are not supported (denv must be built with the zstd feature)",
+                archive_filepath.display()
+            ),
+        ))
+    }
+
+    fn unzip(&self, archive_filepath: &Path, dest: &Path, strip_components: usize) -> Result {
         debug!(
             "Extracting {} into {}",
             archive_filepath.display(),
@@ -52,12 +249,246 @@ impl Unarchiver for DefaultUnarchiver {
         let zip_file_buf = BufReader::new(zip_file);
         let mut zip = ZipArchive::new(zip_file_buf)
             .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
-        zip.extract(dest)
-            .map_err(|err| Error::new(ErrorKind::Other, err))
+        if strip_components == 0 {
+            return zip.extract(dest).map_err(Error::other);
+        }
+        for idx in 0..zip.len() {
+            let mut archived_file = zip.by_index(idx).map_err(Error::other)?;
+            let path = archived_file
+                .enclosed_name()
+                .and_then(|path| strip_path(path, strip_components));
+            let path = match path {
+                Some(path) => dest.join(path),
+                None => continue,
+            };
+            if archived_file.is_dir() {
+                fs::create_dir_all(&path)?;
+                continue;
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            io::copy(&mut archived_file, &mut File::create(&path)?)?;
+            #[cfg(unix)]
+            if let Some(mode) = archived_file.unix_mode() {
+                fs::set_permissions(&path, fs::Permissions::from_mode(mode))?;
+            }
+        }
+        Ok(())
     }
 }
 
+// Formats are still detected, so the error tells which archive could not be extracted.
+#[cfg(not(feature = "archive"))]
+impl Unarchiver for DefaultUnarchiver {
+    fn extract_dir_from_tar(
+        &self,
+        archive_filepath: &Path,
+        _dirpath: &Path,
+        _dest: &Path,
+    ) -> Result {
+        Err(unsupported(archive_filepath))
+    }
+
+    fn extract_from_tar(
+        &self,
+        archive_filepath: &Path,
+        _entry: &Path,
+        _out: &mut dyn Write,
+    ) -> Result {
+        Err(unsupported(archive_filepath))
+    }
+
+    fn unarchive(&self, archive_filepath: &Path, dest: &Path, strip_components: usize) -> Result {
+        match detect_format(archive_filepath)? {
+            Format::TarGz => self.untar(archive_filepath, dest, strip_components),
+            Format::TarXz => self.untar_xz(archive_filepath, dest, strip_components),
+            Format::TarZst => self.untar_zst(archive_filepath, dest, strip_components),
+            Format::Zip => self.unzip(archive_filepath, dest, strip_components),
+        }
+    }
+
+    fn untar(&self, archive_filepath: &Path, _dest: &Path, _strip_components: usize) -> Result {
+        Err(unsupported(archive_filepath))
+    }
+
+    fn untar_xz(&self, archive_filepath: &Path, _dest: &Path, _strip_components: usize) -> Result {
+        Err(unsupported(archive_filepath))
+    }
+
+    fn untar_zst(&self, archive_filepath: &Path, _dest: &Path, _strip_components: usize) -> Result {
+        Err(unsupported(archive_filepath))
+    }
+
+    fn unzip(&self, archive_filepath: &Path, _dest: &Path, _strip_components: usize) -> Result {
+        Err(unsupported(archive_filepath))
+    }
+}
+
+// FUNCTIONS
+
+#[inline]
+fn detect_format(archive_filepath: &Path) -> io::Result<Format> {
+    // xz magic bytes are the longest ones.
+    let mut header = vec![];
+    fs::File::open(archive_filepath)?
+        .take(XZ_MAGIC_BYTES.len() as u64)
+        .read_to_end(&mut header)?;
+    Format::from_magic_bytes(&header)
+        .or_else(|| Format::from_extension(&archive_filepath.to_string_lossy()))
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("{}: Unknown archive format", archive_filepath.display()),
+            )
+        })
+}
+
+// Entries which do not have more components than the stripped ones (or which would escape the
+// destination directory) are skipped, as GNU tar does.
+#[cfg(feature = "archive")]
+#[inline]
+fn strip_path(path: &Path, strip_components: usize) -> Option<PathBuf> {
+    let mut components = path
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .skip(strip_components)
+        .peekable();
+    components.peek()?;
+    let mut stripped = PathBuf::new();
+    for component in components {
+        match component {
+            Component::Normal(name) => stripped.push(name),
+            _ => return None,
+        }
+    }
+    Some(stripped)
+}
+
+#[cfg(not(feature = "archive"))]
+#[inline]
+fn unsupported(archive_filepath: &Path) -> Error {
+    Error::new(
+        ErrorKind::Unsupported,
+        format!(
+            "{}: archives are not supported (denv must be built with the archive feature)",
+            archive_filepath.display()
+        ),
+    )
+}
+
+#[cfg(feature = "archive")]
+#[inline]
+fn unpack_tar<R: Read>(mut tar: Archive<R>, dest: &Path, strip_components: usize) -> Result {
+    if strip_components == 0 {
+        return tar.unpack(dest);
+    }
+    fs::create_dir_all(dest)?;
+    for archived_file in tar.entries()? {
+        let mut archived_file = archived_file?;
+        let path = archived_file.path()?.into_owned();
+        if let Some(path) = strip_path(&path, strip_components) {
+            let path = dest.join(path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            archived_file.unpack(path)?;
+        }
+    }
+    Ok(())
+}
+
+// TESTS
+
 #[cfg(test)]
+mod format_test {
+    use super::*;
+
+    mod from_extension {
+        use super::*;
+
+        #[test]
+        fn should_return_none() {
+            assert_eq!(Format::from_extension("archive.tar.bz2"), None);
+        }
+
+        #[test]
+        fn should_return_targz() {
+            assert_eq!(
+                Format::from_extension("archive.tar.gz"),
+                Some(Format::TarGz)
+            );
+            assert_eq!(Format::from_extension("archive.tgz"), Some(Format::TarGz));
+        }
+
+        #[test]
+        fn should_return_tarxz() {
+            assert_eq!(
+                Format::from_extension("archive.tar.xz"),
+                Some(Format::TarXz)
+            );
+            assert_eq!(Format::from_extension("archive.txz"), Some(Format::TarXz));
+        }
+
+        #[test]
+        fn should_return_tarzst() {
+            assert_eq!(
+                Format::from_extension("archive.tar.zst"),
+                Some(Format::TarZst)
+            );
+            assert_eq!(Format::from_extension("archive.tzst"), Some(Format::TarZst));
+        }
+
+        #[test]
+        fn should_return_zip() {
+            assert_eq!(Format::from_extension("archive.zip"), Some(Format::Zip));
+        }
+    }
+
+    mod from_magic_bytes {
+        use super::*;
+
+        #[test]
+        fn should_return_none() {
+            assert_eq!(Format::from_magic_bytes(b"ustar"), None);
+            assert_eq!(Format::from_magic_bytes(&[]), None);
+        }
+
+        #[test]
+        fn should_return_targz() {
+            assert_eq!(
+                Format::from_magic_bytes(&[0x1f, 0x8b, 0x08, 0x00]),
+                Some(Format::TarGz)
+            );
+        }
+
+        #[test]
+        fn should_return_tarxz() {
+            assert_eq!(
+                Format::from_magic_bytes(&[0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00]),
+                Some(Format::TarXz)
+            );
+        }
+
+        #[test]
+        fn should_return_tarzst() {
+            assert_eq!(
+                Format::from_magic_bytes(&[0x28, 0xb5, 0x2f, 0xfd]),
+                Some(Format::TarZst)
+            );
+        }
+
+        #[test]
+        fn should_return_zip() {
+            assert_eq!(
+                Format::from_magic_bytes(&[0x50, 0x4b, 0x03, 0x04]),
+                Some(Format::Zip)
+            );
+        }
+    }
+}
+
+#[cfg(all(test, feature = "archive"))]
 mod default_unarchiver_test {
     use super::*;
     use flate2::{write::GzEncoder, Compression};
@@ -65,6 +496,167 @@ mod default_unarchiver_test {
     use tar::Builder;
     use zip::{write::FileOptions, ZipWriter};
 
+    mod extract_dir_from_tar {
+        use super::*;
+
+        #[test]
+        fn should_return_not_found_err_if_dir_does_not_exist() {
+            let dirpath = tempfile::tempdir().unwrap().into_path();
+            let archive_filepath = dirpath.join("archive.tar.gz");
+            extract_from_tar::create_tgz(&archive_filepath);
+            let err = DefaultUnarchiver
+                .extract_dir_from_tar(&archive_filepath, Path::new("other"), &dirpath)
+                .unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::NotFound);
+        }
+
+        #[test]
+        fn should_extract_dir_only() {
+            let dirpath = tempfile::tempdir().unwrap().into_path();
+            let archive_filepath = dirpath.join("archive.tar.gz");
+            let dest = dirpath.join("dest");
+            extract_from_tar::create_tgz(&archive_filepath);
+            DefaultUnarchiver
+                .extract_dir_from_tar(&archive_filepath, Path::new("dir"), &dest)
+                .unwrap();
+            assert_eq!(
+                fs::read_to_string(dest.join("dir/file")).unwrap(),
+                "Hello world!"
+            );
+            assert!(!dest.join("LICENSE").exists());
+        }
+    }
+
+    mod extract_from_tar {
+        use super::*;
+
+        struct Data {
+            archive_filepath: PathBuf,
+            entry: &'static Path,
+        }
+
+        impl Default for Data {
+            fn default() -> Self {
+                Self {
+                    archive_filepath: tempfile::tempdir()
+                        .unwrap()
+                        .into_path()
+                        .join("archive.tar.gz"),
+                    entry: Path::new("dir/file"),
+                }
+            }
+        }
+
+        #[test]
+        fn should_return_err_if_file_does_not_exist() {
+            let data = Data::default();
+            test(&data, |res, _| {
+                res.unwrap_err();
+            })
+        }
+
+        #[test]
+        fn should_return_not_found_err_if_entry_does_not_exist() {
+            let data = Data {
+                entry: Path::new("dir/other"),
+                ..Data::default()
+            };
+            create_tgz(&data.archive_filepath);
+            test(&data, |res, out| {
+                let err = res.unwrap_err();
+                assert_eq!(err.kind(), ErrorKind::NotFound);
+                assert!(out.is_empty());
+            })
+        }
+
+        #[test]
+        fn should_write_entry_only() {
+            let data = Data::default();
+            create_tgz(&data.archive_filepath);
+            test(&data, |res, out| {
+                res.unwrap();
+                assert_eq!(String::from_utf8(out).unwrap(), "Hello world!");
+            })
+        }
+
+        #[inline]
+        pub fn create_tgz(archive_filepath: &Path) {
+            let tar_file = File::create(archive_filepath).unwrap();
+            let encoder = GzEncoder::new(tar_file, Compression::default());
+            let mut tar = Builder::new(encoder);
+            for (path, content) in [("LICENSE", "MIT"), ("./dir/file", "Hello world!")] {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(content.len() as u64);
+                header.set_mode(0o644);
+                header.set_cksum();
+                tar.append_data(&mut header, path, content.as_bytes())
+                    .unwrap();
+            }
+            tar.into_inner().unwrap().finish().unwrap();
+        }
+
+        #[inline]
+        fn test<F: Fn(Result, Vec<u8>)>(data: &Data, assert_fn: F) {
+            let unarchiver = DefaultUnarchiver;
+            let mut out = vec![];
+            let res = unarchiver.extract_from_tar(&data.archive_filepath, data.entry, &mut out);
+            assert_fn(res, out);
+        }
+    }
+
+    mod unarchive {
+        use super::*;
+
+        #[test]
+        fn should_return_err_if_file_does_not_exist() {
+            let dest = tempfile::tempdir().unwrap().into_path();
+            let res = DefaultUnarchiver.unarchive(&dest.join("archive"), &dest, 0);
+            res.unwrap_err();
+        }
+
+        #[test]
+        fn should_return_err_if_format_is_unknown() {
+            let archive_filepath = tempfile::tempdir().unwrap().into_path().join("archive");
+            fs::write(&archive_filepath, "Hello world!").unwrap();
+            let dest = tempfile::tempdir().unwrap().into_path();
+            let res = DefaultUnarchiver.unarchive(&archive_filepath, &dest, 0);
+            let err = res.unwrap_err();
+            match err.kind() {
+                ErrorKind::InvalidInput => {}
+                kind => panic!("{}", kind),
+            }
+        }
+
+        #[test]
+        fn should_detect_format_from_extension() {
+            let archive_filepath = tempfile::tempdir().unwrap().into_path().join("archive.zip");
+            File::create(&archive_filepath).unwrap();
+            let dest = tempfile::tempdir().unwrap().into_path();
+            let res = DefaultUnarchiver.unarchive(&archive_filepath, &dest, 0);
+            let err = res.unwrap_err();
+            match err.kind() {
+                ErrorKind::InvalidInput => {}
+                kind => panic!("{}", kind),
+            }
+        }
+
+        #[test]
+        fn should_detect_format_from_magic_bytes() {
+            let archive_filepath = tempfile::tempdir().unwrap().into_path().join("archive");
+            let zip_file = File::create(&archive_filepath).unwrap();
+            let mut zip = ZipWriter::new(zip_file);
+            zip.start_file("file", FileOptions::default()).unwrap();
+            zip.write_all(b"Hello world!").unwrap();
+            zip.finish().unwrap();
+            let dest = tempfile::tempdir().unwrap().into_path();
+            DefaultUnarchiver
+                .unarchive(&archive_filepath, &dest, 0)
+                .unwrap();
+            let content = fs::read_to_string(dest.join("file")).unwrap();
+            assert_eq!(content, "Hello world!");
+        }
+    }
+
     mod untar {
         use super::*;
 
@@ -73,6 +665,7 @@ mod default_unarchiver_test {
             archived_file_content: &'static str,
             archived_filepath: &'static Path,
             dest: PathBuf,
+            strip_components: usize,
         }
 
         impl Default for Data {
@@ -85,6 +678,7 @@ mod default_unarchiver_test {
                     archived_file_content: "Hello world!",
                     archived_filepath: Path::new("dir/file"),
                     dest: tempfile::tempdir().unwrap().into_path(),
+                    strip_components: 0,
                 }
             }
         }
@@ -129,6 +723,21 @@ mod default_unarchiver_test {
             })
         }
 
+        #[test]
+        fn should_strip_components() {
+            let data = Data {
+                strip_components: 1,
+                ..Data::default()
+            };
+            create_tgz(&data);
+            test(&data, |res| {
+                res.unwrap();
+                let content = fs::read_to_string(data.dest.join("file")).unwrap();
+                assert_eq!(content, data.archived_file_content);
+                assert!(!data.dest.join("dir").exists());
+            })
+        }
+
         #[inline]
         fn create_tgz(data: &Data) {
             let temp_dirpath = tempfile::tempdir().unwrap().into_path();
@@ -148,11 +757,85 @@ mod default_unarchiver_test {
         #[inline]
         fn test<F: Fn(Result)>(data: &Data, assert_fn: F) {
             let unarchiver = DefaultUnarchiver;
-            let res = unarchiver.untar(&data.archive_filepath, &data.dest);
+            let res = unarchiver.untar(&data.archive_filepath, &data.dest, data.strip_components);
             assert_fn(res);
         }
     }
 
+    mod untar_xz {
+        use super::*;
+
+        #[cfg(feature = "xz")]
+        #[test]
+        fn should_return_ok() {
+            let temp_dirpath = tempfile::tempdir().unwrap().into_path();
+            let archived_filepath = temp_dirpath.join("file");
+            fs::write(&archived_filepath, "Hello world!").unwrap();
+            let archive_filepath = temp_dirpath.join("archive.tar.xz");
+            let tar_file = File::create(&archive_filepath).unwrap();
+            let encoder = liblzma::write::XzEncoder::new(tar_file, 6);
+            let mut tar = Builder::new(encoder);
+            tar.append_path_with_name(&archived_filepath, "zig/lib/file")
+                .unwrap();
+            tar.into_inner().unwrap().finish().unwrap();
+            let dest = tempfile::tempdir().unwrap().into_path();
+            DefaultUnarchiver
+                .untar_xz(&archive_filepath, &dest, 1)
+                .unwrap();
+            let content = fs::read_to_string(dest.join("lib/file")).unwrap();
+            assert_eq!(content, "Hello world!");
+        }
+
+        #[cfg(not(feature = "xz"))]
+        #[test]
+        fn should_return_unsupported_err() {
+            let dest = tempfile::tempdir().unwrap().into_path();
+            let res = DefaultUnarchiver.untar_xz(&dest.join("archive.tar.xz"), &dest, 0);
+            let err = res.unwrap_err();
+            match err.kind() {
+                ErrorKind::Unsupported => {}
+                kind => panic!("{}", kind),
+            }
+        }
+    }
+
+    mod untar_zst {
+        use super::*;
+
+        #[cfg(feature = "zstd")]
+        #[test]
+        fn should_return_ok() {
+            let temp_dirpath = tempfile::tempdir().unwrap().into_path();
+            let archived_filepath = temp_dirpath.join("file");
+            fs::write(&archived_filepath, "Hello world!").unwrap();
+            let archive_filepath = temp_dirpath.join("archive.tar.zst");
+            let tar_file = File::create(&archive_filepath).unwrap();
+            let encoder = zstd::Encoder::new(tar_file, 0).unwrap().auto_finish();
+            let mut tar = Builder::new(encoder);
+            tar.append_path_with_name(&archived_filepath, "dir/file")
+                .unwrap();
+            tar.into_inner().unwrap();
+            let dest = tempfile::tempdir().unwrap().into_path();
+            DefaultUnarchiver
+                .untar_zst(&archive_filepath, &dest, 0)
+                .unwrap();
+            let content = fs::read_to_string(dest.join("dir/file")).unwrap();
+            assert_eq!(content, "Hello world!");
+        }
+
+        #[cfg(not(feature = "zstd"))]
+        #[test]
+        fn should_return_unsupported_err() {
+            let dest = tempfile::tempdir().unwrap().into_path();
+            let res = DefaultUnarchiver.untar_zst(&dest.join("archive.tar.zst"), &dest, 0);
+            let err = res.unwrap_err();
+            match err.kind() {
+                ErrorKind::Unsupported => {}
+                kind => panic!("{}", kind),
+            }
+        }
+    }
+
     mod unzip {
         use super::*;
 
@@ -161,6 +844,7 @@ mod default_unarchiver_test {
             archived_file_content: &'static str,
             archived_filepath: &'static str,
             dest: PathBuf,
+            strip_components: usize,
         }
 
         impl Default for Data {
@@ -170,6 +854,7 @@ mod default_unarchiver_test {
                     archived_file_content: "Hello world!",
                     archived_filepath: "dir/file",
                     dest: tempfile::tempdir().unwrap().into_path(),
+                    strip_components: 0,
                 }
             }
         }
@@ -218,6 +903,21 @@ mod default_unarchiver_test {
             })
         }
 
+        #[test]
+        fn should_strip_components() {
+            let data = Data {
+                strip_components: 1,
+                ..Data::default()
+            };
+            create_zip(&data);
+            test(&data, |res| {
+                res.unwrap();
+                let content = fs::read_to_string(data.dest.join("file")).unwrap();
+                assert_eq!(content, data.archived_file_content);
+                assert!(!data.dest.join("dir").exists());
+            })
+        }
+
         #[inline]
         fn create_zip(data: &Data) {
             let zip_file = File::create(&data.archive_filepath).unwrap();
@@ -232,8 +932,31 @@ mod default_unarchiver_test {
         #[inline]
         fn test<F: Fn(Result)>(data: &Data, assert_fn: F) {
             let unarchiver = DefaultUnarchiver;
-            let res = unarchiver.unzip(&data.archive_filepath, &data.dest);
+            let res = unarchiver.unzip(&data.archive_filepath, &data.dest, data.strip_components);
             assert_fn(res);
         }
     }
 }
+
+#[cfg(all(test, feature = "archive"))]
+mod strip_path_test {
+    use super::*;
+
+    #[test]
+    fn should_return_none_if_path_is_stripped_root() {
+        assert_eq!(strip_path(Path::new("./jdk-17/"), 1), None);
+    }
+
+    #[test]
+    fn should_return_none_if_path_escapes_dest() {
+        assert_eq!(strip_path(Path::new("jdk-17/../../etc/passwd"), 1), None);
+    }
+
+    #[test]
+    fn should_return_stripped_path() {
+        assert_eq!(
+            strip_path(Path::new("./jdk-17/bin/java"), 1),
+            Some(PathBuf::from("bin/java"))
+        );
+    }
+}
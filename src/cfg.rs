@@ -1,32 +1,113 @@
 // IMPORTS
 
 use crate::{
-    soft::{k8s::ChartTesting, tf::Terraform, Software},
-    var::{Literal, Var},
+    net::{DownloadConfig, HeaderDefinition, Mirror},
+    soft::{
+        docker::{
+            Dive, Lazydocker, DIVE_BIN_NAME, DIVE_SOFT_NAME, DIVE_VERSION_ARGS,
+            LAZYDOCKER_BIN_NAME, LAZYDOCKER_SOFT_NAME, LAZYDOCKER_VERSION_ARGS,
+        },
+        github::{GithubRelease, GithubReleaseConfig, GITHUB_RELEASE_VERSION_ARGS},
+        java::{Java, JAVA_BIN_NAME, JAVA_SOFT_NAME, JAVA_VERSION_ARGS},
+        k8s::{
+            ChartTesting, Helm, Kubectl, Stern, CT_BIN_NAME, CT_SOFT_NAME, CT_VERSION_ARGS,
+            HELM_BIN_NAME, HELM_SOFT_NAME, HELM_VERSION_ARGS, KUBECTL_BIN_NAME, KUBECTL_SOFT_NAME,
+            KUBECTL_VERSION_ARGS, STERN_BIN_NAME, STERN_SOFT_NAME, STERN_VERSION_ARGS,
+        },
+        policy::{
+            Conftest, Opa, CONFTEST_BIN_NAME, CONFTEST_SOFT_NAME, CONFTEST_VERSION_ARGS,
+            OPA_BIN_NAME, OPA_SOFT_NAME, OPA_VERSION_ARGS,
+        },
+        proto::{
+            Buf, Grpcurl, BUF_BIN_NAME, BUF_SOFT_NAME, BUF_VERSION_ARGS, GRPCURL_BIN_NAME,
+            GRPCURL_SOFT_NAME, GRPCURL_VERSION_ARGS,
+        },
+        rust::{Rust, RUST_BIN_NAME, RUST_SOFT_NAME, RUST_VERSION_ARGS},
+        system::System,
+        tf::{
+            Terraform, Terragrunt, TERRAGRUNT_BIN_NAME, TERRAGRUNT_SOFT_NAME,
+            TERRAGRUNT_VERSION_ARGS, TF_BIN_NAME, TF_SOFT_NAME, TF_VERSION_ARGS,
+        },
+        tinygo::{TinyGo, TINYGO_BIN_NAME, TINYGO_SOFT_NAME, TINYGO_VERSION_ARGS},
+        zig::{Zig, ZIG_BIN_NAME, ZIG_SOFT_NAME, ZIG_VERSION_ARGS},
+        Software,
+    },
+    var::{dotenv::DotenvFile, Command, Literal, Var},
+    version::{Version, VersionReq},
 };
+#[cfg(feature = "schema")]
 use jsonschema::JSONSchema;
 use log::debug;
-use serde_json::Value;
+use serde::{
+    de::Error as _,
+    ser::{Error as _, SerializeMap},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_json::{Map, Value};
 use std::{
     fmt::{self, Display, Formatter},
-    fs::File,
-    io,
-    path::Path,
+    fs, io,
+    path::{Path, PathBuf},
 };
 #[cfg(test)]
 use stub_trait::stub;
 
+// CONSTS
+
+pub const LATEST_VERSION: &str = "latest";
+pub const SOFTWARE_NAMES: [&str; 16] = [
+    BUF_SOFT_NAME,
+    CT_SOFT_NAME,
+    CONFTEST_SOFT_NAME,
+    DIVE_SOFT_NAME,
+    GRPCURL_SOFT_NAME,
+    HELM_SOFT_NAME,
+    JAVA_SOFT_NAME,
+    KUBECTL_SOFT_NAME,
+    LAZYDOCKER_SOFT_NAME,
+    OPA_SOFT_NAME,
+    RUST_SOFT_NAME,
+    STERN_SOFT_NAME,
+    TF_SOFT_NAME,
+    TERRAGRUNT_SOFT_NAME,
+    TINYGO_SOFT_NAME,
+    ZIG_SOFT_NAME,
+];
+pub const REAL_BIN_SUFFIX: &str = "-real";
+
+const APPEND_PATH_KEY: &str = "append_path";
+const DENV_VERSION: &str = env!("CARGO_PKG_VERSION");
+const DOTENV_KEY: &str = "dotenv";
+const PREPEND_PATH_KEY: &str = "prepend_path";
+const TF_VAR_PREFIX: &str = "TF_VAR_";
+const VAR_KIND_KEYS: [&str; 5] = [
+    APPEND_PATH_KEY,
+    "command",
+    DOTENV_KEY,
+    PREPEND_PATH_KEY,
+    "value",
+];
+
 // MACROS
 
 macro_rules! add_software_definition_if_present {
-    ($key:literal, $kind:expr, $value:expr, $cfg:expr) => {
-        if let Some(soft_version) = $value.get($key) {
-            let soft_version = soft_version.as_str().unwrap();
-            let soft_def = SoftwareDefinition {
-                kind: $kind,
-                version: soft_version.into(),
-            };
-            $cfg.soft_defs.push(soft_def);
+    ($key:literal, $kind:expr, $value:expr, $soft_defs:expr) => {
+        if let Some(soft) = $value.get($key) {
+            $soft_defs.retain(|def: &SoftwareDefinition| def.kind != $kind);
+            match soft {
+                Value::Array(softs) => {
+                    for soft in softs {
+                        let soft_def =
+                            DefaultConfigLoader::parse_software_definition($key, $kind, soft)?;
+                        $soft_defs.push(soft_def);
+                    }
+                }
+                soft => {
+                    let soft_def =
+                        DefaultConfigLoader::parse_software_definition($key, $kind, soft)?;
+                    $soft_defs.push(soft_def);
+                }
+            }
         }
     };
 }
@@ -39,6 +120,8 @@ pub type Result = std::result::Result<Config, Error>;
 
 #[derive(Debug)]
 pub enum Error {
+    DenvVersion(String),
+    DuplicateVars(Vec<String>),
     Invalid(Vec<String>),
     Io(io::Error),
     Version(Option<String>),
@@ -48,6 +131,15 @@ pub enum Error {
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
+            Self::DenvVersion(req) => write!(
+                f,
+                "This configuration requires D-Env {} but current version is {}, please upgrade D-Env",
+                req, DENV_VERSION
+            ),
+            Self::DuplicateVars(_) => write!(
+                f,
+                "Variables are defined several times (use --lenient to only warn)"
+            ),
             Self::Invalid(_) => write!(f, "Invalid configuration"),
             Self::Io(err) => write!(f, "{}", err),
             Self::Version(version) => match version {
@@ -59,42 +151,374 @@ impl Display for Error {
     }
 }
 
-// ENUMS
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum SchemaVersion {
+    V1,
+    V2,
+}
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum SoftwareDefinitionKind {
+    Buf,
     ChartTesting,
+    Conftest,
+    Custom(GithubReleaseConfig),
+    Dive,
+    Grpcurl,
+    Helm,
+    Java,
+    Kubectl,
+    Lazydocker,
+    Opa,
+    Rust,
+    Stern,
     Terraform,
+    Terragrunt,
+    TinyGo,
+    Zig,
+}
+
+impl SoftwareDefinitionKind {
+    // Name, main binary name and arguments to print the version.
+    #[inline]
+    fn defaults(&self) -> (&str, &str, &'static [&'static str]) {
+        match self {
+            Self::Buf => (BUF_SOFT_NAME, BUF_BIN_NAME, BUF_VERSION_ARGS),
+            Self::ChartTesting => (CT_SOFT_NAME, CT_BIN_NAME, CT_VERSION_ARGS),
+            Self::Conftest => (CONFTEST_SOFT_NAME, CONFTEST_BIN_NAME, CONFTEST_VERSION_ARGS),
+            Self::Custom(cfg) => (&cfg.name, cfg.bin_name(), GITHUB_RELEASE_VERSION_ARGS),
+            Self::Dive => (DIVE_SOFT_NAME, DIVE_BIN_NAME, DIVE_VERSION_ARGS),
+            Self::Grpcurl => (GRPCURL_SOFT_NAME, GRPCURL_BIN_NAME, GRPCURL_VERSION_ARGS),
+            Self::Helm => (HELM_SOFT_NAME, HELM_BIN_NAME, HELM_VERSION_ARGS),
+            Self::Java => (JAVA_SOFT_NAME, JAVA_BIN_NAME, JAVA_VERSION_ARGS),
+            Self::Kubectl => (KUBECTL_SOFT_NAME, KUBECTL_BIN_NAME, KUBECTL_VERSION_ARGS),
+            Self::Lazydocker => (
+                LAZYDOCKER_SOFT_NAME,
+                LAZYDOCKER_BIN_NAME,
+                LAZYDOCKER_VERSION_ARGS,
+            ),
+            Self::Opa => (OPA_SOFT_NAME, OPA_BIN_NAME, OPA_VERSION_ARGS),
+            Self::Rust => (RUST_SOFT_NAME, RUST_BIN_NAME, RUST_VERSION_ARGS),
+            Self::Stern => (STERN_SOFT_NAME, STERN_BIN_NAME, STERN_VERSION_ARGS),
+            Self::Terraform => (TF_SOFT_NAME, TF_BIN_NAME, TF_VERSION_ARGS),
+            Self::Terragrunt => (
+                TERRAGRUNT_SOFT_NAME,
+                TERRAGRUNT_BIN_NAME,
+                TERRAGRUNT_VERSION_ARGS,
+            ),
+            Self::TinyGo => (TINYGO_SOFT_NAME, TINYGO_BIN_NAME, TINYGO_VERSION_ARGS),
+            Self::Zig => (ZIG_SOFT_NAME, ZIG_BIN_NAME, ZIG_VERSION_ARGS),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SoftwareSource {
+    Denv,
+    System,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum VarDefinitionKind {
+    AppendPath(PathBuf),
+    Command(String),
+    DotenvFile(PathBuf),
     Literal(String),
+    PrependPath(PathBuf),
+}
+
+impl VarDefinitionKind {
+    // Kinds defined by a path are listed under a top-level key instead of set.
+    #[inline]
+    fn path_key(&self) -> Option<(&'static str, &Path)> {
+        match self {
+            Self::AppendPath(path) => Some((APPEND_PATH_KEY, path)),
+            Self::DotenvFile(path) => Some((DOTENV_KEY, path)),
+            Self::PrependPath(path) => Some((PREPEND_PATH_KEY, path)),
+            Self::Command(_) | Self::Literal(_) => None,
+        }
+    }
 }
 
 // DATA STRUCTS
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Config {
+    pub download: DownloadConfig,
+    pub latency_budget_ms: Option<u64>,
+    pub message: Option<String>,
+    pub notify: Option<NotifyConfig>,
+    pub project_bin: bool,
+    pub remove_quarantine: bool,
     pub soft_defs: Vec<SoftwareDefinition>,
+    pub unset_vars: Vec<String>,
     pub var_defs: Vec<VarDefinition>,
+    pub vault_agent: Option<VaultAgentConfig>,
+}
+
+// A configuration is serialized as a v1 file which loads back to it: groups are already merged.
+impl Serialize for Config {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("version", "v1")?;
+        // Paths are serialized as written in the configuration, relative to its directory.
+        let paths = |key: &str| -> Vec<&str> {
+            self.var_defs
+                .iter()
+                .filter(|var_def| {
+                    var_def.kind.path_key().map(|(path_key, _)| path_key) == Some(key)
+                })
+                .map(|var_def| var_def.name.as_str())
+                .collect()
+        };
+        let append_paths = paths(APPEND_PATH_KEY);
+        if !append_paths.is_empty() {
+            map.serialize_entry(APPEND_PATH_KEY, &append_paths)?;
+        }
+        if self.download != DownloadConfig::default() {
+            map.serialize_entry("download", &self.download)?;
+        }
+        let dotenv_paths = paths(DOTENV_KEY);
+        if !dotenv_paths.is_empty() {
+            map.serialize_entry(DOTENV_KEY, &dotenv_paths)?;
+        }
+        if let Some(latency_budget_ms) = self.latency_budget_ms {
+            map.serialize_entry("latency_budget_ms", &latency_budget_ms)?;
+        }
+        if let Some(message) = &self.message {
+            map.serialize_entry("message", message)?;
+        }
+        if let Some(notify) = &self.notify {
+            map.serialize_entry("notify", notify)?;
+        }
+        let prepend_paths = paths(PREPEND_PATH_KEY);
+        if !prepend_paths.is_empty() {
+            map.serialize_entry(PREPEND_PATH_KEY, &prepend_paths)?;
+        }
+        if self.project_bin {
+            map.serialize_entry("project_bin", &true)?;
+        }
+        if self.remove_quarantine {
+            map.serialize_entry("remove_quarantine", &true)?;
+        }
+        if !self.soft_defs.is_empty() {
+            let softs = softwares_json(&self.soft_defs).map_err(S::Error::custom)?;
+            map.serialize_entry("softwares", &softs)?;
+        }
+        let var_defs: Vec<&VarDefinition> = self
+            .var_defs
+            .iter()
+            .filter(|var_def| var_def.kind.path_key().is_none())
+            .collect();
+        if !var_defs.is_empty() {
+            map.serialize_entry("set", &var_defs)?;
+        }
+        if !self.unset_vars.is_empty() {
+            map.serialize_entry("unset", &self.unset_vars)?;
+        }
+        if let Some(vault_agent) = &self.vault_agent {
+            map.serialize_entry("vault_agent", vault_agent)?;
+        }
+        map.end()
+    }
+}
+
+// A document is loaded like a file of the current directory: relative paths stay relative.
+impl<'de> Deserialize<'de> for Config {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let json = Value::deserialize(deserializer)?;
+        let schema_version = DefaultConfigLoader::check_version(&json).map_err(D::Error::custom)?;
+        DefaultConfigLoader::validate(&json, schema_version).map_err(D::Error::custom)?;
+        DefaultConfigLoader::load_json(json, Path::new(""), schema_version)
+            .map_err(D::Error::custom)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct NotifyConfig {
+    pub after_secs: u64,
+    pub desktop: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SoftwareDefinition {
+    pub auto: Vec<String>,
+    pub bin_name: Option<String>,
+    pub default_args: Vec<String>,
     pub kind: SoftwareDefinitionKind,
+    pub source: SoftwareSource,
     pub version: String,
 }
 
 impl SoftwareDefinition {
-    pub fn into_software(self) -> Box<dyn Software> {
-        match self.kind {
-            SoftwareDefinitionKind::ChartTesting => Box::new(ChartTesting::new(self.version)),
-            SoftwareDefinitionKind::Terraform => Box::new(Terraform::new(self.version)),
+    pub fn bin_name(&self) -> String {
+        self.bin_name
+            .clone()
+            .unwrap_or_else(|| self.kind.defaults().1.into())
+    }
+
+    // With default arguments, the binary is linked with a suffix: a shim passing them takes its
+    // name.
+    pub fn into_software(self, download_cfg: &DownloadConfig) -> Box<dyn Software> {
+        let linked_bin_name = if self.default_args.is_empty() {
+            self.bin_name.clone()
+        } else {
+            Some(format!("{}{}", self.bin_name(), REAL_BIN_SUFFIX))
+        };
+        match self.source {
+            SoftwareSource::Denv => match self.kind {
+                SoftwareDefinitionKind::Buf => {
+                    Box::new(Buf::new(self.version, linked_bin_name, download_cfg))
+                }
+                SoftwareDefinitionKind::ChartTesting => Box::new(ChartTesting::new(
+                    self.version,
+                    linked_bin_name,
+                    download_cfg,
+                )),
+                SoftwareDefinitionKind::Conftest => {
+                    Box::new(Conftest::new(self.version, linked_bin_name, download_cfg))
+                }
+                SoftwareDefinitionKind::Custom(cfg) => Box::new(GithubRelease::new(
+                    cfg,
+                    self.version,
+                    linked_bin_name,
+                    download_cfg,
+                )),
+                SoftwareDefinitionKind::Dive => {
+                    Box::new(Dive::new(self.version, linked_bin_name, download_cfg))
+                }
+                SoftwareDefinitionKind::Grpcurl => {
+                    Box::new(Grpcurl::new(self.version, linked_bin_name, download_cfg))
+                }
+                SoftwareDefinitionKind::Helm => {
+                    Box::new(Helm::new(self.version, linked_bin_name, download_cfg))
+                }
+                SoftwareDefinitionKind::Java => {
+                    Box::new(Java::new(self.version, linked_bin_name, download_cfg))
+                }
+                SoftwareDefinitionKind::Kubectl => {
+                    Box::new(Kubectl::new(self.version, linked_bin_name, download_cfg))
+                }
+                SoftwareDefinitionKind::Lazydocker => {
+                    Box::new(Lazydocker::new(self.version, linked_bin_name, download_cfg))
+                }
+                SoftwareDefinitionKind::Opa => {
+                    Box::new(Opa::new(self.version, linked_bin_name, download_cfg))
+                }
+                SoftwareDefinitionKind::Rust => {
+                    Box::new(Rust::new(self.version, linked_bin_name, download_cfg))
+                }
+                SoftwareDefinitionKind::Stern => {
+                    Box::new(Stern::new(self.version, linked_bin_name, download_cfg))
+                }
+                SoftwareDefinitionKind::Terraform => {
+                    Box::new(Terraform::new(self.version, linked_bin_name, download_cfg))
+                }
+                SoftwareDefinitionKind::Terragrunt => {
+                    Box::new(Terragrunt::new(self.version, linked_bin_name, download_cfg))
+                }
+                SoftwareDefinitionKind::TinyGo => {
+                    Box::new(TinyGo::new(self.version, linked_bin_name, download_cfg))
+                }
+                SoftwareDefinitionKind::Zig => {
+                    Box::new(Zig::new(self.version, linked_bin_name, download_cfg))
+                }
+            },
+            SoftwareSource::System => {
+                let (name, bin_name, version_args) = self.kind.defaults();
+                let bin_name = self.bin_name.unwrap_or_else(|| bin_name.into());
+                Box::new(System::new(
+                    name.into(),
+                    bin_name,
+                    version_args,
+                    self.version,
+                ))
+            }
+        }
+    }
+
+    // Requirements are resolved when the environment is loaded: softwares found in PATH keep
+    // their prefix matching. Latest is a requirement matching every version.
+    pub fn version_req(&self) -> Option<VersionReq> {
+        if self.source != SoftwareSource::Denv {
+            None
+        } else if self.version == LATEST_VERSION {
+            Some(VersionReq::default())
+        } else if VersionReq::is_req(&self.version) {
+            self.version.parse().ok()
+        } else {
+            None
         }
     }
 }
 
+// A definition without options is serialized as its version only.
+impl Serialize for SoftwareDefinition {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let cfg = match &self.kind {
+            SoftwareDefinitionKind::Custom(cfg) => Some(cfg),
+            _ => None,
+        };
+        if cfg.is_none()
+            && self.auto.is_empty()
+            && self.bin_name.is_none()
+            && self.default_args.is_empty()
+            && self.source == SoftwareSource::Denv
+        {
+            return serializer.serialize_str(&self.version);
+        }
+        let names = |names: &[(String, String)]| -> Map<String, Value> {
+            names
+                .iter()
+                .map(|(rust_name, name)| (rust_name.clone(), Value::from(name.as_str())))
+                .collect()
+        };
+        let mut map = serializer.serialize_map(None)?;
+        if let Some(cfg) = cfg {
+            if !cfg.arch.is_empty() {
+                map.serialize_entry("arch", &names(&cfg.arch))?;
+            }
+            map.serialize_entry("asset", &cfg.asset)?;
+        }
+        if !self.auto.is_empty() {
+            map.serialize_entry("auto", &self.auto)?;
+        }
+        if let Some(bin_name) = &self.bin_name {
+            map.serialize_entry("bin_name", bin_name)?;
+        }
+        if let Some(cfg) = cfg {
+            map.serialize_entry("bin_path", &cfg.bin_path)?;
+            if let Some(checksums_asset) = &cfg.checksums_asset {
+                map.serialize_entry("checksums_asset", checksums_asset)?;
+            }
+        }
+        if !self.default_args.is_empty() {
+            map.serialize_entry("default_args", &self.default_args)?;
+        }
+        if let Some(cfg) = cfg {
+            if !cfg.os.is_empty() {
+                map.serialize_entry("os", &names(&cfg.os))?;
+            }
+            map.serialize_entry("repo", &cfg.repo)?;
+        }
+        if self.source == SoftwareSource::System {
+            map.serialize_entry("source", "system")?;
+        }
+        if let Some(cfg) = cfg {
+            if cfg.strip_components > 0 {
+                map.serialize_entry("strip_components", &cfg.strip_components)?;
+            }
+            if let Some(tag) = &cfg.tag {
+                map.serialize_entry("tag", tag)?;
+            }
+        }
+        map.serialize_entry("version", &self.version)?;
+        map.end()
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct VarDefinition {
     pub kind: VarDefinitionKind,
@@ -102,11 +526,88 @@ pub struct VarDefinition {
 }
 
 impl VarDefinition {
+    // PATH entries are not computed: they are exported with the PATH of the environment.
     pub fn into_var(self) -> Box<dyn Var> {
         match self.kind {
+            VarDefinitionKind::Command(command) => Box::new(Command::new(self.name, command)),
+            VarDefinitionKind::DotenvFile(path) => Box::new(DotenvFile::new(self.name, path)),
             VarDefinitionKind::Literal(value) => Box::new(Literal::new(self.name, value)),
+            VarDefinitionKind::AppendPath(_) | VarDefinitionKind::PrependPath(_) => {
+                unreachable!("{} is a PATH entry", self.name)
+            }
         }
     }
+
+    pub fn is_path_entry(&self) -> bool {
+        matches!(
+            self.kind,
+            VarDefinitionKind::AppendPath(_) | VarDefinitionKind::PrependPath(_)
+        )
+    }
+}
+
+impl Serialize for VarDefinition {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("name", &self.name)?;
+        match &self.kind {
+            VarDefinitionKind::Command(command) => map.serialize_entry("command", command)?,
+            VarDefinitionKind::Literal(value) => map.serialize_entry("value", value)?,
+            kind => {
+                let (key, path) = kind.path_key().unwrap();
+                map.serialize_entry(key, path)?
+            }
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for VarDefinition {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let json = Map::deserialize(deserializer)?;
+        let field = |key| json.get(key).and_then(Value::as_str);
+        let name = field("name").ok_or_else(|| D::Error::missing_field("name"))?;
+        let mut kinds = VAR_KIND_KEYS
+            .iter()
+            .filter_map(|key| field(key).map(|value| (*key, value)));
+        let kind = match (kinds.next(), kinds.next()) {
+            (Some((APPEND_PATH_KEY, path)), None) => VarDefinitionKind::AppendPath(path.into()),
+            (Some(("command", command)), None) => VarDefinitionKind::Command(command.into()),
+            (Some((DOTENV_KEY, path)), None) => VarDefinitionKind::DotenvFile(path.into()),
+            (Some((PREPEND_PATH_KEY, path)), None) => VarDefinitionKind::PrependPath(path.into()),
+            (Some(("value", value)), None) => VarDefinitionKind::Literal(value.into()),
+            _ => {
+                return Err(D::Error::custom(
+                    "expected exactly one of append_path, command, dotenv, prepend_path and value",
+                ))
+            }
+        };
+        Ok(Self {
+            kind,
+            name: name.into(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VaultAgentConfig {
+    pub address: String,
+    pub auth_config: Map<String, Value>,
+    pub auth_method: String,
+}
+
+impl Serialize for VaultAgentConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut auth = Map::new();
+        if !self.auth_config.is_empty() {
+            auth.insert("config".into(), Value::Object(self.auth_config.clone()));
+        }
+        auth.insert("method".into(), Value::from(self.auth_method.as_str()));
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("address", &self.address)?;
+        map.serialize_entry("auth", &auth)?;
+        map.end()
+    }
 }
 
 // TRAITS
@@ -114,6 +615,10 @@ impl VarDefinition {
 #[cfg_attr(test, stub)]
 pub trait ConfigLoader {
     fn load(&self, path: &Path) -> Result;
+
+    // The content was already read, e.g. to be hashed: the configuration is loaded from the same
+    // bytes.
+    fn load_content(&self, path: &Path, content: &str, validate: bool) -> Result;
 }
 
 // STRUCTS
@@ -122,74 +627,1070 @@ pub struct DefaultConfigLoader;
 
 impl DefaultConfigLoader {
     #[inline]
-    fn load_v1(json: Value) -> Result {
-        let schema = include_str!("../resources/main/config/v1.schema.json");
-        let schema: Value = serde_json::from_str(schema).unwrap();
-        let schema = JSONSchema::compile(&schema).unwrap();
-        if let Err(errs) = schema.validate(&json) {
-            let err = Error::Invalid(errs.map(|err| err.to_string()).collect());
-            return Err(err);
+    fn check_denv_version(json: &Value) -> std::result::Result<(), Error> {
+        if let Some(json_req) = json.get("requires_denv") {
+            let invalid_err = || {
+                Error::Invalid(vec![format!(
+                    "{} is not a valid D-Env version requirement",
+                    json_req
+                )])
+            };
+            let req = json_req.as_str().ok_or_else(invalid_err)?;
+            let version_req: VersionReq = req.parse().map_err(|_| invalid_err())?;
+            if !Version::from(DENV_VERSION).matches(&version_req) {
+                return Err(Error::DenvVersion(req.into()));
+            }
         }
+        Ok(())
+    }
+
+    // The last definition of a variable wins: the other ones are likely mistakes.
+    #[inline]
+    fn check_duplicate_vars(json: &Value, content: &str) -> std::result::Result<(), Error> {
+        let mut names: Vec<String> = json
+            .get("set")
+            .and_then(Value::as_array)
+            .map(|vars| {
+                vars.iter()
+                    .filter_map(|var| var.get("name").and_then(Value::as_str))
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        if let Some(tf_vars) = json.get("terraform_vars").and_then(Value::as_object) {
+            names.extend(
+                tf_vars
+                    .keys()
+                    .map(|key| format!("{}{}", TF_VAR_PREFIX, key)),
+            );
+        }
+        let mut errs = vec![];
+        for (idx, name) in names.iter().enumerate() {
+            let count = names.iter().filter(|other| *other == name).count();
+            if count > 1 && !names[..idx].contains(name) {
+                let mut lines = var_name_lines(content, name);
+                if let Some(key) = name.strip_prefix(TF_VAR_PREFIX) {
+                    lines.extend(terraform_var_lines(content, key));
+                    lines.sort_unstable();
+                }
+                let lines: Vec<String> = lines.iter().map(ToString::to_string).collect();
+                errs.push(format!(
+                    "Variable {} is defined {} times (lines {})",
+                    name,
+                    count,
+                    lines.join(", ")
+                ));
+            }
+        }
+        if errs.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::DuplicateVars(errs))
+        }
+    }
+
+    #[inline]
+    fn check_version(json: &Value) -> std::result::Result<SchemaVersion, Error> {
+        let json_version = json.get("version").ok_or(Error::Version(None))?;
+        match json_version.as_str() {
+            Some("v1") => Ok(SchemaVersion::V1),
+            Some("v2") => Ok(SchemaVersion::V2),
+            _ => Err(Error::Version(Some(json_version.to_string()))),
+        }
+    }
+
+    // Included files are not covered by the hash of the configuration: they are always validated.
+    #[inline]
+    fn load_groups(json: &Value, dirpath: &Path) -> std::result::Result<Map<String, Value>, Error> {
+        let mut groups = Map::new();
+        if let Some(includes) = json.get("include") {
+            let includes = includes
+                .as_array()
+                .ok_or_else(|| invalid_type_err("include", "an array"))?;
+            for include in includes {
+                let include = include
+                    .as_str()
+                    .ok_or_else(|| invalid_type_err("include", "an array of strings"))?;
+                let path = dirpath.join(include);
+                debug!("Including groups from {}", path.display());
+                let json = Self::read(&path)?;
+                let schema_version = Self::check_version(&json)?;
+                Self::validate(&json, schema_version)?;
+                if let Some(included_groups) = json.get("groups") {
+                    let included_groups = included_groups
+                        .as_object()
+                        .ok_or_else(|| invalid_type_err("groups", "a map"))?;
+                    groups.extend(included_groups.clone());
+                }
+            }
+        }
+        if let Some(local_groups) = json.get("groups") {
+            let local_groups = local_groups
+                .as_object()
+                .ok_or_else(|| invalid_type_err("groups", "a map"))?;
+            groups.extend(local_groups.clone());
+        }
+        Ok(groups)
+    }
+
+    #[inline]
+    fn load_str(path: &Path, content: &str, validate: bool) -> Result {
+        debug!("Loading configuration from {}", path.display());
+        let json = Self::parse(content)?;
+        Self::check_denv_version(&json)?;
+        let schema_version = Self::check_version(&json)?;
+        if validate {
+            Self::validate(&json, schema_version)?;
+            Self::check_duplicate_vars(&json, content)?;
+        }
+        let dirpath = path.parent().unwrap_or_else(|| Path::new(""));
+        Self::load_json(json, dirpath, schema_version)
+    }
+
+    #[inline]
+    fn load_json(json: Value, dirpath: &Path, schema_version: SchemaVersion) -> Result {
+        let groups = Self::load_groups(&json, dirpath)?;
         let mut config = Config {
+            download: Self::parse_download_config(json.get("download"))?,
+            latency_budget_ms: json.get("latency_budget_ms").and_then(Value::as_u64),
+            message: json
+                .get("message")
+                .and_then(Value::as_str)
+                .map(String::from),
+            notify: json
+                .get("notify")
+                .map(Self::parse_notify_config)
+                .transpose()?,
+            project_bin: json
+                .get("project_bin")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            remove_quarantine: json
+                .get("remove_quarantine")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
             soft_defs: vec![],
+            unset_vars: json
+                .get("unset")
+                .and_then(Value::as_array)
+                .map(|names| {
+                    names
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
             var_defs: vec![],
+            vault_agent: json
+                .get("vault_agent")
+                .map(Self::parse_vault_agent_config)
+                .transpose()?,
         };
+        if let Some(used_groups) = json.get("use") {
+            let used_groups = used_groups
+                .as_array()
+                .ok_or_else(|| invalid_type_err("use", "an array"))?;
+            for group_name in used_groups {
+                let group_name = group_name
+                    .as_str()
+                    .ok_or_else(|| invalid_type_err("use", "an array of strings"))?;
+                let softs = groups.get(group_name).ok_or_else(|| {
+                    Error::Invalid(vec![format!("{} is not a defined group", group_name)])
+                })?;
+                Self::parse_software_definitions(softs, &mut config.soft_defs)?;
+            }
+        }
         if let Some(softs) = json.get("softwares") {
-            add_software_definition_if_present!(
-                "chart-testing",
-                SoftwareDefinitionKind::ChartTesting,
-                softs,
-                config
-            );
-            add_software_definition_if_present!(
-                "terraform",
-                SoftwareDefinitionKind::Terraform,
-                softs,
-                config
-            );
+            Self::parse_software_definitions(softs, &mut config.soft_defs)?;
+        }
+        let invalid_reqs: Vec<String> = config
+            .soft_defs
+            .iter()
+            .filter(|soft_def| {
+                VersionReq::is_req(&soft_def.version)
+                    && soft_def.version.parse::<VersionReq>().is_err()
+            })
+            .map(|soft_def| format!("{} is not a valid version requirement", soft_def.version))
+            .collect();
+        if !invalid_reqs.is_empty() {
+            return Err(Error::Invalid(invalid_reqs));
+        }
+        let paths = |key| -> Vec<&str> {
+            match json.get(key) {
+                Some(Value::Array(paths)) => paths.iter().filter_map(Value::as_str).collect(),
+                Some(path) => path.as_str().into_iter().collect(),
+                None => vec![],
+            }
+        };
+        for path in paths(DOTENV_KEY) {
+            config.var_defs.push(VarDefinition {
+                kind: VarDefinitionKind::DotenvFile(dirpath.join(path)),
+                name: path.into(),
+            });
+        }
+        // ./node_modules/.bin is exported as <project>/node_modules/.bin.
+        let entry_path = |path: &str| -> PathBuf { dirpath.join(path).components().collect() };
+        for path in paths(APPEND_PATH_KEY) {
+            config.var_defs.push(VarDefinition {
+                kind: VarDefinitionKind::AppendPath(entry_path(path)),
+                name: path.into(),
+            });
+        }
+        for path in paths(PREPEND_PATH_KEY) {
+            config.var_defs.push(VarDefinition {
+                kind: VarDefinitionKind::PrependPath(entry_path(path)),
+                name: path.into(),
+            });
         }
         if let Some(vars) = json.get("set") {
-            let vars = vars.as_array().unwrap();
+            let vars = vars
+                .as_array()
+                .ok_or_else(|| invalid_type_err("set", "an array"))?;
             for var in vars {
-                let var_name = var.get("name").unwrap().as_str().unwrap();
+                let var_name = var
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| invalid_type_err("name", "a string"))?;
                 if let Some(var_value) = var.get("value") {
-                    let var_value = match var_value {
-                        Value::Bool(var_value) => var_value.to_string(),
-                        Value::Number(var_value) => var_value.to_string(),
-                        Value::String(var_value) => var_value.to_string(),
-                        _ => unreachable!(),
+                    let var_value = match schema_version {
+                        SchemaVersion::V1 => match var_value {
+                            var_value
+                                if var.get("format").and_then(Value::as_str) == Some("json") =>
+                            {
+                                var_value.to_string()
+                            }
+                            Value::Bool(var_value) => var_value.to_string(),
+                            Value::Number(var_value) => var_value.to_string(),
+                            Value::String(var_value) => var_value.to_string(),
+                            _ => {
+                                return Err(invalid_type_err(
+                                    var_name,
+                                    "a boolean, a number or a string",
+                                ))
+                            }
+                        },
+                        SchemaVersion::V2 => Self::parse_var_value_v2(var, var_value),
                     };
                     let var_def = VarDefinition {
                         kind: VarDefinitionKind::Literal(var_value),
                         name: var_name.into(),
                     };
                     config.var_defs.push(var_def);
+                } else if let Some(command) = var.get("command").and_then(Value::as_str) {
+                    config.var_defs.push(VarDefinition {
+                        kind: VarDefinitionKind::Command(command.into()),
+                        name: var_name.into(),
+                    });
                 }
             }
         }
+        if let Some(tf_vars) = json.get("terraform_vars") {
+            let tf_vars = tf_vars
+                .as_object()
+                .ok_or_else(|| invalid_type_err("terraform_vars", "a map"))?;
+            for (key, value) in tf_vars {
+                let value = match value {
+                    Value::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+                config.var_defs.push(VarDefinition {
+                    kind: VarDefinitionKind::Literal(value),
+                    name: format!("{}{}", TF_VAR_PREFIX, key),
+                });
+            }
+        }
         Ok(config)
     }
+
+    #[inline]
+    fn parse_download_config(json: Option<&Value>) -> std::result::Result<DownloadConfig, Error> {
+        let json = match json {
+            Some(json) => json,
+            None => return Ok(DownloadConfig::default()),
+        };
+        let mut headers = vec![];
+        if let Some(hosts) = json.get("headers").and_then(Value::as_object) {
+            for (host, host_headers) in hosts {
+                let host_headers = host_headers
+                    .as_object()
+                    .ok_or_else(|| invalid_type_err(host, "a map"))?;
+                for (name, value) in host_headers {
+                    let value = value
+                        .as_str()
+                        .ok_or_else(|| invalid_type_err(name, "a string"))?;
+                    headers.push(HeaderDefinition {
+                        host: host.clone(),
+                        name: name.clone(),
+                        value: value.into(),
+                    });
+                }
+            }
+        }
+        let mut mirrors = vec![];
+        if let Some(prefixes) = json.get("mirrors").and_then(Value::as_object) {
+            for (prefix, url) in prefixes {
+                let url = url
+                    .as_str()
+                    .ok_or_else(|| invalid_type_err(prefix, "a string"))?;
+                mirrors.push(Mirror {
+                    prefix: prefix.clone(),
+                    url: url.into(),
+                });
+            }
+        }
+        Ok(DownloadConfig {
+            headers,
+            locked_sha256s: vec![],
+            mirrors,
+            shared_cache_dirpath: None,
+            user_agent: json
+                .get("user_agent")
+                .and_then(Value::as_str)
+                .map(String::from),
+        })
+    }
+
+    #[inline]
+    fn parse_github_release_config(name: &str, soft: &Value) -> GithubReleaseConfig {
+        let string = |key: &str| soft.get(key).and_then(Value::as_str).map(String::from);
+        let names = |key: &str| -> Vec<(String, String)> {
+            soft.get(key)
+                .and_then(Value::as_object)
+                .map(|names| {
+                    names
+                        .iter()
+                        .filter_map(|(rust_name, name)| {
+                            name.as_str().map(|name| (rust_name.clone(), name.into()))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        GithubReleaseConfig {
+            arch: names("arch"),
+            asset: string("asset").unwrap_or_default(),
+            bin_path: string("bin_path").unwrap_or_else(|| name.into()),
+            checksums_asset: string("checksums_asset"),
+            name: name.into(),
+            os: names("os"),
+            repo: string("repo").unwrap_or_default(),
+            strip_components: soft
+                .get("strip_components")
+                .and_then(Value::as_u64)
+                .unwrap_or_default() as usize,
+            tag: string("tag"),
+        }
+    }
+
+    // Without webhook, the notification is a desktop one unless it is explicitly disabled.
+    #[inline]
+    fn parse_notify_config(json: &Value) -> std::result::Result<NotifyConfig, Error> {
+        let webhook = json
+            .get("webhook")
+            .and_then(Value::as_str)
+            .map(String::from);
+        let after_secs = json
+            .get("after_secs")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| invalid_type_err("after_secs", "a positive integer"))?;
+        Ok(NotifyConfig {
+            after_secs,
+            desktop: json
+                .get("desktop")
+                .and_then(Value::as_bool)
+                .unwrap_or(webhook.is_none()),
+            webhook,
+        })
+    }
+
+    #[inline]
+    fn parse_software_definition(
+        name: &str,
+        kind: SoftwareDefinitionKind,
+        soft: &Value,
+    ) -> std::result::Result<SoftwareDefinition, Error> {
+        match soft {
+            Value::String(version) => Ok(SoftwareDefinition {
+                auto: vec![],
+                bin_name: None,
+                default_args: vec![],
+                kind,
+                source: SoftwareSource::Denv,
+                version: version.clone(),
+            }),
+            soft => {
+                let version = soft
+                    .get("version")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| invalid_type_err(&format!("{}.version", name), "a string"))?;
+                let source = match soft.get("source").and_then(Value::as_str) {
+                    Some("system") => SoftwareSource::System,
+                    _ => SoftwareSource::Denv,
+                };
+                let strings = |key: &str| -> Vec<String> {
+                    soft.get(key)
+                        .and_then(Value::as_array)
+                        .map(|values| {
+                            values
+                                .iter()
+                                .filter_map(Value::as_str)
+                                .map(String::from)
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                };
+                Ok(SoftwareDefinition {
+                    auto: strings("auto"),
+                    bin_name: soft
+                        .get("bin_name")
+                        .and_then(Value::as_str)
+                        .map(String::from),
+                    default_args: strings("default_args"),
+                    kind,
+                    source,
+                    version: version.into(),
+                })
+            }
+        }
+    }
+
+    #[inline]
+    fn parse_software_definitions(
+        softs: &Value,
+        soft_defs: &mut Vec<SoftwareDefinition>,
+    ) -> std::result::Result<(), Error> {
+        add_software_definition_if_present!("buf", SoftwareDefinitionKind::Buf, softs, soft_defs);
+        add_software_definition_if_present!(
+            "chart-testing",
+            SoftwareDefinitionKind::ChartTesting,
+            softs,
+            soft_defs
+        );
+        add_software_definition_if_present!(
+            "conftest",
+            SoftwareDefinitionKind::Conftest,
+            softs,
+            soft_defs
+        );
+        add_software_definition_if_present!("dive", SoftwareDefinitionKind::Dive, softs, soft_defs);
+        add_software_definition_if_present!(
+            "grpcurl",
+            SoftwareDefinitionKind::Grpcurl,
+            softs,
+            soft_defs
+        );
+        add_software_definition_if_present!("helm", SoftwareDefinitionKind::Helm, softs, soft_defs);
+        add_software_definition_if_present!("java", SoftwareDefinitionKind::Java, softs, soft_defs);
+        add_software_definition_if_present!(
+            "kubectl",
+            SoftwareDefinitionKind::Kubectl,
+            softs,
+            soft_defs
+        );
+        add_software_definition_if_present!(
+            "lazydocker",
+            SoftwareDefinitionKind::Lazydocker,
+            softs,
+            soft_defs
+        );
+        add_software_definition_if_present!("opa", SoftwareDefinitionKind::Opa, softs, soft_defs);
+        add_software_definition_if_present!("rust", SoftwareDefinitionKind::Rust, softs, soft_defs);
+        add_software_definition_if_present!(
+            "stern",
+            SoftwareDefinitionKind::Stern,
+            softs,
+            soft_defs
+        );
+        add_software_definition_if_present!(
+            "terraform",
+            SoftwareDefinitionKind::Terraform,
+            softs,
+            soft_defs
+        );
+        add_software_definition_if_present!(
+            "terragrunt",
+            SoftwareDefinitionKind::Terragrunt,
+            softs,
+            soft_defs
+        );
+        add_software_definition_if_present!(
+            "tinygo",
+            SoftwareDefinitionKind::TinyGo,
+            softs,
+            soft_defs
+        );
+        add_software_definition_if_present!("zig", SoftwareDefinitionKind::Zig, softs, soft_defs);
+        if let Some(custom_softs) = softs.get("custom").and_then(Value::as_object) {
+            for (name, soft) in custom_softs {
+                soft_defs.retain(|def| match &def.kind {
+                    SoftwareDefinitionKind::Custom(cfg) => cfg.name != *name,
+                    _ => true,
+                });
+                let cfg = Self::parse_github_release_config(name, soft);
+                let soft_def = Self::parse_software_definition(
+                    name,
+                    SoftwareDefinitionKind::Custom(cfg),
+                    soft,
+                )?;
+                soft_defs.push(soft_def);
+            }
+        }
+        Ok(())
+    }
+
+    // The type is explicit: a value which YAML does not parse as expected (e.g. 1.0 as a number)
+    // is rejected by the schema instead of being coerced.
+    #[inline]
+    fn parse_var_value_v2(var: &Value, value: &Value) -> String {
+        match (var.get("type").and_then(Value::as_str), value) {
+            (Some("json"), value) => value.to_string(),
+            (_, Value::String(value)) => value.clone(),
+            (_, value) => value.to_string(),
+        }
+    }
+
+    #[inline]
+    fn parse_vault_agent_config(json: &Value) -> std::result::Result<VaultAgentConfig, Error> {
+        let address = json
+            .get("address")
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid_type_err("vault_agent.address", "a string"))?;
+        let auth = json
+            .get("auth")
+            .ok_or_else(|| invalid_type_err("vault_agent.auth", "a map"))?;
+        let auth_method = auth
+            .get("method")
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid_type_err("vault_agent.auth.method", "a string"))?;
+        Ok(VaultAgentConfig {
+            address: address.into(),
+            auth_config: auth
+                .get("config")
+                .and_then(Value::as_object)
+                .cloned()
+                .unwrap_or_default(),
+            auth_method: auth_method.into(),
+        })
+    }
+
+    #[inline]
+    fn parse(content: &str) -> std::result::Result<Value, Error> {
+        serde_yaml::from_str(content).map_err(|err| Error::YamlSyntax(err.to_string()))
+    }
+
+    #[inline]
+    fn read(path: &Path) -> std::result::Result<Value, Error> {
+        let content = fs::read_to_string(path).map_err(Error::Io)?;
+        Self::parse(&content)
+    }
+
+    #[cfg(feature = "schema")]
+    #[inline]
+    fn validate(json: &Value, schema_version: SchemaVersion) -> std::result::Result<(), Error> {
+        let schema = match schema_version {
+            SchemaVersion::V1 => include_str!("../resources/main/config/v1.schema.json"),
+            SchemaVersion::V2 => include_str!("../resources/main/config/v2.schema.json"),
+        };
+        let schema: Value = serde_json::from_str(schema).unwrap();
+        let schema = JSONSchema::compile(&schema).unwrap();
+        schema
+            .validate(json)
+            .map_err(|errs| Error::Invalid(errs.map(|err| err.to_string()).collect()))
+    }
+
+    // Without the schema feature, configurations are not validated: a value which cannot be read is
+    // reported with an invalid error, but other mistakes (e.g. an unknown key) are ignored.
+    #[cfg(not(feature = "schema"))]
+    #[inline]
+    fn validate(_json: &Value, schema_version: SchemaVersion) -> std::result::Result<(), Error> {
+        debug!(
+            "Configuration {:?} is not validated (denv must be built with the schema feature)",
+            schema_version
+        );
+        Ok(())
+    }
 }
 
 impl ConfigLoader for DefaultConfigLoader {
     fn load(&self, path: &Path) -> Result {
-        debug!("Loading configuration from {}", path.display());
-        let file = File::open(&path).map_err(Error::Io)?;
-        let json: Value =
-            serde_yaml::from_reader(file).map_err(|err| Error::YamlSyntax(err.to_string()))?;
-        let json_version = json.get("version").ok_or(Error::Version(None))?;
-        let version = json_version
-            .as_str()
-            .ok_or_else(|| Error::Version(Some(json_version.to_string())))?;
-        match version {
-            "v1" => Self::load_v1(json),
-            _ => Err(Error::Version(Some(json_version.to_string()))),
+        let content = fs::read_to_string(path).map_err(Error::Io)?;
+        Self::load_str(path, &content, true)
+    }
+
+    fn load_content(&self, path: &Path, content: &str, validate: bool) -> Result {
+        Self::load_str(path, content, validate)
+    }
+}
+
+// FUNCTIONS
+
+// Configurations are read without validation when the schema feature is disabled or when they did
+// not change since the last load, so an unexpected value must not be trusted.
+#[inline]
+fn invalid_type_err(key: &str, expected: &str) -> Error {
+    Error::Invalid(vec![format!("{} must be {}", key, expected)])
+}
+
+// Definitions of a same software are grouped under its name, in the order of the configuration.
+#[inline]
+fn softwares_json(soft_defs: &[SoftwareDefinition]) -> serde_json::Result<Map<String, Value>> {
+    let mut softs = Map::new();
+    let mut custom_softs = Map::new();
+    for soft_def in soft_defs {
+        let json = serde_json::to_value(soft_def)?;
+        match &soft_def.kind {
+            SoftwareDefinitionKind::Custom(cfg) => {
+                custom_softs.insert(cfg.name.clone(), json);
+            }
+            kind => {
+                let name = kind.defaults().0;
+                match softs.get_mut(name) {
+                    Some(Value::Array(defs)) => defs.push(json),
+                    Some(def) => *def = Value::Array(vec![def.take(), json]),
+                    None => {
+                        softs.insert(name.into(), json);
+                    }
+                }
+            }
         }
     }
+    if !custom_softs.is_empty() {
+        softs.insert("custom".into(), Value::Object(custom_softs));
+    }
+    Ok(softs)
+}
+
+#[inline]
+fn terraform_var_lines(content: &str, key: &str) -> Vec<usize> {
+    let mut indent = None;
+    content
+        .lines()
+        .enumerate()
+        .skip_while(|(_, line)| !line.starts_with("terraform_vars:"))
+        .skip(1)
+        .take_while(|(_, line)| line.trim().is_empty() || line.starts_with([' ', '\t']))
+        .filter(|(_, line)| {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                return false;
+            }
+            let line_indent = line.len() - trimmed.len();
+            if *indent.get_or_insert(line_indent) != line_indent {
+                return false;
+            }
+            trimmed
+                .split_once(':')
+                .map(|(name, _)| name.trim().trim_matches(['"', '\'']) == key)
+                .unwrap_or(false)
+        })
+        .map(|(idx, _)| idx + 1)
+        .collect()
+}
+
+// Line numbers are looked up in the raw content: YAML values do not keep their positions.
+#[inline]
+fn var_name_lines(content: &str, name: &str) -> Vec<usize> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            line.match_indices("name:").any(|(idx, _)| {
+                let is_key = line[..idx]
+                    .chars()
+                    .last()
+                    .map(|c| !c.is_alphanumeric() && c != '_')
+                    .unwrap_or(true);
+                let value = line[idx + "name:".len()..]
+                    .trim_start()
+                    .split([',', '}', '#'])
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .trim_matches(['"', '\'']);
+                is_key && value == name
+            })
+        })
+        .map(|(idx, _)| idx + 1)
+        .collect()
 }
 
 // TESTS
 
+#[cfg(test)]
+mod config_test {
+    use super::*;
+
+    mod deserialize {
+        use super::*;
+
+        #[test]
+        fn should_return_err_if_version_is_missing() {
+            let err = serde_json::from_str::<Config>("{}").unwrap_err();
+            assert_eq!(err.to_string(), Error::Version(None).to_string());
+        }
+
+        #[test]
+        fn should_return_config_with_relative_paths() {
+            let yaml = r#"version: v1
+dotenv: .env
+softwares:
+  terraform: 1.5.7
+set:
+  - name: FOO
+    value: true
+"#;
+            let cfg: Config = serde_yaml::from_str(yaml).unwrap();
+            let expected = vec![
+                VarDefinition {
+                    kind: VarDefinitionKind::DotenvFile(PathBuf::from(".env")),
+                    name: ".env".into(),
+                },
+                VarDefinition {
+                    kind: VarDefinitionKind::Literal("true".into()),
+                    name: "FOO".into(),
+                },
+            ];
+            assert_eq!(cfg.var_defs, expected);
+            assert_eq!(cfg.soft_defs[0].version, "1.5.7");
+        }
+    }
+
+    mod serialize {
+        use super::*;
+        use crate::net::{HeaderDefinition, Mirror};
+        use proptest::{collection, option, prelude::*, sample};
+
+        const ARCHS: &[&str] = &["aarch64", "arm", "x86", "x86_64"];
+        const OSES: &[&str] = &["linux", "macos"];
+        const PROJECT_DIRPATH: &str = "/project";
+
+        // Printable strings, and scalars that YAML would not read as strings if not quoted.
+        fn string() -> impl Strategy<Value = String> {
+            prop_oneof![
+                "[ -~]{0,12}",
+                sample::select(vec![
+                    "", "true", "no", "~", "null", "1", "1.0", "0x1F", "1e3", "- a", "#", "a: b",
+                    " a ", "'", "\"", "*a", "&a", "!a", "{}", "[]",
+                ])
+                .prop_map(String::from),
+            ]
+        }
+
+        fn version() -> impl Strategy<Value = String> {
+            prop_oneof![
+                "[0-9]{1,2}(\\.[0-9]{1,2}){0,2}",
+                "~> [0-9]\\.[0-9]",
+                Just(LATEST_VERSION.to_string()),
+            ]
+        }
+
+        fn names(keys: &'static [&'static str]) -> impl Strategy<Value = Vec<(String, String)>> {
+            sample::subsequence(keys, 0..=keys.len()).prop_flat_map(|keys| {
+                collection::vec(string(), keys.len()).prop_map(move |names| {
+                    keys.iter().map(|key| key.to_string()).zip(names).collect()
+                })
+            })
+        }
+
+        fn soft_def(
+            kind: impl Strategy<Value = SoftwareDefinitionKind>,
+        ) -> impl Strategy<Value = SoftwareDefinition> {
+            (
+                kind,
+                collection::vec(string(), 0..3),
+                option::of("[a-z][a-z0-9-]{0,8}"),
+                collection::vec(string(), 0..3),
+                prop_oneof![Just(SoftwareSource::Denv), Just(SoftwareSource::System)],
+                version(),
+            )
+                .prop_map(|(kind, auto, bin_name, default_args, source, version)| {
+                    SoftwareDefinition {
+                        auto,
+                        bin_name,
+                        default_args,
+                        kind,
+                        source,
+                        version,
+                    }
+                })
+        }
+
+        fn custom_kind(name: String) -> impl Strategy<Value = SoftwareDefinitionKind> {
+            (
+                names(ARCHS),
+                string(),
+                string(),
+                option::of(string()),
+                names(OSES),
+                "[a-z0-9-]{1,8}/[a-z0-9-]{1,8}",
+                0..3usize,
+                option::of(string()),
+            )
+                .prop_map(
+                    move |(
+                        arch,
+                        asset,
+                        bin_path,
+                        checksums_asset,
+                        os,
+                        repo,
+                        strip_components,
+                        tag,
+                    )| {
+                        SoftwareDefinitionKind::Custom(GithubReleaseConfig {
+                            arch,
+                            asset,
+                            bin_path,
+                            checksums_asset,
+                            name: name.clone(),
+                            os,
+                            repo,
+                            strip_components,
+                            tag,
+                        })
+                    },
+                )
+        }
+
+        // Softwares are loaded in the order of their names, custom ones last.
+        fn soft_defs() -> impl Strategy<Value = Vec<SoftwareDefinition>> {
+            let kinds = vec![
+                SoftwareDefinitionKind::Buf,
+                SoftwareDefinitionKind::ChartTesting,
+                SoftwareDefinitionKind::Conftest,
+                SoftwareDefinitionKind::Dive,
+                SoftwareDefinitionKind::Grpcurl,
+                SoftwareDefinitionKind::Helm,
+                SoftwareDefinitionKind::Java,
+                SoftwareDefinitionKind::Kubectl,
+                SoftwareDefinitionKind::Lazydocker,
+                SoftwareDefinitionKind::Opa,
+                SoftwareDefinitionKind::Rust,
+                SoftwareDefinitionKind::Stern,
+                SoftwareDefinitionKind::Terraform,
+                SoftwareDefinitionKind::Terragrunt,
+                SoftwareDefinitionKind::TinyGo,
+                SoftwareDefinitionKind::Zig,
+            ];
+            let custom_names = collection::btree_set("[A-Za-z0-9][A-Za-z0-9._-]{0,8}", 0..3);
+            (
+                collection::vec(soft_def(sample::select(kinds)), 0..6),
+                custom_names.prop_flat_map(|names| {
+                    names
+                        .into_iter()
+                        .map(|name| soft_def(custom_kind(name)))
+                        .collect::<Vec<_>>()
+                }),
+            )
+                .prop_map(|(mut soft_defs, custom_soft_defs)| {
+                    soft_defs.sort_by_key(|soft_def| soft_def.kind.defaults().0.to_string());
+                    soft_defs.extend(custom_soft_defs);
+                    soft_defs
+                })
+        }
+
+        // Dotenv files are loaded first, then PATH entries. Their paths are relative to the
+        // directory of the file, like in real configurations.
+        fn var_defs() -> impl Strategy<Value = Vec<VarDefinition>> {
+            let kind = prop_oneof![
+                string().prop_map(VarDefinitionKind::Command),
+                string().prop_map(VarDefinitionKind::Literal),
+            ];
+            let path_defs = |pattern, kind: fn(PathBuf) -> VarDefinitionKind| {
+                collection::vec(pattern, 0..3).prop_map(move |paths| {
+                    paths
+                        .into_iter()
+                        .map(|path: String| VarDefinition {
+                            kind: kind(Path::new(PROJECT_DIRPATH).join(&path)),
+                            name: path,
+                        })
+                        .collect::<Vec<_>>()
+                })
+            };
+            (
+                path_defs("([a-z]{1,8}/)?\\.env", VarDefinitionKind::DotenvFile),
+                path_defs("[a-z]{1,8}/bin", VarDefinitionKind::AppendPath),
+                path_defs("[a-z]{1,8}/bin", VarDefinitionKind::PrependPath),
+                collection::btree_map("[A-Za-z_][A-Za-z0-9_]{0,8}", kind, 0..4),
+            )
+                .prop_map(
+                    |(dotenv_defs, append_path_defs, prepend_path_defs, vars)| {
+                        dotenv_defs
+                            .into_iter()
+                            .chain(append_path_defs)
+                            .chain(prepend_path_defs)
+                            .chain(
+                                vars.into_iter()
+                                    .map(|(name, kind)| VarDefinition { kind, name }),
+                            )
+                            .collect()
+                    },
+                )
+        }
+
+        fn download_cfg() -> impl Strategy<Value = DownloadConfig> {
+            (
+                collection::btree_map(
+                    "[a-z]{1,8}\\.com",
+                    collection::btree_map("[A-Za-z-]{1,8}", string(), 1..3),
+                    0..3,
+                ),
+                collection::btree_map(string(), string(), 0..3),
+                option::of(string()),
+            )
+                .prop_map(|(hosts, mirrors, user_agent)| DownloadConfig {
+                    headers: hosts
+                        .into_iter()
+                        .flat_map(|(host, headers)| {
+                            headers
+                                .into_iter()
+                                .map(move |(name, value)| HeaderDefinition {
+                                    host: host.clone(),
+                                    name,
+                                    value,
+                                })
+                        })
+                        .collect(),
+                    locked_sha256s: vec![],
+                    mirrors: mirrors
+                        .into_iter()
+                        .map(|(prefix, url)| Mirror { prefix, url })
+                        .collect(),
+                    shared_cache_dirpath: None,
+                    user_agent,
+                })
+        }
+
+        fn vault_agent_cfg() -> impl Strategy<Value = VaultAgentConfig> {
+            let value = prop_oneof![
+                string().prop_map(Value::from),
+                any::<i32>().prop_map(Value::from),
+                any::<bool>().prop_map(Value::from),
+            ];
+            (
+                string(),
+                collection::btree_map("[a-z_]{1,8}", value, 0..3),
+                string(),
+            )
+                .prop_map(|(address, auth_config, auth_method)| VaultAgentConfig {
+                    address,
+                    auth_config: auth_config.into_iter().collect(),
+                    auth_method,
+                })
+        }
+
+        fn notify_cfg() -> impl Strategy<Value = NotifyConfig> {
+            (0..3600u64, any::<bool>(), option::of(string())).prop_map(
+                |(after_secs, desktop, webhook)| NotifyConfig {
+                    after_secs,
+                    desktop,
+                    webhook,
+                },
+            )
+        }
+
+        fn config() -> impl Strategy<Value = Config> {
+            (
+                download_cfg(),
+                option::of(0..1_000_000u64),
+                option::of(string()),
+                option::of(notify_cfg()),
+                any::<bool>(),
+                any::<bool>(),
+                soft_defs(),
+                collection::vec(string(), 0..3),
+                var_defs(),
+                option::of(vault_agent_cfg()),
+            )
+                .prop_map(
+                    |(
+                        download,
+                        latency_budget_ms,
+                        message,
+                        notify,
+                        project_bin,
+                        remove_quarantine,
+                        soft_defs,
+                        unset_vars,
+                        var_defs,
+                        vault_agent,
+                    )| Config {
+                        download,
+                        latency_budget_ms,
+                        message,
+                        notify,
+                        project_bin,
+                        remove_quarantine,
+                        soft_defs,
+                        unset_vars,
+                        var_defs,
+                        vault_agent,
+                    },
+                )
+        }
+
+        #[inline]
+        fn load_back(cfg: &Config, dirpath: &Path) -> Result {
+            let path = dirpath.join("denv.yml");
+            let content = serde_yaml::to_string(cfg).unwrap();
+            DefaultConfigLoader.load_content(&path, &content, true)
+        }
+
+        #[test]
+        fn should_return_version_only() {
+            let soft_def = SoftwareDefinition {
+                auto: vec![],
+                bin_name: None,
+                default_args: vec![],
+                kind: SoftwareDefinitionKind::Terraform,
+                source: SoftwareSource::Denv,
+                version: "1.5.7".into(),
+            };
+            assert_eq!(serde_json::to_value(soft_def).unwrap(), "1.5.7");
+        }
+
+        #[test]
+        fn should_return_paths_as_written() {
+            let content = "version: v1\ndotenv: .env\nappend_path: [./bin]\n";
+            let cfg = DefaultConfigLoader
+                .load_content(&Path::new(PROJECT_DIRPATH).join("denv.yml"), content, true)
+                .unwrap();
+            let json = serde_json::to_value(&cfg).unwrap();
+            assert_eq!(json["dotenv"], serde_json::json!([".env"]));
+            assert_eq!(json["append_path"], serde_json::json!(["./bin"]));
+        }
+
+        #[test]
+        fn should_load_back_v1_fixture() {
+            let cfg = DefaultConfigLoader
+                .load(Path::new("resources/test/config/v1.yml"))
+                .unwrap();
+            assert_eq!(
+                load_back(&cfg, Path::new("resources/test/config")).unwrap(),
+                cfg
+            );
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(64))]
+
+            #[test]
+            fn should_load_back(cfg in config()) {
+                prop_assert_eq!(load_back(&cfg, Path::new(PROJECT_DIRPATH)).unwrap(), cfg);
+            }
+
+            #[test]
+            fn should_be_deserialized(cfg in config()) {
+                // Without a file, paths are relative to the current directory.
+                let json = serde_json::to_string(&cfg).unwrap();
+                let expected = load_back(&cfg, Path::new("")).unwrap();
+                prop_assert_eq!(serde_json::from_str::<Config>(&json).unwrap(), expected);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod error_test {
     use super::*;
@@ -197,6 +1698,31 @@ mod error_test {
     mod to_string {
         use super::*;
 
+        mod denv_version {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = format!(
+                    "This configuration requires D-Env >=99.0 but current version is {}, please upgrade D-Env",
+                    DENV_VERSION
+                );
+                let err = Error::DenvVersion(">=99.0".into());
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod duplicate_vars {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Variables are defined several times (use --lenient to only warn)";
+                let err = Error::DuplicateVars(vec![]);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
         mod invalid {
             use super::*;
 
@@ -208,90 +1734,377 @@ mod error_test {
             }
         }
 
-        mod io {
-            use super::*;
+        mod io {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let err = ::std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+                let str = err.to_string();
+                let err = Error::Io(err);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod version_missing {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Missing configuration version";
+                let err = Error::Version(None);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod version_invalid {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let version = Value::from(1).to_string();
+                let str = format!("{} is not a valid configuration version", version);
+                let err = Error::Version(Some(version));
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod yaml_syntax {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let err = serde_yaml::from_str::<Value>("{").unwrap_err();
+                let str = err.to_string();
+                let err = Error::YamlSyntax(err.to_string());
+                assert_eq!(err.to_string(), str);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod software_definition {
+    use super::*;
+    use crate::soft::Kind;
+
+    mod into_software {
+        use super::*;
+
+        #[test]
+        fn should_return_buf() {
+            test(SoftwareDefinitionKind::Buf, |kind| match kind {
+                Kind::Buf(_) => {}
+                _ => panic!(),
+            });
+        }
+
+        #[test]
+        fn should_return_chart_testing() {
+            test(SoftwareDefinitionKind::ChartTesting, |kind| match kind {
+                Kind::ChartTesting(_) => {}
+                _ => panic!(),
+            });
+        }
+
+        #[test]
+        fn should_return_conftest() {
+            test(SoftwareDefinitionKind::Conftest, |kind| match kind {
+                Kind::Conftest(_) => {}
+                _ => panic!(),
+            });
+        }
+
+        #[test]
+        fn should_return_custom() {
+            test(
+                SoftwareDefinitionKind::Custom(github_release_cfg()),
+                |kind| match kind {
+                    Kind::GithubRelease(_) => {}
+                    _ => panic!(),
+                },
+            );
+        }
+
+        #[test]
+        fn should_return_dive() {
+            test(SoftwareDefinitionKind::Dive, |kind| match kind {
+                Kind::Dive(_) => {}
+                _ => panic!(),
+            });
+        }
+
+        #[test]
+        fn should_return_grpcurl() {
+            test(SoftwareDefinitionKind::Grpcurl, |kind| match kind {
+                Kind::Grpcurl(_) => {}
+                _ => panic!(),
+            });
+        }
+
+        #[test]
+        fn should_return_java() {
+            test(SoftwareDefinitionKind::Java, |kind| match kind {
+                Kind::Java(_) => {}
+                _ => panic!(),
+            });
+        }
+
+        #[test]
+        fn should_return_helm() {
+            test(SoftwareDefinitionKind::Helm, |kind| match kind {
+                Kind::Helm(_) => {}
+                _ => panic!(),
+            });
+        }
+
+        #[test]
+        fn should_return_kubectl() {
+            test(SoftwareDefinitionKind::Kubectl, |kind| match kind {
+                Kind::Kubectl(_) => {}
+                _ => panic!(),
+            });
+        }
+
+        #[test]
+        fn should_return_lazydocker() {
+            test(SoftwareDefinitionKind::Lazydocker, |kind| match kind {
+                Kind::Lazydocker(_) => {}
+                _ => panic!(),
+            });
+        }
+
+        #[test]
+        fn should_return_opa() {
+            test(SoftwareDefinitionKind::Opa, |kind| match kind {
+                Kind::Opa(_) => {}
+                _ => panic!(),
+            });
+        }
+
+        #[test]
+        fn should_return_rust() {
+            test(SoftwareDefinitionKind::Rust, |kind| match kind {
+                Kind::Rust(_) => {}
+                _ => panic!(),
+            });
+        }
+
+        #[test]
+        fn should_return_stern() {
+            test(SoftwareDefinitionKind::Stern, |kind| match kind {
+                Kind::Stern(_) => {}
+                _ => panic!(),
+            });
+        }
+
+        #[test]
+        fn should_return_terraform() {
+            test(SoftwareDefinitionKind::Terraform, |kind| match kind {
+                Kind::Terraform(_) => {}
+                _ => panic!(),
+            });
+        }
+
+        #[test]
+        fn should_return_terragrunt() {
+            test(SoftwareDefinitionKind::Terragrunt, |kind| match kind {
+                Kind::Terragrunt(_) => {}
+                _ => panic!(),
+            });
+        }
+
+        #[test]
+        fn should_return_tinygo() {
+            test(SoftwareDefinitionKind::TinyGo, |kind| match kind {
+                Kind::TinyGo(_) => {}
+                _ => panic!(),
+            });
+        }
+
+        #[test]
+        fn should_return_zig() {
+            test(SoftwareDefinitionKind::Zig, |kind| match kind {
+                Kind::Zig(_) => {}
+                _ => panic!(),
+            });
+        }
+
+        #[test]
+        fn should_return_system_buf() {
+            test_system(SoftwareDefinitionKind::Buf, BUF_SOFT_NAME);
+        }
+
+        #[test]
+        fn should_return_system_chart_testing() {
+            test_system(SoftwareDefinitionKind::ChartTesting, CT_SOFT_NAME);
+        }
+
+        #[test]
+        fn should_return_system_conftest() {
+            test_system(SoftwareDefinitionKind::Conftest, CONFTEST_SOFT_NAME);
+        }
+
+        #[test]
+        fn should_return_system_custom() {
+            test_system(SoftwareDefinitionKind::Custom(github_release_cfg()), "just");
+        }
+
+        #[test]
+        fn should_return_system_dive() {
+            test_system(SoftwareDefinitionKind::Dive, DIVE_SOFT_NAME);
+        }
+
+        #[test]
+        fn should_return_system_grpcurl() {
+            test_system(SoftwareDefinitionKind::Grpcurl, GRPCURL_SOFT_NAME);
+        }
 
-            #[test]
-            fn should_return_str() {
-                let err = ::std::io::Error::from(std::io::ErrorKind::PermissionDenied);
-                let str = err.to_string();
-                let err = Error::Io(err);
-                assert_eq!(err.to_string(), str);
-            }
+        #[test]
+        fn should_return_system_java() {
+            test_system(SoftwareDefinitionKind::Java, JAVA_SOFT_NAME);
         }
 
-        mod version_missing {
-            use super::*;
+        #[test]
+        fn should_return_system_helm() {
+            test_system(SoftwareDefinitionKind::Helm, HELM_SOFT_NAME);
+        }
 
-            #[test]
-            fn should_return_str() {
-                let str = "Missing configuration version";
-                let err = Error::Version(None);
-                assert_eq!(err.to_string(), str);
-            }
+        #[test]
+        fn should_return_system_kubectl() {
+            test_system(SoftwareDefinitionKind::Kubectl, KUBECTL_SOFT_NAME);
         }
 
-        mod version_invalid {
-            use super::*;
+        #[test]
+        fn should_return_system_lazydocker() {
+            test_system(SoftwareDefinitionKind::Lazydocker, LAZYDOCKER_SOFT_NAME);
+        }
 
-            #[test]
-            fn should_return_str() {
-                let version = Value::from(1).to_string();
-                let str = format!("{} is not a valid configuration version", version);
-                let err = Error::Version(Some(version));
-                assert_eq!(err.to_string(), str);
-            }
+        #[test]
+        fn should_return_system_opa() {
+            test_system(SoftwareDefinitionKind::Opa, OPA_SOFT_NAME);
         }
 
-        mod yaml_syntax {
-            use super::*;
+        #[test]
+        fn should_return_system_rust() {
+            test_system(SoftwareDefinitionKind::Rust, RUST_SOFT_NAME);
+        }
 
-            #[test]
-            fn should_return_str() {
-                let err = serde_yaml::from_str::<Value>("{").unwrap_err();
-                let str = err.to_string();
-                let err = Error::YamlSyntax(err.to_string());
-                assert_eq!(err.to_string(), str);
-            }
+        #[test]
+        fn should_return_system_stern() {
+            test_system(SoftwareDefinitionKind::Stern, STERN_SOFT_NAME);
         }
-    }
-}
 
-#[cfg(test)]
-mod software_definition {
-    use super::*;
-    use crate::soft::Kind;
+        #[test]
+        fn should_return_system_terraform() {
+            test_system(SoftwareDefinitionKind::Terraform, TF_SOFT_NAME);
+        }
 
-    mod into_software {
-        use super::*;
+        #[test]
+        fn should_return_system_terragrunt() {
+            test_system(SoftwareDefinitionKind::Terragrunt, TERRAGRUNT_SOFT_NAME);
+        }
 
         #[test]
-        fn should_return_chart_testing() {
-            test(SoftwareDefinitionKind::ChartTesting, |kind| match kind {
-                Kind::ChartTesting(_) => {}
-                _ => panic!(),
-            });
+        fn should_return_system_tinygo() {
+            test_system(SoftwareDefinitionKind::TinyGo, TINYGO_SOFT_NAME);
         }
 
         #[test]
-        fn should_return_terraform() {
-            test(SoftwareDefinitionKind::Terraform, |kind| match kind {
-                Kind::Terraform(_) => {}
-                _ => panic!(),
-            });
+        fn should_return_system_zig() {
+            test_system(SoftwareDefinitionKind::Zig, ZIG_SOFT_NAME);
+        }
+
+        #[inline]
+        fn github_release_cfg() -> GithubReleaseConfig {
+            GithubReleaseConfig {
+                arch: vec![],
+                asset: "just-{version}-{arch}-{os}.tar.gz".into(),
+                bin_path: "just".into(),
+                checksums_asset: None,
+                name: "just".into(),
+                os: vec![],
+                repo: "casey/just".into(),
+                strip_components: 0,
+                tag: None,
+            }
         }
 
         #[inline]
         fn test<F: Fn(Kind)>(kind: SoftwareDefinitionKind, assert_fn: F) {
             let version = "1.2.3";
             let soft_def = SoftwareDefinition {
+                auto: vec![],
+                bin_name: None,
+                default_args: vec![],
                 kind,
+                source: SoftwareSource::Denv,
                 version: version.into(),
             };
-            let soft = soft_def.into_software();
+            let soft = soft_def.into_software(&DownloadConfig::default());
             assert_eq!(soft.version(), version);
             assert_fn(soft.kind());
         }
+
+        #[inline]
+        fn test_system(kind: SoftwareDefinitionKind, name: &str) {
+            let version = "1.2.3";
+            let soft_def = SoftwareDefinition {
+                auto: vec![],
+                bin_name: None,
+                default_args: vec![],
+                kind,
+                source: SoftwareSource::System,
+                version: version.into(),
+            };
+            let soft = soft_def.into_software(&DownloadConfig::default());
+            assert_eq!(soft.name(), name);
+            assert_eq!(soft.version(), version);
+            match soft.kind() {
+                Kind::System(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod version_req {
+        use super::*;
+
+        #[test]
+        fn should_return_none_if_source_is_system() {
+            let soft_def = soft_def(SoftwareSource::System, "~> 1.5");
+            assert!(soft_def.version_req().is_none());
+        }
+
+        #[test]
+        fn should_return_none_if_version_is_exact() {
+            let soft_def = soft_def(SoftwareSource::Denv, "1.5.0");
+            assert!(soft_def.version_req().is_none());
+        }
+
+        #[test]
+        fn should_return_default_req_if_version_is_latest() {
+            let soft_def = soft_def(SoftwareSource::Denv, LATEST_VERSION);
+            assert_eq!(soft_def.version_req(), Some(VersionReq::default()));
+        }
+
+        #[test]
+        fn should_return_req() {
+            let soft_def = soft_def(SoftwareSource::Denv, ">=1.4, <2");
+            assert_eq!(soft_def.version_req(), ">=1.4, <2".parse().ok());
+        }
+
+        #[inline]
+        fn soft_def(source: SoftwareSource, version: &str) -> SoftwareDefinition {
+            SoftwareDefinition {
+                auto: vec![],
+                bin_name: None,
+                default_args: vec![],
+                kind: SoftwareDefinitionKind::Terraform,
+                source,
+                version: version.into(),
+            }
+        }
     }
 }
 
@@ -300,9 +2113,79 @@ mod var_definition {
     use super::*;
     use crate::var::Kind;
 
+    mod deserialize {
+        use super::*;
+
+        #[test]
+        fn should_return_err_if_kind_is_ambiguous() {
+            let json = r#"{"name":"FOO","command":"date","value":"bar"}"#;
+            let err = serde_json::from_str::<VarDefinition>(json).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "expected exactly one of append_path, command, dotenv, prepend_path and value"
+            );
+        }
+
+        #[test]
+        fn should_return_var_def() {
+            let var_defs = [
+                VarDefinition {
+                    kind: VarDefinitionKind::Command("date".into()),
+                    name: "NOW".into(),
+                },
+                VarDefinition {
+                    kind: VarDefinitionKind::DotenvFile(PathBuf::from("/project/.env")),
+                    name: ".env".into(),
+                },
+                VarDefinition {
+                    kind: VarDefinitionKind::Literal("bar".into()),
+                    name: "FOO".into(),
+                },
+            ];
+            for var_def in var_defs {
+                let json = serde_json::to_string(&var_def).unwrap();
+                assert_eq!(
+                    serde_json::from_str::<VarDefinition>(&json).unwrap(),
+                    var_def
+                );
+            }
+        }
+    }
+
     mod into_var {
         use super::*;
 
+        #[test]
+        fn should_return_command() {
+            let name = "var";
+            let command = "git rev-parse --short HEAD";
+            let var_def = VarDefinition {
+                kind: VarDefinitionKind::Command(command.into()),
+                name: name.into(),
+            };
+            let var = var_def.into_var();
+            assert_eq!(var.name(), name);
+            match var.kind() {
+                Kind::Command(var) => assert_eq!(var.command(), command),
+                _ => panic!(),
+            }
+        }
+
+        #[test]
+        fn should_return_dotenv_file() {
+            let path = Path::new("/project/.env");
+            let var_def = VarDefinition {
+                kind: VarDefinitionKind::DotenvFile(path.to_path_buf()),
+                name: ".env".into(),
+            };
+            let var = var_def.into_var();
+            assert_eq!(var.name(), ".env");
+            match var.kind() {
+                Kind::DotenvFile(var) => assert_eq!(var.path(), path),
+                _ => panic!(),
+            }
+        }
+
         #[test]
         fn should_return_literal() {
             let name = "var";
@@ -315,6 +2198,7 @@ mod var_definition {
             assert_eq!(var.name(), name);
             match var.kind() {
                 Kind::Literal(var) => assert_eq!(var.value(), value),
+                _ => panic!(),
             }
         }
     }
@@ -368,6 +2252,42 @@ mod default_config_loader_test {
             );
         }
 
+        #[test]
+        fn should_return_denv_version_err() {
+            test(
+                Path::new("resources/test/config/requires-denv.yml"),
+                |res| match res.unwrap_err() {
+                    Error::DenvVersion(req) => assert_eq!(req, ">=99.0"),
+                    err => panic!("{}", err),
+                },
+            );
+        }
+
+        #[test]
+        fn should_return_invalid_err_if_software_version_requirement_is_invalid() {
+            test(
+                Path::new("resources/test/config/invalid-version-req.yml"),
+                |res| match res.unwrap_err() {
+                    Error::Invalid(errs) => {
+                        assert_eq!(errs, ["~> latest is not a valid version requirement"])
+                    }
+                    err => panic!("{}", err),
+                },
+            );
+        }
+
+        #[test]
+        fn should_return_invalid_err_if_denv_version_requirement_is_invalid() {
+            test(
+                Path::new("resources/test/config/invalid-requires-denv.yml"),
+                |res| match res.unwrap_err() {
+                    Error::Invalid(errs) => assert_eq!(errs.len(), 1),
+                    err => panic!("{}", err),
+                },
+            );
+        }
+
+        #[cfg(feature = "schema")]
         #[test]
         fn should_return_invalid_err() {
             test(
@@ -379,21 +2299,102 @@ mod default_config_loader_test {
             );
         }
 
+        #[test]
+        fn should_return_duplicate_vars_err() {
+            test(
+                Path::new("resources/test/config/duplicate-vars.yml"),
+                |res| match res.unwrap_err() {
+                    Error::DuplicateVars(errs) => assert_eq!(
+                        errs,
+                        vec![
+                            "Variable VAR1 is defined 2 times (lines 3, 7)".to_string(),
+                            "Variable TF_VAR_region is defined 2 times (lines 8, 11)".to_string(),
+                        ]
+                    ),
+                    err => panic!("{}", err),
+                },
+            );
+        }
+
         #[test]
         fn should_return_ok_if_v1() {
             let path = Path::new("resources/test/config/v1.yml");
             test(path, |res| {
                 let cfg = Config {
+                    download: DownloadConfig {
+                        headers: vec![HeaderDefinition {
+                            host: "releases.hashicorp.com".into(),
+                            name: "X-Token".into(),
+                            value: "${HASHICORP_TOKEN}".into(),
+                        }],
+                        locked_sha256s: vec![],
+                        mirrors: vec![Mirror {
+                            prefix: "https://github.com/".into(),
+                            url: "https://artifactory.example.com/github/".into(),
+                        }],
+                        shared_cache_dirpath: None,
+                        user_agent: Some("my-company-denv".into()),
+                    },
+                    latency_budget_ms: Some(300),
+                    message: Some("Run make bootstrap first".into()),
+                    notify: Some(NotifyConfig {
+                        after_secs: 60,
+                        desktop: false,
+                        webhook: Some("https://hooks.example.com/denv".into()),
+                    }),
+                    project_bin: true,
+                    remove_quarantine: true,
                     soft_defs: vec![
                         SoftwareDefinition {
+                            auto: vec![],
+                            bin_name: None,
+                            default_args: vec![],
                             kind: SoftwareDefinitionKind::ChartTesting,
+                            source: SoftwareSource::Denv,
                             version: "3.7.0".into(),
                         },
                         SoftwareDefinition {
+                            auto: vec!["*.tf".into()],
+                            bin_name: None,
+                            default_args: vec![],
                             kind: SoftwareDefinitionKind::Terraform,
-                            version: "1.2.3".into(),
+                            source: SoftwareSource::System,
+                            version: "1.2".into(),
+                        },
+                        SoftwareDefinition {
+                            auto: vec![],
+                            bin_name: Some("tf13".into()),
+                            default_args: vec!["-no-color".into()],
+                            kind: SoftwareDefinitionKind::Terraform,
+                            source: SoftwareSource::Denv,
+                            version: "0.13.7".into(),
+                        },
+                        SoftwareDefinition {
+                            auto: vec![],
+                            bin_name: None,
+                            default_args: vec![],
+                            kind: SoftwareDefinitionKind::Custom(GithubReleaseConfig {
+                                arch: vec![
+                                    ("aarch64".into(), "aarch64".into()),
+                                    ("x86_64".into(), "x86_64".into()),
+                                ],
+                                asset: "just-{version}-{arch}-{os}.tar.gz".into(),
+                                bin_path: "just".into(),
+                                checksums_asset: None,
+                                name: "just".into(),
+                                os: vec![
+                                    ("linux".into(), "unknown-linux-musl".into()),
+                                    ("macos".into(), "apple-darwin".into()),
+                                ],
+                                repo: "casey/just".into(),
+                                strip_components: 0,
+                                tag: Some("{version}".into()),
+                            }),
+                            source: SoftwareSource::Denv,
+                            version: "1.16.0".into(),
                         },
                     ],
+                    unset_vars: vec!["AWS_PROFILE".into()],
                     var_defs: vec![
                         VarDefinition {
                             kind: VarDefinitionKind::Literal("value".into()),
@@ -411,12 +2412,145 @@ mod default_config_loader_test {
                             kind: VarDefinitionKind::Literal("true".into()),
                             name: "VAR_BOOL".into(),
                         },
+                        VarDefinition {
+                            kind: VarDefinitionKind::Literal(
+                                r#"{"team":"platform","envs":["dev","prod"]}"#.into(),
+                            ),
+                            name: "TF_VAR_tags".into(),
+                        },
+                        VarDefinition {
+                            kind: VarDefinitionKind::Command("git rev-parse --short HEAD".into()),
+                            name: "GIT_SHA".into(),
+                        },
+                        VarDefinition {
+                            kind: VarDefinitionKind::Literal("eu-west-1".into()),
+                            name: "TF_VAR_region".into(),
+                        },
+                        VarDefinition {
+                            kind: VarDefinitionKind::Literal("2".into()),
+                            name: "TF_VAR_instance_count".into(),
+                        },
+                        VarDefinition {
+                            kind: VarDefinitionKind::Literal(
+                                r#"["eu-west-1a","eu-west-1b"]"#.into(),
+                            ),
+                            name: "TF_VAR_azs".into(),
+                        },
+                    ],
+                    vault_agent: Some(VaultAgentConfig {
+                        address: "https://vault.example.com".into(),
+                        auth_config: Map::from_iter([(
+                            "role_id_file_path".to_string(),
+                            Value::from("/etc/vault/role-id"),
+                        )]),
+                        auth_method: "approle".into(),
+                    }),
+                };
+                assert_eq!(res.unwrap(), cfg);
+            });
+        }
+
+        #[test]
+        fn should_return_invalid_err_if_group_is_not_defined() {
+            test(
+                Path::new("resources/test/config/unknown-group.yml"),
+                |res| match res.unwrap_err() {
+                    Error::Invalid(errs) => assert_eq!(errs, vec!["k8s is not a defined group"]),
+                    err => panic!("{}", err),
+                },
+            );
+        }
+
+        #[test]
+        fn should_return_ok_with_groups() {
+            let path = Path::new("resources/test/config/groups.yml");
+            test(path, |res| {
+                let cfg = Config {
+                    download: DownloadConfig::default(),
+                    latency_budget_ms: None,
+                    message: None,
+                    notify: None,
+                    project_bin: false,
+                    remove_quarantine: false,
+                    soft_defs: vec![
+                        SoftwareDefinition {
+                            auto: vec![],
+                            bin_name: None,
+                            default_args: vec![],
+                            kind: SoftwareDefinitionKind::Terraform,
+                            source: SoftwareSource::Denv,
+                            version: "1.2.3".into(),
+                        },
+                        SoftwareDefinition {
+                            auto: vec![],
+                            bin_name: None,
+                            default_args: vec![],
+                            kind: SoftwareDefinitionKind::ChartTesting,
+                            source: SoftwareSource::Denv,
+                            version: "3.7.0".into(),
+                        },
                     ],
+                    unset_vars: vec![],
+                    var_defs: vec![],
+                    vault_agent: None,
                 };
                 assert_eq!(res.unwrap(), cfg);
             });
         }
 
+        #[cfg(feature = "schema")]
+        #[test]
+        fn should_return_invalid_err_if_v2_var_value_does_not_match_type() {
+            test(
+                Path::new("resources/test/config/invalid-v2.yml"),
+                |res| match res.unwrap_err() {
+                    Error::Invalid(errs) => assert_eq!(errs.len(), 3),
+                    err => panic!("{}", err),
+                },
+            );
+        }
+
+        #[test]
+        fn should_return_ok_if_v2() {
+            let path = Path::new("resources/test/config/v2.yml");
+            test(path, |res| {
+                let var_defs: Vec<(String, String)> =
+                    res.unwrap()
+                        .var_defs
+                        .into_iter()
+                        .map(|def| match def.kind {
+                            VarDefinitionKind::Command(value)
+                            | VarDefinitionKind::Literal(value) => (def.name, value),
+                            VarDefinitionKind::AppendPath(path)
+                            | VarDefinitionKind::DotenvFile(path)
+                            | VarDefinitionKind::PrependPath(path) => {
+                                (def.name, path.display().to_string())
+                            }
+                        })
+                        .collect();
+                let expected = vec![
+                    (".env".into(), "resources/test/config/.env".into()),
+                    (
+                        ".env.local".into(),
+                        "resources/test/config/.env.local".into(),
+                    ),
+                    ("scripts".into(), "resources/test/config/scripts".into()),
+                    (
+                        "./node_modules/.bin".into(),
+                        "resources/test/config/node_modules/.bin".into(),
+                    ),
+                    ("VAR_STR".into(), "ON".into()),
+                    ("VAR_TAGGED".into(), "1.0".into()),
+                    ("VAR_BOOL".into(), "true".into()),
+                    ("VAR_NB".into(), "1.0".into()),
+                    ("VAR_HEX".into(), "31".into()),
+                    ("VAR_JSON".into(), r#"{"key":[1,"two",null]}"#.into()),
+                    ("GIT_SHA".into(), "git rev-parse --short HEAD".into()),
+                ];
+                assert_eq!(var_defs, expected);
+            });
+        }
+
         #[inline]
         fn test<F: Fn(Result)>(path: &Path, assert_fn: F) {
             let loader = DefaultConfigLoader;
@@ -424,4 +2558,64 @@ mod default_config_loader_test {
             assert_fn(res);
         }
     }
+
+    mod load_content {
+        use super::*;
+
+        #[test]
+        fn should_return_cfg_with_duplicate_vars_if_validation_is_disabled() {
+            let path = Path::new("resources/test/config/duplicate-vars.yml");
+            let content = fs::read_to_string(path).unwrap();
+            let cfg = DefaultConfigLoader
+                .load_content(path, &content, false)
+                .unwrap();
+            let names: Vec<&str> = cfg.var_defs.iter().map(|def| def.name.as_str()).collect();
+            assert_eq!(
+                names,
+                ["VAR1", "VAR2", "VAR1", "TF_VAR_region", "TF_VAR_region"]
+            );
+        }
+
+        #[test]
+        fn should_return_invalid_err_if_value_cannot_be_read() {
+            let path = Path::new("resources/test/config/invalid-v1.yml");
+            let content = fs::read_to_string(path).unwrap();
+            match DefaultConfigLoader
+                .load_content(path, &content, false)
+                .unwrap_err()
+            {
+                Error::Invalid(errs) => assert_eq!(errs, ["set must be an array"]),
+                err => panic!("{}", err),
+            }
+        }
+
+        #[test]
+        fn should_return_same_cfg_as_load() {
+            let path = Path::new("resources/test/config/v1.yml");
+            let content = fs::read_to_string(path).unwrap();
+            let loader = DefaultConfigLoader;
+            let cfg = loader.load_content(path, &content, false).unwrap();
+            assert_eq!(cfg, loader.load(path).unwrap());
+        }
+
+        #[test]
+        fn should_not_read_file() {
+            let path = Path::new("resources/test/config/missing.yml");
+            let content = fs::read_to_string("resources/test/config/v2.yml").unwrap();
+            DefaultConfigLoader
+                .load_content(path, &content, true)
+                .unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod var_name_lines_test {
+    use super::*;
+
+    #[test]
+    fn should_return_lines() {
+        let content = "set:\n  - name: VAR\n    value: 1\n  - name: VAR_2\n  - { name: 'VAR' }\n  - role_name: VAR\n";
+        assert_eq!(var_name_lines(content, "VAR"), [2, 5]);
+    }
 }
@@ -1,25 +1,250 @@
 // IMPORTS
 
-use clap::{Args, Parser, Subcommand};
+use crate::fs::{DefaultFileSystem, FileSystem};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use log::LevelFilter;
+use serde::Deserialize;
 use std::path::PathBuf;
 
 // ENUMS
 
 #[derive(Clone, Debug, Eq, PartialEq, Subcommand)]
 pub enum Command {
+    #[clap(about = "Add a software or a variable to the configuration")]
+    Add {
+        #[clap(help = "Name of the software (e.g. terraform), or `var` to add a variable")]
+        name: String,
+
+        #[clap(help = "Version of the software, or NAME=VALUE if a variable is added")]
+        value: String,
+    },
+
+    #[clap(about = "Trust the configuration in its current content, so the hook loads it")]
+    Allow,
+
     #[clap(subcommand)]
-    Hook(Shell),
+    Cache(CacheCommand),
+
+    #[clap(subcommand)]
+    Config(ConfigCommand),
+
+    #[clap(about = "Stop trusting the configuration, so the hook refuses to load it")]
+    Deny,
+
+    #[clap(
+        about = "Check that D-Env can run on this system (DNS resolution, TLS, home directory)"
+    )]
+    Doctor {
+        #[clap(flatten)]
+        table_opts: TableOptions,
+    },
+
+    #[clap(
+        about = "Run a command in the environment as a container entrypoint (JSON logs, signals forwarded)"
+    )]
+    Entrypoint {
+        #[clap(required = true, last = true, help = "Command to run")]
+        cmd: Vec<String>,
+    },
+
+    #[clap(subcommand)]
+    Env(EnvCommand),
+
+    #[clap(about = "Run a command in the environment")]
+    Exec {
+        #[clap(required = true, last = true, help = "Command to run")]
+        cmd: Vec<String>,
+
+        #[clap(
+            long,
+            help = "Start from an empty environment, keeping only basic variables (HOME, LANG, LOGNAME, TERM, USER)"
+        )]
+        pure: bool,
+    },
+
+    #[clap(subcommand)]
+    Export(ExportCommand),
+
+    #[clap(about = "Print loads, unloads and upgrades of the project environment")]
+    History {
+        #[clap(flatten)]
+        table_opts: TableOptions,
+    },
+
+    #[clap(about = "Print shell hook statement")]
+    Hook {
+        #[clap(
+            long,
+            global = true,
+            help = "Warn when the installed D-Env is not the version which generated the hook"
+        )]
+        auto_install_check: bool,
+
+        #[clap(subcommand)]
+        shell: Shell,
+    },
+
+    #[clap(about = "Print installed softwares of the loaded environment with their provenance")]
+    List {
+        #[clap(flatten)]
+        table_opts: TableOptions,
+    },
 
     #[clap(about = "Print shell export statements")]
     Load,
 
+    #[clap(about = "Upgrade D-Env data to the current storage layout")]
+    Migrate,
+
+    #[clap(about = "Print PATH entries, one per line")]
+    Path {
+        #[clap(
+            long,
+            help = "Print where each entry comes from (D-Env environment, PATH before load, added after load)"
+        )]
+        explain: bool,
+
+        #[clap(flatten)]
+        table_opts: TableOptions,
+    },
+
+    #[clap(about = "Remove a software or a variable from the configuration")]
+    Remove {
+        #[clap(help = "Name of the software (e.g. terraform), or `var` to remove a variable")]
+        name: String,
+
+        #[clap(
+            required_if_eq("name", "var"),
+            help = "Name of the variable if a variable is removed"
+        )]
+        var_name: Option<String>,
+
+        #[clap(
+            long,
+            requires = "write",
+            help = "Uninstall the versions of the software no longer referenced by the configuration"
+        )]
+        uninstall: bool,
+
+        #[clap(
+            long,
+            help = "Update the configuration file (otherwise only the diff is printed)"
+        )]
+        write: bool,
+    },
+
+    #[clap(about = "Print a software bill of materials of the loaded environment")]
+    Sbom {
+        #[clap(
+            long,
+            value_enum,
+            default_value = "cyclonedx",
+            help = "Document format"
+        )]
+        format: SbomFormat,
+    },
+
+    #[clap(
+        about = "Print whether an environment is loaded, with its softwares and exported variables"
+    )]
+    Status {
+        #[clap(flatten)]
+        table_opts: TableOptions,
+    },
+
     #[clap(about = "Print shell unset statements")]
-    Unload,
+    Unload {
+        #[clap(
+            long,
+            help = "Unset variables modified after load and ignore an unavailable environment state or configuration"
+        )]
+        force: bool,
+    },
+
+    #[clap(about = "Print available upgrades of softwares")]
+    Upgrade {
+        #[clap(long, help = "Print release notes of each newer version")]
+        changelog: bool,
+    },
+
+    #[clap(about = "Print available versions of a software, from the newest to the oldest")]
+    Versions {
+        #[clap(help = "Name of the software (as defined in configuration)")]
+        software: String,
+
+        #[clap(flatten)]
+        table_opts: TableOptions,
+    },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Subcommand)]
+#[clap(about = "Manage cached data")]
+pub enum CacheCommand {
+    #[clap(about = "Delete cached files")]
+    Clear {
+        #[clap(
+            long,
+            value_parser = parse_duration,
+            help = "Only delete files older than this duration (e.g. 30d, 12h)"
+        )]
+        older_than: Option<u64>,
+    },
+
+    #[clap(about = "Print cache directory")]
+    Dir,
+
+    #[clap(about = "Print cache size")]
+    Size,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Subcommand)]
+#[clap(about = "Manage configuration")]
+pub enum ConfigCommand {
+    #[clap(about = "Print the configuration with its groups merged (it loads back as a v1 file)")]
+    Show {
+        #[clap(long, value_enum, default_value = "yaml", help = "Output format")]
+        format: Format,
+    },
+
+    #[clap(about = "Validate configuration against its schema")]
+    Validate,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Subcommand)]
+#[clap(about = "Manage current environment")]
+pub enum EnvCommand {
+    #[clap(about = "Print metadata of current environment")]
+    Inspect {
+        #[clap(long, value_enum, default_value = "yaml", help = "Output format")]
+        format: Format,
+    },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Subcommand)]
+#[clap(about = "Export the environment to other tools")]
+pub enum ExportCommand {
+    #[clap(about = "Print POSIX sh export statements of the environment (e.g. for init scripts)")]
+    Sh,
+
+    #[clap(
+        about = "Merge the environment into .vscode/settings.json of the project (terminal variables, toolchain paths)"
+    )]
+    Vscode,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum Format {
+    Json,
+    Yaml,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum SbomFormat {
+    Cyclonedx,
+    Spdx,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Subcommand)]
-#[clap(about = "Print shell hook statement")]
 pub enum Shell {
     #[clap(about = "Print bash hook statement")]
     Bash,
@@ -42,16 +267,140 @@ pub struct Cli {
 
 #[derive(Args, Clone, Debug, Default, Eq, PartialEq)]
 pub struct Options {
-    #[clap(short = 'f', long = "config", help = "Override configuration file")]
+    #[clap(
+        long,
+        env = "DENV_ALLOW_ROOT",
+        help = "Allow loading environment as root (e.g. in containers)"
+    )]
+    pub allow_root: bool,
+
+    #[clap(
+        short = 'f',
+        long = "config",
+        env = "DENV_CONFIG",
+        help = "Override configuration file"
+    )]
     pub cfg_filepath: Option<PathBuf>,
 
-    #[clap(long, help = "Disable logs color")]
+    #[clap(
+        long = "home",
+        env = "DENV_HOME",
+        help = "Store all D-Env data in this directory instead of XDG base directories"
+    )]
+    pub denv_dirpath: Option<PathBuf>,
+
+    #[clap(
+        long,
+        env = "DENV_FROZEN",
+        help = "Fail if denv.lock does not match the loaded softwares instead of updating it"
+    )]
+    pub frozen: bool,
+
+    #[clap(
+        long,
+        env = "GITHUB_TOKEN",
+        hide_env_values = true,
+        help = "GitHub token used to query releases"
+    )]
+    pub github_token: Option<String>,
+
+    #[clap(
+        short = 'j',
+        long,
+        env = "DENV_JOBS",
+        value_parser = parse_jobs,
+        help = "Maximum number of softwares installed in parallel [default: 4]"
+    )]
+    pub jobs: Option<usize>,
+
+    #[clap(
+        long,
+        env = "DENV_LENIENT",
+        help = "Only warn about variables defined several times in configuration"
+    )]
+    pub lenient: bool,
+
+    #[clap(long, env = "DENV_NO_COLOR", help = "Disable logs color")]
     pub no_color: bool,
 
+    #[clap(
+        long,
+        env = "DENV_REVALIDATE",
+        help = "Validate configuration even if it did not change since the last load"
+    )]
+    pub revalidate: bool,
+
+    #[clap(
+        long = "shared-cache-dir",
+        env = "DENV_SHARED_CACHE_DIR",
+        help = "Share downloaded artifacts with the other users of the group through this directory"
+    )]
+    pub shared_cache_dirpath: Option<PathBuf>,
+
+    #[clap(
+        long,
+        env = "DENV_TIMEOUT",
+        value_parser = parse_duration,
+        help = "Abort loading after this duration (e.g. 90s, 5m), checked between software installations and variable computations"
+    )]
+    pub timeout: Option<u64>,
+
     #[clap(flatten)]
     pub verbosity: Verbosity,
 }
 
+impl Options {
+    // The settings file is read before the logger is initialized, so its errors are returned.
+    pub fn apply_settings(&mut self) -> Result<(), String> {
+        let settings = Settings::load(&DefaultFileSystem::new(None))?;
+        self.merge_settings(settings);
+        Ok(())
+    }
+
+    #[inline]
+    fn merge_settings(&mut self, settings: Settings) {
+        self.cfg_filepath = self.cfg_filepath.take().or(settings.config);
+        self.denv_dirpath = self.denv_dirpath.take().or(settings.home);
+        self.no_color |= settings.no_color;
+        self.verbosity.log_level = self.verbosity.log_level.or(settings.log_level);
+    }
+}
+
+// Options of the user settings file, used when neither a flag nor an environment variable sets
+// them.
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct Settings {
+    config: Option<PathBuf>,
+    home: Option<PathBuf>,
+    log_level: Option<LevelFilter>,
+    no_color: bool,
+}
+
+impl Settings {
+    #[inline]
+    fn load(fs: &dyn FileSystem) -> Result<Self, String> {
+        let path = fs.settings_filepath().map_err(|err| err.to_string())?;
+        if !fs.file_exists(&path) {
+            return Ok(Self::default());
+        }
+        let content = fs
+            .read_to_string(&path)
+            .map_err(|err| format!("Unable to read {}: {}", path.display(), err))?;
+        serde_yaml::from_str(&content)
+            .map_err(|err| format!("Unable to load {}: {}", path.display(), err))
+    }
+}
+
+#[derive(Args, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TableOptions {
+    #[clap(long, help = "Print as JSON")]
+    pub json: bool,
+
+    #[clap(long, help = "Don't print table header")]
+    pub no_header: bool,
+}
+
 #[derive(Args, Clone, Debug, Eq, PartialEq)]
 pub struct Verbosity {
     #[clap(
@@ -61,6 +410,13 @@ pub struct Verbosity {
     )]
     pub count: u8,
 
+    #[clap(
+        long,
+        env = "DENV_LOG_LEVEL",
+        help = "Set log level (off, error, warn, info, debug or trace), ignored if -v is given"
+    )]
+    pub log_level: Option<LevelFilter>,
+
     #[clap(
         short = 'q',
         long = "quiet",
@@ -74,29 +430,213 @@ impl Default for Verbosity {
     fn default() -> Self {
         Self {
             count: 1,
+            log_level: None,
             quiet: false,
         }
     }
 }
 
 impl Verbosity {
+    // -v and -q only exist as flags, so they win over --log-level, which can also come from
+    // DENV_LOG_LEVEL or the settings file.
     pub fn to_log_level(&self) -> LevelFilter {
         if self.quiet {
-            LevelFilter::Off
-        } else {
-            match self.count {
-                0 => LevelFilter::Error,
-                1 => LevelFilter::Warn,
-                2 => LevelFilter::Info,
-                3 => LevelFilter::Debug,
-                _ => LevelFilter::Trace,
-            }
+            return LevelFilter::Off;
+        }
+        match (self.count, self.log_level) {
+            (0, Some(level)) => level,
+            (0, None) => LevelFilter::Error,
+            (1, _) => LevelFilter::Warn,
+            (2, _) => LevelFilter::Info,
+            (3, _) => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
         }
     }
 }
 
+// FUNCTIONS
+
+fn parse_duration(duration: &str) -> Result<u64, String> {
+    let unit_idx = duration
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(duration.len());
+    let (value, unit) = duration.split_at(unit_idx);
+    let value: u64 = value
+        .parse()
+        .map_err(|_| format!("{} is not a valid duration", duration))?;
+    let unit_secs = match unit {
+        "s" | "" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        "w" => 604800,
+        _ => {
+            return Err(format!(
+                "{} is not a valid duration unit (s, m, h, d or w)",
+                unit
+            ))
+        }
+    };
+    Ok(value * unit_secs)
+}
+
+fn parse_jobs(jobs: &str) -> Result<usize, String> {
+    match jobs.parse() {
+        Ok(0) | Err(_) => Err(format!("{} is not a positive number", jobs)),
+        Ok(jobs) => Ok(jobs),
+    }
+}
+
 // TESTS
 
+#[cfg(test)]
+mod options_test {
+    use super::*;
+
+    mod merge_settings {
+        use super::*;
+
+        #[test]
+        fn should_keep_options_set_by_flag_or_env() {
+            let mut opts = Options {
+                cfg_filepath: Some("denv.yml".into()),
+                denv_dirpath: Some("/denv".into()),
+                verbosity: Verbosity {
+                    count: 0,
+                    log_level: Some(LevelFilter::Info),
+                    quiet: false,
+                },
+                ..Options::default()
+            };
+            let expected = opts.clone();
+            opts.merge_settings(Settings {
+                config: Some("other.yml".into()),
+                home: Some("/other".into()),
+                log_level: Some(LevelFilter::Trace),
+                no_color: false,
+            });
+            assert_eq!(opts, expected);
+        }
+
+        #[test]
+        fn should_set_options_from_settings() {
+            let mut opts = Options {
+                verbosity: Verbosity {
+                    count: 0,
+                    log_level: None,
+                    quiet: false,
+                },
+                ..Options::default()
+            };
+            opts.merge_settings(Settings {
+                config: Some("denv.yml".into()),
+                home: Some("/denv".into()),
+                log_level: Some(LevelFilter::Info),
+                no_color: true,
+            });
+            assert_eq!(opts.cfg_filepath, Some("denv.yml".into()));
+            assert_eq!(opts.denv_dirpath, Some("/denv".into()));
+            assert!(opts.no_color);
+            assert_eq!(opts.verbosity.to_log_level(), LevelFilter::Info);
+        }
+    }
+}
+
+#[cfg(test)]
+mod parse_duration_test {
+    use super::*;
+
+    #[test]
+    fn should_return_err_if_value_is_invalid() {
+        assert_eq!(
+            parse_duration("d").unwrap_err(),
+            "d is not a valid duration"
+        );
+    }
+
+    #[test]
+    fn should_return_err_if_unit_is_invalid() {
+        assert_eq!(
+            parse_duration("3y").unwrap_err(),
+            "y is not a valid duration unit (s, m, h, d or w)"
+        );
+    }
+
+    #[test]
+    fn should_return_secs() {
+        assert_eq!(parse_duration("45").unwrap(), 45);
+        assert_eq!(parse_duration("12h").unwrap(), 43200);
+        assert_eq!(parse_duration("30d").unwrap(), 2592000);
+    }
+}
+
+#[cfg(test)]
+mod parse_jobs_test {
+    use super::*;
+
+    #[test]
+    fn should_return_err() {
+        assert_eq!(parse_jobs("0").unwrap_err(), "0 is not a positive number");
+        assert_eq!(parse_jobs("a").unwrap_err(), "a is not a positive number");
+    }
+
+    #[test]
+    fn should_return_jobs() {
+        assert_eq!(parse_jobs("8").unwrap(), 8);
+    }
+}
+
+#[cfg(test)]
+mod settings_test {
+    use super::*;
+
+    mod load {
+        use super::*;
+        use crate::fs::StubFileSystem;
+        use std::path::Path;
+
+        const SETTINGS_FILEPATH: &str = "/config/denv/config.yml";
+
+        #[test]
+        fn should_return_default_if_file_does_not_exist() {
+            let mut fs = stub_fs();
+            fs.stub_file_exists_fn(|_| false);
+            assert_eq!(Settings::load(&fs).unwrap(), Settings::default());
+        }
+
+        #[test]
+        fn should_return_err_if_key_is_unknown() {
+            let mut fs = stub_fs();
+            fs.stub_read_to_string_fn(|_| Ok("profile: ci\n".into()));
+            let err = Settings::load(&fs).unwrap_err();
+            assert!(err.starts_with("Unable to load /config/denv/config.yml: "));
+        }
+
+        #[test]
+        fn should_return_settings() {
+            let mut fs = stub_fs();
+            fs.stub_read_to_string_fn(|_| Ok("home: /denv\nlog_level: debug\n".into()));
+            let expected = Settings {
+                home: Some("/denv".into()),
+                log_level: Some(LevelFilter::Debug),
+                ..Settings::default()
+            };
+            assert_eq!(Settings::load(&fs).unwrap(), expected);
+        }
+
+        #[inline]
+        fn stub_fs() -> StubFileSystem {
+            let mut fs = StubFileSystem::default();
+            fs.stub_settings_filepath_fn(|| Ok(SETTINGS_FILEPATH.into()));
+            fs.stub_file_exists_fn(|path| {
+                assert_eq!(path, Path::new(SETTINGS_FILEPATH));
+                true
+            });
+            fs
+        }
+    }
+}
+
 #[cfg(test)]
 mod verbosity_test {
     use super::*;
@@ -108,15 +648,37 @@ mod verbosity_test {
         fn should_return_off() {
             let verbosity = Verbosity {
                 count: 4,
+                log_level: Some(LevelFilter::Debug),
                 quiet: true,
             };
             assert_eq!(verbosity.to_log_level(), LevelFilter::Off);
         }
 
+        #[test]
+        fn should_return_log_level() {
+            let verbosity = Verbosity {
+                count: 0,
+                log_level: Some(LevelFilter::Info),
+                quiet: false,
+            };
+            assert_eq!(verbosity.to_log_level(), LevelFilter::Info);
+        }
+
+        #[test]
+        fn should_ignore_log_level_if_count_is_given() {
+            let verbosity = Verbosity {
+                count: 3,
+                log_level: Some(LevelFilter::Warn),
+                quiet: false,
+            };
+            assert_eq!(verbosity.to_log_level(), LevelFilter::Debug);
+        }
+
         #[test]
         fn should_return_error() {
             let verbosity = Verbosity {
                 count: 0,
+                log_level: None,
                 quiet: false,
             };
             assert_eq!(verbosity.to_log_level(), LevelFilter::Error);
@@ -126,6 +688,7 @@ mod verbosity_test {
         fn should_return_warn() {
             let verbosity = Verbosity {
                 count: 1,
+                log_level: None,
                 quiet: false,
             };
             assert_eq!(verbosity.to_log_level(), LevelFilter::Warn);
@@ -135,6 +698,7 @@ mod verbosity_test {
         fn should_return_info() {
             let verbosity = Verbosity {
                 count: 2,
+                log_level: None,
                 quiet: false,
             };
             assert_eq!(verbosity.to_log_level(), LevelFilter::Info);
@@ -144,6 +708,7 @@ mod verbosity_test {
         fn should_return_debug() {
             let verbosity = Verbosity {
                 count: 3,
+                log_level: None,
                 quiet: false,
             };
             assert_eq!(verbosity.to_log_level(), LevelFilter::Debug);
@@ -153,6 +718,7 @@ mod verbosity_test {
         fn should_return_trace() {
             let verbosity = Verbosity {
                 count: 4,
+                log_level: None,
                 quiet: false,
             };
             assert_eq!(verbosity.to_log_level(), LevelFilter::Trace);
@@ -166,6 +732,7 @@ mod verbosity_test {
         fn should_return_verbosity() {
             let verbosity = Verbosity {
                 count: 1,
+                log_level: None,
                 quiet: false,
             };
             assert_eq!(Verbosity::default(), verbosity);
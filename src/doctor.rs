@@ -0,0 +1,210 @@
+// IMPORTS
+
+use crate::fs::FileSystem;
+use std::path::Path;
+
+// CONSTS
+
+#[cfg(feature = "reqwest")]
+const CA_BUNDLE_FILEPATHS: &[&str] = &[
+    "/etc/ssl/certs/ca-certificates.crt",
+    "/etc/pki/tls/certs/ca-bundle.crt",
+    "/etc/ssl/cert.pem",
+];
+const DNS_CHECK_NAME: &str = "dns";
+const HOME_CHECK_NAME: &str = "home";
+const LIBC_CHECK_NAME: &str = "libc";
+const RESOLV_CONF_FILEPATH: &str = "/etc/resolv.conf";
+const TLS_CHECK_NAME: &str = "tls";
+
+// ENUMS
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Status {
+    Ok,
+    Warn,
+}
+
+// DATA STRUCTS
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Check {
+    pub detail: String,
+    pub name: &'static str,
+    pub status: Status,
+}
+
+impl Check {
+    #[inline]
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            detail: detail.into(),
+            name,
+            status: Status::Ok,
+        }
+    }
+
+    #[inline]
+    fn warn(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            detail: detail.into(),
+            name,
+            status: Status::Warn,
+        }
+    }
+}
+
+// FUNCTIONS
+
+pub fn checks(denv_dirpath: Option<&Path>, fs: &dyn FileSystem) -> Vec<Check> {
+    vec![
+        libc_check(),
+        dns_check(fs),
+        tls_check(fs),
+        home_check(denv_dirpath, fs),
+    ]
+}
+
+// Host names are resolved by getaddrinfo: musl reads /etc/resolv.conf itself, glibc may also
+// load NSS modules (see libc_check).
+#[inline]
+fn dns_check(fs: &dyn FileSystem) -> Check {
+    if !cfg!(target_os = "linux") {
+        Check::ok(DNS_CHECK_NAME, "system resolver")
+    } else if fs.file_exists(Path::new(RESOLV_CONF_FILEPATH)) {
+        Check::ok(DNS_CHECK_NAME, RESOLV_CONF_FILEPATH)
+    } else {
+        Check::warn(
+            DNS_CHECK_NAME,
+            format!(
+                "{} not found, host names cannot be resolved",
+                RESOLV_CONF_FILEPATH
+            ),
+        )
+    }
+}
+
+#[inline]
+fn home_check(denv_dirpath: Option<&Path>, fs: &dyn FileSystem) -> Check {
+    if let Some(denv_dirpath) = denv_dirpath {
+        return Check::ok(HOME_CHECK_NAME, denv_dirpath.display().to_string());
+    }
+    match fs.home_dirpath() {
+        Ok(home_dirpath) => Check::ok(HOME_CHECK_NAME, home_dirpath.display().to_string()),
+        Err(_) => Check::warn(
+            HOME_CHECK_NAME,
+            "HOME is not set and the user is not in /etc/passwd (set DENV_HOME)",
+        ),
+    }
+}
+
+// A static glibc still loads NSS modules at runtime to resolve host names: they are missing from
+// scratch and distroless images, where only a musl build works.
+#[inline]
+fn libc_check() -> Check {
+    let linkage = if cfg!(target_feature = "crt-static") {
+        "static"
+    } else {
+        "dynamic"
+    };
+    if cfg!(target_env = "musl") {
+        Check::ok(LIBC_CHECK_NAME, format!("musl ({})", linkage))
+    } else if cfg!(target_env = "gnu") && cfg!(target_feature = "crt-static") {
+        Check::warn(
+            LIBC_CHECK_NAME,
+            "glibc (static): DNS resolution requires the NSS modules of the system (use a musl build in scratch containers)",
+        )
+    } else if cfg!(target_env = "gnu") {
+        Check::ok(LIBC_CHECK_NAME, format!("glibc ({})", linkage))
+    } else {
+        Check::ok(LIBC_CHECK_NAME, format!("system ({})", linkage))
+    }
+}
+
+#[cfg(feature = "reqwest")]
+#[inline]
+fn tls_check(fs: &dyn FileSystem) -> Check {
+    if !cfg!(target_os = "linux") {
+        return Check::ok(TLS_CHECK_NAME, "native TLS with system certificates");
+    }
+    match CA_BUNDLE_FILEPATHS
+        .iter()
+        .find(|path| fs.file_exists(Path::new(path)))
+    {
+        Some(path) => Check::ok(TLS_CHECK_NAME, format!("OpenSSL with {}", path)),
+        None => Check::warn(
+            TLS_CHECK_NAME,
+            "OpenSSL without CA certificates (install them or build with the ureq feature)",
+        ),
+    }
+}
+
+#[cfg(all(feature = "ureq", not(feature = "reqwest")))]
+#[inline]
+fn tls_check(_fs: &dyn FileSystem) -> Check {
+    Check::ok(TLS_CHECK_NAME, "rustls with bundled root certificates")
+}
+
+#[cfg(not(any(feature = "reqwest", feature = "ureq")))]
+#[inline]
+fn tls_check(_fs: &dyn FileSystem) -> Check {
+    Check::warn(
+        TLS_CHECK_NAME,
+        "no HTTP client, softwares cannot be downloaded (build with the ureq or reqwest feature)",
+    )
+}
+
+// TESTS
+
+#[cfg(test)]
+mod dns_check_test {
+    use super::*;
+    use crate::fs::StubFileSystem;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn should_return_warn_if_resolv_conf_is_missing() {
+        let mut fs = StubFileSystem::default();
+        fs.stub_file_exists_fn(|path| {
+            assert_eq!(path, Path::new(RESOLV_CONF_FILEPATH));
+            false
+        });
+        assert_eq!(dns_check(&fs).status, Status::Warn);
+    }
+
+    #[test]
+    fn should_return_ok() {
+        let mut fs = StubFileSystem::default();
+        fs.stub_file_exists_fn(|_| true);
+        assert_eq!(dns_check(&fs).status, Status::Ok);
+    }
+}
+
+#[cfg(test)]
+mod home_check_test {
+    use super::*;
+    use crate::fs::StubFileSystem;
+    use std::{io, path::PathBuf};
+
+    #[test]
+    fn should_return_denv_dirpath() {
+        let fs = StubFileSystem::default();
+        let check = home_check(Some(Path::new("/denv")), &fs);
+        assert_eq!(check, Check::ok(HOME_CHECK_NAME, "/denv"));
+    }
+
+    #[test]
+    fn should_return_home_dirpath() {
+        let mut fs = StubFileSystem::default();
+        fs.stub_home_dirpath_fn(|| Ok(PathBuf::from("/home/user")));
+        let check = home_check(None, &fs);
+        assert_eq!(check, Check::ok(HOME_CHECK_NAME, "/home/user"));
+    }
+
+    #[test]
+    fn should_return_warn_if_home_is_not_found() {
+        let mut fs = StubFileSystem::default();
+        fs.stub_home_dirpath_fn(|| Err(io::Error::from(io::ErrorKind::NotFound)));
+        assert_eq!(home_check(None, &fs).status, Status::Warn);
+    }
+}
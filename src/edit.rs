@@ -0,0 +1,611 @@
+// IMPORTS
+
+use std::{
+    fmt::{self, Display, Formatter},
+    io::{Error, ErrorKind, Result},
+};
+
+// CONSTS
+
+const DIFF_CONTEXT: usize = 2;
+const DEFAULT_INDENT: usize = 2;
+const SOFTWARES_KEY: &str = "softwares";
+const VARS_KEY: &str = "set";
+
+// STRUCTS
+
+// Configuration files are edited line by line, so comments, blank lines and the formatting of
+// untouched entries are kept. Only block style sections can be edited.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfigDocument {
+    lines: Vec<String>,
+}
+
+impl ConfigDocument {
+    pub fn parse(content: &str) -> Self {
+        Self {
+            lines: content.lines().map(String::from).collect(),
+        }
+    }
+
+    pub fn remove_software(&mut self, name: &str) -> Result<bool> {
+        let (key_idx, end) = match self.section(SOFTWARES_KEY)? {
+            Some(section) => section,
+            None => return Ok(false),
+        };
+        let soft_idx = match self.find_software(key_idx, end, name) {
+            Some(soft_idx) => soft_idx,
+            None => return Ok(false),
+        };
+        let indent = self.child_indent(key_idx, end);
+        let soft_end = self.block_end(soft_idx, end, indent);
+        self.lines
+            .drain(soft_idx..=self.last_content_idx(soft_idx, soft_end));
+        self.remove_section_if_empty(SOFTWARES_KEY)?;
+        Ok(true)
+    }
+
+    pub fn remove_var(&mut self, name: &str) -> Result<bool> {
+        let (key_idx, end) = match self.section(VARS_KEY)? {
+            Some(section) => section,
+            None => return Ok(false),
+        };
+        let (item_idx, item_end) = match self.find_item(key_idx, end, name) {
+            Some(item) => item,
+            None => return Ok(false),
+        };
+        self.lines
+            .drain(item_idx..=self.last_content_idx(item_idx, item_end));
+        self.remove_section_if_empty(VARS_KEY)?;
+        Ok(true)
+    }
+
+    pub fn set_software(&mut self, name: &str, version: &str) -> Result<()> {
+        let version = scalar(version);
+        let (key_idx, end) = match self.section(SOFTWARES_KEY)? {
+            Some(section) => section,
+            None => {
+                self.append_section(SOFTWARES_KEY, vec![format!("  {}: {}", name, version)]);
+                return Ok(());
+            }
+        };
+        let indent = self.child_indent(key_idx, end);
+        match self.find_software(key_idx, end, name) {
+            Some(soft_idx) if line_value(&self.lines[soft_idx]).is_empty() => {
+                let soft_end = self.block_end(soft_idx, end, indent);
+                let is_seq = (soft_idx + 1..soft_end)
+                    .map(|idx| self.lines[idx].trim_start())
+                    .find(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(|line| line.starts_with('-'))
+                    .unwrap_or(false);
+                if is_seq {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("{} has several versions and cannot be edited", name),
+                    ));
+                }
+                let version_idx = (soft_idx + 1..soft_end)
+                    .find(|idx| line_key(&self.lines[*idx]) == Some("version"));
+                match version_idx {
+                    Some(version_idx) => self.replace_value(version_idx, &version),
+                    None => {
+                        let line = format!("{}version: {}", " ".repeat(indent * 2), version);
+                        self.lines.insert(soft_idx + 1, line);
+                    }
+                }
+            }
+            Some(soft_idx) => self.replace_value(soft_idx, &version),
+            None => {
+                let line = format!("{}{}: {}", " ".repeat(indent), name, version);
+                let idx = self.last_content_idx(key_idx, end) + 1;
+                self.lines.insert(idx, line);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn set_var(&mut self, name: &str, value: &str) -> Result<()> {
+        let value = scalar(value);
+        let (key_idx, end) = match self.section(VARS_KEY)? {
+            Some(section) => section,
+            None => {
+                let lines = vec![
+                    format!("  - name: {}", name),
+                    format!("    value: {}", value),
+                ];
+                self.append_section(VARS_KEY, lines);
+                return Ok(());
+            }
+        };
+        match self.find_item(key_idx, end, name) {
+            Some((item_idx, item_end)) => {
+                let value_idx =
+                    (item_idx..item_end).find(|idx| item_key(&self.lines[*idx]) == Some("value"));
+                match value_idx {
+                    Some(value_idx) => self.replace_value(value_idx, &value),
+                    None => {
+                        let indent = item_content_indent(&self.lines[item_idx]);
+                        let line = format!("{}value: {}", " ".repeat(indent), value);
+                        let idx = self.last_content_idx(item_idx, item_end) + 1;
+                        self.lines.insert(idx, line);
+                    }
+                }
+            }
+            None => {
+                let indent = self.child_indent(key_idx, end);
+                let idx = self.last_content_idx(key_idx, end) + 1;
+                let lines = [
+                    format!("{}- name: {}", " ".repeat(indent), name),
+                    format!("{}value: {}", " ".repeat(indent + 2), value),
+                ];
+                self.lines.splice(idx..idx, lines);
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn append_section(&mut self, key: &str, lines: Vec<String>) {
+        self.lines.push(format!("{}:", key));
+        self.lines.extend(lines);
+    }
+
+    // A block ends on the first line which is not more indented than its key.
+    #[inline]
+    fn block_end(&self, key_idx: usize, end: usize, indent: usize) -> usize {
+        (key_idx + 1..end)
+            .find(|idx| {
+                let line = &self.lines[*idx];
+                !is_blank_or_comment(line) && line_indent(line) <= indent
+            })
+            .unwrap_or(end)
+    }
+
+    #[inline]
+    fn child_indent(&self, key_idx: usize, end: usize) -> usize {
+        self.lines[key_idx + 1..end]
+            .iter()
+            .find(|line| !is_blank_or_comment(line))
+            .map(|line| line_indent(line))
+            .unwrap_or(DEFAULT_INDENT)
+    }
+
+    #[inline]
+    fn find_item(&self, key_idx: usize, end: usize, name: &str) -> Option<(usize, usize)> {
+        let indent = self.child_indent(key_idx, end);
+        let item_idxs: Vec<usize> = (key_idx + 1..end)
+            .filter(|idx| {
+                let line = &self.lines[*idx];
+                line_indent(line) == indent && line.trim_start().starts_with('-')
+            })
+            .collect();
+        item_idxs.iter().enumerate().find_map(|(pos, item_idx)| {
+            let item_end = item_idxs.get(pos + 1).copied().unwrap_or(end);
+            let found = (*item_idx..item_end).any(|idx| {
+                let line = &self.lines[idx];
+                item_key(line) == Some("name") && unquote(line_value(line)) == name
+            });
+            found.then_some((*item_idx, item_end))
+        })
+    }
+
+    #[inline]
+    fn find_software(&self, key_idx: usize, end: usize, name: &str) -> Option<usize> {
+        let indent = self.child_indent(key_idx, end);
+        (key_idx + 1..end).find(|idx| {
+            let line = &self.lines[*idx];
+            line_indent(line) == indent && line_key(line) == Some(name)
+        })
+    }
+
+    #[inline]
+    fn last_content_idx(&self, key_idx: usize, end: usize) -> usize {
+        (key_idx..end)
+            .rev()
+            .find(|idx| !is_blank_or_comment(&self.lines[*idx]))
+            .unwrap_or(key_idx)
+    }
+
+    // An empty section would be read as null, which is not a valid value.
+    #[inline]
+    fn remove_section_if_empty(&mut self, key: &str) -> Result<()> {
+        if let Some((key_idx, end)) = self.section(key)? {
+            if self.last_content_idx(key_idx, end) == key_idx {
+                self.lines.remove(key_idx);
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn replace_value(&mut self, idx: usize, value: &str) {
+        let line = &self.lines[idx];
+        let colon_idx = line.find(':').unwrap_or(line.len());
+        let comment = line_comment(line)
+            .map(|comment| format!(" {}", comment))
+            .unwrap_or_default();
+        self.lines[idx] = format!("{}: {}{}", &line[..colon_idx], value, comment);
+    }
+
+    // Top-level sections end on the next top-level key: sequence items may not be indented.
+    #[inline]
+    fn section(&self, key: &str) -> Result<Option<(usize, usize)>> {
+        let key_idx = match self
+            .lines
+            .iter()
+            .position(|line| line_indent(line) == 0 && line_key(line) == Some(key))
+        {
+            Some(key_idx) => key_idx,
+            None => return Ok(None),
+        };
+        if !line_value(&self.lines[key_idx]).is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("{} is not written in block style and cannot be edited", key),
+            ));
+        }
+        let end = (key_idx + 1..self.lines.len())
+            .find(|idx| {
+                let line = &self.lines[*idx];
+                !is_blank_or_comment(line) && line_indent(line) == 0 && !line.starts_with('-')
+            })
+            .unwrap_or(self.lines.len());
+        Ok(Some((key_idx, end)))
+    }
+}
+
+impl Display for ConfigDocument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+// FUNCTIONS
+
+// Unified diff of two versions of a file, lines being matched by their longest common subsequence.
+pub fn diff(path: &str, old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let mut lcs = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for old_idx in (0..old.len()).rev() {
+        for new_idx in (0..new.len()).rev() {
+            lcs[old_idx][new_idx] = if old[old_idx] == new[new_idx] {
+                lcs[old_idx + 1][new_idx + 1] + 1
+            } else {
+                lcs[old_idx + 1][new_idx].max(lcs[old_idx][new_idx + 1])
+            };
+        }
+    }
+    let mut ops = vec![];
+    let (mut old_idx, mut new_idx) = (0, 0);
+    while old_idx < old.len() || new_idx < new.len() {
+        let op = if old_idx < old.len() && new_idx < new.len() && old[old_idx] == new[new_idx] {
+            (' ', old[old_idx])
+        } else if new_idx < new.len()
+            && (old_idx == old.len() || lcs[old_idx][new_idx + 1] >= lcs[old_idx + 1][new_idx])
+        {
+            ('+', new[new_idx])
+        } else {
+            ('-', old[old_idx])
+        };
+        ops.push((op.0, op.1, old_idx, new_idx));
+        match op.0 {
+            ' ' => {
+                old_idx += 1;
+                new_idx += 1;
+            }
+            '+' => new_idx += 1,
+            _ => old_idx += 1,
+        }
+    }
+    let changed: Vec<usize> = (0..ops.len()).filter(|idx| ops[*idx].0 != ' ').collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+    let mut out = format!("--- {}\n+++ {}\n", path, path);
+    let mut pos = 0;
+    while pos < changed.len() {
+        let start = changed[pos].saturating_sub(DIFF_CONTEXT);
+        let mut end = changed[pos] + 1;
+        while pos < changed.len() && changed[pos] <= end + DIFF_CONTEXT * 2 {
+            end = changed[pos] + 1;
+            pos += 1;
+        }
+        let end = (end + DIFF_CONTEXT).min(ops.len());
+        let hunk = &ops[start..end];
+        let old_count = hunk.iter().filter(|op| op.0 != '+').count();
+        let new_count = hunk.iter().filter(|op| op.0 != '-').count();
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            hunk[0].2 + 1,
+            old_count,
+            hunk[0].3 + 1,
+            new_count
+        ));
+        for (op, line, _, _) in hunk {
+            out.push_str(&format!("{}{}\n", op, line));
+        }
+    }
+    out
+}
+
+#[inline]
+fn is_blank_or_comment(line: &str) -> bool {
+    let line = line.trim_start();
+    line.is_empty() || line.starts_with('#')
+}
+
+#[inline]
+fn item_content_indent(line: &str) -> usize {
+    let content = line.trim_start().trim_start_matches('-').trim_start();
+    line.len() - content.len()
+}
+
+// Keys of a sequence item may be written on the line of the dash.
+#[inline]
+fn item_key(line: &str) -> Option<&str> {
+    line_key(line.trim_start().trim_start_matches('-'))
+}
+
+#[inline]
+fn line_comment(line: &str) -> Option<&str> {
+    line.find(" #").map(|idx| line[idx..].trim_start())
+}
+
+#[inline]
+fn line_indent(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+#[inline]
+fn line_key(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    if line.starts_with('#') || line.starts_with('-') {
+        return None;
+    }
+    let (key, rest) = line.split_once(':')?;
+    (rest.is_empty() || rest.starts_with(' ')).then(|| unquote(key.trim_end()))
+}
+
+#[inline]
+fn line_value(line: &str) -> &str {
+    let line = match line.find(" #") {
+        Some(idx) => &line[..idx],
+        None => line,
+    };
+    line.split_once(':')
+        .map(|(_, value)| value.trim())
+        .unwrap_or_default()
+}
+
+// Values which YAML would not read as strings (e.g. 1.2 or true) are quoted.
+#[inline]
+fn scalar(value: &str) -> String {
+    match serde_yaml::from_str::<serde_yaml::Value>(value) {
+        Ok(serde_yaml::Value::String(parsed)) if parsed == value => value.into(),
+        _ => serde_json::to_string(value).unwrap(),
+    }
+}
+
+#[inline]
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .or_else(|| {
+            value
+                .strip_prefix('\'')
+                .and_then(|value| value.strip_suffix('\''))
+        })
+        .unwrap_or(value)
+}
+
+// TESTS
+
+#[cfg(test)]
+mod config_document_test {
+    use super::*;
+
+    const CFG: &str = r#"# Project environment
+version: v1
+softwares:
+  chart-testing: 3.7.0 # Pinned by CI
+  terraform:
+    version: "1.2"
+    auto:
+      - "*.tf"
+
+set:
+  - name: VAR1
+    value: value1 # Default value
+  - value: 1
+    name: VAR2
+unset:
+  - AWS_PROFILE
+"#;
+
+    mod remove_software {
+        use super::*;
+
+        #[test]
+        fn should_remove_section_if_empty() {
+            let mut doc =
+                ConfigDocument::parse("version: v1\nsoftwares:\n  terraform: 1.6.2\nunset: []\n");
+            assert!(doc.remove_software("terraform").unwrap());
+            assert_eq!(doc.to_string(), "version: v1\nunset: []\n");
+        }
+
+        #[test]
+        fn should_remove_software_mapping() {
+            let mut doc = ConfigDocument::parse(CFG);
+            assert!(doc.remove_software("terraform").unwrap());
+            let expected = CFG.replace(
+                "  terraform:\n    version: \"1.2\"\n    auto:\n      - \"*.tf\"\n",
+                "",
+            );
+            assert_eq!(doc.to_string(), expected);
+        }
+
+        #[test]
+        fn should_remove_software_version() {
+            let mut doc = ConfigDocument::parse(CFG);
+            assert!(doc.remove_software("chart-testing").unwrap());
+            let expected = CFG.replace("  chart-testing: 3.7.0 # Pinned by CI\n", "");
+            assert_eq!(doc.to_string(), expected);
+        }
+
+        #[test]
+        fn should_return_false_if_software_is_not_defined() {
+            let mut doc = ConfigDocument::parse(CFG);
+            assert!(!doc.remove_software("helm").unwrap());
+            assert_eq!(doc.to_string(), CFG);
+        }
+    }
+
+    mod remove_var {
+        use super::*;
+
+        #[test]
+        fn should_remove_var() {
+            let mut doc = ConfigDocument::parse(CFG);
+            assert!(doc.remove_var("VAR2").unwrap());
+            let expected = CFG.replace("  - value: 1\n    name: VAR2\n", "");
+            assert_eq!(doc.to_string(), expected);
+        }
+
+        #[test]
+        fn should_return_false_if_var_is_not_defined() {
+            let mut doc = ConfigDocument::parse(CFG);
+            assert!(!doc.remove_var("FOO").unwrap());
+            assert_eq!(doc.to_string(), CFG);
+        }
+    }
+
+    mod set_software {
+        use super::*;
+
+        #[test]
+        fn should_add_section() {
+            let mut doc = ConfigDocument::parse("version: v1\n");
+            doc.set_software("terraform", "1.6.2").unwrap();
+            assert_eq!(
+                doc.to_string(),
+                "version: v1\nsoftwares:\n  terraform: 1.6.2\n"
+            );
+        }
+
+        #[test]
+        fn should_add_software() {
+            let mut doc = ConfigDocument::parse(
+                "version: v1\nsoftwares:\n  chart-testing: 3.7.0\n\nunset: []\n",
+            );
+            doc.set_software("terraform", "1.6").unwrap();
+            let expected = "version: v1\nsoftwares:\n  chart-testing: 3.7.0\n  terraform: \"1.6\"\n\nunset: []\n";
+            assert_eq!(doc.to_string(), expected);
+        }
+
+        #[test]
+        fn should_return_err_if_section_is_flow_style() {
+            let mut doc = ConfigDocument::parse("version: v1\nsoftwares: {}\n");
+            let err = doc.set_software("terraform", "1.6.2").unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData);
+        }
+
+        #[test]
+        fn should_return_err_if_software_has_several_versions() {
+            let mut doc = ConfigDocument::parse(
+                "version: v1\nsoftwares:\n  terraform:\n    - 1.6.2\n    - version: 0.13.7\n      bin_name: tf13\n",
+            );
+            let err = doc.set_software("terraform", "1.6.3").unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData);
+        }
+
+        #[test]
+        fn should_update_software_mapping() {
+            let mut doc = ConfigDocument::parse(CFG);
+            doc.set_software("terraform", "1.6.2").unwrap();
+            let expected = CFG.replace("version: \"1.2\"", "version: 1.6.2");
+            assert_eq!(doc.to_string(), expected);
+        }
+
+        #[test]
+        fn should_update_software_version() {
+            let mut doc = ConfigDocument::parse(CFG);
+            doc.set_software("chart-testing", "3.8.0").unwrap();
+            let expected = CFG.replace("3.7.0 #", "3.8.0 #");
+            assert_eq!(doc.to_string(), expected);
+        }
+    }
+
+    mod set_var {
+        use super::*;
+
+        #[test]
+        fn should_add_section() {
+            let mut doc = ConfigDocument::parse("version: v1\n");
+            doc.set_var("FOO", "bar").unwrap();
+            assert_eq!(
+                doc.to_string(),
+                "version: v1\nset:\n  - name: FOO\n    value: bar\n"
+            );
+        }
+
+        #[test]
+        fn should_add_var() {
+            let mut doc = ConfigDocument::parse(CFG);
+            doc.set_var("FOO", "true").unwrap();
+            let expected = CFG.replace(
+                "    name: VAR2\n",
+                "    name: VAR2\n  - name: FOO\n    value: \"true\"\n",
+            );
+            assert_eq!(doc.to_string(), expected);
+        }
+
+        #[test]
+        fn should_add_var_to_unindented_sequence() {
+            let mut doc = ConfigDocument::parse("set:\n- name: VAR1\n  value: value1\n");
+            doc.set_var("FOO", "bar").unwrap();
+            assert_eq!(
+                doc.to_string(),
+                "set:\n- name: VAR1\n  value: value1\n- name: FOO\n  value: bar\n"
+            );
+        }
+
+        #[test]
+        fn should_update_var() {
+            let mut doc = ConfigDocument::parse(CFG);
+            doc.set_var("VAR1", "bar").unwrap();
+            let expected = CFG.replace("value: value1 #", "value: bar #");
+            assert_eq!(doc.to_string(), expected);
+        }
+
+        #[test]
+        fn should_update_var_with_value_first() {
+            let mut doc = ConfigDocument::parse(CFG);
+            doc.set_var("VAR2", "bar").unwrap();
+            let expected = CFG.replace("- value: 1", "- value: bar");
+            assert_eq!(doc.to_string(), expected);
+        }
+    }
+}
+
+#[cfg(test)]
+mod diff_test {
+    use super::*;
+
+    #[test]
+    fn should_return_empty_str_if_contents_are_equal() {
+        assert_eq!(diff("denv.yml", "a\nb\n", "a\nb\n"), "");
+    }
+
+    #[test]
+    fn should_return_hunks() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let new = "1\n3\n4\n5\n6\n7\n8\n9\nnine\n10\n";
+        let expected = "--- denv.yml\n+++ denv.yml\n@@ -1,4 +1,3 @@\n 1\n-2\n 3\n 4\n@@ -8,3 +7,4 @@\n 8\n 9\n+nine\n 10\n";
+        assert_eq!(diff("denv.yml", old, new), expected);
+    }
+}
@@ -1,81 +1,1479 @@
 // IMPORTS
 
+use crate::{
+    hash,
+    ignore::{IgnoreRules, MATCH_OPTS},
+};
+use glob::Pattern;
+use log::{debug, info, warn};
 use std::{
-    fs::File,
-    io::Result,
+    env,
+    fs::{self, File, OpenOptions},
+    io::{Error, ErrorKind, Result, Write},
+    os::unix::{self, fs::PermissionsExt},
     path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 #[cfg(test)]
 use stub_trait::stub;
 
+// CONSTS
+
+const ALLOW_DIRNAME: &str = "allow";
+const CACHE_DIRNAME: &str = "cache";
+const DENV_DIRNAME: &str = ".denv";
+const ENVS_DIRNAME: &str = "envs";
+const EXECUTABLE_MODE: u32 = 0o111;
+const HOME_VAR_NAME: &str = "HOME";
+const PASSWD_FILEPATH: &str = "/etc/passwd";
+#[cfg(target_os = "macos")]
+const QUARANTINE_XATTR_NAME: &[u8] = b"com.apple.quarantine\0";
+const SETTINGS_FILENAME: &str = "config.yml";
+const SOFTWARES_DIRNAME: &str = "softwares";
+const STALE_TEMP_DIR_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+const STORE_VERSION_FILENAME: &str = "store_version";
+const TEMP_DIRNAME: &str = "tmp";
+const XDG_CACHE_HOME_DEFAULT_RELPATH: &str = ".cache";
+const XDG_CACHE_HOME_VAR_NAME: &str = "XDG_CACHE_HOME";
+const XDG_CONFIG_HOME_DEFAULT_RELPATH: &str = ".config";
+const XDG_CONFIG_HOME_VAR_NAME: &str = "XDG_CONFIG_HOME";
+const XDG_DATA_HOME_DEFAULT_RELPATH: &str = ".local/share";
+const XDG_DATA_HOME_VAR_NAME: &str = "XDG_DATA_HOME";
+const XDG_DIRNAME: &str = "denv";
+
+// ENUMS
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SymlinkKind {
+    Dir,
+    File,
+}
+
 // DATA STRUCTS
 
+#[derive(Debug)]
 pub struct TempFile {
     pub file: File,
     pub path: PathBuf,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CachedFile {
+    pub modified_at: u64,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
 // TRAITS
 
 #[cfg_attr(test, stub)]
-pub trait FileSystem {
+pub trait FileSystem: Send + Sync {
+    fn append(&self, path: &Path, content: &str) -> Result<()>;
+
+    fn cache_dirpath(&self) -> Result<PathBuf>;
+
+    fn canonicalize(&self, path: &Path) -> Result<PathBuf>;
+
+    fn create_file(&self, path: &Path) -> Result<File>;
+
+    fn create_new(&self, path: &Path, content: &str) -> Result<()>;
+
     fn create_temp_file(&self) -> Result<TempFile>;
 
     fn cwd(&self) -> Result<PathBuf>;
 
     fn delete_env_dir(&self, project_dirpath: &Path) -> Result<()>;
 
+    fn delete_file(&self, path: &Path) -> Result<()>;
+
+    fn delete_software_dir(&self, name: &str, version: &str) -> Result<()>;
+
+    fn ensure_allow_dir(&self) -> Result<PathBuf>;
+
+    fn ensure_cache_dir(&self, name: &str) -> Result<PathBuf>;
+
     fn ensure_env_dir(&self, project_dirpath: &Path) -> Result<PathBuf>;
 
     fn ensure_software_dir(&self, name: &str, version: &str) -> Result<PathBuf>;
 
-    fn ensure_symlink(&self, src: &Path, dest: &Path) -> Result<()>;
+    fn ensure_symlink(&self, src: &Path, dest: &Path, kind: SymlinkKind) -> Result<()>;
 
     fn file_exists(&self, path: &Path) -> bool;
 
+    fn glob_matches(&self, dirpath: &Path, pattern: &str) -> bool;
+
     fn home_dirpath(&self) -> Result<PathBuf>;
 
+    fn list_cached_files(&self) -> Result<Vec<CachedFile>>;
+
+    fn list_files(&self, dirpath: &Path) -> Result<Vec<PathBuf>>;
+
     fn make_executable(&self, path: &Path) -> Result<()>;
+
+    fn migrate_legacy_layout(&self) -> Result<()>;
+
+    fn quarantined(&self, path: &Path) -> bool;
+
+    fn read_to_string(&self, path: &Path) -> Result<String>;
+
+    fn remove_quarantine(&self, path: &Path) -> Result<()>;
+
+    fn set_store_version(&self, version: u32) -> Result<()>;
+
+    fn settings_filepath(&self) -> Result<PathBuf>;
+
+    fn software_dirpath(&self, name: &str, version: &str) -> Result<PathBuf>;
+
+    fn store_version(&self) -> Result<Option<u32>>;
+
+    fn write(&self, path: &Path, content: &str) -> Result<()>;
 }
 
 // STRUCTS
 
-pub struct DefaultFileSystem;
+// Each process gets its own temporary directory, deleted on drop: concurrent processes never
+// share temporary files and directories left by crashed processes are deleted after a day.
+pub struct DefaultFileSystem {
+    denv_dirpath: Option<PathBuf>,
+    temp_dirpath: OnceLock<PathBuf>,
+    temp_file_count: AtomicU64,
+}
+
+impl DefaultFileSystem {
+    pub fn new(denv_dirpath: Option<PathBuf>) -> Self {
+        Self {
+            denv_dirpath,
+            temp_dirpath: OnceLock::new(),
+            temp_file_count: AtomicU64::new(0),
+        }
+    }
+
+    #[inline]
+    fn dirpath(
+        &self,
+        legacy_dirname: &str,
+        xdg_var_name: &str,
+        xdg_default_relpath: &str,
+        xdg_dirname: Option<&str>,
+    ) -> Result<PathBuf> {
+        match &self.denv_dirpath {
+            Some(denv_dirpath) => Ok(denv_dirpath.join(legacy_dirname)),
+            None => {
+                let home_dirpath = self.home_dirpath()?;
+                let xdg_var_value = env::var_os(xdg_var_name).map(PathBuf::from);
+                let mut dirpath = xdg_dirpath(&home_dirpath, xdg_var_value, xdg_default_relpath);
+                if let Some(xdg_dirname) = xdg_dirname {
+                    dirpath = dirpath.join(xdg_dirname);
+                }
+                let legacy_dirpath = home_dirpath.join(DENV_DIRNAME).join(legacy_dirname);
+                migrate_legacy_dir(&legacy_dirpath, &dirpath);
+                Ok(dirpath)
+            }
+        }
+    }
+
+    #[inline]
+    fn data_dirpath(&self) -> Result<PathBuf> {
+        match &self.denv_dirpath {
+            Some(denv_dirpath) => Ok(denv_dirpath.clone()),
+            None => {
+                let home_dirpath = self.home_dirpath()?;
+                let xdg_var_value = env::var_os(XDG_DATA_HOME_VAR_NAME).map(PathBuf::from);
+                Ok(xdg_dirpath(
+                    &home_dirpath,
+                    xdg_var_value,
+                    XDG_DATA_HOME_DEFAULT_RELPATH,
+                ))
+            }
+        }
+    }
+
+    // Environments are keyed by the hash of the project path: projects with the same name never
+    // share their environment.
+    #[inline]
+    fn env_dirpath(&self, project_dirpath: &Path) -> Result<PathBuf> {
+        let envs_dirpath = self.dirpath(
+            ENVS_DIRNAME,
+            XDG_DATA_HOME_VAR_NAME,
+            XDG_DATA_HOME_DEFAULT_RELPATH,
+            Some(ENVS_DIRNAME),
+        )?;
+        let hash = hash::sha256(project_dirpath.as_os_str().as_encoded_bytes());
+        Ok(envs_dirpath.join(hash))
+    }
+
+    #[inline]
+    fn ensure_temp_dir(&self) -> Result<&Path> {
+        if let Some(temp_dirpath) = self.temp_dirpath.get() {
+            return Ok(temp_dirpath);
+        }
+        let root_dirpath = match &self.denv_dirpath {
+            Some(denv_dirpath) => denv_dirpath.join(TEMP_DIRNAME),
+            None => env::temp_dir().join(XDG_DIRNAME),
+        };
+        fs::create_dir_all(&root_dirpath)?;
+        delete_stale_temp_dirs(&root_dirpath);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.subsec_nanos())
+            .unwrap_or_default();
+        let mut suffix = 0;
+        let temp_dirpath = loop {
+            let dirname = format!("{}-{}-{}", process::id(), nanos, suffix);
+            let temp_dirpath = root_dirpath.join(dirname);
+            match fs::create_dir(&temp_dirpath) {
+                Ok(()) => break temp_dirpath,
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => suffix += 1,
+                Err(err) => return Err(err),
+            }
+        };
+        debug!("Temporary directory {} created", temp_dirpath.display());
+        let dirpath = self.temp_dirpath.get_or_init(|| temp_dirpath.clone());
+        if *dirpath != temp_dirpath {
+            fs::remove_dir(&temp_dirpath).ok();
+        }
+        Ok(dirpath)
+    }
+
+    #[inline]
+    fn softwares_dirpath(&self) -> Result<PathBuf> {
+        self.dirpath(
+            SOFTWARES_DIRNAME,
+            XDG_DATA_HOME_VAR_NAME,
+            XDG_DATA_HOME_DEFAULT_RELPATH,
+            Some(SOFTWARES_DIRNAME),
+        )
+    }
+}
 
 impl FileSystem for DefaultFileSystem {
+    fn append(&self, path: &Path, content: &str) -> Result<()> {
+        let mut file = OpenOptions::new().append(true).create(true).open(path)?;
+        file.write_all(content.as_bytes())
+    }
+
+    fn cache_dirpath(&self) -> Result<PathBuf> {
+        self.dirpath(
+            CACHE_DIRNAME,
+            XDG_CACHE_HOME_VAR_NAME,
+            XDG_CACHE_HOME_DEFAULT_RELPATH,
+            None,
+        )
+    }
+
+    fn canonicalize(&self, path: &Path) -> Result<PathBuf> {
+        fs::canonicalize(path)
+    }
+
+    fn create_file(&self, path: &Path) -> Result<File> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        File::create(path)
+    }
+
+    fn create_new(&self, path: &Path, content: &str) -> Result<()> {
+        let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+        file.write_all(content.as_bytes())
+    }
+
     fn create_temp_file(&self) -> Result<TempFile> {
-        unimplemented!();
+        let temp_dirpath = self.ensure_temp_dir()?;
+        loop {
+            let count = self.temp_file_count.fetch_add(1, Ordering::SeqCst);
+            let path = temp_dirpath.join(count.to_string());
+            match OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(file) => return Ok(TempFile { file, path }),
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err),
+            }
+        }
     }
 
     fn cwd(&self) -> Result<PathBuf> {
-        unimplemented!();
+        env::current_dir()
+    }
+
+    fn delete_env_dir(&self, project_dirpath: &Path) -> Result<()> {
+        let env_dirpath = self.env_dirpath(project_dirpath)?;
+        debug!("Deleting {}", env_dirpath.display());
+        match fs::remove_dir_all(&env_dirpath) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn delete_file(&self, path: &Path) -> Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn delete_software_dir(&self, name: &str, version: &str) -> Result<()> {
+        let soft_dirpath = self.software_dirpath(name, version)?;
+        debug!("Deleting {}", soft_dirpath.display());
+        fs::remove_dir_all(soft_dirpath)
     }
 
-    fn delete_env_dir(&self, _project_dirpath: &Path) -> Result<()> {
-        unimplemented!();
+    fn ensure_allow_dir(&self) -> Result<PathBuf> {
+        let allow_dirpath = self.data_dirpath()?.join(ALLOW_DIRNAME);
+        fs::create_dir_all(&allow_dirpath)?;
+        Ok(allow_dirpath)
     }
 
-    fn ensure_env_dir(&self, _project_dirpath: &Path) -> Result<PathBuf> {
-        unimplemented!();
+    fn ensure_cache_dir(&self, name: &str) -> Result<PathBuf> {
+        let cache_dirpath = self.cache_dirpath()?.join(name);
+        fs::create_dir_all(&cache_dirpath)?;
+        Ok(cache_dirpath)
     }
 
-    fn ensure_software_dir(&self, _name: &str, _version: &str) -> Result<PathBuf> {
-        unimplemented!();
+    fn ensure_env_dir(&self, project_dirpath: &Path) -> Result<PathBuf> {
+        let env_dirpath = self.env_dirpath(project_dirpath)?;
+        fs::create_dir_all(&env_dirpath)?;
+        Ok(env_dirpath)
     }
 
-    fn ensure_symlink(&self, _src: &Path, _dest: &Path) -> Result<()> {
-        unimplemented!();
+    fn ensure_software_dir(&self, name: &str, version: &str) -> Result<PathBuf> {
+        let soft_dirpath = self.software_dirpath(name, version)?;
+        fs::create_dir_all(&soft_dirpath)?;
+        Ok(soft_dirpath)
     }
 
-    fn file_exists(&self, _path: &Path) -> bool {
-        unimplemented!();
+    // Only symlinks are replaced: an existing file or directory is never deleted.
+    fn ensure_symlink(&self, src: &Path, dest: &Path, kind: SymlinkKind) -> Result<()> {
+        let src_is_dir = fs::metadata(src)?.is_dir();
+        if src_is_dir != (kind == SymlinkKind::Dir) {
+            let expected = match kind {
+                SymlinkKind::Dir => "directory",
+                SymlinkKind::File => "file",
+            };
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} is not a {}", src.display(), expected),
+            ));
+        }
+        match fs::symlink_metadata(dest) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                if fs::read_link(dest)? == src {
+                    return Ok(());
+                }
+                fs::remove_file(dest)?;
+            }
+            Ok(_) => {
+                return Err(Error::new(
+                    ErrorKind::AlreadyExists,
+                    format!("{} already exists and is not a symlink", dest.display()),
+                ))
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                if let Some(parent) = dest.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+            }
+            Err(err) => return Err(err),
+        }
+        debug!("Creating symlink {} -> {}", dest.display(), src.display());
+        unix::fs::symlink(src, dest)
     }
 
+    fn file_exists(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    // Ignored directories are not walked at all, so vendored trees do not slow down the scan.
+    fn glob_matches(&self, dirpath: &Path, pattern: &str) -> bool {
+        let dir_only = pattern.ends_with('/');
+        let pattern = pattern.trim_end_matches('/');
+        let ignore_rules = IgnoreRules::load(dirpath);
+        if ignore_rules.is_empty() {
+            let pattern = dirpath.join(pattern);
+            let pattern = pattern.to_string_lossy();
+            return match glob::glob(&pattern) {
+                Ok(paths) => paths
+                    .filter_map(std::result::Result::ok)
+                    .any(|path| !dir_only || path.is_dir()),
+                Err(err) => {
+                    warn!("{} is not a valid glob pattern: {}", pattern, err);
+                    false
+                }
+            };
+        }
+        match Pattern::new(pattern) {
+            Ok(pattern) => {
+                let max_depth = if pattern.as_str().contains("**") {
+                    usize::MAX
+                } else {
+                    Path::new(pattern.as_str()).components().count()
+                };
+                glob_matches_rec(
+                    dirpath,
+                    Path::new(""),
+                    &pattern,
+                    dir_only,
+                    max_depth,
+                    &ignore_rules,
+                )
+            }
+            Err(err) => {
+                warn!("{} is not a valid glob pattern: {}", pattern, err);
+                false
+            }
+        }
+    }
+
+    // HOME is not always set in containers: the passwd file is then read directly rather than
+    // through getpwuid, which depends on NSS modules with glibc and is not available in static builds.
     fn home_dirpath(&self) -> Result<PathBuf> {
-        unimplemented!();
+        env::var_os(HOME_VAR_NAME)
+            .map(PathBuf::from)
+            .or_else(|| {
+                let passwd = fs::read_to_string(PASSWD_FILEPATH).ok()?;
+                let uid = unsafe { libc::getuid() };
+                passwd_home_dirpath(&passwd, uid)
+            })
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "Unable to find home directory"))
+    }
+
+    fn list_cached_files(&self) -> Result<Vec<CachedFile>> {
+        let mut files = vec![];
+        let mut dirpaths = vec![self.cache_dirpath()?];
+        while let Some(dirpath) = dirpaths.pop() {
+            let entries = match fs::read_dir(&dirpath) {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => return Err(err),
+            };
+            for entry in entries {
+                let entry = entry?;
+                let metadata = entry.metadata()?;
+                if metadata.is_dir() {
+                    dirpaths.push(entry.path());
+                } else {
+                    let modified_at = metadata
+                        .modified()?
+                        .duration_since(UNIX_EPOCH)
+                        .map(|duration| duration.as_secs())
+                        .unwrap_or_default();
+                    files.push(CachedFile {
+                        modified_at,
+                        path: entry.path(),
+                        size: metadata.len(),
+                    });
+                }
+            }
+        }
+        files.sort_by(|file1, file2| file1.path.cmp(&file2.path));
+        Ok(files)
+    }
+
+    fn list_files(&self, dirpath: &Path) -> Result<Vec<PathBuf>> {
+        let entries = match fs::read_dir(dirpath) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err),
+        };
+        let mut paths = vec![];
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_file() {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
+    fn make_executable(&self, path: &Path) -> Result<()> {
+        let mut perms = fs::metadata(path)?.permissions();
+        perms.set_mode(perms.mode() | EXECUTABLE_MODE);
+        fs::set_permissions(path, perms)
+    }
+
+    fn migrate_legacy_layout(&self) -> Result<()> {
+        self.cache_dirpath()?;
+        self.softwares_dirpath()?;
+        Ok(())
+    }
+
+    fn quarantined(&self, path: &Path) -> bool {
+        quarantined(path)
+    }
+
+    fn read_to_string(&self, path: &Path) -> Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn remove_quarantine(&self, path: &Path) -> Result<()> {
+        remove_quarantine(path)
+    }
+
+    fn set_store_version(&self, version: u32) -> Result<()> {
+        let data_dirpath = self.data_dirpath()?;
+        fs::create_dir_all(&data_dirpath)?;
+        fs::write(
+            data_dirpath.join(STORE_VERSION_FILENAME),
+            version.to_string(),
+        )
+    }
+
+    // The settings can override the D-Env home directory, so they are never read from it.
+    fn settings_filepath(&self) -> Result<PathBuf> {
+        let home_dirpath = self.home_dirpath()?;
+        let xdg_var_value = env::var_os(XDG_CONFIG_HOME_VAR_NAME).map(PathBuf::from);
+        let dirpath = xdg_dirpath(
+            &home_dirpath,
+            xdg_var_value,
+            XDG_CONFIG_HOME_DEFAULT_RELPATH,
+        );
+        Ok(dirpath.join(SETTINGS_FILENAME))
+    }
+
+    fn software_dirpath(&self, name: &str, version: &str) -> Result<PathBuf> {
+        Ok(self.softwares_dirpath()?.join(name).join(version))
+    }
+
+    fn store_version(&self) -> Result<Option<u32>> {
+        let path = self.data_dirpath()?.join(STORE_VERSION_FILENAME);
+        let version = match fs::read_to_string(&path) {
+            Ok(version) => version,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        version.trim().parse().map(Some).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("{} is not a valid store version", version.trim()),
+            )
+        })
+    }
+
+    fn write(&self, path: &Path, content: &str) -> Result<()> {
+        fs::write(path, content)
+    }
+}
+
+impl Drop for DefaultFileSystem {
+    fn drop(&mut self) {
+        if let Some(temp_dirpath) = self.temp_dirpath.get() {
+            if let Err(err) = fs::remove_dir_all(temp_dirpath) {
+                warn!(
+                    "Unable to delete temporary directory {}: {}",
+                    temp_dirpath.display(),
+                    err
+                );
+            }
+        }
+    }
+}
+
+// FUNCTIONS
+
+#[inline]
+fn delete_stale_temp_dirs(root_dirpath: &Path) {
+    let entries = match fs::read_dir(root_dirpath) {
+        Ok(entries) => entries,
+        Err(err) => {
+            warn!("Unable to read {}: {}", root_dirpath.display(), err);
+            return;
+        }
+    };
+    for entry in entries.filter_map(std::result::Result::ok) {
+        let stale = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified_at| modified_at.elapsed().ok())
+            .map(|age| age > STALE_TEMP_DIR_AGE)
+            .unwrap_or(false);
+        if stale {
+            let path = entry.path();
+            debug!("Deleting stale temporary directory {}", path.display());
+            if let Err(err) = fs::remove_dir_all(&path) {
+                warn!("Unable to delete {}: {}", path.display(), err);
+            }
+        }
+    }
+}
+
+#[inline]
+fn glob_matches_rec(
+    root_dirpath: &Path,
+    relpath: &Path,
+    pattern: &Pattern,
+    dir_only: bool,
+    max_depth: usize,
+    ignore_rules: &IgnoreRules,
+) -> bool {
+    let entries = match fs::read_dir(root_dirpath.join(relpath)) {
+        Ok(entries) => entries,
+        Err(err) => {
+            debug!("unable to read {}: {}", relpath.display(), err);
+            return false;
+        }
+    };
+    let depth = relpath.components().count() + 1;
+    entries.filter_map(std::result::Result::ok).any(|entry| {
+        let entry_relpath = relpath.join(entry.file_name());
+        let path = entry.path();
+        let is_dir = path.is_dir();
+        if ignore_rules.is_ignored(&entry_relpath, is_dir) {
+            return false;
+        }
+        if (!dir_only || is_dir) && pattern.matches_path_with(&entry_relpath, MATCH_OPTS) {
+            return true;
+        }
+        let is_symlink = entry
+            .file_type()
+            .map(|file_type| file_type.is_symlink())
+            .unwrap_or(true);
+        is_dir
+            && !is_symlink
+            && depth < max_depth
+            && glob_matches_rec(
+                root_dirpath,
+                &entry_relpath,
+                pattern,
+                dir_only,
+                max_depth,
+                ignore_rules,
+            )
+    })
+}
+
+fn migrate_legacy_dir(legacy_dirpath: &Path, dirpath: &Path) {
+    if !legacy_dirpath.is_dir() || dirpath.exists() {
+        return;
+    }
+    info!(
+        "Migrating {} to {}",
+        legacy_dirpath.display(),
+        dirpath.display()
+    );
+    let res = match dirpath.parent() {
+        Some(parent) => fs::create_dir_all(parent),
+        None => Ok(()),
+    }
+    .and_then(|_| fs::rename(legacy_dirpath, dirpath));
+    match res {
+        Ok(()) => {
+            if let Some(legacy_parent) = legacy_dirpath.parent() {
+                fs::remove_dir(legacy_parent).ok();
+            }
+        }
+        Err(err) => warn!(
+            "Unable to migrate {} to {}: {}",
+            legacy_dirpath.display(),
+            dirpath.display(),
+            err
+        ),
+    }
+}
+
+#[cfg(target_os = "macos")]
+#[inline]
+fn quarantined(path: &Path) -> bool {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt, ptr};
+    let path = match CString::new(path.as_os_str().as_bytes()) {
+        Ok(path) => path,
+        Err(_) => return false,
+    };
+    let size = unsafe {
+        libc::getxattr(
+            path.as_ptr(),
+            QUARANTINE_XATTR_NAME.as_ptr() as *const libc::c_char,
+            ptr::null_mut(),
+            0,
+            0,
+            0,
+        )
+    };
+    size >= 0
+}
+
+#[cfg(not(target_os = "macos"))]
+#[inline]
+fn quarantined(_path: &Path) -> bool {
+    false
+}
+
+#[cfg(target_os = "macos")]
+#[inline]
+fn remove_quarantine(path: &Path) -> Result<()> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+    let res = unsafe {
+        libc::removexattr(
+            path.as_ptr(),
+            QUARANTINE_XATTR_NAME.as_ptr() as *const libc::c_char,
+            0,
+        )
+    };
+    if res == 0 {
+        return Ok(());
+    }
+    let err = Error::last_os_error();
+    if err.raw_os_error() == Some(libc::ENOATTR) {
+        Ok(())
+    } else {
+        Err(err)
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+#[inline]
+fn remove_quarantine(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[inline]
+fn passwd_home_dirpath(passwd: &str, uid: u32) -> Option<PathBuf> {
+    let uid = uid.to_string();
+    passwd.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        match fields.as_slice() {
+            [_, _, entry_uid, _, _, home, ..] if *entry_uid == uid && !home.is_empty() => {
+                Some(PathBuf::from(home))
+            }
+            _ => None,
+        }
+    })
+}
+
+#[inline]
+fn xdg_dirpath(home_dirpath: &Path, var_value: Option<PathBuf>, default_relpath: &str) -> PathBuf {
+    var_value
+        .filter(|path| path.is_absolute())
+        .unwrap_or_else(|| home_dirpath.join(default_relpath))
+        .join(XDG_DIRNAME)
+}
+
+// TESTS
+
+#[cfg(test)]
+mod default_file_system_test {
+    use super::*;
+
+    mod append {
+        use super::*;
+
+        #[test]
+        fn should_return_io_err() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let path = temp_dir.path().join("dir/file");
+            let fs = DefaultFileSystem::new(None);
+            fs.append(&path, "content").unwrap_err();
+        }
+
+        #[test]
+        fn should_append_content() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let path = temp_dir.path().join("file");
+            let fs = DefaultFileSystem::new(None);
+            fs.append(&path, "line1\n").unwrap();
+            fs.append(&path, "line2\n").unwrap();
+            assert_eq!(fs::read_to_string(&path).unwrap(), "line1\nline2\n");
+        }
+    }
+
+    mod canonicalize {
+        use super::*;
+
+        #[test]
+        fn should_return_symlink_target() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let dirpath = temp_dir.path().to_path_buf();
+            let target = dirpath.join("target");
+            fs::write(&target, "content").unwrap();
+            let link = dirpath.join("link");
+            unix::fs::symlink(&target, &link).unwrap();
+            let fs = DefaultFileSystem::new(None);
+            assert_eq!(
+                fs.canonicalize(&link).unwrap(),
+                fs::canonicalize(&target).unwrap()
+            );
+        }
+    }
+
+    mod create_file {
+        use super::*;
+
+        #[test]
+        fn should_return_io_err() {
+            let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+            let fs = DefaultFileSystem::new(None);
+            fs.create_file(&path.join("file")).unwrap_err();
+        }
+
+        #[test]
+        fn should_create_file_and_parent_dirs() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let path = temp_dir.path().join("dir/file");
+            let fs = DefaultFileSystem::new(None);
+            let mut file = fs.create_file(&path).unwrap();
+            write!(file, "content").unwrap();
+            assert_eq!(fs::read_to_string(&path).unwrap(), "content");
+        }
+    }
+
+    mod create_temp_file {
+        use super::*;
+        use std::{collections::HashSet, thread};
+
+        #[test]
+        fn should_return_io_err_if_denv_dir_is_file() {
+            let denv_dirpath = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath.to_path_buf()));
+            fs.create_temp_file().unwrap_err();
+        }
+
+        #[test]
+        fn should_create_files_in_dir_of_process() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let denv_dirpath = temp_dir.path().to_path_buf();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath.clone()));
+            let mut temp_file1 = fs.create_temp_file().unwrap();
+            let temp_file2 = fs.create_temp_file().unwrap();
+            write!(temp_file1.file, "content").unwrap();
+            assert_ne!(temp_file1.path, temp_file2.path);
+            assert_eq!(temp_file1.path.parent(), temp_file2.path.parent());
+            let temp_dirpath = temp_file1.path.parent().unwrap().to_path_buf();
+            assert!(temp_dirpath.starts_with(denv_dirpath.join(TEMP_DIRNAME)));
+            assert_eq!(fs::read_to_string(&temp_file1.path).unwrap(), "content");
+            drop(fs);
+            assert!(!temp_dirpath.exists());
+        }
+
+        #[test]
+        fn should_not_collide_between_concurrent_file_systems() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let denv_dirpath = temp_dir.path().to_path_buf();
+            let handles: Vec<_> = (0..8)
+                .map(|_| {
+                    let denv_dirpath = denv_dirpath.clone();
+                    thread::spawn(move || {
+                        let fs = DefaultFileSystem::new(Some(denv_dirpath));
+                        let temp_file = fs.create_temp_file().unwrap();
+                        (fs, temp_file.path)
+                    })
+                })
+                .collect();
+            let results: Vec<_> = handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect();
+            let dirpaths: HashSet<_> = results
+                .iter()
+                .map(|(_, path)| path.parent().unwrap().to_path_buf())
+                .collect();
+            assert_eq!(dirpaths.len(), results.len());
+        }
+
+        #[test]
+        fn should_delete_stale_dirs() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let denv_dirpath = temp_dir.path().to_path_buf();
+            let root_dirpath = denv_dirpath.join(TEMP_DIRNAME);
+            let stale_dirpath = root_dirpath.join("1-0-0");
+            let recent_dirpath = root_dirpath.join("2-0-0");
+            fs::create_dir_all(&stale_dirpath).unwrap();
+            fs::create_dir_all(&recent_dirpath).unwrap();
+            File::open(&stale_dirpath)
+                .unwrap()
+                .set_modified(SystemTime::now() - STALE_TEMP_DIR_AGE * 2)
+                .unwrap();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath));
+            fs.create_temp_file().unwrap();
+            assert!(!stale_dirpath.exists());
+            assert!(recent_dirpath.exists());
+        }
+    }
+
+    mod create_new {
+        use super::*;
+
+        #[test]
+        fn should_return_already_exists_err() {
+            let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+            let fs = DefaultFileSystem::new(None);
+            let err = fs.create_new(&path, "content").unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        }
+
+        #[test]
+        fn should_create_file() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let path = temp_dir.path().join("file");
+            let fs = DefaultFileSystem::new(None);
+            fs.create_new(&path, "content").unwrap();
+            assert_eq!(fs::read_to_string(&path).unwrap(), "content");
+        }
+    }
+
+    mod cwd {
+        use super::*;
+
+        #[test]
+        fn should_return_current_dir() {
+            let fs = DefaultFileSystem::new(None);
+            assert_eq!(fs.cwd().unwrap(), env::current_dir().unwrap());
+        }
+    }
+
+    mod delete_env_dir {
+        use super::*;
+
+        #[test]
+        fn should_return_ok_if_env_dir_does_not_exist() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let denv_dirpath = temp_dir.path().to_path_buf();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath));
+            fs.delete_env_dir(Path::new("/project")).unwrap();
+        }
+
+        #[test]
+        fn should_delete_dir() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let denv_dirpath = temp_dir.path().to_path_buf();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath));
+            let env_dirpath = fs.ensure_env_dir(Path::new("/project")).unwrap();
+            fs::write(env_dirpath.join("state.json"), "{}").unwrap();
+            fs.delete_env_dir(Path::new("/project")).unwrap();
+            assert!(!env_dirpath.exists());
+        }
+    }
+
+    mod delete_file {
+        use super::*;
+
+        #[test]
+        fn should_return_io_err() {
+            let fs = DefaultFileSystem::new(None);
+            fs.delete_file(Path::new("notfound")).unwrap_err();
+        }
+
+        #[test]
+        fn should_delete_file() {
+            let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+            let fs = DefaultFileSystem::new(None);
+            fs.delete_file(&path).unwrap();
+            assert!(!path.exists());
+        }
+    }
+
+    mod delete_software_dir {
+        use super::*;
+
+        #[test]
+        fn should_return_io_err_if_software_is_not_installed() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let denv_dirpath = temp_dir.path().to_path_buf();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath));
+            fs.delete_software_dir("soft", "1.0.0").unwrap_err();
+        }
+
+        #[test]
+        fn should_delete_dir() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let denv_dirpath = temp_dir.path().to_path_buf();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath));
+            let soft_dirpath = fs.ensure_software_dir("soft", "1.0.0").unwrap();
+            fs::write(soft_dirpath.join("soft"), "").unwrap();
+            fs.delete_software_dir("soft", "1.0.0").unwrap();
+            assert!(!soft_dirpath.exists());
+        }
+    }
+
+    mod glob_matches {
+        use super::*;
+
+        #[test]
+        fn should_return_false() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let dirpath = temp_dir.path().to_path_buf();
+            fs::write(dirpath.join("charts"), "").unwrap();
+            let fs = DefaultFileSystem::new(None);
+            assert!(!fs.glob_matches(&dirpath, "*.tf"));
+            assert!(!fs.glob_matches(&dirpath, "charts/"));
+            assert!(!fs.glob_matches(&dirpath, "[*.tf"));
+        }
+
+        #[test]
+        fn should_return_true() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let dirpath = temp_dir.path().to_path_buf();
+            fs::write(dirpath.join("main.tf"), "").unwrap();
+            fs::create_dir(dirpath.join("charts")).unwrap();
+            let fs = DefaultFileSystem::new(None);
+            assert!(fs.glob_matches(&dirpath, "*.tf"));
+            assert!(fs.glob_matches(&dirpath, "charts/"));
+            assert!(fs.glob_matches(&dirpath, "**/*.tf"));
+        }
+
+        #[test]
+        fn should_return_false_if_path_is_ignored() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let dirpath = temp_dir.path().to_path_buf();
+            fs::create_dir_all(dirpath.join("vendor/module")).unwrap();
+            fs::write(dirpath.join("vendor/module/main.tf"), "").unwrap();
+            fs::write(dirpath.join(".denvignore"), "vendor/\n").unwrap();
+            let fs = DefaultFileSystem::new(None);
+            assert!(!fs.glob_matches(&dirpath, "**/*.tf"));
+            assert!(!fs.glob_matches(&dirpath, "vendor/"));
+        }
+
+        #[test]
+        fn should_return_true_if_path_is_not_ignored() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let dirpath = temp_dir.path().to_path_buf();
+            fs::create_dir_all(dirpath.join("infra/vendor")).unwrap();
+            fs::write(dirpath.join("infra/main.tf"), "").unwrap();
+            fs::write(dirpath.join(".denvignore"), "/vendor\n").unwrap();
+            let fs = DefaultFileSystem::new(None);
+            assert!(fs.glob_matches(&dirpath, "**/*.tf"));
+            assert!(fs.glob_matches(&dirpath, "infra/*.tf"));
+            assert!(fs.glob_matches(&dirpath, "infra/vendor/"));
+            assert!(!fs.glob_matches(&dirpath, "*.tf"));
+        }
+    }
+
+    mod list_cached_files {
+        use super::*;
+
+        #[test]
+        fn should_return_empty_vec_if_cache_dir_does_not_exist() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let denv_dirpath = temp_dir.path().to_path_buf();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath));
+            assert!(fs.list_cached_files().unwrap().is_empty());
+        }
+
+        #[test]
+        fn should_return_files_recursively() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let denv_dirpath = temp_dir.path().to_path_buf();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath.clone()));
+            let github_dirpath = fs.ensure_cache_dir("github").unwrap();
+            fs::write(github_dirpath.join("releases.json"), "[]").unwrap();
+            let files = fs.list_cached_files().unwrap();
+            assert_eq!(files.len(), 1);
+            assert_eq!(files[0].path, github_dirpath.join("releases.json"));
+            assert_eq!(files[0].size, 2);
+            assert!(files[0].modified_at > 0);
+        }
+    }
+
+    mod list_files {
+        use super::*;
+
+        #[test]
+        fn should_return_empty_vec_if_dir_does_not_exist() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let dirpath = temp_dir.path().join("notfound");
+            let fs = DefaultFileSystem::new(None);
+            assert!(fs.list_files(&dirpath).unwrap().is_empty());
+        }
+
+        #[test]
+        fn should_return_sorted_files() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let dirpath = temp_dir.path().to_path_buf();
+            fs::write(dirpath.join("20-b.sh"), "").unwrap();
+            fs::write(dirpath.join("10-a.sh"), "").unwrap();
+            fs::create_dir(dirpath.join("00-dir")).unwrap();
+            let fs = DefaultFileSystem::new(None);
+            let expected = vec![dirpath.join("10-a.sh"), dirpath.join("20-b.sh")];
+            assert_eq!(fs.list_files(&dirpath).unwrap(), expected);
+        }
+    }
+
+    mod make_executable {
+        use super::*;
+
+        #[test]
+        fn should_return_io_err() {
+            let fs = DefaultFileSystem::new(None);
+            fs.make_executable(Path::new("notfound")).unwrap_err();
+        }
+
+        #[test]
+        fn should_set_executable_bits() {
+            let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+            let fs = DefaultFileSystem::new(None);
+            fs.make_executable(&path).unwrap();
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o751);
+        }
+    }
+
+    mod read_to_string {
+        use super::*;
+
+        #[test]
+        fn should_return_io_err() {
+            let fs = DefaultFileSystem::new(None);
+            fs.read_to_string(Path::new("notfound")).unwrap_err();
+        }
+
+        #[test]
+        fn should_return_content() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let path = temp_dir.path().join("file");
+            fs::write(&path, "content").unwrap();
+            let fs = DefaultFileSystem::new(None);
+            assert_eq!(fs.read_to_string(&path).unwrap(), "content");
+        }
+    }
+
+    mod write {
+        use super::*;
+
+        #[test]
+        fn should_return_io_err() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let path = temp_dir.path().join("dir/file");
+            let fs = DefaultFileSystem::new(None);
+            fs.write(&path, "content").unwrap_err();
+        }
+
+        #[test]
+        fn should_write_content() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let path = temp_dir.path().join("file");
+            let fs = DefaultFileSystem::new(None);
+            fs.write(&path, "content").unwrap();
+            assert_eq!(fs::read_to_string(&path).unwrap(), "content");
+        }
+    }
+
+    mod ensure_allow_dir {
+        use super::*;
+
+        #[test]
+        fn should_return_io_err_if_denv_dir_is_file() {
+            let denv_dirpath = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath.to_path_buf()));
+            fs.ensure_allow_dir().unwrap_err();
+        }
+
+        #[test]
+        fn should_return_ok() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let denv_dirpath = temp_dir.path().to_path_buf();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath.clone()));
+            let allow_dirpath = fs.ensure_allow_dir().unwrap();
+            assert_eq!(allow_dirpath, denv_dirpath.join(ALLOW_DIRNAME));
+            assert!(allow_dirpath.is_dir());
+        }
+    }
+
+    mod ensure_cache_dir {
+        use super::*;
+
+        #[test]
+        fn should_return_io_err_if_denv_dir_is_file() {
+            let denv_dirpath = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath.to_path_buf()));
+            fs.ensure_cache_dir("github").unwrap_err();
+        }
+
+        #[test]
+        fn should_return_ok() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let denv_dirpath = temp_dir.path().to_path_buf();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath.clone()));
+            let cache_dirpath = fs.ensure_cache_dir("github").unwrap();
+            assert_eq!(
+                cache_dirpath,
+                denv_dirpath.join(CACHE_DIRNAME).join("github")
+            );
+            assert!(cache_dirpath.is_dir());
+        }
+    }
+
+    mod ensure_env_dir {
+        use super::*;
+
+        #[test]
+        fn should_return_io_err_if_denv_dir_is_file() {
+            let denv_dirpath = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath.to_path_buf()));
+            fs.ensure_env_dir(Path::new("/project")).unwrap_err();
+        }
+
+        #[test]
+        fn should_return_dir_keyed_by_project_path() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let denv_dirpath = temp_dir.path().to_path_buf();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath.clone()));
+            let env_dirpath = fs.ensure_env_dir(Path::new("/project")).unwrap();
+            assert_eq!(
+                env_dirpath,
+                denv_dirpath
+                    .join(ENVS_DIRNAME)
+                    .join(hash::sha256(b"/project"))
+            );
+            assert!(env_dirpath.is_dir());
+            let other_env_dirpath = fs.ensure_env_dir(Path::new("/other/project")).unwrap();
+            assert_ne!(env_dirpath, other_env_dirpath);
+        }
+    }
+
+    mod ensure_symlink {
+        use super::*;
+
+        #[test]
+        fn should_return_not_found_err_if_src_does_not_exist() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let dirpath = temp_dir.path().to_path_buf();
+            let fs = DefaultFileSystem::new(None);
+            let err = fs
+                .ensure_symlink(
+                    &dirpath.join("src"),
+                    &dirpath.join("dest"),
+                    SymlinkKind::File,
+                )
+                .unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::NotFound);
+        }
+
+        #[test]
+        fn should_return_invalid_input_err_if_src_is_not_dir() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let dirpath = temp_dir.path().to_path_buf();
+            fs::write(dirpath.join("src"), "").unwrap();
+            let fs = DefaultFileSystem::new(None);
+            let err = fs
+                .ensure_symlink(
+                    &dirpath.join("src"),
+                    &dirpath.join("dest"),
+                    SymlinkKind::Dir,
+                )
+                .unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        }
+
+        #[test]
+        fn should_return_already_exists_err_if_dest_is_not_symlink() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let dirpath = temp_dir.path().to_path_buf();
+            fs::create_dir(dirpath.join("src")).unwrap();
+            fs::create_dir(dirpath.join("dest")).unwrap();
+            let fs = DefaultFileSystem::new(None);
+            let err = fs
+                .ensure_symlink(
+                    &dirpath.join("src"),
+                    &dirpath.join("dest"),
+                    SymlinkKind::Dir,
+                )
+                .unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        }
+
+        #[test]
+        fn should_create_file_symlink_and_parent_dirs() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let dirpath = temp_dir.path().to_path_buf();
+            let src = dirpath.join("src");
+            let dest = dirpath.join("dir/dest");
+            fs::write(&src, "content").unwrap();
+            let fs = DefaultFileSystem::new(None);
+            fs.ensure_symlink(&src, &dest, SymlinkKind::File).unwrap();
+            assert_eq!(fs::read_link(&dest).unwrap(), src);
+        }
+
+        #[test]
+        fn should_replace_dir_symlink() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let dirpath = temp_dir.path().to_path_buf();
+            let src = dirpath.join("src");
+            let dest = dirpath.join("dest");
+            fs::create_dir_all(src.join("include")).unwrap();
+            fs::create_dir(dirpath.join("old")).unwrap();
+            unix::fs::symlink(dirpath.join("old"), &dest).unwrap();
+            let fs = DefaultFileSystem::new(None);
+            fs.ensure_symlink(&src, &dest, SymlinkKind::Dir).unwrap();
+            assert_eq!(fs::read_link(&dest).unwrap(), src);
+            assert!(dest.join("include").is_dir());
+        }
+    }
+
+    mod ensure_software_dir {
+        use super::*;
+
+        #[test]
+        fn should_return_io_err_if_denv_dir_is_file() {
+            let denv_dirpath = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath.to_path_buf()));
+            fs.ensure_software_dir("soft", "1.0.0").unwrap_err();
+        }
+
+        #[test]
+        fn should_return_ok() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let denv_dirpath = temp_dir.path().to_path_buf();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath.clone()));
+            let soft_dirpath = fs.ensure_software_dir("soft", "1.0.0").unwrap();
+            assert_eq!(
+                soft_dirpath,
+                denv_dirpath.join(SOFTWARES_DIRNAME).join("soft/1.0.0")
+            );
+            assert!(soft_dirpath.is_dir());
+        }
+    }
+
+    mod software_dirpath {
+        use super::*;
+
+        #[test]
+        fn should_return_path_without_creating_dir() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let denv_dirpath = temp_dir.path().to_path_buf();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath.clone()));
+            let soft_dirpath = fs.software_dirpath("soft", "1.0.0").unwrap();
+            assert_eq!(
+                soft_dirpath,
+                denv_dirpath.join(SOFTWARES_DIRNAME).join("soft/1.0.0")
+            );
+            assert!(!soft_dirpath.exists());
+        }
+    }
+
+    mod store_version {
+        use super::*;
+
+        #[test]
+        fn should_return_none_if_file_does_not_exist() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let denv_dirpath = temp_dir.path().to_path_buf();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath));
+            assert!(fs.store_version().unwrap().is_none());
+        }
+
+        #[test]
+        fn should_return_invalid_data_err() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let denv_dirpath = temp_dir.path().to_path_buf();
+            fs::write(denv_dirpath.join(STORE_VERSION_FILENAME), "abc").unwrap();
+            let fs = DefaultFileSystem::new(Some(denv_dirpath));
+            let err = fs.store_version().unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData);
+        }
+
+        #[test]
+        fn should_return_saved_version() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let denv_dirpath = temp_dir.path().join("denv");
+            let fs = DefaultFileSystem::new(Some(denv_dirpath.clone()));
+            fs.set_store_version(1).unwrap();
+            assert_eq!(
+                fs::read_to_string(denv_dirpath.join(STORE_VERSION_FILENAME)).unwrap(),
+                "1"
+            );
+            assert_eq!(fs.store_version().unwrap(), Some(1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod migrate_legacy_dir_test {
+    use super::*;
+
+    #[test]
+    fn should_move_legacy_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let home_dirpath = temp_dir.path().to_path_buf();
+        let legacy_dirpath = home_dirpath.join(DENV_DIRNAME).join(SOFTWARES_DIRNAME);
+        fs::create_dir_all(legacy_dirpath.join("terraform")).unwrap();
+        let dirpath = home_dirpath.join(".local/share/denv/softwares");
+        migrate_legacy_dir(&legacy_dirpath, &dirpath);
+        assert!(dirpath.join("terraform").is_dir());
+        assert!(!home_dirpath.join(DENV_DIRNAME).exists());
+    }
+
+    #[test]
+    fn should_not_override_existing_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let home_dirpath = temp_dir.path().to_path_buf();
+        let legacy_dirpath = home_dirpath.join(DENV_DIRNAME).join(CACHE_DIRNAME);
+        fs::create_dir_all(legacy_dirpath.join("github")).unwrap();
+        let dirpath = home_dirpath.join(".cache/denv");
+        fs::create_dir_all(&dirpath).unwrap();
+        migrate_legacy_dir(&legacy_dirpath, &dirpath);
+        assert!(legacy_dirpath.join("github").is_dir());
+        assert!(!dirpath.join("github").exists());
+    }
+
+    #[test]
+    fn should_do_nothing_if_legacy_dir_does_not_exist() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let home_dirpath = temp_dir.path().to_path_buf();
+        let dirpath = home_dirpath.join(".cache/denv");
+        migrate_legacy_dir(&home_dirpath.join(DENV_DIRNAME), &dirpath);
+        assert!(!dirpath.exists());
+    }
+}
+
+#[cfg(test)]
+mod passwd_home_dirpath_test {
+    use super::*;
+
+    const PASSWD: &str = "root:x:0:0:root:/root:/bin/sh\nnobody:x:65534:65534::/:/sbin/nologin\nuser:x:1000:1000::/home/user:/bin/bash\nnohome:x:1001:1001:::/bin/sh\n";
+
+    #[test]
+    fn should_return_none_if_user_is_missing() {
+        assert!(passwd_home_dirpath(PASSWD, 42).is_none());
+    }
+
+    #[test]
+    fn should_return_none_if_home_is_empty() {
+        assert!(passwd_home_dirpath(PASSWD, 1001).is_none());
+    }
+
+    #[test]
+    fn should_return_home_dirpath() {
+        let dirpath = passwd_home_dirpath(PASSWD, 1000).unwrap();
+        assert_eq!(dirpath, Path::new("/home/user"));
+    }
+}
+
+#[cfg(test)]
+mod xdg_dirpath_test {
+    use super::*;
+
+    #[test]
+    fn should_return_default_dirpath_if_var_is_unset() {
+        let dirpath = xdg_dirpath(Path::new("/home/user"), None, ".cache");
+        assert_eq!(dirpath, Path::new("/home/user/.cache/denv"));
+    }
+
+    #[test]
+    fn should_return_default_dirpath_if_var_is_relative() {
+        let dirpath = xdg_dirpath(
+            Path::new("/home/user"),
+            Some(PathBuf::from("cache")),
+            ".cache",
+        );
+        assert_eq!(dirpath, Path::new("/home/user/.cache/denv"));
     }
 
-    fn make_executable(&self, _path: &Path) -> Result<()> {
-        unimplemented!();
+    #[test]
+    fn should_return_var_dirpath() {
+        let dirpath = xdg_dirpath(
+            Path::new("/home/user"),
+            Some(PathBuf::from("/xdg/cache")),
+            ".cache",
+        );
+        assert_eq!(dirpath, Path::new("/xdg/cache/denv"));
     }
 }
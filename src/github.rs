@@ -0,0 +1,352 @@
+// IMPORTS
+
+use crate::http::{self, Response};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+#[cfg(test)]
+use stub_trait::stub;
+
+// CONSTS
+
+pub const CACHE_DIRNAME: &str = "github";
+
+const API_URL: &str = "https://api.github.com";
+const ETAG_HEADER: &str = "etag";
+const MAX_RETRIES: u32 = 3;
+const MAX_RETRY_AFTER: u64 = 60;
+const RATE_LIMIT_REMAINING_HEADER: &str = "x-ratelimit-remaining";
+const RETRY_AFTER_HEADER: &str = "retry-after";
+const TOKEN_VAR_NAME: &str = "GITHUB_TOKEN";
+
+// TYPES
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+type SleepFn = dyn Fn(Duration);
+
+// ENUMS
+
+#[derive(Debug)]
+pub enum Error {
+    Json(serde_json::Error),
+    RateLimited,
+    Request(http::Error),
+    Status(u16),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "{}", err),
+            Self::RateLimited => write!(
+                f,
+                "GitHub API rate limit exceeded (set {} to raise it)",
+                TOKEN_VAR_NAME
+            ),
+            Self::Request(err) => write!(f, "{}", err),
+            Self::Status(code) => write!(f, "GitHub API sent status code {}", code),
+        }
+    }
+}
+
+// DATA STRUCTS
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Release {
+    pub body: Option<String>,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub prerelease: bool,
+    pub tag_name: String,
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct CachedReleases {
+    etag: String,
+    releases: Vec<Release>,
+}
+
+// TRAITS
+
+#[cfg_attr(test, stub)]
+pub trait GithubClient {
+    fn releases(&self, repo: &str) -> Result<Vec<Release>>;
+}
+
+// STRUCTS
+
+pub struct DefaultGithubClient {
+    api_url: String,
+    cache_dirpath: PathBuf,
+    sleep_fn: Box<SleepFn>,
+    token: Option<String>,
+}
+
+impl DefaultGithubClient {
+    pub fn new(cache_dirpath: PathBuf, token: Option<String>) -> Self {
+        Self {
+            api_url: API_URL.into(),
+            cache_dirpath,
+            sleep_fn: Box::new(thread::sleep),
+            token,
+        }
+    }
+
+    #[inline]
+    fn cache_filepath(&self, repo: &str) -> PathBuf {
+        self.cache_dirpath
+            .join(format!("{}.json", repo.replace('/', "_")))
+    }
+
+    #[inline]
+    fn load_cache(path: &Path) -> Option<CachedReleases> {
+        let json = fs::read_to_string(path).ok()?;
+        match serde_json::from_str(&json) {
+            Ok(cached) => Some(cached),
+            Err(err) => {
+                debug!("Ignoring invalid cache {}: {}", path.display(), err);
+                None
+            }
+        }
+    }
+
+    #[inline]
+    fn save_cache(path: &Path, cached: &CachedReleases) -> io::Result<()> {
+        let json = serde_json::to_string(cached).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+}
+
+impl GithubClient for DefaultGithubClient {
+    fn releases(&self, repo: &str) -> Result<Vec<Release>> {
+        let cache_filepath = self.cache_filepath(repo);
+        let cached = Self::load_cache(&cache_filepath);
+        let url = format!("{}/repos/{}/releases", self.api_url, repo);
+        let mut headers = vec![
+            (
+                "Accept".to_string(),
+                "application/vnd.github+json".to_string(),
+            ),
+            (
+                "User-Agent".into(),
+                concat!("denv/", env!("CARGO_PKG_VERSION")).into(),
+            ),
+        ];
+        if let Some(token) = &self.token {
+            headers.push(("Authorization".into(), format!("Bearer {}", token)));
+        }
+        if let Some(cached) = &cached {
+            headers.push(("If-None-Match".into(), cached.etag.clone()));
+        }
+        let mut retries = 0;
+        loop {
+            debug!("Processing GET request on {}", url);
+            let resp = http::get(&url, &headers).map_err(Error::Request)?;
+            let status = resp.status;
+            debug!("GitHub API sent status code {}", status);
+            if status == 304 {
+                if let Some(cached) = cached {
+                    debug!("Using cached releases of {}", repo);
+                    return Ok(cached.releases);
+                }
+            }
+            if status == 403 || status == 429 {
+                match retry_after(&resp) {
+                    Some(delay) if retries < MAX_RETRIES && delay <= MAX_RETRY_AFTER => {
+                        warn!(
+                            "GitHub API rate limit reached, retrying in {} seconds",
+                            delay
+                        );
+                        (self.sleep_fn)(Duration::from_secs(delay));
+                        retries += 1;
+                        continue;
+                    }
+                    _ if is_rate_limited(&resp) => return Err(Error::RateLimited),
+                    _ => {}
+                }
+            }
+            if !resp.is_success() {
+                return Err(Error::Status(status));
+            }
+            let etag = resp.header(ETAG_HEADER).map(String::from);
+            let releases: Vec<Release> = serde_json::from_reader(resp.body).map_err(Error::Json)?;
+            if let Some(etag) = etag {
+                let cached = CachedReleases { etag, releases };
+                if let Err(err) = Self::save_cache(&cache_filepath, &cached) {
+                    warn!("Unable to write {}: {}", cache_filepath.display(), err);
+                }
+                return Ok(cached.releases);
+            }
+            return Ok(releases);
+        }
+    }
+}
+
+// FUNCTIONS
+
+#[inline]
+fn is_rate_limited(resp: &Response) -> bool {
+    resp.header(RATE_LIMIT_REMAINING_HEADER)
+        .map(|remaining| remaining.trim() == "0")
+        .unwrap_or(resp.header(RETRY_AFTER_HEADER).is_some())
+}
+
+#[inline]
+fn retry_after(resp: &Response) -> Option<u64> {
+    resp.header(RETRY_AFTER_HEADER)
+        .and_then(|value| value.trim().parse().ok())
+}
+
+// TESTS
+
+#[cfg(test)]
+mod error_test {
+    use super::*;
+
+    mod to_string {
+        use super::*;
+
+        mod json {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let err = serde_json::from_str::<Release>("{").unwrap_err();
+                let str = err.to_string();
+                let err = Error::Json(err);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod rate_limited {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "GitHub API rate limit exceeded (set GITHUB_TOKEN to raise it)";
+                let err = Error::RateLimited;
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod status {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "GitHub API sent status code 500";
+                let err = Error::Status(500);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod default_github_client_test {
+    use super::*;
+
+    mod cache {
+        use super::*;
+
+        #[test]
+        fn should_return_none_if_cache_does_not_exist() {
+            let client = DefaultGithubClient::new(tempfile::tempdir().unwrap().into_path(), None);
+            let path = client.cache_filepath("hashicorp/terraform");
+            assert!(DefaultGithubClient::load_cache(&path).is_none());
+        }
+
+        #[test]
+        fn should_return_none_if_cache_is_invalid() {
+            let client = DefaultGithubClient::new(tempfile::tempdir().unwrap().into_path(), None);
+            let path = client.cache_filepath("hashicorp/terraform");
+            fs::write(&path, "{").unwrap();
+            assert!(DefaultGithubClient::load_cache(&path).is_none());
+        }
+
+        #[test]
+        fn should_return_saved_releases() {
+            let cache_dirpath = tempfile::tempdir().unwrap().into_path();
+            let client = DefaultGithubClient::new(cache_dirpath.clone(), None);
+            let path = client.cache_filepath("hashicorp/terraform");
+            assert_eq!(path, cache_dirpath.join("hashicorp_terraform.json"));
+            let cached = CachedReleases {
+                etag: "\"etag\"".into(),
+                releases: vec![Release {
+                    body: Some("changelog".into()),
+                    draft: false,
+                    prerelease: false,
+                    tag_name: "v1.2.3".into(),
+                }],
+            };
+            DefaultGithubClient::save_cache(&path, &cached).unwrap();
+            assert_eq!(DefaultGithubClient::load_cache(&path).unwrap(), cached);
+        }
+    }
+}
+
+#[cfg(test)]
+mod is_rate_limited_test {
+    use super::*;
+
+    #[test]
+    fn should_return_false() {
+        assert!(!is_rate_limited(&response(&[])));
+        assert!(!is_rate_limited(&response(&[(
+            RATE_LIMIT_REMAINING_HEADER,
+            "42"
+        )])));
+    }
+
+    #[test]
+    fn should_return_true() {
+        assert!(is_rate_limited(&response(&[(
+            RATE_LIMIT_REMAINING_HEADER,
+            "0"
+        )])));
+        assert!(is_rate_limited(&response(&[(RETRY_AFTER_HEADER, "120")])));
+    }
+}
+
+#[cfg(test)]
+mod retry_after_test {
+    use super::*;
+
+    #[test]
+    fn should_return_none() {
+        assert!(retry_after(&response(&[])).is_none());
+        assert!(retry_after(&response(&[(
+            RETRY_AFTER_HEADER,
+            "Wed, 21 Oct 2015 07:28:00 GMT"
+        )]))
+        .is_none());
+    }
+
+    #[test]
+    fn should_return_delay() {
+        assert_eq!(
+            retry_after(&response(&[(RETRY_AFTER_HEADER, "30")])),
+            Some(30)
+        );
+    }
+}
+
+#[cfg(test)]
+fn response(headers: &[(&str, &str)]) -> Response {
+    Response {
+        body: Box::new(io::empty()),
+        headers: headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        status: 403,
+    }
+}
@@ -0,0 +1,80 @@
+// IMPORTS
+
+use sha2::{Digest, Sha256};
+use std::io::{Result, Write};
+
+// STRUCTS
+
+pub struct Sha256Writer<W: Write> {
+    hasher: Sha256,
+    out: W,
+}
+
+impl<W: Write> Sha256Writer<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            hasher: Sha256::new(),
+            out,
+        }
+    }
+
+    pub fn digest(self) -> String {
+        hex(&self.hasher.finalize())
+    }
+}
+
+impl<W: Write> Write for Sha256Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let size = self.out.write(buf)?;
+        self.hasher.update(&buf[..size]);
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.out.flush()
+    }
+}
+
+// FUNCTIONS
+
+pub fn sha256(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+#[inline]
+fn hex(digest: &[u8]) -> String {
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// TESTS
+
+#[cfg(test)]
+mod sha256_writer_test {
+    use super::*;
+
+    #[test]
+    fn should_write_and_return_hex_digest() {
+        let mut out = vec![];
+        let mut writer = Sha256Writer::new(&mut out);
+        writer.write_all(b"hel").unwrap();
+        writer.write_all(b"lo").unwrap();
+        assert_eq!(
+            writer.digest(),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(out, b"hello");
+    }
+}
+
+#[cfg(test)]
+mod sha256_test {
+    use super::*;
+
+    #[test]
+    fn should_return_hex_digest() {
+        assert_eq!(
+            sha256(b"hello"),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+    }
+}
@@ -0,0 +1,187 @@
+// IMPORTS
+
+use crate::fs::FileSystem;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    io::{self, Error},
+    path::{Path, PathBuf},
+};
+
+// CONSTS
+
+pub const HISTORY_FILE_SUFFIX: &str = ".history.jsonl";
+
+// ENUMS
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Load,
+    Unload,
+    Upgrade,
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Load => write!(f, "load"),
+            Self::Unload => write!(f, "unload"),
+            Self::Upgrade => write!(f, "upgrade"),
+        }
+    }
+}
+
+// DATA STRUCTS
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HistoryEntry {
+    pub action: Action,
+    pub at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cfg_hash: Option<String>,
+    pub duration_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub softwares: BTreeMap<String, String>,
+}
+
+impl HistoryEntry {
+    // An entry is one line: concurrent shells never interleave their entries.
+    pub fn append(&self, env_dirpath: &Path, fs: &dyn FileSystem) -> io::Result<()> {
+        let json = serde_json::to_string(self).map_err(Error::other)?;
+        fs.append(&filepath(env_dirpath), &format!("{}\n", json))
+    }
+
+    // A line truncated by a crash is skipped: the other entries are still worth reading.
+    pub fn load_all(env_dirpath: &Path, fs: &dyn FileSystem) -> io::Result<Vec<Self>> {
+        let path = filepath(env_dirpath);
+        if !fs.file_exists(&path) {
+            return Ok(vec![]);
+        }
+        let content = fs.read_to_string(&path)?;
+        let entries = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .filter_map(|(idx, line)| match serde_json::from_str(line) {
+                Ok(entry) => Some(entry),
+                Err(err) => {
+                    warn!(
+                        "Invalid entry at line {} of {}: {}",
+                        idx + 1,
+                        path.display(),
+                        err
+                    );
+                    None
+                }
+            })
+            .collect();
+        Ok(entries)
+    }
+}
+
+// FUNCTIONS
+
+// The history is kept next to the environment directory, which is deleted on unload.
+pub fn filepath(env_dirpath: &Path) -> PathBuf {
+    let mut filename = env_dirpath.file_name().unwrap_or_default().to_os_string();
+    filename.push(HISTORY_FILE_SUFFIX);
+    env_dirpath.with_file_name(filename)
+}
+
+// TESTS
+
+#[cfg(test)]
+mod history_entry_test {
+    use super::*;
+    use crate::fs::StubFileSystem;
+
+    const HISTORY_PATH: &str = "/envs/abc.history.jsonl";
+
+    #[inline]
+    fn entry() -> HistoryEntry {
+        HistoryEntry {
+            action: Action::Load,
+            at: 1000,
+            cfg_hash: Some("hash".into()),
+            duration_ms: 42,
+            error: None,
+            softwares: BTreeMap::from([("terraform".into(), "1.5.7".into())]),
+        }
+    }
+
+    mod append {
+        use super::*;
+
+        #[test]
+        fn should_append_line() {
+            let mut fs = StubFileSystem::default();
+            fs.stub_append_fn(|path, content| {
+                assert_eq!(path, Path::new(HISTORY_PATH));
+                assert_eq!(
+                    content,
+                    "{\"action\":\"load\",\"at\":1000,\"cfg_hash\":\"hash\",\"duration_ms\":42,\"softwares\":{\"terraform\":\"1.5.7\"}}\n"
+                );
+                Ok(())
+            });
+            entry().append(Path::new("/envs/abc"), &fs).unwrap();
+        }
+    }
+
+    mod load_all {
+        use super::*;
+
+        #[test]
+        fn should_return_empty_vec() {
+            let mut fs = StubFileSystem::default();
+            fs.stub_file_exists_fn(|path| {
+                assert_eq!(path, Path::new(HISTORY_PATH));
+                false
+            });
+            let entries = HistoryEntry::load_all(Path::new("/envs/abc"), &fs).unwrap();
+            assert!(entries.is_empty());
+        }
+
+        #[test]
+        fn should_return_entries_without_invalid_lines() {
+            let mut fs = StubFileSystem::default();
+            fs.stub_file_exists_fn(|_| true);
+            fs.stub_read_to_string_fn(|path| {
+                assert_eq!(path, Path::new(HISTORY_PATH));
+                let json = serde_json::to_string(&entry()).unwrap();
+                Ok(format!(
+                    "{}\n{{\"action\":\"unload\",\"at\":2000,\"duration_ms\":1,\"error\":\"failed\"}}\n{{\"action\"\n",
+                    json
+                ))
+            });
+            let entries = HistoryEntry::load_all(Path::new("/envs/abc"), &fs).unwrap();
+            let expected = vec![
+                entry(),
+                HistoryEntry {
+                    action: Action::Unload,
+                    at: 2000,
+                    cfg_hash: None,
+                    duration_ms: 1,
+                    error: Some("failed".into()),
+                    softwares: BTreeMap::new(),
+                },
+            ];
+            assert_eq!(entries, expected);
+        }
+    }
+}
+
+#[cfg(test)]
+mod filepath_test {
+    use super::*;
+
+    #[test]
+    fn should_return_sibling_of_env_dir() {
+        let path = filepath(Path::new("/envs/abc"));
+        assert_eq!(path, Path::new("/envs/abc.history.jsonl"));
+    }
+}
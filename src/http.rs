@@ -0,0 +1,320 @@
+// IMPORTS
+
+use std::{
+    fmt::{self, Display, Formatter},
+    io::Read,
+    time::Duration,
+};
+
+// TYPES
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+// ENUMS
+
+#[derive(Debug)]
+pub enum Error {
+    Header(String),
+    #[cfg(feature = "reqwest")]
+    Reqwest(reqwest::Error),
+    #[cfg(all(feature = "ureq", not(feature = "reqwest")))]
+    Ureq(Box<ureq::Transport>),
+    #[cfg(not(any(feature = "reqwest", feature = "ureq")))]
+    Unsupported,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Header(name) => write!(f, "Header {} is not valid", name),
+            #[cfg(feature = "reqwest")]
+            Self::Reqwest(err) => write!(f, "{}", err),
+            #[cfg(all(feature = "ureq", not(feature = "reqwest")))]
+            Self::Ureq(err) => write!(f, "{}", err),
+            #[cfg(not(any(feature = "reqwest", feature = "ureq")))]
+            Self::Unsupported => write!(
+                f,
+                "Downloads are not supported (denv must be built with the ureq or the reqwest feature)"
+            ),
+        }
+    }
+}
+
+// STRUCTS
+
+pub struct Response {
+    pub body: Box<dyn Read + Send>,
+    pub headers: Vec<(String, String)>,
+    pub status: u16,
+}
+
+impl Response {
+    pub fn content_length(&self) -> Option<u64> {
+        self.header("content-length")
+            .and_then(|value| value.trim().parse().ok())
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+// FUNCTIONS
+
+// Status codes are not errors: the caller decides what to do with the response, e.g. retry after
+// the delay sent by a rate-limited API. Header values are never logged.
+#[cfg(feature = "reqwest")]
+pub fn get(url: &str, headers: &[(String, String)]) -> Result<Response> {
+    use reqwest::{
+        blocking::Client,
+        header::{HeaderMap, HeaderName, HeaderValue},
+    };
+
+    let mut header_map = HeaderMap::new();
+    for (name, value) in headers {
+        let header_name =
+            HeaderName::from_bytes(name.as_bytes()).map_err(|_| Error::Header(name.clone()))?;
+        let mut header_value =
+            HeaderValue::from_str(value).map_err(|_| Error::Header(name.clone()))?;
+        header_value.set_sensitive(true);
+        header_map.append(header_name, header_value);
+    }
+    let resp = Client::new()
+        .get(url)
+        .headers(header_map)
+        .send()
+        .map_err(Error::Reqwest)?;
+    Ok(reqwest_response(resp))
+}
+
+// Unlike downloads, a post must not hold the caller: the connection and the whole request are
+// bounded by the given timeout.
+#[cfg(feature = "reqwest")]
+pub fn post(url: &str, content_type: &str, body: &[u8], timeout: Duration) -> Result<Response> {
+    use reqwest::{blocking::Client, header::CONTENT_TYPE};
+
+    let client = Client::builder()
+        .connect_timeout(timeout)
+        .timeout(timeout)
+        .build()
+        .map_err(Error::Reqwest)?;
+    let resp = client
+        .post(url)
+        .header(CONTENT_TYPE, content_type)
+        .body(body.to_vec())
+        .send()
+        .map_err(Error::Reqwest)?;
+    Ok(reqwest_response(resp))
+}
+
+// Status codes are not errors: the caller decides what to do with the response, e.g. retry after
+// the delay sent by a rate-limited API. Header values are never logged.
+#[cfg(all(feature = "ureq", not(feature = "reqwest")))]
+pub fn get(url: &str, headers: &[(String, String)]) -> Result<Response> {
+    use ureq::{AgentBuilder, RedirectAuthHeaders};
+
+    let agent = AgentBuilder::new()
+        .try_proxy_from_env(true)
+        .redirect_auth_headers(RedirectAuthHeaders::SameHost)
+        .build();
+    let mut req = agent.get(url);
+    for (name, value) in headers {
+        if !is_valid_header(name, value) {
+            return Err(Error::Header(name.clone()));
+        }
+        req = req.set(name, value);
+    }
+    ureq_response(req.call())
+}
+
+// Unlike downloads, a post must not hold the caller: the connection, each read and each write are
+// bounded by the given timeout.
+#[cfg(all(feature = "ureq", not(feature = "reqwest")))]
+pub fn post(url: &str, content_type: &str, body: &[u8], timeout: Duration) -> Result<Response> {
+    let agent = ureq::AgentBuilder::new()
+        .try_proxy_from_env(true)
+        .timeout_connect(timeout)
+        .timeout_read(timeout)
+        .timeout_write(timeout)
+        .build();
+    ureq_response(
+        agent
+            .post(url)
+            .set("Content-Type", content_type)
+            .send_bytes(body),
+    )
+}
+
+// Without HTTP client, denv only works with softwares which are already installed.
+#[cfg(not(any(feature = "reqwest", feature = "ureq")))]
+pub fn get(_url: &str, _headers: &[(String, String)]) -> Result<Response> {
+    Err(Error::Unsupported)
+}
+
+#[cfg(not(any(feature = "reqwest", feature = "ureq")))]
+pub fn post(_url: &str, _content_type: &str, _body: &[u8], _timeout: Duration) -> Result<Response> {
+    Err(Error::Unsupported)
+}
+
+#[cfg(all(feature = "ureq", not(feature = "reqwest")))]
+#[inline]
+fn is_valid_header(name: &str, value: &str) -> bool {
+    let name_is_valid = !name.is_empty()
+        && name
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&c));
+    let value_is_valid = value
+        .bytes()
+        .all(|c| c == b'\t' || (c >= 0x20 && c != 0x7f));
+    name_is_valid && value_is_valid
+}
+
+#[cfg(feature = "reqwest")]
+#[inline]
+fn reqwest_response(resp: reqwest::blocking::Response) -> Response {
+    let headers = resp
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .to_str()
+                .ok()
+                .map(|value| (name.as_str().into(), value.into()))
+        })
+        .collect();
+    let status = resp.status().as_u16();
+    Response {
+        body: Box::new(resp),
+        headers,
+        status,
+    }
+}
+
+#[cfg(all(feature = "ureq", not(feature = "reqwest")))]
+#[inline]
+fn ureq_response(res: std::result::Result<ureq::Response, ureq::Error>) -> Result<Response> {
+    let resp = match res {
+        Ok(resp) | Err(ureq::Error::Status(_, resp)) => resp,
+        Err(ureq::Error::Transport(err)) => return Err(Error::Ureq(Box::new(err))),
+    };
+    let headers = resp
+        .headers_names()
+        .into_iter()
+        .filter_map(|name| {
+            let value = resp.header(&name).map(String::from);
+            value.map(|value| (name, value))
+        })
+        .collect();
+    let status = resp.status();
+    Ok(Response {
+        body: Box::new(resp.into_reader()),
+        headers,
+        status,
+    })
+}
+
+// TESTS
+
+#[cfg(test)]
+mod error_test {
+    use super::*;
+
+    mod to_string {
+        use super::*;
+
+        mod header {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Header X Token is not valid";
+                let err = Error::Header("X Token".into());
+                assert_eq!(err.to_string(), str);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod response_test {
+    use super::*;
+    use std::io;
+
+    mod content_length {
+        use super::*;
+
+        #[test]
+        fn should_return_none() {
+            assert!(response(vec![]).content_length().is_none());
+        }
+
+        #[test]
+        fn should_return_length() {
+            let resp = response(vec![("Content-Length".into(), "42".into())]);
+            assert_eq!(resp.content_length(), Some(42));
+        }
+    }
+
+    mod header {
+        use super::*;
+
+        #[test]
+        fn should_return_value_whatever_the_case() {
+            let resp = response(vec![("etag".into(), "\"etag\"".into())]);
+            assert_eq!(resp.header("ETag"), Some("\"etag\""));
+            assert!(resp.header("retry-after").is_none());
+        }
+    }
+
+    #[inline]
+    fn response(headers: Vec<(String, String)>) -> Response {
+        Response {
+            body: Box::new(io::empty()),
+            headers,
+            status: 200,
+        }
+    }
+}
+
+#[cfg(all(test, any(feature = "reqwest", feature = "ureq")))]
+mod post_test {
+    use super::*;
+    use std::{net::TcpListener, thread, time::Instant};
+
+    #[test]
+    fn should_time_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        // The connection is accepted but the server never responds.
+        let handle = thread::spawn(move || listener.accept().map(|(stream, _)| stream));
+        let start = Instant::now();
+        let res = post(&url, "text/plain", b"body", Duration::from_millis(200));
+        assert!(res.is_err());
+        assert!(start.elapsed() < Duration::from_secs(10));
+        drop(handle.join().unwrap());
+    }
+}
+
+#[cfg(all(test, feature = "ureq", not(feature = "reqwest")))]
+mod is_valid_header_test {
+    use super::*;
+
+    #[test]
+    fn should_return_false() {
+        assert!(!is_valid_header("", "value"));
+        assert!(!is_valid_header("X Token", "value"));
+        assert!(!is_valid_header("X-Token", "value\r\nX-Other: value"));
+    }
+
+    #[test]
+    fn should_return_true() {
+        assert!(is_valid_header("X-Token", "Bearer secret"));
+    }
+}
@@ -0,0 +1,145 @@
+// IMPORTS
+
+use serde_json::{Map, Value};
+use std::io::{Error, ErrorKind, Result};
+
+// CONSTS
+
+pub const VSCODE_SETTINGS_FILEPATH: &str = ".vscode/settings.json";
+
+const VSCODE_BIN_SETTINGS: [(&str, &str); 1] =
+    [("terraform", "terraform.languageServer.terraform.path")];
+const VSCODE_TERMINAL_ENV_SETTING_PREFIX: &str = "terminal.integrated.env.";
+
+// DATA STRUCTS
+
+pub struct VscodeEnv<'a> {
+    pub bins: &'a [(String, String)],
+    pub os: &'a str,
+    pub unset_vars: &'a [String],
+    pub vars: &'a [(String, String)],
+}
+
+// FUNCTIONS
+
+// Only the settings managed by D-Env are overwritten: other settings, including other terminal
+// variables, are kept in place.
+pub fn merge_vscode_settings(settings: Option<Value>, env: &VscodeEnv) -> Result<Value> {
+    let mut settings = match settings {
+        Some(Value::Object(settings)) => settings,
+        Some(_) => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "VS Code settings are not a JSON object",
+            ))
+        }
+        None => Map::new(),
+    };
+    let terminal_env_key = format!(
+        "{}{}",
+        VSCODE_TERMINAL_ENV_SETTING_PREFIX,
+        vscode_os(env.os)
+    );
+    let terminal_env = object_entry(&mut settings, terminal_env_key);
+    for name in env.unset_vars {
+        terminal_env.insert(name.clone(), Value::Null);
+    }
+    for (name, value) in env.vars {
+        terminal_env.insert(name.clone(), Value::String(value.clone()));
+    }
+    for (bin_name, setting) in VSCODE_BIN_SETTINGS {
+        if let Some((_, path)) = env.bins.iter().find(|(name, _)| name == bin_name) {
+            settings.insert(setting.into(), Value::String(path.clone()));
+        }
+    }
+    Ok(Value::Object(settings))
+}
+
+#[inline]
+fn object_entry(map: &mut Map<String, Value>, key: String) -> &mut Map<String, Value> {
+    let entry = map.entry(key).or_insert_with(|| Value::Object(Map::new()));
+    if !entry.is_object() {
+        *entry = Value::Object(Map::new());
+    }
+    match entry {
+        Value::Object(obj) => obj,
+        _ => unreachable!(),
+    }
+}
+
+#[inline]
+fn vscode_os(os: &str) -> &str {
+    match os {
+        "macos" => "osx",
+        "windows" => "windows",
+        _ => "linux",
+    }
+}
+
+// TESTS
+
+#[cfg(test)]
+mod merge_vscode_settings_test {
+    use super::*;
+    use serde_json::json;
+
+    fn merge(settings: Option<Value>) -> Result<Value> {
+        let bins = [(
+            "terraform".to_string(),
+            "/softwares/terraform/1.5.0/terraform".to_string(),
+        )];
+        let unset_vars = ["AWS_PROFILE".to_string()];
+        let vars = [
+            ("PATH".to_string(), "/env:${env:PATH}".to_string()),
+            ("VAR".to_string(), "value".to_string()),
+        ];
+        let env = VscodeEnv {
+            bins: &bins,
+            os: "macos",
+            unset_vars: &unset_vars,
+            vars: &vars,
+        };
+        merge_vscode_settings(settings, &env)
+    }
+
+    #[test]
+    fn should_return_err_if_settings_are_not_object() {
+        let err = merge(Some(json!([]))).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn should_return_settings() {
+        let expected = json!({
+            "terminal.integrated.env.osx": {
+                "AWS_PROFILE": null,
+                "PATH": "/env:${env:PATH}",
+                "VAR": "value",
+            },
+            "terraform.languageServer.terraform.path": "/softwares/terraform/1.5.0/terraform",
+        });
+        assert_eq!(merge(None).unwrap(), expected);
+    }
+
+    #[test]
+    fn should_keep_unrelated_settings() {
+        let settings = json!({
+            "editor.tabSize": 2,
+            "terminal.integrated.env.osx": {
+                "EDITOR": "vim",
+                "VAR": "old",
+            },
+        });
+        let expected = json!({
+            "editor.tabSize": 2,
+            "terminal.integrated.env.osx": {
+                "AWS_PROFILE": null,
+                "EDITOR": "vim",
+                "PATH": "/env:${env:PATH}",
+                "VAR": "value",
+            },
+            "terraform.languageServer.terraform.path": "/softwares/terraform/1.5.0/terraform",
+        });
+        assert_eq!(merge(Some(settings)).unwrap(), expected);
+    }
+}
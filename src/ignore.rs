@@ -0,0 +1,129 @@
+// IMPORTS
+
+use glob::{MatchOptions, Pattern};
+use log::warn;
+use std::{fs, path::Path};
+
+// CONSTS
+
+pub const IGNORE_FILENAME: &str = ".denvignore";
+pub const MATCH_OPTS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_leading_dot: false,
+    require_literal_separator: true,
+};
+
+// DATA STRUCTS
+
+#[derive(Clone, Debug)]
+struct Rule {
+    dir_only: bool,
+    pattern: Pattern,
+}
+
+// STRUCTS
+
+// Rules follow the syntax of .gitignore, without negation: a pattern without slash matches at any
+// depth, a leading slash anchors it to the project directory and a trailing slash only matches
+// directories. Everything under an ignored directory is ignored.
+#[derive(Clone, Debug, Default)]
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+impl IgnoreRules {
+    pub fn load(project_dirpath: &Path) -> Self {
+        match fs::read_to_string(project_dirpath.join(IGNORE_FILENAME)) {
+            Ok(content) => Self::parse(&content),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let rules = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let dir_only = line.ends_with('/');
+                let line = line.trim_end_matches('/');
+                let pattern = match line.strip_prefix('/') {
+                    Some(line) => line.to_string(),
+                    None if line.contains('/') => line.to_string(),
+                    None => format!("**/{}", line),
+                };
+                match Pattern::new(&pattern) {
+                    Ok(pattern) => Some(Rule { dir_only, pattern }),
+                    Err(err) => {
+                        warn!(
+                            "{}: {} is not a valid pattern: {}",
+                            IGNORE_FILENAME, line, err
+                        );
+                        None
+                    }
+                }
+            })
+            .collect();
+        Self { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    // The path is relative to the project directory.
+    pub fn is_ignored(&self, relpath: &Path, is_dir: bool) -> bool {
+        self.rules.iter().any(|rule| {
+            (is_dir || !rule.dir_only) && rule.pattern.matches_path_with(relpath, MATCH_OPTS)
+        })
+    }
+}
+
+// TESTS
+
+#[cfg(test)]
+mod ignore_rules_test {
+    use super::*;
+
+    mod is_ignored {
+        use super::*;
+
+        const CONTENT: &str = "# Dependencies\n\nnode_modules/\n/build\nvendor/*.tf\n[\n";
+
+        #[test]
+        fn should_return_false() {
+            let rules = IgnoreRules::parse(CONTENT);
+            assert!(!rules.is_ignored(Path::new("node_modules"), false));
+            assert!(!rules.is_ignored(Path::new("src/build"), true));
+            assert!(!rules.is_ignored(Path::new("vendor/modules/main.tf"), false));
+            assert!(!rules.is_ignored(Path::new("main.tf"), false));
+        }
+
+        #[test]
+        fn should_return_true() {
+            let rules = IgnoreRules::parse(CONTENT);
+            assert!(rules.is_ignored(Path::new("node_modules"), true));
+            assert!(rules.is_ignored(Path::new("web/node_modules"), true));
+            assert!(rules.is_ignored(Path::new("build"), false));
+            assert!(rules.is_ignored(Path::new("vendor/main.tf"), false));
+        }
+    }
+
+    mod load {
+        use super::*;
+
+        #[test]
+        fn should_return_empty_rules_if_file_does_not_exist() {
+            let dirpath = tempfile::tempdir().unwrap().into_path();
+            assert!(IgnoreRules::load(&dirpath).is_empty());
+        }
+
+        #[test]
+        fn should_return_rules() {
+            let dirpath = tempfile::tempdir().unwrap().into_path();
+            fs::write(dirpath.join(IGNORE_FILENAME), "target/\n").unwrap();
+            let rules = IgnoreRules::load(&dirpath);
+            assert!(rules.is_ignored(Path::new("target"), true));
+        }
+    }
+}
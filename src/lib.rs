@@ -0,0 +1,53 @@
+// IMPORTS
+
+use cli::Options;
+use run::{LoadOutcome, Result, Runner};
+use std::path::PathBuf;
+
+// MODS
+
+mod archive;
+pub mod cfg;
+pub mod cli;
+mod doctor;
+mod edit;
+mod fs;
+mod github;
+mod hash;
+mod history;
+mod http;
+mod ide;
+mod ignore;
+mod lock;
+pub mod log;
+mod net;
+mod notify;
+mod proc;
+pub mod release;
+mod render;
+pub mod run;
+mod sbom;
+mod shell;
+mod soft;
+pub mod state;
+mod store;
+mod table;
+#[cfg(test)]
+mod test;
+#[cfg(any(test, feature = "test-support"))]
+pub mod testing;
+mod timing;
+mod trust;
+mod var;
+mod vault;
+mod version;
+
+// STRUCTS
+
+pub struct Denv;
+
+impl Denv {
+    pub fn load(project_dirpath: PathBuf, opts: Options) -> Result<LoadOutcome> {
+        Runner::default().load(Some(project_dirpath), opts)
+    }
+}
@@ -0,0 +1,417 @@
+// IMPORTS
+
+use crate::{fs::FileSystem, soft::receipt::InstalledSoftware};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+// CONSTS
+
+pub const LOCK_FILENAME: &str = "denv.lock";
+
+// TYPES
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+// ENUMS
+
+#[derive(Debug)]
+pub enum Error {
+    ChecksumMismatch {
+        actual: String,
+        expected: String,
+        name: String,
+        version: String,
+    },
+    Io(io::Error),
+    Missing,
+    Outdated(Vec<String>),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ChecksumMismatch {
+                actual,
+                expected,
+                name,
+                version,
+            } => write!(
+                f,
+                "{} v{}: Artifact checksum {} does not match the one of {} ({})",
+                name, version, actual, LOCK_FILENAME, expected
+            ),
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Missing => write!(
+                f,
+                "{} not found (run denv load without --frozen to create it)",
+                LOCK_FILENAME
+            ),
+            Self::Outdated(names) => {
+                write!(f, "{} is out of date: {}", LOCK_FILENAME, names.join(", "))
+            }
+        }
+    }
+}
+
+// DATA STRUCTS
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Lock {
+    pub softwares: Vec<LockedSoftware>,
+}
+
+impl Lock {
+    // Softwares are sorted by name so the file does not change with the configuration order.
+    pub fn new(softs: &[InstalledSoftware]) -> Self {
+        let mut softwares: Vec<LockedSoftware> = softs
+            .iter()
+            .map(|soft| LockedSoftware {
+                name: soft.name.clone(),
+                sha256: soft.receipt.as_ref().map(|receipt| receipt.sha256.clone()),
+                url: soft.receipt.as_ref().map(|receipt| receipt.url.clone()),
+                version: soft.version.clone(),
+            })
+            .collect();
+        softwares.sort_by(|soft1, soft2| soft1.name.cmp(&soft2.name));
+        Self { softwares }
+    }
+
+    pub fn load(project_dirpath: &Path, fs: &dyn FileSystem) -> Result<Option<Self>> {
+        let path = project_dirpath.join(LOCK_FILENAME);
+        if !fs.file_exists(&path) {
+            return Ok(None);
+        }
+        let json = fs.read_to_string(&path).map_err(Error::Io)?;
+        serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|err| Error::Io(io::Error::new(ErrorKind::InvalidData, err)))
+    }
+
+    // Names of the softwares which are not locked with the same version, or which are locked but
+    // not loaded anymore.
+    pub fn outdated(&self, lock: &Self) -> Vec<String> {
+        let mut names: Vec<String> = lock
+            .softwares
+            .iter()
+            .filter(|soft| {
+                self.find(&soft.name).map(|locked| &locked.version) != Some(&soft.version)
+            })
+            .chain(
+                self.softwares
+                    .iter()
+                    .filter(|locked| lock.find(&locked.name).is_none()),
+            )
+            .map(|soft| soft.name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    pub fn save(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(self).map_err(|err| Error::Io(io::Error::other(err)))?;
+        fs.write(&project_dirpath.join(LOCK_FILENAME), &format!("{}\n", json))
+            .map_err(Error::Io)
+    }
+
+    // Checksums of the locked artifacts, by URL.
+    pub fn sha256s(&self) -> Vec<(String, String)> {
+        self.softwares
+            .iter()
+            .filter_map(|soft| Some((soft.url.clone()?, soft.sha256.clone()?)))
+            .collect()
+    }
+
+    // Artifacts downloaded for a locked version must have the locked checksum.
+    pub fn verify(&self, lock: &Self) -> Result<()> {
+        for soft in &lock.softwares {
+            let locked = match self.find(&soft.name) {
+                Some(locked) if locked.version == soft.version => locked,
+                _ => continue,
+            };
+            if let (Some(expected), Some(actual)) = (&locked.sha256, &soft.sha256) {
+                if expected != actual {
+                    return Err(Error::ChecksumMismatch {
+                        actual: actual.clone(),
+                        expected: expected.clone(),
+                        name: soft.name.clone(),
+                        version: soft.version.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn version(&self, name: &str) -> Option<&str> {
+        self.find(name).map(|soft| soft.version.as_str())
+    }
+
+    #[inline]
+    fn find(&self, name: &str) -> Option<&LockedSoftware> {
+        self.softwares.iter().find(|soft| soft.name == name)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LockedSoftware {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub version: String,
+}
+
+// TESTS
+
+#[cfg(test)]
+mod error_test {
+    use super::*;
+
+    mod to_string {
+        use super::*;
+
+        mod checksum_mismatch {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "terraform v1.5.0: Artifact checksum def does not match the one of denv.lock (abc)";
+                let err = Error::ChecksumMismatch {
+                    actual: "def".into(),
+                    expected: "abc".into(),
+                    name: "terraform".into(),
+                    version: "1.5.0".into(),
+                };
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod io {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let err = std::io::Error::from(ErrorKind::PermissionDenied);
+                let str = err.to_string();
+                let err = Error::Io(err);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod missing {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "denv.lock not found (run denv load without --frozen to create it)";
+                assert_eq!(Error::Missing.to_string(), str);
+            }
+        }
+
+        mod outdated {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "denv.lock is out of date: helm, terraform";
+                let err = Error::Outdated(vec!["helm".into(), "terraform".into()]);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod lock_test {
+    use super::*;
+    use crate::{fs::StubFileSystem, soft::receipt::receipt};
+
+    #[inline]
+    fn locked(name: &str, version: &str, sha256: Option<&str>) -> LockedSoftware {
+        LockedSoftware {
+            name: name.into(),
+            sha256: sha256.map(String::from),
+            url: sha256.map(|_| "url".into()),
+            version: version.into(),
+        }
+    }
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_lock_sorted_by_name() {
+            let softs = [
+                InstalledSoftware {
+                    name: "terraform".into(),
+                    receipt: Some(receipt()),
+                    version: "1.5.0".into(),
+                },
+                InstalledSoftware {
+                    name: "helm".into(),
+                    receipt: None,
+                    version: "3.12.0".into(),
+                },
+            ];
+            let expected = Lock {
+                softwares: vec![
+                    locked("helm", "3.12.0", None),
+                    locked("terraform", "1.5.0", Some("sha256")),
+                ],
+            };
+            assert_eq!(Lock::new(&softs), expected);
+        }
+    }
+
+    mod load {
+        use super::*;
+
+        #[test]
+        fn should_return_none() {
+            let mut fs = StubFileSystem::default();
+            fs.stub_file_exists_fn(|path| {
+                assert_eq!(path, Path::new("/project/denv.lock"));
+                false
+            });
+            let lock = Lock::load(Path::new("/project"), &fs).unwrap();
+            assert!(lock.is_none());
+        }
+
+        #[test]
+        fn should_return_io_err_if_json_is_invalid() {
+            let mut fs = StubFileSystem::default();
+            fs.stub_file_exists_fn(|_| true);
+            fs.stub_read_to_string_fn(|_| Ok("{".into()));
+            match Lock::load(Path::new("/project"), &fs).unwrap_err() {
+                Error::Io(err) => assert_eq!(err.kind(), ErrorKind::InvalidData),
+                err => panic!("{}", err),
+            }
+        }
+
+        #[test]
+        fn should_return_lock() {
+            let mut fs = StubFileSystem::default();
+            fs.stub_file_exists_fn(|_| true);
+            fs.stub_read_to_string_fn(|path| {
+                assert_eq!(path, Path::new("/project/denv.lock"));
+                Ok(r#"{"softwares":[{"name":"helm","version":"3.12.0"}]}"#.into())
+            });
+            let lock = Lock::load(Path::new("/project"), &fs).unwrap();
+            let expected = Lock {
+                softwares: vec![locked("helm", "3.12.0", None)],
+            };
+            assert_eq!(lock, Some(expected));
+        }
+    }
+
+    mod outdated {
+        use super::*;
+
+        #[test]
+        fn should_return_empty_vec() {
+            let lock = Lock {
+                softwares: vec![locked("terraform", "1.5.0", Some("abc"))],
+            };
+            let loaded = Lock {
+                softwares: vec![locked("terraform", "1.5.0", None)],
+            };
+            assert!(lock.outdated(&loaded).is_empty());
+        }
+
+        #[test]
+        fn should_return_names() {
+            let lock = Lock {
+                softwares: vec![
+                    locked("helm", "3.12.0", None),
+                    locked("terraform", "1.5.0", None),
+                ],
+            };
+            let loaded = Lock {
+                softwares: vec![
+                    locked("kind", "0.20.0", None),
+                    locked("terraform", "1.6.0", None),
+                ],
+            };
+            assert_eq!(lock.outdated(&loaded), ["helm", "kind", "terraform"]);
+        }
+    }
+
+    mod save {
+        use super::*;
+
+        #[test]
+        fn should_write_lock() {
+            let lock = Lock {
+                softwares: vec![locked("terraform", "1.5.0", Some("abc"))],
+            };
+            let expected = lock.clone();
+            let mut fs = StubFileSystem::default();
+            fs.stub_write_fn(move |path, content| {
+                assert_eq!(path, Path::new("/project/denv.lock"));
+                assert!(content.ends_with('\n'));
+                let saved: Lock = serde_json::from_str(content).unwrap();
+                assert_eq!(saved, expected);
+                Ok(())
+            });
+            lock.save(Path::new("/project"), &fs).unwrap();
+        }
+    }
+
+    mod sha256s {
+        use super::*;
+
+        #[test]
+        fn should_return_checksums_by_url() {
+            let lock = Lock {
+                softwares: vec![
+                    locked("helm", "3.12.0", None),
+                    locked("terraform", "1.5.0", Some("abc")),
+                ],
+            };
+            assert_eq!(lock.sha256s(), [("url".to_string(), "abc".to_string())]);
+        }
+    }
+
+    mod verify {
+        use super::*;
+
+        #[test]
+        fn should_return_checksum_mismatch_err() {
+            let lock = Lock {
+                softwares: vec![locked("terraform", "1.5.0", Some("abc"))],
+            };
+            let loaded = Lock {
+                softwares: vec![locked("terraform", "1.5.0", Some("def"))],
+            };
+            match lock.verify(&loaded).unwrap_err() {
+                Error::ChecksumMismatch {
+                    actual, expected, ..
+                } => {
+                    assert_eq!(actual, "def");
+                    assert_eq!(expected, "abc");
+                }
+                err => panic!("{}", err),
+            }
+        }
+
+        #[test]
+        fn should_ignore_softwares_locked_with_other_version() {
+            let lock = Lock {
+                softwares: vec![locked("terraform", "1.5.0", Some("abc"))],
+            };
+            let loaded = Lock {
+                softwares: vec![
+                    locked("helm", "3.12.0", Some("def")),
+                    locked("terraform", "1.6.0", Some("def")),
+                ],
+            };
+            lock.verify(&loaded).unwrap();
+        }
+    }
+}
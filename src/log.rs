@@ -3,6 +3,7 @@
 use log::{
     set_boxed_logger, set_max_level, Level, LevelFilter, Log, Metadata, Record, SetLoggerError,
 };
+use serde_json::json;
 use std::{
     io::{self, Stderr, Write},
     sync::Mutex,
@@ -14,20 +15,23 @@ const APP_NAME: &str = env!("CARGO_PKG_NAME");
 const DEBUG_COLOR: &str = "\x1b[0;34m";
 const ERROR_COLOR: &str = "\x1b[0;31m";
 const INFO_COLOR: &str = "\x1b[0;32m";
+const REDACTED_TARGET: &str = "ureq::unit";
 const TRACE_COLOR: &str = "\x1b[0;30m";
 const WARN_COLOR: &str = "\x1b[0;33m";
 
 // STRUCTS
 
 pub struct Logger<W: Write + Sync + Send> {
+    json: bool,
     level: LevelFilter,
     out: Mutex<W>,
     with_color: bool,
 }
 
 impl Logger<Stderr> {
-    pub fn init(level: LevelFilter, with_color: bool) -> Result<(), SetLoggerError> {
+    pub fn init(level: LevelFilter, with_color: bool, json: bool) -> Result<(), SetLoggerError> {
         let logger = Self {
+            json,
             level,
             out: Mutex::new(io::stderr()),
             with_color,
@@ -39,15 +43,28 @@ impl Logger<Stderr> {
 }
 
 impl<W: Write + Sync + Send> Log for Logger<W> {
+    // ureq logs requests with their headers, which may contain secrets.
     fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.level() <= self.level
+        metadata.level() <= self.level && metadata.target() != REDACTED_TARGET
     }
 
     fn flush(&self) {}
 
     fn log(&self, record: &Record) {
-        let log = format!("[{}] {}", APP_NAME, record.args());
-        let log = if self.with_color {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let log = if self.json {
+            json!({
+                "level": record.level().as_str().to_lowercase(),
+                "logger": APP_NAME,
+                "message": record.args().to_string(),
+            })
+            .to_string()
+        } else {
+            format!("[{}] {}", APP_NAME, record.args())
+        };
+        let log = if self.with_color && !self.json {
             let color = match record.level() {
                 Level::Trace => TRACE_COLOR,
                 Level::Debug => DEBUG_COLOR,
@@ -90,6 +107,21 @@ mod logger_test {
             );
         }
 
+        #[test]
+        fn should_return_false_if_target_is_redacted() {
+            let logger = Logger {
+                json: false,
+                level: LevelFilter::Trace,
+                out: Mutex::new(vec![]),
+                with_color: true,
+            };
+            let metadata = Metadata::builder()
+                .level(Level::Debug)
+                .target(REDACTED_TARGET)
+                .build();
+            assert!(!logger.enabled(&metadata));
+        }
+
         #[test]
         fn should_return_true() {
             test(
@@ -104,6 +136,7 @@ mod logger_test {
         #[inline]
         fn test<F: Fn(Data, bool)>(data: Data, assert_fn: F) {
             let logger = Logger {
+                json: false,
                 level: data.level,
                 out: Mutex::new(vec![]),
                 with_color: true,
@@ -117,6 +150,7 @@ mod logger_test {
         use super::*;
 
         struct Data {
+            json: bool,
             logs: Vec<(Level, &'static str)>,
             with_color: bool,
         }
@@ -124,6 +158,7 @@ mod logger_test {
         impl Data {
             pub fn new(with_color: bool) -> Self {
                 Self {
+                    json: false,
                     logs: vec![
                         (Level::Trace, "trace"),
                         (Level::Debug, "debug"),
@@ -159,6 +194,32 @@ mod logger_test {
             });
         }
 
+        #[test]
+        fn should_return_json_logs() {
+            let data = Data {
+                json: true,
+                ..Data::new(true)
+            };
+            test(data, |data, res| {
+                let logs: Vec<serde_json::Value> = res
+                    .lines()
+                    .map(|log| serde_json::from_str(log).unwrap())
+                    .collect();
+                let expected: Vec<serde_json::Value> = data
+                    .logs
+                    .iter()
+                    .map(|(level, log)| {
+                        json!({
+                            "level": level.as_str().to_lowercase(),
+                            "logger": APP_NAME,
+                            "message": log,
+                        })
+                    })
+                    .collect();
+                assert_eq!(logs, expected);
+            });
+        }
+
         #[test]
         fn should_return_uncolorized_logs() {
             test(Data::new(false), |data, res| {
@@ -176,6 +237,7 @@ mod logger_test {
         #[inline]
         fn test<F: Fn(Data, String)>(data: Data, assert_fn: F) {
             let logger = Logger {
+                json: data.json,
                 level: LevelFilter::Trace,
                 out: Mutex::new(vec![]),
                 with_color: data.with_color,
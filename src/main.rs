@@ -1,35 +1,43 @@
 // IMPORTS
 
-use ::log::error;
+use ::log::{error, warn};
 use clap::Parser;
-use cli::Cli;
-use run::{Error, Runner};
+use denv::{
+    cfg,
+    cli::{Cli, Command},
+    log::Logger,
+    run::{Error, Runner},
+};
 use std::process;
 
-// MODS
-
-mod archive;
-mod cfg;
-mod cli;
-mod fs;
-mod log;
-mod net;
-mod run;
-mod soft;
-#[cfg(test)]
-mod test;
-mod var;
-
 // FUNTIONS
 
 fn main() {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+    let settings_res = cli.opts.apply_settings();
     let log_level = cli.opts.verbosity.to_log_level();
-    log::Logger::init(log_level, !cli.opts.no_color).unwrap();
+    let json_logs = matches!(cli.cmd, Command::Entrypoint { .. });
+    Logger::init(log_level, !cli.opts.no_color, json_logs).unwrap();
+    if let Err(err) = settings_res {
+        error!("{}", err);
+        process::exit(exitcode::CONFIG);
+    }
     let runner = Runner::default();
     let exit_code = match runner.run(cli.cmd, cli.opts) {
         Ok(()) => 0,
         Err(err) => match &err {
+            Error::Cancelled(pending) | Error::Timeout(_, pending) => {
+                error!("{}", err);
+                for label in pending {
+                    error!("{}: skipped", label);
+                }
+                exitcode::TEMPFAIL
+            }
+            Error::BinaryCollision(_, _) => {
+                error!("{}", err);
+                exitcode::CONFIG
+            }
+            Error::CommandFailed(code) => *code,
             Error::Compute(errs) => {
                 error!("{}", err);
                 for err in errs {
@@ -40,7 +48,7 @@ fn main() {
             Error::Config(err) => {
                 error!("Unable to load configuration");
                 match err {
-                    cfg::Error::Invalid(errs) => {
+                    cfg::Error::DuplicateVars(errs) | cfg::Error::Invalid(errs) => {
                         for err in errs {
                             error!("{}", err);
                         }
@@ -56,6 +64,14 @@ fn main() {
                 }
                 exitcode::SOFTWARE
             }
+            Error::LoadInProgress => {
+                warn!("{}", err);
+                exitcode::TEMPFAIL
+            }
+            Error::Root => {
+                error!("{}", err);
+                exitcode::NOPERM
+            }
             err => {
                 error!("{}", err);
                 exitcode::SOFTWARE
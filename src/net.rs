@@ -1,72 +1,381 @@
 // IMPORTS
 
+use crate::http;
 use log::debug;
-use reqwest::blocking;
-use std::io::{self, BufWriter, Error, ErrorKind, Write};
+use serde::{ser::SerializeMap, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::{
+    env,
+    fmt::{self, Display, Formatter},
+    io::{self, BufWriter, Read, Write},
+    path::PathBuf,
+};
 #[cfg(test)]
 use stub_trait::stub;
+use url::Url;
+
+// CONSTS
+
+const CONTENT_TYPE_HEADER: &str = "content-type";
+const DEFAULT_USER_AGENT: &str = concat!("denv/", env!("CARGO_PKG_VERSION"));
+const HTML_CONTENT_TYPE: &str = "text/html";
+const HTML_SNIFF_LEN: u64 = 512;
 
 // TYPES
 
-pub type Result = io::Result<()>;
+pub type Result = std::result::Result<(), Error>;
+
+// ENUMS
+
+#[derive(Debug)]
+pub enum Error {
+    Header(String),
+    HeaderVar { header: String, var: String },
+    Io(io::Error),
+    Request(http::Error),
+    SizeMismatch { expected: u64, found: u64 },
+    Status(u16),
+    UnexpectedContent(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Header(name) => write!(f, "Header {} is not valid", name),
+            Self::HeaderVar { header, var } => write!(
+                f,
+                "Environment variable {} referenced by header {} is not set",
+                var, header
+            ),
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Request(err) => write!(f, "{}", err),
+            Self::SizeMismatch { expected, found } => write!(
+                f,
+                "Server announced {} bytes but sent {} bytes (check your proxy or mirror configuration)",
+                expected, found
+            ),
+            Self::Status(code) => write!(f, "Server sent status code {}", code),
+            Self::UnexpectedContent(content_type) => write!(
+                f,
+                "Server sent {} content instead of an archive (check your proxy or mirror configuration)",
+                content_type
+            ),
+        }
+    }
+}
+
+// DATA STRUCTS
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DownloadConfig {
+    pub headers: Vec<HeaderDefinition>,
+    // Checksums of the artifacts locked in the project, by URL.
+    pub locked_sha256s: Vec<(String, String)>,
+    pub mirrors: Vec<Mirror>,
+    pub shared_cache_dirpath: Option<PathBuf>,
+    pub user_agent: Option<String>,
+}
+
+impl DownloadConfig {
+    pub fn resolve_url(&self, url: &str) -> String {
+        self.mirrors
+            .iter()
+            .find_map(|mirror| {
+                url.strip_prefix(&mirror.prefix)
+                    .map(|path| format!("{}{}", mirror.url, path))
+            })
+            .unwrap_or_else(|| url.into())
+    }
+}
+
+// The shared cache directory does not come from the configuration file: it is not serialized.
+impl Serialize for DownloadConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        if !self.headers.is_empty() {
+            let mut hosts = Map::new();
+            for header in &self.headers {
+                let headers = hosts
+                    .entry(header.host.clone())
+                    .or_insert_with(|| Value::Object(Map::new()));
+                if let Value::Object(headers) = headers {
+                    headers.insert(header.name.clone(), Value::from(header.value.as_str()));
+                }
+            }
+            map.serialize_entry("headers", &hosts)?;
+        }
+        if !self.mirrors.is_empty() {
+            let mirrors: Map<String, Value> = self
+                .mirrors
+                .iter()
+                .map(|mirror| (mirror.prefix.clone(), Value::from(mirror.url.as_str())))
+                .collect();
+            map.serialize_entry("mirrors", &mirrors)?;
+        }
+        if let Some(user_agent) = &self.user_agent {
+            map.serialize_entry("user_agent", user_agent)?;
+        }
+        map.end()
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HeaderDefinition {
+    pub host: String,
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Mirror {
+    pub prefix: String,
+    pub url: String,
+}
 
 // TRAITS
 
 #[cfg_attr(test, stub)]
-pub trait Downloader {
+pub trait Downloader: Send + Sync {
     fn download(&self, url: &str, out: &mut dyn Write) -> Result;
 }
 
 // STRUCTS
 
-pub struct DefaultDownloader;
+pub struct DefaultDownloader {
+    cfg: DownloadConfig,
+}
+
+impl DefaultDownloader {
+    pub fn new(cfg: DownloadConfig) -> Self {
+        Self { cfg }
+    }
+}
 
 impl Downloader for DefaultDownloader {
     fn download(&self, url: &str, out: &mut dyn Write) -> Result {
         let mut buf = BufWriter::new(out);
+        let user_agent = self.cfg.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT);
+        let url = self.cfg.resolve_url(url);
         debug!("Processing GET request on {}", url);
-        let mut resp = blocking::get(url).map_err(|err| Error::new(ErrorKind::Other, err))?;
-        let status = resp.status();
-        debug!("Server sent status code {}", status.as_u16());
-        if !status.is_success() {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("Server sent status code {}", status.as_u16()),
+        let mut headers = vec![("User-Agent".to_string(), user_agent.to_string())];
+        let host = Url::parse(&url)
+            .ok()
+            .and_then(|url| url.host_str().map(String::from));
+        let header_defs = self
+            .cfg
+            .headers
+            .iter()
+            .filter(|header_def| host.as_deref() == Some(header_def.host.as_str()));
+        for header_def in header_defs {
+            let value =
+                expand_vars(&header_def.value, |var| env::var(var).ok()).map_err(|var| {
+                    Error::HeaderVar {
+                        header: header_def.name.clone(),
+                        var,
+                    }
+                })?;
+            debug!("Adding header {} (value redacted)", header_def.name);
+            headers.push((header_def.name.clone(), value));
+        }
+        let mut resp = http::get(&url, &headers).map_err(|err| match err {
+            http::Error::Header(name) => Error::Header(name),
+            err => Error::Request(err),
+        })?;
+        debug!("Server sent status code {}", resp.status);
+        if !resp.is_success() {
+            return Err(Error::Status(resp.status));
+        }
+        let content_type = resp.header(CONTENT_TYPE_HEADER).map(String::from);
+        let expected_size = resp.content_length();
+        let mut head = vec![];
+        (&mut resp.body)
+            .take(HTML_SNIFF_LEN)
+            .read_to_end(&mut head)
+            .map_err(Error::Io)?;
+        if is_html(content_type.as_deref(), &head) {
+            return Err(Error::UnexpectedContent(
+                content_type.unwrap_or_else(|| HTML_CONTENT_TYPE.into()),
             ));
         }
-        resp.copy_to(&mut buf)
-            .map_err(|err| Error::new(ErrorKind::Other, err))?;
-        Ok(())
+        buf.write_all(&head).map_err(Error::Io)?;
+        let size = head.len() as u64 + io::copy(&mut resp.body, &mut buf).map_err(Error::Io)?;
+        buf.flush().map_err(Error::Io)?;
+        match expected_size {
+            Some(expected) if expected != size => Err(Error::SizeMismatch {
+                expected,
+                found: size,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+// FUNCTIONS
+
+// Replaces ${VAR} references so secrets can be kept out of the configuration file.
+#[inline]
+fn expand_vars<F: Fn(&str) -> Option<String>>(
+    value: &str,
+    env_var_fn: F,
+) -> std::result::Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+        };
+        let var = &rest[start + 2..end];
+        expanded.push_str(&env_var_fn(var).ok_or_else(|| var.to_string())?);
+        rest = &rest[end + 1..];
     }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+#[inline]
+fn is_html(content_type: Option<&str>, head: &[u8]) -> bool {
+    let content_type_is_html = content_type
+        .map(|content_type| content_type.trim().starts_with(HTML_CONTENT_TYPE))
+        .unwrap_or(false);
+    let head = String::from_utf8_lossy(head).trim_start().to_lowercase();
+    content_type_is_html || head.starts_with("<!doctype html") || head.starts_with("<html")
 }
 
 // TESTS
 
 #[cfg(test)]
+mod error_test {
+    use super::*;
+
+    mod to_string {
+        use super::*;
+
+        mod header {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Header X Token is not valid";
+                let err = Error::Header("X Token".into());
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod header_var {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Environment variable TOKEN referenced by header X-Token is not set";
+                let err = Error::HeaderVar {
+                    header: "X-Token".into(),
+                    var: "TOKEN".into(),
+                };
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod io {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let err = ::std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+                let str = err.to_string();
+                let err = Error::Io(err);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod size_mismatch {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Server announced 10 bytes but sent 5 bytes (check your proxy or mirror configuration)";
+                let err = Error::SizeMismatch {
+                    expected: 10,
+                    found: 5,
+                };
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod status {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Server sent status code 404";
+                let err = Error::Status(404);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod unexpected_content {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Server sent text/html content instead of an archive (check your proxy or mirror configuration)";
+                let err = Error::UnexpectedContent("text/html".into());
+                assert_eq!(err.to_string(), str);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, any(feature = "reqwest", feature = "ureq")))]
 mod default_downloader_test {
     use super::*;
     use crate::test::WriteFailer;
 
     mod download {
         use super::*;
+        use crate::testing::{Fixture, FixtureServer};
+        use std::collections::HashMap;
+
+        const ARCHIVE: &[u8] = &[0x1f, 0x8b, 0x08, 0x00];
 
         #[test]
-        fn should_return_err_if_get_failed() {
-            test("not an url", vec![], |_, res| {
-                let err = res.unwrap_err();
-                assert_eq!(err.kind(), ErrorKind::Other);
-            });
+        fn should_return_request_err_if_get_failed() {
+            let downloader = DefaultDownloader::new(DownloadConfig::default());
+            match downloader.download("not an url", &mut vec![]).unwrap_err() {
+                Error::Request(_) => {}
+                err => panic!("{}", err),
+            }
         }
 
         #[test]
-        fn should_return_err_if_server_sent_error() {
+        fn should_return_status_err_if_server_sent_error() {
             test(
-                "https://fr.archive.ubuntu.com/ubuntu2/",
+                DownloadConfig::default(),
+                "/missing",
                 vec![],
-                |_, res| {
-                    let err = res.unwrap_err();
-                    assert_eq!(err.kind(), ErrorKind::Other);
-                    assert_eq!(err.to_string(), "Server sent status code 404");
+                |_, _, res| match res.unwrap_err() {
+                    Error::Status(404) => {}
+                    err => panic!("{}", err),
+                },
+            );
+        }
+
+        #[test]
+        fn should_return_unexpected_content_err_if_server_sent_html() {
+            test(
+                DownloadConfig::default(),
+                "/index.html",
+                vec![],
+                |out, _, res| {
+                    match res.unwrap_err() {
+                        Error::UnexpectedContent(_) => {}
+                        err => panic!("{}", err),
+                    }
+                    assert!(out.is_empty());
                 },
             );
         }
@@ -74,9 +383,10 @@ mod default_downloader_test {
         #[test]
         fn should_return_err_if_write_on_output_failed() {
             test(
-                "https://fr.archive.ubuntu.com/ubuntu/",
+                DownloadConfig::default(),
+                "/archive.tar.gz",
                 WriteFailer,
-                |_, res| {
+                |_, _, res| {
                     res.unwrap_err();
                 },
             );
@@ -84,20 +394,177 @@ mod default_downloader_test {
 
         #[test]
         fn should_return_ok() {
-            let url = "https://fr.archive.ubuntu.com/ubuntu/";
-            test(url, vec![], |out, res| {
+            test(
+                DownloadConfig::default(),
+                "/archive.tar.gz",
+                vec![],
+                |out, reqs, res| {
+                    res.unwrap();
+                    assert_eq!(out, ARCHIVE);
+                    assert_eq!(reqs[0].headers["user-agent"], DEFAULT_USER_AGENT);
+                },
+            );
+        }
+
+        #[test]
+        fn should_send_configured_headers() {
+            env::set_var("DENV_TEST_DOWNLOAD_TOKEN", "secret");
+            let cfg = DownloadConfig {
+                headers: vec![
+                    HeaderDefinition {
+                        host: "127.0.0.1".into(),
+                        name: "X-Token".into(),
+                        value: "Bearer ${DENV_TEST_DOWNLOAD_TOKEN}".into(),
+                    },
+                    HeaderDefinition {
+                        host: "example.com".into(),
+                        name: "X-Other".into(),
+                        value: "value".into(),
+                    },
+                ],
+                user_agent: Some("custom".into()),
+                ..DownloadConfig::default()
+            };
+            test(cfg, "/archive.tar.gz", vec![], |_, reqs, res| {
                 res.unwrap();
-                let content = blocking::get(url).unwrap().text().unwrap();
-                let out = String::from_utf8(out).unwrap();
-                assert_eq!(out, content);
+                let headers = &reqs[0].headers;
+                assert_eq!(headers["user-agent"], "custom");
+                assert_eq!(headers["x-token"], "Bearer secret");
+                assert!(!headers.contains_key("x-other"));
             });
         }
 
+        #[test]
+        fn should_download_from_mirror() {
+            let cfg = DownloadConfig {
+                mirrors: vec![Mirror {
+                    prefix: "https://releases.example.com/".into(),
+                    url: "<server>/".into(),
+                }],
+                ..DownloadConfig::default()
+            };
+            test(
+                cfg,
+                "https://releases.example.com/archive.tar.gz",
+                vec![],
+                |out, reqs, res| {
+                    res.unwrap();
+                    assert_eq!(out, ARCHIVE);
+                    assert_eq!(reqs[0].path, "/archive.tar.gz");
+                },
+            );
+        }
+
+        // Paths are served by a local server: `<server>` in URLs is replaced by its address.
         #[inline]
-        fn test<W: Write, F: Fn(W, Result)>(url: &str, mut out: W, assert_fn: F) {
-            let downloader = DefaultDownloader;
-            let res = downloader.download(url, &mut out);
-            assert_fn(out, res);
+        fn test<W: Write, F: Fn(W, Vec<crate::testing::Request>, Result)>(
+            mut cfg: DownloadConfig,
+            url: &str,
+            mut out: W,
+            assert_fn: F,
+        ) {
+            let fixtures = HashMap::from([
+                ("/archive.tar.gz".to_string(), Fixture::new(ARCHIVE)),
+                (
+                    "/index.html".to_string(),
+                    Fixture {
+                        content_type: "text/html; charset=utf-8".into(),
+                        ..Fixture::new("<!DOCTYPE html><html></html>")
+                    },
+                ),
+            ]);
+            let server = FixtureServer::start(fixtures).unwrap();
+            let server_url = server.url("");
+            for mirror in &mut cfg.mirrors {
+                mirror.url = mirror.url.replace("<server>", &server_url);
+            }
+            let url = if url.starts_with('/') {
+                server.url(url)
+            } else {
+                url.into()
+            };
+            let downloader = DefaultDownloader::new(cfg);
+            let res = downloader.download(&url, &mut out);
+            assert_fn(out, server.requests(), res);
         }
     }
 }
+
+#[cfg(test)]
+mod download_config_test {
+    use super::*;
+
+    mod resolve_url {
+        use super::*;
+
+        #[test]
+        fn should_return_mirror_url() {
+            let cfg = DownloadConfig {
+                mirrors: vec![Mirror {
+                    prefix: "https://releases.hashicorp.com/".into(),
+                    url: "https://artifactory/hashicorp/".into(),
+                }],
+                ..DownloadConfig::default()
+            };
+            assert_eq!(
+                cfg.resolve_url("https://releases.hashicorp.com/terraform/1.2.3/terraform.zip"),
+                "https://artifactory/hashicorp/terraform/1.2.3/terraform.zip"
+            );
+        }
+
+        #[test]
+        fn should_return_unchanged_url() {
+            let cfg = DownloadConfig::default();
+            assert_eq!(cfg.resolve_url("https://host/file"), "https://host/file");
+        }
+    }
+}
+
+#[cfg(test)]
+mod expand_vars_test {
+    use super::*;
+
+    #[test]
+    fn should_return_err_if_var_is_missing() {
+        let res = expand_vars("Bearer ${TOKEN}", |_| None);
+        assert_eq!(res.unwrap_err(), "TOKEN");
+    }
+
+    #[test]
+    fn should_return_expanded_value() {
+        let res = expand_vars("Bearer ${TOKEN}${SUFFIX}!", |var| match var {
+            "TOKEN" => Some("secret".into()),
+            "SUFFIX" => Some("-1".into()),
+            _ => None,
+        });
+        assert_eq!(res.unwrap(), "Bearer secret-1!");
+    }
+
+    #[test]
+    fn should_return_unchanged_value() {
+        let res = expand_vars("$TOKEN ${unclosed", |_| panic!("unexpected"));
+        assert_eq!(res.unwrap(), "$TOKEN ${unclosed");
+    }
+}
+
+#[cfg(test)]
+mod is_html_test {
+    use super::*;
+
+    #[test]
+    fn should_return_false() {
+        assert!(!is_html(None, &[0x1f, 0x8b, 0x08]));
+        assert!(!is_html(Some("application/gzip"), b"PK"));
+    }
+
+    #[test]
+    fn should_return_true_if_content_type_is_html() {
+        assert!(is_html(Some("text/html; charset=utf-8"), &[0x1f, 0x8b]));
+    }
+
+    #[test]
+    fn should_return_true_if_content_looks_like_html() {
+        assert!(is_html(None, b"\n  <!DOCTYPE html><html></html>"));
+        assert!(is_html(Some("application/octet-stream"), b"<HTML>"));
+    }
+}
@@ -0,0 +1,120 @@
+// IMPORTS
+
+use crate::{
+    cfg::NotifyConfig,
+    http,
+    proc::{DefaultProcessRunner, ProcessRunner},
+};
+use log::{debug, warn};
+use serde_json::json;
+use std::time::Duration;
+
+// CONSTS
+
+const JSON_CONTENT_TYPE: &str = "application/json";
+const NOTIFICATION_TITLE: &str = "D-Env";
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+// FUNCTIONS
+
+// A notification is a courtesy: failures are logged and never fail the load, and an unresponsive
+// webhook holds it for a few seconds at most.
+pub fn send(cfg: &NotifyConfig, msg: &str) {
+    if cfg.desktop {
+        let (program, args) = desktop_command(msg);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        if let Err(err) = DefaultProcessRunner.output(program, &args) {
+            warn!("Unable to show desktop notification: {}", err);
+        }
+    }
+    if let Some(url) = &cfg.webhook {
+        debug!("Posting notification to webhook");
+        match http::post(
+            url,
+            JSON_CONTENT_TYPE,
+            webhook_body(msg).as_bytes(),
+            WEBHOOK_TIMEOUT,
+        ) {
+            Ok(resp) if resp.is_success() => {}
+            Ok(resp) => warn!("Webhook responded with status {}", resp.status),
+            Err(err) => warn!("Unable to call webhook: {}", err),
+        }
+    }
+}
+
+#[inline]
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// notify-send is shipped with libnotify on Linux desktops, osascript with macOS.
+#[inline]
+fn desktop_command(msg: &str) -> (&'static str, Vec<String>) {
+    if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {} with title {}",
+            applescript_string(msg),
+            applescript_string(NOTIFICATION_TITLE)
+        );
+        ("osascript", vec!["-e".into(), script])
+    } else {
+        ("notify-send", vec![NOTIFICATION_TITLE.into(), msg.into()])
+    }
+}
+
+// The text field is understood by Slack and Mattermost incoming webhooks.
+#[inline]
+fn webhook_body(msg: &str) -> String {
+    json!({ "text": msg }).to_string()
+}
+
+// TESTS
+
+#[cfg(test)]
+mod applescript_string_test {
+    use super::*;
+
+    #[test]
+    fn should_return_escaped_str() {
+        let str = applescript_string("say \"hi\" \\o/");
+        assert_eq!(str, "\"say \\\"hi\\\" \\\\o/\"");
+    }
+}
+
+#[cfg(test)]
+mod desktop_command_test {
+    use super::*;
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn should_return_osascript() {
+        let (program, args) = desktop_command("Environment loaded");
+        assert_eq!(program, "osascript");
+        assert_eq!(
+            args,
+            [
+                "-e",
+                "display notification \"Environment loaded\" with title \"D-Env\""
+            ]
+        );
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn should_return_notify_send() {
+        let (program, args) = desktop_command("Environment loaded");
+        assert_eq!(program, "notify-send");
+        assert_eq!(args, ["D-Env", "Environment loaded"]);
+    }
+}
+
+#[cfg(test)]
+mod webhook_body_test {
+    use super::*;
+
+    #[test]
+    fn should_return_json() {
+        let body = webhook_body("Environment \"api\" loaded");
+        assert_eq!(body, "{\"text\":\"Environment \\\"api\\\" loaded\"}");
+    }
+}
@@ -0,0 +1,279 @@
+// IMPORTS
+
+use log::debug;
+use std::{
+    fs::File,
+    io::{Error, ErrorKind, Result},
+    mem,
+    os::unix::process::{CommandExt, ExitStatusExt},
+    path::Path,
+    process::{self, Command, Stdio},
+    ptr,
+};
+#[cfg(test)]
+use stub_trait::stub;
+
+// TRAITS
+
+#[cfg_attr(test, stub)]
+pub trait ProcessRunner: Send + Sync {
+    fn output(&self, program: &str, args: &[&str]) -> Result<String>;
+}
+
+// STRUCTS
+
+pub struct DefaultProcessRunner;
+
+impl ProcessRunner for DefaultProcessRunner {
+    fn output(&self, program: &str, args: &[&str]) -> Result<String> {
+        debug!("Running {} {}", program, args.join(" "));
+        let output = Command::new(program).args(args).output()?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            Err(Error::other(format!(
+                "{} exited with {}",
+                program, output.status
+            )))
+        }
+    }
+}
+
+// FUNCTIONS
+
+// As PID 1, the command would ignore signals it does not handle and never reap orphaned
+// processes: D-Env stays in front of it to forward signals and reap zombies.
+pub fn entrypoint(cmd: &[String], vars: &[(String, String)], unset_vars: &[String]) -> Result<i32> {
+    let mut proc = command(cmd, vars, unset_vars, false)?;
+    if process::id() == 1 {
+        supervise(proc)
+    } else {
+        Err(proc.exec())
+    }
+}
+
+// Like system(3), D-Env ignores interrupts while the command runs: the command decides how to
+// handle them and its status is reported.
+pub fn exec(
+    cmd: &[String],
+    vars: &[(String, String)],
+    unset_vars: &[String],
+    clear_env: bool,
+) -> Result<i32> {
+    let mut proc = command(cmd, vars, unset_vars, clear_env)?;
+    let prev_sigint = unsafe { libc::signal(libc::SIGINT, libc::SIG_IGN) };
+    let prev_sigquit = unsafe { libc::signal(libc::SIGQUIT, libc::SIG_IGN) };
+    unsafe {
+        proc.pre_exec(move || {
+            libc::signal(libc::SIGINT, prev_sigint);
+            libc::signal(libc::SIGQUIT, prev_sigquit);
+            Ok(())
+        });
+    }
+    let status = proc.status();
+    unsafe {
+        libc::signal(libc::SIGINT, prev_sigint);
+        libc::signal(libc::SIGQUIT, prev_sigquit);
+    }
+    Ok(exit_code(status?))
+}
+
+pub fn kill(pid: u32, signal: i32) -> Result<()> {
+    if unsafe { libc::kill(pid as libc::pid_t, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(Error::last_os_error())
+    }
+}
+
+// The process gets its own process group so the interrupts of the shell are not sent to it: it
+// runs until it is explicitly killed.
+pub fn spawn_daemon(program: &str, args: &[String], log_filepath: &Path) -> Result<u32> {
+    debug!("Spawning {} {}", program, args.join(" "));
+    let log_file = File::create(log_filepath)?;
+    let child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(log_file.try_clone()?)
+        .stderr(log_file)
+        .process_group(0)
+        .spawn()?;
+    Ok(child.id())
+}
+
+#[inline]
+fn command(
+    cmd: &[String],
+    vars: &[(String, String)],
+    unset_vars: &[String],
+    clear_env: bool,
+) -> Result<Command> {
+    let (program, args) = cmd
+        .split_first()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "No command to run"))?;
+    debug!("Running {}", cmd.join(" "));
+    let mut proc = Command::new(program);
+    proc.args(args);
+    if clear_env {
+        proc.env_clear();
+    }
+    for var_name in unset_vars {
+        proc.env_remove(var_name);
+    }
+    proc.envs(vars.iter().map(|(name, value)| (name, value)));
+    Ok(proc)
+}
+
+// Shells report a command killed by a signal with the status 128 + signal number.
+#[inline]
+fn exit_code(status: process::ExitStatus) -> i32 {
+    status
+        .code()
+        .or_else(|| status.signal().map(|sig| 128 + sig))
+        .unwrap_or(1)
+}
+
+#[inline]
+fn supervise(mut proc: Command) -> Result<i32> {
+    let mut sigset: libc::sigset_t = unsafe { mem::zeroed() };
+    let mut prev_sigset: libc::sigset_t = unsafe { mem::zeroed() };
+    unsafe {
+        libc::sigfillset(&mut sigset);
+        libc::pthread_sigmask(libc::SIG_SETMASK, &sigset, &mut prev_sigset);
+        proc.pre_exec(move || {
+            libc::pthread_sigmask(libc::SIG_SETMASK, &prev_sigset, ptr::null_mut());
+            Ok(())
+        });
+    }
+    let child_pid = proc.spawn()?.id() as libc::pid_t;
+    loop {
+        let mut sig = 0;
+        unsafe { libc::sigwait(&sigset, &mut sig) };
+        if sig != libc::SIGCHLD {
+            debug!("Forwarding signal {} to process {}", sig, child_pid);
+            unsafe { libc::kill(child_pid, sig) };
+            continue;
+        }
+        loop {
+            let mut status = 0;
+            let pid = unsafe { libc::waitpid(-1, &mut status, libc::WNOHANG) };
+            if pid <= 0 {
+                break;
+            }
+            if pid == child_pid {
+                return Ok(exit_code(process::ExitStatus::from_raw(status)));
+            }
+        }
+    }
+}
+
+// TESTS
+
+#[cfg(test)]
+mod default_process_runner_test {
+    use super::*;
+
+    mod output {
+        use super::*;
+
+        #[test]
+        fn should_return_not_found_err() {
+            test("denv-not-found", &[], |res| {
+                let err = res.unwrap_err();
+                assert_eq!(err.kind(), ErrorKind::NotFound);
+            });
+        }
+
+        #[test]
+        fn should_return_err_if_process_failed() {
+            test("false", &[], |res| {
+                let err = res.unwrap_err();
+                assert_eq!(err.kind(), ErrorKind::Other);
+            });
+        }
+
+        #[test]
+        fn should_return_stdout() {
+            test("echo", &["hello", "world"], |res| {
+                assert_eq!(res.unwrap(), "hello world\n");
+            });
+        }
+
+        #[inline]
+        fn test<F: Fn(Result<String>)>(program: &str, args: &[&str], assert_fn: F) {
+            let runner = DefaultProcessRunner;
+            let res = runner.output(program, args);
+            assert_fn(res);
+        }
+    }
+}
+
+#[cfg(test)]
+mod exec_test {
+    use super::*;
+
+    #[test]
+    fn should_return_invalid_input_err_if_cmd_is_empty() {
+        let err = exec(&[], &[], &[], false).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn should_return_not_found_err() {
+        let err = exec(&["denv-not-found".into()], &[], &[], false).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn should_return_exit_code() {
+        let cmd = ["sh".into(), "-c".into(), "exit 3".into()];
+        assert_eq!(exec(&cmd, &[], &[], false).unwrap(), 3);
+    }
+
+    #[test]
+    fn should_return_signal_status() {
+        let cmd = ["sh".into(), "-c".into(), "kill -TERM $$".into()];
+        assert_eq!(exec(&cmd, &[], &[], false).unwrap(), 128 + libc::SIGTERM);
+    }
+
+    #[test]
+    fn should_pass_vars() {
+        let cmd = ["sh".into(), "-c".into(), "test \"$FOO\" = bar".into()];
+        let vars = [("FOO".into(), "bar".into())];
+        assert_eq!(exec(&cmd, &vars, &[], false).unwrap(), 0);
+    }
+
+    #[test]
+    fn should_unset_vars() {
+        let cmd = ["/bin/sh".into(), "-c".into(), "test -z \"$HOME\"".into()];
+        assert_eq!(exec(&cmd, &[], &["HOME".into()], false).unwrap(), 0);
+    }
+
+    #[test]
+    fn should_clear_env() {
+        let cmd = [
+            "/bin/sh".into(),
+            "-c".into(),
+            "test \"$FOO\" = bar && test -z \"$HOME\"".into(),
+        ];
+        let vars = [("FOO".into(), "bar".into())];
+        assert_eq!(exec(&cmd, &vars, &[], true).unwrap(), 0);
+    }
+}
+
+#[cfg(test)]
+mod exit_code_test {
+    use super::*;
+
+    #[test]
+    fn should_return_code() {
+        let status = process::ExitStatus::from_raw(2 << 8);
+        assert_eq!(exit_code(status), 2);
+    }
+
+    #[test]
+    fn should_return_signal_status() {
+        let status = process::ExitStatus::from_raw(libc::SIGKILL);
+        assert_eq!(exit_code(status), 137);
+    }
+}
@@ -0,0 +1,817 @@
+// IMPORTS
+
+use crate::{
+    fs::FileSystem,
+    github::{self, GithubClient},
+    version::Version,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+#[cfg(test)]
+use stub_trait::stub;
+
+// CONSTS
+
+pub const LATEST_CACHE_DIRNAME: &str = "latest";
+pub const LATEST_VERSION_TTL_SECS: u64 = 86400;
+
+const ADOPTIUM_API_URL: &str = "https://api.adoptium.net/v3/binary";
+const GITHUB_DEFAULT_TAG: &str = "v{version}";
+const HASHICORP_RELEASES_URL: &str = "https://releases.hashicorp.com";
+const HELM_DOWNLOAD_URL: &str = "https://get.helm.sh";
+const HELM_GITHUB_REPO: &str = "helm/helm";
+const KUBECTL_DOWNLOAD_URL: &str = "https://dl.k8s.io/release";
+const KUBERNETES_GITHUB_REPO: &str = "kubernetes/kubernetes";
+const RUST_DIST_URL: &str = "https://static.rust-lang.org/dist";
+const RUST_GITHUB_REPO: &str = "rust-lang/rust";
+const ZIG_ARCH_FIRST_VERSION: &str = "0.14.1";
+const ZIG_DOWNLOAD_URL: &str = "https://ziglang.org/download";
+const ZIG_GITHUB_REPO: &str = "ziglang/zig";
+
+// TYPES
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+// ENUMS
+
+#[derive(Debug)]
+pub enum Error {
+    Github(github::Error),
+    Unsupported,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Github(err) => write!(f, "{}", err),
+            Self::Unsupported => write!(f, "Release index does not support version listing"),
+        }
+    }
+}
+
+// DATA STRUCTS
+
+// Newest release resolved for a software pinned to latest, cached under the cache directory.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LatestVersion {
+    pub resolved_at: u64,
+    pub version: String,
+}
+
+impl LatestVersion {
+    pub fn is_fresh(&self, now: u64) -> bool {
+        now.saturating_sub(self.resolved_at) < LATEST_VERSION_TTL_SECS
+    }
+
+    pub fn load(cache_dirpath: &Path, name: &str, fs: &dyn FileSystem) -> io::Result<Option<Self>> {
+        let path = latest_version_filepath(cache_dirpath, name);
+        if !fs.file_exists(&path) {
+            return Ok(None);
+        }
+        let json = fs.read_to_string(&path)?;
+        serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, cache_dirpath: &Path, name: &str, fs: &dyn FileSystem) -> io::Result<()> {
+        let json = serde_json::to_string(self).map_err(io::Error::other)?;
+        fs.write(&latest_version_filepath(cache_dirpath, name), &json)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReleaseVersion {
+    pub changelog: Option<String>,
+    pub version: Version,
+}
+
+// TRAITS
+
+#[cfg_attr(test, stub)]
+pub trait ReleaseIndex {
+    fn artifact_url(&self, version: &str, os: &str, arch: &str) -> String;
+
+    fn checksums_url(&self, version: &str) -> Option<String>;
+
+    fn list_versions(&self, github: &dyn GithubClient) -> Result<Vec<ReleaseVersion>>;
+}
+
+// STRUCTS
+
+// A major version (e.g. 17) is resolved by the Adoptium API to its latest release. Versions are
+// listed from the GitHub repository of the major version: Temurin tags look like jdk-17.0.8+7.
+pub struct AdoptiumReleases {
+    major: String,
+}
+
+impl AdoptiumReleases {
+    pub fn new(version: &str) -> Self {
+        let major = version
+            .split(['.', 'u', '+'])
+            .next()
+            .unwrap_or(version)
+            .into();
+        Self { major }
+    }
+}
+
+impl ReleaseIndex for AdoptiumReleases {
+    fn artifact_url(&self, version: &str, os: &str, arch: &str) -> String {
+        let release = if version == self.major {
+            format!("latest/{}/ga", self.major)
+        } else if version.contains('u') {
+            format!("version/jdk{}", version)
+        } else {
+            format!("version/jdk-{}", version.replace('+', "%2B"))
+        };
+        format!(
+            "{}/{}/{}/{}/jdk/hotspot/normal/eclipse",
+            ADOPTIUM_API_URL, release, os, arch
+        )
+    }
+
+    fn checksums_url(&self, _version: &str) -> Option<String> {
+        None
+    }
+
+    fn list_versions(&self, github: &dyn GithubClient) -> Result<Vec<ReleaseVersion>> {
+        let repo = format!("adoptium/temurin{}-binaries", self.major);
+        let versions = github_versions(&repo, github)?
+            .into_iter()
+            .map(|version| ReleaseVersion {
+                version: version
+                    .version
+                    .as_str()
+                    .trim_start_matches("jdk-")
+                    .trim_start_matches("jdk")
+                    .into(),
+                ..version
+            })
+            .collect();
+        Ok(versions)
+    }
+}
+
+// Releases are tagged v{version} unless another tag template is given: versions are listed from
+// the tags matching it.
+pub struct GithubReleases {
+    asset: String,
+    checksums_asset: Option<String>,
+    repo: String,
+    tag: String,
+}
+
+impl GithubReleases {
+    pub fn new(repo: &str, asset: &str, checksums_asset: Option<&str>) -> Self {
+        Self {
+            asset: asset.into(),
+            checksums_asset: checksums_asset.map(String::from),
+            repo: repo.into(),
+            tag: GITHUB_DEFAULT_TAG.into(),
+        }
+    }
+
+    pub fn with_tag(self, tag: &str) -> Self {
+        Self {
+            tag: tag.into(),
+            ..self
+        }
+    }
+
+    #[inline]
+    fn asset_url(&self, asset: &str, version: &str, os: &str, arch: &str) -> String {
+        format!(
+            "https://github.com/{}/releases/download/{}/{}",
+            self.repo,
+            render(&self.tag, version, os, arch),
+            render(asset, version, os, arch)
+        )
+    }
+}
+
+impl ReleaseIndex for GithubReleases {
+    fn artifact_url(&self, version: &str, os: &str, arch: &str) -> String {
+        self.asset_url(&self.asset, version, os, arch)
+    }
+
+    fn checksums_url(&self, version: &str) -> Option<String> {
+        self.checksums_asset
+            .as_ref()
+            .map(|asset| self.asset_url(asset, version, "", ""))
+    }
+
+    fn list_versions(&self, github: &dyn GithubClient) -> Result<Vec<ReleaseVersion>> {
+        let versions = github_versions(&self.repo, github)?;
+        let prefix = self.tag.split("{version}").next().unwrap_or_default();
+        let prefix = prefix.trim_start_matches('v');
+        if prefix.is_empty() {
+            return Ok(versions);
+        }
+        let versions = versions
+            .into_iter()
+            .filter_map(|version| {
+                let stripped = version.version.as_str().strip_prefix(prefix)?.to_string();
+                Some(ReleaseVersion {
+                    version: stripped.into(),
+                    ..version
+                })
+            })
+            .collect();
+        Ok(versions)
+    }
+}
+
+// HashiCorp does not publish changelogs on its releases site: versions are listed from the
+// GitHub repository of the product.
+pub struct HashicorpReleases {
+    product: &'static str,
+}
+
+impl HashicorpReleases {
+    pub fn new(product: &'static str) -> Self {
+        Self { product }
+    }
+}
+
+impl ReleaseIndex for HashicorpReleases {
+    fn artifact_url(&self, version: &str, os: &str, arch: &str) -> String {
+        format!(
+            "{}/{}/{}/{}_{}_{}_{}.zip",
+            HASHICORP_RELEASES_URL, self.product, version, self.product, version, os, arch
+        )
+    }
+
+    fn checksums_url(&self, version: &str) -> Option<String> {
+        Some(format!(
+            "{}/{}/{}/{}_{}_SHA256SUMS",
+            HASHICORP_RELEASES_URL, self.product, version, self.product, version
+        ))
+    }
+
+    fn list_versions(&self, github: &dyn GithubClient) -> Result<Vec<ReleaseVersion>> {
+        github_versions(&format!("hashicorp/{}", self.product), github)
+    }
+}
+
+// Helm archives are not attached to GitHub releases: they are downloaded from get.helm.sh, each
+// with its own checksums file (<archive>.sha256sum).
+pub struct HelmReleases;
+
+impl ReleaseIndex for HelmReleases {
+    fn artifact_url(&self, version: &str, os: &str, arch: &str) -> String {
+        format!(
+            "{}/helm-v{}-{}-{}.tar.gz",
+            HELM_DOWNLOAD_URL, version, os, arch
+        )
+    }
+
+    fn checksums_url(&self, _version: &str) -> Option<String> {
+        None
+    }
+
+    fn list_versions(&self, github: &dyn GithubClient) -> Result<Vec<ReleaseVersion>> {
+        github_versions(HELM_GITHUB_REPO, github)
+    }
+}
+
+// Kubernetes binaries are not attached to GitHub releases: they are downloaded from dl.k8s.io,
+// each with its own checksums file (<binary>.sha256).
+pub struct KubectlReleases;
+
+impl ReleaseIndex for KubectlReleases {
+    fn artifact_url(&self, version: &str, os: &str, arch: &str) -> String {
+        format!(
+            "{}/v{}/bin/{}/{}/kubectl",
+            KUBECTL_DOWNLOAD_URL, version, os, arch
+        )
+    }
+
+    fn checksums_url(&self, _version: &str) -> Option<String> {
+        None
+    }
+
+    fn list_versions(&self, github: &dyn GithubClient) -> Result<Vec<ReleaseVersion>> {
+        github_versions(KUBERNETES_GITHUB_REPO, github)
+    }
+}
+
+// Each archive has its own checksums file (<archive>.sha256): there is no checksums file per
+// version.
+pub struct RustReleases;
+
+impl ReleaseIndex for RustReleases {
+    fn artifact_url(&self, version: &str, os: &str, arch: &str) -> String {
+        format!("{}/rust-{}-{}-{}.tar.gz", RUST_DIST_URL, version, arch, os)
+    }
+
+    fn checksums_url(&self, _version: &str) -> Option<String> {
+        None
+    }
+
+    fn list_versions(&self, github: &dyn GithubClient) -> Result<Vec<ReleaseVersion>> {
+        github_versions(RUST_GITHUB_REPO, github)
+    }
+}
+
+pub struct UrlTemplate {
+    artifact: String,
+    checksums: Option<String>,
+}
+
+impl UrlTemplate {
+    pub fn new(artifact: String, checksums: Option<String>) -> Self {
+        Self {
+            artifact,
+            checksums,
+        }
+    }
+}
+
+impl ReleaseIndex for UrlTemplate {
+    fn artifact_url(&self, version: &str, os: &str, arch: &str) -> String {
+        render(&self.artifact, version, os, arch)
+    }
+
+    fn checksums_url(&self, version: &str) -> Option<String> {
+        self.checksums
+            .as_ref()
+            .map(|checksums| render(checksums, version, "", ""))
+    }
+
+    fn list_versions(&self, _github: &dyn GithubClient) -> Result<Vec<ReleaseVersion>> {
+        Err(Error::Unsupported)
+    }
+}
+
+// Since 0.14.1, archives are named after the architecture first (zig-x86_64-linux-0.14.1) instead
+// of the OS first (zig-linux-x86_64-0.13.0). Zig does not publish a checksums file.
+pub struct ZigReleases;
+
+impl ReleaseIndex for ZigReleases {
+    fn artifact_url(&self, version: &str, os: &str, arch: &str) -> String {
+        let platform = if Version::from(version) >= Version::from(ZIG_ARCH_FIRST_VERSION) {
+            format!("{}-{}", arch, os)
+        } else {
+            format!("{}-{}", os, arch)
+        };
+        format!(
+            "{}/{}/zig-{}-{}.tar.xz",
+            ZIG_DOWNLOAD_URL, version, platform, version
+        )
+    }
+
+    fn checksums_url(&self, _version: &str) -> Option<String> {
+        None
+    }
+
+    fn list_versions(&self, github: &dyn GithubClient) -> Result<Vec<ReleaseVersion>> {
+        github_versions(ZIG_GITHUB_REPO, github)
+    }
+}
+
+// FUNCTIONS
+
+#[inline]
+fn latest_version_filepath(cache_dirpath: &Path, name: &str) -> PathBuf {
+    cache_dirpath.join(format!("{}.json", name))
+}
+
+#[inline]
+fn github_versions(repo: &str, github: &dyn GithubClient) -> Result<Vec<ReleaseVersion>> {
+    let releases = github.releases(repo).map_err(Error::Github)?;
+    let versions = releases
+        .into_iter()
+        .filter(|release| !release.draft && !release.prerelease)
+        .map(|release| ReleaseVersion {
+            changelog: release.body,
+            version: release.tag_name.trim_start_matches('v').into(),
+        })
+        .collect();
+    Ok(versions)
+}
+
+#[inline]
+fn render(template: &str, version: &str, os: &str, arch: &str) -> String {
+    template
+        .replace("{version}", version)
+        .replace("{os}", os)
+        .replace("{arch}", arch)
+}
+
+// TESTS
+
+#[cfg(test)]
+mod adoptium_releases_test {
+    use super::*;
+    use crate::github::{Release, StubGithubClient};
+
+    #[test]
+    fn should_return_artifact_url_of_exact_version() {
+        let index = AdoptiumReleases::new("17.0.8+7");
+        assert_eq!(
+            index.artifact_url("17.0.8+7", "linux", "x64"),
+            "https://api.adoptium.net/v3/binary/version/jdk-17.0.8%2B7/linux/x64/jdk/hotspot/normal/eclipse"
+        );
+    }
+
+    #[test]
+    fn should_return_artifact_url_of_java8_version() {
+        let index = AdoptiumReleases::new("8u382-b05");
+        assert_eq!(
+            index.artifact_url("8u382-b05", "mac", "x64"),
+            "https://api.adoptium.net/v3/binary/version/jdk8u382-b05/mac/x64/jdk/hotspot/normal/eclipse"
+        );
+    }
+
+    #[test]
+    fn should_return_artifact_url_of_major_version() {
+        let index = AdoptiumReleases::new("21");
+        assert_eq!(
+            index.artifact_url("21", "linux", "aarch64"),
+            "https://api.adoptium.net/v3/binary/latest/21/ga/linux/aarch64/jdk/hotspot/normal/eclipse"
+        );
+    }
+
+    #[test]
+    fn should_return_versions_without_jdk_prefix() {
+        let mut github = StubGithubClient::default();
+        github.stub_releases_fn(|repo| {
+            assert_eq!(repo, "adoptium/temurin17-binaries");
+            Ok(vec![Release {
+                body: None,
+                draft: false,
+                prerelease: false,
+                tag_name: "jdk-17.0.8+7".into(),
+            }])
+        });
+        let index = AdoptiumReleases::new("17.0.7+7");
+        let expected = vec![ReleaseVersion {
+            changelog: None,
+            version: "17.0.8+7".into(),
+        }];
+        assert_eq!(index.list_versions(&github).unwrap(), expected);
+    }
+}
+
+#[cfg(test)]
+mod error_test {
+    use super::*;
+
+    mod to_string {
+        use super::*;
+
+        mod github {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let err = crate::github::Error::RateLimited;
+                let str = err.to_string();
+                let err = Error::Github(err);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod unsupported {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Release index does not support version listing";
+                let err = Error::Unsupported;
+                assert_eq!(err.to_string(), str);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod github_releases_test {
+    use super::*;
+    use crate::github::{Release, StubGithubClient};
+
+    #[test]
+    fn should_return_artifact_url() {
+        let index = GithubReleases::new("org/soft", "soft_{version}_{os}_{arch}.tar.gz", None);
+        assert_eq!(
+            index.artifact_url("1.0.0", "linux", "amd64"),
+            "https://github.com/org/soft/releases/download/v1.0.0/soft_1.0.0_linux_amd64.tar.gz"
+        );
+    }
+
+    #[test]
+    fn should_return_checksums_url() {
+        let index = GithubReleases::new("org/soft", "soft", Some("soft_{version}_checksums.txt"));
+        assert_eq!(
+            index.checksums_url("1.0.0").unwrap(),
+            "https://github.com/org/soft/releases/download/v1.0.0/soft_1.0.0_checksums.txt"
+        );
+    }
+
+    #[test]
+    fn should_return_stable_versions() {
+        let mut github = StubGithubClient::default();
+        github.stub_releases_fn(|repo| {
+            assert_eq!(repo, "org/soft");
+            let release = |tag_name: &str, draft, prerelease| Release {
+                body: Some(format!("Changelog of {}", tag_name)),
+                draft,
+                prerelease,
+                tag_name: tag_name.into(),
+            };
+            Ok(vec![
+                release("v1.0.0", false, false),
+                release("v1.1.0", true, false),
+                release("v2.0.0-rc1", false, true),
+            ])
+        });
+        let index = GithubReleases::new("org/soft", "soft", None);
+        let versions = index.list_versions(&github).unwrap();
+        let expected = vec![ReleaseVersion {
+            changelog: Some("Changelog of v1.0.0".into()),
+            version: "1.0.0".into(),
+        }];
+        assert_eq!(versions, expected);
+    }
+
+    #[test]
+    fn should_return_artifact_url_with_tag() {
+        let index = GithubReleases::new("org/soft", "soft", None).with_tag("{version}");
+        assert_eq!(
+            index.artifact_url("1.0.0", "linux", "amd64"),
+            "https://github.com/org/soft/releases/download/1.0.0/soft"
+        );
+    }
+
+    #[test]
+    fn should_return_versions_matching_tag() {
+        let mut github = StubGithubClient::default();
+        github.stub_releases_fn(|_| {
+            let release = |tag_name: &str| Release {
+                body: None,
+                draft: false,
+                prerelease: false,
+                tag_name: tag_name.into(),
+            };
+            Ok(vec![release("soft-1.0.0"), release("other-2.0.0")])
+        });
+        let index = GithubReleases::new("org/soft", "soft", None).with_tag("soft-{version}");
+        let versions = index.list_versions(&github).unwrap();
+        let expected = vec![ReleaseVersion {
+            changelog: None,
+            version: "1.0.0".into(),
+        }];
+        assert_eq!(versions, expected);
+    }
+}
+
+#[cfg(test)]
+mod hashicorp_releases_test {
+    use super::*;
+    use crate::github::StubGithubClient;
+
+    #[test]
+    fn should_return_artifact_url() {
+        let index = HashicorpReleases::new("terraform");
+        assert_eq!(
+            index.artifact_url("1.2.3", "linux", "amd64"),
+            "https://releases.hashicorp.com/terraform/1.2.3/terraform_1.2.3_linux_amd64.zip"
+        );
+    }
+
+    #[test]
+    fn should_return_checksums_url() {
+        let index = HashicorpReleases::new("terraform");
+        assert_eq!(
+            index.checksums_url("1.2.3").unwrap(),
+            "https://releases.hashicorp.com/terraform/1.2.3/terraform_1.2.3_SHA256SUMS"
+        );
+    }
+
+    #[test]
+    fn should_list_versions_from_github() {
+        let mut github = StubGithubClient::default();
+        github.stub_releases_fn(|repo| {
+            assert_eq!(repo, "hashicorp/terraform");
+            Ok(vec![])
+        });
+        let index = HashicorpReleases::new("terraform");
+        assert!(index.list_versions(&github).unwrap().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod helm_releases_test {
+    use super::*;
+    use crate::github::StubGithubClient;
+
+    #[test]
+    fn should_return_artifact_url() {
+        assert_eq!(
+            HelmReleases.artifact_url("3.13.1", "linux", "amd64"),
+            "https://get.helm.sh/helm-v3.13.1-linux-amd64.tar.gz"
+        );
+    }
+
+    #[test]
+    fn should_list_versions_from_github() {
+        let mut github = StubGithubClient::default();
+        github.stub_releases_fn(|repo| {
+            assert_eq!(repo, "helm/helm");
+            Ok(vec![])
+        });
+        assert!(HelmReleases.list_versions(&github).unwrap().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod kubectl_releases_test {
+    use super::*;
+    use crate::github::StubGithubClient;
+
+    #[test]
+    fn should_return_artifact_url() {
+        assert_eq!(
+            KubectlReleases.artifact_url("1.28.2", "linux", "amd64"),
+            "https://dl.k8s.io/release/v1.28.2/bin/linux/amd64/kubectl"
+        );
+    }
+
+    #[test]
+    fn should_list_versions_from_github() {
+        let mut github = StubGithubClient::default();
+        github.stub_releases_fn(|repo| {
+            assert_eq!(repo, "kubernetes/kubernetes");
+            Ok(vec![])
+        });
+        assert!(KubectlReleases.list_versions(&github).unwrap().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod latest_version_test {
+    use super::*;
+    use crate::fs::StubFileSystem;
+
+    #[inline]
+    fn latest() -> LatestVersion {
+        LatestVersion {
+            resolved_at: 1000,
+            version: "1.6.0".into(),
+        }
+    }
+
+    mod is_fresh {
+        use super::*;
+
+        #[test]
+        fn should_return_false() {
+            assert!(!latest().is_fresh(1000 + LATEST_VERSION_TTL_SECS));
+        }
+
+        #[test]
+        fn should_return_true() {
+            assert!(latest().is_fresh(1000 + LATEST_VERSION_TTL_SECS - 1));
+        }
+    }
+
+    mod load {
+        use super::*;
+
+        #[test]
+        fn should_return_none() {
+            let mut fs = StubFileSystem::default();
+            fs.stub_file_exists_fn(|path| {
+                assert_eq!(path, Path::new("/cache/terraform.json"));
+                false
+            });
+            let latest = LatestVersion::load(Path::new("/cache"), "terraform", &fs).unwrap();
+            assert!(latest.is_none());
+        }
+
+        #[test]
+        fn should_return_invalid_data_err() {
+            let mut fs = StubFileSystem::default();
+            fs.stub_file_exists_fn(|_| true);
+            fs.stub_read_to_string_fn(|_| Ok("{".into()));
+            let err = LatestVersion::load(Path::new("/cache"), "terraform", &fs).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData);
+        }
+
+        #[test]
+        fn should_return_latest_version() {
+            let mut fs = StubFileSystem::default();
+            fs.stub_file_exists_fn(|_| true);
+            fs.stub_read_to_string_fn(|path| {
+                assert_eq!(path, Path::new("/cache/terraform.json"));
+                Ok(r#"{"resolved_at":1000,"version":"1.6.0"}"#.into())
+            });
+            let latest = LatestVersion::load(Path::new("/cache"), "terraform", &fs).unwrap();
+            assert_eq!(latest, Some(self::latest()));
+        }
+    }
+
+    mod save {
+        use super::*;
+
+        #[test]
+        fn should_write_latest_version() {
+            let mut fs = StubFileSystem::default();
+            fs.stub_write_fn(|path, content| {
+                assert_eq!(path, Path::new("/cache/terraform.json"));
+                let saved: LatestVersion = serde_json::from_str(content).unwrap();
+                assert_eq!(saved, latest());
+                Ok(())
+            });
+            latest()
+                .save(Path::new("/cache"), "terraform", &fs)
+                .unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod rust_releases_test {
+    use super::*;
+    use crate::github::StubGithubClient;
+
+    #[test]
+    fn should_return_artifact_url() {
+        assert_eq!(
+            RustReleases.artifact_url("1.72.0", "unknown-linux-gnu", "x86_64"),
+            "https://static.rust-lang.org/dist/rust-1.72.0-x86_64-unknown-linux-gnu.tar.gz"
+        );
+    }
+
+    #[test]
+    fn should_list_versions_from_github() {
+        let mut github = StubGithubClient::default();
+        github.stub_releases_fn(|repo| {
+            assert_eq!(repo, "rust-lang/rust");
+            Ok(vec![])
+        });
+        assert!(RustReleases.list_versions(&github).unwrap().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod url_template_test {
+    use super::*;
+    use crate::github::StubGithubClient;
+
+    #[test]
+    fn should_return_artifact_url() {
+        let index = UrlTemplate::new("https://host/{version}/soft-{os}-{arch}".into(), None);
+        assert_eq!(
+            index.artifact_url("1.0.0", "linux", "amd64"),
+            "https://host/1.0.0/soft-linux-amd64"
+        );
+    }
+
+    #[test]
+    fn should_return_checksums_url() {
+        let index = UrlTemplate::new("".into(), Some("https://host/{version}/SHA256SUMS".into()));
+        assert_eq!(
+            index.checksums_url("1.0.0").unwrap(),
+            "https://host/1.0.0/SHA256SUMS"
+        );
+    }
+
+    #[test]
+    fn should_return_unsupported_err() {
+        let index = UrlTemplate::new("".into(), None);
+        match index
+            .list_versions(&StubGithubClient::default())
+            .unwrap_err()
+        {
+            Error::Unsupported => {}
+            err => panic!("{}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod zig_releases_test {
+    use super::*;
+
+    #[test]
+    fn should_return_artifact_url_with_arch_first() {
+        assert_eq!(
+            ZigReleases.artifact_url("0.14.1", "linux", "x86_64"),
+            "https://ziglang.org/download/0.14.1/zig-x86_64-linux-0.14.1.tar.xz"
+        );
+    }
+
+    #[test]
+    fn should_return_artifact_url_with_os_first() {
+        assert_eq!(
+            ZigReleases.artifact_url("0.11.0", "macos", "aarch64"),
+            "https://ziglang.org/download/0.11.0/zig-macos-aarch64-0.11.0.tar.xz"
+        );
+    }
+}
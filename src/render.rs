@@ -0,0 +1,519 @@
+// IMPORTS
+
+use crate::{
+    shell::{self, ShellOutput},
+    state::{SoftwareState, VarState},
+};
+use std::{
+    io::{Result, Write},
+    path::Path,
+};
+
+// CONSTS
+
+pub const DENV_CFG_FILE_VAR_NAME: &str = "DENV_CONFIG_FILE";
+pub const DENV_CWD_VAR_NAME: &str = "DENV_CWD";
+pub const DENV_IN_PROGRESS_VAR_NAME: &str = "DENV_IN_PROGRESS";
+pub const DENV_PATH_BACKUP_VAR_NAME: &str = "DENV_PATH_BACKUP";
+pub const PATH_VAR_NAME: &str = "PATH";
+
+const DENV_TOOL_VAR_NAME_PREFIX: &str = "DENV_TOOL_";
+const DENV_TOOL_VAR_NAME_SUFFIX: &str = "_VERSION";
+
+// ENUMS
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Export {
+    Literal {
+        name: String,
+        value: String,
+    },
+    Path {
+        name: String,
+        prefix: String,
+        suffix: String,
+    },
+}
+
+impl Export {
+    pub fn resolve(self, path: &str) -> (String, String) {
+        match self {
+            Self::Literal { name, value } => (name, value),
+            Self::Path {
+                name,
+                prefix,
+                suffix,
+            } => (name, join_path(&[&prefix, path, &suffix])),
+        }
+    }
+}
+
+// DATA STRUCTS
+
+pub struct Env<'a> {
+    pub cfg_filepath: &'a Path,
+    pub path_backup: Option<&'a str>,
+    pub path_prefix: &'a str,
+    pub path_suffix: &'a str,
+    pub project_dirpath: &'a Path,
+    pub softwares: &'a [SoftwareState],
+    pub vars: &'a [VarState],
+}
+
+// FUNCTIONS
+
+// The order is part of the output contract: D-Env control variables, then PATH, then software
+// versions and variables, then user variables in configuration order (so they can override
+// anything).
+pub fn exports(env: &Env) -> Vec<Export> {
+    let mut exports = vec![
+        literal(DENV_CWD_VAR_NAME, env.project_dirpath.display().to_string()),
+        literal(
+            DENV_CFG_FILE_VAR_NAME,
+            env.cfg_filepath.display().to_string(),
+        ),
+    ];
+    if let Some(path_backup) = env.path_backup {
+        exports.push(literal(DENV_PATH_BACKUP_VAR_NAME, path_backup.into()));
+    }
+    exports.push(Export::Path {
+        name: PATH_VAR_NAME.into(),
+        prefix: env.path_prefix.into(),
+        suffix: env.path_suffix.into(),
+    });
+    for soft in env.softwares {
+        exports.push(literal(
+            &tool_version_var_name(&soft.name),
+            soft.version.clone(),
+        ));
+        exports.extend(
+            soft.vars
+                .iter()
+                .map(|var| literal(&var.name, var.value.clone())),
+        );
+    }
+    exports.extend(
+        env.vars
+            .iter()
+            .map(|var| literal(&var.name, var.value.clone())),
+    );
+    exports
+}
+
+pub fn render<W: Write>(exports: &[Export], out: &ShellOutput<W>) -> Result<()> {
+    for export in exports {
+        match export {
+            Export::Literal { name, value } => out.export(name, value)?,
+            Export::Path {
+                name,
+                prefix,
+                suffix,
+            } => out.export_expr(name, &path_expr(name, prefix, suffix))?,
+        }
+    }
+    Ok(())
+}
+
+// Same statements as render, understood by any POSIX sh (dash, busybox) and the Bourne shell.
+pub fn render_sh<W: Write>(exports: &[Export], out: &ShellOutput<W>) -> Result<()> {
+    for export in exports {
+        match export {
+            Export::Literal { name, value } => out.assign_export(name, value)?,
+            Export::Path {
+                name,
+                prefix,
+                suffix,
+            } => out.assign_export_expr(name, &path_expr(name, prefix, suffix))?,
+        }
+    }
+    Ok(())
+}
+
+// Empty parts are skipped: PATH never gets an empty entry, which would mean the current directory.
+pub fn join_path(parts: &[&str]) -> String {
+    parts
+        .iter()
+        .filter(|part| !part.is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+// The first occurrence of each entry of the prefix is removed, and the last one of each entry of
+// the suffix: the entries added after load are kept.
+pub fn strip_path(path: &str, prefix: &str, suffix: &str) -> String {
+    let mut entries: Vec<&str> = path.split(':').collect();
+    for prefix_entry in prefix.split(':').filter(|entry| !entry.is_empty()) {
+        if let Some(idx) = entries.iter().position(|entry| *entry == prefix_entry) {
+            entries.remove(idx);
+        }
+    }
+    for suffix_entry in suffix.split(':').filter(|entry| !entry.is_empty()) {
+        if let Some(idx) = entries.iter().rposition(|entry| *entry == suffix_entry) {
+            entries.remove(idx);
+        }
+    }
+    entries.join(":")
+}
+
+pub fn tool_version_var_name(soft_name: &str) -> String {
+    let name: String = soft_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!(
+        "{}{}{}",
+        DENV_TOOL_VAR_NAME_PREFIX, name, DENV_TOOL_VAR_NAME_SUFFIX
+    )
+}
+
+#[inline]
+fn literal(name: &str, value: String) -> Export {
+    Export::Literal {
+        name: name.into(),
+        value,
+    }
+}
+
+// Only the variable is expanded: the prefix and the suffix come from project paths, so they are
+// quoted like literal values.
+#[inline]
+fn path_expr(name: &str, prefix: &str, suffix: &str) -> String {
+    let quote = |part: &str| {
+        if part.is_empty() {
+            String::new()
+        } else {
+            shell::quote(part)
+        }
+    };
+    join_path(&[
+        &quote(prefix),
+        &format!("\"${{{}}}\"", name),
+        &quote(suffix),
+    ])
+}
+
+// TESTS
+
+#[cfg(test)]
+fn path_export(prefix: &str, suffix: &str) -> Export {
+    Export::Path {
+        name: PATH_VAR_NAME.into(),
+        prefix: prefix.into(),
+        suffix: suffix.into(),
+    }
+}
+
+#[cfg(test)]
+mod export_test {
+    use super::*;
+
+    mod resolve {
+        use super::*;
+
+        #[test]
+        fn should_return_literal() {
+            let export = literal("VAR", "value".into());
+            assert_eq!(export.resolve("/bin"), ("VAR".into(), "value".into()));
+        }
+
+        #[test]
+        fn should_return_prefixed_path() {
+            let export = path_export("/env", "");
+            assert_eq!(
+                export.resolve("/bin"),
+                (PATH_VAR_NAME.into(), "/env:/bin".into())
+            );
+        }
+
+        #[test]
+        fn should_return_prefixed_and_suffixed_path() {
+            let export = path_export("/env:/cwd/node_modules/.bin", "/cwd/scripts");
+            assert_eq!(
+                export.resolve("/bin"),
+                (
+                    PATH_VAR_NAME.into(),
+                    "/env:/cwd/node_modules/.bin:/bin:/cwd/scripts".into()
+                )
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod exports_test {
+    use super::*;
+
+    #[test]
+    fn should_return_exports_in_stable_order() {
+        let softs = vec![SoftwareState {
+            name: "terraform".into(),
+            vars: vec![VarState {
+                name: "TF_HOME".into(),
+                value: "/softwares/terraform/1.5.0".into(),
+            }],
+            version: "1.5.0".into(),
+        }];
+        let vars = vec![
+            VarState {
+                name: "ZZZ".into(),
+                value: "1".into(),
+            },
+            VarState {
+                name: "AAA".into(),
+                value: "2".into(),
+            },
+        ];
+        let env = Env {
+            cfg_filepath: Path::new("/cwd/denv.yml"),
+            path_backup: Some("/bin"),
+            path_prefix: "/env",
+            path_suffix: "/cwd/scripts",
+            project_dirpath: Path::new("/cwd"),
+            softwares: &softs,
+            vars: &vars,
+        };
+        let expected = vec![
+            literal(DENV_CWD_VAR_NAME, "/cwd".into()),
+            literal(DENV_CFG_FILE_VAR_NAME, "/cwd/denv.yml".into()),
+            literal(DENV_PATH_BACKUP_VAR_NAME, "/bin".into()),
+            path_export("/env", "/cwd/scripts"),
+            literal("DENV_TOOL_TERRAFORM_VERSION", "1.5.0".into()),
+            literal("TF_HOME", "/softwares/terraform/1.5.0".into()),
+            literal("ZZZ", "1".into()),
+            literal("AAA", "2".into()),
+        ];
+        assert_eq!(exports(&env), expected);
+    }
+
+    #[test]
+    fn should_not_return_path_backup() {
+        let env = Env {
+            cfg_filepath: Path::new("/cwd/denv.yml"),
+            path_backup: None,
+            path_prefix: "/env",
+            path_suffix: "",
+            project_dirpath: Path::new("/cwd"),
+            softwares: &[],
+            vars: &[],
+        };
+        let names: Vec<String> = exports(&env)
+            .into_iter()
+            .map(|export| export.resolve("").0)
+            .collect();
+        assert_eq!(
+            names,
+            vec![DENV_CWD_VAR_NAME, DENV_CFG_FILE_VAR_NAME, PATH_VAR_NAME]
+        );
+    }
+}
+
+#[cfg(test)]
+mod render_test {
+    use super::*;
+    use crate::test::WriteFailer;
+    use proptest::{collection, prelude::*};
+    use std::process::Command;
+
+    #[test]
+    fn should_return_err() {
+        let out = ShellOutput::new(WriteFailer);
+        render(&[literal("VAR", "value".into())], &out).unwrap_err();
+    }
+
+    #[test]
+    fn should_write_statements() {
+        let exports = [
+            literal(DENV_CWD_VAR_NAME, "/cwd".into()),
+            path_export("/env", ""),
+            literal("VAR", "it's".into()),
+        ];
+        let out = ShellOutput::new(vec![]);
+        render(&exports, &out).unwrap();
+        let out = String::from_utf8(out.into_inner()).unwrap();
+        let expected_out =
+            "export DENV_CWD='/cwd'\nexport PATH='/env':\"${PATH}\"\nexport VAR='it'\\''s'\n";
+        assert_eq!(out, expected_out);
+    }
+
+    #[test]
+    fn should_write_single_path_statement() {
+        let exports = [path_export("/env:/cwd/bin", "/cwd/scripts")];
+        let out = ShellOutput::new(vec![]);
+        render(&exports, &out).unwrap();
+        let out = String::from_utf8(out.into_inner()).unwrap();
+        assert_eq!(
+            out,
+            "export PATH='/env:/cwd/bin':\"${PATH}\":'/cwd/scripts'\n"
+        );
+    }
+
+    #[test]
+    fn should_quote_path_entries() {
+        let exports = [path_export("/env:/it's/$HOME", "/`id`/\\")];
+        let out = ShellOutput::new(vec![]);
+        render(&exports, &out).unwrap();
+        let statements = String::from_utf8(out.into_inner()).unwrap();
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(format!("PATH=/bin\n{}printf %s \"$PATH\"", statements))
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "/env:/it's/$HOME:/bin:/`id`/\\"
+        );
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        // Whatever the values are, the shell reads them back unchanged.
+        #[test]
+        fn should_be_evaluated_by_shell(values in collection::vec("[^\\x00]{0,16}", 1..4)) {
+            let exports: Vec<Export> = values
+                .iter()
+                .enumerate()
+                .map(|(idx, value)| literal(&format!("VAR{}", idx), value.clone()))
+                .collect();
+            let out = ShellOutput::new(vec![]);
+            render(&exports, &out).unwrap();
+            let statements = String::from_utf8(out.into_inner()).unwrap();
+            let prints: String = (0..values.len())
+                .map(|idx| format!("printf '%s\\0' \"$VAR{}\"\n", idx))
+                .collect();
+            let output = Command::new("sh")
+                .arg("-c")
+                .arg(format!("{}{}", statements, prints))
+                .output()
+                .unwrap();
+            prop_assert!(output.status.success());
+            let stdout = String::from_utf8(output.stdout).unwrap();
+            let evaluated: Vec<&str> = stdout.split_terminator('\0').collect();
+            prop_assert_eq!(evaluated, values);
+        }
+    }
+}
+
+#[cfg(test)]
+mod render_sh_test {
+    use super::*;
+    use crate::test::WriteFailer;
+    use std::process::Command;
+
+    #[test]
+    fn should_return_err() {
+        let out = ShellOutput::new(WriteFailer);
+        render_sh(&[literal("VAR", "value".into())], &out).unwrap_err();
+    }
+
+    #[test]
+    fn should_write_statements() {
+        let exports = [
+            path_export("/env", "/cwd/scripts"),
+            literal("VAR", "it's".into()),
+        ];
+        let out = ShellOutput::new(vec![]);
+        render_sh(&exports, &out).unwrap();
+        let out = String::from_utf8(out.into_inner()).unwrap();
+        let expected_out =
+            "PATH='/env':\"${PATH}\":'/cwd/scripts'; export PATH\nVAR='it'\\''s'; export VAR\n";
+        assert_eq!(out, expected_out);
+    }
+
+    #[test]
+    fn should_be_evaluated_by_shell() {
+        let exports = [
+            path_export("/env", ""),
+            literal("VAR", "a \"$b\" `c`".into()),
+        ];
+        let out = ShellOutput::new(vec![]);
+        render_sh(&exports, &out).unwrap();
+        let statements = String::from_utf8(out.into_inner()).unwrap();
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(format!(
+                "PATH=/bin\n{}sh -c 'printf \"%s|%s\" \"$PATH\" \"$VAR\"'",
+                statements
+            ))
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "/env:/bin|a \"$b\" `c`"
+        );
+    }
+}
+
+#[cfg(test)]
+mod join_path_test {
+    use super::*;
+
+    #[test]
+    fn should_skip_empty_parts() {
+        assert_eq!(join_path(&["/env", "", "/bin"]), "/env:/bin");
+    }
+}
+
+#[cfg(test)]
+mod strip_path_test {
+    use super::*;
+
+    #[test]
+    fn should_return_path_without_prefix() {
+        assert_eq!(
+            strip_path("/env:/usr/bin:/bin", "/env", ""),
+            "/usr/bin:/bin"
+        );
+    }
+
+    #[test]
+    fn should_return_path_without_prefix_and_suffix() {
+        assert_eq!(
+            strip_path(
+                "/env:/cwd/bin:/usr/bin:/cwd/scripts",
+                "/env:/cwd/bin",
+                "/cwd/scripts"
+            ),
+            "/usr/bin"
+        );
+    }
+
+    #[test]
+    fn should_keep_entries_added_after_load() {
+        assert_eq!(
+            strip_path("/custom:/env:/usr/bin:/env", "/env", ""),
+            "/custom:/usr/bin:/env"
+        );
+        assert_eq!(
+            strip_path("/scripts:/usr/bin:/scripts:/custom", "", "/scripts"),
+            "/scripts:/usr/bin:/custom"
+        );
+    }
+
+    #[test]
+    fn should_return_unchanged_path_if_prefix_is_missing() {
+        assert_eq!(strip_path("/usr/bin:/bin", "/env", ""), "/usr/bin:/bin");
+    }
+}
+
+#[cfg(test)]
+mod tool_version_var_name_test {
+    use super::*;
+
+    #[test]
+    fn should_return_var_name() {
+        assert_eq!(
+            tool_version_var_name("chart-testing"),
+            "DENV_TOOL_CHART_TESTING_VERSION"
+        );
+    }
+}
@@ -1,80 +1,253 @@
 // IMPORTS
 
 use crate::{
-    cfg::{self, ConfigLoader, DefaultConfigLoader, SoftwareDefinition, VarDefinition},
-    cli::{Command, Options, Shell},
+    cfg::{
+        self, Config, ConfigLoader, DefaultConfigLoader, NotifyConfig, SoftwareDefinition,
+        SoftwareSource, VarDefinition, VarDefinitionKind, VaultAgentConfig, LATEST_VERSION,
+        REAL_BIN_SUFFIX,
+    },
+    cli::{
+        CacheCommand, Command, ConfigCommand, EnvCommand, ExportCommand, Format, Options,
+        SbomFormat, Shell, TableOptions,
+    },
+    doctor::{self, Status},
+    edit::{self, ConfigDocument},
     fs::{DefaultFileSystem, FileSystem},
-    soft::{Error as SoftwareError, Software},
-    var::{Error as VarError, Var},
+    github::{self, DefaultGithubClient, GithubClient},
+    hash,
+    history::{Action, HistoryEntry},
+    ide::{self, VscodeEnv, VSCODE_SETTINGS_FILEPATH},
+    lock::{self, Lock},
+    net::DownloadConfig,
+    notify, proc,
+    release::{self, LatestVersion, ReleaseVersion},
+    render::{
+        self, DENV_CFG_FILE_VAR_NAME, DENV_CWD_VAR_NAME, DENV_IN_PROGRESS_VAR_NAME,
+        DENV_PATH_BACKUP_VAR_NAME, PATH_VAR_NAME,
+    },
+    sbom,
+    shell::{self, ShellOutput},
+    soft::{
+        receipt::{InstalledSoftware, Receipt},
+        Error as SoftwareError, Software,
+    },
+    state::{SoftwareState, State, VarState, VaultAgentState, STATE_FILENAME},
+    store::{self, STORE_VERSION},
+    table::{Cell, Color, Style, Table},
+    timing::{ElapsedFn, Timings},
+    trust,
+    var::{self, Error as VarError, Var},
+    vault::{
+        self, VAULT_AGENT_CFG_FILENAME, VAULT_AGENT_LOG_FILENAME, VAULT_AGENT_SINK_VAR_NAME,
+        VAULT_BIN_NAME, VAULT_SINK_FILENAME,
+    },
+    version::Version,
 };
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
 use std::{
+    collections::{BTreeMap, HashMap, HashSet},
     env,
+    ffi::OsStr,
     fmt::{self, Debug, Display, Formatter},
-    io::{self, Stdout, Write},
+    io::{self, IsTerminal, Stdout, Write},
+    iter,
     path::{Path, PathBuf},
-    sync::Mutex,
+    process,
+    sync::{
+        atomic::{self, AtomicBool, AtomicUsize},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-// MACROS
-
-macro_rules! write {
-    ($out:expr, $($arg:tt)*) => {{
-        std::write!($out, $($arg)*).map_err(|err| Error::Io(err))
-    }};
-}
-
-macro_rules! writeln {
-    ($out:expr, $($arg:tt)*) => {{
-        std::writeln!($out, $($arg)*).map_err(|err| Error::Io(err))
-    }};
-}
-
 // TYPES
 
 pub type Result<T> = std::result::Result<T, Error>;
 
 type ArgsFn = dyn Fn() -> Vec<String>;
 
-type CreateFsFn = dyn Fn() -> Box<dyn FileSystem>;
+type CreateFsFn = dyn Fn(Option<PathBuf>) -> Box<dyn FileSystem>;
+
+type CreateGithubClientFn = dyn Fn(PathBuf, Option<String>) -> Box<dyn GithubClient>;
 
-type ConvertSoftFn = dyn Fn(SoftwareDefinition) -> Box<dyn Software>;
+type ConvertSoftFn = dyn Fn(SoftwareDefinition, &DownloadConfig) -> Box<dyn Software>;
 
 type ConvertVarFn = dyn Fn(VarDefinition) -> Box<dyn Var>;
 
+type EntrypointFn = dyn Fn(&[String], &[(String, String)], &[String]) -> io::Result<i32>;
+
 type EnvVarFn = dyn Fn(&str) -> std::result::Result<String, env::VarError>;
 
+type ExecFn = dyn Fn(&[String], &[(String, String)], &[String], bool) -> io::Result<i32>;
+
+type IsRootFn = dyn Fn() -> bool;
+
+type IsTerminalFn = dyn Fn() -> bool;
+
+type KillFn = dyn Fn(u32, i32) -> io::Result<()>;
+
+type LoadedSoftware = (Option<ArgsShim>, Box<dyn Software>);
+
+type NotifyFn = dyn Fn(&NotifyConfig, &str);
+
+type NowFn = dyn Fn() -> u64;
+
+type PrintMsgFn = dyn Fn(&str);
+
+type SpawnFn = dyn Fn(&str, &[String], &Path) -> io::Result<u32>;
+
 // CONSTS
 
-const DENV_CFG_FILE_VAR_NAME: &str = "DENV_CONFIG_FILE";
-const DENV_CWD_VAR_NAME: &str = "DENV_CWD";
-const DENV_PATH_BACKUP_VAR_NAME: &str = "DENV_PATH_BACKUP";
-const PATH_VAR_NAME: &str = "PATH";
+const ACTIVATE_DIRPATH: &str = ".denv/activate.d";
+const CHECK_VERSION_FN_NAME: &str = "_denv_check_version";
+const CFG_FILENAMES: [&str; 4] = ["denv.yml", "denv.yaml", ".denv.yml", ".config/denv.yml"];
+const CFG_HASH_LEN: usize = 8;
+const DAY_SECS: u64 = 86400;
+const DEACTIVATE_DIRPATH: &str = ".denv/deactivate.d";
+const DEFAULT_JOBS: usize = 4;
+const DENV_VERSION: &str = env!("CARGO_PKG_VERSION");
+const GITIGNORE_FILENAME: &str = ".gitignore";
+const LOAD_LOCK_FILENAME: &str = "load.lock";
+const LOCK_EXTENSION: &str = "lock";
+const LOAD_LOCK_TIMEOUT: u64 = 600;
+const NEW_CFG_CONTENT: &str = "version: v1\n";
+const PROJECT_BIN_DIRPATH: &str = ".denv/bin";
+const PURE_PATH: &str = "/usr/local/bin:/usr/bin:/bin";
+const PURE_VAR_NAMES: [&str; 5] = ["HOME", "LANG", "LOGNAME", "TERM", "USER"];
+const SLOWEST_SPAN_COUNT: usize = 3;
+const VAR_KEYWORD: &str = "var";
+const VSCODE_PATH_EXPR: &str = "${env:PATH}";
 
 // ENUMS
 
 #[derive(Debug)]
 pub enum Error {
+    BinaryCollision(String, Vec<String>),
+    Cancelled(Vec<String>),
+    CommandFailed(i32),
     Compute(Vec<ComputeError>),
     Config(cfg::Error),
+    ConfigConflict(Vec<PathBuf>),
+    ConfigNotFound,
     EnvNotLoaded,
+    Github(github::Error),
     Install(Vec<InstallError>),
+    InvalidAssignment(String),
     Io(io::Error),
+    LoadInProgress,
+    Lock(lock::Error),
+    NotAllowed(PathBuf),
+    Root,
+    SoftwareNotFound(String),
+    Store(store::Error),
+    Timeout(u64, Vec<String>),
+    UnexpectedArgument(String),
+    UnresolvedVersion(String, String),
+    UnsupportedSoftware(String),
+    VarNotFound(String),
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
+            Self::BinaryCollision(bin_name, softs) => std::write!(
+                f,
+                "{} is provided by several softwares: {}",
+                bin_name,
+                softs.join(", ")
+            ),
+            Self::Cancelled(_) => std::write!(f, "Loading was cancelled"),
+            Self::CommandFailed(code) => std::write!(f, "Command exited with status {}", code),
             Self::Compute(_) => std::write!(f, "Unable to compute value of some variables"),
             Self::Config(err) => std::write!(f, "{}", err),
+            Self::ConfigConflict(paths) => {
+                let paths: Vec<String> = paths
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect();
+                std::write!(
+                    f,
+                    "Multiple configuration files found: {}",
+                    paths.join(", ")
+                )
+            }
+            Self::ConfigNotFound => std::write!(
+                f,
+                "No configuration file found (expected one of {})",
+                CFG_FILENAMES.join(", ")
+            ),
             Self::EnvNotLoaded => std::write!(f, "No environment loaded"),
+            Self::Github(err) => std::write!(f, "{}", err),
             Self::Install(_) => std::write!(f, "Unable to install some softwares"),
+            Self::InvalidAssignment(assignment) => std::write!(
+                f,
+                "{} is not a valid variable assignment (expected NAME=VALUE)",
+                assignment
+            ),
             Self::Io(err) => std::write!(f, "{}", err),
+            Self::LoadInProgress => std::write!(f, "Environment is already being loaded"),
+            Self::Lock(err) => std::write!(f, "{}", err),
+            Self::NotAllowed(cfg_path) => std::write!(
+                f,
+                "{} is not allowed, review it then run `denv allow` to load it",
+                cfg_path.display()
+            ),
+            Self::Root => std::write!(
+                f,
+                "Refusing to load environment as root (use --allow-root to override)"
+            ),
+            Self::SoftwareNotFound(name) => {
+                std::write!(f, "{} is not defined in configuration", name)
+            }
+            Self::Store(err) => std::write!(f, "{}", err),
+            Self::Timeout(timeout, _) => std::write!(f, "Loading timed out after {}s", timeout),
+            Self::UnexpectedArgument(arg) => std::write!(f, "Unexpected argument {}", arg),
+            Self::UnresolvedVersion(name, req) => {
+                std::write!(f, "No release of {} matches {}", name, req)
+            }
+            Self::UnsupportedSoftware(name) => std::write!(
+                f,
+                "{} is not a supported software (supported: {})",
+                name,
+                cfg::SOFTWARE_NAMES.join(", ")
+            ),
+            Self::VarNotFound(name) => {
+                std::write!(f, "Variable {} is not defined in configuration", name)
+            }
         }
     }
 }
 
 // STRUCTS
 
+struct ArgsShim {
+    bin_name: String,
+    default_args: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, atomic::Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(atomic::Ordering::SeqCst)
+    }
+}
+
+// Configuration files are looked up in this order, the one the environment was loaded from is
+// marked.
+#[derive(Serialize)]
+struct CfgCandidate {
+    matched: bool,
+    path: PathBuf,
+}
+
 pub struct ComputeError {
     pub cause: VarError,
     pub var: Box<dyn Var>,
@@ -89,6 +262,16 @@ impl Debug for ComputeError {
     }
 }
 
+#[derive(Serialize)]
+struct EnvStatus {
+    cfg_candidates: Vec<CfgCandidate>,
+    cfg_filepath: Option<PathBuf>,
+    loaded: bool,
+    project_dirpath: Option<PathBuf>,
+    softwares: Vec<SoftwareStatus>,
+    vars: Vec<String>,
+}
+
 pub struct InstallError {
     pub cause: SoftwareError,
     pub soft: Box<dyn Software>,
@@ -104,395 +287,7500 @@ impl Debug for InstallError {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LoadOutcome {
+    pub cfg_filepath: PathBuf,
+    pub env_dirpath: PathBuf,
+    pub path_prefix: String,
+    pub path_suffix: String,
+    pub project_dirpath: PathBuf,
+    pub softwares: Vec<SoftwareState>,
+    pub statements: String,
+    pub unset_vars: Vec<VarState>,
+    pub vars: Vec<VarState>,
+}
+
+impl LoadOutcome {
+    // The program is looked up in the PATH of the environment, so pinned softwares win.
+    pub fn command<S: AsRef<OsStr>>(&self, program: S) -> process::Command {
+        let path = env::var(PATH_VAR_NAME).unwrap_or_default();
+        let mut cmd = process::Command::new(program);
+        for var in &self.unset_vars {
+            cmd.env_remove(&var.name);
+        }
+        cmd.envs(self.effective_vars(&path));
+        cmd
+    }
+
+    #[inline]
+    fn effective_vars(&self, path: &str) -> Vec<(String, String)> {
+        let mut vars = vec![(
+            PATH_VAR_NAME.to_string(),
+            render::join_path(&[&self.path_prefix, path, &self.path_suffix]),
+        )];
+        for soft in &self.softwares {
+            vars.push((
+                render::tool_version_var_name(&soft.name),
+                soft.version.clone(),
+            ));
+            vars.extend(
+                soft.vars
+                    .iter()
+                    .map(|var| (var.name.clone(), var.value.clone())),
+            );
+        }
+        vars.extend(
+            self.vars
+                .iter()
+                .map(|var| (var.name.clone(), var.value.clone())),
+        );
+        vars
+    }
+}
+
+// Interruptions are cooperative: they are only checked between software installations and
+// variable computations, the pending ones being reported in the error.
+struct LoadContext<'a> {
+    cancel_token: &'a CancelToken,
+    cfg_hash: String,
+    frozen: bool,
+    github_token: Option<String>,
+    jobs: usize,
+    lenient: bool,
+    timeout: Option<u64>,
+    timings: Timings<'a>,
+}
+
+impl LoadContext<'_> {
+    #[inline]
+    fn check<F: FnOnce() -> Vec<String>>(&self, pending_fn: F) -> Result<()> {
+        if self.cancel_token.is_cancelled() {
+            return Err(Error::Cancelled(pending_fn()));
+        }
+        match self.timeout {
+            Some(timeout) if self.timings.total() >= Duration::from_secs(timeout) => {
+                Err(Error::Timeout(timeout, pending_fn()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
 pub struct Runner<W: Write> {
     args_fn: Box<ArgsFn>,
+    cancel_token: CancelToken,
     cfg_loader: Box<dyn ConfigLoader>,
     convert_soft_fn: Box<ConvertSoftFn>,
     convert_var_fn: Box<ConvertVarFn>,
     create_fs_fn: Box<CreateFsFn>,
+    create_github_client_fn: Box<CreateGithubClientFn>,
+    elapsed_fn: Box<ElapsedFn>,
+    entrypoint_fn: Box<EntrypointFn>,
     env_var_fn: Box<EnvVarFn>,
-    out: Mutex<W>,
+    exec_fn: Box<ExecFn>,
+    is_root_fn: Box<IsRootFn>,
+    is_terminal_fn: Box<IsTerminalFn>,
+    kill_fn: Box<KillFn>,
+    notify_fn: Box<NotifyFn>,
+    now_fn: Box<NowFn>,
+    out: ShellOutput<W>,
+    print_msg_fn: Box<PrintMsgFn>,
+    spawn_fn: Box<SpawnFn>,
 }
 
 impl<W: Write> Runner<W> {
+    pub fn cancel_token(&self) -> CancelToken {
+        self.cancel_token.clone()
+    }
+
+    pub fn load(&self, project_dirpath: Option<PathBuf>, opts: Options) -> Result<LoadOutcome> {
+        self.load_project(project_dirpath, opts, false)
+    }
+
     pub fn run(&self, cmd: Command, opts: Options) -> Result<()> {
+        if matches!(cmd, Command::Load | Command::Unload { .. })
+            && (self.env_var_fn)(DENV_IN_PROGRESS_VAR_NAME).is_ok()
+        {
+            debug!(
+                "{} is set, a D-Env hook is already running: skipping",
+                DENV_IN_PROGRESS_VAR_NAME
+            );
+            return Ok(());
+        }
         match cmd {
-            Command::Hook(shell) => self.run_hook(shell),
+            Command::Add { name, value } => self.run_add(name, value, opts),
+            Command::Allow => self.run_allow(opts),
+            Command::Cache(CacheCommand::Clear { older_than }) => {
+                self.run_cache_clear(older_than, opts)
+            }
+            Command::Cache(CacheCommand::Dir) => self.run_cache_dir(opts),
+            Command::Cache(CacheCommand::Size) => self.run_cache_size(opts),
+            Command::Config(ConfigCommand::Show { format }) => self.run_config_show(format, opts),
+            Command::Config(ConfigCommand::Validate) => self.run_config_validate(opts),
+            Command::Deny => self.run_deny(opts),
+            Command::Doctor { table_opts } => self.run_doctor(table_opts, opts),
+            Command::Entrypoint { cmd } => self.run_entrypoint(cmd, opts),
+            Command::Env(EnvCommand::Inspect { format }) => self.run_env_inspect(format, opts),
+            Command::Exec { cmd, pure } => self.run_exec(cmd, pure, opts),
+            Command::Export(ExportCommand::Sh) => self.run_export_sh(opts),
+            Command::Export(ExportCommand::Vscode) => self.run_export_vscode(opts),
+            Command::History { table_opts } => self.run_history(table_opts, opts),
+            Command::Hook {
+                auto_install_check,
+                shell,
+            } => self.run_hook(shell, auto_install_check),
+            Command::List { table_opts } => self.run_list(table_opts, opts),
             Command::Load => self.run_load(opts),
-            Command::Unload => self.run_unload(),
+            Command::Migrate => self.run_migrate(opts),
+            Command::Path {
+                explain,
+                table_opts,
+            } => self.run_path(explain, table_opts, opts),
+            Command::Remove {
+                name,
+                var_name,
+                uninstall,
+                write,
+            } => self.run_remove(name, var_name, uninstall, write, opts),
+            Command::Sbom { format } => self.run_sbom(format, opts),
+            Command::Status { table_opts } => self.run_status(table_opts, opts),
+            Command::Unload { force } => self.run_unload(force, opts),
+            Command::Upgrade { changelog } => self.run_upgrade(changelog, opts),
+            Command::Versions {
+                software,
+                table_opts,
+            } => self.run_versions(software, table_opts, opts),
         }
     }
 
+    // The history only helps debugging: an entry which cannot be written is lost.
     #[inline]
-    fn install_softwares(
-        &self,
-        cwd: &Path,
-        soft_defs: Vec<SoftwareDefinition>,
-        fs: &dyn FileSystem,
-    ) -> Result<()> {
-        let mut install_errs = vec![];
-        for soft_def in soft_defs {
-            let soft = (self.convert_soft_fn)(soft_def);
-            let res = soft
-                .install(cwd, fs)
-                .map_err(|err| InstallError { cause: err, soft });
-            if let Err(err) = res {
-                install_errs.push(err);
-            }
-        }
-        if install_errs.is_empty() {
-            Ok(())
-        } else {
-            Err(Error::Install(install_errs))
+    fn append_history(entry: &HistoryEntry, env_dirpath: &Path, fs: &dyn FileSystem) {
+        if let Err(err) = entry.append(env_dirpath, fs) {
+            warn!("Unable to write history: {}", err);
         }
     }
 
+    // On reload from the same shell, variables are already unset: their backups are kept.
     #[inline]
-    fn print_export_statements(
+    fn backup_unset_vars(
         &self,
         cwd: &Path,
-        env_path: &Path,
-        cfg_path: &Path,
-        var_defs: Vec<VarDefinition>,
-    ) -> Result<()> {
-        let mut out = self.out.lock().unwrap();
-        writeln!(out, "export {}='{}'", DENV_CWD_VAR_NAME, cwd.display())?;
-        writeln!(
-            out,
-            "export {}='{}'",
-            DENV_CFG_FILE_VAR_NAME,
-            cfg_path.display()
-        )?;
-        writeln!(
-            out,
-            "export {}='{}'",
-            DENV_PATH_BACKUP_VAR_NAME,
-            (self.env_var_fn)(PATH_VAR_NAME).unwrap_or_default(),
-        )?;
-        writeln!(
-            out,
-            "export {}=\"{}:${{{}}}\"",
-            PATH_VAR_NAME,
-            env_path.display(),
-            PATH_VAR_NAME
-        )?;
-        let mut compute_errs = vec![];
-        for var_def in var_defs {
-            let var = (self.convert_var_fn)(var_def);
-            let var_name: String = var.name().into();
-            let compute_res = var
-                .compute_value()
-                .map_err(|err| ComputeError { cause: err, var });
-            match compute_res {
-                Err(err) => compute_errs.push(err),
-                Ok(value) => writeln!(out, "export {}='{}'", var_name, value,)?,
-            }
+        var_names: Vec<String>,
+        prev_state: Option<&State>,
+    ) -> Vec<VarState> {
+        if var_names.is_empty() {
+            return vec![];
         }
-        if compute_errs.is_empty() {
-            Ok(())
-        } else {
-            Err(Error::Compute(compute_errs))
+        let reloading = (self.env_var_fn)(DENV_CWD_VAR_NAME)
+            .map(|dirpath| Path::new(&dirpath) == cwd)
+            .unwrap_or(false);
+        var_names
+            .into_iter()
+            .filter_map(|var_name| match (self.env_var_fn)(&var_name) {
+                Ok(value) => Some(VarState {
+                    name: var_name,
+                    value,
+                }),
+                Err(_) if reloading => prev_state.and_then(|state| {
+                    state
+                        .unset_vars
+                        .iter()
+                        .find(|var| var.name == var_name)
+                        .cloned()
+                }),
+                Err(_) => None,
+            })
+            .collect()
+    }
+
+    #[inline]
+    fn cfg_filepath(
+        cfg_filepath: Option<PathBuf>,
+        cwd: &Path,
+        fs: &dyn FileSystem,
+    ) -> Result<PathBuf> {
+        match cfg_filepath {
+            Some(cfg_filepath) => Ok(cfg_filepath),
+            None => Self::find_cfg_filepath(cwd, fs),
         }
     }
 
     #[inline]
-    fn run_hook(&self, shell: Shell) -> Result<()> {
-        let mut args = (self.args_fn)().into_iter();
-        let program = args.next().unwrap();
-        let opts = args
-            .filter(|arg| arg.starts_with('-'))
-            .reduce(|cli, arg| format!(" {} {}", cli, arg))
-            .unwrap_or_default();
-        let cli = format!("{}{}", program, opts);
-        let template = match shell {
-            Shell::Bash => include_str!("../resources/main/hooks/bash"),
-            Shell::Zsh => include_str!("../resources/main/hooks/zsh"),
-        };
-        let statement = template
-            .replace("<denv_cwd_var_name>", DENV_CWD_VAR_NAME)
-            .replace("<load_cmd>", &cli)
-            .replace("<unload_cmd>", &format!("{} unload", cli));
-        let mut out = self.out.lock().unwrap();
-        write!(out, "{}", statement)
+    fn cfg_hash(cfg_path: &Path, fs: &dyn FileSystem) -> Option<String> {
+        fs.read_to_string(cfg_path)
+            .ok()
+            .map(|content| hash::sha256(content.as_bytes()))
     }
 
+    // The state is only saved after a successful load: if it was saved by this version of D-Env
+    // from the same content without --lenient, the configuration was already validated against
+    // the same schema.
     #[inline]
-    fn run_load(&self, opts: Options) -> Result<()> {
-        let cfg_path = opts
-            .cfg_filepath
-            .unwrap_or_else(|| PathBuf::from("denv.yml"));
-        let cfg = self.cfg_loader.load(&cfg_path).map_err(Error::Config)?;
-        let fs = (self.create_fs_fn)();
-        let fs = fs.as_ref();
-        let cwd = fs.cwd().map_err(Error::Io)?;
-        let env_dirpath = fs.ensure_env_dir(&cwd).map_err(Error::Io)?;
-        self.install_softwares(&cwd, cfg.soft_defs, fs)?;
-        self.print_export_statements(&cwd, &env_dirpath, &cfg_path, cfg.var_defs)
+    fn cfg_validated(
+        cfg_path: &Path,
+        cfg_hash: &str,
+        env_dirpath: &Path,
+        fs: &dyn FileSystem,
+    ) -> bool {
+        let state = match State::load(env_dirpath, fs) {
+            Ok(Some(state)) => state,
+            _ => return false,
+        };
+        !state.lenient
+            && state.denv_version == DENV_VERSION
+            && state.cfg_filepath == cfg_path
+            && state.cfg_hash == cfg_hash
     }
 
     #[inline]
-    fn run_unload(&self) -> Result<()> {
-        let cfg_path = (self.env_var_fn)(DENV_CFG_FILE_VAR_NAME)
-            .map(PathBuf::from)
-            .map_err(|_| Error::EnvNotLoaded)?;
-        let cfg = self.cfg_loader.load(&cfg_path).map_err(Error::Config)?;
-        let fs = (self.create_fs_fn)();
-        let fs = fs.as_ref();
-        let cwd = fs.cwd().map_err(Error::Io)?;
-        let mut out = self.out.lock().unwrap();
-        writeln!(
-            out,
-            "export {}='{}'",
-            PATH_VAR_NAME, DENV_PATH_BACKUP_VAR_NAME
-        )?;
-        writeln!(out, "unset {}", DENV_CWD_VAR_NAME)?;
-        writeln!(out, "unset {}", DENV_CFG_FILE_VAR_NAME)?;
-        writeln!(out, "unset {}", DENV_PATH_BACKUP_VAR_NAME)?;
-        for var_def in cfg.var_defs {
-            writeln!(out, "unset {}", var_def.name)?;
+    fn cfg_var_names(
+        &self,
+        force: bool,
+        env_dirpath: &Path,
+        fs: &dyn FileSystem,
+    ) -> Result<Vec<String>> {
+        let cfg = (self.env_var_fn)(DENV_CFG_FILE_VAR_NAME)
+            .map_err(|_| Error::EnvNotLoaded)
+            .and_then(|path| {
+                self.cfg_loader
+                    .load(Path::new(&path))
+                    .map_err(Error::Config)
+            });
+        match cfg {
+            Ok(cfg) => {
+                let mut var_names = vec![];
+                for soft_def in cfg.soft_defs {
+                    let soft = (self.convert_soft_fn)(soft_def, &cfg.download);
+                    let soft_dirpath = fs
+                        .software_dirpath(soft.name(), soft.version())
+                        .map_err(Error::Io)?;
+                    var_names.push(render::tool_version_var_name(soft.name()));
+                    var_names.extend(
+                        soft.env_vars(&soft_dirpath, env_dirpath)
+                            .into_iter()
+                            .map(|(name, _)| name),
+                    );
+                }
+                for var_def in cfg.var_defs {
+                    match var_def.kind {
+                        VarDefinitionKind::AppendPath(_) | VarDefinitionKind::PrependPath(_) => {}
+                        // The name of a dotenv file is its path: its variables are the ones
+                        // it currently defines.
+                        VarDefinitionKind::DotenvFile(path) => {
+                            let entries = fs
+                                .read_to_string(&path)
+                                .map_err(|err| err.to_string())
+                                .and_then(|content| {
+                                    var::dotenv::parse(&content)
+                                        .map_err(|line| format!("line {} is invalid", line))
+                                });
+                            match entries {
+                                Ok(entries) => {
+                                    var_names.extend(entries.into_iter().map(|(name, _)| name))
+                                }
+                                Err(err) => warn!(
+                                    "unable to read {}: {}, its variables will not be unset",
+                                    path.display(),
+                                    err
+                                ),
+                            }
+                        }
+                        _ => var_names.push(var_def.name),
+                    }
+                }
+                if cfg.vault_agent.is_some() {
+                    var_names.push(VAULT_AGENT_SINK_VAR_NAME.into());
+                }
+                Ok(var_names)
+            }
+            Err(err) if force => {
+                warn!("{}, only D-Env variables will be unset", err);
+                Ok(vec![])
+            }
+            Err(err) => Err(err),
         }
-        fs.delete_env_dir(&cwd).map_err(Error::Io)
     }
-}
 
-impl Default for Runner<Stdout> {
-    fn default() -> Self {
-        Self {
-            args_fn: Box::new(|| env::args().collect()),
-            cfg_loader: Box::new(DefaultConfigLoader),
-            convert_soft_fn: Box::new(SoftwareDefinition::into_software),
-            convert_var_fn: Box::new(VarDefinition::into_var),
-            create_fs_fn: Box::new(|| Box::new(DefaultFileSystem)),
-            env_var_fn: Box::new(|var_name| env::var(var_name)),
-            out: Mutex::new(io::stdout()),
+    // Binaries are linked in the same directory: the last installed one would silently win.
+    #[inline]
+    fn check_bin_collisions(softs: &[LoadedSoftware]) -> Result<()> {
+        let mut owners: HashMap<String, &dyn Software> = HashMap::new();
+        for (_, soft) in softs {
+            for bin_name in soft.bin_names() {
+                if let Some(owner) = owners.insert(bin_name.clone(), soft.as_ref()) {
+                    return Err(Error::BinaryCollision(
+                        bin_name,
+                        vec![
+                            format!("{} v{}", owner.name(), owner.version()),
+                            format!("{} v{}", soft.name(), soft.version()),
+                        ],
+                    ));
+                }
+            }
         }
+        Ok(())
     }
-}
 
-// TESTS
-
-#[cfg(test)]
-mod error_test {
-    use super::*;
+    // With --frozen, nothing is installed unless the lock lists the same versions.
+    #[inline]
+    fn check_frozen_lock(lock: Option<&Lock>, softs: &[LoadedSoftware]) -> Result<()> {
+        let lock = lock.ok_or(Error::Lock(lock::Error::Missing))?;
+        let softs: Vec<InstalledSoftware> = softs
+            .iter()
+            .map(|(_, soft)| InstalledSoftware {
+                name: soft.name().into(),
+                receipt: None,
+                version: soft.version().into(),
+            })
+            .collect();
+        let outdated = lock.outdated(&Lock::new(&softs));
+        if outdated.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Lock(lock::Error::Outdated(outdated)))
+        }
+    }
 
-    mod to_string {
-        use super::*;
+    #[inline]
+    fn check_quarantine(env_dirpath: &Path, remove: bool, fs: &dyn FileSystem) -> Result<()> {
+        for path in fs.list_files(env_dirpath).map_err(Error::Io)? {
+            if remove {
+                fs.remove_quarantine(&path).map_err(|err| {
+                    Error::Io(io::Error::new(
+                        err.kind(),
+                        format!(
+                            "Unable to remove quarantine attribute of {}: {}",
+                            path.display(),
+                            err
+                        ),
+                    ))
+                })?;
+            } else if fs.quarantined(&path) {
+                warn!(
+                    "{} is quarantined by macOS Gatekeeper and its execution may be blocked (set remove_quarantine: true in configuration to allow it)",
+                    path.display()
+                );
+            }
+        }
+        Ok(())
+    }
 
-        mod compute {
-            use super::*;
+    #[inline]
+    fn find_cfg_filepath(cwd: &Path, fs: &dyn FileSystem) -> Result<PathBuf> {
+        let mut paths: Vec<PathBuf> = CFG_FILENAMES
+            .iter()
+            .map(|filename| cwd.join(filename))
+            .filter(|path| fs.file_exists(path))
+            .collect();
+        match paths.len() {
+            0 => Err(Error::ConfigNotFound),
+            1 => Ok(paths.remove(0)),
+            _ => Err(Error::ConfigConflict(paths)),
+        }
+    }
 
-            #[test]
-            fn should_return_str() {
-                let str = "Unable to compute value of some variables";
-                let err = Error::Compute(vec![]);
-                assert_eq!(err.to_string(), str);
+    // A stale or unreadable cache entry is resolved again.
+    #[inline]
+    fn cached_latest_version(name: &str, now: u64, fs: &dyn FileSystem) -> Result<Option<String>> {
+        let cache_dirpath = fs
+            .ensure_cache_dir(release::LATEST_CACHE_DIRNAME)
+            .map_err(Error::Io)?;
+        match LatestVersion::load(&cache_dirpath, name, fs) {
+            Ok(latest) => Ok(latest
+                .filter(|latest| latest.is_fresh(now))
+                .map(|latest| latest.version)),
+            Err(err) => {
+                warn!("{}: unable to read cached latest version: {}", name, err);
+                Ok(None)
             }
         }
+    }
 
-        mod config {
-            use super::*;
+    #[inline]
+    fn github_client(
+        &self,
+        token: Option<String>,
+        fs: &dyn FileSystem,
+    ) -> Result<Box<dyn GithubClient>> {
+        let cache_dirpath = fs
+            .ensure_cache_dir(github::CACHE_DIRNAME)
+            .map_err(Error::Io)?;
+        Ok((self.create_github_client_fn)(cache_dirpath, token))
+    }
 
-            #[test]
-            fn should_return_str() {
-                let err = cfg::Error::Version(None);
-                let str = err.to_string();
-                let err = Error::Config(err);
-                assert_eq!(err.to_string(), str);
+    // A configuration file is created if the project has none.
+    #[inline]
+    fn edit_cfg<F: FnOnce(&mut ConfigDocument) -> io::Result<()>>(
+        &self,
+        opts: Options,
+        edit_fn: F,
+    ) -> Result<()> {
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let fs = fs.as_ref();
+        let cwd = fs.cwd().map_err(Error::Io)?;
+        let (cfg_path, content) = match Self::cfg_filepath(opts.cfg_filepath, &cwd, fs) {
+            Ok(cfg_path) => {
+                let content = fs.read_to_string(&cfg_path).map_err(Error::Io)?;
+                (cfg_path, Some(content))
             }
-        }
+            Err(Error::ConfigNotFound) => (cwd.join(CFG_FILENAMES[0]), None),
+            Err(err) => return Err(err),
+        };
+        let mut doc = ConfigDocument::parse(content.as_deref().unwrap_or(NEW_CFG_CONTENT));
+        edit_fn(&mut doc).map_err(Error::Io)?;
+        self.write_cfg(&cfg_path, content.as_deref(), &doc.to_string(), fs)?;
+        Ok(())
+    }
 
-        mod env_not_loaded {
-            use super::*;
+    // Shims are regular files: they are deleted with their binary before installing softwares, so
+    // the installer can link them again if default arguments were removed.
+    #[inline]
+    fn delete_args_shims(env_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
+        for path in fs.list_files(env_dirpath).map_err(Error::Io)? {
+            let filename = path.file_name().unwrap_or_default().to_string_lossy();
+            if let Some(bin_name) = filename.strip_suffix(REAL_BIN_SUFFIX) {
+                for path in [env_dirpath.join(bin_name), path.clone()] {
+                    match fs.delete_file(&path) {
+                        Ok(()) => debug!("Deleted {}", path.display()),
+                        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                        Err(err) => return Err(Error::Io(err)),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 
-            #[test]
-            fn should_return_str() {
-                let str = "No environment loaded";
-                let err = Error::EnvNotLoaded;
-                assert_eq!(err.to_string(), str);
+    // Binaries are resolved to the installed softwares, which outlive the environment directory.
+    #[inline]
+    fn env_bins(env_dirpath: &Path, fs: &dyn FileSystem) -> Result<Vec<(String, PathBuf)>> {
+        let mut bins = vec![];
+        for path in fs.list_files(env_dirpath).map_err(Error::Io)? {
+            if !is_env_bin(&path) {
+                continue;
             }
+            let bin_name = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            let target = fs.canonicalize(&path).map_err(Error::Io)?;
+            bins.push((bin_name, target));
         }
+        Ok(bins)
+    }
 
-        mod install {
-            use super::*;
+    #[inline]
+    fn convert_softwares(
+        &self,
+        cwd: &Path,
+        soft_defs: Vec<SoftwareDefinition>,
+        download_cfg: &DownloadConfig,
+        fs: &dyn FileSystem,
+    ) -> Result<Vec<LoadedSoftware>> {
+        let softs: Vec<LoadedSoftware> = soft_defs
+            .into_iter()
+            .filter_map(|soft_def| {
+                let auto = soft_def.auto.clone();
+                let shim = (soft_def.source == SoftwareSource::Denv
+                    && !soft_def.default_args.is_empty())
+                .then(|| ArgsShim {
+                    bin_name: soft_def.bin_name(),
+                    default_args: soft_def.default_args.clone(),
+                });
+                let soft = (self.convert_soft_fn)(soft_def, download_cfg);
+                if !auto.is_empty() && !auto.iter().any(|pattern| fs.glob_matches(cwd, pattern)) {
+                    debug!(
+                        "{}: No project file matches auto rules, skipping",
+                        soft.name()
+                    );
+                    None
+                } else {
+                    Some((shim, soft))
+                }
+            })
+            .collect();
+        Self::check_bin_collisions(&softs)?;
+        Ok(softs)
+    }
 
-            #[test]
-            fn should_return_str() {
-                let str = "Unable to install some softwares";
-                let err = Error::Install(vec![]);
-                assert_eq!(err.to_string(), str);
+    #[inline]
+    fn install_softwares(
+        &self,
+        cwd: &Path,
+        env_dirpath: &Path,
+        softs: Vec<LoadedSoftware>,
+        ctx: &LoadContext,
+        fs: &dyn FileSystem,
+    ) -> Result<(Vec<SoftwareState>, Vec<ArgsShim>)> {
+        let labels: Vec<String> = softs
+            .iter()
+            .map(|(_, soft)| format!("software {}", soft.name()))
+            .collect();
+        let next_idx = AtomicUsize::new(0);
+        let mut results: Vec<(usize, Result<std::result::Result<(), SoftwareError>>)> =
+            thread::scope(|scope| {
+                let workers: Vec<_> = (0..ctx.jobs.min(softs.len()))
+                    .map(|_| {
+                        scope.spawn(|| {
+                            let mut results = vec![];
+                            loop {
+                                let idx = next_idx.fetch_add(1, atomic::Ordering::SeqCst);
+                                if idx >= softs.len() {
+                                    break;
+                                }
+                                if let Err(err) = ctx.check(|| labels[idx..].to_vec()) {
+                                    next_idx.store(softs.len(), atomic::Ordering::SeqCst);
+                                    results.push((idx, Err(err)));
+                                    break;
+                                }
+                                let res = ctx
+                                    .timings
+                                    .measure(labels[idx].clone(), || softs[idx].1.install(cwd, fs));
+                                results.push((idx, Ok(res)));
+                            }
+                            results
+                        })
+                    })
+                    .collect();
+                workers
+                    .into_iter()
+                    .flat_map(|worker| worker.join().unwrap())
+                    .collect()
+            });
+        results.sort_by_key(|(idx, _)| *idx);
+        let results = results
+            .into_iter()
+            .map(|(_, res)| res)
+            .collect::<Result<Vec<_>>>()?;
+        let mut install_errs = vec![];
+        let mut shims = vec![];
+        let mut soft_states = vec![];
+        for ((shim, soft), res) in softs.into_iter().zip(results) {
+            match res {
+                Ok(()) => {
+                    let soft_dirpath = fs
+                        .software_dirpath(soft.name(), soft.version())
+                        .map_err(Error::Io)?;
+                    let vars = soft
+                        .env_vars(&soft_dirpath, env_dirpath)
+                        .into_iter()
+                        .map(|(name, value)| VarState { name, value })
+                        .collect();
+                    soft_states.push(SoftwareState {
+                        name: soft.name().into(),
+                        vars,
+                        version: soft.version().into(),
+                    });
+                    shims.extend(shim);
+                }
+                Err(err) => install_errs.push(InstallError { cause: err, soft }),
             }
         }
+        if install_errs.is_empty() {
+            Ok((soft_states, shims))
+        } else {
+            Err(Error::Install(install_errs))
+        }
+    }
 
-        mod io {
-            use super::*;
+    #[inline]
+    fn installed_softwares(
+        softs: Vec<SoftwareState>,
+        fs: &dyn FileSystem,
+    ) -> Result<Vec<InstalledSoftware>> {
+        let mut installed_softs = vec![];
+        for soft in softs {
+            let soft_dirpath = fs
+                .software_dirpath(&soft.name, &soft.version)
+                .map_err(Error::Io)?;
+            let receipt = Receipt::load(&soft_dirpath, fs).map_err(Error::Io)?;
+            installed_softs.push(InstalledSoftware {
+                name: soft.name,
+                receipt,
+                version: soft.version,
+            });
+        }
+        Ok(installed_softs)
+    }
 
-            #[test]
-            fn should_return_str() {
-                let err = ::std::io::Error::from(std::io::ErrorKind::PermissionDenied);
-                let str = err.to_string();
-                let err = Error::Io(err);
-                assert_eq!(err.to_string(), str);
+    #[inline]
+    fn compute_vars(
+        &self,
+        var_defs: Vec<VarDefinition>,
+        ctx: &LoadContext,
+    ) -> Result<Vec<VarState>> {
+        let labels: Vec<String> = var_defs
+            .iter()
+            .map(|var_def| format!("var {}", var_def.name))
+            .collect();
+        let mut compute_errs = vec![];
+        let mut var_states = vec![];
+        for (idx, var_def) in var_defs.into_iter().enumerate() {
+            ctx.check(|| labels[idx..].to_vec())?;
+            let var = (self.convert_var_fn)(var_def);
+            match ctx
+                .timings
+                .measure(labels[idx].clone(), || var.compute_values())
+            {
+                Ok(values) => var_states.extend(
+                    values
+                        .into_iter()
+                        .map(|(name, value)| VarState { name, value }),
+                ),
+                Err(err) => compute_errs.push(ComputeError { cause: err, var }),
             }
         }
+        if compute_errs.is_empty() {
+            Ok(var_states)
+        } else {
+            Err(Error::Compute(compute_errs))
+        }
     }
-}
 
-#[cfg(test)]
-mod runner_test {
-    use super::*;
-    use crate::{
-        cfg::{
-            Config, SoftwareDefinition, SoftwareDefinitionKind, StubConfigLoader, VarDefinition,
-            VarDefinitionKind,
-        },
-        fs::StubFileSystem,
-        soft::StubSoftware,
-        test::WriteFailer,
-        var::StubVar,
-    };
+    #[inline]
+    fn load_env(
+        &self,
+        cwd: &Path,
+        cfg_path: &Path,
+        mut cfg: Config,
+        env_dirpath: &Path,
+        ctx: &LoadContext,
+        fs: &dyn FileSystem,
+    ) -> Result<State> {
+        Self::delete_args_shims(env_dirpath, fs)?;
+        let prev_lock = Lock::load(cwd, fs).map_err(Error::Lock)?;
+        if let Some(prev_lock) = &prev_lock {
+            cfg.download.locked_sha256s = prev_lock.sha256s();
+        }
+        let soft_defs =
+            self.resolve_versions(cfg.soft_defs, &cfg.download, prev_lock.as_ref(), ctx, fs)?;
+        let softs = self.convert_softwares(cwd, soft_defs, &cfg.download, fs)?;
+        if ctx.frozen {
+            Self::check_frozen_lock(prev_lock.as_ref(), &softs)?;
+        }
+        let (softwares, shims) = self.install_softwares(cwd, env_dirpath, softs, ctx, fs)?;
+        let lock = Lock::new(&Self::installed_softwares(softwares.clone(), fs)?);
+        if let Some(prev_lock) = &prev_lock {
+            prev_lock.verify(&lock).map_err(Error::Lock)?;
+        }
+        ctx.timings.measure("fs scan".into(), || {
+            Self::reconcile_env_dir(env_dirpath, &softwares, fs)?;
+            Self::write_args_shims(env_dirpath, &shims, fs)?;
+            Self::check_quarantine(env_dirpath, cfg.remove_quarantine, fs)
+        })?;
+        if cfg.project_bin {
+            Self::write_project_shims(cwd, env_dirpath, fs)?;
+        }
+        let (path_defs, var_defs): (Vec<VarDefinition>, Vec<VarDefinition>) = cfg
+            .var_defs
+            .into_iter()
+            .partition(VarDefinition::is_path_entry);
+        let (path_prefix, path_suffix) = path_entries(env_dirpath, &path_defs);
+        let mut vars = self.compute_vars(var_defs, ctx)?;
+        let prev_state = State::load(env_dirpath, fs).map_err(Error::Io)?;
+        let prev_vault_agent = prev_state
+            .as_ref()
+            .and_then(|state| state.vault_agent.as_ref());
+        let vault_agent = match &cfg.vault_agent {
+            Some(agent_cfg) => {
+                let (sink_var, agent) =
+                    self.start_vault_agent(agent_cfg, env_dirpath, prev_vault_agent, fs)?;
+                vars.push(sink_var);
+                Some(agent)
+            }
+            None => {
+                if let Some(agent) = prev_vault_agent {
+                    self.stop_vault_agent(agent);
+                }
+                None
+            }
+        };
+        let unset_vars = self.backup_unset_vars(cwd, cfg.unset_vars, prev_state.as_ref());
+        let now = (self.now_fn)();
+        if let Some(msg) = &cfg.message {
+            let first_load_of_day = prev_state
+                .as_ref()
+                .map(|state| state.last_loaded_at / DAY_SECS != now / DAY_SECS)
+                .unwrap_or(true);
+            if first_load_of_day {
+                (self.print_msg_fn)(msg);
+            }
+        }
+        let state = State {
+            cfg_filepath: cfg_path.to_path_buf(),
+            cfg_hash: ctx.cfg_hash.clone(),
+            created_at: prev_state.map(|state| state.created_at).unwrap_or(now),
+            denv_version: DENV_VERSION.into(),
+            id: env_dirpath
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            last_loaded_at: now,
+            lenient: ctx.lenient,
+            path_prefix,
+            path_suffix,
+            project_dirpath: cwd.to_path_buf(),
+            softwares,
+            unset_vars,
+            vars,
+            vault_agent,
+        };
+        state.save(env_dirpath, fs).map_err(Error::Io)?;
+        if !ctx.frozen && prev_lock.as_ref() != Some(&lock) {
+            lock.save(cwd, fs).map_err(Error::Lock)?;
+        }
+        Ok(state)
+    }
+
+    // Versions are sorted from the newest to the oldest.
+    #[inline]
+    fn list_versions(
+        soft: &dyn Software,
+        github: &dyn GithubClient,
+    ) -> Result<Option<Vec<ReleaseVersion>>> {
+        let index = match soft.release_index() {
+            Some(index) => index,
+            None => {
+                debug!("{}: No release index available", soft.name());
+                return Ok(None);
+            }
+        };
+        let mut versions = match index.list_versions(github) {
+            Ok(versions) => versions,
+            Err(release::Error::Github(err)) => return Err(Error::Github(err)),
+            Err(release::Error::Unsupported) => {
+                debug!("{}: Release index does not list versions", soft.name());
+                return Ok(None);
+            }
+        };
+        versions.sort_by(|version1, version2| version2.version.cmp(&version1.version));
+        Ok(Some(versions))
+    }
+
+    #[inline]
+    fn loaded_state(&self, fs: &dyn FileSystem) -> Result<State> {
+        let cwd = (self.env_var_fn)(DENV_CWD_VAR_NAME)
+            .map(PathBuf::from)
+            .map_err(|_| Error::EnvNotLoaded)?;
+        let env_dirpath = fs.ensure_env_dir(&cwd).map_err(Error::Io)?;
+        State::load(&env_dirpath, fs)
+            .map_err(Error::Io)?
+            .ok_or(Error::EnvNotLoaded)
+    }
+
+    // Loading a configuration may download and run softwares, but only the hook checks whether it
+    // was allowed: the library and the commands run explicitly do not. The content is read once,
+    // so the allowed content is the loaded one.
+    #[inline]
+    fn load_project(
+        &self,
+        project_dirpath: Option<PathBuf>,
+        opts: Options,
+        check_trust: bool,
+    ) -> Result<LoadOutcome> {
+        if !opts.allow_root && (self.is_root_fn)() {
+            return Err(Error::Root);
+        }
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let fs = fs.as_ref();
+        store::check_version(fs).map_err(Error::Store)?;
+        let cwd = match project_dirpath {
+            Some(project_dirpath) => project_dirpath,
+            None => fs.cwd().map_err(Error::Io)?,
+        };
+        let cfg_path = Self::cfg_filepath(opts.cfg_filepath, &cwd, fs)?;
+        let cfg_content = fs.read_to_string(&cfg_path).map_err(Error::Io)?;
+        let cfg_hash = hash::sha256(cfg_content.as_bytes());
+        if check_trust {
+            let cfg_path = cwd.join(&cfg_path);
+            if !trust::is_allowed(&cfg_path, &cfg_hash, fs).map_err(Error::Io)? {
+                return Err(Error::NotAllowed(cfg_path));
+            }
+        }
+        let mut ctx = LoadContext {
+            cancel_token: &self.cancel_token,
+            cfg_hash,
+            frozen: opts.frozen,
+            github_token: opts.github_token,
+            jobs: opts.jobs.unwrap_or(DEFAULT_JOBS),
+            lenient: false,
+            timeout: opts.timeout,
+            timings: Timings::start(self.elapsed_fn.as_ref()),
+        };
+        let env_dirpath = fs.ensure_env_dir(&cwd).map_err(Error::Io)?;
+        let mut notify_cfg = None;
+        // Loads are recorded in the history whether they failed or not, except the ones skipped
+        // because another load of the environment is in progress.
+        let res = (|| {
+            let validated =
+                !opts.revalidate && Self::cfg_validated(&cfg_path, &ctx.cfg_hash, &env_dirpath, fs);
+            let (mut cfg, lenient) = ctx
+                .timings
+                .measure("config".into(), || {
+                    if validated {
+                        debug!("Configuration did not change since last load, skipping validation");
+                        return self
+                            .cfg_loader
+                            .load_content(&cfg_path, &cfg_content, false)
+                            .map(|cfg| (cfg, false));
+                    }
+                    match self.cfg_loader.load_content(&cfg_path, &cfg_content, true) {
+                        Err(cfg::Error::DuplicateVars(errs)) if opts.lenient => {
+                            for err in errs {
+                                warn!("{}", err);
+                            }
+                            self.cfg_loader
+                                .load_content(&cfg_path, &cfg_content, false)
+                                .map(|cfg| (cfg, true))
+                        }
+                        res => res.map(|cfg| (cfg, false)),
+                    }
+                })
+                .map_err(Error::Config)?;
+            ctx.lenient = lenient;
+            cfg.download.shared_cache_dirpath = opts.shared_cache_dirpath;
+            notify_cfg = cfg.notify.clone();
+            let latency_budget_ms = cfg.latency_budget_ms;
+            let lock_path = env_dirpath.join(LOAD_LOCK_FILENAME);
+            self.lock_env(&lock_path, fs)?;
+            let res = self.load_env(&cwd, &cfg_path, cfg, &env_dirpath, &ctx, fs);
+            if let Err(err) = fs.delete_file(&lock_path) {
+                warn!("Unable to delete {}: {}", lock_path.display(), err);
+            }
+            let state = res?;
+            let statements = ctx
+                .timings
+                .measure("statements".into(), || self.render_statements(&state, fs))?;
+            if let Some(msg) = latency_warning(latency_budget_ms, &ctx.timings) {
+                warn!("{}", msg);
+            }
+            Ok(LoadOutcome {
+                cfg_filepath: state.cfg_filepath,
+                env_dirpath: env_dirpath.clone(),
+                path_prefix: state.path_prefix,
+                path_suffix: state.path_suffix,
+                project_dirpath: state.project_dirpath,
+                softwares: state.softwares,
+                statements,
+                unset_vars: state.unset_vars,
+                vars: state.vars,
+            })
+        })();
+        if !matches!(res, Err(Error::LoadInProgress)) {
+            let entry = HistoryEntry {
+                action: Action::Load,
+                at: (self.now_fn)(),
+                cfg_hash: Some(ctx.cfg_hash.clone()),
+                duration_ms: ctx.timings.total().as_millis() as u64,
+                error: res.as_ref().err().map(ToString::to_string),
+                softwares: res
+                    .as_ref()
+                    .map(|outcome| software_versions(&outcome.softwares))
+                    .unwrap_or_default(),
+            };
+            Self::append_history(&entry, &env_dirpath, fs);
+        }
+        let duration = ctx.timings.total();
+        match notify_cfg {
+            Some(cfg) if duration >= Duration::from_secs(cfg.after_secs) => {
+                let project_dirpath = cfg_path.parent().unwrap_or(&cwd);
+                let msg = notification(project_dirpath, duration.as_millis() as u64, res.as_ref());
+                (self.notify_fn)(&cfg, &msg);
+            }
+            _ => {}
+        }
+        res
+    }
+
+    #[inline]
+    fn lock_env(&self, lock_path: &Path, fs: &dyn FileSystem) -> Result<()> {
+        let now = (self.now_fn)();
+        match fs.create_new(lock_path, &now.to_string()) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                let locked_at = fs
+                    .read_to_string(lock_path)
+                    .ok()
+                    .and_then(|locked_at| locked_at.trim().parse().ok())
+                    .unwrap_or_default();
+                if now.saturating_sub(locked_at) < LOAD_LOCK_TIMEOUT {
+                    Err(Error::LoadInProgress)
+                } else {
+                    warn!("Removing stale lock {}", lock_path.display());
+                    fs.write(lock_path, &now.to_string()).map_err(Error::Io)
+                }
+            }
+            Err(err) => Err(Error::Io(err)),
+        }
+    }
+
+    // Versions installed by D-Env: system softwares are not managed by it.
+    #[inline]
+    fn pinned_versions(&self, cfg: Config) -> HashSet<(String, String)> {
+        cfg.soft_defs
+            .into_iter()
+            .filter(|soft_def| soft_def.source == SoftwareSource::Denv)
+            .map(|soft_def| {
+                let soft = (self.convert_soft_fn)(soft_def, &cfg.download);
+                (soft.name().to_string(), soft.version().to_string())
+            })
+            .collect()
+    }
+
+    // Entries left by previous loads (softwares removed from the configuration or skipped by auto
+    // rules, broken symlinks) are deleted: PATH only exposes the softwares installed by this load.
+    #[inline]
+    fn reconcile_env_dir(
+        env_dirpath: &Path,
+        softs: &[SoftwareState],
+        fs: &dyn FileSystem,
+    ) -> Result<()> {
+        let mut soft_dirpaths = vec![];
+        for soft in softs {
+            let soft_dirpath = fs
+                .software_dirpath(&soft.name, &soft.version)
+                .map_err(Error::Io)?;
+            soft_dirpaths.push(fs.canonicalize(&soft_dirpath).unwrap_or(soft_dirpath));
+        }
+        for path in fs.list_files(env_dirpath).map_err(Error::Io)? {
+            if !is_env_bin(&path) {
+                continue;
+            }
+            let target = match fs.canonicalize(&path) {
+                Ok(target) => Some(target),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+                Err(err) => return Err(Error::Io(err)),
+            };
+            let expected = target
+                .map(|target| {
+                    soft_dirpaths
+                        .iter()
+                        .any(|dirpath| target.starts_with(dirpath))
+                })
+                .unwrap_or(false);
+            if !expected {
+                debug!("Removing stale {}", path.display());
+                fs.delete_file(&path).map_err(Error::Io)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn render_statements(&self, state: &State, fs: &dyn FileSystem) -> Result<String> {
+        let path_backup = (self.env_var_fn)(PATH_VAR_NAME).unwrap_or_default();
+        let exports = render::exports(&render::Env {
+            cfg_filepath: &state.cfg_filepath,
+            path_backup: Some(&path_backup),
+            path_prefix: &state.path_prefix,
+            path_suffix: &state.path_suffix,
+            project_dirpath: &state.project_dirpath,
+            softwares: &state.softwares,
+            vars: &state.vars,
+        });
+        let out = ShellOutput::new(vec![]);
+        render::render(&exports, &out).map_err(Error::Io)?;
+        for var in &state.unset_vars {
+            out.unset(&var.name).map_err(Error::Io)?;
+        }
+        Self::source_scripts(&state.project_dirpath.join(ACTIVATE_DIRPATH), &out, fs)?;
+        String::from_utf8(out.into_inner()).map_err(|err| Error::Io(io::Error::other(err)))
+    }
+
+    // Requirements are resolved to the newest matching release, unless the lock already has a
+    // matching version: it caches resolutions, so loads do not query releases nor upgrade silently.
+    #[inline]
+    fn resolve_versions(
+        &self,
+        soft_defs: Vec<SoftwareDefinition>,
+        download_cfg: &DownloadConfig,
+        lock: Option<&Lock>,
+        ctx: &LoadContext,
+        fs: &dyn FileSystem,
+    ) -> Result<Vec<SoftwareDefinition>> {
+        let now = (self.now_fn)();
+        let mut github: Option<Box<dyn GithubClient>> = None;
+        let mut resolved_defs = vec![];
+        for mut soft_def in soft_defs {
+            let req = match soft_def.version_req() {
+                Some(req) => req,
+                None => {
+                    resolved_defs.push(soft_def);
+                    continue;
+                }
+            };
+            let soft = (self.convert_soft_fn)(soft_def.clone(), download_cfg);
+            let latest = soft_def.version == LATEST_VERSION;
+            let cached_version = if latest {
+                Self::cached_latest_version(soft.name(), now, fs)?
+            } else {
+                lock.and_then(|lock| lock.version(soft.name()))
+                    .filter(|version| Version::from(*version).matches(&req))
+                    .map(String::from)
+            };
+            let version = match cached_version {
+                Some(version) => version,
+                None => {
+                    let github = match &github {
+                        Some(github) => github,
+                        None => github.insert(self.github_client(ctx.github_token.clone(), fs)?),
+                    };
+                    let version = Self::list_versions(soft.as_ref(), github.as_ref())?
+                        .unwrap_or_default()
+                        .into_iter()
+                        .find(|version| {
+                            !version.version.is_prerelease() && version.version.matches(&req)
+                        })
+                        .map(|version| version.version.as_str().trim_start_matches('v').to_string())
+                        .ok_or_else(|| {
+                            Error::UnresolvedVersion(soft.name().into(), soft_def.version.clone())
+                        })?;
+                    if latest {
+                        let cache_dirpath = fs
+                            .ensure_cache_dir(release::LATEST_CACHE_DIRNAME)
+                            .map_err(Error::Io)?;
+                        let latest = LatestVersion {
+                            resolved_at: now,
+                            version: version.clone(),
+                        };
+                        if let Err(err) = latest.save(&cache_dirpath, soft.name(), fs) {
+                            warn!("{}: unable to cache latest version: {}", soft.name(), err);
+                        }
+                    }
+                    version
+                }
+            };
+            if latest {
+                info!("{}: latest resolved to {}", soft.name(), version);
+            } else {
+                debug!(
+                    "{}: {} resolved to {}",
+                    soft.name(),
+                    soft_def.version,
+                    version
+                );
+            }
+            soft_def.version = version;
+            resolved_defs.push(soft_def);
+        }
+        Ok(resolved_defs)
+    }
+
+    #[inline]
+    fn run_add(&self, name: String, value: String, opts: Options) -> Result<()> {
+        if name == VAR_KEYWORD {
+            let (var_name, var_value) = value
+                .split_once('=')
+                .filter(|(var_name, _)| !var_name.is_empty())
+                .ok_or_else(|| Error::InvalidAssignment(value.clone()))?;
+            self.edit_cfg(opts, |doc| doc.set_var(var_name, var_value))
+        } else if cfg::SOFTWARE_NAMES.contains(&name.as_str()) {
+            self.edit_cfg(opts, |doc| doc.set_software(&name, &value))
+        } else {
+            Err(Error::UnsupportedSoftware(name))
+        }
+    }
+
+    #[inline]
+    fn run_allow(&self, opts: Options) -> Result<()> {
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let fs = fs.as_ref();
+        let (cfg_path, cfg_hash) = Self::trusted_cfg(opts.cfg_filepath, fs)?;
+        trust::allow(&cfg_path, &cfg_hash, fs).map_err(Error::Io)?;
+        info!("{} allowed", cfg_path.display());
+        Ok(())
+    }
+
+    #[inline]
+    fn run_cache_clear(&self, older_than: Option<u64>, opts: Options) -> Result<()> {
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let fs = fs.as_ref();
+        let now = (self.now_fn)();
+        let files = fs.list_cached_files().map_err(Error::Io)?;
+        let locked_paths: HashSet<PathBuf> = files
+            .iter()
+            .filter(|file| is_lock(&file.path))
+            .map(|file| file.path.with_extension(""))
+            .collect();
+        let mut deleted_count = 0;
+        let mut deleted_size = 0;
+        for file in files {
+            if is_lock(&file.path) || locked_paths.contains(&file.path) {
+                debug!("{} is locked, skipping", file.path.display());
+                continue;
+            }
+            let recent = older_than
+                .map(|older_than| now.saturating_sub(file.modified_at) < older_than)
+                .unwrap_or(false);
+            if recent {
+                continue;
+            }
+            match fs.delete_file(&file.path) {
+                Ok(()) => {
+                    deleted_count += 1;
+                    deleted_size += file.size;
+                }
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    debug!("{} was already deleted", file.path.display());
+                }
+                Err(err) => return Err(Error::Io(err)),
+            }
+        }
+        self.out
+            .write(&format!(
+                "Deleted {} files ({})\n",
+                deleted_count,
+                format_size(deleted_size)
+            ))
+            .map_err(Error::Io)
+    }
+
+    #[inline]
+    fn run_cache_dir(&self, opts: Options) -> Result<()> {
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let cache_dirpath = fs.cache_dirpath().map_err(Error::Io)?;
+        self.out
+            .write(&format!("{}\n", cache_dirpath.display()))
+            .map_err(Error::Io)
+    }
+
+    #[inline]
+    fn run_cache_size(&self, opts: Options) -> Result<()> {
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let files = fs.list_cached_files().map_err(Error::Io)?;
+        let size = files.iter().map(|file| file.size).sum();
+        self.out
+            .write(&format!("{} ({} files)\n", format_size(size), files.len()))
+            .map_err(Error::Io)
+    }
+
+    #[inline]
+    fn run_config_show(&self, format: Format, opts: Options) -> Result<()> {
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let fs = fs.as_ref();
+        let cwd = fs.cwd().map_err(Error::Io)?;
+        let cfg_path = Self::cfg_filepath(opts.cfg_filepath, &cwd, fs)?;
+        let cfg = self.cfg_loader.load(&cfg_path).map_err(Error::Config)?;
+        let doc = match format {
+            Format::Json => serde_json::to_string_pretty(&cfg)
+                .map(|json| format!("{}\n", json))
+                .map_err(io::Error::other),
+            Format::Yaml => serde_yaml::to_string(&cfg).map_err(io::Error::other),
+        }
+        .map_err(Error::Io)?;
+        self.out.write(&doc).map_err(Error::Io)
+    }
+
+    #[inline]
+    fn run_config_validate(&self, opts: Options) -> Result<()> {
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let fs = fs.as_ref();
+        let cwd = fs.cwd().map_err(Error::Io)?;
+        let cfg_path = Self::cfg_filepath(opts.cfg_filepath, &cwd, fs)?;
+        self.cfg_loader.load(&cfg_path).map_err(Error::Config)?;
+        self.out
+            .write(&format!("{} is valid\n", cfg_path.display()))
+            .map_err(Error::Io)
+    }
+
+    #[inline]
+    fn run_deny(&self, opts: Options) -> Result<()> {
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let fs = fs.as_ref();
+        let (cfg_path, _) = Self::trusted_cfg(opts.cfg_filepath, fs)?;
+        if trust::deny(&cfg_path, fs).map_err(Error::Io)? {
+            info!("{} denied", cfg_path.display());
+        } else {
+            info!("{} was not allowed", cfg_path.display());
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn run_doctor(&self, table_opts: TableOptions, opts: Options) -> Result<()> {
+        let no_color = opts.no_color;
+        let fs = (self.create_fs_fn)(opts.denv_dirpath.clone());
+        let checks = doctor::checks(opts.denv_dirpath.as_deref(), fs.as_ref());
+        let mut table = Table::new(vec!["CHECK", "STATUS", "DETAIL"]);
+        for check in checks {
+            let status = match check.status {
+                Status::Ok => Cell::colored("ok", Color::Green),
+                Status::Warn => Cell::colored("warn", Color::Yellow),
+            };
+            table.push(vec![check.name.into(), status, check.detail.into()]);
+        }
+        self.write_table(&table, table_opts, no_color)
+    }
+
+    // The environment is applied as is: D-Env control variables only serve the shell hook.
+    #[inline]
+    fn run_entrypoint(&self, cmd: Vec<String>, opts: Options) -> Result<()> {
+        let outcome = self.load(None, opts)?;
+        let path = (self.env_var_fn)(PATH_VAR_NAME).unwrap_or_default();
+        let vars = outcome.effective_vars(&path);
+        let unset_vars: Vec<String> = outcome.unset_vars.into_iter().map(|var| var.name).collect();
+        match (self.entrypoint_fn)(&cmd, &vars, &unset_vars).map_err(Error::Io)? {
+            0 => Ok(()),
+            code => Err(Error::CommandFailed(code)),
+        }
+    }
+
+    #[inline]
+    fn run_env_inspect(&self, format: Format, opts: Options) -> Result<()> {
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let state = self.loaded_state(fs.as_ref())?;
+        let doc = match format {
+            Format::Json => serde_json::to_string_pretty(&state)
+                .map(|json| format!("{}\n", json))
+                .map_err(io::Error::other),
+            Format::Yaml => serde_yaml::to_string(&state).map_err(io::Error::other),
+        }
+        .map_err(Error::Io)?;
+        self.out.write(&doc).map_err(Error::Io)
+    }
+
+    #[inline]
+    fn run_exec(&self, cmd: Vec<String>, pure: bool, opts: Options) -> Result<()> {
+        let outcome = self.load(None, opts)?;
+        let mut vars: Vec<(String, String)> = vec![];
+        let path = if pure {
+            for var_name in PURE_VAR_NAMES {
+                if let Ok(value) = (self.env_var_fn)(var_name) {
+                    vars.push((var_name.into(), value));
+                }
+            }
+            PURE_PATH.into()
+        } else {
+            (self.env_var_fn)(PATH_VAR_NAME).unwrap_or_default()
+        };
+        let exports = render::exports(&render::Env {
+            cfg_filepath: &outcome.cfg_filepath,
+            path_backup: None,
+            path_prefix: &outcome.path_prefix,
+            path_suffix: &outcome.path_suffix,
+            project_dirpath: &outcome.project_dirpath,
+            softwares: &outcome.softwares,
+            vars: &outcome.vars,
+        });
+        vars.extend(exports.into_iter().map(|export| export.resolve(&path)));
+        let unset_vars: Vec<String> = outcome.unset_vars.into_iter().map(|var| var.name).collect();
+        match (self.exec_fn)(&cmd, &vars, &unset_vars, pure).map_err(Error::Io)? {
+            0 => Ok(()),
+            code => Err(Error::CommandFailed(code)),
+        }
+    }
+
+    // Init scripts never unload the environment: the statements are printed without a PATH backup.
+    #[inline]
+    fn run_export_sh(&self, opts: Options) -> Result<()> {
+        let outcome = self.load(None, opts)?;
+        let exports = render::exports(&render::Env {
+            cfg_filepath: &outcome.cfg_filepath,
+            path_backup: None,
+            path_prefix: &outcome.path_prefix,
+            path_suffix: &outcome.path_suffix,
+            project_dirpath: &outcome.project_dirpath,
+            softwares: &outcome.softwares,
+            vars: &outcome.vars,
+        });
+        render::render_sh(&exports, &self.out).map_err(Error::Io)?;
+        for var in &outcome.unset_vars {
+            self.out.unset(&var.name).map_err(Error::Io)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn run_export_vscode(&self, opts: Options) -> Result<()> {
+        let fs = (self.create_fs_fn)(opts.denv_dirpath.clone());
+        let fs = fs.as_ref();
+        let outcome = self.load(None, opts)?;
+        let bins: Vec<(String, String)> = Self::env_bins(&outcome.env_dirpath, fs)?
+            .into_iter()
+            .map(|(bin_name, path)| (bin_name, path.display().to_string()))
+            .collect();
+        let unset_vars: Vec<String> = outcome
+            .unset_vars
+            .iter()
+            .map(|var| var.name.clone())
+            .collect();
+        let vars = outcome.effective_vars(VSCODE_PATH_EXPR);
+        let settings_path = outcome.project_dirpath.join(VSCODE_SETTINGS_FILEPATH);
+        let settings = if fs.file_exists(&settings_path) {
+            let content = fs.read_to_string(&settings_path).map_err(Error::Io)?;
+            let settings = serde_json::from_str(&content).map_err(|err| {
+                Error::Io(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {}", settings_path.display(), err),
+                ))
+            })?;
+            Some(settings)
+        } else {
+            None
+        };
+        let settings = ide::merge_vscode_settings(
+            settings,
+            &VscodeEnv {
+                bins: &bins,
+                os: env::consts::OS,
+                unset_vars: &unset_vars,
+                vars: &vars,
+            },
+        )
+        .map_err(Error::Io)?;
+        let mut file = fs.create_file(&settings_path).map_err(Error::Io)?;
+        serde_json::to_writer_pretty(&mut file, &settings)
+            .map_err(|err| Error::Io(io::Error::other(err)))?;
+        writeln!(file).map_err(Error::Io)?;
+        info!("VS Code settings written to {}", settings_path.display());
+        Ok(())
+    }
+
+    #[inline]
+    fn run_history(&self, table_opts: TableOptions, opts: Options) -> Result<()> {
+        let no_color = opts.no_color;
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let fs = fs.as_ref();
+        let cwd = fs.cwd().map_err(Error::Io)?;
+        let env_dirpath = fs.ensure_env_dir(&cwd).map_err(Error::Io)?;
+        let entries = HistoryEntry::load_all(&env_dirpath, fs).map_err(Error::Io)?;
+        let mut table = Table::new(vec![
+            "DATE",
+            "ACTION",
+            "DURATION",
+            "CONFIG",
+            "SOFTWARES",
+            "RESULT",
+        ]);
+        for entry in entries {
+            let cfg_hash: String = entry
+                .cfg_hash
+                .as_deref()
+                .map(|hash| hash.chars().take(CFG_HASH_LEN).collect())
+                .unwrap_or_else(|| "-".into());
+            let softwares: Vec<String> = entry
+                .softwares
+                .iter()
+                .map(|(name, version)| format!("{} {}", name, version))
+                .collect();
+            let res = match entry.error {
+                Some(err) => Cell::colored(err, Color::Red),
+                None => Cell::colored("ok", Color::Green),
+            };
+            table.push(vec![
+                format_timestamp(entry.at).into(),
+                entry.action.to_string().into(),
+                format_duration_ms(entry.duration_ms).into(),
+                cfg_hash.into(),
+                softwares.join(", ").into(),
+                res,
+            ]);
+        }
+        self.write_table(&table, table_opts, no_color)
+    }
+
+    #[inline]
+    fn run_hook(&self, shell: Shell, auto_install_check: bool) -> Result<()> {
+        let mut args = (self.args_fn)().into_iter();
+        let program = args.next().unwrap();
+        let opts = args
+            .take_while(|arg| arg != "hook")
+            .filter(|arg| arg.starts_with('-'))
+            .map(|arg| format!(" {}", arg))
+            .collect::<String>();
+        let cli = format!("{}{}", program, opts);
+        let template = match shell {
+            Shell::Bash => include_str!("../resources/main/hooks/bash"),
+            Shell::Zsh => include_str!("../resources/main/hooks/zsh"),
+        };
+        let (preamble, check_version_cmd) = if auto_install_check {
+            let preamble = include_str!("../resources/main/hooks/check-version")
+                .replace("<denv_cmd>", &program)
+                .replace("<version>", DENV_VERSION);
+            (preamble, CHECK_VERSION_FN_NAME)
+        } else {
+            (String::new(), ":")
+        };
+        let statement = template
+            .replace("<check_version_cmd>", check_version_cmd)
+            .replace("<denv_cwd_var_name>", DENV_CWD_VAR_NAME)
+            .replace("<load_cmd>", &format!("{} load", cli))
+            .replace("<unload_cmd>", &format!("{} unload", cli));
+        self.out
+            .write(&format!("{}{}", preamble, statement))
+            .map_err(Error::Io)
+    }
+
+    #[inline]
+    fn run_list(&self, table_opts: TableOptions, opts: Options) -> Result<()> {
+        let no_color = opts.no_color;
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let fs = fs.as_ref();
+        let state = self.loaded_state(fs)?;
+        let softs = Self::installed_softwares(state.softwares, fs)?;
+        // Receipts are kept as objects, which a table cannot represent.
+        if table_opts.json {
+            return self.write_json(&softs);
+        }
+        let mut table = Table::new(vec!["NAME", "VERSION", "URL", "SHA256"]);
+        for soft in softs {
+            let (url, sha256) = match soft.receipt {
+                Some(receipt) => (receipt.url.into(), receipt.sha256.into()),
+                None => (Cell::colored("no receipt", Color::Yellow), "-".into()),
+            };
+            table.push(vec![soft.name.into(), soft.version.into(), url, sha256]);
+        }
+        self.write_table(&table, table_opts, no_color)
+    }
+
+    #[inline]
+    fn run_load(&self, opts: Options) -> Result<()> {
+        let outcome = self.load_project(None, opts, true)?;
+        self.out.write(&outcome.statements).map_err(Error::Io)
+    }
+
+    #[inline]
+    fn run_migrate(&self, opts: Options) -> Result<()> {
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let versions = store::migrate(fs.as_ref()).map_err(Error::Store)?;
+        let msg = match versions.last() {
+            Some(version) => format!("Store migrated to version {}\n", version),
+            None => format!("Store is up to date (version {})\n", STORE_VERSION),
+        };
+        self.out.write(&msg).map_err(Error::Io)
+    }
+
+    // Entries are explained from the PATH saved at load: an entry which was not in it was added
+    // after load, e.g. by a script of the project or by hand.
+    #[inline]
+    fn run_path(&self, explain: bool, table_opts: TableOptions, opts: Options) -> Result<()> {
+        let path = (self.env_var_fn)(PATH_VAR_NAME).unwrap_or_default();
+        let entries = path.split(':').filter(|entry| !entry.is_empty());
+        if !explain {
+            for entry in entries {
+                self.out.write(&format!("{}\n", entry)).map_err(Error::Io)?;
+            }
+            return Ok(());
+        }
+        let no_color = opts.no_color;
+        let env = match (self.env_var_fn)(DENV_CWD_VAR_NAME) {
+            Ok(cwd) => {
+                let fs = (self.create_fs_fn)(opts.denv_dirpath);
+                let env_dirpath = fs.ensure_env_dir(Path::new(&cwd)).map_err(Error::Io)?;
+                let path_backup = (self.env_var_fn)(DENV_PATH_BACKUP_VAR_NAME).unwrap_or_default();
+                let cfg_entries = State::load(&env_dirpath, fs.as_ref())
+                    .map_err(Error::Io)?
+                    .map(|state| render::join_path(&[&state.path_prefix, &state.path_suffix]))
+                    .unwrap_or_default();
+                Some((env_dirpath, path_backup, cfg_entries))
+            }
+            Err(_) => None,
+        };
+        let mut table = Table::new(vec!["ENTRY", "SOURCE"]);
+        for entry in entries {
+            let src = match &env {
+                Some((env_dirpath, _, _)) if Path::new(entry) == env_dirpath => "denv environment",
+                Some((_, _, cfg_entries)) if cfg_entries.split(':').any(|other| other == entry) => {
+                    "denv configuration"
+                }
+                Some((_, path_backup, _))
+                    if !path_backup.split(':').any(|other| other == entry) =>
+                {
+                    "added after load"
+                }
+                _ => "PATH before load",
+            };
+            table.push(vec![entry.into(), src.into()]);
+        }
+        self.write_table(&table, table_opts, no_color)
+    }
+
+    #[inline]
+    fn run_remove(
+        &self,
+        name: String,
+        var_name: Option<String>,
+        uninstall: bool,
+        write: bool,
+        opts: Options,
+    ) -> Result<()> {
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let fs = fs.as_ref();
+        let cwd = fs.cwd().map_err(Error::Io)?;
+        let cfg_path = Self::cfg_filepath(opts.cfg_filepath, &cwd, fs)?;
+        let content = fs.read_to_string(&cfg_path).map_err(Error::Io)?;
+        let mut doc = ConfigDocument::parse(&content);
+        match var_name {
+            Some(var_name) if name == VAR_KEYWORD => {
+                if !doc.remove_var(&var_name).map_err(Error::Io)? {
+                    return Err(Error::VarNotFound(var_name));
+                }
+            }
+            Some(arg) => return Err(Error::UnexpectedArgument(arg)),
+            None => {
+                if !cfg::SOFTWARE_NAMES.contains(&name.as_str()) {
+                    return Err(Error::UnsupportedSoftware(name));
+                }
+                if !doc.remove_software(&name).map_err(Error::Io)? {
+                    return Err(Error::SoftwareNotFound(name));
+                }
+            }
+        }
+        let new_content = doc.to_string();
+        let diff = edit::diff(&cfg_path.display().to_string(), &content, &new_content);
+        self.out.write(&diff).map_err(Error::Io)?;
+        if !write {
+            info!("Run with --write to update {}", cfg_path.display());
+            return Ok(());
+        }
+        let prev_cfg = if uninstall {
+            Some(self.cfg_loader.load(&cfg_path).map_err(Error::Config)?)
+        } else {
+            None
+        };
+        let cfg = self.write_cfg(&cfg_path, Some(&content), &new_content, fs)?;
+        if let Some(prev_cfg) = prev_cfg {
+            let pinned_versions = self.pinned_versions(cfg);
+            for (name, version) in self.pinned_versions(prev_cfg) {
+                if pinned_versions.contains(&(name.clone(), version.clone())) {
+                    continue;
+                }
+                self.uninstall_software(&name, &version, fs)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn run_sbom(&self, format: SbomFormat, opts: Options) -> Result<()> {
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let fs = fs.as_ref();
+        let state = self.loaded_state(fs)?;
+        let softs = Self::installed_softwares(state.softwares, fs)?;
+        let doc = match format {
+            SbomFormat::Cyclonedx => sbom::cyclonedx(&softs),
+            SbomFormat::Spdx => {
+                let project_name = state
+                    .project_dirpath
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                sbom::spdx(&softs, &project_name, &state.id, (self.now_fn)())
+            }
+        };
+        let json =
+            serde_json::to_string_pretty(&doc).map_err(|err| Error::Io(io::Error::other(err)))?;
+        self.out.write(&format!("{}\n", json)).map_err(Error::Io)
+    }
+
+    // Only the names of variables are printed: their values may be secrets.
+    #[inline]
+    fn run_status(&self, table_opts: TableOptions, opts: Options) -> Result<()> {
+        let no_color = opts.no_color;
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let state = match self.loaded_state(fs.as_ref()) {
+            Ok(state) => Some(state),
+            Err(Error::EnvNotLoaded) => None,
+            Err(err) => return Err(err),
+        };
+        let status = match state {
+            Some(state) => EnvStatus {
+                cfg_candidates: CFG_FILENAMES
+                    .iter()
+                    .map(|filename| {
+                        let path = state.project_dirpath.join(filename);
+                        CfgCandidate {
+                            matched: path == state.cfg_filepath,
+                            path,
+                        }
+                    })
+                    .collect(),
+                cfg_filepath: Some(state.cfg_filepath),
+                loaded: true,
+                project_dirpath: Some(state.project_dirpath),
+                softwares: state
+                    .softwares
+                    .into_iter()
+                    .map(|soft| SoftwareStatus {
+                        name: soft.name,
+                        version: soft.version,
+                    })
+                    .collect(),
+                vars: state.vars.into_iter().map(|var| var.name).collect(),
+            },
+            None => EnvStatus {
+                cfg_candidates: vec![],
+                cfg_filepath: None,
+                loaded: false,
+                project_dirpath: None,
+                softwares: vec![],
+                vars: vec![],
+            },
+        };
+        if table_opts.json {
+            return self.write_json(&status);
+        }
+        let cfg_filepath = match status.cfg_filepath {
+            Some(cfg_filepath) => cfg_filepath,
+            None => return self.out.write("No environment loaded\n").map_err(Error::Io),
+        };
+        self.out
+            .write(&format!(
+                "Environment loaded from {}\n\n",
+                cfg_filepath.display()
+            ))
+            .map_err(Error::Io)?;
+        let mut cfgs_table = Table::new(vec!["CONFIGURATION", "MATCHED"]);
+        for candidate in status.cfg_candidates {
+            let matched = if candidate.matched { "yes" } else { "no" };
+            cfgs_table.push(vec![
+                candidate.path.display().to_string().into(),
+                matched.into(),
+            ]);
+        }
+        self.write_table(&cfgs_table, table_opts, no_color)?;
+        self.out.write("\n").map_err(Error::Io)?;
+        let mut softs_table = Table::new(vec!["NAME", "VERSION"]);
+        for soft in status.softwares {
+            softs_table.push(vec![soft.name.into(), soft.version.into()]);
+        }
+        self.write_table(&softs_table, table_opts, no_color)?;
+        self.out.write("\n").map_err(Error::Io)?;
+        let mut vars_table = Table::new(vec!["VARIABLE"]);
+        for var in status.vars {
+            vars_table.push(vec![var.into()]);
+        }
+        self.write_table(&vars_table, table_opts, no_color)
+    }
+
+    #[inline]
+    fn run_unload(&self, force: bool, opts: Options) -> Result<()> {
+        let project_dirpath = (self.env_var_fn)(DENV_CWD_VAR_NAME)
+            .map(PathBuf::from)
+            .map_err(|_| Error::EnvNotLoaded)?;
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let fs = fs.as_ref();
+        let env_dirpath = fs.ensure_env_dir(&project_dirpath).map_err(Error::Io)?;
+        let timings = Timings::start(self.elapsed_fn.as_ref());
+        let mut entry = HistoryEntry {
+            action: Action::Unload,
+            at: (self.now_fn)(),
+            cfg_hash: None,
+            duration_ms: 0,
+            error: None,
+            softwares: BTreeMap::new(),
+        };
+        let res = (|| {
+            let state = match State::load(&env_dirpath, fs) {
+                Ok(state) => state,
+                Err(err) if force => {
+                    warn!("Unable to read environment state: {}", err);
+                    None
+                }
+                Err(err) => return Err(Error::Io(err)),
+            };
+            if let Some(state) = &state {
+                entry.cfg_hash = Some(state.cfg_hash.clone());
+                entry.softwares = software_versions(&state.softwares);
+            }
+            let vault_agent = state.as_ref().and_then(|state| state.vault_agent.clone());
+            let (path_prefix, path_suffix, vars, unset_vars): (
+                String,
+                String,
+                Vec<(String, Option<String>)>,
+                _,
+            ) = match state {
+                Some(state) => (
+                    Some(state.path_prefix)
+                        .filter(|path_prefix| !path_prefix.is_empty())
+                        .unwrap_or_else(|| env_dirpath.display().to_string()),
+                    state.path_suffix,
+                    state
+                        .softwares
+                        .into_iter()
+                        .flat_map(|soft| {
+                            let tool_var = (
+                                render::tool_version_var_name(&soft.name),
+                                Some(soft.version),
+                            );
+                            iter::once(tool_var)
+                                .chain(soft.vars.into_iter().map(|var| (var.name, Some(var.value))))
+                        })
+                        .chain(
+                            state
+                                .vars
+                                .into_iter()
+                                .map(|var| (var.name, Some(var.value))),
+                        )
+                        .collect(),
+                    state.unset_vars,
+                ),
+                None => (
+                    env_dirpath.display().to_string(),
+                    String::new(),
+                    self.cfg_var_names(force, &env_dirpath, fs)?
+                        .into_iter()
+                        .map(|var_name| (var_name, None))
+                        .collect(),
+                    vec![],
+                ),
+            };
+            let path = (self.env_var_fn)(PATH_VAR_NAME).unwrap_or_default();
+            if let Ok(path_backup) = (self.env_var_fn)(DENV_PATH_BACKUP_VAR_NAME) {
+                let mut loaded_path = format!("{}:{}", path_prefix, path_backup);
+                if !path_suffix.is_empty() {
+                    loaded_path = format!("{}:{}", loaded_path, path_suffix);
+                }
+                if path != loaded_path {
+                    warn!(
+                        "PATH was modified after load, only {} is removed from it",
+                        render::join_path(&[&path_prefix, &path_suffix])
+                    );
+                }
+            }
+            self.out
+                .export(
+                    PATH_VAR_NAME,
+                    &render::strip_path(&path, &path_prefix, &path_suffix),
+                )
+                .map_err(Error::Io)?;
+            for var_name in [
+                DENV_CWD_VAR_NAME,
+                DENV_CFG_FILE_VAR_NAME,
+                DENV_PATH_BACKUP_VAR_NAME,
+            ] {
+                self.out.unset(var_name).map_err(Error::Io)?;
+            }
+            for (var_name, value) in vars {
+                let changed = value
+                    .and_then(|value| {
+                        (self.env_var_fn)(&var_name)
+                            .ok()
+                            .map(|current_value| current_value != value)
+                    })
+                    .unwrap_or(false);
+                if changed && !force {
+                    warn!(
+                        "{} was modified after load, it is kept (use --force to unset it)",
+                        var_name
+                    );
+                } else {
+                    self.out.unset(&var_name).map_err(Error::Io)?;
+                }
+            }
+            for var in unset_vars {
+                if (self.env_var_fn)(&var.name).is_ok() && !force {
+                    warn!(
+                        "{} was set after load, it is kept (use --force to restore it)",
+                        var.name
+                    );
+                } else {
+                    self.out.export(&var.name, &var.value).map_err(Error::Io)?;
+                }
+            }
+            Self::source_scripts(&project_dirpath.join(DEACTIVATE_DIRPATH), &self.out, fs)?;
+            if let Some(agent) = &vault_agent {
+                self.stop_vault_agent(agent);
+            }
+            fs.delete_env_dir(&project_dirpath).map_err(Error::Io)
+        })();
+        entry.duration_ms = timings.total().as_millis() as u64;
+        entry.error = res.as_ref().err().map(ToString::to_string);
+        Self::append_history(&entry, &env_dirpath, fs);
+        res
+    }
+
+    #[inline]
+    fn run_upgrade(&self, changelog: bool, opts: Options) -> Result<()> {
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let fs = fs.as_ref();
+        let cwd = fs.cwd().map_err(Error::Io)?;
+        let cfg_path = Self::cfg_filepath(opts.cfg_filepath, &cwd, fs)?;
+        let env_dirpath = fs.ensure_env_dir(&cwd).map_err(Error::Io)?;
+        let timings = Timings::start(self.elapsed_fn.as_ref());
+        let mut softwares = BTreeMap::new();
+        let res = (|| {
+            let cfg = self.cfg_loader.load(&cfg_path).map_err(Error::Config)?;
+            let github = self.github_client(opts.github_token, fs)?;
+            for soft_def in cfg.soft_defs {
+                let soft = (self.convert_soft_fn)(soft_def, &cfg.download);
+                softwares.insert(soft.name().to_string(), soft.version().to_string());
+                let versions = match Self::list_versions(soft.as_ref(), github.as_ref())? {
+                    Some(versions) => versions,
+                    None => continue,
+                };
+                // Pre-releases are only proposed to softwares already pinned to a pre-release.
+                let current = Version::from(soft.version());
+                let newer_versions: Vec<&ReleaseVersion> = versions
+                    .iter()
+                    .filter(|version| {
+                        version.version > current
+                            && (!version.version.is_prerelease() || current.is_prerelease())
+                    })
+                    .collect();
+                let latest = match newer_versions.first() {
+                    Some(latest) => latest,
+                    None => {
+                        debug!("{} v{} is up to date", soft.name(), soft.version());
+                        continue;
+                    }
+                };
+                self.out
+                    .write(&format!(
+                        "{}: {} -> {}\n",
+                        soft.name(),
+                        soft.version(),
+                        latest.version
+                    ))
+                    .map_err(Error::Io)?;
+                if changelog {
+                    for version in &newer_versions {
+                        let changelog = version
+                            .changelog
+                            .as_deref()
+                            .map(str::trim)
+                            .unwrap_or_default();
+                        self.out
+                            .write(&format!("\n## {}\n\n{}\n", version.version, changelog))
+                            .map_err(Error::Io)?;
+                    }
+                    self.out.write("\n").map_err(Error::Io)?;
+                }
+            }
+            Ok(())
+        })();
+        let entry = HistoryEntry {
+            action: Action::Upgrade,
+            at: (self.now_fn)(),
+            cfg_hash: Self::cfg_hash(&cfg_path, fs),
+            duration_ms: timings.total().as_millis() as u64,
+            error: res.as_ref().err().map(ToString::to_string),
+            softwares,
+        };
+        Self::append_history(&entry, &env_dirpath, fs);
+        res
+    }
+
+    #[inline]
+    fn run_versions(&self, name: String, table_opts: TableOptions, opts: Options) -> Result<()> {
+        let no_color = opts.no_color;
+        let fs = (self.create_fs_fn)(opts.denv_dirpath);
+        let fs = fs.as_ref();
+        let cwd = fs.cwd().map_err(Error::Io)?;
+        let cfg_path = Self::cfg_filepath(opts.cfg_filepath, &cwd, fs)?;
+        let cfg = self.cfg_loader.load(&cfg_path).map_err(Error::Config)?;
+        let soft = cfg
+            .soft_defs
+            .into_iter()
+            .map(|soft_def| (self.convert_soft_fn)(soft_def, &cfg.download))
+            .find(|soft| soft.name() == name)
+            .ok_or(Error::SoftwareNotFound(name))?;
+        let github = self.github_client(opts.github_token, fs)?;
+        let versions = Self::list_versions(soft.as_ref(), github.as_ref())?.unwrap_or_default();
+        let current = Version::from(soft.version());
+        let mut table = Table::new(vec!["VERSION", "STATUS"]);
+        for version in versions {
+            let status = if version.version == current {
+                Cell::colored("current", Color::Green)
+            } else {
+                "".into()
+            };
+            table.push(vec![version.version.to_string().into(), status]);
+        }
+        self.write_table(&table, table_opts, no_color)
+    }
+
+    #[inline]
+    fn source_scripts<O: Write>(
+        dirpath: &Path,
+        out: &ShellOutput<O>,
+        fs: &dyn FileSystem,
+    ) -> Result<()> {
+        let paths = fs.list_files(dirpath).map_err(Error::Io)?;
+        if paths.is_empty() {
+            return Ok(());
+        }
+        out.export(DENV_IN_PROGRESS_VAR_NAME, "1")
+            .map_err(Error::Io)?;
+        for path in paths {
+            debug!("Sourcing {}", path.display());
+            out.source(&path).map_err(Error::Io)?;
+        }
+        out.unset(DENV_IN_PROGRESS_VAR_NAME).map_err(Error::Io)
+    }
+
+    // The agent is restarted only if it is not running anymore or if its configuration changed.
+    #[inline]
+    fn start_vault_agent(
+        &self,
+        agent_cfg: &VaultAgentConfig,
+        env_dirpath: &Path,
+        prev_agent: Option<&VaultAgentState>,
+        fs: &dyn FileSystem,
+    ) -> Result<(VarState, VaultAgentState)> {
+        let sink_filepath = env_dirpath.join(VAULT_SINK_FILENAME);
+        let sink_var = VarState {
+            name: VAULT_AGENT_SINK_VAR_NAME.into(),
+            value: sink_filepath.display().to_string(),
+        };
+        let content = vault::agent_config(agent_cfg, &sink_filepath);
+        let cfg_hash = hash::sha256(content.as_bytes());
+        if let Some(agent) = prev_agent {
+            if (self.kill_fn)(agent.pid, 0).is_ok() {
+                if agent.cfg_hash == cfg_hash {
+                    debug!("Vault agent is already running (pid {})", agent.pid);
+                    return Ok((sink_var, agent.clone()));
+                }
+                self.stop_vault_agent(agent);
+            }
+        }
+        let cfg_filepath = env_dirpath.join(VAULT_AGENT_CFG_FILENAME);
+        fs.write(&cfg_filepath, &content).map_err(Error::Io)?;
+        let pid = (self.spawn_fn)(
+            VAULT_BIN_NAME,
+            &vault::agent_args(&cfg_filepath),
+            &env_dirpath.join(VAULT_AGENT_LOG_FILENAME),
+        )
+        .map_err(Error::Io)?;
+        info!("Vault agent started (pid {})", pid);
+        Ok((sink_var, VaultAgentState { cfg_hash, pid }))
+    }
+
+    #[inline]
+    fn stop_vault_agent(&self, agent: &VaultAgentState) {
+        debug!("Stopping Vault agent (pid {})", agent.pid);
+        match (self.kill_fn)(agent.pid, libc::SIGTERM) {
+            Err(err) if err.raw_os_error() != Some(libc::ESRCH) => {
+                warn!("Unable to stop Vault agent (pid {}): {}", agent.pid, err);
+            }
+            _ => {}
+        }
+    }
+
+    // The configuration is trusted by absolute path, so that it is the same from any directory.
+    #[inline]
+    fn trusted_cfg(
+        cfg_filepath: Option<PathBuf>,
+        fs: &dyn FileSystem,
+    ) -> Result<(PathBuf, String)> {
+        let cwd = fs.cwd().map_err(Error::Io)?;
+        let cfg_path = cwd.join(Self::cfg_filepath(cfg_filepath, &cwd, fs)?);
+        let content = fs.read_to_string(&cfg_path).map_err(Error::Io)?;
+        Ok((cfg_path, hash::sha256(content.as_bytes())))
+    }
+
+    // Other projects of the user may use the same versions: they are installed again on their next
+    // load.
+    #[inline]
+    fn uninstall_software(&self, name: &str, version: &str, fs: &dyn FileSystem) -> Result<()> {
+        let soft_dirpath = fs.software_dirpath(name, version).map_err(Error::Io)?;
+        match fs.delete_software_dir(name, version) {
+            Ok(()) => {
+                Receipt::delete(&soft_dirpath, fs).map_err(Error::Io)?;
+                info!("{} v{} uninstalled", name, version);
+                Ok(())
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                debug!("{} v{} is not installed", name, version);
+                Ok(())
+            }
+            Err(err) => Err(Error::Io(err)),
+        }
+    }
+
+    // The configuration is loaded after the write to validate it: the previous content is restored
+    // (or the file deleted if it was created) when it is invalid.
+    #[inline]
+    fn write_cfg(
+        &self,
+        cfg_path: &Path,
+        prev_content: Option<&str>,
+        content: &str,
+        fs: &dyn FileSystem,
+    ) -> Result<Config> {
+        fs.write(cfg_path, content).map_err(Error::Io)?;
+        match self.cfg_loader.load(cfg_path) {
+            Ok(cfg) => {
+                info!("{} updated", cfg_path.display());
+                Ok(cfg)
+            }
+            Err(err) => {
+                match prev_content {
+                    Some(prev_content) => fs.write(cfg_path, prev_content),
+                    None => fs.delete_file(cfg_path),
+                }
+                .map_err(Error::Io)?;
+                Err(Error::Config(err))
+            }
+        }
+    }
+
+    #[inline]
+    fn write_json<T: Serialize>(&self, value: &T) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(value).map_err(|err| Error::Io(io::Error::other(err)))?;
+        self.out.write(&format!("{}\n", json)).map_err(Error::Io)
+    }
+
+    #[inline]
+    fn write_args_shims(env_dirpath: &Path, shims: &[ArgsShim], fs: &dyn FileSystem) -> Result<()> {
+        for shim in shims {
+            let shim_path = env_dirpath.join(&shim.bin_name);
+            let real_path = env_dirpath.join(format!("{}{}", shim.bin_name, REAL_BIN_SUFFIX));
+            let args: Vec<String> = shim
+                .default_args
+                .iter()
+                .map(|arg| shell::quote(arg))
+                .collect();
+            // A symlink linked before default arguments were set must not be written through.
+            match fs.delete_file(&shim_path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(Error::Io(err)),
+            }
+            debug!("Writing shim {}", shim_path.display());
+            let mut file = fs.create_file(&shim_path).map_err(Error::Io)?;
+            write!(
+                file,
+                "#!/bin/sh\nexec {} {} \"$@\"\n",
+                shell::quote(&real_path.to_string_lossy()),
+                args.join(" ")
+            )
+            .map_err(Error::Io)?;
+            fs.make_executable(&shim_path).map_err(Error::Io)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn write_project_shims(cwd: &Path, env_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
+        let bin_dirpath = cwd.join(PROJECT_BIN_DIRPATH);
+        let mut bin_names = HashSet::new();
+        for (bin_name, target) in Self::env_bins(env_dirpath, fs)? {
+            let shim_path = bin_dirpath.join(&bin_name);
+            debug!("Writing shim {}", shim_path.display());
+            let mut file = fs.create_file(&shim_path).map_err(Error::Io)?;
+            write!(
+                file,
+                "#!/bin/sh\nexec {} \"$@\"\n",
+                shell::quote(&target.to_string_lossy())
+            )
+            .map_err(Error::Io)?;
+            fs.make_executable(&shim_path).map_err(Error::Io)?;
+            bin_names.insert(bin_name);
+        }
+        for path in fs.list_files(&bin_dirpath).map_err(Error::Io)? {
+            let stale = path
+                .file_name()
+                .map(|name| {
+                    let name = name.to_string_lossy();
+                    name != GITIGNORE_FILENAME && !bin_names.contains(name.as_ref())
+                })
+                .unwrap_or(false);
+            if stale {
+                debug!("Deleting stale shim {}", path.display());
+                fs.delete_file(&path).map_err(Error::Io)?;
+            }
+        }
+        fs.write(&bin_dirpath.join(GITIGNORE_FILENAME), "*\n")
+            .map_err(Error::Io)
+    }
+
+    #[inline]
+    fn write_table(&self, table: &Table, table_opts: TableOptions, no_color: bool) -> Result<()> {
+        if table_opts.json {
+            return self.write_json(&table.to_json());
+        }
+        let style = Style {
+            color: !no_color && (self.is_terminal_fn)(),
+            header: !table_opts.no_header,
+        };
+        self.out.write(&table.render(style)).map_err(Error::Io)
+    }
+}
+
+impl Default for Runner<Stdout> {
+    fn default() -> Self {
+        Self {
+            args_fn: Box::new(|| env::args().collect()),
+            cancel_token: CancelToken::default(),
+            cfg_loader: Box::new(DefaultConfigLoader),
+            convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+            convert_var_fn: Box::new(VarDefinition::into_var),
+            create_fs_fn: Box::new(|denv_dirpath| Box::new(DefaultFileSystem::new(denv_dirpath))),
+            create_github_client_fn: Box::new(|cache_dirpath, token| {
+                Box::new(DefaultGithubClient::new(cache_dirpath, token))
+            }),
+            elapsed_fn: {
+                let started_at = Instant::now();
+                Box::new(move || started_at.elapsed())
+            },
+            entrypoint_fn: Box::new(proc::entrypoint),
+            env_var_fn: Box::new(|var_name| env::var(var_name)),
+            exec_fn: Box::new(proc::exec),
+            is_root_fn: Box::new(|| unsafe { libc::geteuid() } == 0),
+            is_terminal_fn: Box::new(|| io::stdout().is_terminal()),
+            kill_fn: Box::new(proc::kill),
+            notify_fn: Box::new(notify::send),
+            now_fn: Box::new(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or_default()
+            }),
+            out: ShellOutput::new(io::stdout()),
+            print_msg_fn: Box::new(|msg| eprintln!("{}", msg)),
+            spawn_fn: Box::new(proc::spawn_daemon),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SoftwareStatus {
+    name: String,
+    version: String,
+}
+
+// FUNCTIONS
+
+#[inline]
+fn format_duration_ms(duration_ms: u64) -> String {
+    if duration_ms < 1000 {
+        format!("{} ms", duration_ms)
+    } else {
+        format!("{:.1} s", duration_ms as f64 / 1000.0)
+    }
+}
+
+#[inline]
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if size < 1024 {
+        return format!("{} B", size);
+    }
+    let mut size = size as f64 / 1024.0;
+    let mut unit_idx = 0;
+    while size >= 1024.0 && unit_idx < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit_idx += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit_idx])
+}
+
+// Timestamps are printed in UTC. The date is computed with the days-to-civil algorithm of Howard
+// Hinnant.
+#[inline]
+fn format_timestamp(secs: u64) -> String {
+    let days = secs / DAY_SECS + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_idx = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_idx + 2) / 5 + 1;
+    let month = if month_idx < 10 {
+        month_idx + 3
+    } else {
+        month_idx - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    let secs_of_day = secs % DAY_SECS;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
+
+// Hidden entries of the environment directory are data of softwares (e.g. CARGO_HOME), they are
+// neither binaries nor stale symlinks.
+#[inline]
+fn is_env_bin(path: &Path) -> bool {
+    match path.file_name() {
+        Some(filename) => {
+            filename != STATE_FILENAME
+                && filename != LOAD_LOCK_FILENAME
+                && !filename.to_string_lossy().starts_with('.')
+        }
+        None => false,
+    }
+}
+
+#[inline]
+fn is_lock(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext == LOCK_EXTENSION)
+        .unwrap_or(false)
+}
+
+#[inline]
+fn latency_warning(budget_ms: Option<u64>, timings: &Timings) -> Option<String> {
+    let budget_ms = budget_ms?;
+    let total_ms = timings.total().as_millis();
+    if total_ms <= budget_ms as u128 {
+        return None;
+    }
+    let slowest: Vec<String> = timings
+        .slowest(SLOWEST_SPAN_COUNT)
+        .into_iter()
+        .map(|span| format!("{} ({}ms)", span.label, span.duration.as_millis()))
+        .collect();
+    Some(format!(
+        "Environment loaded in {}ms, over the budget of {}ms (slowest: {})",
+        total_ms,
+        budget_ms,
+        slowest.join(", ")
+    ))
+}
+
+// The project is named after its directory: the notification is read out of the terminal.
+#[inline]
+fn notification(
+    project_dirpath: &Path,
+    duration_ms: u64,
+    res: std::result::Result<&LoadOutcome, &Error>,
+) -> String {
+    let name = project_dirpath
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| project_dirpath.display().to_string());
+    let duration = format_duration_ms(duration_ms);
+    match res {
+        Ok(_) => format!("Environment of {} loaded in {}", name, duration),
+        Err(err) => format!(
+            "Environment of {} failed to load after {}: {}",
+            name, duration, err
+        ),
+    }
+}
+
+// The softwares of the environment come first: they win over the directories of the project.
+#[inline]
+fn path_entries(env_dirpath: &Path, path_defs: &[VarDefinition]) -> (String, String) {
+    let mut prefix = vec![env_dirpath.display().to_string()];
+    let mut suffix = vec![];
+    for path_def in path_defs {
+        match &path_def.kind {
+            VarDefinitionKind::AppendPath(path) => suffix.push(path.display().to_string()),
+            VarDefinitionKind::PrependPath(path) => prefix.push(path.display().to_string()),
+            _ => {}
+        }
+    }
+    (prefix.join(":"), suffix.join(":"))
+}
+
+#[inline]
+fn software_versions(softs: &[SoftwareState]) -> BTreeMap<String, String> {
+    softs
+        .iter()
+        .map(|soft| (soft.name.clone(), soft.version.clone()))
+        .collect()
+}
+
+// TESTS
+
+#[cfg(test)]
+mod error_test {
+    use super::*;
+
+    mod to_string {
+        use super::*;
+
+        mod binary_collision {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "terraform is provided by several softwares: terraform v0.13.0, terraform v1.5.0";
+                let err = Error::BinaryCollision(
+                    "terraform".into(),
+                    vec!["terraform v0.13.0".into(), "terraform v1.5.0".into()],
+                );
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod cancelled {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Loading was cancelled";
+                let err = Error::Cancelled(vec!["var VAR".into()]);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod command_failed {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Command exited with status 2";
+                let err = Error::CommandFailed(2);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod compute {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Unable to compute value of some variables";
+                let err = Error::Compute(vec![]);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod config {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let err = cfg::Error::Version(None);
+                let str = err.to_string();
+                let err = Error::Config(err);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod config_conflict {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Multiple configuration files found: /denv.yml, /.denv.yml";
+                let err = Error::ConfigConflict(vec![
+                    PathBuf::from("/denv.yml"),
+                    PathBuf::from("/.denv.yml"),
+                ]);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod config_not_found {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "No configuration file found (expected one of denv.yml, denv.yaml, .denv.yml, .config/denv.yml)";
+                let err = Error::ConfigNotFound;
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod env_not_loaded {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "No environment loaded";
+                let err = Error::EnvNotLoaded;
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod github {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let err = crate::github::Error::Status(500);
+                let str = err.to_string();
+                let err = Error::Github(err);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod install {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Unable to install some softwares";
+                let err = Error::Install(vec![]);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod invalid_assignment {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "FOO is not a valid variable assignment (expected NAME=VALUE)";
+                let err = Error::InvalidAssignment("FOO".into());
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod io {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let err = ::std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+                let str = err.to_string();
+                let err = Error::Io(err);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod load_in_progress {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Environment is already being loaded";
+                let err = Error::LoadInProgress;
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod lock {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let err = crate::lock::Error::Missing;
+                let str = err.to_string();
+                let err = Error::Lock(err);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod not_allowed {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str =
+                    "/cwd/denv.yml is not allowed, review it then run `denv allow` to load it";
+                let err = Error::NotAllowed(PathBuf::from("/cwd/denv.yml"));
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod root {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Refusing to load environment as root (use --allow-root to override)";
+                let err = Error::Root;
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod software_not_found {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "kubectl is not defined in configuration";
+                let err = Error::SoftwareNotFound("kubectl".into());
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod store {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let err = crate::store::Error::UnsupportedVersion(42);
+                let str = err.to_string();
+                let err = Error::Store(err);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod timeout {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Loading timed out after 30s";
+                let err = Error::Timeout(30, vec!["var VAR".into()]);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod unexpected_argument {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Unexpected argument FOO";
+                let err = Error::UnexpectedArgument("FOO".into());
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod unresolved_version {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "No release of terraform matches ~> 1.5";
+                let err = Error::UnresolvedVersion("terraform".into(), "~> 1.5".into());
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod unsupported_software {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str =
+                    "kind is not a supported software (supported: buf, chart-testing, conftest, dive, grpcurl, helm, java, kubectl, lazydocker, opa, rust, stern, terraform, terragrunt, tinygo, zig)";
+                let err = Error::UnsupportedSoftware("kind".into());
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod var_not_found {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Variable FOO is not defined in configuration";
+                let err = Error::VarNotFound("FOO".into());
+                assert_eq!(err.to_string(), str);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod format_duration_ms_test {
+    use super::*;
+
+    #[test]
+    fn should_return_ms() {
+        assert_eq!(format_duration_ms(999), "999 ms");
+    }
+
+    #[test]
+    fn should_return_secs() {
+        assert_eq!(format_duration_ms(1250), "1.2 s");
+    }
+}
+
+#[cfg(test)]
+mod format_size_test {
+    use super::*;
+
+    #[test]
+    fn should_return_bytes() {
+        assert_eq!(format_size(512), "512 B");
+    }
+
+    #[test]
+    fn should_return_gib() {
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024 / 2), "1.5 GiB");
+    }
+
+    #[test]
+    fn should_return_kib() {
+        assert_eq!(format_size(1024), "1.0 KiB");
+    }
+}
+
+#[cfg(test)]
+mod format_timestamp_test {
+    use super::*;
+
+    #[test]
+    fn should_return_epoch() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00");
+    }
+
+    #[test]
+    fn should_return_date() {
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00");
+        assert_eq!(format_timestamp(1_700_000_000), "2023-11-14 22:13:20");
+        assert_eq!(format_timestamp(4_102_444_799), "2099-12-31 23:59:59");
+    }
+}
+
+#[cfg(test)]
+mod is_env_bin_test {
+    use super::*;
+
+    #[test]
+    fn should_return_false_if_entry_is_hidden() {
+        assert!(!is_env_bin(Path::new("/env/.cargo")));
+    }
+
+    #[test]
+    fn should_return_false_if_entry_is_state() {
+        assert!(!is_env_bin(&Path::new("/env").join(STATE_FILENAME)));
+    }
+
+    #[test]
+    fn should_return_true() {
+        assert!(is_env_bin(Path::new("/env/terraform")));
+    }
+}
+
+#[cfg(test)]
+mod latency_warning_test {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    fn test(budget_ms: Option<u64>, expected: Option<&str>) {
+        let now = AtomicU64::new(0);
+        let elapsed_fn = || Duration::from_millis(now.load(Ordering::SeqCst));
+        let timings = Timings::start(&elapsed_fn);
+        for (label, millis) in [
+            ("config", 120),
+            ("fs scan", 10),
+            ("var VAR", 80),
+            ("software terraform", 40),
+        ] {
+            timings.measure(label.into(), || {
+                now.fetch_add(millis, Ordering::SeqCst);
+            });
+        }
+        assert_eq!(latency_warning(budget_ms, &timings).as_deref(), expected);
+    }
+
+    #[test]
+    fn should_return_none_if_budget_is_not_configured() {
+        test(None, None);
+    }
+
+    #[test]
+    fn should_return_none_if_budget_is_respected() {
+        test(Some(250), None);
+    }
+
+    #[test]
+    fn should_return_slowest_contributors_if_budget_is_exceeded() {
+        test(
+            Some(200),
+            Some("Environment loaded in 250ms, over the budget of 200ms (slowest: config (120ms), var VAR (80ms), software terraform (40ms))"),
+        );
+    }
+}
+
+#[cfg(test)]
+mod load_outcome_test {
+    use super::*;
+
+    mod command {
+        use super::*;
+        use std::ffi::OsString;
+
+        #[test]
+        fn should_return_command_with_env() {
+            let outcome = LoadOutcome {
+                cfg_filepath: PathBuf::from("/cwd/denv.yml"),
+                env_dirpath: PathBuf::from("/env"),
+                path_prefix: "/env:/cwd/bin".into(),
+                path_suffix: "/cwd/scripts".into(),
+                project_dirpath: PathBuf::from("/cwd"),
+                softwares: vec![],
+                statements: String::new(),
+                unset_vars: vec![VarState {
+                    name: "AWS_PROFILE".into(),
+                    value: "default".into(),
+                }],
+                vars: vec![VarState {
+                    name: "VAR".into(),
+                    value: "value".into(),
+                }],
+            };
+            let cmd = outcome.command("terraform");
+            assert_eq!(cmd.get_program(), "terraform");
+            let path = render::join_path(&[
+                "/env:/cwd/bin",
+                &env::var(PATH_VAR_NAME).unwrap_or_default(),
+                "/cwd/scripts",
+            ]);
+            let envs: Vec<(&OsStr, Option<&OsStr>)> = cmd.get_envs().collect();
+            let expected_envs: Vec<(OsString, Option<OsString>)> = vec![
+                ("AWS_PROFILE".into(), None),
+                (PATH_VAR_NAME.into(), Some(path.into())),
+                ("VAR".into(), Some("value".into())),
+            ];
+            let expected_envs: Vec<(&OsStr, Option<&OsStr>)> = expected_envs
+                .iter()
+                .map(|(name, value)| (name.as_os_str(), value.as_deref()))
+                .collect();
+            assert_eq!(envs, expected_envs);
+        }
+    }
+}
+
+#[cfg(test)]
+mod notification_test {
+    use super::*;
+
+    #[test]
+    fn should_return_success_msg() {
+        let outcome = LoadOutcome {
+            cfg_filepath: PathBuf::from("/projects/api/denv.yml"),
+            env_dirpath: PathBuf::from("/env"),
+            path_prefix: "/env".into(),
+            path_suffix: String::new(),
+            project_dirpath: PathBuf::from("/projects/api"),
+            softwares: vec![],
+            statements: String::new(),
+            unset_vars: vec![],
+            vars: vec![],
+        };
+        let msg = notification(Path::new("/projects/api"), 95_000, Ok(&outcome));
+        assert_eq!(msg, "Environment of api loaded in 95.0 s");
+    }
+
+    #[test]
+    fn should_return_failure_msg() {
+        let msg = notification(Path::new("/projects/api"), 1500, Err(&Error::Root));
+        let expected = format!(
+            "Environment of api failed to load after 1.5 s: {}",
+            Error::Root
+        );
+        assert_eq!(msg, expected);
+    }
+}
+
+#[cfg(test)]
+mod runner_test {
+    use super::*;
+    use crate::{
+        cfg::{
+            Config, SoftwareDefinition, SoftwareDefinitionKind, SoftwareSource, StubConfigLoader,
+            VarDefinition, VarDefinitionKind,
+        },
+        fs::StubFileSystem,
+        github::{Release, StubGithubClient},
+        lock::{LockedSoftware, LOCK_FILENAME},
+        release::StubReleaseIndex,
+        soft::{receipt::receipt, StubSoftware},
+        state::{self, STATE_FILENAME},
+        test::WriteFailer,
+        var::StubVar,
+    };
     use std::path::Path;
 
-    mod run {
-        use super::*;
+    mod run {
+        use super::*;
+
+        mod add {
+            use super::*;
+            use std::sync::{Arc, Mutex};
+
+            const CFG_PATH: &str = "/cwd/denv.yml";
+            const CWD: &str = "/cwd";
+
+            #[derive(Default)]
+            struct Writes {
+                deleted: Vec<PathBuf>,
+                written: Vec<(PathBuf, String)>,
+            }
+
+            #[test]
+            fn should_add_software() {
+                test(
+                    "terraform",
+                    "1.6.2",
+                    Some("version: v1\n# Softwares\nsoftwares:\n  chart-testing: 3.7.0\n"),
+                    true,
+                    |writes, res| {
+                        res.unwrap();
+                        let expected = "version: v1\n# Softwares\nsoftwares:\n  chart-testing: 3.7.0\n  terraform: 1.6.2\n";
+                        assert_eq!(writes.written, [(PathBuf::from(CFG_PATH), expected.into())]);
+                    },
+                );
+            }
+
+            #[test]
+            fn should_add_var() {
+                test(
+                    "var",
+                    "FOO=bar=baz",
+                    Some("version: v1\n"),
+                    true,
+                    |writes, res| {
+                        res.unwrap();
+                        let expected = "version: v1\nset:\n  - name: FOO\n    value: bar=baz\n";
+                        assert_eq!(writes.written, [(PathBuf::from(CFG_PATH), expected.into())]);
+                    },
+                );
+            }
+
+            #[test]
+            fn should_create_config() {
+                test("terraform", "1.6.2", None, true, |writes, res| {
+                    res.unwrap();
+                    let expected = "version: v1\nsoftwares:\n  terraform: 1.6.2\n";
+                    assert_eq!(writes.written, [(PathBuf::from(CFG_PATH), expected.into())]);
+                });
+            }
+
+            #[test]
+            fn should_delete_created_config_if_it_is_invalid() {
+                test("terraform", "1.6.2", None, false, |writes, res| {
+                    match res.unwrap_err() {
+                        Error::Config(_) => {}
+                        err => panic!("{}", err),
+                    }
+                    assert_eq!(writes.written.len(), 1);
+                    assert_eq!(writes.deleted, [PathBuf::from(CFG_PATH)]);
+                });
+            }
+
+            #[test]
+            fn should_restore_config_if_it_is_invalid() {
+                let content = "version: v1\n";
+                test("terraform", "1.6.2", Some(content), false, |writes, res| {
+                    match res.unwrap_err() {
+                        Error::Config(_) => {}
+                        err => panic!("{}", err),
+                    }
+                    assert_eq!(writes.written[1], (PathBuf::from(CFG_PATH), content.into()));
+                });
+            }
+
+            #[test]
+            fn should_return_invalid_assignment_err() {
+                test("var", "=bar", Some("version: v1\n"), true, |writes, res| {
+                    match res.unwrap_err() {
+                        Error::InvalidAssignment(assignment) => assert_eq!(assignment, "=bar"),
+                        err => panic!("{}", err),
+                    }
+                    assert!(writes.written.is_empty());
+                });
+            }
+
+            #[test]
+            fn should_return_unsupported_software_err() {
+                test(
+                    "kind",
+                    "3.10.0",
+                    Some("version: v1\n"),
+                    true,
+                    |writes, res| {
+                        match res.unwrap_err() {
+                            Error::UnsupportedSoftware(name) => assert_eq!(name, "kind"),
+                            err => panic!("{}", err),
+                        }
+                        assert!(writes.written.is_empty());
+                    },
+                );
+            }
+
+            #[inline]
+            fn test<F: Fn(Writes, Result<()>)>(
+                name: &str,
+                value: &str,
+                content: Option<&'static str>,
+                valid: bool,
+                assert_fn: F,
+            ) {
+                let writes = Arc::new(Mutex::new(Writes::default()));
+                let mut cfg_loader = StubConfigLoader::default();
+                cfg_loader.stub_load_fn(move |path| {
+                    assert_eq!(path, Path::new(CFG_PATH));
+                    if valid {
+                        Ok(Config {
+                            download: DownloadConfig::default(),
+                            latency_budget_ms: None,
+                            message: None,
+                            notify: None,
+                            project_bin: false,
+                            remove_quarantine: false,
+                            soft_defs: vec![],
+                            unset_vars: vec![],
+                            var_defs: vec![],
+                            vault_agent: None,
+                        })
+                    } else {
+                        Err(cfg::Error::Invalid(vec![]))
+                    }
+                });
+                let runner = Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(cfg_loader),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(VarDefinition::into_var),
+                    create_fs_fn: {
+                        let writes = writes.clone();
+                        Box::new(move |_| {
+                            let mut fs = StubFileSystem::default();
+                            fs.stub_cwd_fn(|| Ok(PathBuf::from(CWD)));
+                            fs.stub_file_exists_fn(move |path| {
+                                content.is_some() && path == Path::new(CFG_PATH)
+                            });
+                            fs.stub_read_to_string_fn(move |path| {
+                                assert_eq!(path, Path::new(CFG_PATH));
+                                Ok(content.unwrap().into())
+                            });
+                            let write_writes = writes.clone();
+                            fs.stub_write_fn(move |path, content| {
+                                let mut writes = write_writes.lock().unwrap();
+                                writes.written.push((path.to_path_buf(), content.into()));
+                                Ok(())
+                            });
+                            let writes = writes.clone();
+                            fs.stub_delete_file_fn(move |path| {
+                                writes.lock().unwrap().deleted.push(path.to_path_buf());
+                                Ok(())
+                            });
+                            Box::new(fs)
+                        })
+                    },
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn: Box::new(|var_name| panic!("unexpected {}", var_name)),
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                };
+                let cmd = Command::Add {
+                    name: name.into(),
+                    value: value.into(),
+                };
+                let res = runner.run(cmd, Options::default());
+                assert!(runner.out.into_inner().is_empty());
+                let writes = std::mem::take(&mut *writes.lock().unwrap());
+                assert_fn(writes, res);
+            }
+        }
+
+        mod allow {
+            use super::*;
+
+            const ALLOW_DIRPATH: &str = "/allow";
+            const CFG_CONTENT: &str = "version: v1";
+            const CFG_PATH: &str = "/cwd/denv.yml";
+            const CWD: &str = "/cwd";
+
+            #[test]
+            fn should_return_config_not_found_err() {
+                let res = test(&[], |fs| {
+                    fs.stub_file_exists_fn(|_| false);
+                });
+                match res.unwrap_err() {
+                    Error::ConfigNotFound => {}
+                    err => panic!("{}", err),
+                }
+            }
+
+            #[test]
+            fn should_replace_allow_file_of_cfg() {
+                test(&["/allow/old"], |fs| {
+                    fs.stub_delete_file_fn(|path| {
+                        assert_eq!(path, Path::new("/allow/old"));
+                        Ok(())
+                    });
+                    fs.stub_write_fn(|path, content| {
+                        let key = format!("{}\n{}", CFG_PATH, hash::sha256(CFG_CONTENT.as_bytes()));
+                        assert_eq!(
+                            path,
+                            Path::new(ALLOW_DIRPATH).join(hash::sha256(key.as_bytes()))
+                        );
+                        assert_eq!(content, CFG_PATH);
+                        Ok(())
+                    });
+                })
+                .unwrap();
+            }
+
+            #[inline]
+            fn test(
+                allow_files: &'static [&'static str],
+                stub_fn: fn(&mut StubFileSystem),
+            ) -> Result<()> {
+                let runner = Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(StubConfigLoader::default()),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(VarDefinition::into_var),
+                    create_fs_fn: Box::new(move |_| {
+                        let mut fs = StubFileSystem::default();
+                        fs.stub_cwd_fn(|| Ok(PathBuf::from(CWD)));
+                        fs.stub_file_exists_fn(|path| path == Path::new(CFG_PATH));
+                        fs.stub_ensure_allow_dir_fn(|| Ok(PathBuf::from(ALLOW_DIRPATH)));
+                        fs.stub_list_files_fn(|dirpath| {
+                            assert_eq!(dirpath, Path::new(ALLOW_DIRPATH));
+                            Ok(allow_files.iter().map(PathBuf::from).collect())
+                        });
+                        fs.stub_read_to_string_fn(|path| {
+                            if path == Path::new(CFG_PATH) {
+                                Ok(CFG_CONTENT.into())
+                            } else {
+                                Ok(CFG_PATH.into())
+                            }
+                        });
+                        stub_fn(&mut fs);
+                        Box::new(fs)
+                    }),
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn: Box::new(|var_name| panic!("unexpected {}", var_name)),
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                };
+                let res = runner.run(Command::Allow, Options::default());
+                assert!(runner.out.into_inner().is_empty());
+                res
+            }
+        }
+
+        mod cache {
+            use super::*;
+            use crate::fs::CachedFile;
+
+            const CACHE_DIRPATH: &str = "/cache";
+
+            #[test]
+            fn should_delete_all_files() {
+                test(
+                    CacheCommand::Clear { older_than: None },
+                    |fs| {
+                        fs.stub_delete_file_fn(|path| {
+                            assert!(
+                                path == Path::new("/cache/old") || path == Path::new("/cache/new")
+                            );
+                            Ok(())
+                        });
+                    },
+                    |out, res| {
+                        res.unwrap();
+                        assert_eq!(out, "Deleted 2 files (2.0 KiB)\n");
+                    },
+                );
+            }
+
+            #[test]
+            fn should_delete_files_older_than() {
+                test(
+                    CacheCommand::Clear {
+                        older_than: Some(100),
+                    },
+                    |fs| {
+                        fs.stub_delete_file_fn(|path| {
+                            assert_eq!(path, Path::new("/cache/old"));
+                            Ok(())
+                        });
+                    },
+                    |out, res| {
+                        res.unwrap();
+                        assert_eq!(out, "Deleted 1 files (1.0 KiB)\n");
+                    },
+                );
+            }
+
+            #[test]
+            fn should_ignore_files_deleted_concurrently() {
+                test(
+                    CacheCommand::Clear { older_than: None },
+                    |fs| {
+                        fs.stub_delete_file_fn(|path| {
+                            if path == Path::new("/cache/old") {
+                                Err(io::Error::from(io::ErrorKind::NotFound))
+                            } else {
+                                Ok(())
+                            }
+                        });
+                    },
+                    |out, res| {
+                        res.unwrap();
+                        assert_eq!(out, "Deleted 1 files (1.0 KiB)\n");
+                    },
+                );
+            }
+
+            #[test]
+            fn should_print_cache_dirpath() {
+                test(
+                    CacheCommand::Dir,
+                    |_| {},
+                    |out, res| {
+                        res.unwrap();
+                        assert_eq!(out, format!("{}\n", CACHE_DIRPATH));
+                    },
+                );
+            }
+
+            #[test]
+            fn should_print_cache_size() {
+                test(
+                    CacheCommand::Size,
+                    |_| {},
+                    |out, res| {
+                        res.unwrap();
+                        assert_eq!(out, "3.0 KiB (5 files)\n");
+                    },
+                );
+            }
+
+            #[test]
+            fn should_return_io_err_if_delete_failed() {
+                test(
+                    CacheCommand::Clear { older_than: None },
+                    |fs| {
+                        fs.stub_delete_file_fn(|_| {
+                            Err(io::Error::from(io::ErrorKind::PermissionDenied))
+                        });
+                    },
+                    |_, res| match res.unwrap_err() {
+                        Error::Io(_) => {}
+                        err => panic!("{}", err),
+                    },
+                );
+            }
+
+            #[inline]
+            fn cached_file(path: &str, modified_at: u64, size: u64) -> CachedFile {
+                CachedFile {
+                    modified_at,
+                    path: PathBuf::from(path),
+                    size,
+                }
+            }
+
+            #[inline]
+            fn test<S: Fn(&mut StubFileSystem) + 'static, F: Fn(String, Result<()>)>(
+                cmd: CacheCommand,
+                stub_fn: S,
+                assert_fn: F,
+            ) {
+                let runner = Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(StubConfigLoader::default()),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(VarDefinition::into_var),
+                    create_fs_fn: Box::new(move |_| {
+                        let mut fs = StubFileSystem::default();
+                        fs.stub_cache_dirpath_fn(|| Ok(PathBuf::from(CACHE_DIRPATH)));
+                        fs.stub_list_cached_files_fn(|| {
+                            Ok(vec![
+                                cached_file("/cache/locked", 0, 512),
+                                cached_file("/cache/locked.lock", 0, 0),
+                                cached_file("/cache/new", 950, 1024),
+                                cached_file("/cache/old", 800, 1024),
+                                cached_file("/cache/other.lock", 0, 512),
+                            ])
+                        });
+                        stub_fn(&mut fs);
+                        Box::new(fs)
+                    }),
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn: Box::new(|var_name| panic!("unexpected {}", var_name)),
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 1000),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                };
+                let res = runner.run(Command::Cache(cmd), Options::default());
+                let out = String::from_utf8(runner.out.into_inner()).unwrap();
+                assert_fn(out, res);
+            }
+        }
+
+        mod config {
+            use super::*;
+
+            const CFG_PATH: &str = "/cwd/denv.yml";
+
+            #[test]
+            fn should_return_config_err() {
+                let mut cfg_loader = StubConfigLoader::default();
+                cfg_loader.stub_load_fn(|_| Err(cfg::Error::Invalid(vec![])));
+                test(ConfigCommand::Validate, cfg_loader, |out, res| {
+                    match res.unwrap_err() {
+                        Error::Config(cfg::Error::Invalid(_)) => {}
+                        err => panic!("{}", err),
+                    }
+                    assert!(out.is_empty());
+                });
+            }
+
+            #[test]
+            fn should_print_cfg_is_valid() {
+                let mut cfg_loader = StubConfigLoader::default();
+                cfg_loader.stub_load_fn(|path| {
+                    assert_eq!(path, Path::new(CFG_PATH));
+                    Ok(Config {
+                        download: DownloadConfig::default(),
+                        latency_budget_ms: None,
+                        message: None,
+                        notify: None,
+                        project_bin: false,
+                        remove_quarantine: false,
+                        soft_defs: vec![],
+                        unset_vars: vec![],
+                        var_defs: vec![],
+                        vault_agent: None,
+                    })
+                });
+                test(ConfigCommand::Validate, cfg_loader, |out, res| {
+                    res.unwrap();
+                    assert_eq!(out, format!("{} is valid\n", CFG_PATH));
+                });
+            }
+
+            #[test]
+            fn should_print_cfg_as_json() {
+                let expected = cfg();
+                test(
+                    ConfigCommand::Show {
+                        format: Format::Json,
+                    },
+                    cfg_loader(),
+                    |out, res| {
+                        res.unwrap();
+                        let cfg: Config = serde_json::from_str(&out).unwrap();
+                        assert_eq!(cfg, expected);
+                    },
+                );
+            }
+
+            #[test]
+            fn should_print_cfg_as_yaml() {
+                let expected = cfg();
+                test(
+                    ConfigCommand::Show {
+                        format: Format::Yaml,
+                    },
+                    cfg_loader(),
+                    |out, res| {
+                        res.unwrap();
+                        let cfg: Config = serde_yaml::from_str(&out).unwrap();
+                        assert_eq!(cfg, expected);
+                    },
+                );
+            }
+
+            #[inline]
+            fn cfg() -> Config {
+                Config {
+                    download: DownloadConfig::default(),
+                    latency_budget_ms: None,
+                    message: Some("Welcome".into()),
+                    notify: None,
+                    project_bin: false,
+                    remove_quarantine: false,
+                    soft_defs: vec![],
+                    unset_vars: vec!["AWS_PROFILE".into()],
+                    var_defs: vec![VarDefinition {
+                        kind: VarDefinitionKind::Literal("bar".into()),
+                        name: "FOO".into(),
+                    }],
+                    vault_agent: None,
+                }
+            }
+
+            #[inline]
+            fn cfg_loader() -> StubConfigLoader {
+                let mut cfg_loader = StubConfigLoader::default();
+                cfg_loader.stub_load_fn(|path| {
+                    assert_eq!(path, Path::new(CFG_PATH));
+                    Ok(cfg())
+                });
+                cfg_loader
+            }
+
+            #[inline]
+            fn test<F: Fn(String, Result<()>)>(
+                cmd: ConfigCommand,
+                cfg_loader: StubConfigLoader,
+                assert_fn: F,
+            ) {
+                let runner = Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(cfg_loader),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(VarDefinition::into_var),
+                    create_fs_fn: Box::new(|_| {
+                        let mut fs = StubFileSystem::default();
+                        fs.stub_cwd_fn(|| Ok(PathBuf::from("/cwd")));
+                        fs.stub_file_exists_fn(|path| path == Path::new(CFG_PATH));
+                        Box::new(fs)
+                    }),
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn: Box::new(|var_name| panic!("unexpected {}", var_name)),
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                };
+                let res = runner.run(Command::Config(cmd), Options::default());
+                let out = String::from_utf8(runner.out.into_inner()).unwrap();
+                assert_fn(out, res);
+            }
+        }
+
+        mod deny {
+            use super::*;
+
+            const ALLOW_DIRPATH: &str = "/allow";
+            const CFG_CONTENT: &str = "version: v1";
+            const CFG_PATH: &str = "/cwd/denv.yml";
+            const CWD: &str = "/cwd";
+
+            #[test]
+            fn should_do_nothing_if_cfg_was_not_allowed() {
+                test(&[], |fs| {
+                    fs.stub_delete_file_fn(|path| {
+                        panic!("unexpected deletion of {}", path.display())
+                    });
+                })
+                .unwrap();
+            }
+
+            #[test]
+            fn should_delete_allow_files_of_cfg() {
+                test(&["/allow/entry"], |fs| {
+                    fs.stub_delete_file_fn(|path| {
+                        assert_eq!(path, Path::new("/allow/entry"));
+                        Ok(())
+                    });
+                })
+                .unwrap();
+            }
+
+            #[inline]
+            fn test(
+                allow_files: &'static [&'static str],
+                stub_fn: fn(&mut StubFileSystem),
+            ) -> Result<()> {
+                let runner = Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(StubConfigLoader::default()),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(VarDefinition::into_var),
+                    create_fs_fn: Box::new(move |_| {
+                        let mut fs = StubFileSystem::default();
+                        fs.stub_cwd_fn(|| Ok(PathBuf::from(CWD)));
+                        fs.stub_file_exists_fn(|path| path == Path::new(CFG_PATH));
+                        fs.stub_ensure_allow_dir_fn(|| Ok(PathBuf::from(ALLOW_DIRPATH)));
+                        fs.stub_list_files_fn(|dirpath| {
+                            assert_eq!(dirpath, Path::new(ALLOW_DIRPATH));
+                            Ok(allow_files.iter().map(PathBuf::from).collect())
+                        });
+                        fs.stub_read_to_string_fn(|path| {
+                            if path == Path::new(CFG_PATH) {
+                                Ok(CFG_CONTENT.into())
+                            } else {
+                                Ok(CFG_PATH.into())
+                            }
+                        });
+                        stub_fn(&mut fs);
+                        Box::new(fs)
+                    }),
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn: Box::new(|var_name| panic!("unexpected {}", var_name)),
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                };
+                let res = runner.run(Command::Deny, Options::default());
+                assert!(runner.out.into_inner().is_empty());
+                res
+            }
+        }
+
+        mod doctor {
+            use super::*;
+
+            #[test]
+            fn should_print_checks() {
+                let runner = Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(StubConfigLoader::default()),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(VarDefinition::into_var),
+                    create_fs_fn: Box::new(|_| {
+                        let mut fs = StubFileSystem::default();
+                        fs.stub_file_exists_fn(|_| true);
+                        fs.stub_home_dirpath_fn(|| Ok(PathBuf::from("/home/user")));
+                        Box::new(fs)
+                    }),
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn: Box::new(|var_name| panic!("unexpected {}", var_name)),
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                };
+                let cmd = Command::Doctor {
+                    table_opts: TableOptions {
+                        json: true,
+                        no_header: false,
+                    },
+                };
+                runner.run(cmd, Options::default()).unwrap();
+                let out = runner.out.into_inner();
+                let checks: Vec<serde_json::Value> = serde_json::from_slice(&out).unwrap();
+                let names: Vec<&str> = checks
+                    .iter()
+                    .map(|check| check["check"].as_str().unwrap())
+                    .collect();
+                assert_eq!(names, ["libc", "dns", "tls", "home"]);
+                assert_eq!(checks[3]["detail"], "/home/user");
+            }
+        }
+
+        mod entrypoint {
+            use super::*;
+
+            const CFG_PATH: &str = "/cwd/denv.yml";
+            const CWD: &str = "/cwd";
+            const ENV_DIRPATH: &str = "/env";
+
+            #[test]
+            fn should_return_io_err_if_exec_failed() {
+                test(
+                    Box::new(|_, _, _| Err(io::Error::from(io::ErrorKind::NotFound))),
+                    |res| match res.unwrap_err() {
+                        Error::Io(_) => {}
+                        err => panic!("{}", err),
+                    },
+                );
+            }
+
+            #[test]
+            fn should_return_command_failed_err() {
+                test(Box::new(|_, _, _| Ok(143)), |res| match res.unwrap_err() {
+                    Error::CommandFailed(143) => {}
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_run_cmd_without_control_vars() {
+                test(
+                    Box::new(|cmd, vars, unset_vars| {
+                        assert_eq!(cmd, ["terraform", "plan"]);
+                        assert_eq!(unset_vars, ["AWS_PROFILE"]);
+                        let expected_vars: Vec<(String, String)> = [
+                            (PATH_VAR_NAME, format!("{}:/path", ENV_DIRPATH)),
+                            ("VAR1", "value1".into()),
+                        ]
+                        .into_iter()
+                        .map(|(name, value)| (name.into(), value))
+                        .collect();
+                        assert_eq!(vars, expected_vars);
+                        Ok(0)
+                    }),
+                    |res| res.unwrap(),
+                );
+            }
+
+            #[inline]
+            fn test<F: Fn(Result<()>)>(entrypoint_fn: Box<EntrypointFn>, assert_fn: F) {
+                let mut cfg_loader = StubConfigLoader::default();
+                cfg_loader.stub_load_content_fn(|path, _, _| {
+                    assert_eq!(path, Path::new(CFG_PATH));
+                    Ok(Config {
+                        download: DownloadConfig::default(),
+                        latency_budget_ms: None,
+                        message: None,
+                        notify: None,
+                        project_bin: false,
+                        remove_quarantine: false,
+                        soft_defs: vec![],
+                        unset_vars: vec!["AWS_PROFILE".into()],
+                        var_defs: vec![VarDefinition {
+                            kind: VarDefinitionKind::Literal("value1".into()),
+                            name: "VAR1".into(),
+                        }],
+                        vault_agent: None,
+                    })
+                });
+                let runner = Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(cfg_loader),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(|var_def| {
+                        let mut var = StubVar::default();
+                        var.stub_compute_values_fn(|| Ok(vec![("VAR1".into(), "value1".into())]));
+                        var.stub_name_fn(move || "VAR1");
+                        assert_eq!(var_def.name, "VAR1");
+                        Box::new(var)
+                    }),
+                    create_fs_fn: Box::new(|_| {
+                        let mut fs = StubFileSystem::default();
+                        fs.stub_store_version_fn(|| Ok(Some(STORE_VERSION)));
+                        fs.stub_cwd_fn(|| Ok(PathBuf::from(CWD)));
+                        fs.stub_file_exists_fn(|path| path == Path::new(CFG_PATH));
+                        fs.stub_ensure_env_dir_fn(|_| Ok(PathBuf::from(ENV_DIRPATH)));
+                        fs.stub_create_new_fn(|_, _| Ok(()));
+                        fs.stub_read_to_string_fn(|_| Ok(String::new()));
+                        fs.stub_write_fn(|_, _| Ok(()));
+                        fs.stub_append_fn(|_, _| Ok(()));
+                        fs.stub_delete_file_fn(|_| Ok(()));
+                        fs.stub_list_files_fn(|_| Ok(vec![]));
+                        Box::new(fs)
+                    }),
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn,
+                    env_var_fn: Box::new(|var_name| match var_name {
+                        "AWS_PROFILE" => Ok("default".into()),
+                        PATH_VAR_NAME => Ok("/path".into()),
+                        _ => Err(env::VarError::NotPresent),
+                    }),
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                };
+                let cmd = Command::Entrypoint {
+                    cmd: vec!["terraform".into(), "plan".into()],
+                };
+                let res = runner.run(cmd, Options::default());
+                assert!(runner.out.into_inner().is_empty());
+                assert_fn(res);
+            }
+        }
+
+        mod env_inspect {
+            use super::*;
+
+            struct Data {
+                cwd: &'static Path,
+                env_dirpath: &'static Path,
+                state_exists: bool,
+            }
+
+            impl Default for Data {
+                fn default() -> Self {
+                    Self {
+                        cwd: Path::new("/cwd"),
+                        env_dirpath: Path::new("/env"),
+                        state_exists: true,
+                    }
+                }
+            }
+
+            #[test]
+            fn should_return_env_not_loaded_err_if_var_is_missing() {
+                let data = Data::default();
+                test(
+                    &data,
+                    Format::Json,
+                    Box::new(|_| Err(env::VarError::NotPresent)),
+                    |_, res| match res.unwrap_err() {
+                        Error::EnvNotLoaded => {}
+                        err => panic!("{}", err),
+                    },
+                );
+            }
+
+            #[test]
+            fn should_return_env_not_loaded_err_if_state_is_missing() {
+                let data = Data {
+                    state_exists: false,
+                    ..Data::default()
+                };
+                test(&data, Format::Json, env_var_fn(&data), |_, res| {
+                    match res.unwrap_err() {
+                        Error::EnvNotLoaded => {}
+                        err => panic!("{}", err),
+                    }
+                });
+            }
+
+            #[test]
+            fn should_return_json() {
+                let data = Data::default();
+                test(&data, Format::Json, env_var_fn(&data), |out, res| {
+                    res.unwrap();
+                    let state: State = serde_json::from_slice(&out).unwrap();
+                    assert_eq!(state, expected_state(&data));
+                });
+            }
+
+            #[test]
+            fn should_return_yaml() {
+                let data = Data::default();
+                test(&data, Format::Yaml, env_var_fn(&data), |out, res| {
+                    res.unwrap();
+                    let state: State = serde_yaml::from_slice(&out).unwrap();
+                    assert_eq!(state, expected_state(&data));
+                });
+            }
+
+            #[inline]
+            fn env_var_fn(data: &Data) -> Box<EnvVarFn> {
+                let cwd = data.cwd;
+                Box::new(move |var_name| match var_name {
+                    DENV_CWD_VAR_NAME => Ok(cwd.display().to_string()),
+                    _ => panic!("unexpected {}", var_name),
+                })
+            }
+
+            #[inline]
+            fn expected_state(data: &Data) -> State {
+                state::state(data.cwd, &data.cwd.join("denv.yml"))
+            }
+
+            #[inline]
+            fn test<F: Fn(Vec<u8>, Result<()>)>(
+                data: &Data,
+                format: Format,
+                env_var_fn: Box<EnvVarFn>,
+                assert_fn: F,
+            ) {
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let state_exists = data.state_exists;
+                let runner = Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(StubConfigLoader::default()),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(VarDefinition::into_var),
+                    create_fs_fn: Box::new(move |_| {
+                        let mut fs = StubFileSystem::default();
+                        fs.stub_ensure_env_dir_fn(move |project_dirpath| {
+                            assert_eq!(project_dirpath, cwd);
+                            Ok(env_dirpath.to_path_buf())
+                        });
+                        fs.stub_file_exists_fn(move |path| {
+                            assert_eq!(path, env_dirpath.join(STATE_FILENAME));
+                            state_exists
+                        });
+                        fs.stub_read_to_string_fn(move |_| {
+                            let state = state::state(cwd, &cwd.join("denv.yml"));
+                            Ok(serde_json::to_string(&state).unwrap())
+                        });
+                        Box::new(fs)
+                    }),
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn,
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                };
+                let res = runner.run(
+                    Command::Env(EnvCommand::Inspect { format }),
+                    Options::default(),
+                );
+                let out = runner.out.into_inner();
+                assert_fn(out, res);
+            }
+        }
+
+        mod exec {
+            use super::*;
+
+            const CFG_PATH: &str = "/cwd/denv.yml";
+            const CWD: &str = "/cwd";
+            const ENV_DIRPATH: &str = "/env";
+
+            #[test]
+            fn should_return_io_err_if_exec_failed() {
+                test(
+                    false,
+                    Box::new(|_, _, _, _| Err(io::Error::from(io::ErrorKind::NotFound))),
+                    |res| match res.unwrap_err() {
+                        Error::Io(_) => {}
+                        err => panic!("{}", err),
+                    },
+                );
+            }
+
+            #[test]
+            fn should_return_command_failed_err() {
+                test(false, Box::new(|_, _, _, _| Ok(2)), |res| {
+                    match res.unwrap_err() {
+                        Error::CommandFailed(2) => {}
+                        err => panic!("{}", err),
+                    }
+                });
+            }
+
+            #[test]
+            fn should_run_cmd_with_current_env() {
+                test(
+                    false,
+                    Box::new(|cmd, vars, unset_vars, clear_env| {
+                        assert_eq!(cmd, ["terraform", "plan"]);
+                        assert_eq!(unset_vars, ["AWS_PROFILE"]);
+                        assert_eq!(vars, expected_vars(vec![], "/path"));
+                        assert!(!clear_env);
+                        Ok(0)
+                    }),
+                    |res| res.unwrap(),
+                );
+            }
+
+            #[test]
+            fn should_run_cmd_with_pure_env() {
+                test(
+                    true,
+                    Box::new(|cmd, vars, unset_vars, clear_env| {
+                        assert_eq!(cmd, ["terraform", "plan"]);
+                        assert_eq!(unset_vars, ["AWS_PROFILE"]);
+                        let expected_vars = expected_vars(vec![("HOME", "/home/user")], PURE_PATH);
+                        assert_eq!(vars, expected_vars);
+                        assert!(clear_env);
+                        Ok(0)
+                    }),
+                    |res| res.unwrap(),
+                );
+            }
+
+            #[inline]
+            fn expected_vars(base: Vec<(&str, &str)>, path: &str) -> Vec<(String, String)> {
+                base.into_iter()
+                    .chain([
+                        (DENV_CWD_VAR_NAME, CWD),
+                        (DENV_CFG_FILE_VAR_NAME, CFG_PATH),
+                        (PATH_VAR_NAME, &format!("{}:{}", ENV_DIRPATH, path)),
+                        ("VAR1", "value1"),
+                    ])
+                    .map(|(name, value)| (name.into(), value.into()))
+                    .collect()
+            }
+
+            #[inline]
+            fn test<F: Fn(Result<()>)>(pure: bool, exec_fn: Box<ExecFn>, assert_fn: F) {
+                let mut cfg_loader = StubConfigLoader::default();
+                cfg_loader.stub_load_content_fn(|path, _, _| {
+                    assert_eq!(path, Path::new(CFG_PATH));
+                    Ok(Config {
+                        download: DownloadConfig::default(),
+                        latency_budget_ms: None,
+                        message: None,
+                        notify: None,
+                        project_bin: false,
+                        remove_quarantine: false,
+                        soft_defs: vec![],
+                        unset_vars: vec!["AWS_PROFILE".into()],
+                        var_defs: vec![VarDefinition {
+                            kind: VarDefinitionKind::Literal("value1".into()),
+                            name: "VAR1".into(),
+                        }],
+                        vault_agent: None,
+                    })
+                });
+                let runner = Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(cfg_loader),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(|var_def| {
+                        let mut var = StubVar::default();
+                        var.stub_compute_values_fn(|| Ok(vec![("VAR1".into(), "value1".into())]));
+                        var.stub_name_fn(move || "VAR1");
+                        assert_eq!(var_def.name, "VAR1");
+                        Box::new(var)
+                    }),
+                    create_fs_fn: Box::new(|_| {
+                        let mut fs = StubFileSystem::default();
+                        fs.stub_store_version_fn(|| Ok(Some(STORE_VERSION)));
+                        fs.stub_cwd_fn(|| Ok(PathBuf::from(CWD)));
+                        fs.stub_file_exists_fn(|path| path == Path::new(CFG_PATH));
+                        fs.stub_ensure_env_dir_fn(|_| Ok(PathBuf::from(ENV_DIRPATH)));
+                        fs.stub_create_new_fn(|_, _| Ok(()));
+                        fs.stub_read_to_string_fn(|_| Ok(String::new()));
+                        fs.stub_write_fn(|_, _| Ok(()));
+                        fs.stub_append_fn(|_, _| Ok(()));
+                        fs.stub_delete_file_fn(|_| Ok(()));
+                        fs.stub_list_files_fn(|_| Ok(vec![]));
+                        Box::new(fs)
+                    }),
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn: Box::new(|var_name| match var_name {
+                        "AWS_PROFILE" => Ok("default".into()),
+                        "HOME" => Ok("/home/user".into()),
+                        PATH_VAR_NAME => Ok("/path".into()),
+                        _ => Err(env::VarError::NotPresent),
+                    }),
+                    exec_fn,
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                };
+                let cmd = Command::Exec {
+                    cmd: vec!["terraform".into(), "plan".into()],
+                    pure,
+                };
+                let res = runner.run(cmd, Options::default());
+                assert!(runner.out.into_inner().is_empty());
+                assert_fn(res);
+            }
+        }
+
+        mod export {
+            use super::*;
+
+            const CFG_PATH: &str = "/cwd/denv.yml";
+            const CWD: &str = "/cwd";
+            const ENV_DIRPATH: &str = "/env";
+            const SETTINGS_PATH: &str = "/cwd/.vscode/settings.json";
+
+            #[test]
+            fn should_merge_vscode_settings() {
+                let settings_file = Arc::new(tempfile::NamedTempFile::new().unwrap());
+                let mut cfg_loader = StubConfigLoader::default();
+                cfg_loader.stub_load_content_fn(|_, _, _| {
+                    Ok(Config {
+                        download: DownloadConfig::default(),
+                        latency_budget_ms: None,
+                        message: None,
+                        notify: None,
+                        project_bin: false,
+                        remove_quarantine: false,
+                        soft_defs: vec![],
+                        unset_vars: vec!["AWS_PROFILE".into()],
+                        var_defs: vec![VarDefinition {
+                            kind: VarDefinitionKind::Literal("value1".into()),
+                            name: "VAR1".into(),
+                        }],
+                        vault_agent: None,
+                    })
+                });
+                let runner = Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(cfg_loader),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(|_| {
+                        let mut var = StubVar::default();
+                        var.stub_compute_values_fn(|| Ok(vec![("VAR1".into(), "value1".into())]));
+                        var.stub_name_fn(move || "VAR1");
+                        Box::new(var)
+                    }),
+                    create_fs_fn: {
+                        let settings_file = settings_file.clone();
+                        Box::new(move |_| {
+                            let mut fs = StubFileSystem::default();
+                            fs.stub_store_version_fn(|| Ok(Some(STORE_VERSION)));
+                            fs.stub_cwd_fn(|| Ok(PathBuf::from(CWD)));
+                            fs.stub_file_exists_fn(|path| {
+                                path == Path::new(CFG_PATH) || path == Path::new(SETTINGS_PATH)
+                            });
+                            fs.stub_ensure_env_dir_fn(|_| Ok(PathBuf::from(ENV_DIRPATH)));
+                            fs.stub_create_new_fn(|_, _| Ok(()));
+                            fs.stub_read_to_string_fn(|path| {
+                                if path == Path::new(SETTINGS_PATH) {
+                                    Ok(r#"{"editor.tabSize": 2}"#.into())
+                                } else {
+                                    Ok(String::new())
+                                }
+                            });
+                            fs.stub_write_fn(|_, _| Ok(()));
+                            fs.stub_append_fn(|_, _| Ok(()));
+                            fs.stub_delete_file_fn(|_| Ok(()));
+                            fs.stub_list_files_fn(|dirpath| {
+                                if dirpath == Path::new(ENV_DIRPATH) {
+                                    Ok(vec![
+                                        dirpath.join(STATE_FILENAME),
+                                        dirpath.join("terraform"),
+                                    ])
+                                } else {
+                                    Ok(vec![])
+                                }
+                            });
+                            fs.stub_quarantined_fn(|_| false);
+                            fs.stub_canonicalize_fn(|path| {
+                                assert_eq!(path, Path::new(ENV_DIRPATH).join("terraform"));
+                                Ok(PathBuf::from("/softwares/terraform/1.2.3/terraform"))
+                            });
+                            let settings_file = settings_file.clone();
+                            fs.stub_create_file_fn(move |path| {
+                                assert_eq!(path, Path::new(SETTINGS_PATH));
+                                settings_file.reopen()
+                            });
+                            Box::new(fs)
+                        })
+                    },
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn: Box::new(|var_name| match var_name {
+                        "AWS_PROFILE" => Ok("default".into()),
+                        _ => Err(env::VarError::NotPresent),
+                    }),
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                };
+                runner
+                    .run(Command::Export(ExportCommand::Vscode), Options::default())
+                    .unwrap();
+                assert!(runner.out.into_inner().is_empty());
+                let settings = std::fs::read_to_string(settings_file.path()).unwrap();
+                let settings: serde_json::Value = serde_json::from_str(&settings).unwrap();
+                let terminal_env_key = format!(
+                    "terminal.integrated.env.{}",
+                    match env::consts::OS {
+                        "macos" => "osx",
+                        "windows" => "windows",
+                        _ => "linux",
+                    }
+                );
+                assert_eq!(settings["editor.tabSize"], 2);
+                assert_eq!(
+                    settings[&terminal_env_key]["AWS_PROFILE"],
+                    serde_json::Value::Null
+                );
+                assert_eq!(settings[&terminal_env_key]["VAR1"], "value1");
+                assert_eq!(
+                    settings[&terminal_env_key][PATH_VAR_NAME],
+                    format!("{}:{}", ENV_DIRPATH, VSCODE_PATH_EXPR)
+                );
+                assert_eq!(
+                    settings["terraform.languageServer.terraform.path"],
+                    "/softwares/terraform/1.2.3/terraform"
+                );
+            }
+
+            #[test]
+            fn should_print_sh_statements() {
+                let mut cfg_loader = StubConfigLoader::default();
+                cfg_loader.stub_load_content_fn(|_, _, _| {
+                    Ok(Config {
+                        download: DownloadConfig::default(),
+                        latency_budget_ms: None,
+                        message: None,
+                        notify: None,
+                        project_bin: false,
+                        remove_quarantine: false,
+                        soft_defs: vec![],
+                        unset_vars: vec!["AWS_PROFILE".into()],
+                        var_defs: vec![VarDefinition {
+                            kind: VarDefinitionKind::Literal("value1".into()),
+                            name: "VAR1".into(),
+                        }],
+                        vault_agent: None,
+                    })
+                });
+                let runner = Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(cfg_loader),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(|_| {
+                        let mut var = StubVar::default();
+                        var.stub_compute_values_fn(|| Ok(vec![("VAR1".into(), "value1".into())]));
+                        var.stub_name_fn(move || "VAR1");
+                        Box::new(var)
+                    }),
+                    create_fs_fn: Box::new(|_| {
+                        let mut fs = StubFileSystem::default();
+                        fs.stub_store_version_fn(|| Ok(Some(STORE_VERSION)));
+                        fs.stub_cwd_fn(|| Ok(PathBuf::from(CWD)));
+                        fs.stub_file_exists_fn(|path| path == Path::new(CFG_PATH));
+                        fs.stub_ensure_env_dir_fn(|_| Ok(PathBuf::from(ENV_DIRPATH)));
+                        fs.stub_create_new_fn(|_, _| Ok(()));
+                        fs.stub_read_to_string_fn(|_| Ok(String::new()));
+                        fs.stub_write_fn(|_, _| Ok(()));
+                        fs.stub_append_fn(|_, _| Ok(()));
+                        fs.stub_delete_file_fn(|_| Ok(()));
+                        fs.stub_list_files_fn(|_| Ok(vec![]));
+                        Box::new(fs)
+                    }),
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn: Box::new(|var_name| match var_name {
+                        "AWS_PROFILE" => Ok("default".into()),
+                        _ => Err(env::VarError::NotPresent),
+                    }),
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                };
+                runner
+                    .run(Command::Export(ExportCommand::Sh), Options::default())
+                    .unwrap();
+                let out = String::from_utf8(runner.out.into_inner()).unwrap();
+                let expected_out = format!(
+                    "{cwd_var}='{CWD}'; export {cwd_var}\n\
+                     {cfg_var}='{CFG_PATH}'; export {cfg_var}\n\
+                     PATH='{ENV_DIRPATH}':\"${{PATH}}\"; export PATH\n\
+                     VAR1='value1'; export VAR1\n\
+                     unset AWS_PROFILE\n",
+                    cwd_var = DENV_CWD_VAR_NAME,
+                    cfg_var = DENV_CFG_FILE_VAR_NAME,
+                );
+                assert_eq!(out, expected_out);
+            }
+        }
+
+        mod history {
+            use super::*;
+
+            const ENV_DIRPATH: &str = "/env";
+
+            #[test]
+            fn should_print_entries() {
+                test(TableOptions::default(), |out| {
+                    let expected_out = "DATE                 ACTION  DURATION  CONFIG    SOFTWARES        RESULT\n\
+                                            2023-11-14 22:13:20  load    1.5 s     abcdef01  terraform 1.5.7  ok\n\
+                                            2023-11-14 22:15:00  unload  12 ms     -                          Permission denied\n";
+                    assert_eq!(out, expected_out);
+                });
+            }
+
+            #[test]
+            fn should_print_entries_as_json() {
+                test(
+                    TableOptions {
+                        json: true,
+                        no_header: false,
+                    },
+                    |out| {
+                        let entries: Vec<serde_json::Value> = serde_json::from_str(&out).unwrap();
+                        assert_eq!(entries.len(), 2);
+                        assert_eq!(entries[0]["date"], "2023-11-14 22:13:20");
+                        assert_eq!(entries[1]["result"], "Permission denied");
+                    },
+                );
+            }
+
+            #[inline]
+            fn test<F: Fn(String)>(table_opts: TableOptions, assert_fn: F) {
+                let runner = Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(StubConfigLoader::default()),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(VarDefinition::into_var),
+                    create_fs_fn: Box::new(|_| {
+                        let mut fs = StubFileSystem::default();
+                        fs.stub_cwd_fn(|| Ok(PathBuf::from("/cwd")));
+                        fs.stub_ensure_env_dir_fn(|project_dirpath| {
+                            assert_eq!(project_dirpath, Path::new("/cwd"));
+                            Ok(PathBuf::from(ENV_DIRPATH))
+                        });
+                        fs.stub_file_exists_fn(|path| {
+                            path == crate::history::filepath(Path::new(ENV_DIRPATH))
+                        });
+                        fs.stub_read_to_string_fn(|_| {
+                            let entries = [
+                                HistoryEntry {
+                                    action: Action::Load,
+                                    at: 1_700_000_000,
+                                    cfg_hash: Some("abcdef0123456789".into()),
+                                    duration_ms: 1500,
+                                    error: None,
+                                    softwares: BTreeMap::from([(
+                                        "terraform".into(),
+                                        "1.5.7".into(),
+                                    )]),
+                                },
+                                HistoryEntry {
+                                    action: Action::Unload,
+                                    at: 1_700_000_100,
+                                    cfg_hash: None,
+                                    duration_ms: 12,
+                                    error: Some("Permission denied".into()),
+                                    softwares: BTreeMap::new(),
+                                },
+                            ];
+                            Ok(entries
+                                .iter()
+                                .map(|entry| format!("{}\n", serde_json::to_string(entry).unwrap()))
+                                .collect())
+                        });
+                        Box::new(fs)
+                    }),
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn: Box::new(|var_name| panic!("unexpected {}", var_name)),
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                };
+                runner
+                    .run(Command::History { table_opts }, Options::default())
+                    .unwrap();
+                let out = String::from_utf8(runner.out.into_inner()).unwrap();
+                assert_fn(out);
+            }
+        }
+
+        mod hook {
+            use super::*;
+
+            macro_rules! tests {
+                ($ident:ident, $shell:expr, $template:literal) => {
+                    mod $ident {
+                        use super::*;
+
+                        #[test]
+                        fn should_return_io_err() {
+                            test(
+                                $shell,
+                                WriteFailer,
+                                vec!["denv".into(), "hook".into(), stringify!($ident).into()],
+                                |_, res| {
+                                    let err = res.unwrap_err();
+                                    match err {
+                                        Error::Io(_) => {}
+                                        err => panic!("{}", err),
+                                    }
+                                },
+                            );
+                        }
+
+                        #[test]
+                        fn should_return_ok_with_opts() {
+                            let opt1 = "-vvvv";
+                            let opt2 = "--no-color";
+                            let args = vec![
+                                "denv".into(),
+                                opt1.into(),
+                                opt2.into(),
+                                "hook".into(),
+                                stringify!($ident).into(),
+                            ];
+                            test($shell, vec![], args.clone(), |out, res| {
+                                let cli = format!("{} {} {}", args[0], opt1, opt2);
+                                verify(out, res, cli, include_str!($template));
+                            });
+                        }
+
+                        #[test]
+                        fn should_return_ok_with_single_opt() {
+                            let opt = "--no-color";
+                            let args = vec![
+                                "denv".into(),
+                                opt.into(),
+                                "hook".into(),
+                                stringify!($ident).into(),
+                            ];
+                            test($shell, vec![], args.clone(), |out, res| {
+                                let cli = format!("{} {}", args[0], opt);
+                                verify(out, res, cli, include_str!($template));
+                            });
+                        }
+
+                        #[test]
+                        fn should_return_ok_without_opts() {
+                            let args =
+                                vec!["denv".into(), "hook".into(), stringify!($ident).into()];
+                            test($shell, vec![], args.clone(), |out, res| {
+                                verify(out, res, args[0].clone(), include_str!($template));
+                            });
+                        }
+
+                        #[test]
+                        fn should_return_ok_with_auto_install_check() {
+                            let args = vec![
+                                "denv".into(),
+                                "hook".into(),
+                                stringify!($ident).into(),
+                                "--auto-install-check".into(),
+                            ];
+                            let runner = runner(vec![], args.clone());
+                            let cmd = Command::Hook {
+                                auto_install_check: true,
+                                shell: $shell,
+                            };
+                            let res = runner.run(cmd, Options::default());
+                            let out = String::from_utf8(runner.out.into_inner()).unwrap();
+                            res.unwrap();
+                            let preamble = include_str!("../resources/main/hooks/check-version")
+                                .replace("<denv_cmd>", "denv")
+                                .replace("<version>", DENV_VERSION);
+                            let statement = render_template(
+                                include_str!($template),
+                                "denv",
+                                CHECK_VERSION_FN_NAME,
+                            );
+                            assert_eq!(out, format!("{}{}", preamble, statement));
+                        }
+                    }
+                };
+            }
+
+            tests!(bash, Shell::Bash, "../resources/main/hooks/bash");
+            tests!(zsh, Shell::Zsh, "../resources/main/hooks/zsh");
+
+            #[test]
+            fn should_check_version_once_per_shell() {
+                let dirpath = tempfile::tempdir().unwrap();
+                let calls_filepath = dirpath.path().join("calls");
+                let preamble = include_str!("../resources/main/hooks/check-version")
+                    .replace("<denv_cmd>", "fake_denv")
+                    .replace("<version>", DENV_VERSION);
+                let script = format!(
+                    "fake_denv() {{ echo >> '{}'; echo 'D-Env 0.0.0'; }}\n{}{}\n{}\n",
+                    calls_filepath.display(),
+                    preamble,
+                    CHECK_VERSION_FN_NAME,
+                    CHECK_VERSION_FN_NAME,
+                );
+                let output = std::process::Command::new("bash")
+                    .arg("-c")
+                    .arg(script)
+                    .output()
+                    .unwrap();
+                assert!(output.status.success());
+                let stderr = String::from_utf8(output.stderr).unwrap();
+                assert_eq!(stderr.lines().count(), 1, "{}", stderr);
+                assert!(stderr.contains("D-Env 0.0.0 is installed"), "{}", stderr);
+                assert_eq!(std::fs::read_to_string(&calls_filepath).unwrap(), "\n");
+            }
+
+            // macOS ships bash 3.2: the bash hook must not rely on features added since.
+            #[test]
+            fn should_not_use_bash_4_features() {
+                let hook = [
+                    include_str!("../resources/main/hooks/check-version"),
+                    include_str!("../resources/main/hooks/bash"),
+                ]
+                .concat();
+                let features = [
+                    "declare -A",
+                    "local -A",
+                    "^}",
+                    ",}",
+                    "mapfile",
+                    "readarray",
+                    "|&",
+                    "&>>",
+                    ";;&",
+                    "coproc",
+                    "[[ -v",
+                ];
+                for feature in features {
+                    assert!(!hook.contains(feature), "{}", feature);
+                }
+            }
+
+            #[inline]
+            fn render_template(template: &str, cli: &str, check_version_cmd: &str) -> String {
+                template
+                    .replace("<check_version_cmd>", check_version_cmd)
+                    .replace("<denv_cwd_var_name>", DENV_CWD_VAR_NAME)
+                    .replace("<load_cmd>", &format!("{} load", cli))
+                    .replace("<unload_cmd>", &format!("{} unload", cli))
+            }
+
+            #[inline]
+            fn runner<W: Write>(out: W, args: Vec<String>) -> Runner<W> {
+                Runner {
+                    args_fn: Box::new(move || args.clone()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(StubConfigLoader::default()),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(VarDefinition::into_var),
+                    create_fs_fn: Box::new(|_| Box::new(StubFileSystem::default())),
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn: Box::new(|var_name| env::var(var_name)),
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(out),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                }
+            }
+
+            #[inline]
+            fn test<W: Write, F: Fn(W, Result<()>)>(
+                shell: Shell,
+                out: W,
+                args: Vec<String>,
+                assert_fn: F,
+            ) {
+                let runner = runner(out, args);
+                let cmd = Command::Hook {
+                    auto_install_check: false,
+                    shell,
+                };
+                let res = runner.run(cmd, Options::default());
+                let out = runner.out.into_inner();
+                assert_fn(out, res);
+            }
+
+            #[inline]
+            fn verify(out: Vec<u8>, res: Result<()>, cli: String, template: &str) {
+                res.unwrap();
+                let out = String::from_utf8(out).unwrap();
+                assert_eq!(out, render_template(template, &cli, ":"));
+            }
+        }
+
+        mod list {
+            use super::*;
+            use crate::soft::receipt;
+
+            const CWD: &str = "/cwd";
+            const ENV_DIRPATH: &str = "/env";
+            const SOFT_DIRPATH: &str = "/softwares/soft/1.0.0";
+
+            #[test]
+            fn should_return_env_not_loaded_err() {
+                test(
+                    TableOptions::default(),
+                    true,
+                    Box::new(|_| Err(env::VarError::NotPresent)),
+                    |_, res| match res.unwrap_err() {
+                        Error::EnvNotLoaded => {}
+                        err => panic!("{}", err),
+                    },
+                );
+            }
+
+            #[test]
+            fn should_return_json() {
+                let table_opts = TableOptions {
+                    json: true,
+                    no_header: false,
+                };
+                test(table_opts, true, env_var_fn(), |out, res| {
+                    res.unwrap();
+                    let softs: serde_json::Value = serde_json::from_slice(&out).unwrap();
+                    let expected = serde_json::json!([{
+                        "name": "soft",
+                        "receipt": receipt::receipt(),
+                        "version": "1.0.0",
+                    }]);
+                    assert_eq!(softs, expected);
+                });
+            }
+
+            #[test]
+            fn should_return_text() {
+                test(TableOptions::default(), true, env_var_fn(), |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    assert_eq!(
+                        out,
+                        "NAME  VERSION  URL  SHA256\nsoft  1.0.0    url  sha256\n"
+                    );
+                });
+            }
+
+            #[test]
+            fn should_return_text_without_header() {
+                let table_opts = TableOptions {
+                    json: false,
+                    no_header: true,
+                };
+                test(table_opts, true, env_var_fn(), |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    assert_eq!(out, "soft  1.0.0  url  sha256\n");
+                });
+            }
+
+            #[test]
+            fn should_return_text_without_receipt() {
+                test(TableOptions::default(), false, env_var_fn(), |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    assert_eq!(
+                        out,
+                        "NAME  VERSION  URL         SHA256\nsoft  1.0.0    no receipt  -\n"
+                    );
+                });
+            }
+
+            #[inline]
+            fn env_var_fn() -> Box<EnvVarFn> {
+                Box::new(|var_name| match var_name {
+                    DENV_CWD_VAR_NAME => Ok(CWD.into()),
+                    _ => panic!("unexpected {}", var_name),
+                })
+            }
+
+            #[inline]
+            fn test<F: Fn(Vec<u8>, Result<()>)>(
+                table_opts: TableOptions,
+                receipt_exists: bool,
+                env_var_fn: Box<EnvVarFn>,
+                assert_fn: F,
+            ) {
+                let runner = Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(StubConfigLoader::default()),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(VarDefinition::into_var),
+                    create_fs_fn: Box::new(move |_| {
+                        let mut fs = StubFileSystem::default();
+                        fs.stub_ensure_env_dir_fn(|project_dirpath| {
+                            assert_eq!(project_dirpath, Path::new(CWD));
+                            Ok(PathBuf::from(ENV_DIRPATH))
+                        });
+                        fs.stub_software_dirpath_fn(|name, version| {
+                            assert_eq!(name, "soft");
+                            assert_eq!(version, "1.0.0");
+                            Ok(PathBuf::from(SOFT_DIRPATH))
+                        });
+                        fs.stub_file_exists_fn(move |path| {
+                            path == Path::new(ENV_DIRPATH).join(STATE_FILENAME) || receipt_exists
+                        });
+                        fs.stub_read_to_string_fn(|path| {
+                            if path == Path::new(ENV_DIRPATH).join(STATE_FILENAME) {
+                                let state = state::state(Path::new(CWD), Path::new(CWD));
+                                Ok(serde_json::to_string(&state).unwrap())
+                            } else {
+                                Ok(serde_json::to_string(&receipt::receipt()).unwrap())
+                            }
+                        });
+                        Box::new(fs)
+                    }),
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn,
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                };
+                let res = runner.run(Command::List { table_opts }, Options::default());
+                let out = runner.out.into_inner();
+                assert_fn(out, res);
+            }
+        }
+
+        mod load {
+            use super::*;
+
+            use std::sync::{
+                atomic::{AtomicBool, AtomicU64, Ordering},
+                Arc, Mutex,
+            };
+
+            const ALLOW_DIRPATH: &str = "/allow";
+            const CFG_CONTENT: &str = "version: v1";
+            const NOW: u64 = 1000;
+            const SOFT_VAR_NAME: &str = "SOFT_HOME";
+            const SOFT_VERSION: &str = "1.2.3";
+            const SOFTWARES_DIRPATH: &str = "/softwares";
+            const VAULT_AGENT_PID: u32 = 42;
+
+            struct Data {
+                cfg: Config,
+                cfg_path: &'static Path,
+                cwd: &'static Path,
+                env_dirpath: &'static Path,
+                opts: Options,
+                path_env_var_value: &'static str,
+                soft_name: &'static str,
+                var_name: &'static str,
+                var_value: &'static str,
+            }
+
+            impl Default for Data {
+                fn default() -> Self {
+                    Self {
+                        cfg: Config {
+                            download: DownloadConfig::default(),
+                            latency_budget_ms: None,
+                            message: None,
+                            notify: None,
+                            project_bin: false,
+                            remove_quarantine: false,
+                            soft_defs: vec![SoftwareDefinition {
+                                auto: vec![],
+                                bin_name: None,
+                                default_args: vec![],
+                                kind: SoftwareDefinitionKind::Terraform,
+                                source: SoftwareSource::Denv,
+                                version: "1.2.3".into(),
+                            }],
+                            unset_vars: vec![],
+                            var_defs: vec![VarDefinition {
+                                kind: VarDefinitionKind::Literal("value".into()),
+                                name: "var".into(),
+                            }],
+                            vault_agent: None,
+                        },
+                        cfg_path: Path::new("/config"),
+                        cwd: Path::new("/cwd"),
+                        env_dirpath: Path::new("/env"),
+                        opts: Options {
+                            cfg_filepath: Some(PathBuf::from("/config")),
+                            denv_dirpath: Some(PathBuf::from("/denv")),
+                            ..Options::default()
+                        },
+                        path_env_var_value: "path",
+                        soft_name: "soft1",
+                        var_name: "var1",
+                        var_value: "value1",
+                    }
+                }
+            }
+
+            struct Stubs {
+                cfg_loader: StubConfigLoader,
+                convert_soft_fn: Box<ConvertSoftFn>,
+                convert_var_fn: Box<ConvertVarFn>,
+                create_fs_fn: Box<CreateFsFn>,
+                env_var_fn: Box<EnvVarFn>,
+                is_root_fn: Box<IsRootFn>,
+                kill_fn: Box<KillFn>,
+                notify_fn: Box<NotifyFn>,
+                print_msg_fn: Box<PrintMsgFn>,
+                spawn_fn: Box<SpawnFn>,
+            }
+
+            impl Stubs {
+                fn new(data: &Data) -> Self {
+                    let cfg = data.cfg.clone();
+                    let cfg_path = data.cfg_path;
+                    let cwd = data.cwd;
+                    let expected_denv_dirpath = data.opts.denv_dirpath.clone();
+                    let env_dirpath = data.env_dirpath;
+                    let expected_soft_def = cfg.soft_defs[0].clone();
+                    let path_env_var_value = data.path_env_var_value;
+                    let soft_name = data.soft_name;
+                    let expected_var_def = cfg.var_defs[0].clone();
+                    let var_name = data.var_name;
+                    let var_value = data.var_value;
+                    let mut stubs = Self {
+                        cfg_loader: StubConfigLoader::default(),
+                        convert_soft_fn: Box::new(move |soft_def, _| {
+                            assert_eq!(soft_def, expected_soft_def);
+                            Box::new(stub_software(soft_name, cwd))
+                        }),
+                        convert_var_fn: Box::new(move |var_def| {
+                            assert_eq!(var_def, expected_var_def);
+                            Box::new(stub_var(var_name, var_value))
+                        }),
+                        create_fs_fn: Box::new(move |denv_dirpath| {
+                            assert_eq!(denv_dirpath, expected_denv_dirpath);
+                            Box::new(stub_fs(cwd, env_dirpath, cfg_path))
+                        }),
+                        env_var_fn: Box::new(|var_name| match var_name {
+                            PATH_VAR_NAME => Ok(path_env_var_value.into()),
+                            DENV_IN_PROGRESS_VAR_NAME => Err(env::VarError::NotPresent),
+                            _ => panic!("unexpected {}", var_name),
+                        }),
+                        is_root_fn: Box::new(|| false),
+                        kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                        notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                        print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                        spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                    };
+                    stubs
+                        .cfg_loader
+                        .stub_load_content_fn(move |path, content, validate| {
+                            assert_eq!(path, cfg_path);
+                            assert_eq!(content, CFG_CONTENT);
+                            assert!(validate);
+                            Ok(cfg.clone())
+                        });
+                    stubs
+                }
+            }
+
+            #[test]
+            fn should_return_config_err() {
+                let data = Data::default();
+                let mut stubs = Stubs::new(&data);
+                stubs
+                    .cfg_loader
+                    .stub_load_content_fn(|_, _, _| Err(cfg::Error::Version(None)));
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::Config(_) => {}
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_return_io_err_if_cwd_failed() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(|_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_cwd_fn(|| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::Io(_) => {}
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_return_io_err_if_ensure_env_dir_failed() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(|_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_ensure_env_dir_fn(|_| {
+                        Err(io::Error::from(io::ErrorKind::PermissionDenied))
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::Io(_) => {}
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_return_install_err_if_install_failed() {
+                let data = Data::default();
+                let cwd = data.cwd;
+                let soft_name = data.soft_name;
+                let mut stubs = Stubs::new(&data);
+                stubs.convert_soft_fn = Box::new(move |_, _| {
+                    let mut soft = stub_software(soft_name, cwd);
+                    soft.stub_install_fn(|_, _| Err(SoftwareError::UnsupportedSystem));
+                    Box::new(soft)
+                });
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::Install(errs) => {
+                        assert_eq!(errs.len(), 1);
+                        let err = &errs[0];
+                        assert_eq!(err.soft.name(), data.soft_name);
+                        match &err.cause {
+                            SoftwareError::UnsupportedSystem => {}
+                            err => panic!("{}", err),
+                        }
+                    }
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_do_nothing_if_hook_is_already_running() {
+                let data = Data::default();
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(|_| panic!("environment must not be loaded"));
+                stubs.env_var_fn = Box::new(|var_name| match var_name {
+                    DENV_IN_PROGRESS_VAR_NAME => Ok("1".into()),
+                    _ => panic!("unexpected {}", var_name),
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    res.unwrap();
+                    assert!(out.is_empty());
+                });
+            }
+
+            #[test]
+            fn should_return_install_errs_if_softwares_installed_in_parallel_failed() {
+                let mut data = Data::default();
+                data.cfg.soft_defs.push(SoftwareDefinition {
+                    kind: SoftwareDefinitionKind::Zig,
+                    ..data.cfg.soft_defs[0].clone()
+                });
+                data.opts.jobs = Some(2);
+                let cwd = data.cwd;
+                let running = Arc::new(AtomicU64::new(0));
+                let mut stubs = Stubs::new(&data);
+                stubs.convert_soft_fn = Box::new(move |soft_def, _| {
+                    let name = match soft_def.kind {
+                        SoftwareDefinitionKind::Terraform => "soft1",
+                        _ => "soft2",
+                    };
+                    let running = running.clone();
+                    let mut soft = stub_software(name, cwd);
+                    soft.stub_install_fn(move |_, _| {
+                        running.fetch_add(1, Ordering::SeqCst);
+                        let started_at = Instant::now();
+                        while running.load(Ordering::SeqCst) < 2 {
+                            assert!(
+                                started_at.elapsed() < Duration::from_secs(5),
+                                "softwares are not installed in parallel"
+                            );
+                            thread::yield_now();
+                        }
+                        Err(SoftwareError::UnsupportedSystem)
+                    });
+                    Box::new(soft)
+                });
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::Install(errs) => {
+                        let soft_names: Vec<&str> =
+                            errs.iter().map(|err| err.soft.name()).collect();
+                        assert_eq!(soft_names, ["soft1", "soft2"]);
+                    }
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_return_binary_collision_err() {
+                let mut data = Data::default();
+                data.cfg.soft_defs.push(data.cfg.soft_defs[0].clone());
+                let cwd = data.cwd;
+                let soft_name = data.soft_name;
+                let mut stubs = Stubs::new(&data);
+                stubs.convert_soft_fn = Box::new(move |_, _| {
+                    let mut soft = stub_software(soft_name, cwd);
+                    soft.stub_install_fn(|_, _| panic!("software must not be installed"));
+                    Box::new(soft)
+                });
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::BinaryCollision(bin_name, softs) => {
+                        let soft = format!("{} v{}", soft_name, SOFT_VERSION);
+                        assert_eq!(bin_name, soft_name);
+                        assert_eq!(softs, [soft.clone(), soft]);
+                    }
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_return_cancelled_err() {
+                let data = Data::default();
+                let stubs = Stubs::new(&data);
+                let runner = runner(vec![], stubs);
+                runner.cancel_token().cancel();
+                match runner.run(Command::Load, data.opts).unwrap_err() {
+                    Error::Cancelled(pending) => assert_eq!(pending, vec!["software soft1"]),
+                    err => panic!("{}", err),
+                }
+            }
+
+            #[test]
+            fn should_return_timeout_err() {
+                let data = Data::default();
+                let cwd = data.cwd;
+                let soft_name = data.soft_name;
+                let elapsed_secs = Arc::new(AtomicU64::new(0));
+                let mut stubs = Stubs::new(&data);
+                stubs.convert_soft_fn = {
+                    let elapsed_secs = elapsed_secs.clone();
+                    Box::new(move |_, _| {
+                        let elapsed_secs = elapsed_secs.clone();
+                        let mut soft = stub_software(soft_name, cwd);
+                        soft.stub_install_fn(move |_, _| {
+                            elapsed_secs.store(10, Ordering::SeqCst);
+                            Ok(())
+                        });
+                        Box::new(soft)
+                    })
+                };
+                let mut runner = runner(vec![], stubs);
+                runner.elapsed_fn =
+                    Box::new(move || Duration::from_secs(elapsed_secs.load(Ordering::SeqCst)));
+                let opts = Options {
+                    timeout: Some(5),
+                    ..data.opts
+                };
+                match runner.run(Command::Load, opts).unwrap_err() {
+                    Error::Timeout(timeout, pending) => {
+                        assert_eq!(timeout, 5);
+                        assert_eq!(pending, vec!["var var"]);
+                    }
+                    err => panic!("{}", err),
+                }
+            }
+
+            #[test]
+            fn should_return_io_err_if_write_on_output_failed() {
+                let data = Data::default();
+                let stubs = Stubs::new(&data);
+                test(WriteFailer, &data.opts, stubs, |_, res| {
+                    match res.unwrap_err() {
+                        Error::Io(_) => {}
+                        err => panic!("{}", err),
+                    }
+                });
+            }
+
+            #[test]
+            fn should_return_install_err_if_compute_failed() {
+                let data = Data::default();
+                let var_name = data.var_name;
+                let var_value = data.var_value;
+                let mut stubs = Stubs::new(&data);
+                stubs.convert_var_fn = Box::new(|_| {
+                    let mut var = stub_var(var_name, var_value);
+                    var.stub_compute_values_fn(|| Err(VarError::Stub));
+                    Box::new(var)
+                });
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::Compute(errs) => {
+                        assert_eq!(errs.len(), 1);
+                        let err = &errs[0];
+                        assert_eq!(err.var.name(), data.var_name);
+                        match &err.cause {
+                            VarError::Stub => {}
+                            err => panic!("{}", err),
+                        }
+                    }
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_return_outcome_of_project_dir() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_cwd_fn(|| panic!("cwd must not be used"));
+                    Box::new(fs)
+                });
+                let runner = runner(vec![], stubs);
+                let outcome = runner
+                    .load(Some(cwd.to_path_buf()), data.opts.clone())
+                    .unwrap();
+                assert_eq!(outcome.cfg_filepath, cfg_path);
+                assert_eq!(outcome.env_dirpath, env_dirpath);
+                assert_eq!(outcome.project_dirpath, cwd);
+                assert_eq!(
+                    outcome.softwares,
+                    vec![SoftwareState {
+                        name: data.soft_name.into(),
+                        vars: vec![soft_var(data.soft_name)],
+                        version: SOFT_VERSION.into(),
+                    }]
+                );
+                assert_eq!(
+                    outcome.vars,
+                    vec![VarState {
+                        name: data.var_name.into(),
+                        value: data.var_value.into(),
+                    }]
+                );
+                verify(&data, outcome.statements.into_bytes(), Ok(()));
+                assert!(runner.out.into_inner().is_empty());
+            }
+
+            #[test]
+            fn should_return_outcome_with_every_value_of_var() {
+                let data = Data::default();
+                let mut stubs = Stubs::new(&data);
+                stubs.convert_var_fn = Box::new(|_| {
+                    let mut var = StubVar::default();
+                    var.stub_compute_values_fn(|| {
+                        Ok(vec![
+                            ("FOO".into(), "foo".into()),
+                            ("BAR".into(), "bar".into()),
+                        ])
+                    });
+                    var.stub_name_fn(|| ".env");
+                    Box::new(var)
+                });
+                let runner = runner(vec![], stubs);
+                let outcome = runner.load(None, data.opts.clone()).unwrap();
+                assert_eq!(
+                    outcome.vars,
+                    vec![
+                        VarState {
+                            name: "FOO".into(),
+                            value: "foo".into(),
+                        },
+                        VarState {
+                            name: "BAR".into(),
+                            value: "bar".into(),
+                        },
+                    ]
+                );
+                assert!(outcome
+                    .statements
+                    .contains("export FOO='foo'\nexport BAR='bar'\n"));
+            }
+
+            #[test]
+            fn should_return_install_ok_with_opts() {
+                let data = Data::default();
+                let stubs = Stubs::new(&data);
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+            }
+
+            #[test]
+            fn should_return_install_ok_without_opts() {
+                let data = Data {
+                    cfg_path: Path::new("/cwd/denv.yml"),
+                    opts: Options::default(),
+                    ..Data::default()
+                };
+                let stubs = Stubs::new(&data);
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+            }
+
+            #[test]
+            fn should_return_io_err_if_remove_quarantine_failed() {
+                let mut data = Data::default();
+                data.cfg.remove_quarantine = true;
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_list_files_fn(move |dirpath| {
+                        if dirpath == env_dirpath {
+                            Ok(vec![env_dirpath.join("terraform")])
+                        } else {
+                            Ok(vec![])
+                        }
+                    });
+                    fs.stub_remove_quarantine_fn(|_| {
+                        Err(io::Error::from(io::ErrorKind::PermissionDenied))
+                    });
+                    fs.stub_delete_file_fn(|_| Ok(()));
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::Io(_) => {}
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_remove_quarantine() {
+                let mut data = Data::default();
+                data.cfg.remove_quarantine = true;
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let removed = Arc::new(Mutex::new(vec![]));
+                let mut stubs = Stubs::new(&data);
+                let fs_removed = removed.clone();
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_list_files_fn(move |dirpath| {
+                        if dirpath == env_dirpath {
+                            Ok(vec![env_dirpath.join("terraform")])
+                        } else {
+                            Ok(vec![])
+                        }
+                    });
+                    fs.stub_canonicalize_fn(move |path| {
+                        if path == env_dirpath.join("terraform") {
+                            Ok(PathBuf::from("/softwares/soft1/1.2.3/terraform"))
+                        } else {
+                            Ok(path.to_path_buf())
+                        }
+                    });
+                    let removed = fs_removed.clone();
+                    fs.stub_remove_quarantine_fn(move |path| {
+                        removed.lock().unwrap().push(path.to_path_buf());
+                        Ok(())
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+                assert_eq!(*removed.lock().unwrap(), [env_dirpath.join("terraform")]);
+            }
+
+            #[test]
+            fn should_remove_stale_env_entries() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let deleted = Arc::new(Mutex::new(vec![]));
+                let mut stubs = Stubs::new(&data);
+                let fs_deleted = deleted.clone();
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_list_files_fn(move |dirpath| {
+                        if dirpath == env_dirpath {
+                            Ok(vec![
+                                env_dirpath.join(STATE_FILENAME),
+                                env_dirpath.join("helm"),
+                                env_dirpath.join("kubectl"),
+                                env_dirpath.join("terraform"),
+                            ])
+                        } else {
+                            Ok(vec![])
+                        }
+                    });
+                    fs.stub_quarantined_fn(|_| false);
+                    fs.stub_canonicalize_fn(move |path| {
+                        if path == env_dirpath.join("helm") {
+                            Err(io::Error::from(io::ErrorKind::NotFound))
+                        } else if path == env_dirpath.join("kubectl") {
+                            Ok(PathBuf::from("/softwares/kubectl/1.25.0/kubectl"))
+                        } else if path == env_dirpath.join("terraform") {
+                            Ok(PathBuf::from("/softwares/soft1/1.2.3/terraform"))
+                        } else {
+                            Ok(path.to_path_buf())
+                        }
+                    });
+                    let deleted = fs_deleted.clone();
+                    fs.stub_delete_file_fn(move |path| {
+                        if path != env_dirpath.join(LOAD_LOCK_FILENAME) {
+                            deleted.lock().unwrap().push(path.to_path_buf());
+                        }
+                        Ok(())
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+                assert_eq!(
+                    *deleted.lock().unwrap(),
+                    [env_dirpath.join("helm"), env_dirpath.join("kubectl")]
+                );
+            }
+
+            #[test]
+            fn should_write_default_args_shim() {
+                let mut data = Data::default();
+                data.cfg.soft_defs[0].default_args = vec!["-chdir=infra".into()];
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let shim_file = Arc::new(tempfile::NamedTempFile::new().unwrap());
+                let deleted = Arc::new(Mutex::new(vec![]));
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = {
+                    let shim_file = shim_file.clone();
+                    let deleted = deleted.clone();
+                    Box::new(move |_| {
+                        let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                        fs.stub_list_files_fn(move |dirpath| {
+                            if dirpath == env_dirpath {
+                                Ok(vec![env_dirpath.join("terraform-real")])
+                            } else {
+                                Ok(vec![])
+                            }
+                        });
+                        fs.stub_quarantined_fn(|_| false);
+                        fs.stub_canonicalize_fn(move |path| {
+                            if path == env_dirpath.join("terraform-real") {
+                                Ok(Path::new(SOFTWARES_DIRPATH).join("soft1/1.2.3/terraform"))
+                            } else {
+                                Ok(path.to_path_buf())
+                            }
+                        });
+                        let shim_file = shim_file.clone();
+                        fs.stub_create_file_fn(move |path| {
+                            assert_eq!(path, env_dirpath.join("terraform"));
+                            shim_file.reopen()
+                        });
+                        fs.stub_make_executable_fn(move |path| {
+                            assert_eq!(path, env_dirpath.join("terraform"));
+                            Ok(())
+                        });
+                        let deleted = deleted.clone();
+                        fs.stub_delete_file_fn(move |path| {
+                            if path != env_dirpath.join(LOAD_LOCK_FILENAME) {
+                                deleted.lock().unwrap().push(path.to_path_buf());
+                            }
+                            Ok(())
+                        });
+                        Box::new(fs)
+                    })
+                };
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+                let shim = std::fs::read_to_string(shim_file.path()).unwrap();
+                assert_eq!(
+                    shim,
+                    "#!/bin/sh\nexec '/env/terraform-real' '-chdir=infra' \"$@\"\n"
+                );
+                assert_eq!(
+                    *deleted.lock().unwrap(),
+                    [
+                        env_dirpath.join("terraform"),
+                        env_dirpath.join("terraform-real"),
+                        env_dirpath.join("terraform"),
+                    ]
+                );
+            }
+
+            #[test]
+            fn should_write_project_shims() {
+                let mut data = Data::default();
+                data.cfg.project_bin = true;
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let bin_dirpath = cwd.join(PROJECT_BIN_DIRPATH);
+                let shim_file = Arc::new(tempfile::NamedTempFile::new().unwrap());
+                let deleted = Arc::new(Mutex::new(vec![]));
+                let executables = Arc::new(Mutex::new(vec![]));
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = {
+                    let bin_dirpath = bin_dirpath.clone();
+                    let shim_file = shim_file.clone();
+                    let deleted = deleted.clone();
+                    let executables = executables.clone();
+                    Box::new(move |_| {
+                        let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                        let list_bin_dirpath = bin_dirpath.clone();
+                        fs.stub_list_files_fn(move |dirpath| {
+                            if dirpath == env_dirpath {
+                                Ok(vec![
+                                    env_dirpath.join(STATE_FILENAME),
+                                    env_dirpath.join("terraform"),
+                                ])
+                            } else if dirpath == list_bin_dirpath {
+                                Ok(vec![
+                                    dirpath.join(GITIGNORE_FILENAME),
+                                    dirpath.join("kubectl"),
+                                    dirpath.join("terraform"),
+                                ])
+                            } else {
+                                Ok(vec![])
+                            }
+                        });
+                        fs.stub_quarantined_fn(|_| false);
+                        fs.stub_canonicalize_fn(move |path| {
+                            if path == env_dirpath.join("terraform") {
+                                Ok(PathBuf::from("/softwares/soft1/1.2.3/terraform"))
+                            } else {
+                                Ok(path.to_path_buf())
+                            }
+                        });
+                        let shim_file = shim_file.clone();
+                        let create_bin_dirpath = bin_dirpath.clone();
+                        fs.stub_create_file_fn(move |path| {
+                            assert_eq!(path, create_bin_dirpath.join("terraform"));
+                            shim_file.reopen()
+                        });
+                        let executables = executables.clone();
+                        fs.stub_make_executable_fn(move |path| {
+                            executables.lock().unwrap().push(path.to_path_buf());
+                            Ok(())
+                        });
+                        let deleted = deleted.clone();
+                        fs.stub_delete_file_fn(move |path| {
+                            if path != env_dirpath.join(LOAD_LOCK_FILENAME) {
+                                deleted.lock().unwrap().push(path.to_path_buf());
+                            }
+                            Ok(())
+                        });
+                        let write_bin_dirpath = bin_dirpath.clone();
+                        fs.stub_write_fn(move |path, content| {
+                            if path.ends_with(LOCK_FILENAME) {
+                                return Ok(());
+                            }
+                            if path == write_bin_dirpath.join(GITIGNORE_FILENAME) {
+                                assert_eq!(content, "*\n");
+                            } else {
+                                assert_eq!(path, env_dirpath.join(STATE_FILENAME));
+                            }
+                            Ok(())
+                        });
+                        Box::new(fs)
+                    })
+                };
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+                let shim = std::fs::read_to_string(shim_file.path()).unwrap();
+                assert_eq!(
+                    shim,
+                    "#!/bin/sh\nexec '/softwares/soft1/1.2.3/terraform' \"$@\"\n"
+                );
+                assert_eq!(
+                    *executables.lock().unwrap(),
+                    [bin_dirpath.join("terraform")]
+                );
+                assert_eq!(*deleted.lock().unwrap(), [bin_dirpath.join("kubectl")]);
+            }
+
+            #[test]
+            fn should_source_activate_scripts() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_list_files_fn(move |dirpath| {
+                        if dirpath == env_dirpath {
+                            Ok(vec![])
+                        } else {
+                            Ok(vec![dirpath.join("10-script.sh")])
+                        }
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    let expected_suffix = format!(
+                        "export {}='{}'\nexport {}='1'\n. '{}'\nunset {}\n",
+                        data.var_name,
+                        data.var_value,
+                        DENV_IN_PROGRESS_VAR_NAME,
+                        cwd.join(ACTIVATE_DIRPATH).join("10-script.sh").display(),
+                        DENV_IN_PROGRESS_VAR_NAME,
+                    );
+                    assert!(out.ends_with(&expected_suffix), "{}", out);
+                });
+            }
+
+            #[test]
+            fn should_return_load_in_progress_err() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_create_new_fn(|_, _| {
+                        Err(io::Error::from(io::ErrorKind::AlreadyExists))
+                    });
+                    fs.stub_read_to_string_fn(move |path| {
+                        if path == cfg_path {
+                            return Ok(CFG_CONTENT.into());
+                        }
+                        assert_eq!(path, env_dirpath.join(LOAD_LOCK_FILENAME));
+                        Ok((NOW - 1).to_string())
+                    });
+                    fs.stub_delete_file_fn(|_| panic!("lock must not be deleted"));
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    match res.unwrap_err() {
+                        Error::LoadInProgress => {}
+                        err => panic!("{}", err),
+                    }
+                    assert!(out.is_empty());
+                });
+            }
+
+            #[test]
+            fn should_return_install_ok_if_lock_is_stale() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    let lock_path = env_dirpath.join(LOAD_LOCK_FILENAME);
+                    fs.stub_create_new_fn(|_, _| {
+                        Err(io::Error::from(io::ErrorKind::AlreadyExists))
+                    });
+                    fs.stub_read_to_string_fn(move |path| {
+                        if path == lock_path {
+                            Ok("0".into())
+                        } else {
+                            Ok(CFG_CONTENT.into())
+                        }
+                    });
+                    fs.stub_write_fn(|_, _| Ok(()));
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+            }
+
+            #[test]
+            fn should_unlock_env_if_install_failed() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let soft_name = data.soft_name;
+                let unlocked = Arc::new(AtomicBool::new(false));
+                let mut stubs = Stubs::new(&data);
+                stubs.convert_soft_fn = Box::new(move |_, _| {
+                    let mut soft = stub_software(soft_name, cwd);
+                    soft.stub_install_fn(|_, _| Err(SoftwareError::UnsupportedSystem));
+                    Box::new(soft)
+                });
+                let fs_unlocked = unlocked.clone();
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    let unlocked = fs_unlocked.clone();
+                    fs.stub_delete_file_fn(move |_| {
+                        unlocked.store(true, Ordering::SeqCst);
+                        Ok(())
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::Install(_) => {}
+                    err => panic!("{}", err),
+                });
+                assert!(unlocked.load(Ordering::SeqCst));
+            }
+
+            #[test]
+            fn should_return_io_err_if_save_state_failed() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(|_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_write_fn(|_, _| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::Io(_) => {}
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_return_lock_err_if_lock_is_missing_and_frozen() {
+                let mut data = Data::default();
+                data.opts.frozen = true;
+                let cwd = data.cwd;
+                let soft_name = data.soft_name;
+                let mut stubs = Stubs::new(&data);
+                stubs.convert_soft_fn = Box::new(move |_, _| {
+                    let mut soft = stub_software(soft_name, cwd);
+                    soft.stub_install_fn(|_, _| panic!("software must not be installed"));
+                    Box::new(soft)
+                });
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::Lock(lock::Error::Missing) => {}
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_return_lock_err_if_lock_is_outdated_and_frozen() {
+                let mut data = Data::default();
+                data.opts.frozen = true;
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let soft_name = data.soft_name;
+                let mut stubs = Stubs::new(&data);
+                stubs.convert_soft_fn = Box::new(move |_, _| {
+                    let mut soft = stub_software(soft_name, cwd);
+                    soft.stub_install_fn(|_, _| panic!("software must not be installed"));
+                    Box::new(soft)
+                });
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    stub_lock(&mut fs, cwd, cfg_path, "1.0.0", None);
+                    fs.stub_write_fn(|path, _| panic!("unexpected write of {}", path.display()));
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::Lock(lock::Error::Outdated(names)) => assert_eq!(names, ["soft1"]),
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_return_lock_err_if_checksum_does_not_match() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    stub_lock(&mut fs, cwd, cfg_path, SOFT_VERSION, Some("other"));
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::Lock(lock::Error::ChecksumMismatch { name, .. }) => {
+                        assert_eq!(name, "soft1")
+                    }
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_pass_locked_checksums_to_softwares() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let soft_name = data.soft_name;
+                let mut stubs = Stubs::new(&data);
+                stubs.convert_soft_fn = Box::new(move |_, download_cfg| {
+                    assert_eq!(
+                        download_cfg.locked_sha256s,
+                        [("url".to_string(), "sha256".to_string())]
+                    );
+                    Box::new(stub_software(soft_name, cwd))
+                });
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    stub_lock(&mut fs, cwd, cfg_path, SOFT_VERSION, Some("sha256"));
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+            }
+
+            #[test]
+            fn should_not_write_lock_if_it_did_not_change() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    stub_lock(&mut fs, cwd, cfg_path, SOFT_VERSION, Some("sha256"));
+                    fs.stub_write_fn(move |path, _| {
+                        assert_eq!(path, env_dirpath.join(STATE_FILENAME));
+                        Ok(())
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+            }
+
+            #[test]
+            fn should_resolve_latest_version_from_cache() {
+                let mut data = Data::default();
+                data.cfg.soft_defs[0].version = LATEST_VERSION.into();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let versions = Arc::new(Mutex::new(vec![]));
+                let mut stubs = Stubs::new(&data);
+                stubs.convert_soft_fn = stub_versioned_software(versions.clone(), cwd, vec![]);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    let cache_path = Path::new("/cache/soft1.json");
+                    fs.stub_ensure_cache_dir_fn(|name| {
+                        assert_eq!(name, release::LATEST_CACHE_DIRNAME);
+                        Ok(PathBuf::from("/cache"))
+                    });
+                    fs.stub_file_exists_fn(move |path| {
+                        path == cfg_path || path == cache_path || is_allow_file(path)
+                    });
+                    fs.stub_read_to_string_fn(move |path| {
+                        if path == cache_path {
+                            Ok(r#"{"resolved_at":0,"version":"1.6.0"}"#.into())
+                        } else {
+                            assert_eq!(path, cfg_path);
+                            Ok(CFG_CONTENT.into())
+                        }
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |_, res| {
+                    res.unwrap();
+                });
+                assert_eq!(*versions.lock().unwrap(), [LATEST_VERSION, "1.6.0"]);
+            }
+
+            #[test]
+            fn should_resolve_latest_version_from_releases() {
+                let mut data = Data::default();
+                data.cfg.soft_defs[0].version = LATEST_VERSION.into();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let versions = Arc::new(Mutex::new(vec![]));
+                let cached = Arc::new(Mutex::new(None));
+                let mut stubs = Stubs::new(&data);
+                stubs.convert_soft_fn = stub_versioned_software(
+                    versions.clone(),
+                    cwd,
+                    vec!["v2.0.0-rc1", "v1.6.0", "v1.5.0"],
+                );
+                stubs.create_fs_fn = {
+                    let cached = cached.clone();
+                    Box::new(move |_| {
+                        let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                        fs.stub_ensure_cache_dir_fn(|_| Ok(PathBuf::from("/cache")));
+                        let cached = cached.clone();
+                        fs.stub_write_fn(move |path, content| {
+                            if path == Path::new("/cache/soft1.json") {
+                                let latest: LatestVersion = serde_json::from_str(content).unwrap();
+                                *cached.lock().unwrap() = Some(latest);
+                            }
+                            Ok(())
+                        });
+                        Box::new(fs)
+                    })
+                };
+                test(vec![], &data.opts, stubs, |_, res| {
+                    res.unwrap();
+                });
+                assert_eq!(*versions.lock().unwrap(), [LATEST_VERSION, "1.6.0"]);
+                let expected = LatestVersion {
+                    resolved_at: NOW,
+                    version: "1.6.0".into(),
+                };
+                assert_eq!(*cached.lock().unwrap(), Some(expected));
+            }
+
+            #[test]
+            fn should_resolve_version_requirement_from_lock() {
+                let mut data = Data::default();
+                data.cfg.soft_defs[0].version = "~> 1.2".into();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let versions = Arc::new(Mutex::new(vec![]));
+                let mut stubs = Stubs::new(&data);
+                stubs.convert_soft_fn = stub_versioned_software(versions.clone(), cwd, vec![]);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    stub_lock(&mut fs, cwd, cfg_path, SOFT_VERSION, Some("sha256"));
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |_, res| {
+                    res.unwrap();
+                });
+                assert_eq!(*versions.lock().unwrap(), ["~> 1.2", SOFT_VERSION]);
+            }
+
+            #[test]
+            fn should_resolve_version_requirement_from_releases() {
+                let mut data = Data::default();
+                data.cfg.soft_defs[0].version = "~> 1.2".into();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let versions = Arc::new(Mutex::new(vec![]));
+                let mut stubs = Stubs::new(&data);
+                stubs.convert_soft_fn = stub_versioned_software(
+                    versions.clone(),
+                    cwd,
+                    vec!["v2.0.0", "v1.3.0-rc1", "v1.2.9", "v1.2.3"],
+                );
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_ensure_cache_dir_fn(|_| Ok(PathBuf::from("/cache")));
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |_, res| {
+                    res.unwrap();
+                });
+                assert_eq!(*versions.lock().unwrap(), ["~> 1.2", "1.2.9"]);
+            }
+
+            #[test]
+            fn should_return_unresolved_version_err() {
+                let mut data = Data::default();
+                data.cfg.soft_defs[0].version = "~> 3".into();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.convert_soft_fn =
+                    stub_versioned_software(Arc::default(), cwd, vec!["v2.0.0", "v3.0.0-rc1"]);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_ensure_cache_dir_fn(|_| Ok(PathBuf::from("/cache")));
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::UnresolvedVersion(name, req) => {
+                        assert_eq!(name, "soft1");
+                        assert_eq!(req, "~> 3");
+                    }
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_append_history_entry() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let soft_name = data.soft_name;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_append_fn(move |path, content| {
+                        assert_eq!(path, crate::history::filepath(env_dirpath));
+                        let entry: HistoryEntry = serde_json::from_str(content).unwrap();
+                        let expected_entry = HistoryEntry {
+                            action: Action::Load,
+                            at: NOW,
+                            cfg_hash: Some(hash::sha256(CFG_CONTENT.as_bytes())),
+                            duration_ms: 0,
+                            error: None,
+                            softwares: BTreeMap::from([(soft_name.into(), SOFT_VERSION.into())]),
+                        };
+                        assert_eq!(entry, expected_entry);
+                        Ok(())
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+            }
+
+            #[test]
+            fn should_append_history_entry_if_load_failed() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs
+                    .cfg_loader
+                    .stub_load_content_fn(|_, _, _| Err(cfg::Error::Version(None)));
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_append_fn(|_, content| {
+                        let entry: HistoryEntry = serde_json::from_str(content).unwrap();
+                        let err = cfg::Error::Version(None).to_string();
+                        assert_eq!(entry.error, Some(err));
+                        assert!(entry.softwares.is_empty());
+                        Ok(())
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::Config(_) => {}
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_notify() {
+                let mut data = Data::default();
+                data.cfg.notify = Some(NotifyConfig {
+                    after_secs: 0,
+                    desktop: true,
+                    webhook: None,
+                });
+                let expected_cfg = data.cfg.notify.clone().unwrap();
+                let notified = Arc::new(AtomicBool::new(false));
+                let mut stubs = Stubs::new(&data);
+                stubs.notify_fn = Box::new({
+                    let notified = notified.clone();
+                    move |cfg, msg| {
+                        assert_eq!(*cfg, expected_cfg);
+                        assert_eq!(msg, "Environment of / loaded in 0 ms");
+                        notified.store(true, Ordering::SeqCst);
+                    }
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+                assert!(notified.load(Ordering::SeqCst));
+            }
+
+            #[test]
+            fn should_not_notify_if_load_was_fast() {
+                let mut data = Data::default();
+                data.cfg.notify = Some(NotifyConfig {
+                    after_secs: 1,
+                    desktop: true,
+                    webhook: None,
+                });
+                let stubs = Stubs::new(&data);
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+            }
+
+            #[test]
+            fn should_export_path_entries_with_path() {
+                let mut data = Data::default();
+                data.cfg.var_defs.extend([
+                    VarDefinition {
+                        kind: VarDefinitionKind::AppendPath(PathBuf::from("/cwd/scripts")),
+                        name: "scripts".into(),
+                    },
+                    VarDefinition {
+                        kind: VarDefinitionKind::PrependPath(PathBuf::from(
+                            "/cwd/node_modules/.bin",
+                        )),
+                        name: "./node_modules/.bin".into(),
+                    },
+                ]);
+                let stubs = Stubs::new(&data);
+                test(vec![], &data.opts, stubs, |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    let path_stmt = format!(
+                        "export {}='{}:/cwd/node_modules/.bin':\"${{{}}}\":'/cwd/scripts'\n",
+                        PATH_VAR_NAME,
+                        data.env_dirpath.display(),
+                        PATH_VAR_NAME
+                    );
+                    assert!(out.contains(&path_stmt), "{}", out);
+                    assert_eq!(out.matches("export PATH=").count(), 1);
+                });
+            }
+
+            #[test]
+            fn should_save_state() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let soft_name = data.soft_name;
+                let var_name = data.var_name;
+                let var_value = data.var_value;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_write_fn(move |path, content| {
+                        if path.ends_with(LOCK_FILENAME) {
+                            return Ok(());
+                        }
+                        let state: State = serde_json::from_str(content).unwrap();
+                        let expected_state = State {
+                            cfg_filepath: cfg_path.to_path_buf(),
+                            cfg_hash: hash::sha256(CFG_CONTENT.as_bytes()),
+                            created_at: NOW,
+                            denv_version: DENV_VERSION.into(),
+                            id: "env".into(),
+                            last_loaded_at: NOW,
+                            lenient: false,
+                            path_prefix: env_dirpath.display().to_string(),
+                            path_suffix: String::new(),
+                            project_dirpath: cwd.to_path_buf(),
+                            softwares: vec![SoftwareState {
+                                name: soft_name.into(),
+                                vars: vec![soft_var(soft_name)],
+                                version: SOFT_VERSION.into(),
+                            }],
+                            unset_vars: vec![],
+                            vars: vec![VarState {
+                                name: var_name.into(),
+                                value: var_value.into(),
+                            }],
+                            vault_agent: None,
+                        };
+                        assert_eq!(state, expected_state);
+                        Ok(())
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+            }
+
+            #[test]
+            fn should_keep_creation_date_of_previous_state() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    let state_path = env_dirpath.join(STATE_FILENAME);
+                    fs.stub_file_exists_fn(move |path| {
+                        path == cfg_path || path == state_path || is_allow_file(path)
+                    });
+                    fs.stub_read_to_string_fn(move |path| {
+                        if path == cfg_path {
+                            Ok(CFG_CONTENT.into())
+                        } else {
+                            let state = state::state(cwd, cfg_path);
+                            Ok(serde_json::to_string(&state).unwrap())
+                        }
+                    });
+                    fs.stub_write_fn(move |path, content| {
+                        if path.ends_with(LOCK_FILENAME) {
+                            return Ok(());
+                        }
+                        let state: State = serde_json::from_str(content).unwrap();
+                        assert_eq!(state.created_at, state::state(cwd, cfg_path).created_at);
+                        assert_eq!(state.last_loaded_at, NOW);
+                        Ok(())
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+            }
+
+            #[test]
+            fn should_return_config_err_if_vars_are_duplicated() {
+                let data = Data::default();
+                let mut stubs = Stubs::new(&data);
+                stubs.cfg_loader.stub_load_content_fn(|_, _, _| {
+                    Err(cfg::Error::DuplicateVars(vec!["VAR is duplicated".into()]))
+                });
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::Config(cfg::Error::DuplicateVars(_)) => {}
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_load_cfg_with_duplicate_vars_if_lenient_is_enabled() {
+                let mut data = Data::default();
+                data.opts.lenient = true;
+                let cfg = data.cfg.clone();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs
+                    .cfg_loader
+                    .stub_load_content_fn(move |path, _, validate| {
+                        assert_eq!(path, cfg_path);
+                        if validate {
+                            Err(cfg::Error::DuplicateVars(vec!["VAR is duplicated".into()]))
+                        } else {
+                            Ok(cfg.clone())
+                        }
+                    });
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_write_fn(move |path, content| {
+                        if path == env_dirpath.join(STATE_FILENAME) {
+                            let state: State = serde_json::from_str(content).unwrap();
+                            assert!(state.lenient);
+                        }
+                        Ok(())
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+            }
+
+            #[test]
+            fn should_skip_validation_if_cfg_did_not_change() {
+                let data = Data::default();
+                let cfg = data.cfg.clone();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs
+                    .cfg_loader
+                    .stub_load_content_fn(move |path, _, validate| {
+                        assert_eq!(path, cfg_path);
+                        assert!(!validate, "config must not be validated");
+                        Ok(cfg.clone())
+                    });
+                stubs.create_fs_fn =
+                    Box::new(move |_| Box::new(validated_fs(cwd, env_dirpath, cfg_path, false)));
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+            }
+
+            #[test]
+            fn should_validate_cfg_if_previous_load_was_lenient() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.cfg_loader.stub_load_content_fn(|_, _, validate| {
+                    assert!(validate, "config must be validated");
+                    Err(cfg::Error::DuplicateVars(vec!["VAR is duplicated".into()]))
+                });
+                stubs.create_fs_fn =
+                    Box::new(move |_| Box::new(validated_fs(cwd, env_dirpath, cfg_path, true)));
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::Config(cfg::Error::DuplicateVars(_)) => {}
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_validate_cfg_if_revalidate_is_enabled() {
+                let mut data = Data::default();
+                data.opts.revalidate = true;
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn =
+                    Box::new(move |_| Box::new(validated_fs(cwd, env_dirpath, cfg_path, false)));
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+            }
+
+            #[test]
+            fn should_unset_vars_and_save_backup() {
+                let mut data = Data::default();
+                data.cfg.unset_vars = vec!["AWS_PROFILE".into(), "AWS_REGION".into()];
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let path_env_var_value = data.path_env_var_value;
+                let mut stubs = Stubs::new(&data);
+                stubs.env_var_fn = Box::new(move |var_name| match var_name {
+                    "AWS_PROFILE" => Ok("default".into()),
+                    PATH_VAR_NAME => Ok(path_env_var_value.into()),
+                    _ => Err(env::VarError::NotPresent),
+                });
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_write_fn(|path, content| {
+                        if path.ends_with(LOCK_FILENAME) {
+                            return Ok(());
+                        }
+                        let state: State = serde_json::from_str(content).unwrap();
+                        let expected_unset_vars = vec![VarState {
+                            name: "AWS_PROFILE".into(),
+                            value: "default".into(),
+                        }];
+                        assert_eq!(state.unset_vars, expected_unset_vars);
+                        Ok(())
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    assert!(out.ends_with("unset AWS_PROFILE\n"), "{}", out);
+                });
+            }
+
+            #[test]
+            fn should_keep_backup_of_unset_vars_on_reload() {
+                let mut data = Data::default();
+                data.cfg.unset_vars = vec!["AWS_PROFILE".into()];
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let path_env_var_value = data.path_env_var_value;
+                let mut stubs = Stubs::new(&data);
+                stubs.env_var_fn = Box::new(move |var_name| match var_name {
+                    DENV_CWD_VAR_NAME => Ok(cwd.display().to_string()),
+                    PATH_VAR_NAME => Ok(path_env_var_value.into()),
+                    _ => Err(env::VarError::NotPresent),
+                });
+                let prev_unset_vars = vec![VarState {
+                    name: "AWS_PROFILE".into(),
+                    value: "default".into(),
+                }];
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    let state_path = env_dirpath.join(STATE_FILENAME);
+                    fs.stub_file_exists_fn(move |path| {
+                        path == cfg_path || path == state_path || is_allow_file(path)
+                    });
+                    let saved_unset_vars = prev_unset_vars.clone();
+                    let read_unset_vars = prev_unset_vars.clone();
+                    fs.stub_read_to_string_fn(move |path| {
+                        if path == cfg_path {
+                            Ok(CFG_CONTENT.into())
+                        } else {
+                            let state = State {
+                                unset_vars: read_unset_vars.clone(),
+                                ..state::state(cwd, cfg_path)
+                            };
+                            Ok(serde_json::to_string(&state).unwrap())
+                        }
+                    });
+                    fs.stub_write_fn(move |path, content| {
+                        if path.ends_with(LOCK_FILENAME) {
+                            return Ok(());
+                        }
+                        let state: State = serde_json::from_str(content).unwrap();
+                        assert_eq!(state.unset_vars, saved_unset_vars);
+                        Ok(())
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |_, res| res.unwrap());
+            }
+
+            #[test]
+            fn should_return_root_err() {
+                let data = Data::default();
+                let mut stubs = Stubs::new(&data);
+                stubs.is_root_fn = Box::new(|| true);
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::Root => {}
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_skip_software_if_auto_rules_do_not_match() {
+                let mut data = Data::default();
+                data.cfg.soft_defs[0].auto = vec!["*.tf".into(), "charts/".into()];
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let soft_name = data.soft_name;
+                let mut stubs = Stubs::new(&data);
+                stubs.convert_soft_fn = Box::new(move |_, _| {
+                    let mut soft = stub_software(soft_name, cwd);
+                    soft.stub_install_fn(|_, _| panic!("software must not be installed"));
+                    Box::new(soft)
+                });
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_glob_matches_fn(move |dirpath, _| {
+                        assert_eq!(dirpath, cwd);
+                        false
+                    });
+                    fs.stub_write_fn(|path, content| {
+                        if path.ends_with(LOCK_FILENAME) {
+                            return Ok(());
+                        }
+                        let state: State = serde_json::from_str(content).unwrap();
+                        assert!(state.softwares.is_empty());
+                        Ok(())
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    assert!(!out.contains("DENV_TOOL_"), "{}", out);
+                });
+            }
+
+            #[test]
+            fn should_install_software_if_auto_rules_match() {
+                let mut data = Data::default();
+                data.cfg.soft_defs[0].auto = vec!["*.tf".into(), "charts/".into()];
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_glob_matches_fn(|_, pattern| pattern == "charts/");
+                    fs.stub_write_fn(|path, content| {
+                        if path.ends_with(LOCK_FILENAME) {
+                            return Ok(());
+                        }
+                        let state: State = serde_json::from_str(content).unwrap();
+                        assert_eq!(state.softwares.len(), 1);
+                        Ok(())
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+            }
+
+            #[test]
+            fn should_return_store_err_if_store_is_newer() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_store_version_fn(|| Ok(Some(STORE_VERSION + 1)));
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    match res.unwrap_err() {
+                        Error::Store(store::Error::UnsupportedVersion(version)) => {
+                            assert_eq!(version, STORE_VERSION + 1)
+                        }
+                        err => panic!("{}", err),
+                    }
+                    assert!(out.is_empty());
+                });
+            }
+
+            #[test]
+            fn should_return_install_ok_if_root_is_allowed() {
+                let data = Data {
+                    opts: Options {
+                        allow_root: true,
+                        ..Data::default().opts
+                    },
+                    ..Data::default()
+                };
+                let mut stubs = Stubs::new(&data);
+                stubs.is_root_fn = Box::new(|| true);
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+            }
+
+            #[test]
+            fn should_start_vault_agent() {
+                let mut data = Data::default();
+                data.cfg.vault_agent = Some(vault_agent_cfg());
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_write_fn(move |path, content| {
+                        if path.ends_with(LOCK_FILENAME) {
+                            return Ok(());
+                        }
+                        if path == env_dirpath.join(VAULT_AGENT_CFG_FILENAME) {
+                            assert_eq!(content, vault_agent_content(env_dirpath));
+                        } else {
+                            let state: State = serde_json::from_str(content).unwrap();
+                            let expected_agent = VaultAgentState {
+                                cfg_hash: hash::sha256(vault_agent_content(env_dirpath).as_bytes()),
+                                pid: VAULT_AGENT_PID,
+                            };
+                            assert_eq!(state.vault_agent, Some(expected_agent));
+                        }
+                        Ok(())
+                    });
+                    Box::new(fs)
+                });
+                stubs.spawn_fn = Box::new(move |program, args, log_filepath| {
+                    let cfg_filepath = env_dirpath.join(VAULT_AGENT_CFG_FILENAME);
+                    assert_eq!(program, VAULT_BIN_NAME);
+                    assert_eq!(args, vault::agent_args(&cfg_filepath));
+                    assert_eq!(log_filepath, env_dirpath.join(VAULT_AGENT_LOG_FILENAME));
+                    Ok(VAULT_AGENT_PID)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    let expected_export = format!(
+                        "export {}='{}'\n",
+                        VAULT_AGENT_SINK_VAR_NAME,
+                        env_dirpath.join(VAULT_SINK_FILENAME).display()
+                    );
+                    assert!(out.ends_with(&expected_export));
+                });
+            }
+
+            #[test]
+            fn should_keep_running_vault_agent() {
+                let mut data = Data::default();
+                data.cfg.vault_agent = Some(vault_agent_cfg());
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                let cfg_hash = hash::sha256(vault_agent_content(env_dirpath).as_bytes());
+                stubs.create_fs_fn = Box::new(move |_| {
+                    Box::new(vault_agent_fs(cwd, env_dirpath, cfg_path, cfg_hash.clone()))
+                });
+                stubs.kill_fn = Box::new(|pid, sig| {
+                    assert_eq!(pid, VAULT_AGENT_PID);
+                    assert_eq!(sig, 0);
+                    Ok(())
+                });
+                test(vec![], &data.opts, stubs, |_, res| {
+                    res.unwrap();
+                });
+            }
+
+            #[test]
+            fn should_restart_vault_agent_if_config_changed() {
+                let mut data = Data::default();
+                data.cfg.vault_agent = Some(vault_agent_cfg());
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let signals = Arc::new(Mutex::new(vec![]));
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = vault_agent_fs(cwd, env_dirpath, cfg_path, "old".into());
+                    fs.stub_write_fn(|_, _| Ok(()));
+                    Box::new(fs)
+                });
+                let killed = signals.clone();
+                stubs.kill_fn = Box::new(move |pid, sig| {
+                    killed.lock().unwrap().push((pid, sig));
+                    Ok(())
+                });
+                stubs.spawn_fn = Box::new(|_, _, _| Ok(VAULT_AGENT_PID + 1));
+                test(vec![], &data.opts, stubs, |_, res| {
+                    res.unwrap();
+                });
+                let expected_signals = vec![(VAULT_AGENT_PID, 0), (VAULT_AGENT_PID, libc::SIGTERM)];
+                assert_eq!(*signals.lock().unwrap(), expected_signals);
+            }
+
+            #[test]
+            fn should_stop_vault_agent_if_it_is_not_configured_anymore() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let signals = Arc::new(Mutex::new(vec![]));
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = vault_agent_fs(cwd, env_dirpath, cfg_path, "hash".into());
+                    fs.stub_write_fn(|path, content| {
+                        if path.ends_with(LOCK_FILENAME) {
+                            return Ok(());
+                        }
+                        let state: State = serde_json::from_str(content).unwrap();
+                        assert!(state.vault_agent.is_none());
+                        Ok(())
+                    });
+                    Box::new(fs)
+                });
+                let killed = signals.clone();
+                stubs.kill_fn = Box::new(move |pid, sig| {
+                    killed.lock().unwrap().push((pid, sig));
+                    Err(io::Error::from_raw_os_error(libc::ESRCH))
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+                assert_eq!(
+                    *signals.lock().unwrap(),
+                    vec![(VAULT_AGENT_PID, libc::SIGTERM)]
+                );
+            }
+
+            #[test]
+            fn should_print_message_on_first_load() {
+                let mut data = Data::default();
+                data.cfg.message = Some("Run make bootstrap first".into());
+                let mut stubs = Stubs::new(&data);
+                let msgs = Arc::new(Mutex::new(vec![]));
+                let printed_msgs = msgs.clone();
+                stubs.print_msg_fn = Box::new(move |msg| {
+                    printed_msgs.lock().unwrap().push(msg.to_string());
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+                assert_eq!(*msgs.lock().unwrap(), vec!["Run make bootstrap first"]);
+            }
+
+            #[test]
+            fn should_not_print_message_if_env_was_loaded_same_day() {
+                let mut data = Data::default();
+                data.cfg.message = Some("Run make bootstrap first".into());
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    let state_path = env_dirpath.join(STATE_FILENAME);
+                    fs.stub_file_exists_fn(move |path| {
+                        path == cfg_path || path == state_path || is_allow_file(path)
+                    });
+                    fs.stub_read_to_string_fn(move |path| {
+                        if path == cfg_path {
+                            Ok(CFG_CONTENT.into())
+                        } else {
+                            let state = state::state(cwd, cfg_path);
+                            Ok(serde_json::to_string(&state).unwrap())
+                        }
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+            }
+
+            #[test]
+            fn should_write_only_shell_statements() {
+                let data = Data::default();
+                let stubs = Stubs::new(&data);
+                test(vec![], &data.opts, stubs, |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    for line in out.lines() {
+                        assert!(line.starts_with("export "), "{}", line);
+                    }
+                    let status = std::process::Command::new("sh")
+                        .arg("-c")
+                        .arg(out)
+                        .status()
+                        .unwrap();
+                    assert!(status.success());
+                });
+            }
+
+            #[test]
+            fn should_return_install_ok_with_alternative_config_file() {
+                let data = Data {
+                    cfg_path: Path::new("/cwd/.config/denv.yml"),
+                    opts: Options::default(),
+                    ..Data::default()
+                };
+                let stubs = Stubs::new(&data);
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+            }
+
+            #[test]
+            fn should_return_config_not_found_err() {
+                let data = Data {
+                    opts: Options::default(),
+                    ..Data::default()
+                };
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(|_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_file_exists_fn(|_| false);
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::ConfigNotFound => {}
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_return_config_conflict_err() {
+                let data = Data {
+                    opts: Options::default(),
+                    ..Data::default()
+                };
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_file_exists_fn(move |path| {
+                        path == cwd.join("denv.yml") || path == cwd.join(".denv.yml")
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                    Error::ConfigConflict(paths) => {
+                        let expected_paths = vec![cwd.join("denv.yml"), cwd.join(".denv.yml")];
+                        assert_eq!(paths, expected_paths);
+                    }
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_return_not_allowed_err() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let mut stubs = Stubs::new(&data);
+                stubs.cfg_loader = StubConfigLoader::default();
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    fs.stub_file_exists_fn(move |path| path == cfg_path);
+                    fs.stub_ensure_env_dir_fn(|_| panic!("environment must not be loaded"));
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    match res.unwrap_err() {
+                        Error::NotAllowed(path) => assert_eq!(path, cfg_path),
+                        err => panic!("{}", err),
+                    }
+                    assert!(out.is_empty());
+                });
+            }
+
+            #[test]
+            fn should_read_cfg_once() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let env_dirpath = data.env_dirpath;
+                let reads = Arc::new(AtomicUsize::new(0));
+                let mut stubs = Stubs::new(&data);
+                let fs_reads = reads.clone();
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                    let reads = fs_reads.clone();
+                    fs.stub_read_to_string_fn(move |path| {
+                        assert_eq!(path, cfg_path);
+                        reads.fetch_add(1, Ordering::SeqCst);
+                        Ok(CFG_CONTENT.into())
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], &data.opts, stubs, |out, res| {
+                    verify(&data, out, res);
+                });
+                assert_eq!(reads.load(Ordering::SeqCst), 1);
+            }
+
+            // The allowed configurations are the ones of stub_fs.
+            #[inline]
+            fn is_allow_file(path: &Path) -> bool {
+                path.parent() == Some(Path::new(ALLOW_DIRPATH))
+            }
+
+            #[inline]
+            fn stub_fs(
+                cwd: &'static Path,
+                env_dirpath: &'static Path,
+                cfg_path: &'static Path,
+            ) -> StubFileSystem {
+                let mut fs = StubFileSystem::default();
+                fs.stub_cwd_fn(|| Ok(cwd.to_path_buf()));
+                fs.stub_store_version_fn(|| Ok(Some(STORE_VERSION)));
+                fs.stub_ensure_allow_dir_fn(|| Ok(PathBuf::from(ALLOW_DIRPATH)));
+                fs.stub_file_exists_fn(move |path| path == cfg_path || is_allow_file(path));
+                fs.stub_list_files_fn(move |dirpath| {
+                    assert!(dirpath == env_dirpath || dirpath == cwd.join(ACTIVATE_DIRPATH));
+                    Ok(vec![])
+                });
+                fs.stub_ensure_env_dir_fn(move |project_dirpath| {
+                    assert_eq!(project_dirpath, cwd);
+                    Ok(env_dirpath.to_path_buf())
+                });
+                fs.stub_read_to_string_fn(move |path| {
+                    assert_eq!(path, cfg_path);
+                    Ok(CFG_CONTENT.into())
+                });
+                fs.stub_write_fn(move |path, _| {
+                    assert!(
+                        path == env_dirpath.join(STATE_FILENAME) || path == cwd.join(LOCK_FILENAME)
+                    );
+                    Ok(())
+                });
+                fs.stub_create_new_fn(move |path, content| {
+                    assert_eq!(path, env_dirpath.join(LOAD_LOCK_FILENAME));
+                    assert_eq!(content, NOW.to_string());
+                    Ok(())
+                });
+                fs.stub_append_fn(move |path, _| {
+                    assert_eq!(path, crate::history::filepath(env_dirpath));
+                    Ok(())
+                });
+                fs.stub_delete_file_fn(move |path| {
+                    assert_eq!(path, env_dirpath.join(LOAD_LOCK_FILENAME));
+                    Ok(())
+                });
+                fs.stub_software_dirpath_fn(|name, version| {
+                    Ok(Path::new(SOFTWARES_DIRPATH).join(name).join(version))
+                });
+                fs.stub_canonicalize_fn(|path| Ok(path.to_path_buf()));
+                fs
+            }
+
+            // The software is installed with the receipt of receipt::receipt().
+            #[inline]
+            fn stub_lock(
+                fs: &mut StubFileSystem,
+                cwd: &'static Path,
+                cfg_path: &'static Path,
+                version: &'static str,
+                sha256: Option<&'static str>,
+            ) {
+                let lock_path = cwd.join(LOCK_FILENAME);
+                let receipt_path = PathBuf::from(format!(
+                    "{}/soft1/{}.receipt.json",
+                    SOFTWARES_DIRPATH, SOFT_VERSION
+                ));
+                let lock = Lock {
+                    softwares: vec![LockedSoftware {
+                        name: "soft1".into(),
+                        sha256: sha256.map(String::from),
+                        url: sha256.map(|_| "url".into()),
+                        version: version.into(),
+                    }],
+                };
+                let lock_json = serde_json::to_string(&lock).unwrap();
+                let receipt_json = serde_json::to_string(&receipt()).unwrap();
+                {
+                    let lock_path = lock_path.clone();
+                    let receipt_path = receipt_path.clone();
+                    fs.stub_file_exists_fn(move |path| {
+                        path == cfg_path
+                            || path == lock_path
+                            || path == receipt_path
+                            || is_allow_file(path)
+                    });
+                }
+                fs.stub_read_to_string_fn(move |path| {
+                    if path == lock_path {
+                        Ok(lock_json.clone())
+                    } else if path == receipt_path {
+                        Ok(receipt_json.clone())
+                    } else {
+                        assert_eq!(path, cfg_path);
+                        Ok(CFG_CONTENT.into())
+                    }
+                });
+            }
 
-        mod hook {
+            #[inline]
+            fn stub_software(name: &'static str, cwd: &'static Path) -> StubSoftware {
+                let mut soft = StubSoftware::default();
+                soft.stub_env_vars_fn(|install_dirpath, _| {
+                    vec![(SOFT_VAR_NAME.into(), install_dirpath.display().to_string())]
+                });
+                soft.stub_install_fn(move |project_dirpath, _| {
+                    assert_eq!(project_dirpath, cwd);
+                    Ok(())
+                });
+                soft.stub_bin_names_fn(move || vec![name.into()]);
+                soft.stub_name_fn(move || name);
+                soft.stub_version_fn(|| SOFT_VERSION);
+                soft
+            }
+
+            #[inline]
+            fn soft_var(soft_name: &str) -> VarState {
+                VarState {
+                    name: SOFT_VAR_NAME.into(),
+                    value: Path::new(SOFTWARES_DIRPATH)
+                        .join(soft_name)
+                        .join(SOFT_VERSION)
+                        .display()
+                        .to_string(),
+                }
+            }
+
+            // The versions of the converted definitions are collected.
+            #[inline]
+            fn stub_versioned_software(
+                versions: Arc<Mutex<Vec<String>>>,
+                cwd: &'static Path,
+                releases: Vec<&'static str>,
+            ) -> Box<ConvertSoftFn> {
+                Box::new(move |soft_def, _| {
+                    versions.lock().unwrap().push(soft_def.version);
+                    let releases = releases.clone();
+                    let mut soft = stub_software("soft1", cwd);
+                    soft.stub_release_index_fn(move || {
+                        let releases = releases.clone();
+                        let mut index = StubReleaseIndex::default();
+                        index.stub_list_versions_fn(move |_| {
+                            Ok(releases
+                                .iter()
+                                .map(|version| ReleaseVersion {
+                                    changelog: None,
+                                    version: Version::from(*version),
+                                })
+                                .collect())
+                        });
+                        Some(Box::new(index))
+                    });
+                    Box::new(soft)
+                })
+            }
+
+            #[inline]
+            fn stub_var(name: &'static str, value: &'static str) -> StubVar {
+                let mut var = StubVar::default();
+                var.stub_compute_values_fn(move || Ok(vec![(name.into(), value.into())]));
+                var.stub_name_fn(move || name);
+                var
+            }
+
+            #[inline]
+            fn runner<W: Write>(out: W, stubs: Stubs) -> Runner<W> {
+                Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(stubs.cfg_loader),
+                    convert_soft_fn: stubs.convert_soft_fn,
+                    convert_var_fn: stubs.convert_var_fn,
+                    create_fs_fn: stubs.create_fs_fn,
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn: stubs.env_var_fn,
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: stubs.is_root_fn,
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: stubs.kill_fn,
+                    notify_fn: stubs.notify_fn,
+                    now_fn: Box::new(|| NOW),
+                    out: ShellOutput::new(out),
+                    print_msg_fn: stubs.print_msg_fn,
+                    spawn_fn: stubs.spawn_fn,
+                }
+            }
+
+            #[inline]
+            fn test<W: Write, F: Fn(W, Result<()>)>(
+                out: W,
+                opts: &Options,
+                stubs: Stubs,
+                assert_fn: F,
+            ) {
+                let runner = runner(out, stubs);
+                let res = runner.run(Command::Load, opts.clone());
+                let out = runner.out.into_inner();
+                assert_fn(out, res);
+            }
+
+            #[inline]
+            fn validated_fs(
+                cwd: &'static Path,
+                env_dirpath: &'static Path,
+                cfg_path: &'static Path,
+                lenient: bool,
+            ) -> StubFileSystem {
+                let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                let state_path = env_dirpath.join(STATE_FILENAME);
+                fs.stub_file_exists_fn(move |path| {
+                    path == cfg_path || path == state_path || is_allow_file(path)
+                });
+                fs.stub_read_to_string_fn(move |path| {
+                    if path == cfg_path {
+                        Ok(CFG_CONTENT.into())
+                    } else {
+                        let state = State {
+                            cfg_hash: hash::sha256(CFG_CONTENT.as_bytes()),
+                            denv_version: DENV_VERSION.into(),
+                            lenient,
+                            ..state::state(cwd, cfg_path)
+                        };
+                        Ok(serde_json::to_string(&state).unwrap())
+                    }
+                });
+                fs
+            }
+
+            #[inline]
+            fn vault_agent_cfg() -> VaultAgentConfig {
+                VaultAgentConfig {
+                    address: "https://vault.example.com".into(),
+                    auth_config: serde_json::Map::new(),
+                    auth_method: "approle".into(),
+                }
+            }
+
+            #[inline]
+            fn vault_agent_content(env_dirpath: &Path) -> String {
+                vault::agent_config(&vault_agent_cfg(), &env_dirpath.join(VAULT_SINK_FILENAME))
+            }
+
+            #[inline]
+            fn vault_agent_fs(
+                cwd: &'static Path,
+                env_dirpath: &'static Path,
+                cfg_path: &'static Path,
+                cfg_hash: String,
+            ) -> StubFileSystem {
+                let mut fs = stub_fs(cwd, env_dirpath, cfg_path);
+                let state_path = env_dirpath.join(STATE_FILENAME);
+                fs.stub_file_exists_fn(move |path| {
+                    path == cfg_path || path == state_path || is_allow_file(path)
+                });
+                fs.stub_read_to_string_fn(move |path| {
+                    if path == cfg_path {
+                        Ok(CFG_CONTENT.into())
+                    } else {
+                        let state = State {
+                            vault_agent: Some(VaultAgentState {
+                                cfg_hash: cfg_hash.clone(),
+                                pid: VAULT_AGENT_PID,
+                            }),
+                            ..state::state(cwd, cfg_path)
+                        };
+                        Ok(serde_json::to_string(&state).unwrap())
+                    }
+                });
+                fs
+            }
+
+            #[inline]
+            fn verify(data: &Data, out: Vec<u8>, res: Result<()>) {
+                let expected_out = format!(
+                    "export {}='{}'\nexport {}='{}'\nexport {}='{}'\nexport {}='{}':\"${{{}}}\"\nexport {}='{}'\nexport {}='{}'\nexport {}='{}'\n",
+                    DENV_CWD_VAR_NAME,
+                    data.cwd.display(),
+                    DENV_CFG_FILE_VAR_NAME,
+                    data.cfg_path.display(),
+                    DENV_PATH_BACKUP_VAR_NAME,
+                    data.path_env_var_value,
+                    PATH_VAR_NAME,
+                    data.env_dirpath.display(),
+                    PATH_VAR_NAME,
+                    render::tool_version_var_name(data.soft_name),
+                    SOFT_VERSION,
+                    SOFT_VAR_NAME,
+                    soft_var(data.soft_name).value,
+                    data.var_name,
+                    data.var_value,
+                );
+                res.unwrap();
+                let out = String::from_utf8(out).unwrap();
+                assert_eq!(out, expected_out);
+            }
+        }
+
+        mod migrate {
             use super::*;
 
-            macro_rules! tests {
-                ($ident:ident, $shell:expr, $template:literal) => {
-                    mod $ident {
-                        use super::*;
+            #[test]
+            fn should_return_store_err() {
+                test(Some(STORE_VERSION + 1), |_, res| match res.unwrap_err() {
+                    Error::Store(_) => {}
+                    err => panic!("{}", err),
+                });
+            }
 
-                        #[test]
-                        fn should_return_io_err() {
-                            test(
-                                $shell,
-                                WriteFailer,
-                                vec!["denv".into(), "hook".into(), stringify!($ident).into()],
-                                |_, res| {
-                                    let err = res.unwrap_err();
-                                    match err {
-                                        Error::Io(_) => {}
-                                        err => panic!("{}", err),
-                                    }
-                                },
-                            );
-                        }
+            #[test]
+            fn should_migrate_store() {
+                test(None, |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    assert_eq!(
+                        out,
+                        format!("Store migrated to version {}\n", STORE_VERSION)
+                    );
+                });
+            }
 
-                        #[test]
-                        fn should_return_ok_with_opts() {
-                            let opt1 = "-vvvv";
-                            let opt2 = "--no-color";
-                            let args = vec![
-                                "denv".into(),
-                                opt1.into(),
-                                opt2.into(),
-                                "hook".into(),
-                                stringify!($ident).into(),
-                            ];
-                            test($shell, vec![], args.clone(), |out, res| {
-                                let cli = format!("{} {} {}", args[0], opt1, opt2);
-                                verify(out, res, cli, include_str!($template));
-                            });
-                        }
+            #[test]
+            fn should_print_store_is_up_to_date() {
+                test(Some(STORE_VERSION), |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    assert_eq!(
+                        out,
+                        format!("Store is up to date (version {})\n", STORE_VERSION)
+                    );
+                });
+            }
 
-                        #[test]
-                        fn should_return_ok_without_opts() {
-                            let args =
-                                vec!["denv".into(), "hook".into(), stringify!($ident).into()];
-                            test($shell, vec![], args.clone(), |out, res| {
-                                verify(out, res, args[0].clone(), include_str!($template));
+            #[inline]
+            fn test<F: Fn(Vec<u8>, Result<()>)>(version: Option<u32>, assert_fn: F) {
+                let runner = Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(StubConfigLoader::default()),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(VarDefinition::into_var),
+                    create_fs_fn: Box::new(move |_| {
+                        let mut fs = StubFileSystem::default();
+                        fs.stub_store_version_fn(move || Ok(version));
+                        fs.stub_migrate_legacy_layout_fn(|| Ok(()));
+                        fs.stub_set_store_version_fn(|_| Ok(()));
+                        Box::new(fs)
+                    }),
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn: Box::new(|var_name| panic!("unexpected {}", var_name)),
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                };
+                let res = runner.run(Command::Migrate, Options::default());
+                let out = runner.out.into_inner();
+                assert_fn(out, res);
+            }
+        }
+
+        mod path {
+            use super::*;
+
+            const CWD: &str = "/cwd";
+            const ENV_DIRPATH: &str = "/env";
+
+            #[test]
+            fn should_explain_entries() {
+                let table_opts = TableOptions::default();
+                test(true, table_opts, loaded_env_var_fn(), |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    let expected_out = "ENTRY         SOURCE\n/custom       added after load\n/env          denv environment\n/cwd/bin      denv configuration\n/usr/bin      PATH before load\n/bin          PATH before load\n/cwd/scripts  denv configuration\n";
+                    assert_eq!(out, expected_out);
+                });
+            }
+
+            #[test]
+            fn should_explain_entries_if_env_is_not_loaded() {
+                let env_var_fn = Box::new(|var_name: &str| match var_name {
+                    PATH_VAR_NAME => Ok("/usr/bin:/bin".into()),
+                    _ => Err(env::VarError::NotPresent),
+                });
+                let table_opts = TableOptions {
+                    json: true,
+                    no_header: false,
+                };
+                test(true, table_opts, env_var_fn, |out, res| {
+                    res.unwrap();
+                    let entries: serde_json::Value = serde_json::from_slice(&out).unwrap();
+                    let expected = serde_json::json!([
+                        {"entry": "/usr/bin", "source": "PATH before load"},
+                        {"entry": "/bin", "source": "PATH before load"},
+                    ]);
+                    assert_eq!(entries, expected);
+                });
+            }
+
+            #[test]
+            fn should_print_entries() {
+                let table_opts = TableOptions::default();
+                test(false, table_opts, loaded_env_var_fn(), |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    assert_eq!(
+                        out,
+                        "/custom\n/env\n/cwd/bin\n/usr/bin\n/bin\n/cwd/scripts\n"
+                    );
+                });
+            }
+
+            #[inline]
+            fn loaded_env_var_fn() -> Box<EnvVarFn> {
+                Box::new(|var_name| match var_name {
+                    DENV_CWD_VAR_NAME => Ok(CWD.into()),
+                    DENV_PATH_BACKUP_VAR_NAME => Ok("/usr/bin:/bin".into()),
+                    PATH_VAR_NAME => Ok(format!(
+                        "/custom:{}:/cwd/bin::/usr/bin:/bin:/cwd/scripts",
+                        ENV_DIRPATH
+                    )),
+                    _ => panic!("unexpected {}", var_name),
+                })
+            }
+
+            #[inline]
+            fn test<F: Fn(Vec<u8>, Result<()>)>(
+                explain: bool,
+                table_opts: TableOptions,
+                env_var_fn: Box<EnvVarFn>,
+                assert_fn: F,
+            ) {
+                let runner = Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(StubConfigLoader::default()),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(VarDefinition::into_var),
+                    create_fs_fn: Box::new(|_| {
+                        let mut fs = StubFileSystem::default();
+                        fs.stub_ensure_env_dir_fn(|project_dirpath| {
+                            assert_eq!(project_dirpath, Path::new(CWD));
+                            Ok(PathBuf::from(ENV_DIRPATH))
+                        });
+                        fs.stub_file_exists_fn(|path| {
+                            assert_eq!(path, Path::new(ENV_DIRPATH).join(STATE_FILENAME));
+                            true
+                        });
+                        fs.stub_read_to_string_fn(|_| {
+                            let mut state = state::state(Path::new(CWD), Path::new(CWD));
+                            state.path_prefix = format!("{}:/cwd/bin", ENV_DIRPATH);
+                            state.path_suffix = "/cwd/scripts".into();
+                            Ok(serde_json::to_string(&state).unwrap())
+                        });
+                        Box::new(fs)
+                    }),
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn,
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                };
+                let cmd = Command::Path {
+                    explain,
+                    table_opts,
+                };
+                let res = runner.run(cmd, Options::default());
+                let out = runner.out.into_inner();
+                assert_fn(out, res);
+            }
+        }
+
+        mod remove {
+            use super::*;
+            use std::sync::{Arc, Mutex};
+
+            const CFG_CONTENT: &str =
+                "version: v1\nsoftwares:\n  terraform: 1.6.2\nset:\n  - name: FOO\n    value: bar\n";
+            const CFG_PATH: &str = "/cwd/denv.yml";
+            const CWD: &str = "/cwd";
+
+            #[derive(Default)]
+            struct Writes {
+                deleted_softs: Vec<(String, String)>,
+                written: Vec<String>,
+            }
+
+            #[test]
+            fn should_print_diff() {
+                test("terraform", None, false, false, |out, writes, res| {
+                    res.unwrap();
+                    let expected = "--- /cwd/denv.yml\n+++ /cwd/denv.yml\n@@ -1,5 +1,3 @@\n version: v1\n-softwares:\n-  terraform: 1.6.2\n set:\n   - name: FOO\n";
+                    assert_eq!(out, expected);
+                    assert!(writes.written.is_empty());
+                });
+            }
+
+            #[test]
+            fn should_remove_software_and_uninstall_it() {
+                test("terraform", None, true, true, |_, writes, res| {
+                    res.unwrap();
+                    let expected = CFG_CONTENT.replace("softwares:\n  terraform: 1.6.2\n", "");
+                    assert_eq!(writes.written, [expected]);
+                    assert_eq!(
+                        writes.deleted_softs,
+                        [("terraform".to_string(), "1.6.2".to_string())]
+                    );
+                });
+            }
+
+            #[test]
+            fn should_remove_var() {
+                test("var", Some("FOO"), false, true, |_, writes, res| {
+                    res.unwrap();
+                    let expected = CFG_CONTENT.replace("set:\n  - name: FOO\n    value: bar\n", "");
+                    assert_eq!(writes.written, [expected]);
+                    assert!(writes.deleted_softs.is_empty());
+                });
+            }
+
+            #[test]
+            fn should_return_software_not_found_err() {
+                test("chart-testing", None, false, true, |out, writes, res| {
+                    match res.unwrap_err() {
+                        Error::SoftwareNotFound(name) => assert_eq!(name, "chart-testing"),
+                        err => panic!("{}", err),
+                    }
+                    assert!(out.is_empty());
+                    assert!(writes.written.is_empty());
+                });
+            }
+
+            #[test]
+            fn should_return_unexpected_argument_err() {
+                test("terraform", Some("FOO"), false, true, |out, writes, res| {
+                    match res.unwrap_err() {
+                        Error::UnexpectedArgument(arg) => assert_eq!(arg, "FOO"),
+                        err => panic!("{}", err),
+                    }
+                    assert!(out.is_empty());
+                    assert!(writes.written.is_empty());
+                });
+            }
+
+            #[test]
+            fn should_return_unsupported_software_err() {
+                test("kind", None, false, true, |_, writes, res| {
+                    match res.unwrap_err() {
+                        Error::UnsupportedSoftware(name) => assert_eq!(name, "kind"),
+                        err => panic!("{}", err),
+                    }
+                    assert!(writes.written.is_empty());
+                });
+            }
+
+            #[test]
+            fn should_return_var_not_found_err() {
+                test("var", Some("BAR"), false, true, |out, writes, res| {
+                    match res.unwrap_err() {
+                        Error::VarNotFound(name) => assert_eq!(name, "BAR"),
+                        err => panic!("{}", err),
+                    }
+                    assert!(out.is_empty());
+                    assert!(writes.written.is_empty());
+                });
+            }
+
+            #[inline]
+            fn test<F: Fn(String, Writes, Result<()>)>(
+                name: &str,
+                var_name: Option<&str>,
+                uninstall: bool,
+                write: bool,
+                assert_fn: F,
+            ) {
+                let writes = Arc::new(Mutex::new(Writes::default()));
+                let mut cfg_loader = StubConfigLoader::default();
+                {
+                    let writes = writes.clone();
+                    cfg_loader.stub_load_fn(move |path| {
+                        assert_eq!(path, Path::new(CFG_PATH));
+                        let mut soft_defs = vec![];
+                        if writes.lock().unwrap().written.is_empty() {
+                            soft_defs.push(SoftwareDefinition {
+                                auto: vec![],
+                                bin_name: None,
+                                default_args: vec![],
+                                kind: SoftwareDefinitionKind::Terraform,
+                                source: SoftwareSource::Denv,
+                                version: "1.6.2".into(),
                             });
                         }
-                    }
+                        Ok(Config {
+                            download: DownloadConfig::default(),
+                            latency_budget_ms: None,
+                            message: None,
+                            notify: None,
+                            project_bin: false,
+                            remove_quarantine: false,
+                            soft_defs,
+                            unset_vars: vec![],
+                            var_defs: vec![],
+                            vault_agent: None,
+                        })
+                    });
+                }
+                let runner = Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(cfg_loader),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(VarDefinition::into_var),
+                    create_fs_fn: {
+                        let writes = writes.clone();
+                        Box::new(move |_| {
+                            let mut fs = StubFileSystem::default();
+                            fs.stub_cwd_fn(|| Ok(PathBuf::from(CWD)));
+                            fs.stub_file_exists_fn(|path| path == Path::new(CFG_PATH));
+                            fs.stub_read_to_string_fn(|path| {
+                                assert_eq!(path, Path::new(CFG_PATH));
+                                Ok(CFG_CONTENT.into())
+                            });
+                            let write_writes = writes.clone();
+                            fs.stub_write_fn(move |path, content| {
+                                assert_eq!(path, Path::new(CFG_PATH));
+                                write_writes.lock().unwrap().written.push(content.into());
+                                Ok(())
+                            });
+                            fs.stub_software_dirpath_fn(|name, version| {
+                                Ok(Path::new("/softwares").join(name).join(version))
+                            });
+                            let writes = writes.clone();
+                            fs.stub_delete_software_dir_fn(move |name, version| {
+                                let soft = (name.to_string(), version.to_string());
+                                writes.lock().unwrap().deleted_softs.push(soft);
+                                Ok(())
+                            });
+                            Box::new(fs)
+                        })
+                    },
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn: Box::new(|var_name| panic!("unexpected {}", var_name)),
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                };
+                let cmd = Command::Remove {
+                    name: name.into(),
+                    var_name: var_name.map(String::from),
+                    uninstall,
+                    write,
                 };
+                let res = runner.run(cmd, Options::default());
+                let out = String::from_utf8(runner.out.into_inner()).unwrap();
+                let writes = std::mem::take(&mut *writes.lock().unwrap());
+                assert_fn(out, writes, res);
             }
+        }
 
-            tests!(bash, Shell::Bash, "../resources/main/hooks/bash");
-            tests!(zsh, Shell::Zsh, "../resources/main/hooks/zsh");
+        mod sbom {
+            use super::*;
+            use crate::soft::receipt;
+
+            const CWD: &str = "/cwd";
+            const ENV_DIRPATH: &str = "/env";
+            const SOFT_DIRPATH: &str = "/softwares/soft/1.0.0";
+
+            #[test]
+            fn should_return_env_not_loaded_err() {
+                test(
+                    SbomFormat::Cyclonedx,
+                    true,
+                    Box::new(|_| Err(env::VarError::NotPresent)),
+                    |_, res| match res.unwrap_err() {
+                        Error::EnvNotLoaded => {}
+                        err => panic!("{}", err),
+                    },
+                );
+            }
+
+            #[test]
+            fn should_return_cyclonedx_bom() {
+                test(SbomFormat::Cyclonedx, true, env_var_fn(), |out, res| {
+                    res.unwrap();
+                    let bom: serde_json::Value = serde_json::from_slice(&out).unwrap();
+                    let softs = [InstalledSoftware {
+                        name: "soft".into(),
+                        receipt: Some(receipt::receipt()),
+                        version: "1.0.0".into(),
+                    }];
+                    assert_eq!(bom, crate::sbom::cyclonedx(&softs));
+                });
+            }
+
+            #[test]
+            fn should_return_spdx_document() {
+                test(SbomFormat::Spdx, false, env_var_fn(), |out, res| {
+                    res.unwrap();
+                    let doc: serde_json::Value = serde_json::from_slice(&out).unwrap();
+                    let softs = [InstalledSoftware {
+                        name: "soft".into(),
+                        receipt: None,
+                        version: "1.0.0".into(),
+                    }];
+                    assert_eq!(doc, crate::sbom::spdx(&softs, "cwd", "id", 0));
+                });
+            }
 
             #[inline]
-            fn test<W: Write, F: Fn(W, Result<()>)>(
-                shell: Shell,
-                out: W,
-                args: Vec<String>,
+            fn env_var_fn() -> Box<EnvVarFn> {
+                Box::new(|var_name| match var_name {
+                    DENV_CWD_VAR_NAME => Ok(CWD.into()),
+                    _ => panic!("unexpected {}", var_name),
+                })
+            }
+
+            #[inline]
+            fn test<F: Fn(Vec<u8>, Result<()>)>(
+                format: SbomFormat,
+                receipt_exists: bool,
+                env_var_fn: Box<EnvVarFn>,
                 assert_fn: F,
             ) {
                 let runner = Runner {
-                    args_fn: Box::new(move || args.clone()),
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
                     cfg_loader: Box::new(StubConfigLoader::default()),
                     convert_soft_fn: Box::new(SoftwareDefinition::into_software),
                     convert_var_fn: Box::new(VarDefinition::into_var),
-                    create_fs_fn: Box::new(|| Box::new(StubFileSystem::default())),
-                    env_var_fn: Box::new(|var_name| env::var(var_name)),
-                    out: Mutex::new(out),
+                    create_fs_fn: Box::new(move |_| {
+                        let mut fs = StubFileSystem::default();
+                        fs.stub_ensure_env_dir_fn(|project_dirpath| {
+                            assert_eq!(project_dirpath, Path::new(CWD));
+                            Ok(PathBuf::from(ENV_DIRPATH))
+                        });
+                        fs.stub_software_dirpath_fn(|name, version| {
+                            assert_eq!(name, "soft");
+                            assert_eq!(version, "1.0.0");
+                            Ok(PathBuf::from(SOFT_DIRPATH))
+                        });
+                        fs.stub_file_exists_fn(move |path| {
+                            path == Path::new(ENV_DIRPATH).join(STATE_FILENAME) || receipt_exists
+                        });
+                        fs.stub_read_to_string_fn(|path| {
+                            if path == Path::new(ENV_DIRPATH).join(STATE_FILENAME) {
+                                let state = state::state(Path::new(CWD), Path::new(CWD));
+                                Ok(serde_json::to_string(&state).unwrap())
+                            } else {
+                                Ok(serde_json::to_string(&receipt::receipt()).unwrap())
+                            }
+                        });
+                        Box::new(fs)
+                    }),
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn,
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
                 };
-                let res = runner.run(Command::Hook(shell), Options::default());
-                let out = runner.out.into_inner().unwrap();
+                let res = runner.run(Command::Sbom { format }, Options::default());
+                let out = runner.out.into_inner();
                 assert_fn(out, res);
             }
+        }
+
+        mod status {
+            use super::*;
+
+            const CFG_PATH: &str = "/cwd/denv.yml";
+            const CWD: &str = "/cwd";
+            const ENV_DIRPATH: &str = "/env";
+
+            #[test]
+            fn should_print_env_is_not_loaded() {
+                let env_var_fn = Box::new(|_: &str| Err(env::VarError::NotPresent));
+                test(TableOptions::default(), env_var_fn, |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    assert_eq!(out, "No environment loaded\n");
+                });
+            }
+
+            #[test]
+            fn should_return_json() {
+                let table_opts = TableOptions {
+                    json: true,
+                    no_header: false,
+                };
+                test(table_opts, env_var_fn(), |out, res| {
+                    res.unwrap();
+                    let status: serde_json::Value = serde_json::from_slice(&out).unwrap();
+                    let expected = serde_json::json!({
+                        "cfg_candidates": [
+                            {"matched": true, "path": CFG_PATH},
+                            {"matched": false, "path": "/cwd/denv.yaml"},
+                            {"matched": false, "path": "/cwd/.denv.yml"},
+                            {"matched": false, "path": "/cwd/.config/denv.yml"},
+                        ],
+                        "cfg_filepath": CFG_PATH,
+                        "loaded": true,
+                        "project_dirpath": CWD,
+                        "softwares": [{
+                            "name": "soft",
+                            "version": "1.0.0",
+                        }],
+                        "vars": ["VAR"],
+                    });
+                    assert_eq!(status, expected);
+                });
+            }
+
+            #[test]
+            fn should_return_text() {
+                test(TableOptions::default(), env_var_fn(), |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    let expected_out = format!(
+                        "Environment loaded from {}\n\n\
+                         CONFIGURATION          MATCHED\n\
+                         {}          yes\n\
+                         /cwd/denv.yaml         no\n\
+                         /cwd/.denv.yml         no\n\
+                         /cwd/.config/denv.yml  no\n\n\
+                         NAME  VERSION\nsoft  1.0.0\n\nVARIABLE\nVAR\n",
+                        CFG_PATH, CFG_PATH
+                    );
+                    assert_eq!(out, expected_out);
+                });
+            }
 
             #[inline]
-            fn verify(out: Vec<u8>, res: Result<()>, cli: String, template: &str) {
-                res.unwrap();
-                let out = String::from_utf8(out).unwrap();
-                let statement = template
-                    .replace("<denv_cwd_var_name>", DENV_CWD_VAR_NAME)
-                    .replace("<load_cmd>", &cli)
-                    .replace("<unload_cmd>", &format!("{} unload", cli));
-                assert_eq!(out, statement);
+            fn env_var_fn() -> Box<EnvVarFn> {
+                Box::new(|var_name| match var_name {
+                    DENV_CWD_VAR_NAME => Ok(CWD.into()),
+                    _ => panic!("unexpected {}", var_name),
+                })
+            }
+
+            #[inline]
+            fn test<F: Fn(Vec<u8>, Result<()>)>(
+                table_opts: TableOptions,
+                env_var_fn: Box<EnvVarFn>,
+                assert_fn: F,
+            ) {
+                let runner = Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(StubConfigLoader::default()),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(VarDefinition::into_var),
+                    create_fs_fn: Box::new(|_| {
+                        let mut fs = StubFileSystem::default();
+                        fs.stub_ensure_env_dir_fn(|project_dirpath| {
+                            assert_eq!(project_dirpath, Path::new(CWD));
+                            Ok(PathBuf::from(ENV_DIRPATH))
+                        });
+                        fs.stub_file_exists_fn(|path| {
+                            path == Path::new(ENV_DIRPATH).join(STATE_FILENAME)
+                        });
+                        fs.stub_read_to_string_fn(|path| {
+                            assert_eq!(path, Path::new(ENV_DIRPATH).join(STATE_FILENAME));
+                            let state = state::state(Path::new(CWD), Path::new(CFG_PATH));
+                            Ok(serde_json::to_string(&state).unwrap())
+                        });
+                        Box::new(fs)
+                    }),
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn,
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                };
+                let res = runner.run(Command::Status { table_opts }, Options::default());
+                let out = runner.out.into_inner();
+                assert_fn(out, res);
             }
         }
 
-        mod load {
+        mod unload {
             use super::*;
+            use std::sync::{Arc, Mutex};
+
+            const ENV_DIRPATH: &str = "/env";
+            const PATH_BACKUP: &str = "/usr/bin";
 
             struct Data {
                 cfg: Config,
-                cfg_path: &'static Path,
+                cfg_path: &'static str,
                 cwd: &'static Path,
-                env_dirpath: &'static Path,
-                opts: Options,
-                path_env_var_value: &'static str,
-                soft_name: &'static str,
-                var_name: &'static str,
-                var_value: &'static str,
+                state: Option<State>,
             }
 
             impl Default for Data {
                 fn default() -> Self {
+                    let cfg_path = "/config";
+                    let cwd = Path::new("/cwd");
                     Self {
                         cfg: Config {
-                            soft_defs: vec![SoftwareDefinition {
-                                kind: SoftwareDefinitionKind::Terraform,
-                                version: "1.2.3".into(),
-                            }],
+                            download: DownloadConfig::default(),
+                            latency_budget_ms: None,
+                            message: None,
+                            notify: None,
+                            project_bin: false,
+                            remove_quarantine: false,
+                            soft_defs: vec![],
+                            unset_vars: vec![],
                             var_defs: vec![VarDefinition {
                                 kind: VarDefinitionKind::Literal("value".into()),
                                 name: "var".into(),
                             }],
+                            vault_agent: None,
                         },
-                        cfg_path: Path::new("/config"),
-                        cwd: Path::new("/cwd"),
-                        env_dirpath: Path::new("/env"),
-                        opts: Options {
-                            cfg_filepath: Some(PathBuf::from("/config")),
-                            ..Options::default()
-                        },
-                        path_env_var_value: "path",
-                        soft_name: "soft1",
-                        var_name: "var1",
-                        var_value: "value1",
+                        cfg_path,
+                        cwd,
+                        state: Some(state::state(cwd, Path::new(cfg_path))),
                     }
                 }
             }
 
             struct Stubs {
                 cfg_loader: StubConfigLoader,
-                convert_soft_fn: Box<ConvertSoftFn>,
-                convert_var_fn: Box<ConvertVarFn>,
                 create_fs_fn: Box<CreateFsFn>,
                 env_var_fn: Box<EnvVarFn>,
+                kill_fn: Box<KillFn>,
             }
 
             impl Stubs {
@@ -500,31 +7788,24 @@ mod runner_test {
                     let cfg = data.cfg.clone();
                     let cfg_path = data.cfg_path;
                     let cwd = data.cwd;
-                    let env_dirpath = data.env_dirpath;
-                    let expected_soft_def = cfg.soft_defs[0].clone();
-                    let path_env_var_value = data.path_env_var_value;
-                    let soft_name = data.soft_name;
-                    let expected_var_def = cfg.var_defs[0].clone();
-                    let var_name = data.var_name;
-                    let var_value = data.var_value;
+                    let state = data.state.clone();
                     let mut stubs = Self {
                         cfg_loader: StubConfigLoader::default(),
-                        convert_soft_fn: Box::new(move |soft_def| {
-                            assert_eq!(soft_def, expected_soft_def);
-                            Box::new(stub_software(soft_name, cwd))
-                        }),
-                        convert_var_fn: Box::new(move |var_def| {
-                            assert_eq!(var_def, expected_var_def);
-                            Box::new(stub_var(var_name, var_value))
-                        }),
-                        create_fs_fn: Box::new(|| Box::new(stub_fs(cwd, env_dirpath))),
-                        env_var_fn: Box::new(|var_name| match var_name {
-                            PATH_VAR_NAME => Ok(path_env_var_value.into()),
+                        create_fs_fn: Box::new(move |_| Box::new(stub_fs(cwd, state.clone()))),
+                        env_var_fn: Box::new(move |var_name| match var_name {
+                            DENV_CFG_FILE_VAR_NAME => Ok(cfg_path.into()),
+                            DENV_CWD_VAR_NAME => Ok(cwd.display().to_string()),
+                            DENV_PATH_BACKUP_VAR_NAME => Ok(PATH_BACKUP.into()),
+                            PATH_VAR_NAME => Ok(format!("{}:{}", ENV_DIRPATH, PATH_BACKUP)),
+                            "DENV_TOOL_SOFT_VERSION" => Ok("1.0.0".into()),
+                            "VAR" => Ok("value".into()),
+                            DENV_IN_PROGRESS_VAR_NAME => Err(env::VarError::NotPresent),
                             _ => panic!("unexpected {}", var_name),
                         }),
+                        kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
                     };
                     stubs.cfg_loader.stub_load_fn(move |path| {
-                        assert_eq!(path, cfg_path);
+                        assert_eq!(path, Path::new(cfg_path));
                         Ok(cfg.clone())
                     });
                     stubs
@@ -532,231 +7813,563 @@ mod runner_test {
             }
 
             #[test]
-            fn should_return_config_err() {
+            fn should_return_env_not_loaded_err() {
+                let data = Data::default();
+                let mut stubs = Stubs::new(&data);
+                stubs.env_var_fn = Box::new(|_| Err(env::VarError::NotPresent));
+                test(vec![], false, stubs, |_, res| match res.unwrap_err() {
+                    Error::EnvNotLoaded => {}
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_return_io_err_if_ensure_env_dir_failed() {
+                let data = Data::default();
+                let cwd = data.cwd;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, None);
+                    fs.stub_ensure_env_dir_fn(|_| {
+                        Err(io::Error::from(io::ErrorKind::PermissionDenied))
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], false, stubs, |_, res| match res.unwrap_err() {
+                    Error::Io(_) => {}
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_return_io_err_if_state_is_invalid() {
                 let data = Data::default();
+                let cwd = data.cwd;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| Box::new(stub_invalid_state_fs(cwd)));
+                test(vec![], false, stubs, |_, res| match res.unwrap_err() {
+                    Error::Io(_) => {}
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_return_config_err_if_state_does_not_exist() {
+                let data = Data {
+                    state: None,
+                    ..Data::default()
+                };
                 let mut stubs = Stubs::new(&data);
                 stubs
                     .cfg_loader
                     .stub_load_fn(|_| Err(cfg::Error::Version(None)));
-                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
+                test(vec![], false, stubs, |_, res| match res.unwrap_err() {
                     Error::Config(_) => {}
                     err => panic!("{}", err),
                 });
             }
 
             #[test]
-            fn should_return_io_err_if_cwd_failed() {
+            fn should_return_io_err_if_write_on_output_failed() {
+                let data = Data::default();
+                let stubs = Stubs::new(&data);
+                test(WriteFailer, false, stubs, |_, res| match res.unwrap_err() {
+                    Error::Io(_) => {}
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_return_io_err_if_delete_env_dir_failed() {
+                let data = Data::default();
+                let cwd = data.cwd;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, None);
+                    fs.stub_delete_env_dir_fn(|_| {
+                        Err(io::Error::from(io::ErrorKind::PermissionDenied))
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], false, stubs, |_, res| match res.unwrap_err() {
+                    Error::Io(_) => {}
+                    err => panic!("{}", err),
+                });
+            }
+
+            #[test]
+            fn should_append_history_entry() {
+                let data = Data::default();
+                let cwd = data.cwd;
+                let state = data.state.clone();
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, state.clone());
+                    fs.stub_append_fn(|path, content| {
+                        assert_eq!(path, crate::history::filepath(Path::new(ENV_DIRPATH)));
+                        let entry: HistoryEntry = serde_json::from_str(content).unwrap();
+                        let expected_entry = HistoryEntry {
+                            action: Action::Unload,
+                            at: 0,
+                            cfg_hash: Some("hash".into()),
+                            duration_ms: 0,
+                            error: None,
+                            softwares: BTreeMap::from([("soft".into(), "1.0.0".into())]),
+                        };
+                        assert_eq!(entry, expected_entry);
+                        Ok(())
+                    });
+                    Box::new(fs)
+                });
+                test(vec![], false, stubs, |out, res| {
+                    verify(PATH_BACKUP, &["DENV_TOOL_SOFT_VERSION", "VAR"], out, res);
+                });
+            }
+
+            #[test]
+            fn should_unset_vars_of_state() {
+                let data = Data::default();
+                let mut stubs = Stubs::new(&data);
+                stubs
+                    .cfg_loader
+                    .stub_load_fn(|path| panic!("unexpected load of {}", path.display()));
+                test(vec![], false, stubs, |out, res| {
+                    verify(PATH_BACKUP, &["DENV_TOOL_SOFT_VERSION", "VAR"], out, res);
+                });
+            }
+
+            #[test]
+            fn should_unset_software_vars_of_state() {
+                let data = Data::default();
+                let cwd = data.cwd;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut state = state::state(cwd, Path::new("/config"));
+                    state.softwares[0].vars = vec![VarState {
+                        name: "SOFT_HOME".into(),
+                        value: "/softwares/soft/1.0.0".into(),
+                    }];
+                    Box::new(stub_fs(cwd, Some(state)))
+                });
+                let env_var_fn = stubs.env_var_fn;
+                stubs.env_var_fn = Box::new(move |var_name| match var_name {
+                    "SOFT_HOME" => Ok("/softwares/soft/1.0.0".into()),
+                    _ => env_var_fn(var_name),
+                });
+                test(vec![], false, stubs, |out, res| {
+                    verify(
+                        PATH_BACKUP,
+                        &["DENV_TOOL_SOFT_VERSION", "SOFT_HOME", "VAR"],
+                        out,
+                        res,
+                    );
+                });
+            }
+
+            #[test]
+            fn should_stop_vault_agent_of_state() {
+                let data = Data::default();
+                let cwd = data.cwd;
+                let signals = Arc::new(Mutex::new(vec![]));
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let state = State {
+                        vault_agent: Some(VaultAgentState {
+                            cfg_hash: "hash".into(),
+                            pid: 42,
+                        }),
+                        ..state::state(cwd, Path::new("/config"))
+                    };
+                    Box::new(stub_fs(cwd, Some(state)))
+                });
+                let killed = signals.clone();
+                stubs.kill_fn = Box::new(move |pid, sig| {
+                    killed.lock().unwrap().push((pid, sig));
+                    Ok(())
+                });
+                test(vec![], false, stubs, |out, res| {
+                    verify(PATH_BACKUP, &["DENV_TOOL_SOFT_VERSION", "VAR"], out, res);
+                });
+                assert_eq!(*signals.lock().unwrap(), vec![(42, libc::SIGTERM)]);
+            }
+
+            #[test]
+            fn should_keep_path_entries_added_after_load() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let mut stubs = Stubs::new(&data);
+                stubs.env_var_fn = Box::new(move |var_name| match var_name {
+                    DENV_CFG_FILE_VAR_NAME => Ok(cfg_path.into()),
+                    DENV_CWD_VAR_NAME => Ok(cwd.display().to_string()),
+                    DENV_PATH_BACKUP_VAR_NAME => Ok(PATH_BACKUP.into()),
+                    PATH_VAR_NAME => Ok(format!("/custom:{}:{}", ENV_DIRPATH, PATH_BACKUP)),
+                    "DENV_TOOL_SOFT_VERSION" => Ok("1.0.0".into()),
+                    "VAR" => Ok("value".into()),
+                    DENV_IN_PROGRESS_VAR_NAME => Err(env::VarError::NotPresent),
+                    _ => panic!("unexpected {}", var_name),
+                });
+                test(vec![], false, stubs, |out, res| {
+                    verify(
+                        "/custom:/usr/bin",
+                        &["DENV_TOOL_SOFT_VERSION", "VAR"],
+                        out,
+                        res,
+                    );
+                });
+            }
+
+            #[test]
+            fn should_remove_path_entries_of_config() {
+                let data = Data::default();
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut state = state::state(cwd, Path::new(cfg_path));
+                    state.path_prefix = format!("{}:/cwd/node_modules/.bin", ENV_DIRPATH);
+                    state.path_suffix = "/cwd/scripts".into();
+                    Box::new(stub_fs(cwd, Some(state)))
+                });
+                let env_var_fn = stubs.env_var_fn;
+                stubs.env_var_fn = Box::new(move |var_name| match var_name {
+                    PATH_VAR_NAME => Ok(format!(
+                        "{}:/cwd/node_modules/.bin:{}:/cwd/scripts",
+                        ENV_DIRPATH, PATH_BACKUP
+                    )),
+                    _ => env_var_fn(var_name),
+                });
+                test(vec![], false, stubs, |out, res| {
+                    verify(PATH_BACKUP, &["DENV_TOOL_SOFT_VERSION", "VAR"], out, res);
+                });
+            }
+
+            #[test]
+            fn should_keep_vars_modified_after_load() {
+                let data = Data::default();
+                let stubs = modified_var_stubs(&data);
+                test(vec![], false, stubs, |out, res| {
+                    verify(PATH_BACKUP, &["DENV_TOOL_SOFT_VERSION"], out, res);
+                });
+            }
+
+            #[test]
+            fn should_unset_vars_modified_after_load_with_force() {
+                let data = Data::default();
+                let stubs = modified_var_stubs(&data);
+                test(vec![], true, stubs, |out, res| {
+                    verify(PATH_BACKUP, &["DENV_TOOL_SOFT_VERSION", "VAR"], out, res);
+                });
+            }
+
+            #[test]
+            fn should_restore_unset_vars() {
                 let data = Data::default();
                 let cwd = data.cwd;
-                let env_dirpath = data.env_dirpath;
                 let mut stubs = Stubs::new(&data);
-                stubs.create_fs_fn = Box::new(|| {
-                    let mut fs = stub_fs(cwd, env_dirpath);
-                    fs.stub_cwd_fn(|| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
-                    Box::new(fs)
+                stubs.create_fs_fn =
+                    Box::new(move |_| Box::new(stub_fs(cwd, Some(unset_state(cwd)))));
+                let env_var_fn = stubs.env_var_fn;
+                stubs.env_var_fn = Box::new(move |var_name| match var_name {
+                    "AWS_PROFILE" => Err(env::VarError::NotPresent),
+                    _ => env_var_fn(var_name),
                 });
-                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
-                    Error::Io(_) => {}
-                    err => panic!("{}", err),
+                test(vec![], false, stubs, |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    assert!(
+                        out.ends_with("unset VAR\nexport AWS_PROFILE='default'\n"),
+                        "{}",
+                        out
+                    );
                 });
             }
 
             #[test]
-            fn should_return_io_err_if_ensure_env_dir_failed() {
+            fn should_keep_unset_vars_set_after_load() {
                 let data = Data::default();
                 let cwd = data.cwd;
-                let env_dirpath = data.env_dirpath;
                 let mut stubs = Stubs::new(&data);
-                stubs.create_fs_fn = Box::new(|| {
-                    let mut fs = stub_fs(cwd, env_dirpath);
-                    fs.stub_ensure_env_dir_fn(|_| {
-                        Err(io::Error::from(io::ErrorKind::PermissionDenied))
-                    });
-                    Box::new(fs)
+                stubs.create_fs_fn =
+                    Box::new(move |_| Box::new(stub_fs(cwd, Some(unset_state(cwd)))));
+                let env_var_fn = stubs.env_var_fn;
+                stubs.env_var_fn = Box::new(move |var_name| match var_name {
+                    "AWS_PROFILE" => Ok("prod".into()),
+                    _ => env_var_fn(var_name),
                 });
-                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
-                    Error::Io(_) => {}
-                    err => panic!("{}", err),
+                test(vec![], false, stubs, |out, res| {
+                    verify(PATH_BACKUP, &["DENV_TOOL_SOFT_VERSION", "VAR"], out, res);
                 });
             }
 
             #[test]
-            fn should_return_install_err_if_install_failed() {
+            fn should_source_deactivate_scripts() {
                 let data = Data::default();
                 let cwd = data.cwd;
-                let soft_name = data.soft_name;
                 let mut stubs = Stubs::new(&data);
-                stubs.convert_soft_fn = Box::new(move |_| {
-                    let mut soft = stub_software(soft_name, cwd);
-                    soft.stub_install_fn(|_, _| Err(SoftwareError::UnsupportedSystem));
-                    Box::new(soft)
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, Some(state::state(cwd, Path::new("/config"))));
+                    fs.stub_list_files_fn(|dirpath| Ok(vec![dirpath.join("10-script.sh")]));
+                    Box::new(fs)
                 });
-                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
-                    Error::Install(errs) => {
-                        assert_eq!(errs.len(), 1);
-                        let err = &errs[0];
-                        assert_eq!(err.soft.name(), data.soft_name);
-                        match &err.cause {
-                            SoftwareError::UnsupportedSystem => {}
-                            err => panic!("{}", err),
-                        }
-                    }
-                    err => panic!("{}", err),
+                test(vec![], false, stubs, |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    let expected_suffix = format!(
+                        "unset VAR\nexport {}='1'\n. '{}'\nunset {}\n",
+                        DENV_IN_PROGRESS_VAR_NAME,
+                        cwd.join(DEACTIVATE_DIRPATH).join("10-script.sh").display(),
+                        DENV_IN_PROGRESS_VAR_NAME,
+                    );
+                    assert!(out.ends_with(&expected_suffix), "{}", out);
                 });
             }
 
             #[test]
-            fn should_return_io_err_if_write_on_output_failed() {
-                let data = Data::default();
+            fn should_unset_vars_of_config_if_state_does_not_exist() {
+                let data = Data {
+                    state: None,
+                    ..Data::default()
+                };
                 let stubs = Stubs::new(&data);
-                test(WriteFailer, &data.opts, stubs, |_, res| {
-                    match res.unwrap_err() {
-                        Error::Io(_) => {}
-                        err => panic!("{}", err),
-                    }
+                test(vec![], false, stubs, |out, res| {
+                    verify(PATH_BACKUP, &["var"], out, res);
                 });
             }
 
             #[test]
-            fn should_return_install_err_if_compute_failed() {
-                let data = Data::default();
-                let var_name = data.var_name;
-                let var_value = data.var_value;
+            fn should_unset_vars_of_dotenv_file_of_config_if_state_does_not_exist() {
+                let mut data = Data {
+                    state: None,
+                    ..Data::default()
+                };
+                data.cfg.var_defs.push(VarDefinition {
+                    kind: VarDefinitionKind::DotenvFile(data.cwd.join(".env")),
+                    name: ".env".into(),
+                });
+                let cwd = data.cwd;
                 let mut stubs = Stubs::new(&data);
-                stubs.convert_var_fn = Box::new(|_| {
-                    let mut var = stub_var(var_name, var_value);
-                    var.stub_compute_value_fn(|| Err(VarError::Stub));
-                    Box::new(var)
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = stub_fs(cwd, None);
+                    fs.stub_read_to_string_fn(move |path| {
+                        assert_eq!(path, cwd.join(".env"));
+                        Ok("FOO=foo\nexport BAR=bar\n".into())
+                    });
+                    Box::new(fs)
                 });
-                test(vec![], &data.opts, stubs, |_, res| match res.unwrap_err() {
-                    Error::Compute(errs) => {
-                        assert_eq!(errs.len(), 1);
-                        let err = &errs[0];
-                        assert_eq!(err.var.name(), data.var_name);
-                        match err.cause {
-                            VarError::Stub => {}
-                        }
-                    }
-                    err => panic!("{}", err),
+                test(vec![], false, stubs, |out, res| {
+                    verify(PATH_BACKUP, &["var", "FOO", "BAR"], out, res);
                 });
             }
 
             #[test]
-            fn should_return_install_ok_with_opts() {
+            fn should_unset_vars_of_config_if_state_is_invalid_with_force() {
                 let data = Data::default();
-                let stubs = Stubs::new(&data);
-                test(vec![], &data.opts, stubs, |out, res| {
-                    verify(&data, out, res);
+                let cwd = data.cwd;
+                let mut stubs = Stubs::new(&data);
+                stubs.create_fs_fn = Box::new(move |_| Box::new(stub_invalid_state_fs(cwd)));
+                test(vec![], true, stubs, |out, res| {
+                    verify(PATH_BACKUP, &["var"], out, res);
                 });
             }
 
             #[test]
-            fn should_return_install_ok_without_opts() {
+            fn should_unset_denv_vars_if_config_failed_with_force() {
                 let data = Data {
-                    cfg_path: Path::new("denv.yml"),
-                    opts: Options::default(),
+                    state: None,
                     ..Data::default()
                 };
-                let stubs = Stubs::new(&data);
-                test(vec![], &data.opts, stubs, |out, res| {
-                    verify(&data, out, res);
+                let mut stubs = Stubs::new(&data);
+                stubs
+                    .cfg_loader
+                    .stub_load_fn(|_| Err(cfg::Error::Version(None)));
+                test(vec![], true, stubs, |out, res| {
+                    verify(PATH_BACKUP, &[], out, res);
+                });
+            }
+
+            #[inline]
+            fn modified_var_stubs(data: &Data) -> Stubs {
+                let cfg_path = data.cfg_path;
+                let cwd = data.cwd;
+                let mut stubs = Stubs::new(data);
+                stubs.env_var_fn = Box::new(move |var_name| match var_name {
+                    DENV_CFG_FILE_VAR_NAME => Ok(cfg_path.into()),
+                    DENV_CWD_VAR_NAME => Ok(cwd.display().to_string()),
+                    DENV_PATH_BACKUP_VAR_NAME => Ok(PATH_BACKUP.into()),
+                    PATH_VAR_NAME => Ok(format!("{}:{}", ENV_DIRPATH, PATH_BACKUP)),
+                    "DENV_TOOL_SOFT_VERSION" => Ok("1.0.0".into()),
+                    "VAR" => Ok("overridden".into()),
+                    DENV_IN_PROGRESS_VAR_NAME => Err(env::VarError::NotPresent),
+                    _ => panic!("unexpected {}", var_name),
                 });
+                stubs
             }
 
             #[inline]
-            fn stub_fs(cwd: &'static Path, env_dirpath: &'static Path) -> StubFileSystem {
+            fn stub_fs(cwd: &'static Path, state: Option<State>) -> StubFileSystem {
                 let mut fs = StubFileSystem::default();
-                fs.stub_cwd_fn(|| Ok(cwd.to_path_buf()));
                 fs.stub_ensure_env_dir_fn(move |project_dirpath| {
                     assert_eq!(project_dirpath, cwd);
-                    Ok(env_dirpath.to_path_buf())
+                    Ok(PathBuf::from(ENV_DIRPATH))
+                });
+                let state_exists = state.is_some();
+                fs.stub_file_exists_fn(move |path| {
+                    assert_eq!(path, Path::new(ENV_DIRPATH).join(STATE_FILENAME));
+                    state_exists
+                });
+                fs.stub_read_to_string_fn(move |_| {
+                    Ok(serde_json::to_string(state.as_ref().unwrap()).unwrap())
+                });
+                fs.stub_list_files_fn(move |dirpath| {
+                    assert_eq!(dirpath, cwd.join(DEACTIVATE_DIRPATH));
+                    Ok(vec![])
+                });
+                fs.stub_delete_env_dir_fn(move |project_dirpath| {
+                    assert_eq!(project_dirpath, cwd);
+                    Ok(())
+                });
+                fs.stub_append_fn(|path, _| {
+                    assert_eq!(path, crate::history::filepath(Path::new(ENV_DIRPATH)));
+                    Ok(())
                 });
                 fs
             }
 
             #[inline]
-            fn stub_software(name: &'static str, cwd: &'static Path) -> StubSoftware {
-                let mut soft = StubSoftware::default();
-                soft.stub_install_fn(move |project_dirpath, _| {
-                    assert_eq!(project_dirpath, cwd);
-                    Ok(())
-                });
-                soft.stub_name_fn(move || name);
-                soft
+            fn stub_invalid_state_fs(cwd: &'static Path) -> StubFileSystem {
+                let mut fs = stub_fs(cwd, None);
+                fs.stub_file_exists_fn(|_| true);
+                fs.stub_read_to_string_fn(|_| Ok("{}".into()));
+                fs
             }
 
             #[inline]
-            fn stub_var(name: &'static str, value: &'static str) -> StubVar {
-                let mut var = StubVar::default();
-                var.stub_compute_value_fn(|| Ok(value.into()));
-                var.stub_name_fn(move || name);
-                var
+            fn unset_state(cwd: &Path) -> State {
+                State {
+                    unset_vars: vec![VarState {
+                        name: "AWS_PROFILE".into(),
+                        value: "default".into(),
+                    }],
+                    ..state::state(cwd, Path::new("/config"))
+                }
             }
 
             #[inline]
             fn test<W: Write, F: Fn(W, Result<()>)>(
                 out: W,
-                opts: &Options,
+                force: bool,
                 stubs: Stubs,
                 assert_fn: F,
             ) {
                 let runner = Runner {
                     args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
                     cfg_loader: Box::new(stubs.cfg_loader),
-                    convert_soft_fn: stubs.convert_soft_fn,
-                    convert_var_fn: stubs.convert_var_fn,
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(VarDefinition::into_var),
                     create_fs_fn: stubs.create_fs_fn,
+                    create_github_client_fn: Box::new(|_, _| Box::new(StubGithubClient::default())),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
                     env_var_fn: stubs.env_var_fn,
-                    out: Mutex::new(out),
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: stubs.kill_fn,
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(out),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
                 };
-                let res = runner.run(Command::Load, opts.clone());
-                let out = runner.out.into_inner().unwrap();
+                let opts = Options::default();
+                let res = runner.run(Command::Unload { force }, opts);
+                let out = runner.out.into_inner();
                 assert_fn(out, res);
             }
 
             #[inline]
-            fn verify(data: &Data, out: Vec<u8>, res: Result<()>) {
-                let expected_out = format!(
-                    "export {}='{}'\nexport {}='{}'\nexport {}='{}'\nexport {}=\"{}:${{{}}}\"\nexport {}='{}'\n",
+            fn verify(path: &str, var_names: &[&str], out: Vec<u8>, res: Result<()>) {
+                let mut expected_out = format!(
+                    "export {}='{}'\nunset {}\nunset {}\nunset {}\n",
+                    PATH_VAR_NAME,
+                    path,
                     DENV_CWD_VAR_NAME,
-                    data.cwd.display(),
                     DENV_CFG_FILE_VAR_NAME,
-                    data.cfg_path.display(),
                     DENV_PATH_BACKUP_VAR_NAME,
-                    data.path_env_var_value,
-                    PATH_VAR_NAME,
-                    data.env_dirpath.display(),
-                    PATH_VAR_NAME,
-                    data.var_name,
-                    data.var_value,
                 );
+                for var_name in var_names {
+                    expected_out.push_str(&format!("unset {}\n", var_name));
+                }
                 res.unwrap();
                 let out = String::from_utf8(out).unwrap();
                 assert_eq!(out, expected_out);
             }
         }
 
-        mod unload {
+        mod upgrade {
             use super::*;
 
+            const ENV_DIRPATH: &str = "/env";
+
             struct Data {
+                cache_dirpath: &'static Path,
                 cfg: Config,
-                cfg_path: &'static str,
+                cfg_path: &'static Path,
+                changelog: bool,
                 cwd: &'static Path,
+                opts: Options,
             }
 
             impl Default for Data {
                 fn default() -> Self {
                     Self {
+                        cache_dirpath: Path::new("/cache"),
                         cfg: Config {
-                            soft_defs: vec![],
-                            var_defs: vec![VarDefinition {
-                                kind: VarDefinitionKind::Literal("value".into()),
-                                name: "var".into(),
-                            }],
+                            download: DownloadConfig::default(),
+                            latency_budget_ms: None,
+                            message: None,
+                            notify: None,
+                            project_bin: false,
+                            remove_quarantine: false,
+                            soft_defs: vec![
+                                SoftwareDefinition {
+                                    auto: vec![],
+                                    bin_name: None,
+                                    default_args: vec![],
+                                    kind: SoftwareDefinitionKind::Terraform,
+                                    source: SoftwareSource::Denv,
+                                    version: "1.2.3".into(),
+                                },
+                                SoftwareDefinition {
+                                    auto: vec![],
+                                    bin_name: None,
+                                    default_args: vec![],
+                                    kind: SoftwareDefinitionKind::Terraform,
+                                    source: SoftwareSource::System,
+                                    version: "1.2.3".into(),
+                                },
+                                SoftwareDefinition {
+                                    auto: vec![],
+                                    bin_name: None,
+                                    default_args: vec![],
+                                    kind: SoftwareDefinitionKind::ChartTesting,
+                                    source: SoftwareSource::Denv,
+                                    version: "3.7.0".into(),
+                                },
+                            ],
+                            unset_vars: vec![],
+                            var_defs: vec![],
+                            vault_agent: None,
                         },
-                        cfg_path: "/config",
+                        cfg_path: Path::new("/cwd/denv.yml"),
+                        changelog: false,
                         cwd: Path::new("/cwd"),
+                        opts: Options {
+                            github_token: Some("token".into()),
+                            ..Options::default()
+                        },
                     }
                 }
             }
@@ -764,24 +8377,50 @@ mod runner_test {
             struct Stubs {
                 cfg_loader: StubConfigLoader,
                 create_fs_fn: Box<CreateFsFn>,
-                env_var_fn: Box<EnvVarFn>,
+                create_github_client_fn: Box<CreateGithubClientFn>,
             }
 
             impl Stubs {
                 fn new(data: &Data) -> Self {
+                    let cache_dirpath = data.cache_dirpath;
                     let cfg = data.cfg.clone();
                     let cfg_path = data.cfg_path;
                     let cwd = data.cwd;
+                    let expected_token = data.opts.github_token.clone();
                     let mut stubs = Self {
                         cfg_loader: StubConfigLoader::default(),
-                        create_fs_fn: Box::new(|| Box::new(stub_fs(cwd))),
-                        env_var_fn: Box::new(|var_name| match var_name {
-                            DENV_CFG_FILE_VAR_NAME => Ok(cfg_path.into()),
-                            _ => panic!("unexpected {}", var_name),
+                        create_fs_fn: Box::new(move |_| {
+                            let mut fs = StubFileSystem::default();
+                            fs.stub_cwd_fn(|| Ok(cwd.to_path_buf()));
+                            fs.stub_file_exists_fn(move |path| path == cfg_path);
+                            fs.stub_ensure_cache_dir_fn(move |name| {
+                                assert_eq!(name, github::CACHE_DIRNAME);
+                                Ok(cache_dirpath.to_path_buf())
+                            });
+                            stub_history(&mut fs, cwd, cfg_path);
+                            Box::new(fs)
+                        }),
+                        create_github_client_fn: Box::new(move |dirpath, token| {
+                            assert_eq!(dirpath, cache_dirpath);
+                            assert_eq!(token, expected_token);
+                            let mut github = StubGithubClient::default();
+                            github.stub_releases_fn(|repo| match repo {
+                                "hashicorp/terraform" => Ok(vec![
+                                    release("v1.2.3", false, false),
+                                    release("v1.10.0", false, false),
+                                    release("v1.11.0", true, false),
+                                    release("v1.11.0-beta1", false, false),
+                                    release("v2.0.0-rc1", false, true),
+                                    release("v1.9.0", false, false),
+                                ]),
+                                "helm/chart-testing" => Ok(vec![release("v3.7.0", false, false)]),
+                                _ => panic!("unexpected {}", repo),
+                            });
+                            Box::new(github)
                         }),
                     };
                     stubs.cfg_loader.stub_load_fn(move |path| {
-                        assert_eq!(path, Path::new(cfg_path));
+                        assert_eq!(path, cfg_path);
                         Ok(cfg.clone())
                     });
                     stubs
@@ -789,124 +8428,323 @@ mod runner_test {
             }
 
             #[test]
-            fn should_return_env_not_loaded_err() {
-                let data = Data::default();
-                let mut stubs = Stubs::new(&data);
-                stubs.env_var_fn = Box::new(|_| Err(env::VarError::NotPresent));
-                test(vec![], stubs, |_, res| match res.unwrap_err() {
-                    Error::EnvNotLoaded => {}
+            fn should_return_config_not_found_err() {
+                let data = Data {
+                    cfg_path: Path::new("/other/denv.yml"),
+                    ..Data::default()
+                };
+                let stubs = Stubs::new(&data);
+                test(&data, stubs, |_, res| match res.unwrap_err() {
+                    Error::ConfigNotFound => {}
                     err => panic!("{}", err),
                 });
             }
 
             #[test]
-            fn should_return_config_err() {
+            fn should_return_io_err_if_ensure_cache_dir_failed() {
                 let data = Data::default();
                 let mut stubs = Stubs::new(&data);
-                stubs
-                    .cfg_loader
-                    .stub_load_fn(|_| Err(cfg::Error::Version(None)));
-                test(vec![], stubs, |_, res| match res.unwrap_err() {
-                    Error::Config(_) => {}
+                let cfg_path = data.cfg_path;
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = StubFileSystem::default();
+                    fs.stub_cwd_fn(|| Ok(PathBuf::from("/cwd")));
+                    fs.stub_file_exists_fn(move |path| path == cfg_path);
+                    fs.stub_ensure_cache_dir_fn(|_| {
+                        Err(io::Error::from(io::ErrorKind::PermissionDenied))
+                    });
+                    stub_history(&mut fs, Path::new("/cwd"), cfg_path);
+                    Box::new(fs)
+                });
+                test(&data, stubs, |_, res| match res.unwrap_err() {
+                    Error::Io(_) => {}
                     err => panic!("{}", err),
                 });
             }
 
             #[test]
-            fn should_return_io_err_if_cwd_failed() {
+            fn should_return_github_err() {
                 let data = Data::default();
-                let cwd = data.cwd;
                 let mut stubs = Stubs::new(&data);
-                stubs.create_fs_fn = Box::new(|| {
-                    let mut fs = stub_fs(cwd);
-                    fs.stub_cwd_fn(|| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
-                    Box::new(fs)
+                stubs.create_github_client_fn = Box::new(|_, _| {
+                    let mut github = StubGithubClient::default();
+                    github.stub_releases_fn(|_| Err(github::Error::RateLimited));
+                    Box::new(github)
                 });
-                test(vec![], stubs, |_, res| match res.unwrap_err() {
-                    Error::Io(_) => {}
+                test(&data, stubs, |_, res| match res.unwrap_err() {
+                    Error::Github(_) => {}
                     err => panic!("{}", err),
                 });
             }
 
             #[test]
-            fn should_return_io_err_if_write_on_output_failed() {
+            fn should_print_upgrades() {
                 let data = Data::default();
                 let stubs = Stubs::new(&data);
-                test(WriteFailer, stubs, |_, res| match res.unwrap_err() {
-                    Error::Io(_) => {}
-                    err => panic!("{}", err),
+                test(&data, stubs, |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    assert_eq!(out, "terraform: 1.2.3 -> 1.10.0\n");
                 });
             }
 
             #[test]
-            fn should_return_io_err_if_delete_env_dir_failed() {
+            fn should_print_prerelease_upgrades_if_current_version_is_prerelease() {
+                let mut data = Data::default();
+                data.cfg.soft_defs[0].version = "1.11.0-alpha1".into();
+                let stubs = Stubs::new(&data);
+                test(&data, stubs, |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    assert_eq!(out, "terraform: 1.11.0-alpha1 -> 1.11.0-beta1\n");
+                });
+            }
+
+            #[test]
+            fn should_append_history_entry() {
                 let data = Data::default();
+                let cache_dirpath = data.cache_dirpath;
+                let cfg_path = data.cfg_path;
                 let cwd = data.cwd;
                 let mut stubs = Stubs::new(&data);
-                stubs.create_fs_fn = Box::new(|| {
-                    let mut fs = stub_fs(cwd);
-                    fs.stub_delete_env_dir_fn(|_| {
-                        Err(io::Error::from(io::ErrorKind::PermissionDenied))
+                stubs.create_fs_fn = Box::new(move |_| {
+                    let mut fs = StubFileSystem::default();
+                    fs.stub_cwd_fn(|| Ok(cwd.to_path_buf()));
+                    fs.stub_file_exists_fn(move |path| path == cfg_path);
+                    fs.stub_ensure_cache_dir_fn(move |_| Ok(cache_dirpath.to_path_buf()));
+                    stub_history(&mut fs, cwd, cfg_path);
+                    fs.stub_append_fn(|_, content| {
+                        let entry: HistoryEntry = serde_json::from_str(content).unwrap();
+                        let expected_entry = HistoryEntry {
+                            action: Action::Upgrade,
+                            at: 0,
+                            cfg_hash: Some(hash::sha256(b"")),
+                            duration_ms: 0,
+                            error: None,
+                            softwares: BTreeMap::from([
+                                ("chart-testing".into(), "3.7.0".into()),
+                                ("terraform".into(), "1.2.3".into()),
+                            ]),
+                        };
+                        assert_eq!(entry, expected_entry);
+                        Ok(())
                     });
                     Box::new(fs)
                 });
-                test(vec![], stubs, |_, res| match res.unwrap_err() {
-                    Error::Io(_) => {}
-                    err => panic!("{}", err),
+                test(&data, stubs, |_, res| {
+                    res.unwrap();
                 });
             }
 
             #[test]
-            fn should_return_install_ok() {
-                let data = Data::default();
+            fn should_print_upgrades_and_changelog() {
+                let data = Data {
+                    changelog: true,
+                    ..Data::default()
+                };
                 let stubs = Stubs::new(&data);
-                test(vec![], stubs, |out, res| {
-                    verify(&data, out, res);
+                test(&data, stubs, |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    let expected_out = "terraform: 1.2.3 -> 1.10.0\n\n## 1.10.0\n\nChangelog of v1.10.0\n\n## 1.9.0\n\nChangelog of v1.9.0\n\n";
+                    assert_eq!(out, expected_out);
                 });
             }
 
             #[inline]
-            fn stub_fs(cwd: &'static Path) -> StubFileSystem {
-                let mut fs = StubFileSystem::default();
-                fs.stub_cwd_fn(|| Ok(cwd.to_path_buf()));
-                fs.stub_delete_env_dir_fn(move |project_dirpath| {
+            fn release(tag_name: &str, draft: bool, prerelease: bool) -> Release {
+                Release {
+                    body: Some(format!("Changelog of {}\n", tag_name)),
+                    draft,
+                    prerelease,
+                    tag_name: tag_name.into(),
+                }
+            }
+
+            #[inline]
+            fn stub_history(fs: &mut StubFileSystem, cwd: &'static Path, cfg_path: &'static Path) {
+                fs.stub_ensure_env_dir_fn(move |project_dirpath| {
                     assert_eq!(project_dirpath, cwd);
+                    Ok(PathBuf::from(ENV_DIRPATH))
+                });
+                fs.stub_read_to_string_fn(move |path| {
+                    assert_eq!(path, cfg_path);
+                    Ok(String::new())
+                });
+                fs.stub_append_fn(|path, _| {
+                    assert_eq!(path, crate::history::filepath(Path::new(ENV_DIRPATH)));
                     Ok(())
                 });
-                fs
             }
 
             #[inline]
-            fn test<W: Write, F: Fn(W, Result<()>)>(out: W, stubs: Stubs, assert_fn: F) {
+            fn test<F: Fn(Vec<u8>, Result<()>)>(data: &Data, stubs: Stubs, assert_fn: F) {
                 let runner = Runner {
                     args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
                     cfg_loader: Box::new(stubs.cfg_loader),
                     convert_soft_fn: Box::new(SoftwareDefinition::into_software),
                     convert_var_fn: Box::new(VarDefinition::into_var),
                     create_fs_fn: stubs.create_fs_fn,
-                    env_var_fn: stubs.env_var_fn,
-                    out: Mutex::new(out),
+                    create_github_client_fn: stubs.create_github_client_fn,
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn: Box::new(|var_name| panic!("unexpected {}", var_name)),
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
                 };
-                let opts = Options::default();
-                let res = runner.run(Command::Unload, opts);
-                let out = runner.out.into_inner().unwrap();
+                let res = runner.run(
+                    Command::Upgrade {
+                        changelog: data.changelog,
+                    },
+                    data.opts.clone(),
+                );
+                let out = runner.out.into_inner();
                 assert_fn(out, res);
             }
+        }
+
+        mod versions {
+            use super::*;
+
+            const CFG_PATH: &str = "/cwd/denv.yml";
+
+            #[test]
+            fn should_return_software_not_found_err() {
+                test("kubectl", TableOptions::default(), |_, res| {
+                    match res.unwrap_err() {
+                        Error::SoftwareNotFound(name) => assert_eq!(name, "kubectl"),
+                        err => panic!("{}", err),
+                    }
+                });
+            }
+
+            #[test]
+            fn should_print_json() {
+                let table_opts = TableOptions {
+                    json: true,
+                    no_header: false,
+                };
+                test("terraform", table_opts, |out, res| {
+                    res.unwrap();
+                    let versions: serde_json::Value = serde_json::from_slice(&out).unwrap();
+                    let expected = serde_json::json!([
+                        {"status": "", "version": "1.10.0"},
+                        {"status": "", "version": "1.9.0"},
+                        {"status": "current", "version": "1.2.3"},
+                    ]);
+                    assert_eq!(versions, expected);
+                });
+            }
+
+            #[test]
+            fn should_print_versions() {
+                test("terraform", TableOptions::default(), |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    assert_eq!(out, "VERSION  STATUS\n1.10.0\n1.9.0\n1.2.3    current\n");
+                });
+            }
+
+            #[test]
+            fn should_print_versions_without_header() {
+                let table_opts = TableOptions {
+                    json: false,
+                    no_header: true,
+                };
+                test("terraform", table_opts, |out, res| {
+                    res.unwrap();
+                    let out = String::from_utf8(out).unwrap();
+                    assert_eq!(out, "1.10.0\n1.9.0\n1.2.3   current\n");
+                });
+            }
 
             #[inline]
-            fn verify(data: &Data, out: Vec<u8>, res: Result<()>) {
-                let expected_out = format!(
-                    "export {}='{}'\nunset {}\nunset {}\nunset {}\nunset {}\n",
-                    PATH_VAR_NAME,
-                    DENV_PATH_BACKUP_VAR_NAME,
-                    DENV_CWD_VAR_NAME,
-                    DENV_CFG_FILE_VAR_NAME,
-                    DENV_PATH_BACKUP_VAR_NAME,
-                    data.cfg.var_defs[0].name,
-                );
-                res.unwrap();
-                let out = String::from_utf8(out).unwrap();
-                assert_eq!(out, expected_out);
+            fn test<F: Fn(Vec<u8>, Result<()>)>(
+                software: &str,
+                table_opts: TableOptions,
+                assert_fn: F,
+            ) {
+                let mut cfg_loader = StubConfigLoader::default();
+                cfg_loader.stub_load_fn(|_| {
+                    Ok(Config {
+                        download: DownloadConfig::default(),
+                        latency_budget_ms: None,
+                        message: None,
+                        notify: None,
+                        project_bin: false,
+                        remove_quarantine: false,
+                        soft_defs: vec![SoftwareDefinition {
+                            auto: vec![],
+                            bin_name: None,
+                            default_args: vec![],
+                            kind: SoftwareDefinitionKind::Terraform,
+                            source: SoftwareSource::Denv,
+                            version: "1.2.3".into(),
+                        }],
+                        unset_vars: vec![],
+                        var_defs: vec![],
+                        vault_agent: None,
+                    })
+                });
+                let runner = Runner {
+                    args_fn: Box::new(|| env::args().collect()),
+                    cancel_token: CancelToken::default(),
+                    cfg_loader: Box::new(cfg_loader),
+                    convert_soft_fn: Box::new(SoftwareDefinition::into_software),
+                    convert_var_fn: Box::new(VarDefinition::into_var),
+                    create_fs_fn: Box::new(|_| {
+                        let mut fs = StubFileSystem::default();
+                        fs.stub_cwd_fn(|| Ok(PathBuf::from("/cwd")));
+                        fs.stub_file_exists_fn(|path| path == Path::new(CFG_PATH));
+                        fs.stub_ensure_cache_dir_fn(|_| Ok(PathBuf::from("/cache")));
+                        Box::new(fs)
+                    }),
+                    create_github_client_fn: Box::new(|_, _| {
+                        let mut github = StubGithubClient::default();
+                        github.stub_releases_fn(|repo| {
+                            assert_eq!(repo, "hashicorp/terraform");
+                            let release = |tag_name: &str, prerelease| Release {
+                                body: None,
+                                draft: false,
+                                prerelease,
+                                tag_name: tag_name.into(),
+                            };
+                            Ok(vec![
+                                release("v1.2.3", false),
+                                release("v1.10.0", false),
+                                release("v2.0.0-rc1", true),
+                                release("v1.9.0", false),
+                            ])
+                        });
+                        Box::new(github)
+                    }),
+                    elapsed_fn: Box::new(|| Duration::ZERO),
+                    entrypoint_fn: Box::new(|cmd, _, _| panic!("unexpected {:?}", cmd)),
+                    env_var_fn: Box::new(|var_name| panic!("unexpected {}", var_name)),
+                    exec_fn: Box::new(|cmd, _, _, _| panic!("unexpected {:?}", cmd)),
+                    is_root_fn: Box::new(|| false),
+                    is_terminal_fn: Box::new(|| false),
+                    kill_fn: Box::new(|pid, _| panic!("unexpected kill of {}", pid)),
+                    notify_fn: Box::new(|_, msg| panic!("unexpected notification: {}", msg)),
+                    now_fn: Box::new(|| 0),
+                    out: ShellOutput::new(vec![]),
+                    print_msg_fn: Box::new(|msg| panic!("unexpected message: {}", msg)),
+                    spawn_fn: Box::new(|program, _, _| panic!("unexpected {}", program)),
+                };
+                let cmd = Command::Versions {
+                    software: software.into(),
+                    table_opts,
+                };
+                let res = runner.run(cmd, Options::default());
+                let out = runner.out.into_inner();
+                assert_fn(out, res);
             }
         }
     }
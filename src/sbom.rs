@@ -0,0 +1,220 @@
+// IMPORTS
+
+use crate::soft::receipt::InstalledSoftware;
+use serde_json::{json, Value};
+
+// CONSTS
+
+const CYCLONEDX_SPEC_VERSION: &str = "1.4";
+const DAY_SECS: u64 = 86400;
+const DENV_VERSION: &str = env!("CARGO_PKG_VERSION");
+const NO_ASSERTION: &str = "NOASSERTION";
+const SPDX_VERSION: &str = "SPDX-2.3";
+
+// FUNCTIONS
+
+pub fn cyclonedx(softs: &[InstalledSoftware]) -> Value {
+    let components: Vec<Value> = softs
+        .iter()
+        .map(|soft| {
+            let mut component = json!({
+                "type": "application",
+                "name": soft.name,
+                "version": soft.version,
+            });
+            if let Some(receipt) = &soft.receipt {
+                component["hashes"] = json!([{ "alg": "SHA-256", "content": receipt.sha256 }]);
+                component["externalReferences"] =
+                    json!([{ "type": "distribution", "url": receipt.url }]);
+            }
+            component
+        })
+        .collect();
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": CYCLONEDX_SPEC_VERSION,
+        "version": 1,
+        "metadata": {
+            "tools": [{ "name": "denv", "version": DENV_VERSION }],
+        },
+        "components": components,
+    })
+}
+
+pub fn spdx(softs: &[InstalledSoftware], project_name: &str, id: &str, now: u64) -> Value {
+    let packages: Vec<Value> = softs
+        .iter()
+        .map(|soft| {
+            let mut package = json!({
+                "SPDXID": format!("SPDXRef-Package-{}", soft.name),
+                "name": soft.name,
+                "versionInfo": soft.version,
+                "downloadLocation": soft
+                    .receipt
+                    .as_ref()
+                    .map(|receipt| receipt.url.as_str())
+                    .unwrap_or(NO_ASSERTION),
+                "filesAnalyzed": false,
+            });
+            if let Some(receipt) = &soft.receipt {
+                package["checksums"] =
+                    json!([{ "algorithm": "SHA256", "checksumValue": receipt.sha256 }]);
+            }
+            package
+        })
+        .collect();
+    json!({
+        "spdxVersion": SPDX_VERSION,
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": format!("denv-{}", project_name),
+        "documentNamespace": format!("https://spdx.org/spdxdocs/denv-{}-{}", id, now),
+        "creationInfo": {
+            "created": rfc3339(now),
+            "creators": [format!("Tool: denv-{}", DENV_VERSION)],
+        },
+        "packages": packages,
+    })
+}
+
+// See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+#[inline]
+fn rfc3339(timestamp: u64) -> String {
+    let days = (timestamp / DAY_SECS) as i64 + 719468;
+    let secs = timestamp % DAY_SECS;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_idx = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_idx + 2) / 5 + 1;
+    let month = if month_idx < 10 {
+        month_idx + 3
+    } else {
+        month_idx - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+// TESTS
+
+#[cfg(test)]
+mod cyclonedx_test {
+    use super::*;
+    use crate::soft::receipt;
+
+    #[test]
+    fn should_return_bom() {
+        let softs = [
+            InstalledSoftware {
+                name: "terraform".into(),
+                receipt: Some(receipt::receipt()),
+                version: "1.2.3".into(),
+            },
+            InstalledSoftware {
+                name: "kubectl".into(),
+                receipt: None,
+                version: "1.25.0".into(),
+            },
+        ];
+        let expected = json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": CYCLONEDX_SPEC_VERSION,
+            "version": 1,
+            "metadata": {
+                "tools": [{ "name": "denv", "version": DENV_VERSION }],
+            },
+            "components": [
+                {
+                    "type": "application",
+                    "name": "terraform",
+                    "version": "1.2.3",
+                    "hashes": [{ "alg": "SHA-256", "content": "sha256" }],
+                    "externalReferences": [{ "type": "distribution", "url": "url" }],
+                },
+                {
+                    "type": "application",
+                    "name": "kubectl",
+                    "version": "1.25.0",
+                },
+            ],
+        });
+        assert_eq!(cyclonedx(&softs), expected);
+    }
+}
+
+#[cfg(test)]
+mod spdx_test {
+    use super::*;
+    use crate::soft::receipt;
+
+    #[test]
+    fn should_return_document() {
+        let softs = [
+            InstalledSoftware {
+                name: "terraform".into(),
+                receipt: Some(receipt::receipt()),
+                version: "1.2.3".into(),
+            },
+            InstalledSoftware {
+                name: "kubectl".into(),
+                receipt: None,
+                version: "1.25.0".into(),
+            },
+        ];
+        let expected = json!({
+            "spdxVersion": SPDX_VERSION,
+            "dataLicense": "CC0-1.0",
+            "SPDXID": "SPDXRef-DOCUMENT",
+            "name": "denv-project",
+            "documentNamespace": "https://spdx.org/spdxdocs/denv-id-1666000000",
+            "creationInfo": {
+                "created": "2022-10-17T09:46:40Z",
+                "creators": [format!("Tool: denv-{}", DENV_VERSION)],
+            },
+            "packages": [
+                {
+                    "SPDXID": "SPDXRef-Package-terraform",
+                    "name": "terraform",
+                    "versionInfo": "1.2.3",
+                    "downloadLocation": "url",
+                    "filesAnalyzed": false,
+                    "checksums": [{ "algorithm": "SHA256", "checksumValue": "sha256" }],
+                },
+                {
+                    "SPDXID": "SPDXRef-Package-kubectl",
+                    "name": "kubectl",
+                    "versionInfo": "1.25.0",
+                    "downloadLocation": NO_ASSERTION,
+                    "filesAnalyzed": false,
+                },
+            ],
+        });
+        assert_eq!(spdx(&softs, "project", "id", 1666000000), expected);
+    }
+}
+
+#[cfg(test)]
+mod rfc3339_test {
+    use super::*;
+
+    #[test]
+    fn should_return_epoch() {
+        assert_eq!(rfc3339(0), "1970-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn should_return_leap_day() {
+        assert_eq!(rfc3339(951782400), "2000-02-29T00:00:00Z");
+    }
+}
@@ -0,0 +1,183 @@
+// IMPORTS
+
+use std::{
+    io::{Result, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+// STRUCTS
+
+// Only channel allowed to write on stdout: everything written here is evaluated by the shell, so
+// anything else must be logged.
+pub struct ShellOutput<W: Write> {
+    out: Mutex<W>,
+}
+
+impl<W: Write> ShellOutput<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out: Mutex::new(out),
+        }
+    }
+
+    // The Bourne shell does not support assignments in export: the variable is assigned first.
+    pub fn assign_export(&self, name: &str, value: &str) -> Result<()> {
+        self.writeln(&format!("{}={}; export {}", name, quote(value), name))
+    }
+
+    pub fn assign_export_expr(&self, name: &str, expr: &str) -> Result<()> {
+        self.writeln(&format!("{}={}; export {}", name, expr, name))
+    }
+
+    pub fn export(&self, name: &str, value: &str) -> Result<()> {
+        self.writeln(&format!("export {}={}", name, quote(value)))
+    }
+
+    pub fn export_expr(&self, name: &str, expr: &str) -> Result<()> {
+        self.writeln(&format!("export {}={}", name, expr))
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out.into_inner().unwrap()
+    }
+
+    pub fn source(&self, path: &Path) -> Result<()> {
+        self.writeln(&format!(". {}", quote(&path.to_string_lossy())))
+    }
+
+    pub fn unset(&self, name: &str) -> Result<()> {
+        self.writeln(&format!("unset {}", name))
+    }
+
+    pub fn write(&self, content: &str) -> Result<()> {
+        let mut out = self.out.lock().unwrap();
+        write!(out, "{}", content)
+    }
+
+    #[inline]
+    fn writeln(&self, statement: &str) -> Result<()> {
+        let mut out = self.out.lock().unwrap();
+        writeln!(out, "{}", statement)
+    }
+}
+
+// FUNCTIONS
+
+#[inline]
+pub fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+// TESTS
+
+#[cfg(test)]
+mod shell_output_test {
+    use super::*;
+    use crate::test::WriteFailer;
+    use std::process::Command;
+
+    mod assign_export {
+        use super::*;
+
+        #[test]
+        fn should_write_statement() {
+            test(
+                |out| out.assign_export("VAR", "it's"),
+                "VAR='it'\\''s'; export VAR\n",
+            );
+        }
+    }
+
+    mod assign_export_expr {
+        use super::*;
+
+        #[test]
+        fn should_write_statement() {
+            test(
+                |out| out.assign_export_expr("PATH", "'/env':\"${PATH}\""),
+                "PATH='/env':\"${PATH}\"; export PATH\n",
+            );
+        }
+    }
+
+    mod export {
+        use super::*;
+
+        #[test]
+        fn should_return_err() {
+            let out = ShellOutput::new(WriteFailer);
+            out.export("VAR", "value").unwrap_err();
+        }
+
+        #[test]
+        fn should_write_statement() {
+            test(|out| out.export("VAR", "it's"), "export VAR='it'\\''s'\n");
+        }
+
+        #[test]
+        fn should_be_evaluated_by_shell() {
+            let value = "it's a \"$value\" with `backquotes`";
+            let out = ShellOutput::new(vec![]);
+            out.export("VAR", value).unwrap();
+            let statements = String::from_utf8(out.into_inner()).unwrap();
+            let output = Command::new("sh")
+                .arg("-c")
+                .arg(format!("{}printf %s \"$VAR\"", statements))
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+            assert_eq!(String::from_utf8(output.stdout).unwrap(), value);
+        }
+    }
+
+    mod export_expr {
+        use super::*;
+
+        #[test]
+        fn should_write_statement() {
+            test(
+                |out| out.export_expr("PATH", "'/env':\"${PATH}\""),
+                "export PATH='/env':\"${PATH}\"\n",
+            );
+        }
+    }
+
+    mod source {
+        use super::*;
+
+        #[test]
+        fn should_write_statement() {
+            test(
+                |out| out.source(Path::new("/cwd/it's.sh")),
+                ". '/cwd/it'\\''s.sh'\n",
+            );
+        }
+    }
+
+    mod unset {
+        use super::*;
+
+        #[test]
+        fn should_write_statement() {
+            test(|out| out.unset("VAR"), "unset VAR\n");
+        }
+    }
+
+    mod write {
+        use super::*;
+
+        #[test]
+        fn should_write_content() {
+            test(|out| out.write("echo\n"), "echo\n");
+        }
+    }
+
+    #[inline]
+    fn test<F: Fn(&ShellOutput<Vec<u8>>) -> Result<()>>(write_fn: F, expected: &str) {
+        let out = ShellOutput::new(vec![]);
+        write_fn(&out).unwrap();
+        let out = String::from_utf8(out.into_inner()).unwrap();
+        assert_eq!(out, expected);
+    }
+}
@@ -0,0 +1,221 @@
+// IMPORTS
+
+use crate::hash;
+use log::{debug, warn};
+use std::{
+    fs::{self, File, OpenOptions, Permissions},
+    io::{self, Write},
+    os::unix::fs::{OpenOptionsExt, PermissionsExt},
+    path::{Path, PathBuf},
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// CONSTS
+
+const ARTIFACTS_DIRNAME: &str = "artifacts";
+const DIR_MODE: u32 = 0o2775;
+const FILE_MODE: u32 = 0o664;
+const LOCK_EXTENSION: &str = "lock";
+const LOCK_TIMEOUT: u64 = 600;
+const SHA256_EXTENSION: &str = "sha256";
+
+// STRUCTS
+
+// The cache is shared by the users of a group: directories are group-writable with the setgid bit
+// so entries belong to the group whatever the user who created them, and files are written under
+// a lock then renamed so readers never see a partial artifact. The content is never trusted: the
+// caller verifies it as a downloaded artifact.
+pub struct SharedCache {
+    dirpath: PathBuf,
+}
+
+impl SharedCache {
+    pub fn new(dirpath: PathBuf) -> Self {
+        Self { dirpath }
+    }
+
+    pub fn open(&self, url: &str) -> Option<(File, String)> {
+        let filepath = self.artifact_filepath(url);
+        let file = File::open(&filepath).ok()?;
+        let sha256 = fs::read_to_string(filepath.with_extension(SHA256_EXTENSION)).ok()?;
+        Some((file, sha256.trim().into()))
+    }
+
+    pub fn store(&self, url: &str, src: &Path, sha256: &str) -> io::Result<()> {
+        Self::ensure_dir(&self.dirpath)?;
+        Self::ensure_dir(&self.dirpath.join(ARTIFACTS_DIRNAME))?;
+        let filepath = self.artifact_filepath(url);
+        let lock_filepath = filepath.with_extension(LOCK_EXTENSION);
+        if !Self::lock(&lock_filepath)? {
+            debug!("{} is being cached by another process", url);
+            return Ok(());
+        }
+        debug!("Caching {} as {}", url, filepath.display());
+        let res = Self::write_checksum(&filepath, sha256).and_then(|_| Self::copy(src, &filepath));
+        if let Err(err) = fs::remove_file(&lock_filepath) {
+            warn!("Unable to delete {}: {}", lock_filepath.display(), err);
+        }
+        res
+    }
+
+    #[inline]
+    fn artifact_filepath(&self, url: &str) -> PathBuf {
+        self.dirpath
+            .join(ARTIFACTS_DIRNAME)
+            .join(hash::sha256(url.as_bytes()))
+    }
+
+    #[inline]
+    fn copy(src: &Path, dest: &Path) -> io::Result<()> {
+        let tmp_filepath = Self::tmp_filepath(dest);
+        fs::copy(src, &tmp_filepath)?;
+        fs::set_permissions(&tmp_filepath, Permissions::from_mode(FILE_MODE))?;
+        fs::rename(&tmp_filepath, dest)
+    }
+
+    #[inline]
+    fn ensure_dir(dirpath: &Path) -> io::Result<()> {
+        match fs::create_dir(dirpath) {
+            Ok(()) => fs::set_permissions(dirpath, Permissions::from_mode(DIR_MODE)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    // The umask is ignored so locks left by a user can be removed by the others once stale.
+    #[inline]
+    fn lock(lock_filepath: &Path) -> io::Result<bool> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let res = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(FILE_MODE)
+            .open(lock_filepath);
+        match res {
+            Ok(mut file) => {
+                fs::set_permissions(lock_filepath, Permissions::from_mode(FILE_MODE))?;
+                file.write_all(now.to_string().as_bytes())?;
+                Ok(true)
+            }
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                let locked_at = fs::read_to_string(lock_filepath)
+                    .ok()
+                    .and_then(|locked_at| locked_at.trim().parse().ok())
+                    .unwrap_or_default();
+                if now.saturating_sub(locked_at) < LOCK_TIMEOUT {
+                    Ok(false)
+                } else {
+                    warn!("Removing stale lock {}", lock_filepath.display());
+                    fs::remove_file(lock_filepath)?;
+                    Self::lock(lock_filepath)
+                }
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    #[inline]
+    fn tmp_filepath(filepath: &Path) -> PathBuf {
+        let mut tmp_filepath = filepath.as_os_str().to_owned();
+        tmp_filepath.push(format!(".{}.tmp", process::id()));
+        PathBuf::from(tmp_filepath)
+    }
+
+    #[inline]
+    fn write_checksum(filepath: &Path, sha256: &str) -> io::Result<()> {
+        let dest = filepath.with_extension(SHA256_EXTENSION);
+        let tmp_filepath = Self::tmp_filepath(&dest);
+        File::create(&tmp_filepath)?.write_all(sha256.as_bytes())?;
+        fs::set_permissions(&tmp_filepath, Permissions::from_mode(FILE_MODE))?;
+        fs::rename(&tmp_filepath, dest)
+    }
+}
+
+// TESTS
+
+#[cfg(test)]
+mod shared_cache_test {
+    use super::*;
+    use std::io::Read;
+
+    const URL: &str = "https://host/soft.zip";
+
+    mod open {
+        use super::*;
+
+        #[test]
+        fn should_return_none_if_artifact_is_not_cached() {
+            let dirpath = tempfile::tempdir().unwrap();
+            let cache = SharedCache::new(dirpath.path().to_path_buf());
+            assert!(cache.open(URL).is_none());
+        }
+
+        #[test]
+        fn should_return_none_if_checksum_is_missing() {
+            let dirpath = tempfile::tempdir().unwrap();
+            let cache = SharedCache::new(dirpath.path().to_path_buf());
+            fs::create_dir(dirpath.path().join(ARTIFACTS_DIRNAME)).unwrap();
+            fs::write(cache.artifact_filepath(URL), "artifact").unwrap();
+            assert!(cache.open(URL).is_none());
+        }
+    }
+
+    mod store {
+        use super::*;
+
+        #[test]
+        fn should_store_artifact_with_group_permissions() {
+            let dirpath = tempfile::tempdir().unwrap();
+            let cache_dirpath = dirpath.path().join("cache");
+            let src = dirpath.path().join("artifact");
+            fs::write(&src, "artifact").unwrap();
+            let cache = SharedCache::new(cache_dirpath.clone());
+            cache.store(URL, &src, "sha256").unwrap();
+            let (mut file, sha256) = cache.open(URL).unwrap();
+            let mut content = String::new();
+            file.read_to_string(&mut content).unwrap();
+            assert_eq!(content, "artifact");
+            assert_eq!(sha256, "sha256");
+            let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o7777;
+            assert_eq!(mode(&cache_dirpath), DIR_MODE);
+            assert_eq!(mode(&cache_dirpath.join(ARTIFACTS_DIRNAME)), DIR_MODE);
+            assert_eq!(mode(&cache.artifact_filepath(URL)), FILE_MODE);
+            assert!(!cache
+                .artifact_filepath(URL)
+                .with_extension(LOCK_EXTENSION)
+                .exists());
+        }
+
+        #[test]
+        fn should_skip_if_artifact_is_locked() {
+            let dirpath = tempfile::tempdir().unwrap();
+            let src = dirpath.path().join("artifact");
+            fs::write(&src, "artifact").unwrap();
+            let cache = SharedCache::new(dirpath.path().join("cache"));
+            fs::create_dir_all(dirpath.path().join("cache").join(ARTIFACTS_DIRNAME)).unwrap();
+            let lock_filepath = cache.artifact_filepath(URL).with_extension(LOCK_EXTENSION);
+            assert!(SharedCache::lock(&lock_filepath).unwrap());
+            cache.store(URL, &src, "sha256").unwrap();
+            assert!(cache.open(URL).is_none());
+            assert!(lock_filepath.exists());
+        }
+
+        #[test]
+        fn should_remove_stale_lock() {
+            let dirpath = tempfile::tempdir().unwrap();
+            let src = dirpath.path().join("artifact");
+            fs::write(&src, "artifact").unwrap();
+            let cache = SharedCache::new(dirpath.path().join("cache"));
+            fs::create_dir_all(dirpath.path().join("cache").join(ARTIFACTS_DIRNAME)).unwrap();
+            let lock_filepath = cache.artifact_filepath(URL).with_extension(LOCK_EXTENSION);
+            fs::write(&lock_filepath, "0").unwrap();
+            cache.store(URL, &src, "sha256").unwrap();
+            assert!(cache.open(URL).is_some());
+            assert!(!lock_filepath.exists());
+        }
+    }
+}
@@ -0,0 +1,479 @@
+// IMPORTS
+
+#[cfg(test)]
+use super::Kind;
+use super::{
+    installer::{ArchiveArtifactInstaller, Artifact, DefaultArchiveArtifactInstaller, Symlink},
+    upstream_arch, ArchTable, Error, Result, Software,
+};
+use crate::{
+    fs::{FileSystem, SymlinkKind},
+    net::DownloadConfig,
+    release::{GithubReleases, ReleaseIndex},
+};
+use std::{env, path::Path};
+
+// CONSTS
+
+pub const DIVE_ARCHS: ArchTable = &[("x86_64", "amd64"), ("aarch64", "arm64")];
+pub const DIVE_BIN_NAME: &str = "dive";
+pub const DIVE_GITHUB_REPO: &str = "wagoodman/dive";
+pub const DIVE_SOFT_NAME: &str = "dive";
+pub const DIVE_VERSION_ARGS: &[&str] = &["--version"];
+pub const LAZYDOCKER_ARCHS: ArchTable =
+    &[("x86_64", "x86_64"), ("arm", "armv7"), ("aarch64", "arm64")];
+pub const LAZYDOCKER_BIN_NAME: &str = "lazydocker";
+pub const LAZYDOCKER_GITHUB_REPO: &str = "jesseduffield/lazydocker";
+pub const LAZYDOCKER_SOFT_NAME: &str = "lazydocker";
+pub const LAZYDOCKER_VERSION_ARGS: &[&str] = &["--version"];
+
+const DIVE_ASSET: &str = "dive_{version}_{os}_{arch}.tar.gz";
+const DIVE_CHECKSUMS_ASSET: &str = "dive_{version}_checksums.txt";
+const LAZYDOCKER_ASSET: &str = "lazydocker_{version}_{os}_{arch}.tar.gz";
+const LAZYDOCKER_CHECKSUMS_ASSET: &str = "checksums.txt";
+
+// STRUCTS
+
+pub struct Dive {
+    bin_name: String,
+    installer: Box<dyn ArchiveArtifactInstaller>,
+    version: String,
+}
+
+impl Dive {
+    pub fn new(version: String, bin_name: Option<String>, download_cfg: &DownloadConfig) -> Self {
+        Self {
+            bin_name: bin_name.unwrap_or_else(|| DIVE_BIN_NAME.into()),
+            installer: Box::new(DefaultArchiveArtifactInstaller::new(download_cfg)),
+            version,
+        }
+    }
+
+    #[inline]
+    fn index() -> GithubReleases {
+        GithubReleases::new(DIVE_GITHUB_REPO, DIVE_ASSET, Some(DIVE_CHECKSUMS_ASSET))
+    }
+
+    #[inline]
+    fn os() -> Result<&'static str> {
+        match env::consts::OS {
+            "macos" => Ok("darwin"),
+            "linux" => Ok("linux"),
+            _ => Err(Error::UnsupportedSystem),
+        }
+    }
+}
+
+impl Software for Dive {
+    fn bin_names(&self) -> Vec<String> {
+        vec![self.bin_name.clone()]
+    }
+
+    fn env_vars(&self, _install_dirpath: &Path, _env_dirpath: &Path) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    fn install(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
+        let os = Self::os()?;
+        let arch = upstream_arch(DIVE_ARCHS)?;
+        let env_dirpath = fs.ensure_env_dir(project_dirpath).map_err(Error::Io)?;
+        let index = Self::index();
+        let artifact = Artifact {
+            bin_filepaths: vec![Path::new(DIVE_BIN_NAME)],
+            checksums_url: index.checksums_url(&self.version),
+            name: DIVE_SOFT_NAME,
+            strip_components: 0,
+            symlinks: vec![Symlink {
+                dest: env_dirpath.join(&self.bin_name),
+                kind: SymlinkKind::File,
+                required: true,
+                src: Path::new(DIVE_BIN_NAME),
+            }],
+            url: index.artifact_url(&self.version, os, arch),
+            version: &self.version,
+        };
+        self.installer.install_archive(&artifact, fs)
+    }
+
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
+        Kind::Dive(self)
+    }
+
+    fn name(&self) -> &str {
+        DIVE_SOFT_NAME
+    }
+
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>> {
+        Some(Box::new(Self::index()))
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+pub struct Lazydocker {
+    bin_name: String,
+    installer: Box<dyn ArchiveArtifactInstaller>,
+    version: String,
+}
+
+impl Lazydocker {
+    pub fn new(version: String, bin_name: Option<String>, download_cfg: &DownloadConfig) -> Self {
+        Self {
+            bin_name: bin_name.unwrap_or_else(|| LAZYDOCKER_BIN_NAME.into()),
+            installer: Box::new(DefaultArchiveArtifactInstaller::new(download_cfg)),
+            version,
+        }
+    }
+
+    #[inline]
+    fn index() -> GithubReleases {
+        GithubReleases::new(
+            LAZYDOCKER_GITHUB_REPO,
+            LAZYDOCKER_ASSET,
+            Some(LAZYDOCKER_CHECKSUMS_ASSET),
+        )
+    }
+
+    #[inline]
+    fn os() -> Result<&'static str> {
+        match env::consts::OS {
+            "macos" => Ok("Darwin"),
+            "linux" => Ok("Linux"),
+            _ => Err(Error::UnsupportedSystem),
+        }
+    }
+}
+
+impl Software for Lazydocker {
+    fn bin_names(&self) -> Vec<String> {
+        vec![self.bin_name.clone()]
+    }
+
+    fn env_vars(&self, _install_dirpath: &Path, _env_dirpath: &Path) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    fn install(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
+        let os = Self::os()?;
+        let arch = upstream_arch(LAZYDOCKER_ARCHS)?;
+        let env_dirpath = fs.ensure_env_dir(project_dirpath).map_err(Error::Io)?;
+        let index = Self::index();
+        let artifact = Artifact {
+            bin_filepaths: vec![Path::new(LAZYDOCKER_BIN_NAME)],
+            checksums_url: index.checksums_url(&self.version),
+            name: LAZYDOCKER_SOFT_NAME,
+            strip_components: 0,
+            symlinks: vec![Symlink {
+                dest: env_dirpath.join(&self.bin_name),
+                kind: SymlinkKind::File,
+                required: true,
+                src: Path::new(LAZYDOCKER_BIN_NAME),
+            }],
+            url: index.artifact_url(&self.version, os, arch),
+            version: &self.version,
+        };
+        self.installer.install_archive(&artifact, fs)
+    }
+
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
+        Kind::Lazydocker(self)
+    }
+
+    fn name(&self) -> &str {
+        LAZYDOCKER_SOFT_NAME
+    }
+
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>> {
+        Some(Box::new(Self::index()))
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+// TESTS
+
+#[cfg(test)]
+mod dive_test {
+    use super::*;
+    use crate::{fs::StubFileSystem, soft::installer::StubArchiveArtifactInstaller};
+    use std::io;
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_soft() {
+            let version = "0.11.0";
+            let soft = Dive::new(version.into(), None, &DownloadConfig::default());
+            assert_eq!(soft.bin_names(), [DIVE_BIN_NAME]);
+            assert!(soft.release_index().is_some());
+            assert_eq!(soft.name(), DIVE_SOFT_NAME);
+            assert_eq!(soft.version(), version);
+            match soft.kind() {
+                Kind::Dive(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod install {
+        use super::*;
+
+        struct Data {
+            env_dirpath: &'static Path,
+            project_dirpath: &'static Path,
+            version: &'static str,
+        }
+
+        impl Default for Data {
+            fn default() -> Self {
+                Self {
+                    env_dirpath: Path::new("/env"),
+                    project_dirpath: Path::new("/project"),
+                    version: "0.11.0",
+                }
+            }
+        }
+
+        struct Stubs {
+            installer: StubArchiveArtifactInstaller,
+            fs: StubFileSystem,
+        }
+
+        impl Stubs {
+            fn new(data: &Data) -> Self {
+                let env_dirpath = data.env_dirpath;
+                let expected_project_dirpath = data.project_dirpath;
+                let version = data.version;
+                let mut stubs = Self {
+                    installer: StubArchiveArtifactInstaller::default(),
+                    fs: StubFileSystem::default(),
+                };
+                stubs.fs.stub_ensure_env_dir_fn(move |project_dirpath| {
+                    assert_eq!(project_dirpath, expected_project_dirpath);
+                    Ok(env_dirpath.to_path_buf())
+                });
+                stubs.installer.stub_install_archive_fn(move |artifact, _| {
+                    let expected_artifact = Artifact {
+                        bin_filepaths: vec![Path::new(DIVE_BIN_NAME)],
+                        checksums_url: Some(format!(
+                            "https://github.com/wagoodman/dive/releases/download/v{}/dive_{}_checksums.txt",
+                            version, version,
+                        )),
+                        name: DIVE_SOFT_NAME,
+                        strip_components: 0,
+                        symlinks: vec![Symlink {
+                            dest: env_dirpath.join(DIVE_BIN_NAME),
+                            kind: SymlinkKind::File,
+                            required: true,
+                            src: Path::new(DIVE_BIN_NAME),
+                        }],
+                        url: format!(
+                            "https://github.com/wagoodman/dive/releases/download/v{}/dive_{}_{}_{}.tar.gz",
+                            version,
+                            version,
+                            Dive::os().unwrap(),
+                            upstream_arch(DIVE_ARCHS).unwrap(),
+                        ),
+                        version,
+                    };
+                    assert_eq!(*artifact, expected_artifact);
+                    Ok(())
+                });
+                stubs
+            }
+        }
+
+        #[test]
+        fn should_return_io_err_if_ensure_env_dir_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .fs
+                .stub_ensure_env_dir_fn(|_| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::Io(_) => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_err_if_install_archive_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .installer
+                .stub_install_archive_fn(|_, _| Err(Error::UnsupportedSystem));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::UnsupportedSystem => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_ok() {
+            let data = Data::default();
+            let stubs = Stubs::new(&data);
+            test(&data, stubs, |res| {
+                res.unwrap();
+            });
+        }
+
+        #[inline]
+        fn test<F: Fn(Result<()>)>(data: &Data, stubs: Stubs, assert_fn: F) {
+            let soft = Dive {
+                bin_name: DIVE_BIN_NAME.into(),
+                installer: Box::new(stubs.installer),
+                version: data.version.into(),
+            };
+            let res = soft.install(data.project_dirpath, &stubs.fs);
+            assert_fn(res);
+        }
+    }
+}
+
+#[cfg(test)]
+mod lazydocker_test {
+    use super::*;
+    use crate::{fs::StubFileSystem, soft::installer::StubArchiveArtifactInstaller};
+    use std::io;
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_soft() {
+            let version = "0.23.1";
+            let soft = Lazydocker::new(version.into(), None, &DownloadConfig::default());
+            assert_eq!(soft.bin_names(), [LAZYDOCKER_BIN_NAME]);
+            assert!(soft.release_index().is_some());
+            assert_eq!(soft.name(), LAZYDOCKER_SOFT_NAME);
+            assert_eq!(soft.version(), version);
+            match soft.kind() {
+                Kind::Lazydocker(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod install {
+        use super::*;
+
+        struct Data {
+            env_dirpath: &'static Path,
+            project_dirpath: &'static Path,
+            version: &'static str,
+        }
+
+        impl Default for Data {
+            fn default() -> Self {
+                Self {
+                    env_dirpath: Path::new("/env"),
+                    project_dirpath: Path::new("/project"),
+                    version: "0.23.1",
+                }
+            }
+        }
+
+        struct Stubs {
+            installer: StubArchiveArtifactInstaller,
+            fs: StubFileSystem,
+        }
+
+        impl Stubs {
+            fn new(data: &Data) -> Self {
+                let env_dirpath = data.env_dirpath;
+                let expected_project_dirpath = data.project_dirpath;
+                let version = data.version;
+                let mut stubs = Self {
+                    installer: StubArchiveArtifactInstaller::default(),
+                    fs: StubFileSystem::default(),
+                };
+                stubs.fs.stub_ensure_env_dir_fn(move |project_dirpath| {
+                    assert_eq!(project_dirpath, expected_project_dirpath);
+                    Ok(env_dirpath.to_path_buf())
+                });
+                stubs.installer.stub_install_archive_fn(move |artifact, _| {
+                    let expected_artifact = Artifact {
+                        bin_filepaths: vec![Path::new(LAZYDOCKER_BIN_NAME)],
+                        checksums_url: Some(format!(
+                            "https://github.com/jesseduffield/lazydocker/releases/download/v{}/checksums.txt",
+                            version,
+                        )),
+                        name: LAZYDOCKER_SOFT_NAME,
+                        strip_components: 0,
+                        symlinks: vec![Symlink {
+                            dest: env_dirpath.join(LAZYDOCKER_BIN_NAME),
+                            kind: SymlinkKind::File,
+                            required: true,
+                            src: Path::new(LAZYDOCKER_BIN_NAME),
+                        }],
+                        url: format!(
+                            "https://github.com/jesseduffield/lazydocker/releases/download/v{}/lazydocker_{}_{}_{}.tar.gz",
+                            version,
+                            version,
+                            Lazydocker::os().unwrap(),
+                            upstream_arch(LAZYDOCKER_ARCHS).unwrap(),
+                        ),
+                        version,
+                    };
+                    assert_eq!(*artifact, expected_artifact);
+                    Ok(())
+                });
+                stubs
+            }
+        }
+
+        #[test]
+        fn should_return_io_err_if_ensure_env_dir_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .fs
+                .stub_ensure_env_dir_fn(|_| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::Io(_) => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_err_if_install_archive_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .installer
+                .stub_install_archive_fn(|_, _| Err(Error::UnsupportedSystem));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::UnsupportedSystem => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_ok() {
+            let data = Data::default();
+            let stubs = Stubs::new(&data);
+            test(&data, stubs, |res| {
+                res.unwrap();
+            });
+        }
+
+        #[inline]
+        fn test<F: Fn(Result<()>)>(data: &Data, stubs: Stubs, assert_fn: F) {
+            let soft = Lazydocker {
+                bin_name: LAZYDOCKER_BIN_NAME.into(),
+                installer: Box::new(stubs.installer),
+                version: data.version.into(),
+            };
+            let res = soft.install(data.project_dirpath, &stubs.fs);
+            assert_fn(res);
+        }
+    }
+}
@@ -0,0 +1,389 @@
+// IMPORTS
+
+#[cfg(test)]
+use super::Kind;
+use super::{
+    find_upstream_arch,
+    installer::{ArchiveArtifactInstaller, Artifact, DefaultArchiveArtifactInstaller, Symlink},
+    ArchTable, Error, Result, Software,
+};
+use crate::{
+    archive::Format,
+    fs::{FileSystem, SymlinkKind},
+    net::DownloadConfig,
+    release::{GithubReleases, ReleaseIndex},
+};
+use std::{env, path::Path};
+
+// CONSTS
+
+pub const GITHUB_RELEASE_ARCHS: ArchTable = &[
+    ("x86", "386"),
+    ("x86_64", "amd64"),
+    ("arm", "arm"),
+    ("aarch64", "arm64"),
+];
+pub const GITHUB_RELEASE_OSES: ArchTable = &[("linux", "linux"), ("macos", "darwin")];
+pub const GITHUB_RELEASE_VERSION_ARGS: &[&str] = &["--version"];
+
+// DATA STRUCTS
+
+// Asset, checksums asset and tag are templates with {version}, {os} and {arch} placeholders. OS
+// and architecture names default to the ones of Go releases; `os` and `arch` override them, keyed
+// by Rust names (linux, macos, x86_64, aarch64...).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GithubReleaseConfig {
+    pub arch: Vec<(String, String)>,
+    pub asset: String,
+    pub bin_path: String,
+    pub checksums_asset: Option<String>,
+    pub name: String,
+    pub os: Vec<(String, String)>,
+    pub repo: String,
+    pub strip_components: usize,
+    pub tag: Option<String>,
+}
+
+impl GithubReleaseConfig {
+    pub fn bin_name(&self) -> &str {
+        Path::new(&self.bin_path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(&self.bin_path)
+    }
+}
+
+// STRUCTS
+
+// The asset is extracted if it is an archive, downloaded as is otherwise.
+pub struct GithubRelease {
+    bin_name: String,
+    cfg: GithubReleaseConfig,
+    installer: Box<dyn ArchiveArtifactInstaller>,
+    version: String,
+}
+
+impl GithubRelease {
+    pub fn new(
+        cfg: GithubReleaseConfig,
+        version: String,
+        bin_name: Option<String>,
+        download_cfg: &DownloadConfig,
+    ) -> Self {
+        Self {
+            bin_name: bin_name.unwrap_or_else(|| cfg.bin_name().into()),
+            cfg,
+            installer: Box::new(DefaultArchiveArtifactInstaller::new(download_cfg)),
+            version,
+        }
+    }
+
+    #[inline]
+    fn arch(&self, arch: &str) -> Result<&str> {
+        match overridden(&self.cfg.arch, arch) {
+            Some(arch) => Ok(arch),
+            None => find_upstream_arch(GITHUB_RELEASE_ARCHS, arch),
+        }
+    }
+
+    #[inline]
+    fn index(&self) -> GithubReleases {
+        let index = GithubReleases::new(
+            &self.cfg.repo,
+            &self.cfg.asset,
+            self.cfg.checksums_asset.as_deref(),
+        );
+        match &self.cfg.tag {
+            Some(tag) => index.with_tag(tag),
+            None => index,
+        }
+    }
+
+    #[inline]
+    fn os(&self, os: &str) -> Result<&str> {
+        overridden(&self.cfg.os, os)
+            .or_else(|| {
+                GITHUB_RELEASE_OSES
+                    .iter()
+                    .find(|(rust_os, _)| *rust_os == os)
+                    .map(|(_, upstream_os)| *upstream_os)
+            })
+            .ok_or(Error::UnsupportedSystem)
+    }
+}
+
+impl Software for GithubRelease {
+    fn bin_names(&self) -> Vec<String> {
+        vec![self.bin_name.clone()]
+    }
+
+    fn env_vars(&self, _install_dirpath: &Path, _env_dirpath: &Path) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    fn install(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
+        let os = self.os(env::consts::OS)?;
+        let arch = self.arch(env::consts::ARCH)?;
+        let env_dirpath = fs.ensure_env_dir(project_dirpath).map_err(Error::Io)?;
+        let index = self.index();
+        let bin_filepath = Path::new(&self.cfg.bin_path);
+        let artifact = Artifact {
+            bin_filepaths: vec![bin_filepath],
+            checksums_url: index.checksums_url(&self.version),
+            name: &self.cfg.name,
+            strip_components: self.cfg.strip_components,
+            symlinks: vec![Symlink {
+                dest: env_dirpath.join(&self.bin_name),
+                kind: SymlinkKind::File,
+                required: true,
+                src: bin_filepath,
+            }],
+            url: index.artifact_url(&self.version, os, arch),
+            version: &self.version,
+        };
+        if Format::from_extension(&self.cfg.asset).is_some() {
+            self.installer.install_archive(&artifact, fs)
+        } else {
+            self.installer.install_binary(&artifact, fs)
+        }
+    }
+
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
+        Kind::GithubRelease(self)
+    }
+
+    fn name(&self) -> &str {
+        &self.cfg.name
+    }
+
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>> {
+        Some(Box::new(self.index()))
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+// FUNCTIONS
+
+#[inline]
+fn overridden<'a>(names: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    names
+        .iter()
+        .find(|(rust_name, _)| rust_name == name)
+        .map(|(_, upstream_name)| upstream_name.as_str())
+}
+
+// TESTS
+
+#[cfg(test)]
+mod github_release_test {
+    use super::*;
+    use crate::{fs::StubFileSystem, soft::installer::StubArchiveArtifactInstaller};
+    use std::io;
+
+    #[inline]
+    fn cfg() -> GithubReleaseConfig {
+        GithubReleaseConfig {
+            arch: vec![("x86_64".into(), "x86_64".into())],
+            asset: "just-{version}-{arch}-{os}.tar.gz".into(),
+            bin_path: "bin/just".into(),
+            checksums_asset: Some("SHA256SUMS".into()),
+            name: "just".into(),
+            os: vec![("linux".into(), "unknown-linux-musl".into())],
+            repo: "casey/just".into(),
+            strip_components: 1,
+            tag: Some("{version}".into()),
+        }
+    }
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_soft() {
+            let version = "1.16.0";
+            let soft = GithubRelease::new(cfg(), version.into(), None, &DownloadConfig::default());
+            assert_eq!(soft.bin_names(), ["just"]);
+            assert!(soft.release_index().is_some());
+            assert_eq!(soft.name(), "just");
+            assert_eq!(soft.version(), version);
+            match soft.kind() {
+                Kind::GithubRelease(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod arch {
+        use super::*;
+
+        #[test]
+        fn should_return_unsupported_arch_err() {
+            let soft = GithubRelease::new(cfg(), "1.16.0".into(), None, &DownloadConfig::default());
+            match soft.arch("mips").unwrap_err() {
+                Error::UnsupportedArch { arch, .. } => assert_eq!(arch, "mips"),
+                err => panic!("{}", err),
+            }
+        }
+
+        #[test]
+        fn should_return_overridden_arch() {
+            let soft = GithubRelease::new(cfg(), "1.16.0".into(), None, &DownloadConfig::default());
+            assert_eq!(soft.arch("x86_64").unwrap(), "x86_64");
+            assert_eq!(soft.arch("aarch64").unwrap(), "arm64");
+        }
+    }
+
+    mod os {
+        use super::*;
+
+        #[test]
+        fn should_return_unsupported_system_err() {
+            let soft = GithubRelease::new(cfg(), "1.16.0".into(), None, &DownloadConfig::default());
+            match soft.os("windows").unwrap_err() {
+                Error::UnsupportedSystem => {}
+                err => panic!("{}", err),
+            }
+        }
+
+        #[test]
+        fn should_return_overridden_os() {
+            let soft = GithubRelease::new(cfg(), "1.16.0".into(), None, &DownloadConfig::default());
+            assert_eq!(soft.os("linux").unwrap(), "unknown-linux-musl");
+            assert_eq!(soft.os("macos").unwrap(), "darwin");
+        }
+    }
+
+    mod install {
+        use super::*;
+
+        struct Data {
+            cfg: GithubReleaseConfig,
+            env_dirpath: &'static Path,
+            project_dirpath: &'static Path,
+            version: &'static str,
+        }
+
+        impl Default for Data {
+            fn default() -> Self {
+                Self {
+                    cfg: cfg(),
+                    env_dirpath: Path::new("/env"),
+                    project_dirpath: Path::new("/project"),
+                    version: "1.16.0",
+                }
+            }
+        }
+
+        struct Stubs {
+            installer: StubArchiveArtifactInstaller,
+            fs: StubFileSystem,
+        }
+
+        impl Stubs {
+            fn new(data: &Data) -> Self {
+                let env_dirpath = data.env_dirpath;
+                let expected_project_dirpath = data.project_dirpath;
+                let mut stubs = Self {
+                    installer: StubArchiveArtifactInstaller::default(),
+                    fs: StubFileSystem::default(),
+                };
+                stubs.fs.stub_ensure_env_dir_fn(move |project_dirpath| {
+                    assert_eq!(project_dirpath, expected_project_dirpath);
+                    Ok(env_dirpath.to_path_buf())
+                });
+                stubs
+            }
+        }
+
+        #[test]
+        fn should_return_io_err_if_ensure_env_dir_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .fs
+                .stub_ensure_env_dir_fn(|_| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::Io(_) => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_install_archive() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            let soft = GithubRelease::new(cfg(), "1.16.0".into(), None, &DownloadConfig::default());
+            let os = soft.os(env::consts::OS).unwrap().to_string();
+            let arch = soft.arch(env::consts::ARCH).unwrap().to_string();
+            stubs
+                .installer
+                .stub_install_archive_fn(move |artifact, _| {
+                    let expected_artifact = Artifact {
+                        bin_filepaths: vec![Path::new("bin/just")],
+                        checksums_url: Some(
+                            "https://github.com/casey/just/releases/download/1.16.0/SHA256SUMS"
+                                .into(),
+                        ),
+                        name: "just",
+                        strip_components: 1,
+                        symlinks: vec![Symlink {
+                            dest: Path::new("/env/just").to_path_buf(),
+                            kind: SymlinkKind::File,
+                            required: true,
+                            src: Path::new("bin/just"),
+                        }],
+                        url: format!(
+                            "https://github.com/casey/just/releases/download/1.16.0/just-1.16.0-{}-{}.tar.gz",
+                            arch, os,
+                        ),
+                        version: "1.16.0",
+                    };
+                    assert_eq!(*artifact, expected_artifact);
+                    Ok(())
+                });
+            test(&data, stubs, |res| {
+                res.unwrap();
+            });
+        }
+
+        #[test]
+        fn should_install_binary() {
+            let data = Data {
+                cfg: GithubReleaseConfig {
+                    asset: "just_{os}_{arch}".into(),
+                    checksums_asset: None,
+                    tag: None,
+                    ..cfg()
+                },
+                ..Data::default()
+            };
+            let mut stubs = Stubs::new(&data);
+            stubs.installer.stub_install_binary_fn(|artifact, _| {
+                assert!(artifact
+                    .url
+                    .starts_with("https://github.com/casey/just/releases/download/v1.16.0/just_"));
+                assert!(artifact.checksums_url.is_none());
+                Ok(())
+            });
+            test(&data, stubs, |res| {
+                res.unwrap();
+            });
+        }
+
+        #[inline]
+        fn test<F: Fn(Result<()>)>(data: &Data, stubs: Stubs, assert_fn: F) {
+            let soft = GithubRelease {
+                bin_name: "just".into(),
+                cfg: data.cfg.clone(),
+                installer: Box::new(stubs.installer),
+                version: data.version.into(),
+            };
+            let res = soft.install(data.project_dirpath, &stubs.fs);
+            assert_fn(res);
+        }
+    }
+}
@@ -1,19 +1,26 @@
 // IMPORTS
 
-use super::Error;
+use super::{cache::SharedCache, receipt::Receipt, Error};
 use crate::{
     archive::{DefaultUnarchiver, Unarchiver},
-    fs::FileSystem,
-    net::{DefaultDownloader, Downloader},
+    fs::{FileSystem, SymlinkKind, TempFile},
+    hash::Sha256Writer,
+    net::{self, DefaultDownloader, DownloadConfig, Downloader},
 };
 use log::{debug, warn};
 use std::{
-    io,
+    fs::File,
+    io::{self, Seek},
     path::{Path, PathBuf},
+    time::{Instant, SystemTime, UNIX_EPOCH},
 };
 #[cfg(test)]
 use stub_trait::stub;
 
+// CONSTS
+
+const DENV_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 // TYPES
 
 pub type Result = super::Result<()>;
@@ -22,37 +29,56 @@ pub type Result = super::Result<()>;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Artifact<'a> {
-    pub bin_filepaths: Vec<&'static Path>,
+    pub bin_filepaths: Vec<&'a Path>,
+    pub checksums_url: Option<String>,
     pub name: &'a str,
-    pub symlinks: Vec<Symlink>,
+    pub strip_components: usize,
+    pub symlinks: Vec<Symlink<'a>>,
     pub url: String,
     pub version: &'a str,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Symlink {
+pub struct Symlink<'a> {
     pub dest: PathBuf,
+    pub kind: SymlinkKind,
     pub required: bool,
-    pub src: &'static Path,
+    pub src: &'a Path,
 }
 
 // TRAITS
 
 #[cfg_attr(test, stub)]
-pub trait ArchiveArtifactInstaller {
-    fn install_targz(&self, artifact: &Artifact, fs: &dyn FileSystem) -> Result;
+pub trait ArchiveArtifactInstaller: Send + Sync {
+    fn install_archive(&self, artifact: &Artifact, fs: &dyn FileSystem) -> Result;
 
-    fn install_zip(&self, artifact: &Artifact, fs: &dyn FileSystem) -> Result;
+    fn install_binary(&self, artifact: &Artifact, fs: &dyn FileSystem) -> Result;
+
+    fn install_targz_entries(&self, artifact: &Artifact, fs: &dyn FileSystem) -> Result;
 }
 
 // STRUCTS
 
 pub struct DefaultArchiveArtifactInstaller {
     downloader: Box<dyn Downloader>,
+    locked_sha256s: Vec<(String, String)>,
+    shared_cache: Option<SharedCache>,
     unarchiver: Box<dyn Unarchiver>,
 }
 
 impl DefaultArchiveArtifactInstaller {
+    pub fn new(download_cfg: &DownloadConfig) -> Self {
+        Self {
+            downloader: Box::new(DefaultDownloader::new(download_cfg.clone())),
+            locked_sha256s: download_cfg.locked_sha256s.clone(),
+            shared_cache: download_cfg
+                .shared_cache_dirpath
+                .clone()
+                .map(SharedCache::new),
+            unarchiver: Box::new(DefaultUnarchiver),
+        }
+    }
+
     #[inline]
     fn install<F: Fn(&Path, &Path) -> io::Result<()>>(
         &self,
@@ -74,11 +100,43 @@ impl DefaultArchiveArtifactInstaller {
             );
         } else {
             debug!("Installing {} v{}", artifact.name, artifact.version);
+            let started_at = Instant::now();
             let mut archive_file = fs.create_temp_file().map_err(Error::Io)?;
-            self.downloader
-                .download(&artifact.url, &mut archive_file.file)
-                .map_err(Error::Io)?;
+            let sha256 = match self.read_shared_cache(artifact, &mut archive_file)? {
+                Some(sha256) => sha256,
+                None => {
+                    let mut archive_writer = Sha256Writer::new(&mut archive_file.file);
+                    self.downloader
+                        .download(&artifact.url, &mut archive_writer)
+                        .map_err(Error::Download)?;
+                    let sha256 = archive_writer.digest();
+                    if let Some(checksums_url) = &artifact.checksums_url {
+                        self.verify_checksum(artifact, checksums_url, &sha256)?;
+                    }
+                    if let Some(cache) = &self.shared_cache {
+                        if let Err(err) = cache.store(&artifact.url, &archive_file.path, &sha256) {
+                            warn!(
+                                "{}: Unable to cache {}: {}",
+                                artifact.name, artifact.url, err
+                            );
+                        }
+                    }
+                    sha256
+                }
+            };
+            self.verify_locked_checksum(artifact, &sha256)?;
             unarchive_fn(&archive_file.path, &soft_dirpath).map_err(Error::Io)?;
+            let receipt = Receipt {
+                denv_version: DENV_VERSION.into(),
+                duration_ms: started_at.elapsed().as_millis() as u64,
+                installed_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or_default(),
+                sha256,
+                url: artifact.url.clone(),
+            };
+            receipt.save(&soft_dirpath, fs).map_err(Error::Io)?;
         }
         for bin_filepath in &artifact.bin_filepaths {
             let bin_filepath = soft_dirpath.join(bin_filepath);
@@ -86,7 +144,7 @@ impl DefaultArchiveArtifactInstaller {
         }
         for symlink in &artifact.symlinks {
             let src = soft_dirpath.join(symlink.src);
-            match fs.ensure_symlink(&src, &symlink.dest) {
+            match fs.ensure_symlink(&src, &symlink.dest, symlink.kind) {
                 Ok(()) => {}
                 Err(err) => {
                     if symlink.required {
@@ -105,29 +163,181 @@ impl DefaultArchiveArtifactInstaller {
         debug!("{} v{} installed", artifact.name, artifact.version);
         Ok(())
     }
+
+    // Cached artifacts may have been written by any user of the group: they are verified against
+    // the published checksums if any, against the checksum recorded with them otherwise.
+    #[inline]
+    fn read_shared_cache(
+        &self,
+        artifact: &Artifact,
+        archive_file: &mut TempFile,
+    ) -> super::Result<Option<String>> {
+        let (mut cached_file, cached_sha256) = match self
+            .shared_cache
+            .as_ref()
+            .and_then(|cache| cache.open(&artifact.url))
+        {
+            Some(cached) => cached,
+            None => return Ok(None),
+        };
+        let mut archive_writer = Sha256Writer::new(&mut archive_file.file);
+        io::copy(&mut cached_file, &mut archive_writer).map_err(Error::Io)?;
+        let sha256 = archive_writer.digest();
+        let verified = match &artifact.checksums_url {
+            Some(checksums_url) => self
+                .verify_checksum(artifact, checksums_url, &sha256)
+                .is_ok(),
+            None => sha256.eq_ignore_ascii_case(&cached_sha256),
+        };
+        if verified {
+            debug!("{}: Using cached {}", artifact.name, artifact.url);
+            Ok(Some(sha256))
+        } else {
+            warn!(
+                "{}: Ignoring cached {} whose checksum does not match",
+                artifact.name, artifact.url
+            );
+            archive_file.file.set_len(0).map_err(Error::Io)?;
+            archive_file.file.rewind().map_err(Error::Io)?;
+            Ok(None)
+        }
+    }
+
+    #[inline]
+    fn verify_checksum(&self, artifact: &Artifact, checksums_url: &str, sha256: &str) -> Result {
+        let mut checksums = vec![];
+        match self.downloader.download(checksums_url, &mut checksums) {
+            Ok(()) => {}
+            Err(net::Error::Status(404)) => {
+                debug!("{}: No checksums file published", artifact.name);
+                return Ok(());
+            }
+            Err(err) => return Err(Error::Download(err)),
+        }
+        let filename = artifact.url.rsplit('/').next().unwrap_or(&artifact.url);
+        let checksums = String::from_utf8_lossy(&checksums);
+        let expected = find_checksum(&checksums, filename)
+            .ok_or_else(|| Error::ChecksumNotFound(filename.into()))?;
+        if expected.eq_ignore_ascii_case(sha256) {
+            debug!("{}: Checksum of {} verified", artifact.name, filename);
+            Ok(())
+        } else {
+            Err(Error::ChecksumMismatch {
+                expected: expected.into(),
+                found: sha256.into(),
+            })
+        }
+    }
+
+    // The artifact is verified before it is extracted: a mismatch with the lock must not leave
+    // anything in the software directory.
+    #[inline]
+    fn verify_locked_checksum(&self, artifact: &Artifact, sha256: &str) -> Result {
+        let expected = self
+            .locked_sha256s
+            .iter()
+            .find(|(url, _)| *url == artifact.url)
+            .map(|(_, sha256)| sha256);
+        match expected {
+            Some(expected) if !expected.eq_ignore_ascii_case(sha256) => {
+                Err(Error::ChecksumMismatch {
+                    expected: expected.clone(),
+                    found: sha256.into(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 impl ArchiveArtifactInstaller for DefaultArchiveArtifactInstaller {
-    fn install_targz(&self, artifact: &Artifact, fs: &dyn FileSystem) -> Result {
+    fn install_archive(&self, artifact: &Artifact, fs: &dyn FileSystem) -> Result {
         self.install(artifact, fs, |archive_filepath, dest| {
-            self.unarchiver.untar(archive_filepath, dest)
+            self.unarchiver
+                .unarchive(archive_filepath, dest, artifact.strip_components)
+        })
+    }
+
+    // The downloaded file is the binary itself: it is copied to the first binary path.
+    fn install_binary(&self, artifact: &Artifact, fs: &dyn FileSystem) -> Result {
+        self.install(artifact, fs, |binary_filepath, dest| {
+            if let Some(bin_filepath) = artifact.bin_filepaths.first() {
+                let mut file = fs.create_file(&dest.join(bin_filepath))?;
+                io::copy(&mut File::open(binary_filepath)?, &mut file)?;
+            }
+            Ok(())
         })
     }
 
-    fn install_zip(&self, artifact: &Artifact, fs: &dyn FileSystem) -> Result {
+    fn install_targz_entries(&self, artifact: &Artifact, fs: &dyn FileSystem) -> Result {
         self.install(artifact, fs, |archive_filepath, dest| {
-            self.unarchiver.unzip(archive_filepath, dest)
+            let mut entries: Vec<(&Path, bool, SymlinkKind)> = artifact
+                .bin_filepaths
+                .iter()
+                .map(|path| (*path, true, SymlinkKind::File))
+                .collect();
+            for symlink in &artifact.symlinks {
+                if !entries.iter().any(|(path, _, _)| *path == symlink.src) {
+                    entries.push((symlink.src, symlink.required, symlink.kind));
+                }
+            }
+            for (entry, required, kind) in entries {
+                let optional = |err: &io::Error| !required && err.kind() == io::ErrorKind::NotFound;
+                match kind {
+                    SymlinkKind::Dir => {
+                        match self
+                            .unarchiver
+                            .extract_dir_from_tar(archive_filepath, entry, dest)
+                        {
+                            Ok(()) => {}
+                            Err(err) if optional(&err) => debug!("{}: {}", artifact.name, err),
+                            Err(err) => return Err(err),
+                        }
+                    }
+                    SymlinkKind::File => {
+                        let filepath = dest.join(entry);
+                        let mut file = fs.create_file(&filepath)?;
+                        match self
+                            .unarchiver
+                            .extract_from_tar(archive_filepath, entry, &mut file)
+                        {
+                            Ok(()) => {}
+                            Err(err) if optional(&err) => {
+                                debug!("{}: {}", artifact.name, err);
+                                drop(file);
+                                fs.delete_file(&filepath)?;
+                            }
+                            Err(err) => return Err(err),
+                        }
+                    }
+                }
+            }
+            Ok(())
         })
     }
 }
 
-impl Default for DefaultArchiveArtifactInstaller {
-    fn default() -> Self {
-        Self {
-            downloader: Box::new(DefaultDownloader),
-            unarchiver: Box::new(DefaultUnarchiver),
+// FUNCTIONS
+
+// Checksums files use the sha256sum format: `<sha256>  <filename>` (`*` marks binary mode). A file
+// published next to a single artifact may only contain its checksum.
+#[inline]
+fn find_checksum<'a>(checksums: &'a str, filename: &str) -> Option<&'a str> {
+    let lines: Vec<&str> = checksums
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    if let [line] = lines.as_slice() {
+        if !line.contains(char::is_whitespace) {
+            return Some(line);
         }
     }
+    lines.into_iter().find_map(|line| {
+        let (sha256, name) = line.split_once(char::is_whitespace)?;
+        let name = name.trim_start().trim_start_matches('*');
+        (name == filename).then_some(sha256)
+    })
 }
 
 // TESTS
@@ -138,12 +348,15 @@ mod default_archive_artifact_installer {
     use crate::{
         archive::StubUnarchiver,
         fs::{StubFileSystem, TempFile},
-        net::StubDownloader,
+        hash,
+        net::{self, StubDownloader},
     };
 
     mod install {
         use super::*;
 
+        const CHECKSUMS_URL: &str = "https://host/checksums.txt";
+
         macro_rules! tests {
             ($ident:ident, $method:ident, $stub_method:ident) => {
                 mod $ident {
@@ -152,6 +365,7 @@ mod default_archive_artifact_installer {
                     struct Data {
                         archive_filepath: &'static Path,
                         artifact: Artifact<'static>,
+                        locked_sha256s: Vec<(String, String)>,
                         soft_dirpath: &'static Path,
                         soft_is_installed: bool,
                     }
@@ -162,28 +376,41 @@ mod default_archive_artifact_installer {
                                 archive_filepath: Path::new("/archive"),
                                 artifact: Artifact {
                                     bin_filepaths: vec![Path::new("bin")],
+                                    checksums_url: None,
                                     name: "soft",
+                                    strip_components: 0,
                                     symlinks: vec![
                                         Symlink {
                                             dest: PathBuf::from("/dest1"),
+                                            kind: SymlinkKind::File,
                                             required: true,
                                             src: Path::new("bin1"),
                                         },
                                         Symlink {
                                             dest: PathBuf::from("/dest2"),
+                                            kind: SymlinkKind::File,
                                             required: false,
                                             src: Path::new("/bin2"),
                                         },
                                     ],
-                                    url: "url".into(),
+                                    url: "https://host/soft.zip".into(),
                                     version: "1.0.0",
                                 },
+                                locked_sha256s: vec![],
                                 soft_dirpath: Path::new("/soft"),
                                 soft_is_installed: false,
                             }
                         }
                     }
 
+                    impl Data {
+                        fn with_checksums() -> Self {
+                            let mut data = Self::default();
+                            data.artifact.checksums_url = Some(CHECKSUMS_URL.into());
+                            data
+                        }
+                    }
+
                     struct Stubs {
                         downloader: StubDownloader,
                         fs: StubFileSystem,
@@ -200,6 +427,7 @@ mod default_archive_artifact_installer {
                             let symlink1 = data.artifact.symlinks[0].clone();
                             let symlink2 = data.artifact.symlinks[1].clone();
                             let expected_version = data.artifact.version;
+                            let expected_strip_components = data.artifact.strip_components;
                             let expected_url = data.artifact.url.clone();
                             let mut stubs = Self {
                                 downloader: StubDownloader::default(),
@@ -227,19 +455,35 @@ mod default_archive_artifact_installer {
                                     assert_eq!(url, expected_url);
                                     Ok(())
                                 });
-                                stubs
-                                    .unarchiver
-                                    .$stub_method(move |archive_filepath, dest| {
+                                stubs.unarchiver.$stub_method(
+                                    move |archive_filepath, dest, strip_components| {
                                         assert_eq!(archive_filepath, expected_archive_filepath);
                                         assert_eq!(dest, soft_dirpath);
+                                        assert_eq!(strip_components, expected_strip_components);
                                         Ok(())
-                                    });
+                                    },
+                                );
+                                let expected_url = data.artifact.url.clone();
+                                stubs.fs.stub_write_fn(move |path, content| {
+                                    assert_eq!(
+                                        path,
+                                        Path::new(&format!(
+                                            "{}.receipt.json",
+                                            soft_dirpath.display()
+                                        ))
+                                    );
+                                    let receipt: Receipt = serde_json::from_str(content).unwrap();
+                                    assert_eq!(receipt.url, expected_url);
+                                    assert_eq!(receipt.sha256, hash::sha256(b""));
+                                    Ok(())
+                                });
                             }
                             stubs.fs.stub_make_executable_fn(move |path| {
                                 assert_eq!(path, soft_dirpath.join(bin_filepath));
                                 Ok(())
                             });
-                            stubs.fs.stub_ensure_symlink_fn(move |src, dest| {
+                            stubs.fs.stub_ensure_symlink_fn(move |src, dest, kind| {
+                                assert_eq!(kind, SymlinkKind::File);
                                 if src == soft_dirpath.join(symlink1.src) {
                                     assert_eq!(dest, symlink1.dest);
                                     Ok(())
@@ -281,10 +525,23 @@ mod default_archive_artifact_installer {
                     }
 
                     #[test]
-                    fn should_return_io_err_if_download_failed() {
+                    fn should_return_download_err_if_download_failed() {
                         let data = Data::default();
                         let mut stubs = Stubs::new(&data);
                         stubs.downloader.stub_download_fn(|_, _| {
+                            Err(net::Error::UnexpectedContent("text/html".into()))
+                        });
+                        test(&data, stubs, |res| match res.unwrap_err() {
+                            Error::Download(_) => {}
+                            err => panic!("{}", err),
+                        })
+                    }
+
+                    #[test]
+                    fn should_return_io_err_if_unarchive_failed() {
+                        let data = Data::default();
+                        let mut stubs = Stubs::new(&data);
+                        stubs.unarchiver.$stub_method(|_, _, _| {
                             Err(io::Error::from(io::ErrorKind::PermissionDenied))
                         });
                         test(&data, stubs, |res| match res.unwrap_err() {
@@ -294,10 +551,10 @@ mod default_archive_artifact_installer {
                     }
 
                     #[test]
-                    fn should_return_io_err_if_untar_failed() {
+                    fn should_return_io_err_if_save_receipt_failed() {
                         let data = Data::default();
                         let mut stubs = Stubs::new(&data);
-                        stubs.unarchiver.$stub_method(|_, _| {
+                        stubs.fs.stub_write_fn(|_, _| {
                             Err(io::Error::from(io::ErrorKind::PermissionDenied))
                         });
                         test(&data, stubs, |res| match res.unwrap_err() {
@@ -323,7 +580,7 @@ mod default_archive_artifact_installer {
                     fn should_return_io_err_if_ensure_symlink_failed() {
                         let data = Data::default();
                         let mut stubs = Stubs::new(&data);
-                        stubs.fs.stub_ensure_symlink_fn(|_, _| {
+                        stubs.fs.stub_ensure_symlink_fn(|_, _, _| {
                             Err(io::Error::from(io::ErrorKind::PermissionDenied))
                         });
                         test(&data, stubs, |res| match res.unwrap_err() {
@@ -332,6 +589,100 @@ mod default_archive_artifact_installer {
                         })
                     }
 
+                    #[test]
+                    fn should_return_checksum_mismatch_err() {
+                        let data = Data::with_checksums();
+                        let mut stubs = Stubs::new(&data);
+                        stub_checksums(&mut stubs, Ok(format!("{}  soft.zip\n", "0".repeat(64))));
+                        test(&data, stubs, |res| match res.unwrap_err() {
+                            Error::ChecksumMismatch { expected, found } => {
+                                assert_eq!(expected, "0".repeat(64));
+                                assert_eq!(found, hash::sha256(b""));
+                            }
+                            err => panic!("{}", err),
+                        })
+                    }
+
+                    #[test]
+                    fn should_return_checksum_mismatch_err_if_checksum_is_not_the_locked_one() {
+                        let mut data = Data::default();
+                        data.locked_sha256s = vec![(data.artifact.url.clone(), "0".repeat(64))];
+                        let mut stubs = Stubs::new(&data);
+                        stubs.unarchiver = StubUnarchiver::default();
+                        test(&data, stubs, |res| match res.unwrap_err() {
+                            Error::ChecksumMismatch { expected, found } => {
+                                assert_eq!(expected, "0".repeat(64));
+                                assert_eq!(found, hash::sha256(b""));
+                            }
+                            err => panic!("{}", err),
+                        })
+                    }
+
+                    #[test]
+                    fn should_return_checksum_not_found_err() {
+                        let data = Data::with_checksums();
+                        let mut stubs = Stubs::new(&data);
+                        stub_checksums(
+                            &mut stubs,
+                            Ok(format!("{}  other.zip\n", hash::sha256(b""))),
+                        );
+                        test(&data, stubs, |res| match res.unwrap_err() {
+                            Error::ChecksumNotFound(filename) => assert_eq!(filename, "soft.zip"),
+                            err => panic!("{}", err),
+                        })
+                    }
+
+                    #[test]
+                    fn should_return_download_err_if_checksums_download_failed() {
+                        let data = Data::with_checksums();
+                        let mut stubs = Stubs::new(&data);
+                        stub_checksums(&mut stubs, Err(500));
+                        test(&data, stubs, |res| match res.unwrap_err() {
+                            Error::Download(net::Error::Status(500)) => {}
+                            err => panic!("{}", err),
+                        })
+                    }
+
+                    #[test]
+                    fn should_return_ok_if_checksum_matches() {
+                        let data = Data::with_checksums();
+                        let mut stubs = Stubs::new(&data);
+                        stub_checksums(
+                            &mut stubs,
+                            Ok(format!(
+                                "{}  other.zip\n{} *soft.zip\n",
+                                "0".repeat(64),
+                                hash::sha256(b"")
+                            )),
+                        );
+                        test(&data, stubs, |res| {
+                            res.unwrap();
+                        })
+                    }
+
+                    #[test]
+                    fn should_return_ok_if_checksum_is_the_locked_one() {
+                        let mut data = Data::default();
+                        data.locked_sha256s = vec![
+                            ("https://host/other.zip".into(), "0".repeat(64)),
+                            (data.artifact.url.clone(), hash::sha256(b"")),
+                        ];
+                        let stubs = Stubs::new(&data);
+                        test(&data, stubs, |res| {
+                            res.unwrap();
+                        })
+                    }
+
+                    #[test]
+                    fn should_return_ok_if_checksums_file_is_not_published() {
+                        let data = Data::with_checksums();
+                        let mut stubs = Stubs::new(&data);
+                        stub_checksums(&mut stubs, Err(404));
+                        test(&data, stubs, |res| {
+                            res.unwrap();
+                        })
+                    }
+
                     #[test]
                     fn should_return_ok_if_software_is_not_installed() {
                         let data = Data::default();
@@ -353,10 +704,56 @@ mod default_archive_artifact_installer {
                         })
                     }
 
+                    #[test]
+                    fn should_recreate_symlinks_if_software_is_installed() {
+                        let data = Data {
+                            soft_is_installed: true,
+                            ..Data::default()
+                        };
+                        let dests = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+                        let mut stubs = Stubs::new(&data);
+                        let fs_dests = dests.clone();
+                        stubs.fs.stub_ensure_symlink_fn(move |_, dest, _| {
+                            fs_dests.lock().unwrap().push(dest.to_path_buf());
+                            Ok(())
+                        });
+                        test(&data, stubs, |res| {
+                            res.unwrap();
+                        });
+                        let expected: Vec<PathBuf> = data
+                            .artifact
+                            .symlinks
+                            .iter()
+                            .map(|symlink| symlink.dest.clone())
+                            .collect();
+                        assert_eq!(*dests.lock().unwrap(), expected);
+                    }
+
+                    #[inline]
+                    fn stub_checksums(
+                        stubs: &mut Stubs,
+                        checksums: std::result::Result<String, u16>,
+                    ) {
+                        stubs.downloader.stub_download_fn(move |url, out| {
+                            if url != CHECKSUMS_URL {
+                                return Ok(());
+                            }
+                            match &checksums {
+                                Ok(checksums) => {
+                                    out.write_all(checksums.as_bytes()).unwrap();
+                                    Ok(())
+                                }
+                                Err(code) => Err(net::Error::Status(*code)),
+                            }
+                        });
+                    }
+
                     #[inline]
                     fn test<F: Fn(Result)>(data: &Data, stubs: Stubs, assert_fn: F) {
                         let installer = DefaultArchiveArtifactInstaller {
                             downloader: Box::new(stubs.downloader),
+                            locked_sha256s: data.locked_sha256s.clone(),
+                            shared_cache: None,
                             unarchiver: Box::new(stubs.unarchiver),
                         };
                         let res = installer.$method(&data.artifact, &stubs.fs);
@@ -366,7 +763,405 @@ mod default_archive_artifact_installer {
             };
         }
 
-        tests!(targz, install_targz, stub_untar_fn);
-        tests!(zip, install_zip, stub_unzip_fn);
+        tests!(archive, install_archive, stub_unarchive_fn);
+
+        mod targz_entries {
+            use super::*;
+            use std::sync::{Arc, Mutex};
+
+            struct Data {
+                archive_filepath: &'static Path,
+                artifact: Artifact<'static>,
+                soft_dirpath: &'static Path,
+            }
+
+            impl Default for Data {
+                fn default() -> Self {
+                    Self {
+                        archive_filepath: Path::new("/archive"),
+                        artifact: Artifact {
+                            bin_filepaths: vec![Path::new("bin")],
+                            checksums_url: None,
+                            name: "soft",
+                            strip_components: 0,
+                            symlinks: vec![
+                                Symlink {
+                                    dest: PathBuf::from("/dest1"),
+                                    kind: SymlinkKind::File,
+                                    required: true,
+                                    src: Path::new("bin"),
+                                },
+                                Symlink {
+                                    dest: PathBuf::from("/dest2"),
+                                    kind: SymlinkKind::File,
+                                    required: false,
+                                    src: Path::new("etc/conf"),
+                                },
+                            ],
+                            url: "url".into(),
+                            version: "1.0.0",
+                        },
+                        soft_dirpath: Path::new("/soft"),
+                    }
+                }
+            }
+
+            struct Stubs {
+                deleted: Arc<Mutex<Vec<PathBuf>>>,
+                downloader: StubDownloader,
+                extracted: Arc<Mutex<Vec<PathBuf>>>,
+                fs: StubFileSystem,
+                unarchiver: StubUnarchiver,
+            }
+
+            impl Stubs {
+                fn new(data: &Data) -> Self {
+                    let expected_archive_filepath = data.archive_filepath;
+                    let soft_dirpath = data.soft_dirpath;
+                    let mut stubs = Self {
+                        deleted: Arc::new(Mutex::new(vec![])),
+                        downloader: StubDownloader::default(),
+                        extracted: Arc::new(Mutex::new(vec![])),
+                        fs: StubFileSystem::default(),
+                        unarchiver: StubUnarchiver::default(),
+                    };
+                    stubs
+                        .fs
+                        .stub_ensure_software_dir_fn(move |_, _| Ok(soft_dirpath.to_path_buf()));
+                    stubs.fs.stub_file_exists_fn(|_| false);
+                    stubs.fs.stub_create_temp_file_fn(move || {
+                        let file = TempFile {
+                            file: tempfile::tempfile().unwrap(),
+                            path: expected_archive_filepath.to_path_buf(),
+                        };
+                        Ok(file)
+                    });
+                    stubs.downloader.stub_download_fn(|_, _| Ok(()));
+                    stubs.fs.stub_write_fn(|_, _| Ok(()));
+                    stubs.fs.stub_create_file_fn(move |path| {
+                        assert!(path.starts_with(soft_dirpath));
+                        Ok(tempfile::tempfile().unwrap())
+                    });
+                    let deleted = stubs.deleted.clone();
+                    stubs.fs.stub_delete_file_fn(move |path| {
+                        deleted.lock().unwrap().push(path.to_path_buf());
+                        Ok(())
+                    });
+                    let extracted = stubs.extracted.clone();
+                    stubs
+                        .unarchiver
+                        .stub_extract_from_tar_fn(move |archive_filepath, entry, _| {
+                            assert_eq!(archive_filepath, expected_archive_filepath);
+                            extracted.lock().unwrap().push(entry.to_path_buf());
+                            if entry == Path::new("bin") {
+                                Ok(())
+                            } else {
+                                Err(io::Error::from(io::ErrorKind::NotFound))
+                            }
+                        });
+                    stubs.fs.stub_make_executable_fn(|_| Ok(()));
+                    stubs.fs.stub_ensure_symlink_fn(|_, _, _| Ok(()));
+                    stubs
+                }
+            }
+
+            #[test]
+            fn should_return_io_err_if_create_file_failed() {
+                let data = Data::default();
+                let mut stubs = Stubs::new(&data);
+                stubs
+                    .fs
+                    .stub_create_file_fn(|_| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+                test(&data, stubs, |res, _, _| match res.unwrap_err() {
+                    Error::Io(_) => {}
+                    err => panic!("{}", err),
+                })
+            }
+
+            #[test]
+            fn should_return_io_err_if_required_entry_is_missing() {
+                let data = Data::default();
+                let mut stubs = Stubs::new(&data);
+                stubs.unarchiver.stub_extract_from_tar_fn(|_, _, _| {
+                    Err(io::Error::from(io::ErrorKind::NotFound))
+                });
+                test(&data, stubs, |res, _, _| match res.unwrap_err() {
+                    Error::Io(_) => {}
+                    err => panic!("{}", err),
+                })
+            }
+
+            #[test]
+            fn should_return_ok() {
+                let data = Data::default();
+                let stubs = Stubs::new(&data);
+                test(&data, stubs, |res, extracted, deleted| {
+                    res.unwrap();
+                    assert_eq!(extracted, [PathBuf::from("bin"), PathBuf::from("etc/conf")]);
+                    assert_eq!(deleted, [data.soft_dirpath.join("etc/conf")]);
+                })
+            }
+
+            #[test]
+            fn should_extract_dir() {
+                let mut data = Data::default();
+                data.artifact.symlinks[1] = Symlink {
+                    dest: PathBuf::from("/include"),
+                    kind: SymlinkKind::Dir,
+                    required: true,
+                    src: Path::new("include"),
+                };
+                let soft_dirpath = data.soft_dirpath;
+                let mut stubs = Stubs::new(&data);
+                let extracted = stubs.extracted.clone();
+                stubs
+                    .unarchiver
+                    .stub_extract_dir_from_tar_fn(move |_, dirpath, dest| {
+                        assert_eq!(dest, soft_dirpath);
+                        extracted.lock().unwrap().push(dirpath.to_path_buf());
+                        Ok(())
+                    });
+                stubs.fs.stub_ensure_symlink_fn(move |src, dest, kind| {
+                    if dest == Path::new("/include") {
+                        assert_eq!(src, soft_dirpath.join("include"));
+                        assert_eq!(kind, SymlinkKind::Dir);
+                    }
+                    Ok(())
+                });
+                test(&data, stubs, |res, extracted, deleted| {
+                    res.unwrap();
+                    assert_eq!(extracted, [PathBuf::from("bin"), PathBuf::from("include")]);
+                    assert!(deleted.is_empty());
+                })
+            }
+
+            #[inline]
+            fn test<F: Fn(Result, &[PathBuf], &[PathBuf])>(
+                data: &Data,
+                stubs: Stubs,
+                assert_fn: F,
+            ) {
+                let installer = DefaultArchiveArtifactInstaller {
+                    downloader: Box::new(stubs.downloader),
+                    locked_sha256s: vec![],
+                    shared_cache: None,
+                    unarchiver: Box::new(stubs.unarchiver),
+                };
+                let res = installer.install_targz_entries(&data.artifact, &stubs.fs);
+                assert_fn(
+                    res,
+                    &stubs.extracted.lock().unwrap(),
+                    &stubs.deleted.lock().unwrap(),
+                );
+            }
+        }
+    }
+
+    #[cfg(all(feature = "archive", any(feature = "reqwest", feature = "ureq")))]
+    mod fixture_server {
+        use super::*;
+        use crate::{
+            net::Mirror,
+            testing::{Fixture, FixtureServer},
+        };
+        use std::{
+            collections::HashMap,
+            fs::{self, File},
+            io::{Cursor, Write},
+            sync::{Arc, Mutex},
+        };
+        use zip::{write::FileOptions, ZipWriter};
+
+        #[test]
+        fn should_install_binary() {
+            let binary = b"#!/bin/sh".to_vec();
+            let checksums = format!("{}  soft-linux\n", hash::sha256(&binary));
+            let server = FixtureServer::start(HashMap::from([
+                ("/soft-linux".to_string(), Fixture::new(binary)),
+                ("/checksums.txt".to_string(), Fixture::new(checksums)),
+            ]))
+            .unwrap();
+            let dirpath = tempfile::tempdir().unwrap();
+            let soft_dirpath = dirpath.path().join("soft");
+            let binary_filepath = dirpath.path().join("binary");
+            let mut fs = StubFileSystem::default();
+            let expected_soft_dirpath = soft_dirpath.clone();
+            fs.stub_ensure_software_dir_fn(move |_, _| {
+                fs::create_dir_all(&expected_soft_dirpath)?;
+                Ok(expected_soft_dirpath.clone())
+            });
+            fs.stub_file_exists_fn(|_| false);
+            fs.stub_create_temp_file_fn(move || {
+                Ok(TempFile {
+                    file: File::create(&binary_filepath)?,
+                    path: binary_filepath.clone(),
+                })
+            });
+            fs.stub_create_file_fn(|path| File::create(path));
+            fs.stub_write_fn(|_, _| Ok(()));
+            fs.stub_make_executable_fn(|_| Ok(()));
+            let artifact = Artifact {
+                bin_filepaths: vec![Path::new("soft")],
+                checksums_url: Some(server.url("/checksums.txt")),
+                name: "soft",
+                strip_components: 0,
+                symlinks: vec![],
+                url: server.url("/soft-linux"),
+                version: "1.0.0",
+            };
+            let installer = DefaultArchiveArtifactInstaller::new(&DownloadConfig::default());
+            installer.install_binary(&artifact, &fs).unwrap();
+            let content = fs::read_to_string(soft_dirpath.join("soft")).unwrap();
+            assert_eq!(content, "#!/bin/sh");
+        }
+
+        #[test]
+        fn should_install_binary_from_shared_cache() {
+            let server = FixtureServer::start(HashMap::from([(
+                "/soft-linux".to_string(),
+                Fixture::new("#!/bin/sh"),
+            )]))
+            .unwrap();
+            let dirpath = tempfile::tempdir().unwrap();
+            let cache_dirpath = dirpath.path().join("cache");
+            let binary_filepath = dirpath.path().join("binary");
+            let soft_dirpath = Arc::new(Mutex::new(dirpath.path().join("soft1")));
+            let mut fs = StubFileSystem::default();
+            let expected_soft_dirpath = soft_dirpath.clone();
+            fs.stub_ensure_software_dir_fn(move |_, _| {
+                let soft_dirpath = expected_soft_dirpath.lock().unwrap().clone();
+                fs::create_dir_all(&soft_dirpath)?;
+                Ok(soft_dirpath)
+            });
+            fs.stub_file_exists_fn(|_| false);
+            fs.stub_create_temp_file_fn(move || {
+                Ok(TempFile {
+                    file: File::create(&binary_filepath)?,
+                    path: binary_filepath.clone(),
+                })
+            });
+            fs.stub_create_file_fn(|path| File::create(path));
+            fs.stub_write_fn(|_, _| Ok(()));
+            fs.stub_make_executable_fn(|_| Ok(()));
+            let artifact = Artifact {
+                bin_filepaths: vec![Path::new("soft")],
+                checksums_url: None,
+                name: "soft",
+                strip_components: 0,
+                symlinks: vec![],
+                url: server.url("/soft-linux"),
+                version: "1.0.0",
+            };
+            let download_cfg = DownloadConfig {
+                shared_cache_dirpath: Some(cache_dirpath.clone()),
+                ..DownloadConfig::default()
+            };
+            let installer = DefaultArchiveArtifactInstaller::new(&download_cfg);
+            installer.install_binary(&artifact, &fs).unwrap();
+            let installer = DefaultArchiveArtifactInstaller {
+                downloader: Box::new(StubDownloader::default()),
+                locked_sha256s: vec![],
+                shared_cache: Some(SharedCache::new(cache_dirpath.clone())),
+                unarchiver: Box::new(DefaultUnarchiver),
+            };
+            *soft_dirpath.lock().unwrap() = dirpath.path().join("soft2");
+            installer.install_binary(&artifact, &fs).unwrap();
+            let content = fs::read_to_string(dirpath.path().join("soft2/soft")).unwrap();
+            assert_eq!(content, "#!/bin/sh");
+            let cached_filepath = fs::read_dir(cache_dirpath.join("artifacts"))
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .find(|path| path.extension().is_none())
+                .unwrap();
+            fs::write(cached_filepath, "corrupted").unwrap();
+            let mut downloader = StubDownloader::default();
+            downloader.stub_download_fn(|_, out| {
+                out.write_all(b"#!/bin/sh").unwrap();
+                Ok(())
+            });
+            let installer = DefaultArchiveArtifactInstaller {
+                downloader: Box::new(downloader),
+                locked_sha256s: vec![],
+                shared_cache: Some(SharedCache::new(cache_dirpath)),
+                unarchiver: Box::new(DefaultUnarchiver),
+            };
+            *soft_dirpath.lock().unwrap() = dirpath.path().join("soft3");
+            installer.install_binary(&artifact, &fs).unwrap();
+            let content = fs::read_to_string(dirpath.path().join("soft3/soft")).unwrap();
+            assert_eq!(content, "#!/bin/sh");
+        }
+
+        #[test]
+        fn should_install_archive() {
+            let mut zip = ZipWriter::new(Cursor::new(vec![]));
+            zip.start_file("bin", FileOptions::default()).unwrap();
+            zip.write_all(b"#!/bin/sh").unwrap();
+            let archive = zip.finish().unwrap().into_inner();
+            let checksums = format!("{}  soft.zip\n", hash::sha256(&archive));
+            let server = FixtureServer::start(HashMap::from([
+                ("/soft.zip".to_string(), Fixture::new(archive)),
+                ("/checksums.txt".to_string(), Fixture::new(checksums)),
+            ]))
+            .unwrap();
+            let dirpath = tempfile::tempdir().unwrap();
+            let soft_dirpath = dirpath.path().join("soft");
+            let archive_filepath = dirpath.path().join("archive");
+            let mut fs = StubFileSystem::default();
+            let expected_soft_dirpath = soft_dirpath.clone();
+            fs.stub_ensure_software_dir_fn(move |_, _| {
+                fs::create_dir_all(&expected_soft_dirpath)?;
+                Ok(expected_soft_dirpath.clone())
+            });
+            fs.stub_file_exists_fn(|_| false);
+            fs.stub_create_temp_file_fn(move || {
+                Ok(TempFile {
+                    file: File::create(&archive_filepath)?,
+                    path: archive_filepath.clone(),
+                })
+            });
+            fs.stub_write_fn(|_, _| Ok(()));
+            fs.stub_make_executable_fn(|_| Ok(()));
+            let download_cfg = DownloadConfig {
+                mirrors: vec![Mirror {
+                    prefix: "https://releases.example.com/".into(),
+                    url: server.url("/"),
+                }],
+                ..DownloadConfig::default()
+            };
+            let artifact = Artifact {
+                bin_filepaths: vec![Path::new("bin")],
+                checksums_url: Some("https://releases.example.com/checksums.txt".into()),
+                name: "soft",
+                strip_components: 0,
+                symlinks: vec![],
+                url: "https://releases.example.com/soft.zip".into(),
+                version: "1.0.0",
+            };
+            let installer = DefaultArchiveArtifactInstaller::new(&download_cfg);
+            installer.install_archive(&artifact, &fs).unwrap();
+            let content = fs::read_to_string(soft_dirpath.join("bin")).unwrap();
+            assert_eq!(content, "#!/bin/sh");
+        }
+    }
+}
+
+#[cfg(test)]
+mod find_checksum_test {
+    use super::*;
+
+    #[test]
+    fn should_return_none() {
+        assert_eq!(find_checksum("abc  other.zip\n", "soft.zip"), None);
+    }
+
+    #[test]
+    fn should_return_checksum() {
+        let checksums = "abc  other.zip\ndef *soft.zip\n\n";
+        assert_eq!(find_checksum(checksums, "soft.zip"), Some("def"));
+    }
+
+    #[test]
+    fn should_return_checksum_if_file_only_contains_it() {
+        assert_eq!(find_checksum("def\n", "soft"), Some("def"));
     }
 }
@@ -0,0 +1,287 @@
+// IMPORTS
+
+#[cfg(test)]
+use super::Kind;
+use super::{
+    installer::{ArchiveArtifactInstaller, Artifact, DefaultArchiveArtifactInstaller, Symlink},
+    upstream_arch, ArchTable, Error, Result, Software,
+};
+use crate::{
+    fs::{FileSystem, SymlinkKind},
+    net::DownloadConfig,
+    release::{AdoptiumReleases, ReleaseIndex},
+};
+use std::{env, path::Path};
+
+// CONSTS
+
+pub const JAVA_ARCHS: ArchTable = &[("x86_64", "x64"), ("arm", "arm"), ("aarch64", "aarch64")];
+pub const JAVA_BIN_NAME: &str = "java";
+pub const JAVA_HOME_VAR_NAME: &str = "JAVA_HOME";
+pub const JAVA_SOFT_NAME: &str = "java";
+pub const JAVA_VERSION_ARGS: &[&str] = &["--version"];
+
+const JAVA_LINUX_BIN_FILEPATH: &str = "bin/java";
+const JAVA_MACOS_BIN_FILEPATH: &str = "Contents/Home/bin/java";
+const JAVA_MACOS_HOME_RELPATH: &str = "Contents/Home";
+
+// STRUCTS
+
+// Temurin archives contain a single directory named after the release (e.g. jdk-17.0.8+7/): it is
+// stripped so the JDK is at the root of the software directory, whatever the pinned version.
+pub struct Java {
+    bin_name: String,
+    installer: Box<dyn ArchiveArtifactInstaller>,
+    version: String,
+}
+
+impl Java {
+    pub fn new(version: String, bin_name: Option<String>, download_cfg: &DownloadConfig) -> Self {
+        Self {
+            bin_name: bin_name.unwrap_or_else(|| JAVA_BIN_NAME.into()),
+            installer: Box::new(DefaultArchiveArtifactInstaller::new(download_cfg)),
+            version,
+        }
+    }
+
+    #[inline]
+    fn bin_filepath() -> &'static Path {
+        if env::consts::OS == "macos" {
+            Path::new(JAVA_MACOS_BIN_FILEPATH)
+        } else {
+            Path::new(JAVA_LINUX_BIN_FILEPATH)
+        }
+    }
+
+    #[inline]
+    fn home_dirpath(install_dirpath: &Path) -> String {
+        if env::consts::OS == "macos" {
+            install_dirpath
+                .join(JAVA_MACOS_HOME_RELPATH)
+                .display()
+                .to_string()
+        } else {
+            install_dirpath.display().to_string()
+        }
+    }
+
+    #[inline]
+    fn index(&self) -> AdoptiumReleases {
+        AdoptiumReleases::new(&self.version)
+    }
+
+    #[inline]
+    fn os() -> Result<&'static str> {
+        match env::consts::OS {
+            "macos" => Ok("mac"),
+            "linux" => Ok("linux"),
+            _ => Err(Error::UnsupportedSystem),
+        }
+    }
+}
+
+impl Software for Java {
+    fn bin_names(&self) -> Vec<String> {
+        vec![self.bin_name.clone()]
+    }
+
+    fn env_vars(&self, install_dirpath: &Path, _env_dirpath: &Path) -> Vec<(String, String)> {
+        vec![(
+            JAVA_HOME_VAR_NAME.into(),
+            Self::home_dirpath(install_dirpath),
+        )]
+    }
+
+    fn install(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
+        let os = Self::os()?;
+        let arch = upstream_arch(JAVA_ARCHS)?;
+        let env_dirpath = fs.ensure_env_dir(project_dirpath).map_err(Error::Io)?;
+        let index = self.index();
+        let bin_filepath = Self::bin_filepath();
+        let artifact = Artifact {
+            bin_filepaths: vec![bin_filepath],
+            checksums_url: index.checksums_url(&self.version),
+            name: JAVA_SOFT_NAME,
+            strip_components: 1,
+            symlinks: vec![Symlink {
+                dest: env_dirpath.join(&self.bin_name),
+                kind: SymlinkKind::File,
+                required: true,
+                src: bin_filepath,
+            }],
+            url: index.artifact_url(&self.version, os, arch),
+            version: &self.version,
+        };
+        self.installer.install_archive(&artifact, fs)
+    }
+
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
+        Kind::Java(self)
+    }
+
+    fn name(&self) -> &str {
+        JAVA_SOFT_NAME
+    }
+
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>> {
+        Some(Box::new(self.index()))
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+// TESTS
+
+#[cfg(test)]
+mod java_test {
+    use super::*;
+    use crate::{fs::StubFileSystem, soft::installer::StubArchiveArtifactInstaller};
+    use std::io;
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_soft() {
+            let version = "17.0.8+7";
+            let soft = Java::new(version.into(), None, &DownloadConfig::default());
+            assert_eq!(soft.bin_names(), [JAVA_BIN_NAME]);
+            assert!(soft.release_index().is_some());
+            assert_eq!(soft.name(), JAVA_SOFT_NAME);
+            assert_eq!(soft.version(), version);
+            match soft.kind() {
+                Kind::Java(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod env_vars {
+        use super::*;
+
+        #[test]
+        fn should_return_java_home() {
+            let soft = Java::new("17".into(), None, &DownloadConfig::default());
+            let install_dirpath = Path::new("/softwares/java/17");
+            let expected = vec![(
+                JAVA_HOME_VAR_NAME.to_string(),
+                Java::home_dirpath(install_dirpath),
+            )];
+            assert_eq!(soft.env_vars(install_dirpath, Path::new("/env")), expected);
+            assert!(expected[0].1.starts_with("/softwares/java/17"));
+        }
+    }
+
+    mod install {
+        use super::*;
+
+        struct Data {
+            env_dirpath: &'static Path,
+            project_dirpath: &'static Path,
+            version: &'static str,
+        }
+
+        impl Default for Data {
+            fn default() -> Self {
+                Self {
+                    env_dirpath: Path::new("/env"),
+                    project_dirpath: Path::new("/project"),
+                    version: "17.0.8+7",
+                }
+            }
+        }
+
+        struct Stubs {
+            installer: StubArchiveArtifactInstaller,
+            fs: StubFileSystem,
+        }
+
+        impl Stubs {
+            fn new(data: &Data) -> Self {
+                let env_dirpath = data.env_dirpath;
+                let expected_project_dirpath = data.project_dirpath;
+                let version = data.version;
+                let mut stubs = Self {
+                    installer: StubArchiveArtifactInstaller::default(),
+                    fs: StubFileSystem::default(),
+                };
+                stubs.fs.stub_ensure_env_dir_fn(move |project_dirpath| {
+                    assert_eq!(project_dirpath, expected_project_dirpath);
+                    Ok(env_dirpath.to_path_buf())
+                });
+                stubs.installer.stub_install_archive_fn(move |artifact, _| {
+                    let expected_artifact = Artifact {
+                        bin_filepaths: vec![Java::bin_filepath()],
+                        checksums_url: None,
+                        name: JAVA_SOFT_NAME,
+                        strip_components: 1,
+                        symlinks: vec![Symlink {
+                            dest: env_dirpath.join(JAVA_BIN_NAME),
+                            kind: SymlinkKind::File,
+                            required: true,
+                            src: Java::bin_filepath(),
+                        }],
+                        url: format!(
+                            "https://api.adoptium.net/v3/binary/version/jdk-17.0.8%2B7/{}/{}/jdk/hotspot/normal/eclipse",
+                            Java::os().unwrap(),
+                            upstream_arch(JAVA_ARCHS).unwrap(),
+                        ),
+                        version,
+                    };
+                    assert_eq!(*artifact, expected_artifact);
+                    Ok(())
+                });
+                stubs
+            }
+        }
+
+        #[test]
+        fn should_return_io_err_if_ensure_env_dir_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .fs
+                .stub_ensure_env_dir_fn(|_| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::Io(_) => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_err_if_install_archive_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .installer
+                .stub_install_archive_fn(|_, _| Err(Error::UnsupportedSystem));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::UnsupportedSystem => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_ok() {
+            let data = Data::default();
+            let stubs = Stubs::new(&data);
+            test(&data, stubs, |res| {
+                res.unwrap();
+            });
+        }
+
+        #[inline]
+        fn test<F: Fn(Result<()>)>(data: &Data, stubs: Stubs, assert_fn: F) {
+            let soft = Java {
+                bin_name: JAVA_BIN_NAME.into(),
+                installer: Box::new(stubs.installer),
+                version: data.version.into(),
+            };
+            let res = soft.install(data.project_dirpath, &stubs.fs);
+            assert_fn(res);
+        }
+    }
+}
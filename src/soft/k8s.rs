@@ -1,40 +1,66 @@
 // IMPORTS
 
+#[cfg(test)]
+use super::Kind;
 use super::{
     installer::{ArchiveArtifactInstaller, Artifact, DefaultArchiveArtifactInstaller, Symlink},
-    Error, Kind, Result, Software,
+    upstream_arch, ArchTable, Error, Result, Software,
+};
+use crate::{
+    fs::{FileSystem, SymlinkKind},
+    net::DownloadConfig,
+    release::{GithubReleases, HelmReleases, KubectlReleases, ReleaseIndex},
 };
-use crate::fs::FileSystem;
 use std::{env, path::Path};
 
 // CONSTS
 
-const CT_BIN_NAME: &str = "ct";
-const CT_SOFT_NAME: &str = "chart-testing";
+pub const CT_ARCHS: ArchTable = &[("x86_64", "amd64"), ("arm", "armv6"), ("aarch64", "arm64")];
+pub const CT_BIN_NAME: &str = "ct";
+pub const CT_GITHUB_REPO: &str = "helm/chart-testing";
+pub const CT_SOFT_NAME: &str = "chart-testing";
+pub const CT_VERSION_ARGS: &[&str] = &["version"];
+pub const HELM_ARCHS: ArchTable = &[("x86_64", "amd64"), ("arm", "arm"), ("aarch64", "arm64")];
+pub const HELM_BIN_NAME: &str = "helm";
+pub const HELM_SOFT_NAME: &str = "helm";
+pub const HELM_VERSION_ARGS: &[&str] = &["version", "--short"];
+pub const KUBECTL_ARCHS: ArchTable = &[("x86_64", "amd64"), ("arm", "arm"), ("aarch64", "arm64")];
+pub const KUBECTL_BIN_NAME: &str = "kubectl";
+pub const KUBECTL_SOFT_NAME: &str = "kubectl";
+pub const KUBECTL_VERSION_ARGS: &[&str] = &["version", "--client"];
+pub const STERN_ARCHS: ArchTable = &[("x86_64", "amd64"), ("arm", "arm"), ("aarch64", "arm64")];
+pub const STERN_BIN_NAME: &str = "stern";
+pub const STERN_GITHUB_REPO: &str = "stern/stern";
+pub const STERN_SOFT_NAME: &str = "stern";
+pub const STERN_VERSION_ARGS: &[&str] = &["--version"];
+
+const CT_ASSET: &str = "chart-testing_{version}_{os}_{arch}.tar.gz";
+const CT_CHECKSUMS_ASSET: &str = "chart-testing_{version}_checksums.txt";
+const HELM_CHECKSUMS_EXTENSION: &str = ".sha256sum";
+const KUBECTL_CHECKSUMS_EXTENSION: &str = ".sha256";
+const STERN_ASSET: &str = "stern_{version}_{os}_{arch}.tar.gz";
+const STERN_CHECKSUMS_ASSET: &str = "checksums.txt";
 
 // STRUCTS
 
 pub struct ChartTesting {
+    bin_name: String,
     installer: Box<dyn ArchiveArtifactInstaller>,
     version: String,
 }
 
 impl ChartTesting {
-    pub fn new(version: String) -> Self {
+    pub fn new(version: String, bin_name: Option<String>, download_cfg: &DownloadConfig) -> Self {
         Self {
-            installer: Box::new(DefaultArchiveArtifactInstaller::default()),
+            bin_name: bin_name.unwrap_or_else(|| CT_BIN_NAME.into()),
+            installer: Box::new(DefaultArchiveArtifactInstaller::new(download_cfg)),
             version,
         }
     }
 
     #[inline]
-    fn arch() -> Result<&'static str> {
-        match env::consts::ARCH {
-            "x86_64" => Ok("amd64"),
-            "arm" => Ok("armv6"),
-            "aarch64" => Ok("arm64"),
-            _ => Err(Error::UnsupportedSystem),
-        }
+    fn index() -> GithubReleases {
+        GithubReleases::new(CT_GITHUB_REPO, CT_ASSET, Some(CT_CHECKSUMS_ASSET))
     }
 
     #[inline]
@@ -48,39 +74,51 @@ impl ChartTesting {
 }
 
 impl Software for ChartTesting {
+    fn bin_names(&self) -> Vec<String> {
+        vec![self.bin_name.clone()]
+    }
+
+    fn env_vars(&self, _install_dirpath: &Path, _env_dirpath: &Path) -> Vec<(String, String)> {
+        vec![]
+    }
+
     fn install(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
         let env_dirpath = fs.ensure_env_dir(project_dirpath).map_err(Error::Io)?;
         let home_dirpath = fs.home_dirpath().map_err(Error::Io)?;
+        let index = Self::index();
         let artifact = Artifact {
             bin_filepaths: vec![Path::new(CT_BIN_NAME)],
+            checksums_url: index.checksums_url(&self.version),
             name: CT_SOFT_NAME,
+            strip_components: 0,
             symlinks: vec![
                 Symlink {
-                    dest: env_dirpath.join(CT_BIN_NAME),
+                    dest: env_dirpath.join(&self.bin_name),
+                    kind: SymlinkKind::File,
                     required: true,
                     src: Path::new(CT_BIN_NAME),
                 },
                 Symlink {
                     dest: home_dirpath.join(".ct/chart_schema.yaml"),
+                    kind: SymlinkKind::File,
                     required: false,
                     src: Path::new("etc/chart-schema.yaml"),
                 },
                 Symlink {
                     dest: home_dirpath.join(".ct/lintconf.yaml"),
+                    kind: SymlinkKind::File,
                     required: false,
                     src: Path::new("etc/lintconf.yaml"),
-                }
+                },
             ],
-            url: format!(
-                "https://github.com/helm/chart-testing/releases/download/v{}/chart-testing_{}_{}_{}.tar.gz",
-                self.version, self.version, Self::os()?, Self::arch()?,
-            ),
+            url: index.artifact_url(&self.version, Self::os()?, upstream_arch(CT_ARCHS)?),
             version: &self.version,
         };
-        self.installer.install_targz(&artifact, fs)
+        self.installer.install_targz_entries(&artifact, fs)
     }
 
-    fn kind(&self) -> Kind {
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
         Kind::ChartTesting(self)
     }
 
@@ -88,6 +126,239 @@ impl Software for ChartTesting {
         CT_SOFT_NAME
     }
 
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>> {
+        Some(Box::new(Self::index()))
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+// Helm archives contain a single <os>-<arch> directory, which is stripped when extracting.
+pub struct Helm {
+    bin_name: String,
+    installer: Box<dyn ArchiveArtifactInstaller>,
+    version: String,
+}
+
+impl Helm {
+    pub fn new(version: String, bin_name: Option<String>, download_cfg: &DownloadConfig) -> Self {
+        Self {
+            bin_name: bin_name.unwrap_or_else(|| HELM_BIN_NAME.into()),
+            installer: Box::new(DefaultArchiveArtifactInstaller::new(download_cfg)),
+            version,
+        }
+    }
+
+    #[inline]
+    fn os() -> Result<&'static str> {
+        match env::consts::OS {
+            "macos" => Ok("darwin"),
+            "linux" => Ok("linux"),
+            _ => Err(Error::UnsupportedSystem),
+        }
+    }
+}
+
+impl Software for Helm {
+    fn bin_names(&self) -> Vec<String> {
+        vec![self.bin_name.clone()]
+    }
+
+    fn env_vars(&self, _install_dirpath: &Path, _env_dirpath: &Path) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    fn install(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
+        let os = Self::os()?;
+        let arch = upstream_arch(HELM_ARCHS)?;
+        let env_dirpath = fs.ensure_env_dir(project_dirpath).map_err(Error::Io)?;
+        let url = HelmReleases.artifact_url(&self.version, os, arch);
+        let artifact = Artifact {
+            bin_filepaths: vec![Path::new(HELM_BIN_NAME)],
+            checksums_url: Some(format!("{}{}", url, HELM_CHECKSUMS_EXTENSION)),
+            name: HELM_SOFT_NAME,
+            strip_components: 1,
+            symlinks: vec![Symlink {
+                dest: env_dirpath.join(&self.bin_name),
+                kind: SymlinkKind::File,
+                required: true,
+                src: Path::new(HELM_BIN_NAME),
+            }],
+            url,
+            version: &self.version,
+        };
+        self.installer.install_archive(&artifact, fs)
+    }
+
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
+        Kind::Helm(self)
+    }
+
+    fn name(&self) -> &str {
+        HELM_SOFT_NAME
+    }
+
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>> {
+        Some(Box::new(HelmReleases))
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+// kubectl is released as a single binary per platform, which is downloaded as is.
+pub struct Kubectl {
+    bin_name: String,
+    installer: Box<dyn ArchiveArtifactInstaller>,
+    version: String,
+}
+
+impl Kubectl {
+    pub fn new(version: String, bin_name: Option<String>, download_cfg: &DownloadConfig) -> Self {
+        Self {
+            bin_name: bin_name.unwrap_or_else(|| KUBECTL_BIN_NAME.into()),
+            installer: Box::new(DefaultArchiveArtifactInstaller::new(download_cfg)),
+            version,
+        }
+    }
+
+    #[inline]
+    fn os() -> Result<&'static str> {
+        match env::consts::OS {
+            "macos" => Ok("darwin"),
+            "linux" => Ok("linux"),
+            _ => Err(Error::UnsupportedSystem),
+        }
+    }
+}
+
+impl Software for Kubectl {
+    fn bin_names(&self) -> Vec<String> {
+        vec![self.bin_name.clone()]
+    }
+
+    fn env_vars(&self, _install_dirpath: &Path, _env_dirpath: &Path) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    fn install(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
+        let os = Self::os()?;
+        let arch = upstream_arch(KUBECTL_ARCHS)?;
+        let env_dirpath = fs.ensure_env_dir(project_dirpath).map_err(Error::Io)?;
+        let url = KubectlReleases.artifact_url(&self.version, os, arch);
+        let artifact = Artifact {
+            bin_filepaths: vec![Path::new(KUBECTL_BIN_NAME)],
+            checksums_url: Some(format!("{}{}", url, KUBECTL_CHECKSUMS_EXTENSION)),
+            name: KUBECTL_SOFT_NAME,
+            strip_components: 0,
+            symlinks: vec![Symlink {
+                dest: env_dirpath.join(&self.bin_name),
+                kind: SymlinkKind::File,
+                required: true,
+                src: Path::new(KUBECTL_BIN_NAME),
+            }],
+            url,
+            version: &self.version,
+        };
+        self.installer.install_binary(&artifact, fs)
+    }
+
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
+        Kind::Kubectl(self)
+    }
+
+    fn name(&self) -> &str {
+        KUBECTL_SOFT_NAME
+    }
+
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>> {
+        Some(Box::new(KubectlReleases))
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+pub struct Stern {
+    bin_name: String,
+    installer: Box<dyn ArchiveArtifactInstaller>,
+    version: String,
+}
+
+impl Stern {
+    pub fn new(version: String, bin_name: Option<String>, download_cfg: &DownloadConfig) -> Self {
+        Self {
+            bin_name: bin_name.unwrap_or_else(|| STERN_BIN_NAME.into()),
+            installer: Box::new(DefaultArchiveArtifactInstaller::new(download_cfg)),
+            version,
+        }
+    }
+
+    #[inline]
+    fn index() -> GithubReleases {
+        GithubReleases::new(STERN_GITHUB_REPO, STERN_ASSET, Some(STERN_CHECKSUMS_ASSET))
+    }
+
+    #[inline]
+    fn os() -> Result<&'static str> {
+        match env::consts::OS {
+            "macos" => Ok("darwin"),
+            "linux" => Ok("linux"),
+            _ => Err(Error::UnsupportedSystem),
+        }
+    }
+}
+
+impl Software for Stern {
+    fn bin_names(&self) -> Vec<String> {
+        vec![self.bin_name.clone()]
+    }
+
+    fn env_vars(&self, _install_dirpath: &Path, _env_dirpath: &Path) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    fn install(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
+        let os = Self::os()?;
+        let arch = upstream_arch(STERN_ARCHS)?;
+        let env_dirpath = fs.ensure_env_dir(project_dirpath).map_err(Error::Io)?;
+        let index = Self::index();
+        let artifact = Artifact {
+            bin_filepaths: vec![Path::new(STERN_BIN_NAME)],
+            checksums_url: index.checksums_url(&self.version),
+            name: STERN_SOFT_NAME,
+            strip_components: 0,
+            symlinks: vec![Symlink {
+                dest: env_dirpath.join(&self.bin_name),
+                kind: SymlinkKind::File,
+                required: true,
+                src: Path::new(STERN_BIN_NAME),
+            }],
+            url: index.artifact_url(&self.version, os, arch),
+            version: &self.version,
+        };
+        self.installer.install_archive(&artifact, fs)
+    }
+
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
+        Kind::Stern(self)
+    }
+
+    fn name(&self) -> &str {
+        STERN_SOFT_NAME
+    }
+
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>> {
+        Some(Box::new(Self::index()))
+    }
+
     fn version(&self) -> &str {
         &self.version
     }
@@ -107,7 +378,9 @@ mod chart_testing_test {
         #[test]
         fn should_return_soft() {
             let version = "3.7.0";
-            let soft = ChartTesting::new(version.into());
+            let soft = ChartTesting::new(version.into(), None, &DownloadConfig::default());
+            assert_eq!(soft.bin_names(), [CT_BIN_NAME]);
+            assert!(soft.release_index().is_some());
             assert_eq!(soft.name(), CT_SOFT_NAME);
             assert_eq!(soft.version(), version);
             match soft.kind() {
@@ -160,28 +433,36 @@ mod chart_testing_test {
                 stubs
                     .fs
                     .stub_home_dirpath_fn(|| Ok(home_dirpath.to_path_buf()));
-                stubs.installer.stub_install_targz_fn(move |artifact, _| {
+                stubs.installer.stub_install_targz_entries_fn(move |artifact, _| {
                     let expected_artifact = Artifact {
                         bin_filepaths: vec![Path::new(CT_BIN_NAME)],
+                        checksums_url: Some(format!(
+                            "https://github.com/helm/chart-testing/releases/download/v{}/chart-testing_{}_checksums.txt",
+                            version, version,
+                        )),
                         name: CT_SOFT_NAME,
+                        strip_components: 0,
                         symlinks: vec![
                             Symlink {
                                 dest: env_dirpath.join(CT_BIN_NAME),
+                                kind: SymlinkKind::File,
                                 required: true,
                                 src: Path::new(CT_BIN_NAME),
                             }, Symlink {
                                 dest: home_dirpath.join(".ct/chart_schema.yaml"),
+                                kind: SymlinkKind::File,
                                 required: false,
                                 src: Path::new("etc/chart-schema.yaml"),
                             }, Symlink {
                                 dest: home_dirpath.join(".ct/lintconf.yaml"),
+                                kind: SymlinkKind::File,
                                 required: false,
                                 src: Path::new("etc/lintconf.yaml"),
                             }
                         ],
                         url: format!(
                             "https://github.com/helm/chart-testing/releases/download/v{}/chart-testing_{}_{}_{}.tar.gz",
-                            version, version, ChartTesting::os().unwrap(), ChartTesting::arch().unwrap(),
+                            version, version, ChartTesting::os().unwrap(), upstream_arch(CT_ARCHS).unwrap(),
                         ),
                         version,
                     };
@@ -219,12 +500,12 @@ mod chart_testing_test {
         }
 
         #[test]
-        fn should_return_err_if_install_zip_failed() {
+        fn should_return_err_if_install_targz_entries_failed() {
             let data = Data::default();
             let mut stubs = Stubs::new(&data);
             stubs
                 .installer
-                .stub_install_targz_fn(|_, _| Err(Error::UnsupportedSystem));
+                .stub_install_targz_entries_fn(|_, _| Err(Error::UnsupportedSystem));
             test(&data, stubs, |res| match res.unwrap_err() {
                 Error::UnsupportedSystem => {}
                 err => panic!("{}", err),
@@ -243,6 +524,421 @@ mod chart_testing_test {
         #[inline]
         fn test<F: Fn(Result<()>)>(data: &Data, stubs: Stubs, assert_fn: F) {
             let soft = ChartTesting {
+                bin_name: CT_BIN_NAME.into(),
+                installer: Box::new(stubs.installer),
+                version: data.version.into(),
+            };
+            let res = soft.install(data.project_dirpath, &stubs.fs);
+            assert_fn(res);
+        }
+    }
+}
+
+#[cfg(test)]
+mod helm_test {
+    use super::*;
+    use crate::{fs::StubFileSystem, soft::installer::StubArchiveArtifactInstaller};
+    use std::io;
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_soft() {
+            let version = "3.13.1";
+            let soft = Helm::new(version.into(), None, &DownloadConfig::default());
+            assert_eq!(soft.bin_names(), [HELM_BIN_NAME]);
+            assert!(soft.release_index().is_some());
+            assert_eq!(soft.name(), HELM_SOFT_NAME);
+            assert_eq!(soft.version(), version);
+            match soft.kind() {
+                Kind::Helm(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod install {
+        use super::*;
+
+        struct Data {
+            env_dirpath: &'static Path,
+            project_dirpath: &'static Path,
+            version: &'static str,
+        }
+
+        impl Default for Data {
+            fn default() -> Self {
+                Self {
+                    env_dirpath: Path::new("/env"),
+                    project_dirpath: Path::new("/project"),
+                    version: "3.13.1",
+                }
+            }
+        }
+
+        struct Stubs {
+            installer: StubArchiveArtifactInstaller,
+            fs: StubFileSystem,
+        }
+
+        impl Stubs {
+            fn new(data: &Data) -> Self {
+                let env_dirpath = data.env_dirpath;
+                let expected_project_dirpath = data.project_dirpath;
+                let version = data.version;
+                let mut stubs = Self {
+                    installer: StubArchiveArtifactInstaller::default(),
+                    fs: StubFileSystem::default(),
+                };
+                stubs.fs.stub_ensure_env_dir_fn(move |project_dirpath| {
+                    assert_eq!(project_dirpath, expected_project_dirpath);
+                    Ok(env_dirpath.to_path_buf())
+                });
+                stubs.installer.stub_install_archive_fn(move |artifact, _| {
+                    let url = format!(
+                        "https://get.helm.sh/helm-v{}-{}-{}.tar.gz",
+                        version,
+                        Helm::os().unwrap(),
+                        upstream_arch(HELM_ARCHS).unwrap(),
+                    );
+                    let expected_artifact = Artifact {
+                        bin_filepaths: vec![Path::new(HELM_BIN_NAME)],
+                        checksums_url: Some(format!("{}.sha256sum", url)),
+                        name: HELM_SOFT_NAME,
+                        strip_components: 1,
+                        symlinks: vec![Symlink {
+                            dest: env_dirpath.join(HELM_BIN_NAME),
+                            kind: SymlinkKind::File,
+                            required: true,
+                            src: Path::new(HELM_BIN_NAME),
+                        }],
+                        url,
+                        version,
+                    };
+                    assert_eq!(*artifact, expected_artifact);
+                    Ok(())
+                });
+                stubs
+            }
+        }
+
+        #[test]
+        fn should_return_io_err_if_ensure_env_dir_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .fs
+                .stub_ensure_env_dir_fn(|_| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::Io(_) => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_err_if_install_archive_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .installer
+                .stub_install_archive_fn(|_, _| Err(Error::UnsupportedSystem));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::UnsupportedSystem => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_ok() {
+            let data = Data::default();
+            let stubs = Stubs::new(&data);
+            test(&data, stubs, |res| {
+                res.unwrap();
+            });
+        }
+
+        #[inline]
+        fn test<F: Fn(Result<()>)>(data: &Data, stubs: Stubs, assert_fn: F) {
+            let soft = Helm {
+                bin_name: HELM_BIN_NAME.into(),
+                installer: Box::new(stubs.installer),
+                version: data.version.into(),
+            };
+            let res = soft.install(data.project_dirpath, &stubs.fs);
+            assert_fn(res);
+        }
+    }
+}
+
+#[cfg(test)]
+mod kubectl_test {
+    use super::*;
+    use crate::{fs::StubFileSystem, soft::installer::StubArchiveArtifactInstaller};
+    use std::io;
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_soft() {
+            let version = "1.28.2";
+            let soft = Kubectl::new(version.into(), None, &DownloadConfig::default());
+            assert_eq!(soft.bin_names(), [KUBECTL_BIN_NAME]);
+            assert!(soft.release_index().is_some());
+            assert_eq!(soft.name(), KUBECTL_SOFT_NAME);
+            assert_eq!(soft.version(), version);
+            match soft.kind() {
+                Kind::Kubectl(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod install {
+        use super::*;
+
+        struct Data {
+            env_dirpath: &'static Path,
+            project_dirpath: &'static Path,
+            version: &'static str,
+        }
+
+        impl Default for Data {
+            fn default() -> Self {
+                Self {
+                    env_dirpath: Path::new("/env"),
+                    project_dirpath: Path::new("/project"),
+                    version: "1.28.2",
+                }
+            }
+        }
+
+        struct Stubs {
+            installer: StubArchiveArtifactInstaller,
+            fs: StubFileSystem,
+        }
+
+        impl Stubs {
+            fn new(data: &Data) -> Self {
+                let env_dirpath = data.env_dirpath;
+                let expected_project_dirpath = data.project_dirpath;
+                let version = data.version;
+                let mut stubs = Self {
+                    installer: StubArchiveArtifactInstaller::default(),
+                    fs: StubFileSystem::default(),
+                };
+                stubs.fs.stub_ensure_env_dir_fn(move |project_dirpath| {
+                    assert_eq!(project_dirpath, expected_project_dirpath);
+                    Ok(env_dirpath.to_path_buf())
+                });
+                stubs.installer.stub_install_binary_fn(move |artifact, _| {
+                    let url = format!(
+                        "https://dl.k8s.io/release/v{}/bin/{}/{}/kubectl",
+                        version,
+                        Kubectl::os().unwrap(),
+                        upstream_arch(KUBECTL_ARCHS).unwrap(),
+                    );
+                    let expected_artifact = Artifact {
+                        bin_filepaths: vec![Path::new(KUBECTL_BIN_NAME)],
+                        checksums_url: Some(format!("{}.sha256", url)),
+                        name: KUBECTL_SOFT_NAME,
+                        strip_components: 0,
+                        symlinks: vec![Symlink {
+                            dest: env_dirpath.join(KUBECTL_BIN_NAME),
+                            kind: SymlinkKind::File,
+                            required: true,
+                            src: Path::new(KUBECTL_BIN_NAME),
+                        }],
+                        url,
+                        version,
+                    };
+                    assert_eq!(*artifact, expected_artifact);
+                    Ok(())
+                });
+                stubs
+            }
+        }
+
+        #[test]
+        fn should_return_io_err_if_ensure_env_dir_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .fs
+                .stub_ensure_env_dir_fn(|_| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::Io(_) => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_err_if_install_binary_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .installer
+                .stub_install_binary_fn(|_, _| Err(Error::UnsupportedSystem));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::UnsupportedSystem => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_ok() {
+            let data = Data::default();
+            let stubs = Stubs::new(&data);
+            test(&data, stubs, |res| {
+                res.unwrap();
+            });
+        }
+
+        #[inline]
+        fn test<F: Fn(Result<()>)>(data: &Data, stubs: Stubs, assert_fn: F) {
+            let soft = Kubectl {
+                bin_name: KUBECTL_BIN_NAME.into(),
+                installer: Box::new(stubs.installer),
+                version: data.version.into(),
+            };
+            let res = soft.install(data.project_dirpath, &stubs.fs);
+            assert_fn(res);
+        }
+    }
+}
+
+#[cfg(test)]
+mod stern_test {
+    use super::*;
+    use crate::{fs::StubFileSystem, soft::installer::StubArchiveArtifactInstaller};
+    use std::io;
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_soft() {
+            let version = "1.26.0";
+            let soft = Stern::new(version.into(), None, &DownloadConfig::default());
+            assert_eq!(soft.bin_names(), [STERN_BIN_NAME]);
+            assert!(soft.release_index().is_some());
+            assert_eq!(soft.name(), STERN_SOFT_NAME);
+            assert_eq!(soft.version(), version);
+            match soft.kind() {
+                Kind::Stern(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod install {
+        use super::*;
+
+        struct Data {
+            env_dirpath: &'static Path,
+            project_dirpath: &'static Path,
+            version: &'static str,
+        }
+
+        impl Default for Data {
+            fn default() -> Self {
+                Self {
+                    env_dirpath: Path::new("/env"),
+                    project_dirpath: Path::new("/project"),
+                    version: "1.26.0",
+                }
+            }
+        }
+
+        struct Stubs {
+            installer: StubArchiveArtifactInstaller,
+            fs: StubFileSystem,
+        }
+
+        impl Stubs {
+            fn new(data: &Data) -> Self {
+                let env_dirpath = data.env_dirpath;
+                let expected_project_dirpath = data.project_dirpath;
+                let version = data.version;
+                let mut stubs = Self {
+                    installer: StubArchiveArtifactInstaller::default(),
+                    fs: StubFileSystem::default(),
+                };
+                stubs.fs.stub_ensure_env_dir_fn(move |project_dirpath| {
+                    assert_eq!(project_dirpath, expected_project_dirpath);
+                    Ok(env_dirpath.to_path_buf())
+                });
+                stubs.installer.stub_install_archive_fn(move |artifact, _| {
+                    let expected_artifact = Artifact {
+                        bin_filepaths: vec![Path::new(STERN_BIN_NAME)],
+                        checksums_url: Some(format!(
+                            "https://github.com/stern/stern/releases/download/v{}/checksums.txt",
+                            version,
+                        )),
+                        name: STERN_SOFT_NAME,
+                        strip_components: 0,
+                        symlinks: vec![Symlink {
+                            dest: env_dirpath.join(STERN_BIN_NAME),
+                            kind: SymlinkKind::File,
+                            required: true,
+                            src: Path::new(STERN_BIN_NAME),
+                        }],
+                        url: format!(
+                            "https://github.com/stern/stern/releases/download/v{}/stern_{}_{}_{}.tar.gz",
+                            version,
+                            version,
+                            Stern::os().unwrap(),
+                            upstream_arch(STERN_ARCHS).unwrap(),
+                        ),
+                        version,
+                    };
+                    assert_eq!(*artifact, expected_artifact);
+                    Ok(())
+                });
+                stubs
+            }
+        }
+
+        #[test]
+        fn should_return_io_err_if_ensure_env_dir_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .fs
+                .stub_ensure_env_dir_fn(|_| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::Io(_) => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_err_if_install_archive_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .installer
+                .stub_install_archive_fn(|_, _| Err(Error::UnsupportedSystem));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::UnsupportedSystem => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_ok() {
+            let data = Data::default();
+            let stubs = Stubs::new(&data);
+            test(&data, stubs, |res| {
+                res.unwrap();
+            });
+        }
+
+        #[inline]
+        fn test<F: Fn(Result<()>)>(data: &Data, stubs: Stubs, assert_fn: F) {
+            let soft = Stern {
+                bin_name: STERN_BIN_NAME.into(),
                 installer: Box::new(stubs.installer),
                 version: data.version.into(),
             };
@@ -1,62 +1,180 @@
 // IMPORTS
 
-use crate::fs::FileSystem;
-use k8s::ChartTesting;
+use crate::{fs::FileSystem, net, release::ReleaseIndex};
+#[cfg(test)]
+use docker::{Dive, Lazydocker};
+#[cfg(test)]
+use github::GithubRelease;
+#[cfg(test)]
+use java::Java;
+#[cfg(test)]
+use k8s::{ChartTesting, Helm, Kubectl, Stern};
+#[cfg(test)]
+use policy::{Conftest, Opa};
+#[cfg(test)]
+use proto::{Buf, Grpcurl};
+#[cfg(test)]
+use rust::Rust;
 use std::{
+    env,
     fmt::{self, Display, Formatter},
     io,
     path::Path,
 };
 #[cfg(test)]
 use stub_trait::stub;
-use tf::Terraform;
+#[cfg(test)]
+use system::System;
+#[cfg(test)]
+use tf::{Terraform, Terragrunt};
+#[cfg(test)]
+use tinygo::TinyGo;
+#[cfg(test)]
+use zig::Zig;
 
 // MODS
 
+pub mod docker;
+pub mod github;
+pub mod java;
 pub mod k8s;
+pub mod policy;
+pub mod proto;
+pub mod receipt;
+pub mod rust;
+pub mod system;
 pub mod tf;
+pub mod tinygo;
+pub mod zig;
 
+mod cache;
 mod installer;
 
 // TYPES
 
+pub type ArchTable = &'static [(&'static str, &'static str)];
 pub type Result<T> = std::result::Result<T, Error>;
 
 // ENUMS
 
 #[derive(Debug)]
 pub enum Error {
+    ChecksumMismatch {
+        expected: String,
+        found: String,
+    },
+    ChecksumNotFound(String),
+    Download(net::Error),
+    IncompatibleVersion {
+        expected: String,
+        found: String,
+    },
     Io(io::Error),
+    NotInPath(String),
+    UnsupportedArch {
+        arch: String,
+        supported: Vec<&'static str>,
+    },
     UnsupportedSystem,
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
+            Self::ChecksumMismatch { expected, found } => write!(
+                f,
+                "Checksum mismatch (expected: {}, found: {})",
+                expected, found
+            ),
+            Self::ChecksumNotFound(filename) => {
+                write!(
+                    f,
+                    "Unable to find checksum of {} in checksums file",
+                    filename
+                )
+            }
+            Self::Download(err) => write!(f, "{}", err),
+            Self::IncompatibleVersion { expected, found } => write!(
+                f,
+                "Installed version is not compatible with {} (found: {})",
+                expected, found
+            ),
             Self::Io(err) => write!(f, "{}", err),
+            Self::NotInPath(bin_name) => write!(f, "Unable to find {} in PATH", bin_name),
+            Self::UnsupportedArch { arch, supported } => write!(
+                f,
+                "Architecture {} is not supported (supported: {})",
+                arch,
+                supported.join(", ")
+            ),
             Self::UnsupportedSystem => write!(f, "This system is not supported"),
         }
     }
 }
 
+#[allow(dead_code)]
+#[cfg(test)]
 pub enum Kind<'a> {
+    Buf(&'a Buf),
     ChartTesting(&'a ChartTesting),
+    Conftest(&'a Conftest),
+    Dive(&'a Dive),
+    GithubRelease(&'a GithubRelease),
+    Grpcurl(&'a Grpcurl),
+    Helm(&'a Helm),
+    Java(&'a Java),
+    Kubectl(&'a Kubectl),
+    Lazydocker(&'a Lazydocker),
+    Opa(&'a Opa),
+    Rust(&'a Rust),
+    Stern(&'a Stern),
+    System(&'a System),
     Terraform(&'a Terraform),
+    Terragrunt(&'a Terragrunt),
+    TinyGo(&'a TinyGo),
+    Zig(&'a Zig),
 }
 
 // TRAITS
 
 #[cfg_attr(test, stub)]
-pub trait Software {
+pub trait Software: Send + Sync {
+    fn bin_names(&self) -> Vec<String>;
+
+    fn env_vars(&self, install_dirpath: &Path, env_dirpath: &Path) -> Vec<(String, String)>;
+
     fn install(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()>;
 
+    #[cfg(test)]
     fn kind(&self) -> Kind<'_>;
 
     fn name(&self) -> &str;
 
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>>;
+
     fn version(&self) -> &str;
 }
 
+// FUNCTIONS
+
+// Tables map Rust architecture names to the ones used by upstream artifacts.
+#[inline]
+fn find_upstream_arch(table: ArchTable, arch: &str) -> Result<&'static str> {
+    table
+        .iter()
+        .find(|(rust_arch, _)| *rust_arch == arch)
+        .map(|(_, upstream_arch)| *upstream_arch)
+        .ok_or_else(|| Error::UnsupportedArch {
+            arch: arch.into(),
+            supported: table.iter().map(|(rust_arch, _)| *rust_arch).collect(),
+        })
+}
+
+#[inline]
+fn upstream_arch(table: ArchTable) -> Result<&'static str> {
+    find_upstream_arch(table, env::consts::ARCH)
+}
+
 // TESTS
 
 #[cfg(test)]
@@ -66,6 +184,83 @@ mod error_test {
     mod to_string {
         use super::*;
 
+        mod checksum_mismatch {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Checksum mismatch (expected: abc, found: def)";
+                let err = Error::ChecksumMismatch {
+                    expected: "abc".into(),
+                    found: "def".into(),
+                };
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod checksum_not_found {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Unable to find checksum of soft.zip in checksums file";
+                let err = Error::ChecksumNotFound("soft.zip".into());
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod download {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let err = net::Error::Status(404);
+                let str = err.to_string();
+                let err = Error::Download(err);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod incompatible_version {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str =
+                    "Installed version is not compatible with 1.2.3 (found: Terraform v1.0.0)";
+                let err = Error::IncompatibleVersion {
+                    expected: "1.2.3".into(),
+                    found: "Terraform v1.0.0".into(),
+                };
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod not_in_path {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Unable to find terraform in PATH";
+                let err = Error::NotInPath("terraform".into());
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod unsupported_arch {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Architecture riscv64 is not supported (supported: x86_64, aarch64)";
+                let err = Error::UnsupportedArch {
+                    arch: "riscv64".into(),
+                    supported: vec!["x86_64", "aarch64"],
+                };
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
         mod unsupported_system {
             use super::*;
 
@@ -90,3 +285,26 @@ mod error_test {
         }
     }
 }
+
+#[cfg(test)]
+mod find_upstream_arch_test {
+    use super::*;
+
+    const TABLE: ArchTable = &[("x86_64", "amd64"), ("aarch64", "arm64")];
+
+    #[test]
+    fn should_return_unsupported_arch_err() {
+        match find_upstream_arch(TABLE, "riscv64").unwrap_err() {
+            Error::UnsupportedArch { arch, supported } => {
+                assert_eq!(arch, "riscv64");
+                assert_eq!(supported, vec!["x86_64", "aarch64"]);
+            }
+            err => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn should_return_upstream_arch() {
+        assert_eq!(find_upstream_arch(TABLE, "aarch64").unwrap(), "arm64");
+    }
+}
@@ -0,0 +1,480 @@
+// IMPORTS
+
+#[cfg(test)]
+use super::Kind;
+use super::{
+    installer::{ArchiveArtifactInstaller, Artifact, DefaultArchiveArtifactInstaller, Symlink},
+    upstream_arch, ArchTable, Error, Result, Software,
+};
+use crate::{
+    fs::{FileSystem, SymlinkKind},
+    net::DownloadConfig,
+    release::{GithubReleases, ReleaseIndex},
+};
+use std::{env, path::Path};
+
+// CONSTS
+
+pub const CONFTEST_ARCHS: ArchTable = &[("x86_64", "x86_64"), ("aarch64", "arm64")];
+pub const CONFTEST_BIN_NAME: &str = "conftest";
+pub const CONFTEST_GITHUB_REPO: &str = "open-policy-agent/conftest";
+pub const CONFTEST_SOFT_NAME: &str = "conftest";
+pub const CONFTEST_VERSION_ARGS: &[&str] = &["--version"];
+pub const OPA_ARCHS: ArchTable = &[("x86_64", "amd64"), ("aarch64", "arm64_static")];
+pub const OPA_BIN_NAME: &str = "opa";
+pub const OPA_GITHUB_REPO: &str = "open-policy-agent/opa";
+pub const OPA_SOFT_NAME: &str = "opa";
+pub const OPA_VERSION_ARGS: &[&str] = &["version"];
+
+const CONFTEST_ASSET: &str = "conftest_{version}_{os}_{arch}.tar.gz";
+const CONFTEST_CHECKSUMS_ASSET: &str = "checksums.txt";
+const OPA_ASSET: &str = "opa_{os}_{arch}";
+const OPA_CHECKSUMS_EXTENSION: &str = ".sha256";
+
+// STRUCTS
+
+pub struct Conftest {
+    bin_name: String,
+    installer: Box<dyn ArchiveArtifactInstaller>,
+    version: String,
+}
+
+impl Conftest {
+    pub fn new(version: String, bin_name: Option<String>, download_cfg: &DownloadConfig) -> Self {
+        Self {
+            bin_name: bin_name.unwrap_or_else(|| CONFTEST_BIN_NAME.into()),
+            installer: Box::new(DefaultArchiveArtifactInstaller::new(download_cfg)),
+            version,
+        }
+    }
+
+    #[inline]
+    fn index() -> GithubReleases {
+        GithubReleases::new(
+            CONFTEST_GITHUB_REPO,
+            CONFTEST_ASSET,
+            Some(CONFTEST_CHECKSUMS_ASSET),
+        )
+    }
+
+    #[inline]
+    fn os() -> Result<&'static str> {
+        match env::consts::OS {
+            "macos" => Ok("Darwin"),
+            "linux" => Ok("Linux"),
+            _ => Err(Error::UnsupportedSystem),
+        }
+    }
+}
+
+impl Software for Conftest {
+    fn bin_names(&self) -> Vec<String> {
+        vec![self.bin_name.clone()]
+    }
+
+    fn env_vars(&self, _install_dirpath: &Path, _env_dirpath: &Path) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    fn install(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
+        let os = Self::os()?;
+        let arch = upstream_arch(CONFTEST_ARCHS)?;
+        let env_dirpath = fs.ensure_env_dir(project_dirpath).map_err(Error::Io)?;
+        let index = Self::index();
+        let artifact = Artifact {
+            bin_filepaths: vec![Path::new(CONFTEST_BIN_NAME)],
+            checksums_url: index.checksums_url(&self.version),
+            name: CONFTEST_SOFT_NAME,
+            strip_components: 0,
+            symlinks: vec![Symlink {
+                dest: env_dirpath.join(&self.bin_name),
+                kind: SymlinkKind::File,
+                required: true,
+                src: Path::new(CONFTEST_BIN_NAME),
+            }],
+            url: index.artifact_url(&self.version, os, arch),
+            version: &self.version,
+        };
+        self.installer.install_archive(&artifact, fs)
+    }
+
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
+        Kind::Conftest(self)
+    }
+
+    fn name(&self) -> &str {
+        CONFTEST_SOFT_NAME
+    }
+
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>> {
+        Some(Box::new(Self::index()))
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+// The binaries of arm64 are only published statically linked.
+pub struct Opa {
+    bin_name: String,
+    installer: Box<dyn ArchiveArtifactInstaller>,
+    version: String,
+}
+
+impl Opa {
+    pub fn new(version: String, bin_name: Option<String>, download_cfg: &DownloadConfig) -> Self {
+        Self {
+            bin_name: bin_name.unwrap_or_else(|| OPA_BIN_NAME.into()),
+            installer: Box::new(DefaultArchiveArtifactInstaller::new(download_cfg)),
+            version,
+        }
+    }
+
+    #[inline]
+    fn index() -> GithubReleases {
+        GithubReleases::new(OPA_GITHUB_REPO, OPA_ASSET, None)
+    }
+
+    #[inline]
+    fn os() -> Result<&'static str> {
+        match env::consts::OS {
+            "macos" => Ok("darwin"),
+            "linux" => Ok("linux"),
+            _ => Err(Error::UnsupportedSystem),
+        }
+    }
+}
+
+impl Software for Opa {
+    fn bin_names(&self) -> Vec<String> {
+        vec![self.bin_name.clone()]
+    }
+
+    fn env_vars(&self, _install_dirpath: &Path, _env_dirpath: &Path) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    fn install(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
+        let os = Self::os()?;
+        let arch = upstream_arch(OPA_ARCHS)?;
+        let env_dirpath = fs.ensure_env_dir(project_dirpath).map_err(Error::Io)?;
+        let url = Self::index().artifact_url(&self.version, os, arch);
+        let artifact = Artifact {
+            bin_filepaths: vec![Path::new(OPA_BIN_NAME)],
+            checksums_url: Some(format!("{}{}", url, OPA_CHECKSUMS_EXTENSION)),
+            name: OPA_SOFT_NAME,
+            strip_components: 0,
+            symlinks: vec![Symlink {
+                dest: env_dirpath.join(&self.bin_name),
+                kind: SymlinkKind::File,
+                required: true,
+                src: Path::new(OPA_BIN_NAME),
+            }],
+            url,
+            version: &self.version,
+        };
+        self.installer.install_binary(&artifact, fs)
+    }
+
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
+        Kind::Opa(self)
+    }
+
+    fn name(&self) -> &str {
+        OPA_SOFT_NAME
+    }
+
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>> {
+        Some(Box::new(Self::index()))
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+// TESTS
+
+#[cfg(test)]
+mod conftest_test {
+    use super::*;
+    use crate::{fs::StubFileSystem, soft::installer::StubArchiveArtifactInstaller};
+    use std::io;
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_soft() {
+            let version = "0.45.0";
+            let soft = Conftest::new(version.into(), None, &DownloadConfig::default());
+            assert_eq!(soft.bin_names(), [CONFTEST_BIN_NAME]);
+            assert!(soft.release_index().is_some());
+            assert_eq!(soft.name(), CONFTEST_SOFT_NAME);
+            assert_eq!(soft.version(), version);
+            match soft.kind() {
+                Kind::Conftest(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod install {
+        use super::*;
+
+        struct Data {
+            env_dirpath: &'static Path,
+            project_dirpath: &'static Path,
+            version: &'static str,
+        }
+
+        impl Default for Data {
+            fn default() -> Self {
+                Self {
+                    env_dirpath: Path::new("/env"),
+                    project_dirpath: Path::new("/project"),
+                    version: "0.45.0",
+                }
+            }
+        }
+
+        struct Stubs {
+            installer: StubArchiveArtifactInstaller,
+            fs: StubFileSystem,
+        }
+
+        impl Stubs {
+            fn new(data: &Data) -> Self {
+                let env_dirpath = data.env_dirpath;
+                let expected_project_dirpath = data.project_dirpath;
+                let version = data.version;
+                let mut stubs = Self {
+                    installer: StubArchiveArtifactInstaller::default(),
+                    fs: StubFileSystem::default(),
+                };
+                stubs.fs.stub_ensure_env_dir_fn(move |project_dirpath| {
+                    assert_eq!(project_dirpath, expected_project_dirpath);
+                    Ok(env_dirpath.to_path_buf())
+                });
+                stubs.installer.stub_install_archive_fn(move |artifact, _| {
+                    let expected_artifact = Artifact {
+                        bin_filepaths: vec![Path::new(CONFTEST_BIN_NAME)],
+                        checksums_url: Some(format!(
+                            "https://github.com/open-policy-agent/conftest/releases/download/v{}/checksums.txt",
+                            version,
+                        )),
+                        name: CONFTEST_SOFT_NAME,
+                        strip_components: 0,
+                        symlinks: vec![Symlink {
+                            dest: env_dirpath.join(CONFTEST_BIN_NAME),
+                            kind: SymlinkKind::File,
+                            required: true,
+                            src: Path::new(CONFTEST_BIN_NAME),
+                        }],
+                        url: format!(
+                            "https://github.com/open-policy-agent/conftest/releases/download/v{}/conftest_{}_{}_{}.tar.gz",
+                            version,
+                            version,
+                            Conftest::os().unwrap(),
+                            upstream_arch(CONFTEST_ARCHS).unwrap(),
+                        ),
+                        version,
+                    };
+                    assert_eq!(*artifact, expected_artifact);
+                    Ok(())
+                });
+                stubs
+            }
+        }
+
+        #[test]
+        fn should_return_io_err_if_ensure_env_dir_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .fs
+                .stub_ensure_env_dir_fn(|_| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::Io(_) => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_err_if_install_archive_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .installer
+                .stub_install_archive_fn(|_, _| Err(Error::UnsupportedSystem));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::UnsupportedSystem => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_ok() {
+            let data = Data::default();
+            let stubs = Stubs::new(&data);
+            test(&data, stubs, |res| {
+                res.unwrap();
+            });
+        }
+
+        #[inline]
+        fn test<F: Fn(Result<()>)>(data: &Data, stubs: Stubs, assert_fn: F) {
+            let soft = Conftest {
+                bin_name: CONFTEST_BIN_NAME.into(),
+                installer: Box::new(stubs.installer),
+                version: data.version.into(),
+            };
+            let res = soft.install(data.project_dirpath, &stubs.fs);
+            assert_fn(res);
+        }
+    }
+}
+
+#[cfg(test)]
+mod opa_test {
+    use super::*;
+    use crate::{fs::StubFileSystem, soft::installer::StubArchiveArtifactInstaller};
+    use std::io;
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_soft() {
+            let version = "0.55.0";
+            let soft = Opa::new(version.into(), None, &DownloadConfig::default());
+            assert_eq!(soft.bin_names(), [OPA_BIN_NAME]);
+            assert!(soft.release_index().is_some());
+            assert_eq!(soft.name(), OPA_SOFT_NAME);
+            assert_eq!(soft.version(), version);
+            match soft.kind() {
+                Kind::Opa(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod install {
+        use super::*;
+
+        struct Data {
+            env_dirpath: &'static Path,
+            project_dirpath: &'static Path,
+            version: &'static str,
+        }
+
+        impl Default for Data {
+            fn default() -> Self {
+                Self {
+                    env_dirpath: Path::new("/env"),
+                    project_dirpath: Path::new("/project"),
+                    version: "0.55.0",
+                }
+            }
+        }
+
+        struct Stubs {
+            installer: StubArchiveArtifactInstaller,
+            fs: StubFileSystem,
+        }
+
+        impl Stubs {
+            fn new(data: &Data) -> Self {
+                let env_dirpath = data.env_dirpath;
+                let expected_project_dirpath = data.project_dirpath;
+                let version = data.version;
+                let mut stubs = Self {
+                    installer: StubArchiveArtifactInstaller::default(),
+                    fs: StubFileSystem::default(),
+                };
+                stubs.fs.stub_ensure_env_dir_fn(move |project_dirpath| {
+                    assert_eq!(project_dirpath, expected_project_dirpath);
+                    Ok(env_dirpath.to_path_buf())
+                });
+                stubs.installer.stub_install_binary_fn(move |artifact, _| {
+                    let expected_artifact = Artifact {
+                        bin_filepaths: vec![Path::new(OPA_BIN_NAME)],
+                        checksums_url: Some(format!(
+                            "https://github.com/open-policy-agent/opa/releases/download/v{}/opa_{}_{}.sha256",
+                            version,
+                            Opa::os().unwrap(),
+                            upstream_arch(OPA_ARCHS).unwrap(),
+                        )),
+                        name: OPA_SOFT_NAME,
+                        strip_components: 0,
+                        symlinks: vec![Symlink {
+                            dest: env_dirpath.join(OPA_BIN_NAME),
+                            kind: SymlinkKind::File,
+                            required: true,
+                            src: Path::new(OPA_BIN_NAME),
+                        }],
+                        url: format!(
+                            "https://github.com/open-policy-agent/opa/releases/download/v{}/opa_{}_{}",
+                            version,
+                            Opa::os().unwrap(),
+                            upstream_arch(OPA_ARCHS).unwrap(),
+                        ),
+                        version,
+                    };
+                    assert_eq!(*artifact, expected_artifact);
+                    Ok(())
+                });
+                stubs
+            }
+        }
+
+        #[test]
+        fn should_return_io_err_if_ensure_env_dir_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .fs
+                .stub_ensure_env_dir_fn(|_| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::Io(_) => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_err_if_install_binary_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .installer
+                .stub_install_binary_fn(|_, _| Err(Error::UnsupportedSystem));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::UnsupportedSystem => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_ok() {
+            let data = Data::default();
+            let stubs = Stubs::new(&data);
+            test(&data, stubs, |res| {
+                res.unwrap();
+            });
+        }
+
+        #[inline]
+        fn test<F: Fn(Result<()>)>(data: &Data, stubs: Stubs, assert_fn: F) {
+            let soft = Opa {
+                bin_name: OPA_BIN_NAME.into(),
+                installer: Box::new(stubs.installer),
+                version: data.version.into(),
+            };
+            let res = soft.install(data.project_dirpath, &stubs.fs);
+            assert_fn(res);
+        }
+    }
+}
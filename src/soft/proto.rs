@@ -0,0 +1,480 @@
+// IMPORTS
+
+#[cfg(test)]
+use super::Kind;
+use super::{
+    installer::{ArchiveArtifactInstaller, Artifact, DefaultArchiveArtifactInstaller, Symlink},
+    upstream_arch, ArchTable, Error, Result, Software,
+};
+use crate::{
+    fs::{FileSystem, SymlinkKind},
+    net::DownloadConfig,
+    release::{GithubReleases, ReleaseIndex},
+};
+use std::{env, path::Path};
+
+// CONSTS
+
+pub const BUF_BIN_NAME: &str = "buf";
+pub const BUF_GITHUB_REPO: &str = "bufbuild/buf";
+pub const BUF_LINUX_ARCHS: ArchTable = &[("x86_64", "x86_64"), ("aarch64", "aarch64")];
+pub const BUF_MACOS_ARCHS: ArchTable = &[("x86_64", "x86_64"), ("aarch64", "arm64")];
+pub const BUF_SOFT_NAME: &str = "buf";
+pub const BUF_VERSION_ARGS: &[&str] = &["--version"];
+pub const GRPCURL_ARCHS: ArchTable = &[("x86_64", "x86_64"), ("aarch64", "arm64")];
+pub const GRPCURL_BIN_NAME: &str = "grpcurl";
+pub const GRPCURL_GITHUB_REPO: &str = "fullstorydev/grpcurl";
+pub const GRPCURL_SOFT_NAME: &str = "grpcurl";
+pub const GRPCURL_VERSION_ARGS: &[&str] = &["--version"];
+
+const BUF_ASSET: &str = "buf-{os}-{arch}";
+const BUF_CHECKSUMS_ASSET: &str = "sha256.txt";
+const GRPCURL_ASSET: &str = "grpcurl_{version}_{os}_{arch}.tar.gz";
+const GRPCURL_CHECKSUMS_ASSET: &str = "grpcurl_{version}_checksums.txt";
+
+// STRUCTS
+
+// buf is released as a single binary per platform: there is no archive to extract.
+pub struct Buf {
+    bin_name: String,
+    installer: Box<dyn ArchiveArtifactInstaller>,
+    version: String,
+}
+
+impl Buf {
+    pub fn new(version: String, bin_name: Option<String>, download_cfg: &DownloadConfig) -> Self {
+        Self {
+            bin_name: bin_name.unwrap_or_else(|| BUF_BIN_NAME.into()),
+            installer: Box::new(DefaultArchiveArtifactInstaller::new(download_cfg)),
+            version,
+        }
+    }
+
+    #[inline]
+    fn index() -> GithubReleases {
+        GithubReleases::new(BUF_GITHUB_REPO, BUF_ASSET, Some(BUF_CHECKSUMS_ASSET))
+    }
+
+    #[inline]
+    fn platform() -> Result<(&'static str, ArchTable)> {
+        match env::consts::OS {
+            "macos" => Ok(("Darwin", BUF_MACOS_ARCHS)),
+            "linux" => Ok(("Linux", BUF_LINUX_ARCHS)),
+            _ => Err(Error::UnsupportedSystem),
+        }
+    }
+}
+
+impl Software for Buf {
+    fn bin_names(&self) -> Vec<String> {
+        vec![self.bin_name.clone()]
+    }
+
+    fn env_vars(&self, _install_dirpath: &Path, _env_dirpath: &Path) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    fn install(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
+        let (os, archs) = Self::platform()?;
+        let arch = upstream_arch(archs)?;
+        let env_dirpath = fs.ensure_env_dir(project_dirpath).map_err(Error::Io)?;
+        let index = Self::index();
+        let artifact = Artifact {
+            bin_filepaths: vec![Path::new(BUF_BIN_NAME)],
+            checksums_url: index.checksums_url(&self.version),
+            name: BUF_SOFT_NAME,
+            strip_components: 0,
+            symlinks: vec![Symlink {
+                dest: env_dirpath.join(&self.bin_name),
+                kind: SymlinkKind::File,
+                required: true,
+                src: Path::new(BUF_BIN_NAME),
+            }],
+            url: index.artifact_url(&self.version, os, arch),
+            version: &self.version,
+        };
+        self.installer.install_binary(&artifact, fs)
+    }
+
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
+        Kind::Buf(self)
+    }
+
+    fn name(&self) -> &str {
+        BUF_SOFT_NAME
+    }
+
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>> {
+        Some(Box::new(Self::index()))
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+pub struct Grpcurl {
+    bin_name: String,
+    installer: Box<dyn ArchiveArtifactInstaller>,
+    version: String,
+}
+
+impl Grpcurl {
+    pub fn new(version: String, bin_name: Option<String>, download_cfg: &DownloadConfig) -> Self {
+        Self {
+            bin_name: bin_name.unwrap_or_else(|| GRPCURL_BIN_NAME.into()),
+            installer: Box::new(DefaultArchiveArtifactInstaller::new(download_cfg)),
+            version,
+        }
+    }
+
+    #[inline]
+    fn index() -> GithubReleases {
+        GithubReleases::new(
+            GRPCURL_GITHUB_REPO,
+            GRPCURL_ASSET,
+            Some(GRPCURL_CHECKSUMS_ASSET),
+        )
+    }
+
+    #[inline]
+    fn os() -> Result<&'static str> {
+        match env::consts::OS {
+            "macos" => Ok("osx"),
+            "linux" => Ok("linux"),
+            _ => Err(Error::UnsupportedSystem),
+        }
+    }
+}
+
+impl Software for Grpcurl {
+    fn bin_names(&self) -> Vec<String> {
+        vec![self.bin_name.clone()]
+    }
+
+    fn env_vars(&self, _install_dirpath: &Path, _env_dirpath: &Path) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    fn install(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
+        let os = Self::os()?;
+        let arch = upstream_arch(GRPCURL_ARCHS)?;
+        let env_dirpath = fs.ensure_env_dir(project_dirpath).map_err(Error::Io)?;
+        let index = Self::index();
+        let artifact = Artifact {
+            bin_filepaths: vec![Path::new(GRPCURL_BIN_NAME)],
+            checksums_url: index.checksums_url(&self.version),
+            name: GRPCURL_SOFT_NAME,
+            strip_components: 0,
+            symlinks: vec![Symlink {
+                dest: env_dirpath.join(&self.bin_name),
+                kind: SymlinkKind::File,
+                required: true,
+                src: Path::new(GRPCURL_BIN_NAME),
+            }],
+            url: index.artifact_url(&self.version, os, arch),
+            version: &self.version,
+        };
+        self.installer.install_archive(&artifact, fs)
+    }
+
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
+        Kind::Grpcurl(self)
+    }
+
+    fn name(&self) -> &str {
+        GRPCURL_SOFT_NAME
+    }
+
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>> {
+        Some(Box::new(Self::index()))
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+// TESTS
+
+#[cfg(test)]
+mod buf_test {
+    use super::*;
+    use crate::{fs::StubFileSystem, soft::installer::StubArchiveArtifactInstaller};
+    use std::io;
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_soft() {
+            let version = "1.26.1";
+            let soft = Buf::new(version.into(), None, &DownloadConfig::default());
+            assert_eq!(soft.bin_names(), [BUF_BIN_NAME]);
+            assert!(soft.release_index().is_some());
+            assert_eq!(soft.name(), BUF_SOFT_NAME);
+            assert_eq!(soft.version(), version);
+            match soft.kind() {
+                Kind::Buf(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod install {
+        use super::*;
+
+        struct Data {
+            env_dirpath: &'static Path,
+            project_dirpath: &'static Path,
+            version: &'static str,
+        }
+
+        impl Default for Data {
+            fn default() -> Self {
+                Self {
+                    env_dirpath: Path::new("/env"),
+                    project_dirpath: Path::new("/project"),
+                    version: "1.26.1",
+                }
+            }
+        }
+
+        struct Stubs {
+            installer: StubArchiveArtifactInstaller,
+            fs: StubFileSystem,
+        }
+
+        impl Stubs {
+            fn new(data: &Data) -> Self {
+                let env_dirpath = data.env_dirpath;
+                let expected_project_dirpath = data.project_dirpath;
+                let version = data.version;
+                let mut stubs = Self {
+                    installer: StubArchiveArtifactInstaller::default(),
+                    fs: StubFileSystem::default(),
+                };
+                stubs.fs.stub_ensure_env_dir_fn(move |project_dirpath| {
+                    assert_eq!(project_dirpath, expected_project_dirpath);
+                    Ok(env_dirpath.to_path_buf())
+                });
+                stubs.installer.stub_install_binary_fn(move |artifact, _| {
+                    let (os, archs) = Buf::platform().unwrap();
+                    let expected_artifact = Artifact {
+                        bin_filepaths: vec![Path::new(BUF_BIN_NAME)],
+                        checksums_url: Some(format!(
+                            "https://github.com/bufbuild/buf/releases/download/v{}/sha256.txt",
+                            version,
+                        )),
+                        name: BUF_SOFT_NAME,
+                        strip_components: 0,
+                        symlinks: vec![Symlink {
+                            dest: env_dirpath.join(BUF_BIN_NAME),
+                            kind: SymlinkKind::File,
+                            required: true,
+                            src: Path::new(BUF_BIN_NAME),
+                        }],
+                        url: format!(
+                            "https://github.com/bufbuild/buf/releases/download/v{}/buf-{}-{}",
+                            version,
+                            os,
+                            upstream_arch(archs).unwrap(),
+                        ),
+                        version,
+                    };
+                    assert_eq!(*artifact, expected_artifact);
+                    Ok(())
+                });
+                stubs
+            }
+        }
+
+        #[test]
+        fn should_return_io_err_if_ensure_env_dir_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .fs
+                .stub_ensure_env_dir_fn(|_| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::Io(_) => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_err_if_install_binary_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .installer
+                .stub_install_binary_fn(|_, _| Err(Error::UnsupportedSystem));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::UnsupportedSystem => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_ok() {
+            let data = Data::default();
+            let stubs = Stubs::new(&data);
+            test(&data, stubs, |res| {
+                res.unwrap();
+            });
+        }
+
+        #[inline]
+        fn test<F: Fn(Result<()>)>(data: &Data, stubs: Stubs, assert_fn: F) {
+            let soft = Buf {
+                bin_name: BUF_BIN_NAME.into(),
+                installer: Box::new(stubs.installer),
+                version: data.version.into(),
+            };
+            let res = soft.install(data.project_dirpath, &stubs.fs);
+            assert_fn(res);
+        }
+    }
+}
+
+#[cfg(test)]
+mod grpcurl_test {
+    use super::*;
+    use crate::{fs::StubFileSystem, soft::installer::StubArchiveArtifactInstaller};
+    use std::io;
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_soft() {
+            let version = "1.8.7";
+            let soft = Grpcurl::new(version.into(), None, &DownloadConfig::default());
+            assert_eq!(soft.bin_names(), [GRPCURL_BIN_NAME]);
+            assert!(soft.release_index().is_some());
+            assert_eq!(soft.name(), GRPCURL_SOFT_NAME);
+            assert_eq!(soft.version(), version);
+            match soft.kind() {
+                Kind::Grpcurl(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod install {
+        use super::*;
+
+        struct Data {
+            env_dirpath: &'static Path,
+            project_dirpath: &'static Path,
+            version: &'static str,
+        }
+
+        impl Default for Data {
+            fn default() -> Self {
+                Self {
+                    env_dirpath: Path::new("/env"),
+                    project_dirpath: Path::new("/project"),
+                    version: "1.8.7",
+                }
+            }
+        }
+
+        struct Stubs {
+            installer: StubArchiveArtifactInstaller,
+            fs: StubFileSystem,
+        }
+
+        impl Stubs {
+            fn new(data: &Data) -> Self {
+                let env_dirpath = data.env_dirpath;
+                let expected_project_dirpath = data.project_dirpath;
+                let version = data.version;
+                let mut stubs = Self {
+                    installer: StubArchiveArtifactInstaller::default(),
+                    fs: StubFileSystem::default(),
+                };
+                stubs.fs.stub_ensure_env_dir_fn(move |project_dirpath| {
+                    assert_eq!(project_dirpath, expected_project_dirpath);
+                    Ok(env_dirpath.to_path_buf())
+                });
+                stubs.installer.stub_install_archive_fn(move |artifact, _| {
+                    let expected_artifact = Artifact {
+                        bin_filepaths: vec![Path::new(GRPCURL_BIN_NAME)],
+                        checksums_url: Some(format!(
+                            "https://github.com/fullstorydev/grpcurl/releases/download/v{}/grpcurl_{}_checksums.txt",
+                            version, version,
+                        )),
+                        name: GRPCURL_SOFT_NAME,
+                        strip_components: 0,
+                        symlinks: vec![Symlink {
+                            dest: env_dirpath.join(GRPCURL_BIN_NAME),
+                            kind: SymlinkKind::File,
+                            required: true,
+                            src: Path::new(GRPCURL_BIN_NAME),
+                        }],
+                        url: format!(
+                            "https://github.com/fullstorydev/grpcurl/releases/download/v{}/grpcurl_{}_{}_{}.tar.gz",
+                            version,
+                            version,
+                            Grpcurl::os().unwrap(),
+                            upstream_arch(GRPCURL_ARCHS).unwrap(),
+                        ),
+                        version,
+                    };
+                    assert_eq!(*artifact, expected_artifact);
+                    Ok(())
+                });
+                stubs
+            }
+        }
+
+        #[test]
+        fn should_return_io_err_if_ensure_env_dir_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .fs
+                .stub_ensure_env_dir_fn(|_| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::Io(_) => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_err_if_install_archive_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .installer
+                .stub_install_archive_fn(|_, _| Err(Error::UnsupportedSystem));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::UnsupportedSystem => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_ok() {
+            let data = Data::default();
+            let stubs = Stubs::new(&data);
+            test(&data, stubs, |res| {
+                res.unwrap();
+            });
+        }
+
+        #[inline]
+        fn test<F: Fn(Result<()>)>(data: &Data, stubs: Stubs, assert_fn: F) {
+            let soft = Grpcurl {
+                bin_name: GRPCURL_BIN_NAME.into(),
+                installer: Box::new(stubs.installer),
+                version: data.version.into(),
+            };
+            let res = soft.install(data.project_dirpath, &stubs.fs);
+            assert_fn(res);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+// IMPORTS
+
+use crate::fs::FileSystem;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, Error, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+// CONSTS
+
+const RECEIPT_EXTENSION: &str = "receipt.json";
+
+// DATA STRUCTS
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct InstalledSoftware {
+    pub name: String,
+    pub receipt: Option<Receipt>,
+    pub version: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Receipt {
+    pub denv_version: String,
+    pub duration_ms: u64,
+    pub installed_at: u64,
+    pub sha256: String,
+    pub url: String,
+}
+
+impl Receipt {
+    pub fn delete(soft_dirpath: &Path, fs: &dyn FileSystem) -> io::Result<()> {
+        let path = filepath(soft_dirpath);
+        if fs.file_exists(&path) {
+            fs.delete_file(&path)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn load(soft_dirpath: &Path, fs: &dyn FileSystem) -> io::Result<Option<Self>> {
+        let path = filepath(soft_dirpath);
+        if !fs.file_exists(&path) {
+            return Ok(None);
+        }
+        let json = fs.read_to_string(&path)?;
+        serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, soft_dirpath: &Path, fs: &dyn FileSystem) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(Error::other)?;
+        fs.write(&filepath(soft_dirpath), &json)
+    }
+}
+
+// FUNCTIONS
+
+// The receipt is stored next to the software directory so it does not pollute the installed files.
+#[inline]
+fn filepath(soft_dirpath: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.{}", soft_dirpath.display(), RECEIPT_EXTENSION))
+}
+
+#[cfg(test)]
+pub fn receipt() -> Receipt {
+    Receipt {
+        denv_version: "0.1.0".into(),
+        duration_ms: 1,
+        installed_at: 2,
+        sha256: "sha256".into(),
+        url: "url".into(),
+    }
+}
+
+// TESTS
+
+#[cfg(test)]
+mod receipt_test {
+    use super::*;
+    use crate::fs::StubFileSystem;
+
+    mod delete {
+        use super::*;
+
+        #[test]
+        fn should_delete_receipt() {
+            let mut fs = StubFileSystem::default();
+            fs.stub_file_exists_fn(|_| true);
+            fs.stub_delete_file_fn(|path| {
+                assert_eq!(path, Path::new("/soft/1.0.0.receipt.json"));
+                Ok(())
+            });
+            Receipt::delete(Path::new("/soft/1.0.0"), &fs).unwrap();
+        }
+
+        #[test]
+        fn should_do_nothing_if_receipt_does_not_exist() {
+            let mut fs = StubFileSystem::default();
+            fs.stub_file_exists_fn(|_| false);
+            Receipt::delete(Path::new("/soft/1.0.0"), &fs).unwrap();
+        }
+    }
+
+    mod load {
+        use super::*;
+
+        #[test]
+        fn should_return_none() {
+            let mut fs = StubFileSystem::default();
+            fs.stub_file_exists_fn(|path| {
+                assert_eq!(path, Path::new("/soft/1.0.0.receipt.json"));
+                false
+            });
+            let receipt = Receipt::load(Path::new("/soft/1.0.0"), &fs).unwrap();
+            assert!(receipt.is_none());
+        }
+
+        #[test]
+        fn should_return_io_err_if_json_is_invalid() {
+            let mut fs = StubFileSystem::default();
+            fs.stub_file_exists_fn(|_| true);
+            fs.stub_read_to_string_fn(|_| Ok("{".into()));
+            let err = Receipt::load(Path::new("/soft/1.0.0"), &fs).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData);
+        }
+
+        #[test]
+        fn should_return_receipt() {
+            let json = serde_json::to_string(&receipt()).unwrap();
+            let mut fs = StubFileSystem::default();
+            fs.stub_file_exists_fn(|_| true);
+            fs.stub_read_to_string_fn(move |path| {
+                assert_eq!(path, Path::new("/soft/1.0.0.receipt.json"));
+                Ok(json.clone())
+            });
+            let loaded = Receipt::load(Path::new("/soft/1.0.0"), &fs).unwrap();
+            assert_eq!(loaded, Some(receipt()));
+        }
+    }
+
+    mod save {
+        use super::*;
+
+        #[test]
+        fn should_write_receipt() {
+            let mut fs = StubFileSystem::default();
+            fs.stub_write_fn(|path, content| {
+                assert_eq!(path, Path::new("/soft/1.0.0.receipt.json"));
+                let saved: Receipt = serde_json::from_str(content).unwrap();
+                assert_eq!(saved, receipt());
+                Ok(())
+            });
+            receipt().save(Path::new("/soft/1.0.0"), &fs).unwrap();
+        }
+    }
+}
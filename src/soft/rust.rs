@@ -0,0 +1,283 @@
+// IMPORTS
+
+#[cfg(test)]
+use super::Kind;
+use super::{
+    installer::{ArchiveArtifactInstaller, Artifact, DefaultArchiveArtifactInstaller, Symlink},
+    upstream_arch, ArchTable, Error, Result, Software,
+};
+use crate::{
+    fs::{FileSystem, SymlinkKind},
+    net::DownloadConfig,
+    release::{ReleaseIndex, RustReleases},
+};
+use std::{env, path::Path};
+
+// CONSTS
+
+pub const RUST_ARCHS: ArchTable = &[("x86_64", "x86_64"), ("aarch64", "aarch64")];
+pub const RUST_BIN_NAME: &str = "rustc";
+pub const RUST_CARGO_HOME_VAR_NAME: &str = "CARGO_HOME";
+pub const RUST_SOFT_NAME: &str = "rust";
+pub const RUST_VERSION_ARGS: &[&str] = &["--version"];
+
+const RUST_CARGO_BIN_FILEPATH: &str = "bin/cargo";
+const RUST_CARGO_BIN_NAME: &str = "cargo";
+const RUST_CARGO_HOME_DIRNAME: &str = ".cargo";
+const RUST_CHECKSUMS_EXTENSION: &str = ".sha256";
+const RUST_RUSTC_BIN_FILEPATH: &str = "bin/rustc";
+
+// STRUCTS
+
+// Standalone archives contain one directory per component (rustc, cargo, rust-std...), each one
+// being a prefix tree: stripping the archive and component directories merges them into a
+// toolchain, as the bundled install.sh would do.
+pub struct Rust {
+    bin_name: String,
+    installer: Box<dyn ArchiveArtifactInstaller>,
+    version: String,
+}
+
+impl Rust {
+    pub fn new(version: String, bin_name: Option<String>, download_cfg: &DownloadConfig) -> Self {
+        Self {
+            bin_name: bin_name.unwrap_or_else(|| RUST_BIN_NAME.into()),
+            installer: Box::new(DefaultArchiveArtifactInstaller::new(download_cfg)),
+            version,
+        }
+    }
+
+    #[inline]
+    fn os() -> Result<&'static str> {
+        match env::consts::OS {
+            "macos" => Ok("apple-darwin"),
+            "linux" => Ok("unknown-linux-gnu"),
+            _ => Err(Error::UnsupportedSystem),
+        }
+    }
+}
+
+impl Software for Rust {
+    fn bin_names(&self) -> Vec<String> {
+        vec![RUST_CARGO_BIN_NAME.into(), self.bin_name.clone()]
+    }
+
+    fn env_vars(&self, _install_dirpath: &Path, env_dirpath: &Path) -> Vec<(String, String)> {
+        vec![(
+            RUST_CARGO_HOME_VAR_NAME.into(),
+            env_dirpath
+                .join(RUST_CARGO_HOME_DIRNAME)
+                .display()
+                .to_string(),
+        )]
+    }
+
+    fn install(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
+        let os = Self::os()?;
+        let arch = upstream_arch(RUST_ARCHS)?;
+        let env_dirpath = fs.ensure_env_dir(project_dirpath).map_err(Error::Io)?;
+        let url = RustReleases.artifact_url(&self.version, os, arch);
+        let artifact = Artifact {
+            bin_filepaths: vec![
+                Path::new(RUST_CARGO_BIN_FILEPATH),
+                Path::new(RUST_RUSTC_BIN_FILEPATH),
+            ],
+            checksums_url: Some(format!("{}{}", url, RUST_CHECKSUMS_EXTENSION)),
+            name: RUST_SOFT_NAME,
+            strip_components: 2,
+            symlinks: vec![
+                Symlink {
+                    dest: env_dirpath.join(RUST_CARGO_BIN_NAME),
+                    kind: SymlinkKind::File,
+                    required: true,
+                    src: Path::new(RUST_CARGO_BIN_FILEPATH),
+                },
+                Symlink {
+                    dest: env_dirpath.join(&self.bin_name),
+                    kind: SymlinkKind::File,
+                    required: true,
+                    src: Path::new(RUST_RUSTC_BIN_FILEPATH),
+                },
+            ],
+            url,
+            version: &self.version,
+        };
+        self.installer.install_archive(&artifact, fs)
+    }
+
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
+        Kind::Rust(self)
+    }
+
+    fn name(&self) -> &str {
+        RUST_SOFT_NAME
+    }
+
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>> {
+        Some(Box::new(RustReleases))
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+// TESTS
+
+#[cfg(test)]
+mod rust_test {
+    use super::*;
+    use crate::{fs::StubFileSystem, soft::installer::StubArchiveArtifactInstaller};
+    use std::io;
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_soft() {
+            let version = "1.72.0";
+            let soft = Rust::new(version.into(), None, &DownloadConfig::default());
+            assert_eq!(soft.bin_names(), [RUST_CARGO_BIN_NAME, RUST_BIN_NAME]);
+            assert!(soft.release_index().is_some());
+            assert_eq!(soft.name(), RUST_SOFT_NAME);
+            assert_eq!(soft.version(), version);
+            match soft.kind() {
+                Kind::Rust(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod env_vars {
+        use super::*;
+
+        #[test]
+        fn should_return_cargo_home_under_env_dir() {
+            let soft = Rust::new("1.72.0".into(), None, &DownloadConfig::default());
+            let vars = soft.env_vars(Path::new("/softwares/rust/1.72.0"), Path::new("/env"));
+            let expected = vec![(RUST_CARGO_HOME_VAR_NAME.into(), "/env/.cargo".into())];
+            assert_eq!(vars, expected);
+        }
+    }
+
+    mod install {
+        use super::*;
+
+        struct Data {
+            env_dirpath: &'static Path,
+            project_dirpath: &'static Path,
+            version: &'static str,
+        }
+
+        impl Default for Data {
+            fn default() -> Self {
+                Self {
+                    env_dirpath: Path::new("/env"),
+                    project_dirpath: Path::new("/project"),
+                    version: "1.72.0",
+                }
+            }
+        }
+
+        struct Stubs {
+            installer: StubArchiveArtifactInstaller,
+            fs: StubFileSystem,
+        }
+
+        impl Stubs {
+            fn new(data: &Data) -> Self {
+                let env_dirpath = data.env_dirpath;
+                let expected_project_dirpath = data.project_dirpath;
+                let version = data.version;
+                let mut stubs = Self {
+                    installer: StubArchiveArtifactInstaller::default(),
+                    fs: StubFileSystem::default(),
+                };
+                stubs.fs.stub_ensure_env_dir_fn(move |project_dirpath| {
+                    assert_eq!(project_dirpath, expected_project_dirpath);
+                    Ok(env_dirpath.to_path_buf())
+                });
+                stubs.installer.stub_install_archive_fn(move |artifact, _| {
+                    let url = format!(
+                        "https://static.rust-lang.org/dist/rust-{}-{}-{}.tar.gz",
+                        version,
+                        upstream_arch(RUST_ARCHS).unwrap(),
+                        Rust::os().unwrap(),
+                    );
+                    let expected_artifact = Artifact {
+                        bin_filepaths: vec![Path::new("bin/cargo"), Path::new("bin/rustc")],
+                        checksums_url: Some(format!("{}.sha256", url)),
+                        name: RUST_SOFT_NAME,
+                        strip_components: 2,
+                        symlinks: vec![
+                            Symlink {
+                                dest: env_dirpath.join("cargo"),
+                                kind: SymlinkKind::File,
+                                required: true,
+                                src: Path::new("bin/cargo"),
+                            },
+                            Symlink {
+                                dest: env_dirpath.join("rustc"),
+                                kind: SymlinkKind::File,
+                                required: true,
+                                src: Path::new("bin/rustc"),
+                            },
+                        ],
+                        url,
+                        version,
+                    };
+                    assert_eq!(*artifact, expected_artifact);
+                    Ok(())
+                });
+                stubs
+            }
+        }
+
+        #[test]
+        fn should_return_io_err_if_ensure_env_dir_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .fs
+                .stub_ensure_env_dir_fn(|_| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::Io(_) => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_err_if_install_archive_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .installer
+                .stub_install_archive_fn(|_, _| Err(Error::UnsupportedSystem));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::UnsupportedSystem => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_ok() {
+            let data = Data::default();
+            let stubs = Stubs::new(&data);
+            test(&data, stubs, |res| {
+                res.unwrap();
+            });
+        }
+
+        #[inline]
+        fn test<F: Fn(Result<()>)>(data: &Data, stubs: Stubs, assert_fn: F) {
+            let soft = Rust {
+                bin_name: RUST_BIN_NAME.into(),
+                installer: Box::new(stubs.installer),
+                version: data.version.into(),
+            };
+            let res = soft.install(data.project_dirpath, &stubs.fs);
+            assert_fn(res);
+        }
+    }
+}
@@ -0,0 +1,292 @@
+// IMPORTS
+
+#[cfg(test)]
+use super::Kind;
+use super::{Error, Result, Software};
+use crate::{
+    fs::FileSystem,
+    proc::{DefaultProcessRunner, ProcessRunner},
+    release::ReleaseIndex,
+    version::{Version, VersionReq},
+};
+use log::debug;
+use std::{io::ErrorKind, path::Path};
+
+// STRUCTS
+
+pub struct System {
+    bin_name: String,
+    name: String,
+    runner: Box<dyn ProcessRunner>,
+    version: String,
+    version_args: &'static [&'static str],
+}
+
+impl System {
+    pub fn new(
+        name: String,
+        bin_name: String,
+        version_args: &'static [&'static str],
+        version: String,
+    ) -> Self {
+        Self {
+            bin_name,
+            name,
+            runner: Box::new(DefaultProcessRunner),
+            version,
+            version_args,
+        }
+    }
+
+    // A requirement (e.g. >=1.5) is matched against the first dotted version of the output, an
+    // exact version must be a prefix of one of its tokens.
+    #[inline]
+    fn is_compatible(&self, output: &str) -> bool {
+        let mut tokens = output
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .map(|token| token.trim_start_matches('v'));
+        match self.version_req() {
+            Some(req) => tokens
+                .find(|token| {
+                    token.starts_with(|c: char| c.is_ascii_digit()) && token.contains('.')
+                })
+                .map(|token| Version::from(token).matches(&req))
+                .unwrap_or(false),
+            None => tokens.any(|token| {
+                token == self.version
+                    || token
+                        .strip_prefix(&self.version)
+                        .map(|suffix| suffix.starts_with('.'))
+                        .unwrap_or(false)
+            }),
+        }
+    }
+
+    #[inline]
+    fn version_req(&self) -> Option<VersionReq> {
+        if VersionReq::is_req(&self.version) {
+            self.version.parse().ok()
+        } else {
+            None
+        }
+    }
+}
+
+impl Software for System {
+    // The binary is used from PATH, it is not linked in the environment directory.
+    fn bin_names(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn env_vars(&self, _install_dirpath: &Path, _env_dirpath: &Path) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    fn install(&self, _project_dirpath: &Path, _fs: &dyn FileSystem) -> Result<()> {
+        debug!(
+            "Checking {} v{} is available in PATH",
+            self.name, self.version
+        );
+        let output = self
+            .runner
+            .output(&self.bin_name, self.version_args)
+            .map_err(|err| match err.kind() {
+                ErrorKind::NotFound => Error::NotInPath(self.bin_name.clone()),
+                _ => Error::Io(err),
+            })?;
+        if self.is_compatible(&output) {
+            debug!("{} v{} is available in PATH", self.name, self.version);
+            Ok(())
+        } else {
+            Err(Error::IncompatibleVersion {
+                expected: self.version.clone(),
+                found: output.lines().next().unwrap_or_default().trim().into(),
+            })
+        }
+    }
+
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
+        Kind::System(self)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>> {
+        None
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+// TESTS
+
+#[cfg(test)]
+mod system_test {
+    use super::*;
+    use crate::{fs::StubFileSystem, proc::StubProcessRunner};
+    use std::io;
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_soft() {
+            let name = "terraform";
+            let version = "1.2.3";
+            let soft = System::new(
+                name.into(),
+                "terraform".into(),
+                &["--version"],
+                version.into(),
+            );
+            assert!(soft.bin_names().is_empty());
+            assert!(soft.release_index().is_none());
+            assert_eq!(soft.name(), name);
+            assert_eq!(soft.version(), version);
+            match soft.kind() {
+                Kind::System(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod install {
+        use super::*;
+
+        struct Data {
+            bin_name: &'static str,
+            output: &'static str,
+            version: &'static str,
+            version_args: &'static [&'static str],
+        }
+
+        impl Default for Data {
+            fn default() -> Self {
+                Self {
+                    bin_name: "terraform",
+                    output: "Terraform v1.2.3\non linux_amd64\n",
+                    version: "1.2.3",
+                    version_args: &["--version"],
+                }
+            }
+        }
+
+        #[test]
+        fn should_return_not_in_path_err() {
+            let data = Data::default();
+            let mut runner = StubProcessRunner::default();
+            runner.stub_output_fn(|_, _| Err(io::Error::from(io::ErrorKind::NotFound)));
+            test(&data, runner, |res| match res.unwrap_err() {
+                Error::NotInPath(bin_name) => assert_eq!(bin_name, data.bin_name),
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_io_err() {
+            let data = Data::default();
+            let mut runner = StubProcessRunner::default();
+            runner.stub_output_fn(|_, _| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+            test(&data, runner, |res| match res.unwrap_err() {
+                Error::Io(_) => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_incompatible_version_err() {
+            let data = Data {
+                version: "1.2",
+                output: "Terraform v1.20.0\non linux_amd64\n",
+                ..Data::default()
+            };
+            let runner = stub_runner(&data);
+            test(&data, runner, |res| match res.unwrap_err() {
+                Error::IncompatibleVersion { expected, found } => {
+                    assert_eq!(expected, data.version);
+                    assert_eq!(found, "Terraform v1.20.0");
+                }
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_incompatible_version_err_if_version_does_not_match_req() {
+            let data = Data {
+                version: "~> 1.5.0",
+                output: "Terraform v1.6.0\non linux_amd64\n",
+                ..Data::default()
+            };
+            let runner = stub_runner(&data);
+            test(&data, runner, |res| match res.unwrap_err() {
+                Error::IncompatibleVersion { expected, found } => {
+                    assert_eq!(expected, data.version);
+                    assert_eq!(found, "Terraform v1.6.0");
+                }
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_ok_if_version_matches_req() {
+            let data = Data {
+                version: ">=1.5, <2",
+                output: "Terraform v1.6.0\non linux_amd64\n",
+                ..Data::default()
+            };
+            let runner = stub_runner(&data);
+            test(&data, runner, |res| res.unwrap());
+        }
+
+        #[test]
+        fn should_return_ok_if_version_is_exact() {
+            let data = Data::default();
+            let runner = stub_runner(&data);
+            test(&data, runner, |res| res.unwrap());
+        }
+
+        #[test]
+        fn should_return_ok_if_version_is_prefix() {
+            let data = Data {
+                bin_name: "ct",
+                output: "Version:\t v3.7.0\nGit commit: abc\n",
+                version: "3.7",
+                version_args: &["version"],
+            };
+            let runner = stub_runner(&data);
+            test(&data, runner, |res| res.unwrap());
+        }
+
+        #[inline]
+        fn stub_runner(data: &Data) -> StubProcessRunner {
+            let expected_bin_name = data.bin_name;
+            let expected_version_args = data.version_args;
+            let output = data.output;
+            let mut runner = StubProcessRunner::default();
+            runner.stub_output_fn(move |program, args| {
+                assert_eq!(program, expected_bin_name);
+                assert_eq!(args, expected_version_args);
+                Ok(output.into())
+            });
+            runner
+        }
+
+        #[inline]
+        fn test<F: Fn(Result<()>)>(data: &Data, runner: StubProcessRunner, assert_fn: F) {
+            let soft = System {
+                bin_name: data.bin_name.into(),
+                name: "soft".into(),
+                runner: Box::new(runner),
+                version: data.version.into(),
+                version_args: data.version_args,
+            };
+            let res = soft.install(Path::new("/project"), &StubFileSystem::default());
+            assert_fn(res);
+        }
+    }
+}
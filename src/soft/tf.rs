@@ -1,41 +1,59 @@
 // IMPORTS
 
+#[cfg(test)]
+use super::Kind;
 use super::{
     installer::{ArchiveArtifactInstaller, Artifact, DefaultArchiveArtifactInstaller, Symlink},
-    Error, Kind, Result, Software,
+    upstream_arch, ArchTable, Error, Result, Software,
+};
+use crate::{
+    fs::{FileSystem, SymlinkKind},
+    net::DownloadConfig,
+    release::{GithubReleases, HashicorpReleases, ReleaseIndex},
 };
-use crate::fs::FileSystem;
 use std::{env, path::Path};
 
 // CONSTS
 
-const TF_BIN_NAME: &str = "terraform";
-const TF_SOFT_NAME: &str = "terraform";
+pub const TF_ARCHS: ArchTable = &[
+    ("x86", "386"),
+    ("x86_64", "amd64"),
+    ("arm", "arm"),
+    ("aarch64", "arm64"),
+];
+pub const TF_BIN_NAME: &str = "terraform";
+pub const TF_SOFT_NAME: &str = "terraform";
+pub const TF_VERSION_ARGS: &[&str] = &["--version"];
+pub const TERRAGRUNT_ARCHS: ArchTable =
+    &[("x86", "386"), ("x86_64", "amd64"), ("aarch64", "arm64")];
+pub const TERRAGRUNT_BIN_NAME: &str = "terragrunt";
+pub const TERRAGRUNT_GITHUB_REPO: &str = "gruntwork-io/terragrunt";
+pub const TERRAGRUNT_SOFT_NAME: &str = "terragrunt";
+pub const TERRAGRUNT_VERSION_ARGS: &[&str] = &["--version"];
+
+const TERRAGRUNT_ASSET: &str = "terragrunt_{os}_{arch}";
+const TERRAGRUNT_CHECKSUMS_ASSET: &str = "SHA256SUMS";
 
 // STRUCTS
 
 pub struct Terraform {
+    bin_name: String,
     installer: Box<dyn ArchiveArtifactInstaller>,
     version: String,
 }
 
 impl Terraform {
-    pub fn new(version: String) -> Self {
+    pub fn new(version: String, bin_name: Option<String>, download_cfg: &DownloadConfig) -> Self {
         Self {
-            installer: Box::new(DefaultArchiveArtifactInstaller::default()),
+            bin_name: bin_name.unwrap_or_else(|| TF_BIN_NAME.into()),
+            installer: Box::new(DefaultArchiveArtifactInstaller::new(download_cfg)),
             version,
         }
     }
 
     #[inline]
-    fn arch() -> Result<&'static str> {
-        match env::consts::ARCH {
-            "x86" => Ok("386"),
-            "x86_64" => Ok("amd64"),
-            "arm" => Ok("arm"),
-            "aarch64" => Ok("arm64"),
-            _ => Err(Error::UnsupportedSystem),
-        }
+    fn index() -> HashicorpReleases {
+        HashicorpReleases::new(TF_SOFT_NAME)
     }
 
     #[inline]
@@ -49,28 +67,38 @@ impl Terraform {
 }
 
 impl Software for Terraform {
+    fn bin_names(&self) -> Vec<String> {
+        vec![self.bin_name.clone()]
+    }
+
+    fn env_vars(&self, _install_dirpath: &Path, _env_dirpath: &Path) -> Vec<(String, String)> {
+        vec![]
+    }
+
     fn install(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
         let os = Self::os()?;
-        let arch = Self::arch()?;
+        let arch = upstream_arch(TF_ARCHS)?;
         let env_dirpath = fs.ensure_env_dir(project_dirpath).map_err(Error::Io)?;
+        let index = Self::index();
         let artifact = Artifact {
             bin_filepaths: vec![Path::new(TF_BIN_NAME)],
+            checksums_url: index.checksums_url(&self.version),
             name: TF_SOFT_NAME,
+            strip_components: 0,
             symlinks: vec![Symlink {
-                dest: env_dirpath.join(TF_BIN_NAME),
+                dest: env_dirpath.join(&self.bin_name),
+                kind: SymlinkKind::File,
                 required: true,
                 src: Path::new(TF_BIN_NAME),
             }],
-            url: format!(
-                "https://releases.hashicorp.com/terraform/{}/terraform_{}_{}_{}.zip",
-                self.version, self.version, os, arch,
-            ),
+            url: index.artifact_url(&self.version, os, arch),
             version: &self.version,
         };
-        self.installer.install_zip(&artifact, fs)
+        self.installer.install_archive(&artifact, fs)
     }
 
-    fn kind(&self) -> Kind {
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
         Kind::Terraform(self)
     }
 
@@ -78,6 +106,94 @@ impl Software for Terraform {
         TF_SOFT_NAME
     }
 
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>> {
+        Some(Box::new(Self::index()))
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+// terragrunt is released as a single binary per platform: there is no archive to extract.
+pub struct Terragrunt {
+    bin_name: String,
+    installer: Box<dyn ArchiveArtifactInstaller>,
+    version: String,
+}
+
+impl Terragrunt {
+    pub fn new(version: String, bin_name: Option<String>, download_cfg: &DownloadConfig) -> Self {
+        Self {
+            bin_name: bin_name.unwrap_or_else(|| TERRAGRUNT_BIN_NAME.into()),
+            installer: Box::new(DefaultArchiveArtifactInstaller::new(download_cfg)),
+            version,
+        }
+    }
+
+    #[inline]
+    fn index() -> GithubReleases {
+        GithubReleases::new(
+            TERRAGRUNT_GITHUB_REPO,
+            TERRAGRUNT_ASSET,
+            Some(TERRAGRUNT_CHECKSUMS_ASSET),
+        )
+    }
+
+    #[inline]
+    fn os() -> Result<&'static str> {
+        match env::consts::OS {
+            "macos" => Ok("darwin"),
+            "linux" => Ok("linux"),
+            _ => Err(Error::UnsupportedSystem),
+        }
+    }
+}
+
+impl Software for Terragrunt {
+    fn bin_names(&self) -> Vec<String> {
+        vec![self.bin_name.clone()]
+    }
+
+    fn env_vars(&self, _install_dirpath: &Path, _env_dirpath: &Path) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    fn install(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
+        let os = Self::os()?;
+        let arch = upstream_arch(TERRAGRUNT_ARCHS)?;
+        let env_dirpath = fs.ensure_env_dir(project_dirpath).map_err(Error::Io)?;
+        let index = Self::index();
+        let artifact = Artifact {
+            bin_filepaths: vec![Path::new(TERRAGRUNT_BIN_NAME)],
+            checksums_url: index.checksums_url(&self.version),
+            name: TERRAGRUNT_SOFT_NAME,
+            strip_components: 0,
+            symlinks: vec![Symlink {
+                dest: env_dirpath.join(&self.bin_name),
+                kind: SymlinkKind::File,
+                required: true,
+                src: Path::new(TERRAGRUNT_BIN_NAME),
+            }],
+            url: index.artifact_url(&self.version, os, arch),
+            version: &self.version,
+        };
+        self.installer.install_binary(&artifact, fs)
+    }
+
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
+        Kind::Terragrunt(self)
+    }
+
+    fn name(&self) -> &str {
+        TERRAGRUNT_SOFT_NAME
+    }
+
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>> {
+        Some(Box::new(Self::index()))
+    }
+
     fn version(&self) -> &str {
         &self.version
     }
@@ -97,7 +213,9 @@ mod terraform_test {
         #[test]
         fn should_return_soft() {
             let version = "1.2.3";
-            let soft = Terraform::new(version.into());
+            let soft = Terraform::new(version.into(), None, &DownloadConfig::default());
+            assert_eq!(soft.bin_names(), [TF_BIN_NAME]);
+            assert!(soft.release_index().is_some());
             assert_eq!(soft.name(), TF_SOFT_NAME);
             assert_eq!(soft.version(), version);
             match soft.kind() {
@@ -105,6 +223,16 @@ mod terraform_test {
                 _ => panic!(),
             }
         }
+
+        #[test]
+        fn should_return_soft_with_bin_name() {
+            let soft = Terraform::new(
+                "0.13.7".into(),
+                Some("tf13".into()),
+                &DownloadConfig::default(),
+            );
+            assert_eq!(soft.bin_names(), ["tf13"]);
+        }
     }
 
     mod install {
@@ -144,12 +272,18 @@ mod terraform_test {
                     assert_eq!(project_dirpath, expected_project_dirpath);
                     Ok(env_dirpath.to_path_buf())
                 });
-                stubs.installer.stub_install_zip_fn(move |artifact, _| {
+                stubs.installer.stub_install_archive_fn(move |artifact, _| {
                     let expected_artifact = Artifact {
                         bin_filepaths: vec![Path::new(TF_BIN_NAME)],
+                        checksums_url: Some(format!(
+                            "https://releases.hashicorp.com/terraform/{}/terraform_{}_SHA256SUMS",
+                            version, version,
+                        )),
                         name: TF_SOFT_NAME,
+                        strip_components: 0,
                         symlinks: vec![Symlink {
                             dest: env_dirpath.join(TF_BIN_NAME),
+                            kind: SymlinkKind::File,
                             required: true,
                             src: Path::new(TF_BIN_NAME),
                         }],
@@ -158,7 +292,7 @@ mod terraform_test {
                             version,
                             version,
                             Terraform::os().unwrap(),
-                            Terraform::arch().unwrap(),
+                            upstream_arch(TF_ARCHS).unwrap(),
                         ),
                         version,
                     };
@@ -183,12 +317,12 @@ mod terraform_test {
         }
 
         #[test]
-        fn should_return_err_if_install_zip_failed() {
+        fn should_return_err_if_install_archive_failed() {
             let data = Data::default();
             let mut stubs = Stubs::new(&data);
             stubs
                 .installer
-                .stub_install_zip_fn(|_, _| Err(Error::UnsupportedSystem));
+                .stub_install_archive_fn(|_, _| Err(Error::UnsupportedSystem));
             test(&data, stubs, |res| match res.unwrap_err() {
                 Error::UnsupportedSystem => {}
                 err => panic!("{}", err),
@@ -207,6 +341,146 @@ mod terraform_test {
         #[inline]
         fn test<F: Fn(Result<()>)>(data: &Data, stubs: Stubs, assert_fn: F) {
             let soft = Terraform {
+                bin_name: TF_BIN_NAME.into(),
+                installer: Box::new(stubs.installer),
+                version: data.version.into(),
+            };
+            let res = soft.install(data.project_dirpath, &stubs.fs);
+            assert_fn(res);
+        }
+    }
+}
+
+#[cfg(test)]
+mod terragrunt_test {
+    use super::*;
+    use crate::{fs::StubFileSystem, soft::installer::StubArchiveArtifactInstaller};
+    use std::io;
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_soft() {
+            let version = "0.53.2";
+            let soft = Terragrunt::new(version.into(), None, &DownloadConfig::default());
+            assert_eq!(soft.bin_names(), [TERRAGRUNT_BIN_NAME]);
+            assert!(soft.release_index().is_some());
+            assert_eq!(soft.name(), TERRAGRUNT_SOFT_NAME);
+            assert_eq!(soft.version(), version);
+            match soft.kind() {
+                Kind::Terragrunt(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod install {
+        use super::*;
+
+        struct Data {
+            env_dirpath: &'static Path,
+            project_dirpath: &'static Path,
+            version: &'static str,
+        }
+
+        impl Default for Data {
+            fn default() -> Self {
+                Self {
+                    env_dirpath: Path::new("/env"),
+                    project_dirpath: Path::new("/project"),
+                    version: "0.53.2",
+                }
+            }
+        }
+
+        struct Stubs {
+            installer: StubArchiveArtifactInstaller,
+            fs: StubFileSystem,
+        }
+
+        impl Stubs {
+            fn new(data: &Data) -> Self {
+                let env_dirpath = data.env_dirpath;
+                let expected_project_dirpath = data.project_dirpath;
+                let version = data.version;
+                let mut stubs = Self {
+                    installer: StubArchiveArtifactInstaller::default(),
+                    fs: StubFileSystem::default(),
+                };
+                stubs.fs.stub_ensure_env_dir_fn(move |project_dirpath| {
+                    assert_eq!(project_dirpath, expected_project_dirpath);
+                    Ok(env_dirpath.to_path_buf())
+                });
+                stubs.installer.stub_install_binary_fn(move |artifact, _| {
+                    let expected_artifact = Artifact {
+                        bin_filepaths: vec![Path::new(TERRAGRUNT_BIN_NAME)],
+                        checksums_url: Some(format!(
+                            "https://github.com/gruntwork-io/terragrunt/releases/download/v{}/SHA256SUMS",
+                            version,
+                        )),
+                        name: TERRAGRUNT_SOFT_NAME,
+                        strip_components: 0,
+                        symlinks: vec![Symlink {
+                            dest: env_dirpath.join(TERRAGRUNT_BIN_NAME),
+                            kind: SymlinkKind::File,
+                            required: true,
+                            src: Path::new(TERRAGRUNT_BIN_NAME),
+                        }],
+                        url: format!(
+                            "https://github.com/gruntwork-io/terragrunt/releases/download/v{}/terragrunt_{}_{}",
+                            version,
+                            Terragrunt::os().unwrap(),
+                            upstream_arch(TERRAGRUNT_ARCHS).unwrap(),
+                        ),
+                        version,
+                    };
+                    assert_eq!(*artifact, expected_artifact);
+                    Ok(())
+                });
+                stubs
+            }
+        }
+
+        #[test]
+        fn should_return_io_err_if_ensure_env_dir_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .fs
+                .stub_ensure_env_dir_fn(|_| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::Io(_) => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_err_if_install_binary_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .installer
+                .stub_install_binary_fn(|_, _| Err(Error::UnsupportedSystem));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::UnsupportedSystem => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_ok() {
+            let data = Data::default();
+            let stubs = Stubs::new(&data);
+            test(&data, stubs, |res| {
+                res.unwrap();
+            });
+        }
+
+        #[inline]
+        fn test<F: Fn(Result<()>)>(data: &Data, stubs: Stubs, assert_fn: F) {
+            let soft = Terragrunt {
+                bin_name: TERRAGRUNT_BIN_NAME.into(),
                 installer: Box::new(stubs.installer),
                 version: data.version.into(),
             };
@@ -0,0 +1,266 @@
+// IMPORTS
+
+#[cfg(test)]
+use super::Kind;
+use super::{
+    installer::{ArchiveArtifactInstaller, Artifact, DefaultArchiveArtifactInstaller, Symlink},
+    upstream_arch, ArchTable, Error, Result, Software,
+};
+use crate::{
+    fs::{FileSystem, SymlinkKind},
+    net::DownloadConfig,
+    release::{GithubReleases, ReleaseIndex},
+};
+use std::{env, path::Path};
+
+// CONSTS
+
+pub const TINYGO_ARCHS: ArchTable = &[("x86_64", "amd64"), ("arm", "arm"), ("aarch64", "arm64")];
+pub const TINYGO_BIN_NAME: &str = "tinygo";
+pub const TINYGO_GITHUB_REPO: &str = "tinygo-org/tinygo";
+pub const TINYGO_ROOT_VAR_NAME: &str = "TINYGOROOT";
+pub const TINYGO_SOFT_NAME: &str = "tinygo";
+pub const TINYGO_VERSION_ARGS: &[&str] = &["version"];
+
+const TINYGO_ASSET: &str = "tinygo{version}.{os}-{arch}.tar.gz";
+const TINYGO_BIN_FILEPATH: &str = "bin/tinygo";
+
+// STRUCTS
+
+// The lib, src and targets directories are looked up from the binary: TINYGOROOT points to them so
+// they are found whatever the way tinygo is invoked (symlink, shim...).
+pub struct TinyGo {
+    bin_name: String,
+    installer: Box<dyn ArchiveArtifactInstaller>,
+    version: String,
+}
+
+impl TinyGo {
+    pub fn new(version: String, bin_name: Option<String>, download_cfg: &DownloadConfig) -> Self {
+        Self {
+            bin_name: bin_name.unwrap_or_else(|| TINYGO_BIN_NAME.into()),
+            installer: Box::new(DefaultArchiveArtifactInstaller::new(download_cfg)),
+            version,
+        }
+    }
+
+    #[inline]
+    fn index() -> GithubReleases {
+        GithubReleases::new(TINYGO_GITHUB_REPO, TINYGO_ASSET, None)
+    }
+
+    #[inline]
+    fn os() -> Result<&'static str> {
+        match env::consts::OS {
+            "macos" => Ok("darwin"),
+            "linux" => Ok("linux"),
+            _ => Err(Error::UnsupportedSystem),
+        }
+    }
+}
+
+impl Software for TinyGo {
+    fn bin_names(&self) -> Vec<String> {
+        vec![self.bin_name.clone()]
+    }
+
+    fn env_vars(&self, install_dirpath: &Path, _env_dirpath: &Path) -> Vec<(String, String)> {
+        vec![(
+            TINYGO_ROOT_VAR_NAME.into(),
+            install_dirpath.display().to_string(),
+        )]
+    }
+
+    fn install(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
+        let os = Self::os()?;
+        let arch = upstream_arch(TINYGO_ARCHS)?;
+        let env_dirpath = fs.ensure_env_dir(project_dirpath).map_err(Error::Io)?;
+        let index = Self::index();
+        let artifact = Artifact {
+            bin_filepaths: vec![Path::new(TINYGO_BIN_FILEPATH)],
+            checksums_url: index.checksums_url(&self.version),
+            name: TINYGO_SOFT_NAME,
+            strip_components: 1,
+            symlinks: vec![Symlink {
+                dest: env_dirpath.join(&self.bin_name),
+                kind: SymlinkKind::File,
+                required: true,
+                src: Path::new(TINYGO_BIN_FILEPATH),
+            }],
+            url: index.artifact_url(&self.version, os, arch),
+            version: &self.version,
+        };
+        self.installer.install_archive(&artifact, fs)
+    }
+
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
+        Kind::TinyGo(self)
+    }
+
+    fn name(&self) -> &str {
+        TINYGO_SOFT_NAME
+    }
+
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>> {
+        Some(Box::new(Self::index()))
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+// TESTS
+
+#[cfg(test)]
+mod tiny_go_test {
+    use super::*;
+    use crate::{fs::StubFileSystem, soft::installer::StubArchiveArtifactInstaller};
+    use std::io;
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_soft() {
+            let version = "0.30.0";
+            let soft = TinyGo::new(version.into(), None, &DownloadConfig::default());
+            assert_eq!(soft.bin_names(), [TINYGO_BIN_NAME]);
+            assert!(soft.release_index().is_some());
+            assert_eq!(soft.name(), TINYGO_SOFT_NAME);
+            assert_eq!(soft.version(), version);
+            match soft.kind() {
+                Kind::TinyGo(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod env_vars {
+        use super::*;
+
+        #[test]
+        fn should_return_root() {
+            let soft = TinyGo::new("0.30.0".into(), None, &DownloadConfig::default());
+            let vars = soft.env_vars(Path::new("/softwares/tinygo/0.30.0"), Path::new("/env"));
+            let expected = vec![(
+                TINYGO_ROOT_VAR_NAME.into(),
+                "/softwares/tinygo/0.30.0".into(),
+            )];
+            assert_eq!(vars, expected);
+        }
+    }
+
+    mod install {
+        use super::*;
+
+        struct Data {
+            env_dirpath: &'static Path,
+            project_dirpath: &'static Path,
+            version: &'static str,
+        }
+
+        impl Default for Data {
+            fn default() -> Self {
+                Self {
+                    env_dirpath: Path::new("/env"),
+                    project_dirpath: Path::new("/project"),
+                    version: "0.30.0",
+                }
+            }
+        }
+
+        struct Stubs {
+            installer: StubArchiveArtifactInstaller,
+            fs: StubFileSystem,
+        }
+
+        impl Stubs {
+            fn new(data: &Data) -> Self {
+                let env_dirpath = data.env_dirpath;
+                let expected_project_dirpath = data.project_dirpath;
+                let version = data.version;
+                let mut stubs = Self {
+                    installer: StubArchiveArtifactInstaller::default(),
+                    fs: StubFileSystem::default(),
+                };
+                stubs.fs.stub_ensure_env_dir_fn(move |project_dirpath| {
+                    assert_eq!(project_dirpath, expected_project_dirpath);
+                    Ok(env_dirpath.to_path_buf())
+                });
+                stubs.installer.stub_install_archive_fn(move |artifact, _| {
+                    let expected_artifact = Artifact {
+                        bin_filepaths: vec![Path::new(TINYGO_BIN_FILEPATH)],
+                        checksums_url: None,
+                        name: TINYGO_SOFT_NAME,
+                        strip_components: 1,
+                        symlinks: vec![Symlink {
+                            dest: env_dirpath.join(TINYGO_BIN_NAME),
+                            kind: SymlinkKind::File,
+                            required: true,
+                            src: Path::new(TINYGO_BIN_FILEPATH),
+                        }],
+                        url: format!(
+                            "https://github.com/tinygo-org/tinygo/releases/download/v{}/tinygo{}.{}-{}.tar.gz",
+                            version,
+                            version,
+                            TinyGo::os().unwrap(),
+                            upstream_arch(TINYGO_ARCHS).unwrap(),
+                        ),
+                        version,
+                    };
+                    assert_eq!(*artifact, expected_artifact);
+                    Ok(())
+                });
+                stubs
+            }
+        }
+
+        #[test]
+        fn should_return_io_err_if_ensure_env_dir_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .fs
+                .stub_ensure_env_dir_fn(|_| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::Io(_) => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_err_if_install_archive_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .installer
+                .stub_install_archive_fn(|_, _| Err(Error::UnsupportedSystem));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::UnsupportedSystem => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_ok() {
+            let data = Data::default();
+            let stubs = Stubs::new(&data);
+            test(&data, stubs, |res| {
+                res.unwrap();
+            });
+        }
+
+        #[inline]
+        fn test<F: Fn(Result<()>)>(data: &Data, stubs: Stubs, assert_fn: F) {
+            let soft = TinyGo {
+                bin_name: TINYGO_BIN_NAME.into(),
+                installer: Box::new(stubs.installer),
+                version: data.version.into(),
+            };
+            let res = soft.install(data.project_dirpath, &stubs.fs);
+            assert_fn(res);
+        }
+    }
+}
@@ -0,0 +1,255 @@
+// IMPORTS
+
+#[cfg(test)]
+use super::Kind;
+use super::{
+    installer::{ArchiveArtifactInstaller, Artifact, DefaultArchiveArtifactInstaller, Symlink},
+    upstream_arch, ArchTable, Error, Result, Software,
+};
+use crate::{
+    fs::{FileSystem, SymlinkKind},
+    net::DownloadConfig,
+    release::{ReleaseIndex, ZigReleases},
+};
+use std::{env, path::Path};
+
+// CONSTS
+
+pub const ZIG_ARCHS: ArchTable = &[("x86_64", "x86_64"), ("aarch64", "aarch64")];
+pub const ZIG_BIN_NAME: &str = "zig";
+pub const ZIG_SOFT_NAME: &str = "zig";
+pub const ZIG_VERSION_ARGS: &[&str] = &["version"];
+
+const ZIG_LIB_DIRNAME: &str = "lib";
+
+// STRUCTS
+
+// The lib directory is looked up next to the binary: it is linked next to the link of the binary
+// so it is found whatever the way zig is invoked (symlink, shim...).
+pub struct Zig {
+    bin_name: String,
+    installer: Box<dyn ArchiveArtifactInstaller>,
+    version: String,
+}
+
+impl Zig {
+    pub fn new(version: String, bin_name: Option<String>, download_cfg: &DownloadConfig) -> Self {
+        Self {
+            bin_name: bin_name.unwrap_or_else(|| ZIG_BIN_NAME.into()),
+            installer: Box::new(DefaultArchiveArtifactInstaller::new(download_cfg)),
+            version,
+        }
+    }
+
+    #[inline]
+    fn os() -> Result<&'static str> {
+        match env::consts::OS {
+            "macos" => Ok("macos"),
+            "linux" => Ok("linux"),
+            _ => Err(Error::UnsupportedSystem),
+        }
+    }
+}
+
+impl Software for Zig {
+    fn bin_names(&self) -> Vec<String> {
+        vec![self.bin_name.clone()]
+    }
+
+    fn env_vars(&self, _install_dirpath: &Path, _env_dirpath: &Path) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    fn install(&self, project_dirpath: &Path, fs: &dyn FileSystem) -> Result<()> {
+        let os = Self::os()?;
+        let arch = upstream_arch(ZIG_ARCHS)?;
+        let env_dirpath = fs.ensure_env_dir(project_dirpath).map_err(Error::Io)?;
+        let artifact = Artifact {
+            bin_filepaths: vec![Path::new(ZIG_BIN_NAME)],
+            checksums_url: ZigReleases.checksums_url(&self.version),
+            name: ZIG_SOFT_NAME,
+            strip_components: 1,
+            symlinks: vec![
+                Symlink {
+                    dest: env_dirpath.join(&self.bin_name),
+                    kind: SymlinkKind::File,
+                    required: true,
+                    src: Path::new(ZIG_BIN_NAME),
+                },
+                Symlink {
+                    dest: env_dirpath.join(ZIG_LIB_DIRNAME),
+                    kind: SymlinkKind::Dir,
+                    required: true,
+                    src: Path::new(ZIG_LIB_DIRNAME),
+                },
+            ],
+            url: ZigReleases.artifact_url(&self.version, os, arch),
+            version: &self.version,
+        };
+        self.installer.install_archive(&artifact, fs)
+    }
+
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
+        Kind::Zig(self)
+    }
+
+    fn name(&self) -> &str {
+        ZIG_SOFT_NAME
+    }
+
+    fn release_index(&self) -> Option<Box<dyn ReleaseIndex>> {
+        Some(Box::new(ZigReleases))
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+// TESTS
+
+#[cfg(test)]
+mod zig_test {
+    use super::*;
+    use crate::{fs::StubFileSystem, soft::installer::StubArchiveArtifactInstaller};
+    use std::io;
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_soft() {
+            let version = "0.11.0";
+            let soft = Zig::new(version.into(), None, &DownloadConfig::default());
+            assert_eq!(soft.bin_names(), [ZIG_BIN_NAME]);
+            assert!(soft.release_index().is_some());
+            assert_eq!(soft.name(), ZIG_SOFT_NAME);
+            assert_eq!(soft.version(), version);
+            match soft.kind() {
+                Kind::Zig(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod install {
+        use super::*;
+
+        struct Data {
+            env_dirpath: &'static Path,
+            project_dirpath: &'static Path,
+            version: &'static str,
+        }
+
+        impl Default for Data {
+            fn default() -> Self {
+                Self {
+                    env_dirpath: Path::new("/env"),
+                    project_dirpath: Path::new("/project"),
+                    version: "0.11.0",
+                }
+            }
+        }
+
+        struct Stubs {
+            installer: StubArchiveArtifactInstaller,
+            fs: StubFileSystem,
+        }
+
+        impl Stubs {
+            fn new(data: &Data) -> Self {
+                let env_dirpath = data.env_dirpath;
+                let expected_project_dirpath = data.project_dirpath;
+                let version = data.version;
+                let mut stubs = Self {
+                    installer: StubArchiveArtifactInstaller::default(),
+                    fs: StubFileSystem::default(),
+                };
+                stubs.fs.stub_ensure_env_dir_fn(move |project_dirpath| {
+                    assert_eq!(project_dirpath, expected_project_dirpath);
+                    Ok(env_dirpath.to_path_buf())
+                });
+                stubs.installer.stub_install_archive_fn(move |artifact, _| {
+                    let expected_artifact = Artifact {
+                        bin_filepaths: vec![Path::new(ZIG_BIN_NAME)],
+                        checksums_url: None,
+                        name: ZIG_SOFT_NAME,
+                        strip_components: 1,
+                        symlinks: vec![
+                            Symlink {
+                                dest: env_dirpath.join(ZIG_BIN_NAME),
+                                kind: SymlinkKind::File,
+                                required: true,
+                                src: Path::new(ZIG_BIN_NAME),
+                            },
+                            Symlink {
+                                dest: env_dirpath.join(ZIG_LIB_DIRNAME),
+                                kind: SymlinkKind::Dir,
+                                required: true,
+                                src: Path::new(ZIG_LIB_DIRNAME),
+                            },
+                        ],
+                        url: format!(
+                            "https://ziglang.org/download/{}/zig-{}-{}-{}.tar.xz",
+                            version,
+                            Zig::os().unwrap(),
+                            upstream_arch(ZIG_ARCHS).unwrap(),
+                            version,
+                        ),
+                        version,
+                    };
+                    assert_eq!(*artifact, expected_artifact);
+                    Ok(())
+                });
+                stubs
+            }
+        }
+
+        #[test]
+        fn should_return_io_err_if_ensure_env_dir_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .fs
+                .stub_ensure_env_dir_fn(|_| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::Io(_) => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_err_if_install_archive_failed() {
+            let data = Data::default();
+            let mut stubs = Stubs::new(&data);
+            stubs
+                .installer
+                .stub_install_archive_fn(|_, _| Err(Error::UnsupportedSystem));
+            test(&data, stubs, |res| match res.unwrap_err() {
+                Error::UnsupportedSystem => {}
+                err => panic!("{}", err),
+            });
+        }
+
+        #[test]
+        fn should_return_ok() {
+            let data = Data::default();
+            let stubs = Stubs::new(&data);
+            test(&data, stubs, |res| {
+                res.unwrap();
+            });
+        }
+
+        #[inline]
+        fn test<F: Fn(Result<()>)>(data: &Data, stubs: Stubs, assert_fn: F) {
+            let soft = Zig {
+                bin_name: ZIG_BIN_NAME.into(),
+                installer: Box::new(stubs.installer),
+                version: data.version.into(),
+            };
+            let res = soft.install(data.project_dirpath, &stubs.fs);
+            assert_fn(res);
+        }
+    }
+}
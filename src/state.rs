@@ -0,0 +1,177 @@
+// IMPORTS
+
+use crate::fs::FileSystem;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, Error, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+// CONSTS
+
+pub const STATE_FILENAME: &str = "state.json";
+
+// DATA STRUCTS
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SoftwareState {
+    pub name: String,
+    #[serde(default)]
+    pub vars: Vec<VarState>,
+    pub version: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct State {
+    pub cfg_filepath: PathBuf,
+    pub cfg_hash: String,
+    pub created_at: u64,
+    #[serde(default)]
+    pub denv_version: String,
+    pub id: String,
+    pub last_loaded_at: u64,
+    // The configuration was loaded with --lenient despite errors: it must be validated again.
+    #[serde(default)]
+    pub lenient: bool,
+    #[serde(default)]
+    pub path_prefix: String,
+    #[serde(default)]
+    pub path_suffix: String,
+    pub project_dirpath: PathBuf,
+    pub softwares: Vec<SoftwareState>,
+    #[serde(default)]
+    pub unset_vars: Vec<VarState>,
+    pub vars: Vec<VarState>,
+    #[serde(default)]
+    pub vault_agent: Option<VaultAgentState>,
+}
+
+impl State {
+    pub fn load(env_dirpath: &Path, fs: &dyn FileSystem) -> io::Result<Option<Self>> {
+        let path = env_dirpath.join(STATE_FILENAME);
+        if !fs.file_exists(&path) {
+            return Ok(None);
+        }
+        let json = fs.read_to_string(&path)?;
+        serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, env_dirpath: &Path, fs: &dyn FileSystem) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(Error::other)?;
+        fs.write(&env_dirpath.join(STATE_FILENAME), &json)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct VarState {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct VaultAgentState {
+    pub cfg_hash: String,
+    pub pid: u32,
+}
+
+// TESTS
+
+#[cfg(test)]
+pub fn state(project_dirpath: &Path, cfg_filepath: &Path) -> State {
+    State {
+        cfg_filepath: cfg_filepath.to_path_buf(),
+        cfg_hash: "hash".into(),
+        created_at: 1,
+        denv_version: "0.1.0".into(),
+        id: "id".into(),
+        last_loaded_at: 2,
+        lenient: false,
+        path_prefix: "/env".into(),
+        path_suffix: String::new(),
+        project_dirpath: project_dirpath.to_path_buf(),
+        softwares: vec![SoftwareState {
+            name: "soft".into(),
+            vars: vec![],
+            version: "1.0.0".into(),
+        }],
+        unset_vars: vec![],
+        vars: vec![VarState {
+            name: "VAR".into(),
+            value: "value".into(),
+        }],
+        vault_agent: None,
+    }
+}
+
+#[cfg(test)]
+mod state_test {
+    use super::*;
+    use crate::fs::StubFileSystem;
+
+    mod load {
+        use super::*;
+
+        #[test]
+        fn should_return_none() {
+            let mut fs = StubFileSystem::default();
+            fs.stub_file_exists_fn(|path| {
+                assert_eq!(path, Path::new("/env").join(STATE_FILENAME));
+                false
+            });
+            let state = State::load(Path::new("/env"), &fs).unwrap();
+            assert!(state.is_none());
+        }
+
+        #[test]
+        fn should_return_io_err_if_read_failed() {
+            let mut fs = StubFileSystem::default();
+            fs.stub_file_exists_fn(|_| true);
+            fs.stub_read_to_string_fn(|_| Err(io::Error::from(ErrorKind::PermissionDenied)));
+            let err = State::load(Path::new("/env"), &fs).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        }
+
+        #[test]
+        fn should_return_io_err_if_json_is_invalid() {
+            let mut fs = StubFileSystem::default();
+            fs.stub_file_exists_fn(|_| true);
+            fs.stub_read_to_string_fn(|_| Ok("{".into()));
+            let err = State::load(Path::new("/env"), &fs).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData);
+        }
+
+        #[test]
+        fn should_return_state() {
+            let expected_state = state(Path::new("/project"), Path::new("/project/denv.yml"));
+            let json = serde_json::to_string(&expected_state).unwrap();
+            let mut fs = StubFileSystem::default();
+            fs.stub_file_exists_fn(|_| true);
+            fs.stub_read_to_string_fn(move |path| {
+                assert_eq!(path, Path::new("/env").join(STATE_FILENAME));
+                Ok(json.clone())
+            });
+            let state = State::load(Path::new("/env"), &fs).unwrap().unwrap();
+            assert_eq!(state, expected_state);
+        }
+    }
+
+    mod save {
+        use super::*;
+
+        #[test]
+        fn should_write_state() {
+            let expected_state = state(Path::new("/project"), Path::new("/project/denv.yml"));
+            let state = expected_state.clone();
+            let mut fs = StubFileSystem::default();
+            fs.stub_write_fn(move |path, content| {
+                assert_eq!(path, Path::new("/env").join(STATE_FILENAME));
+                let state: State = serde_json::from_str(content).unwrap();
+                assert_eq!(state, expected_state);
+                Ok(())
+            });
+            state.save(Path::new("/env"), &fs).unwrap();
+        }
+    }
+}
@@ -0,0 +1,194 @@
+// IMPORTS
+
+use crate::fs::FileSystem;
+use log::info;
+use std::{
+    fmt::{self, Display, Formatter},
+    io,
+};
+
+// CONSTS
+
+pub const STORE_VERSION: u32 = 1;
+
+const MIGRATIONS: [Migration; STORE_VERSION as usize] = [migrate_to_v1];
+
+// TYPES
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+type Migration = fn(&dyn FileSystem) -> io::Result<()>;
+
+// ENUMS
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    UnsupportedVersion(u32),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "Store version {} is newer than the supported one ({}), please upgrade D-Env",
+                version, STORE_VERSION
+            ),
+        }
+    }
+}
+
+// FUNCTIONS
+
+pub fn check_version(fs: &dyn FileSystem) -> Result<u32> {
+    let version = fs.store_version().map_err(Error::Io)?.unwrap_or_default();
+    if version > STORE_VERSION {
+        Err(Error::UnsupportedVersion(version))
+    } else {
+        Ok(version)
+    }
+}
+
+pub fn migrate(fs: &dyn FileSystem) -> Result<Vec<u32>> {
+    let version = check_version(fs)?;
+    let mut applied = vec![];
+    for (idx, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let next_version = idx as u32 + 1;
+        info!("Migrating store to version {}", next_version);
+        migration(fs).map_err(Error::Io)?;
+        fs.set_store_version(next_version).map_err(Error::Io)?;
+        applied.push(next_version);
+    }
+    Ok(applied)
+}
+
+#[inline]
+fn migrate_to_v1(fs: &dyn FileSystem) -> io::Result<()> {
+    fs.migrate_legacy_layout()
+}
+
+// TESTS
+
+#[cfg(test)]
+mod error_test {
+    use super::*;
+
+    mod to_string {
+        use super::*;
+
+        mod io {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let err = ::std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+                let str = err.to_string();
+                let err = Error::Io(err);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod unsupported_version {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = format!(
+                    "Store version 42 is newer than the supported one ({}), please upgrade D-Env",
+                    STORE_VERSION
+                );
+                let err = Error::UnsupportedVersion(42);
+                assert_eq!(err.to_string(), str);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod check_version_test {
+    use super::*;
+    use crate::fs::StubFileSystem;
+
+    #[test]
+    fn should_return_io_err() {
+        let mut fs = StubFileSystem::default();
+        fs.stub_store_version_fn(|| Err(io::Error::from(io::ErrorKind::InvalidData)));
+        match check_version(&fs).unwrap_err() {
+            Error::Io(_) => {}
+            err => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn should_return_unsupported_version_err() {
+        let mut fs = StubFileSystem::default();
+        fs.stub_store_version_fn(|| Ok(Some(42)));
+        match check_version(&fs).unwrap_err() {
+            Error::UnsupportedVersion(42) => {}
+            err => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn should_return_0_if_store_is_not_versioned() {
+        let mut fs = StubFileSystem::default();
+        fs.stub_store_version_fn(|| Ok(None));
+        assert_eq!(check_version(&fs).unwrap(), 0);
+    }
+
+    #[test]
+    fn should_return_version() {
+        let mut fs = StubFileSystem::default();
+        fs.stub_store_version_fn(|| Ok(Some(1)));
+        assert_eq!(check_version(&fs).unwrap(), 1);
+    }
+}
+
+#[cfg(test)]
+mod migrate_test {
+    use super::*;
+    use crate::fs::StubFileSystem;
+
+    #[test]
+    fn should_return_io_err_if_migration_failed() {
+        let mut fs = StubFileSystem::default();
+        fs.stub_store_version_fn(|| Ok(None));
+        fs.stub_migrate_legacy_layout_fn(|| Err(io::Error::from(io::ErrorKind::PermissionDenied)));
+        fs.stub_set_store_version_fn(|_| panic!("version must not be saved"));
+        match migrate(&fs).unwrap_err() {
+            Error::Io(_) => {}
+            err => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn should_return_unsupported_version_err() {
+        let mut fs = StubFileSystem::default();
+        fs.stub_store_version_fn(|| Ok(Some(42)));
+        match migrate(&fs).unwrap_err() {
+            Error::UnsupportedVersion(42) => {}
+            err => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn should_apply_migrations() {
+        let mut fs = StubFileSystem::default();
+        fs.stub_store_version_fn(|| Ok(None));
+        fs.stub_migrate_legacy_layout_fn(|| Ok(()));
+        fs.stub_set_store_version_fn(|version| {
+            assert_eq!(version, 1);
+            Ok(())
+        });
+        assert_eq!(migrate(&fs).unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn should_do_nothing_if_store_is_up_to_date() {
+        let mut fs = StubFileSystem::default();
+        fs.stub_store_version_fn(|| Ok(Some(STORE_VERSION)));
+        assert!(migrate(&fs).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,223 @@
+// IMPORTS
+
+use serde_json::{Map, Value};
+use std::iter;
+
+// CONSTS
+
+const BOLD: &str = "\x1b[1m";
+const COLUMN_SEPARATOR: &str = "  ";
+const GREEN: &str = "\x1b[0;32m";
+const RED: &str = "\x1b[0;31m";
+const RESET: &str = "\x1b[0m";
+const YELLOW: &str = "\x1b[0;33m";
+
+// ENUMS
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Color {
+    Green,
+    Red,
+    Yellow,
+}
+
+impl Color {
+    #[inline]
+    fn code(self) -> &'static str {
+        match self {
+            Self::Green => GREEN,
+            Self::Red => RED,
+            Self::Yellow => YELLOW,
+        }
+    }
+}
+
+// DATA STRUCTS
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cell {
+    pub color: Option<Color>,
+    pub value: String,
+}
+
+impl Cell {
+    pub fn colored(value: impl Into<String>, color: Color) -> Self {
+        Self {
+            color: Some(color),
+            value: value.into(),
+        }
+    }
+}
+
+impl<S: Into<String>> From<S> for Cell {
+    fn from(value: S) -> Self {
+        Self {
+            color: None,
+            value: value.into(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Style {
+    pub color: bool,
+    pub header: bool,
+}
+
+// STRUCTS
+
+pub struct Table {
+    columns: Vec<&'static str>,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    pub fn new(columns: Vec<&'static str>) -> Self {
+        Self {
+            columns,
+            rows: vec![],
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<Cell>) {
+        self.rows.push(row);
+    }
+
+    pub fn render(&self, style: Style) -> String {
+        let mut widths: Vec<usize> = if style.header {
+            self.columns.iter().map(|col| col.chars().count()).collect()
+        } else {
+            vec![0; self.columns.len()]
+        };
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.value.chars().count());
+            }
+        }
+        let mut out = String::new();
+        if style.header {
+            let header: Vec<Cell> = self.columns.iter().map(|col| Cell::from(*col)).collect();
+            Self::render_row(&header, &widths, |_| style.color.then_some(BOLD), &mut out);
+        }
+        for row in &self.rows {
+            let color_fn = |cell: &Cell| cell.color.filter(|_| style.color).map(Color::code);
+            Self::render_row(row, &widths, color_fn, &mut out);
+        }
+        out
+    }
+
+    // Each row is rendered as an object keyed by the lowercase column names.
+    pub fn to_json(&self) -> Value {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let obj: Map<String, Value> = self
+                    .columns
+                    .iter()
+                    .zip(row)
+                    .map(|(col, cell)| (col.to_lowercase(), Value::String(cell.value.clone())))
+                    .collect();
+                Value::Object(obj)
+            })
+            .collect();
+        Value::Array(rows)
+    }
+
+    #[inline]
+    fn render_row<F: Fn(&Cell) -> Option<&'static str>>(
+        row: &[Cell],
+        widths: &[usize],
+        color_fn: F,
+        out: &mut String,
+    ) {
+        let mut line = String::new();
+        for (idx, (cell, width)) in row.iter().zip(widths).enumerate() {
+            if idx > 0 {
+                line.push_str(COLUMN_SEPARATOR);
+            }
+            match color_fn(cell) {
+                Some(color) => {
+                    line.push_str(color);
+                    line.push_str(&cell.value);
+                    line.push_str(RESET);
+                }
+                None => line.push_str(&cell.value),
+            }
+            let padding = width.saturating_sub(cell.value.chars().count());
+            line.extend(iter::repeat_n(' ', padding));
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+}
+
+// TESTS
+
+#[cfg(test)]
+mod table_test {
+    use super::*;
+
+    fn table() -> Table {
+        let mut table = Table::new(vec!["NAME", "VERSION", "SOURCE"]);
+        table.push(vec!["terraform".into(), "1.2.3".into(), "url".into()]);
+        table.push(vec![
+            "kubectl".into(),
+            "1.25.0".into(),
+            Cell::colored("no receipt", Color::Yellow),
+        ]);
+        table
+    }
+
+    mod render {
+        use super::*;
+
+        #[test]
+        fn should_return_aligned_rows() {
+            let style = Style {
+                color: false,
+                header: true,
+            };
+            let expected = "NAME       VERSION  SOURCE\nterraform  1.2.3    url\nkubectl    1.25.0   no receipt\n";
+            assert_eq!(table().render(style), expected);
+        }
+
+        #[test]
+        fn should_return_colored_rows() {
+            let style = Style {
+                color: true,
+                header: true,
+            };
+            let expected = "\x1b[1mNAME\x1b[0m       \x1b[1mVERSION\x1b[0m  \x1b[1mSOURCE\x1b[0m\nterraform  1.2.3    url\nkubectl    1.25.0   \x1b[0;33mno receipt\x1b[0m\n";
+            assert_eq!(table().render(style), expected);
+        }
+
+        #[test]
+        fn should_return_rows_without_header() {
+            let style = Style::default();
+            let expected = "terraform  1.2.3   url\nkubectl    1.25.0  no receipt\n";
+            assert_eq!(table().render(style), expected);
+        }
+    }
+
+    mod to_json {
+        use super::*;
+
+        #[test]
+        fn should_return_objects() {
+            let expected = serde_json::json!([
+                {
+                    "name": "terraform",
+                    "source": "url",
+                    "version": "1.2.3",
+                },
+                {
+                    "name": "kubectl",
+                    "source": "no receipt",
+                    "version": "1.25.0",
+                },
+            ]);
+            assert_eq!(table().to_json(), expected);
+        }
+    }
+}
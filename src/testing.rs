@@ -0,0 +1,162 @@
+// IMPORTS
+
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+// DATA STRUCTS
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Fixture {
+    pub body: Vec<u8>,
+    pub content_type: String,
+    pub status: u16,
+}
+
+impl Fixture {
+    pub fn new(body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            body: body.into(),
+            content_type: "application/octet-stream".into(),
+            status: 200,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Request {
+    pub headers: HashMap<String, String>,
+    pub path: String,
+}
+
+// STRUCTS
+
+// Serves fixtures over HTTP on a random local port, so downloads can be tested offline.
+pub struct FixtureServer {
+    addr: SocketAddr,
+    handle: Option<JoinHandle<()>>,
+    requests: Arc<Mutex<Vec<Request>>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl FixtureServer {
+    pub fn start(fixtures: HashMap<String, Fixture>) -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let requests = Arc::new(Mutex::new(vec![]));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let requests = requests.clone();
+            let stopped = stopped.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if stopped.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        let _ = Self::serve(stream, &fixtures, &requests);
+                    }
+                }
+            })
+        };
+        Ok(Self {
+            addr,
+            handle: Some(handle),
+            requests,
+            stopped,
+        })
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    #[inline]
+    fn serve(
+        stream: TcpStream,
+        fixtures: &HashMap<String, Fixture>,
+        requests: &Mutex<Vec<Request>>,
+    ) -> io::Result<()> {
+        let mut reader = BufReader::new(&stream);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let mut req = Request {
+            headers: HashMap::new(),
+            path: line.split_whitespace().nth(1).unwrap_or_default().into(),
+        };
+        loop {
+            line.clear();
+            reader.read_line(&mut line)?;
+            match line.trim_end().split_once(':') {
+                Some((name, value)) => {
+                    req.headers
+                        .insert(name.to_lowercase(), value.trim().to_string());
+                }
+                None => break,
+            }
+        }
+        let not_found = Fixture {
+            body: vec![],
+            content_type: "text/plain".into(),
+            status: 404,
+        };
+        let fixture = fixtures.get(&req.path).unwrap_or(&not_found);
+        requests.lock().unwrap().push(req);
+        let mut stream = &stream;
+        write!(
+            stream,
+            "HTTP/1.1 {} Fixture\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            fixture.status,
+            fixture.content_type,
+            fixture.body.len()
+        )?;
+        stream.write_all(&fixture.body)?;
+        stream.flush()
+    }
+}
+
+impl Drop for FixtureServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        let _ = TcpStream::connect(self.addr);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+// TESTS
+
+#[cfg(test)]
+mod fixture_server_test {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn should_serve_fixtures() {
+        let fixtures = HashMap::from([("/file".to_string(), Fixture::new("content"))]);
+        let server = FixtureServer::start(fixtures).unwrap();
+        let mut stream = TcpStream::connect(server.addr).unwrap();
+        write!(stream, "GET /file HTTP/1.1\r\nX-Token: secret\r\n\r\n").unwrap();
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).unwrap();
+        assert!(resp.starts_with("HTTP/1.1 200 "));
+        assert!(resp.ends_with("\r\n\r\ncontent"));
+        let req = Request {
+            headers: HashMap::from([("x-token".to_string(), "secret".to_string())]),
+            path: "/file".into(),
+        };
+        assert_eq!(server.requests(), vec![req]);
+    }
+}
@@ -0,0 +1,92 @@
+// IMPORTS
+
+use std::{cmp::Reverse, sync::Mutex, time::Duration};
+
+// TYPES
+
+pub type ElapsedFn = dyn Fn() -> Duration + Send + Sync;
+
+// DATA STRUCTS
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Span {
+    pub duration: Duration,
+    pub label: String,
+}
+
+// STRUCTS
+
+pub struct Timings<'a> {
+    elapsed_fn: &'a (dyn Fn() -> Duration + Send + Sync),
+    spans: Mutex<Vec<Span>>,
+    started_at: Duration,
+}
+
+impl<'a> Timings<'a> {
+    pub fn start(elapsed_fn: &'a (dyn Fn() -> Duration + Send + Sync)) -> Self {
+        Self {
+            elapsed_fn,
+            spans: Mutex::new(vec![]),
+            started_at: elapsed_fn(),
+        }
+    }
+
+    pub fn measure<T, F: FnOnce() -> T>(&self, label: String, f: F) -> T {
+        let started_at = (self.elapsed_fn)();
+        let res = f();
+        let duration = (self.elapsed_fn)().saturating_sub(started_at);
+        self.spans.lock().unwrap().push(Span { duration, label });
+        res
+    }
+
+    // Spans are sorted from the slowest to the fastest.
+    pub fn slowest(&self, count: usize) -> Vec<Span> {
+        let mut spans = self.spans.lock().unwrap().clone();
+        spans.sort_by_key(|span| Reverse(span.duration));
+        spans.truncate(count);
+        spans
+    }
+
+    pub fn total(&self) -> Duration {
+        (self.elapsed_fn)().saturating_sub(self.started_at)
+    }
+}
+
+// TESTS
+
+#[cfg(test)]
+mod timings_test {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    #[test]
+    fn should_return_slowest_spans() {
+        let now = AtomicU64::new(0);
+        let elapsed_fn = || Duration::from_millis(now.load(Ordering::SeqCst));
+        let timings = Timings::start(&elapsed_fn);
+        for (label, millis) in [("config", 10), ("var VAR", 50), ("fs scan", 30)] {
+            timings.measure(label.into(), || {
+                now.fetch_add(millis, Ordering::SeqCst);
+            });
+        }
+        let expected = vec![
+            Span {
+                duration: Duration::from_millis(50),
+                label: "var VAR".into(),
+            },
+            Span {
+                duration: Duration::from_millis(30),
+                label: "fs scan".into(),
+            },
+        ];
+        assert_eq!(timings.slowest(2), expected);
+        assert_eq!(timings.total(), Duration::from_millis(90));
+    }
+
+    #[test]
+    fn should_return_result_of_measured_fn() {
+        let elapsed_fn = || Duration::ZERO;
+        let timings = Timings::start(&elapsed_fn);
+        assert_eq!(timings.measure("config".into(), || 1), 1);
+    }
+}
@@ -0,0 +1,145 @@
+// IMPORTS
+
+use crate::{fs::FileSystem, hash};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+// FUNCTIONS
+
+// Like direnv, a configuration is allowed for its path and its content: once it is modified, it
+// must be allowed again. The file only contains the path, so the other entries of the path can be
+// found.
+pub fn allow(cfg_path: &Path, cfg_hash: &str, fs: &dyn FileSystem) -> io::Result<()> {
+    deny(cfg_path, fs)?;
+    let allow_dirpath = fs.ensure_allow_dir()?;
+    fs.write(
+        &allow_filepath(&allow_dirpath, cfg_path, cfg_hash),
+        &cfg_path.display().to_string(),
+    )
+}
+
+// Every content of the configuration which was allowed is denied. Whether one was allowed is
+// returned.
+pub fn deny(cfg_path: &Path, fs: &dyn FileSystem) -> io::Result<bool> {
+    let allow_dirpath = fs.ensure_allow_dir()?;
+    let mut denied = false;
+    for path in fs.list_files(&allow_dirpath)? {
+        if fs.read_to_string(&path)? == cfg_path.display().to_string() {
+            fs.delete_file(&path)?;
+            denied = true;
+        }
+    }
+    Ok(denied)
+}
+
+pub fn is_allowed(cfg_path: &Path, cfg_hash: &str, fs: &dyn FileSystem) -> io::Result<bool> {
+    let allow_dirpath = fs.ensure_allow_dir()?;
+    Ok(fs.file_exists(&allow_filepath(&allow_dirpath, cfg_path, cfg_hash)))
+}
+
+#[inline]
+fn allow_filepath(allow_dirpath: &Path, cfg_path: &Path, cfg_hash: &str) -> PathBuf {
+    let key = format!("{}\n{}", cfg_path.display(), cfg_hash);
+    allow_dirpath.join(hash::sha256(key.as_bytes()))
+}
+
+// TESTS
+
+#[cfg(test)]
+mod allow_test {
+    use super::*;
+    use crate::fs::StubFileSystem;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn should_replace_previous_entry_of_path() {
+        let deleted = Arc::new(Mutex::new(vec![]));
+        let written = Arc::new(Mutex::new(vec![]));
+        let mut fs = StubFileSystem::default();
+        fs.stub_ensure_allow_dir_fn(|| Ok(PathBuf::from("/allow")));
+        fs.stub_list_files_fn(|_| Ok(vec![PathBuf::from("/allow/old")]));
+        fs.stub_read_to_string_fn(|_| Ok("/project/denv.yml".into()));
+        {
+            let deleted = deleted.clone();
+            fs.stub_delete_file_fn(move |path| {
+                deleted.lock().unwrap().push(path.to_path_buf());
+                Ok(())
+            });
+        }
+        {
+            let written = written.clone();
+            fs.stub_write_fn(move |path, content| {
+                written
+                    .lock()
+                    .unwrap()
+                    .push((path.to_path_buf(), content.to_string()));
+                Ok(())
+            });
+        }
+        allow(Path::new("/project/denv.yml"), "hash", &fs).unwrap();
+        assert_eq!(*deleted.lock().unwrap(), [PathBuf::from("/allow/old")]);
+        let expected_path = Path::new("/allow").join(hash::sha256(b"/project/denv.yml\nhash"));
+        assert_eq!(
+            *written.lock().unwrap(),
+            [(expected_path, "/project/denv.yml".to_string())]
+        );
+    }
+}
+
+#[cfg(test)]
+mod deny_test {
+    use super::*;
+    use crate::fs::StubFileSystem;
+
+    #[test]
+    fn should_return_false() {
+        let mut fs = StubFileSystem::default();
+        fs.stub_ensure_allow_dir_fn(|| Ok(PathBuf::from("/allow")));
+        fs.stub_list_files_fn(|_| Ok(vec![PathBuf::from("/allow/other")]));
+        fs.stub_read_to_string_fn(|_| Ok("/other/denv.yml".into()));
+        assert!(!deny(Path::new("/project/denv.yml"), &fs).unwrap());
+    }
+
+    #[test]
+    fn should_delete_entries_of_path() {
+        let mut fs = StubFileSystem::default();
+        fs.stub_ensure_allow_dir_fn(|| Ok(PathBuf::from("/allow")));
+        fs.stub_list_files_fn(|_| {
+            Ok(vec![
+                PathBuf::from("/allow/entry"),
+                PathBuf::from("/allow/other"),
+            ])
+        });
+        fs.stub_read_to_string_fn(|path| {
+            if path == Path::new("/allow/entry") {
+                Ok("/project/denv.yml".into())
+            } else {
+                Ok("/other/denv.yml".into())
+            }
+        });
+        fs.stub_delete_file_fn(|path| {
+            assert_eq!(path, Path::new("/allow/entry"));
+            Ok(())
+        });
+        assert!(deny(Path::new("/project/denv.yml"), &fs).unwrap());
+    }
+}
+
+#[cfg(test)]
+mod is_allowed_test {
+    use super::*;
+    use crate::fs::StubFileSystem;
+
+    #[test]
+    fn should_return_whether_entry_of_content_exists() {
+        let mut fs = StubFileSystem::default();
+        fs.stub_ensure_allow_dir_fn(|| Ok(PathBuf::from("/allow")));
+        fs.stub_file_exists_fn(|path| {
+            path == Path::new("/allow").join(hash::sha256(b"/project/denv.yml\nhash"))
+        });
+        assert!(is_allowed(Path::new("/project/denv.yml"), "hash", &fs).unwrap());
+        assert!(!is_allowed(Path::new("/project/denv.yml"), "other", &fs).unwrap());
+    }
+}
@@ -0,0 +1,201 @@
+// IMPORTS
+
+#[cfg(test)]
+use super::Kind;
+use super::{Error, Result, Var};
+use std::{fs, path::PathBuf};
+
+// STRUCTS
+
+// Every entry of the file is a variable. The name is the path as written in the configuration.
+pub struct DotenvFile {
+    name: String,
+    path: PathBuf,
+}
+
+impl DotenvFile {
+    pub fn new(name: String, path: PathBuf) -> Self {
+        Self { name, path }
+    }
+
+    #[cfg(test)]
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+}
+
+impl Var for DotenvFile {
+    fn compute_values(&self) -> Result {
+        let content = fs::read_to_string(&self.path).map_err(Error::Io)?;
+        parse(&content).map_err(Error::Dotenv)
+    }
+
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
+        Kind::DotenvFile(self)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+// FUNCTIONS
+
+// Lines are KEY=VALUE, optionally prefixed by export. Single-quoted values are kept as is,
+// double-quoted ones support \n, \t, \" and \\ escapes, and unquoted ones end at a # preceded by
+// a space. Variables are not expanded. The error is the number of the invalid line.
+pub fn parse(content: &str) -> std::result::Result<Vec<(String, String)>, usize> {
+    let mut entries = vec![];
+    for (idx, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (name, value) = line.split_once('=').ok_or(idx + 1)?;
+        let name = name.trim();
+        if !is_valid_name(name) {
+            return Err(idx + 1);
+        }
+        let value = parse_value(value.trim_start()).ok_or(idx + 1)?;
+        entries.push((name.into(), value));
+    }
+    Ok(entries)
+}
+
+#[inline]
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[inline]
+fn is_comment(rest: &str) -> bool {
+    let rest = rest.trim_start();
+    rest.is_empty() || rest.starts_with('#')
+}
+
+#[inline]
+fn parse_value(value: &str) -> Option<String> {
+    if let Some(value) = value.strip_prefix('\'') {
+        let (value, rest) = value.split_once('\'')?;
+        return is_comment(rest).then(|| value.into());
+    }
+    if let Some(value) = value.strip_prefix('"') {
+        let mut unescaped = String::new();
+        let mut chars = value.chars();
+        loop {
+            match chars.next()? {
+                '"' => return is_comment(chars.as_str()).then_some(unescaped),
+                '\\' => match chars.next()? {
+                    'n' => unescaped.push('\n'),
+                    't' => unescaped.push('\t'),
+                    c => unescaped.push(c),
+                },
+                c => unescaped.push(c),
+            }
+        }
+    }
+    let value = match value.find(" #") {
+        Some(idx) => &value[..idx],
+        None => value,
+    };
+    Some(value.trim_end().into())
+}
+
+// TESTS
+
+#[cfg(test)]
+mod dotenv_file_test {
+    use super::*;
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_var() {
+            let var = DotenvFile::new(".env".into(), PathBuf::from("/project/.env"));
+            assert_eq!(var.name(), ".env");
+            assert_eq!(var.path(), PathBuf::from("/project/.env"));
+            match var.kind() {
+                Kind::DotenvFile(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod compute_values {
+        use super::*;
+
+        #[test]
+        fn should_return_io_err() {
+            let dirpath = tempfile::tempdir().unwrap();
+            let var = DotenvFile::new(".env".into(), dirpath.path().join(".env"));
+            match var.compute_values().unwrap_err() {
+                Error::Io(_) => {}
+                err => panic!("{}", err),
+            }
+        }
+
+        #[test]
+        fn should_return_dotenv_err() {
+            let dirpath = tempfile::tempdir().unwrap();
+            let path = dirpath.path().join(".env");
+            fs::write(&path, "FOO=bar\nBAR\n").unwrap();
+            let var = DotenvFile::new(".env".into(), path);
+            match var.compute_values().unwrap_err() {
+                Error::Dotenv(line) => assert_eq!(line, 2),
+                err => panic!("{}", err),
+            }
+        }
+
+        #[test]
+        fn should_return_entries() {
+            let dirpath = tempfile::tempdir().unwrap();
+            let path = dirpath.path().join(".env");
+            fs::write(&path, "FOO=bar\n").unwrap();
+            let var = DotenvFile::new(".env".into(), path);
+            let expected = vec![("FOO".to_string(), "bar".to_string())];
+            assert_eq!(var.compute_values().unwrap(), expected);
+        }
+    }
+}
+
+#[cfg(test)]
+mod parse_test {
+    use super::*;
+
+    #[test]
+    fn should_return_line_of_invalid_entry() {
+        assert_eq!(parse("# Secrets\n\nFOO=bar\n1FOO=bar\n").unwrap_err(), 4);
+        assert_eq!(parse("FOO='bar\n").unwrap_err(), 1);
+        assert_eq!(parse("FOO=\"bar\" baz\n").unwrap_err(), 1);
+    }
+
+    #[test]
+    fn should_return_entries() {
+        let content = r#"# Secrets
+
+export TOKEN=abc123
+EMPTY=
+SPACED = value with spaces # comment
+HASH=a#b
+SINGLE='it "stays" \n'
+DOUBLE="line1\nline2 \"quoted\"" # comment
+"#;
+        let expected: Vec<(String, String)> = [
+            ("TOKEN", "abc123"),
+            ("EMPTY", ""),
+            ("SPACED", "value with spaces"),
+            ("HASH", "a#b"),
+            ("SINGLE", "it \"stays\" \\n"),
+            ("DOUBLE", "line1\nline2 \"quoted\""),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.into(), value.into()))
+        .collect();
+        assert_eq!(parse(content).unwrap(), expected);
+    }
+}
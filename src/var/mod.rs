@@ -1,37 +1,65 @@
 // IMPORTS
 
-use std::fmt::{self, Display, Formatter};
+#[cfg(test)]
+use dotenv::DotenvFile;
+use log::debug;
+use std::{
+    fmt::{self, Display, Formatter},
+    io,
+    process::Command as Process,
+};
 #[cfg(test)]
 use stub_trait::stub;
 
+// MODS
+
+pub mod dotenv;
+
 // TYPES
 
-pub type Result = std::result::Result<String, Error>;
+pub type Result = std::result::Result<Vec<(String, String)>, Error>;
 
 // ENUMS
 
 #[derive(Debug)]
 pub enum Error {
+    Command {
+        cause: io::Error,
+        command: String,
+    },
+    Dotenv(usize),
+    Io(io::Error),
     #[cfg(test)]
     Stub,
 }
 
 impl Display for Error {
-    fn fmt(&self, _f: &mut Formatter<'_>) -> fmt::Result {
-        Ok(())
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Command { cause, command } => write!(f, "`{}` failed: {}", command, cause),
+            Self::Dotenv(line) => write!(f, "Invalid entry at line {}", line),
+            Self::Io(err) => write!(f, "{}", err),
+            #[cfg(test)]
+            Self::Stub => Ok(()),
+        }
     }
 }
 
+#[cfg(test)]
 pub enum Kind<'a> {
+    Command(&'a Command),
+    DotenvFile(&'a DotenvFile),
     Literal(&'a Literal),
 }
 
 // TRAITS
 
+// A variable definition computes the name and the value of each variable it defines.
 #[cfg_attr(test, stub)]
-pub trait Var {
-    fn compute_value(&self) -> Result;
+pub trait Var: Send + Sync {
+    fn compute_values(&self) -> Result;
 
+    #[cfg(test)]
     fn kind(&self) -> Kind<'_>;
 
     fn name(&self) -> &str;
@@ -39,31 +67,85 @@ pub trait Var {
 
 // STRUCTS
 
+// The command is run by sh in the current directory: its trimmed output is the value.
+pub struct Command {
+    command: String,
+    name: String,
+}
+
+impl Command {
+    pub fn new(name: String, command: String) -> Self {
+        Self { command, name }
+    }
+
+    #[cfg(test)]
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    #[inline]
+    fn run(&self) -> io::Result<String> {
+        debug!("Running {}", self.command);
+        let output = Process::new("sh").arg("-c").arg(&self.command).output()?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().into())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stderr = stderr.trim();
+            if stderr.is_empty() {
+                Err(io::Error::other(format!("exited with {}", output.status)))
+            } else {
+                Err(io::Error::other(format!(
+                    "exited with {}: {}",
+                    output.status, stderr
+                )))
+            }
+        }
+    }
+}
+
+impl Var for Command {
+    fn compute_values(&self) -> Result {
+        let value = self.run().map_err(|cause| Error::Command {
+            cause,
+            command: self.command.clone(),
+        })?;
+        Ok(vec![(self.name.clone(), value)])
+    }
+
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
+        Kind::Command(self)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
 pub struct Literal {
     name: String,
-    _value: String,
+    value: String,
 }
 
 impl Literal {
     pub fn new(name: String, value: String) -> Self {
-        Self {
-            name,
-            _value: value,
-        }
+        Self { name, value }
     }
 
     #[cfg(test)]
     pub fn value(&self) -> &str {
-        &self._value
+        &self.value
     }
 }
 
 impl Var for Literal {
-    fn compute_value(&self) -> Result {
-        unimplemented!();
+    fn compute_values(&self) -> Result {
+        Ok(vec![(self.name.clone(), self.value.clone())])
     }
 
-    fn kind(&self) -> Kind {
+    #[cfg(test)]
+    fn kind(&self) -> Kind<'_> {
         Kind::Literal(self)
     }
 
@@ -74,6 +156,96 @@ impl Var for Literal {
 
 // TESTS
 
+#[cfg(test)]
+mod command_test {
+    use super::*;
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn should_return_var() {
+            let name = "var";
+            let command = "git rev-parse --short HEAD";
+            let var = Command::new(name.into(), command.into());
+            assert_eq!(var.name(), name);
+            assert_eq!(var.command(), command);
+            match var.kind() {
+                Kind::Command(_) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    mod compute_values {
+        use super::*;
+
+        #[test]
+        fn should_return_command_err() {
+            let var = Command::new("var".into(), "echo oops >&2; exit 3".into());
+            match var.compute_values().unwrap_err() {
+                Error::Command { cause, command } => {
+                    assert_eq!(cause.to_string(), "exited with exit status: 3: oops");
+                    assert_eq!(command, "echo oops >&2; exit 3");
+                }
+                err => panic!("{}", err),
+            }
+        }
+
+        #[test]
+        fn should_return_trimmed_output() {
+            let var = Command::new("var".into(), "printf '  value\\n\\n'".into());
+            let expected = vec![("var".to_string(), "value".to_string())];
+            assert_eq!(var.compute_values().unwrap(), expected);
+        }
+    }
+}
+
+#[cfg(test)]
+mod error_test {
+    use super::*;
+
+    mod to_string {
+        use super::*;
+
+        mod command {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let cause = std::io::Error::other("exited with exit status: 1");
+                let str = format!("`false` failed: {}", cause);
+                let err = Error::Command {
+                    cause,
+                    command: "false".into(),
+                };
+                assert_eq!(err.to_string(), str);
+            }
+        }
+
+        mod dotenv {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let str = "Invalid entry at line 3";
+                assert_eq!(Error::Dotenv(3).to_string(), str);
+            }
+        }
+
+        mod io {
+            use super::*;
+
+            #[test]
+            fn should_return_str() {
+                let err = std::io::Error::from(std::io::ErrorKind::NotFound);
+                let str = err.to_string();
+                assert_eq!(Error::Io(err).to_string(), str);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod literal_test {
     use super::*;
@@ -90,7 +262,19 @@ mod literal_test {
             assert_eq!(var.value(), value);
             match var.kind() {
                 Kind::Literal(_) => {}
+                _ => panic!(),
             }
         }
     }
+
+    mod compute_values {
+        use super::*;
+
+        #[test]
+        fn should_return_value() {
+            let var = Literal::new("var".into(), "value".into());
+            let expected = vec![("var".to_string(), "value".to_string())];
+            assert_eq!(var.compute_values().unwrap(), expected);
+        }
+    }
 }
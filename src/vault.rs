@@ -0,0 +1,95 @@
+// IMPORTS
+
+use crate::cfg::VaultAgentConfig;
+use serde_json::json;
+use std::path::Path;
+
+// CONSTS
+
+pub const VAULT_AGENT_CFG_FILENAME: &str = ".vault-agent.json";
+pub const VAULT_AGENT_LOG_FILENAME: &str = ".vault-agent.log";
+pub const VAULT_AGENT_SINK_VAR_NAME: &str = "VAULT_AGENT_SINK";
+pub const VAULT_BIN_NAME: &str = "vault";
+pub const VAULT_SINK_FILENAME: &str = ".vault-token";
+
+// FUNCTIONS
+
+pub fn agent_args(cfg_filepath: &Path) -> Vec<String> {
+    vec![
+        "agent".into(),
+        format!("-config={}", cfg_filepath.display()),
+    ]
+}
+
+// The agent authenticates with the configured method and keeps the token written in the sink file
+// renewed as long as it runs.
+pub fn agent_config(cfg: &VaultAgentConfig, sink_filepath: &Path) -> String {
+    let json = json!({
+        "vault": {
+            "address": cfg.address,
+        },
+        "auto_auth": {
+            "method": [{
+                "type": cfg.auth_method,
+                "config": cfg.auth_config,
+            }],
+            "sink": [{
+                "type": "file",
+                "config": {
+                    "path": sink_filepath.display().to_string(),
+                },
+            }],
+        },
+    });
+    serde_json::to_string_pretty(&json).unwrap()
+}
+
+// TESTS
+
+#[cfg(test)]
+mod agent_args_test {
+    use super::*;
+
+    #[test]
+    fn should_return_args() {
+        let args = agent_args(Path::new("/env/.vault-agent.json"));
+        assert_eq!(args, vec!["agent", "-config=/env/.vault-agent.json"]);
+    }
+}
+
+#[cfg(test)]
+mod agent_config_test {
+    use super::*;
+    use serde_json::{Map, Value};
+
+    #[test]
+    fn should_return_json() {
+        let cfg = VaultAgentConfig {
+            address: "https://vault.example.com".into(),
+            auth_config: Map::from_iter([("role".to_string(), Value::from("dev"))]),
+            auth_method: "kubernetes".into(),
+        };
+        let json: Value =
+            serde_json::from_str(&agent_config(&cfg, Path::new("/env/.vault-token"))).unwrap();
+        let expected = json!({
+            "vault": {
+                "address": "https://vault.example.com",
+            },
+            "auto_auth": {
+                "method": [{
+                    "type": "kubernetes",
+                    "config": {
+                        "role": "dev",
+                    },
+                }],
+                "sink": [{
+                    "type": "file",
+                    "config": {
+                        "path": "/env/.vault-token",
+                    },
+                }],
+            },
+        });
+        assert_eq!(json, expected);
+    }
+}
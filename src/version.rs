@@ -0,0 +1,366 @@
+// IMPORTS
+
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+// CONSTS
+
+const OPS: &[(&str, Op)] = &[
+    (">=", Op::Ge),
+    ("<=", Op::Le),
+    (">", Op::Gt),
+    ("<", Op::Lt),
+    ("=", Op::Eq),
+];
+const PESSIMISTIC_OP: &str = "~>";
+
+// ENUMS
+
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum Chunk {
+    Nb(u64),
+    Str(String),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Op {
+    Eq,
+    Ge,
+    Gt,
+    Le,
+    Lt,
+}
+
+// DATA STRUCTS
+
+// Semver-like versions (1.2, v1.6.0-beta1, 17.0.8+7) are compared component by component, a
+// pre-release being older than its release. Other schemes (8u392) are compared naturally: digits as
+// numbers, everything else as text.
+#[derive(Clone, Debug)]
+pub struct Version {
+    build: Vec<Chunk>,
+    chunks: Vec<Chunk>,
+    pre: Vec<Chunk>,
+    raw: String,
+    release: Option<Vec<u64>>,
+}
+
+impl Version {
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    pub fn matches(&self, req: &VersionReq) -> bool {
+        req.clauses.iter().all(|(op, version)| {
+            let ord = self.cmp(version);
+            match op {
+                Op::Eq => ord == Ordering::Equal,
+                Op::Ge => ord != Ordering::Less,
+                Op::Gt => ord == Ordering::Greater,
+                Op::Le => ord != Ordering::Greater,
+                Op::Lt => ord == Ordering::Less,
+            }
+        })
+    }
+
+    #[inline]
+    fn cmp_release(release1: &[u64], release2: &[u64]) -> Ordering {
+        let len = release1.len().max(release2.len());
+        (0..len)
+            .map(|idx| {
+                let nb1 = release1.get(idx).copied().unwrap_or_default();
+                let nb2 = release2.get(idx).copied().unwrap_or_default();
+                nb1.cmp(&nb2)
+            })
+            .find(|ord| *ord != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+impl Eq for Version {}
+
+impl From<&str> for Version {
+    fn from(version: &str) -> Self {
+        let raw = version.trim();
+        let version = raw.strip_prefix('v').unwrap_or(raw);
+        let (version_without_build, build) = version.split_once('+').unwrap_or((version, ""));
+        let (release, pre) = version_without_build
+            .split_once('-')
+            .unwrap_or((version_without_build, ""));
+        let release = parse_release(release);
+        Self {
+            build: chunks(build),
+            chunks: chunks(version),
+            pre: if release.is_some() {
+                chunks(pre)
+            } else {
+                vec![]
+            },
+            raw: raw.into(),
+            release,
+        }
+    }
+}
+
+impl From<String> for Version {
+    fn from(version: String) -> Self {
+        Self::from(version.as_str())
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (&self.release, &other.release) {
+            (Some(release1), Some(release2)) => Self::cmp_release(release1, release2)
+                .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                    (true, false) => Ordering::Greater,
+                    (false, true) => Ordering::Less,
+                    _ => self.pre.cmp(&other.pre),
+                })
+                .then_with(|| self.build.cmp(&other.build)),
+            _ => self.chunks.cmp(&other.chunks),
+        }
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Requirements are comma-separated clauses (e.g. >=0.4, <1), all of them must match. A clause
+// without operator requires an equal version. Like in Terraform, ~> only allows the rightmost
+// component to increase: ~> 1.5 is >=1.5, <2 and ~> 1.5.0 is >=1.5.0, <1.6.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VersionReq {
+    clauses: Vec<(Op, Version)>,
+}
+
+impl VersionReq {
+    // Exact versions (1.5.0) are valid requirements but they are not considered as such.
+    pub fn is_req(version: &str) -> bool {
+        let version = version.trim_start();
+        version.contains(',')
+            || version.starts_with(PESSIMISTIC_OP)
+            || OPS.iter().any(|(op, _)| version.starts_with(op))
+    }
+}
+
+impl FromStr for VersionReq {
+    type Err = ();
+
+    fn from_str(req: &str) -> Result<Self, Self::Err> {
+        let mut clauses = vec![];
+        for clause in req.split(',') {
+            let clause = clause.trim();
+            if let Some(version) = clause.strip_prefix(PESSIMISTIC_OP) {
+                let version = Version::from(version);
+                let mut release = version.release.clone().ok_or(())?;
+                if release.len() > 1 {
+                    release.pop();
+                }
+                if let Some(nb) = release.last_mut() {
+                    *nb += 1;
+                }
+                let upper_bound = release
+                    .iter()
+                    .map(u64::to_string)
+                    .collect::<Vec<_>>()
+                    .join(".");
+                clauses.push((Op::Ge, version));
+                clauses.push((Op::Lt, Version::from(upper_bound)));
+                continue;
+            }
+            let (op, version) = OPS
+                .iter()
+                .find_map(|(prefix, op)| clause.strip_prefix(prefix).map(|version| (*op, version)))
+                .unwrap_or((Op::Eq, clause));
+            let version = Version::from(version);
+            if version.release.is_none() {
+                return Err(());
+            }
+            clauses.push((op, version));
+        }
+        Ok(Self { clauses })
+    }
+}
+
+// FUNCTIONS
+
+#[inline]
+fn chunks(s: &str) -> Vec<Chunk> {
+    let mut chunks = vec![];
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() {
+            let mut nb = c.to_string();
+            while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                nb.push(c);
+            }
+            chunks.push(nb.parse().map(Chunk::Nb).unwrap_or(Chunk::Str(nb)));
+        } else if c.is_ascii_alphabetic() {
+            let mut str = c.to_string();
+            while let Some(c) = chars.next_if(char::is_ascii_alphabetic) {
+                str.push(c);
+            }
+            chunks.push(Chunk::Str(str.to_ascii_lowercase()));
+        }
+    }
+    chunks
+}
+
+#[inline]
+fn parse_release(release: &str) -> Option<Vec<u64>> {
+    release.split('.').map(|nb| nb.parse().ok()).collect()
+}
+
+// TESTS
+
+#[cfg(test)]
+mod version_test {
+    use super::*;
+
+    mod cmp {
+        use super::*;
+
+        #[test]
+        fn should_compare_release() {
+            assert!(Version::from("1.10.0") > Version::from("1.9.3"));
+            assert!(Version::from("0.4") < Version::from("0.4.1"));
+            assert_eq!(Version::from("v1.2"), Version::from("1.2.0"));
+        }
+
+        #[test]
+        fn should_compare_prerelease() {
+            assert!(Version::from("1.6.0-beta1") < Version::from("1.6.0"));
+            assert!(Version::from("1.6.0-beta1") > Version::from("1.5.7"));
+            assert!(Version::from("1.6.0-beta2") < Version::from("1.6.0-beta10"));
+            assert!(Version::from("1.6.0-beta2") < Version::from("1.6.0-rc1"));
+            assert!(Version::from("1.6.0-alpha20230816") < Version::from("1.6.0-beta1"));
+        }
+
+        #[test]
+        fn should_compare_build() {
+            assert!(Version::from("17.0.8+7") < Version::from("17.0.8.1+1"));
+            assert!(Version::from("17.0.8+7") > Version::from("17.0.8+6"));
+        }
+
+        #[test]
+        fn should_compare_naturally_if_version_is_not_semver() {
+            assert!(Version::from("8u392-b08") > Version::from("8u382-b05"));
+            assert!(Version::from("8u392-b08") < Version::from("8u392-b10"));
+        }
+    }
+
+    mod is_prerelease {
+        use super::*;
+
+        #[test]
+        fn should_return_false() {
+            assert!(!Version::from("1.6.0").is_prerelease());
+            assert!(!Version::from("17.0.8+7").is_prerelease());
+            assert!(!Version::from("8u392-b08").is_prerelease());
+        }
+
+        #[test]
+        fn should_return_true() {
+            assert!(Version::from("1.6.0-rc1").is_prerelease());
+        }
+    }
+
+    mod to_string {
+        use super::*;
+
+        #[test]
+        fn should_return_raw_version() {
+            assert_eq!(Version::from(" v1.6.0-beta1 ").to_string(), "v1.6.0-beta1");
+        }
+    }
+}
+
+#[cfg(test)]
+mod version_req_test {
+    use super::*;
+
+    mod from_str {
+        use super::*;
+
+        #[test]
+        fn should_return_err() {
+            assert!(VersionReq::from_str(">=a.b").is_err());
+            assert!(VersionReq::from_str(">=0.4,").is_err());
+            assert!(VersionReq::from_str("~> a").is_err());
+        }
+    }
+
+    mod is_req {
+        use super::*;
+
+        #[test]
+        fn should_return_false() {
+            assert!(!VersionReq::is_req("1.5.0"));
+            assert!(!VersionReq::is_req("17"));
+        }
+
+        #[test]
+        fn should_return_true() {
+            assert!(VersionReq::is_req("~> 1.5"));
+            assert!(VersionReq::is_req(">=1.4, <2"));
+            assert!(VersionReq::is_req("1.4, 1.5"));
+        }
+    }
+
+    mod matches {
+        use super::*;
+
+        #[test]
+        fn should_return_false() {
+            assert!(!matches("0.1.0", ">=0.4"));
+            assert!(!matches("0.4.0", ">0.4"));
+            assert!(!matches("1.0.0", ">=0.4, <1"));
+            assert!(!matches("0.4.1", "0.4"));
+            assert!(!matches("1.0.0-rc1", ">=1"));
+            assert!(!matches("2.0.0", "~> 1.5"));
+            assert!(!matches("1.6.0", "~> 1.5.0"));
+            assert!(!matches("1.4.9", "~> 1.5"));
+        }
+
+        #[test]
+        fn should_return_true() {
+            assert!(matches("0.4.0", ">=0.4"));
+            assert!(matches("0.4.0", "<=0.4"));
+            assert!(matches("0.5.2", ">=0.4, <1"));
+            assert!(matches("0.4.0", "=v0.4"));
+            assert!(matches("1.0.0-rc1", ">0.9"));
+            assert!(matches("1.9.3", "~> 1.5"));
+            assert!(matches("1.5.7", "~>1.5.0"));
+            assert!(matches("1.9.0", "~> 1"));
+            assert!(Version::from("0.1.0").matches(&VersionReq::default()));
+        }
+
+        #[inline]
+        fn matches(version: &str, req: &str) -> bool {
+            Version::from(version).matches(&req.parse().unwrap())
+        }
+    }
+}
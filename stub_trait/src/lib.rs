@@ -39,7 +39,7 @@ pub fn stub(_: TokenStream, input: TokenStream) -> TokenStream {
             let arg_names: Vec<&Pat> = args.iter().map(|arg| arg.pat.as_ref()).collect();
             let return_type = substitute_return_type(&method.sig.output);
             let fn_type = quote! {
-                Fn(#(#arg_types),*) #return_type
+                Fn(#(#arg_types),*) #return_type + Send + Sync
             };
             let attr = quote! {
                 pub #attr_name: Option<Box<dyn #fn_type>>
@@ -0,0 +1,214 @@
+// Softwares are downloaded by the binary: it must be built with an HTTP client.
+#![cfg(any(feature = "reqwest", feature = "ureq"))]
+
+// IMPORTS
+
+use denv::testing::{Fixture, FixtureServer};
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+// CONSTS
+
+const DENV_BIN: &str = env!("CARGO_BIN_EXE_denv");
+const SHELLS_VAR_NAME: &str = "DENV_TEST_SHELLS";
+const TOOL_ASSET_PATH: &str = "/acme/tool/releases/download/v1.0.0/tool_any_any";
+const TOOL_CONTENT: &str = "#!/bin/sh\necho tool 1.0.0\n";
+
+// FUNCTIONS
+
+// The project is trusted like a user would do after reviewing its configuration.
+#[inline]
+fn allow_project(workdir: &Path, project_dirpath: &Path) {
+    let status = Command::new(DENV_BIN)
+        .arg("allow")
+        .current_dir(project_dirpath)
+        .env("DENV_HOME", workdir.join(".denv"))
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+// The tool is released by a fake GitHub repository, downloaded from the fixture server through a
+// mirror.
+#[inline]
+fn write_project(dirpath: &Path, server: &FixtureServer) {
+    fs::create_dir_all(dirpath).unwrap();
+    let cfg = format!(
+        r#"version: v1
+download:
+  mirrors:
+    "https://github.com/": "{}/"
+softwares:
+  custom:
+    tool:
+      version: 1.0.0
+      repo: acme/tool
+      asset: tool_{{os}}_{{arch}}
+      os:
+        linux: any
+        macos: any
+      arch:
+        x86_64: any
+        aarch64: any
+set:
+  - name: FOO
+    value: bar
+"#,
+        server.url("").trim_end_matches('/')
+    );
+    fs::write(dirpath.join("denv.yml"), cfg).unwrap();
+}
+
+// The script is run by an interactive shell, so the hook runs after each command like in a
+// terminal. Lines printed by the script are returned.
+#[inline]
+fn run_shell(shell: &str, workdir: &Path, script: &str) -> Vec<String> {
+    let (name, args): (&str, &[&str]) = if is_zsh(shell) {
+        ("zsh", &["-f", "-i"])
+    } else {
+        ("bash", &["--norc", "--noprofile", "-i"])
+    };
+    let mut child = Command::new(shell)
+        .args(args)
+        .current_dir(workdir)
+        .env_clear()
+        .env("PATH", env::var("PATH").unwrap_or_default())
+        .env("HOME", workdir)
+        .env("DENV_HOME", workdir.join(".denv"))
+        .env("DENV_ALLOW_ROOT", "1")
+        .env("DENV_NO_COLOR", "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(
+            format!(
+                "PS1=''\neval \"$({} hook {})\"\n{}\nexit\n",
+                DENV_BIN, name, script
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}: {}",
+        shell,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+#[inline]
+fn is_zsh(shell: &str) -> bool {
+    Path::new(shell)
+        .file_name()
+        .map(|name| name.to_string_lossy().starts_with("zsh"))
+        .unwrap_or(false)
+}
+
+// The hook is tested with bash by default. CI also runs it with the shells shipped with macOS
+// (bash 3.2 and zsh) by listing their paths, comma-separated, in DENV_TEST_SHELLS.
+#[inline]
+fn shells() -> Vec<String> {
+    env::var(SHELLS_VAR_NAME)
+        .unwrap_or_else(|_| "bash".into())
+        .split(',')
+        .map(str::trim)
+        .filter(|shell| !shell.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+// TESTS
+
+mod hook {
+    use super::*;
+
+    #[test]
+    fn should_load_env_on_cd_into_project_and_unload_it_on_cd_out() {
+        for shell in shells() {
+            let fixtures =
+                HashMap::from([(TOOL_ASSET_PATH.to_string(), Fixture::new(TOOL_CONTENT))]);
+            let server = FixtureServer::start(fixtures).unwrap();
+            let workdir = tempfile::tempdir().unwrap();
+            let project_dirpath = workdir.path().join("project");
+            write_project(&project_dirpath, &server);
+            allow_project(workdir.path(), &project_dirpath);
+            let lines = run_shell(
+                &shell,
+                workdir.path(),
+                r#"cd project
+echo "FOO=${FOO:-}"
+echo "TOOL=$(tool 2>/dev/null)"
+cd ..
+echo "FOO=${FOO:-}"
+echo "TOOL=$(tool 2>/dev/null)""#,
+            );
+            assert_eq!(
+                lines,
+                ["FOO=bar", "TOOL=tool 1.0.0", "FOO=", "TOOL="],
+                "{}",
+                shell
+            );
+            let paths: Vec<String> = server.requests().into_iter().map(|req| req.path).collect();
+            assert_eq!(paths, [TOOL_ASSET_PATH], "{}", shell);
+        }
+    }
+
+    #[test]
+    fn should_not_download_softwares_again_on_reload() {
+        for shell in shells() {
+            let fixtures =
+                HashMap::from([(TOOL_ASSET_PATH.to_string(), Fixture::new(TOOL_CONTENT))]);
+            let server = FixtureServer::start(fixtures).unwrap();
+            let workdir = tempfile::tempdir().unwrap();
+            let project_dirpath = workdir.path().join("project");
+            write_project(&project_dirpath, &server);
+            allow_project(workdir.path(), &project_dirpath);
+            let lines = run_shell(
+                &shell,
+                workdir.path(),
+                r#"cd project
+cd ..
+cd project
+echo "TOOL=$(tool 2>/dev/null)""#,
+            );
+            assert_eq!(lines, ["TOOL=tool 1.0.0"], "{}", shell);
+            assert_eq!(server.requests().len(), 1, "{}", shell);
+        }
+    }
+
+    #[test]
+    fn should_not_load_env_of_project_which_is_not_allowed() {
+        for shell in shells() {
+            let fixtures =
+                HashMap::from([(TOOL_ASSET_PATH.to_string(), Fixture::new(TOOL_CONTENT))]);
+            let server = FixtureServer::start(fixtures).unwrap();
+            let workdir = tempfile::tempdir().unwrap();
+            write_project(&workdir.path().join("project"), &server);
+            let lines = run_shell(
+                &shell,
+                workdir.path(),
+                r#"cd project
+echo "FOO=${FOO:-}""#,
+            );
+            assert_eq!(lines, ["FOO="], "{}", shell);
+            assert!(server.requests().is_empty(), "{}", shell);
+        }
+    }
+}